@@ -41,6 +41,8 @@ CREATE TABLE IF NOT EXISTS team_members (
   ai_analysis_details JSONB,
   developer_profile TEXT,
   analysis_metadata JSONB,
+  -- Bumped for every background analysis request; older results are discarded on write
+  analysis_version INT DEFAULT 0,
   created_at TIMESTAMPTZ DEFAULT NOW()
 );
 
@@ -57,6 +59,9 @@ CREATE TABLE IF NOT EXISTS team_members (
 -- ALTER TABLE team_members ADD COLUMN IF NOT EXISTS developer_profile TEXT;
 -- ALTER TABLE team_members ADD COLUMN IF NOT EXISTS analysis_metadata JSONB;
 
+-- Migration: Add analysis versioning to existing team_members table
+-- ALTER TABLE team_members ADD COLUMN IF NOT EXISTS analysis_version INT DEFAULT 0;
+
 -- ============================================
 -- Jobs table
 -- ============================================
@@ -71,12 +76,24 @@ CREATE TABLE IF NOT EXISTS jobs (
   location VARCHAR,
   required_skills JSONB DEFAULT '[]',
   experience_level VARCHAR DEFAULT 'any',
+  team_fit_weights JSONB,
+  location_requirement JSONB,
+  score_weights JSONB,
   status VARCHAR DEFAULT 'sourcing',
   team_id UUID REFERENCES teams(id) ON DELETE SET NULL,
   created_at TIMESTAMPTZ DEFAULT NOW(),
   updated_at TIMESTAMPTZ DEFAULT NOW()
 );
 
+-- Migration: Add per-job team fit sub-weights to existing jobs table
+-- ALTER TABLE jobs ADD COLUMN IF NOT EXISTS team_fit_weights JSONB;
+
+-- Migration: Add on-site/hybrid/remote location requirement to existing jobs table
+-- ALTER TABLE jobs ADD COLUMN IF NOT EXISTS location_requirement JSONB;
+
+-- Migration: Add per-job score component weights to existing jobs table
+-- ALTER TABLE jobs ADD COLUMN IF NOT EXISTS score_weights JSONB;
+
 -- ============================================
 -- Candidates table (for AI code analysis)
 -- ============================================
@@ -119,10 +136,27 @@ CREATE TABLE IF NOT EXISTS sourced_candidates (
   developer_profile TEXT,
   analysis_metadata JSONB,
   github_stats JSONB,
+  -- Hash of the stats developer_profile was generated from (skips regeneration when unchanged)
+  profile_stats_hash VARCHAR,
   analysis_status VARCHAR DEFAULT 'pending',
+  -- Why the last GitHub analysis failed (set with analysis_status = 'failed')
+  analysis_error TEXT,
+  -- When an enrichment last confirmed the GitHub analysis, even if it was served from the analysis cache
+  analysis_checked_at TIMESTAMPTZ,
+  -- Hiring pipeline: sourced, screened, interviewing, offer, hired or rejected
+  pipeline_stage VARCHAR DEFAULT 'sourced',
+  work_style JSONB,
+  -- Profile URL a sourcing search found the candidate by (NULL for manual and resume candidates)
+  source_link VARCHAR,
   created_at TIMESTAMPTZ DEFAULT NOW()
 );
 
+-- Migration: Key persisted sourcing results on their profile link (must run before the index below)
+ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS source_link VARCHAR;
+
+-- A sourced profile is stored once per source, however many searches find it at the same time
+CREATE UNIQUE INDEX IF NOT EXISTS sourced_candidates_source_link_key ON sourced_candidates (source, source_link);
+
 -- Migration: Add GitHub enrichment columns to existing sourced_candidates table
 -- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS code_characteristics JSONB DEFAULT NULL;
 -- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS ai_detection_score FLOAT;
@@ -134,6 +168,21 @@ CREATE TABLE IF NOT EXISTS sourced_candidates (
 -- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS github_stats JSONB;
 -- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS analysis_status VARCHAR DEFAULT 'pending';
 
+-- Migration: Add developer profile cache key
+-- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS profile_stats_hash VARCHAR;
+
+-- Migration: Add analysis failure reason
+-- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS analysis_error TEXT;
+
+-- Migration: Add analysis check time
+-- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS analysis_checked_at TIMESTAMPTZ;
+
+-- Migration: Add pipeline stage
+-- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS pipeline_stage VARCHAR DEFAULT 'sourced';
+
+-- Migration: Add candidate work style
+-- ALTER TABLE sourced_candidates ADD COLUMN IF NOT EXISTS work_style JSONB;
+
 -- ============================================
 -- Job Candidates junction table
 -- ============================================
@@ -143,6 +192,7 @@ CREATE TABLE IF NOT EXISTS job_candidates (
   candidate_id UUID NOT NULL REFERENCES sourced_candidates(id) ON DELETE CASCADE,
   job_match_score INT DEFAULT 0,
   team_compatibility_score INT DEFAULT 0,
+  score_breakdown JSONB DEFAULT NULL,        -- breakdown behind job_match_score, set by POST /jobs/<id>/rescore
   -- Take-home project generation
   take_home_projects JSONB DEFAULT NULL,
   projects_generated_at TIMESTAMPTZ DEFAULT NULL,
@@ -154,6 +204,9 @@ CREATE TABLE IF NOT EXISTS job_candidates (
 -- ALTER TABLE job_candidates ADD COLUMN IF NOT EXISTS take_home_projects JSONB DEFAULT NULL;
 -- ALTER TABLE job_candidates ADD COLUMN IF NOT EXISTS projects_generated_at TIMESTAMPTZ DEFAULT NULL;
 
+-- Migration: Keep each job's score breakdown for existing job_candidates table
+-- ALTER TABLE job_candidates ADD COLUMN IF NOT EXISTS score_breakdown JSONB DEFAULT NULL;
+
 -- ============================================
 -- Code Embeddings table (ephemeral - for analysis sessions)
 -- ============================================
@@ -168,6 +221,49 @@ CREATE TABLE IF NOT EXISTS code_embeddings (
   language VARCHAR,
   content TEXT NOT NULL,
   embedding vector(768),
+  content_hash VARCHAR,                      -- sha256 of the normalized chunk content
+  created_at TIMESTAMPTZ DEFAULT NOW(),
+  UNIQUE(analysis_id, repo_name, file_path, line_start)
+);
+
+-- Migration: Make chunk storage idempotent for existing code_embeddings table
+-- ALTER TABLE code_embeddings ADD CONSTRAINT code_embeddings_chunk_key UNIQUE (analysis_id, repo_name, file_path, line_start);
+
+-- Migration: Deduplicate chunks by content for existing code_embeddings table (must run before the index below)
+ALTER TABLE code_embeddings ADD COLUMN IF NOT EXISTS content_hash VARCHAR;
+
+-- One copy of identical content within an analysis (each analysis gets a new analysis_id, so runs don't share chunks)
+CREATE UNIQUE INDEX IF NOT EXISTS code_embeddings_content_hash_key ON code_embeddings (analysis_id, content_hash);
+
+-- Migration: Switch to EMBEDDING_PROVIDER=openai (text-embedding-3-small, 1536 dimensions)
+-- Stored vectors can't be converted between providers, so clear them first
+-- TRUNCATE code_embeddings;
+-- ALTER TABLE code_embeddings ALTER COLUMN embedding TYPE vector(1536);
+
+-- ============================================
+-- GitHub analysis cache (analyses shared by candidates and profile requests for the same GitHub account)
+-- ============================================
+CREATE TABLE IF NOT EXISTS github_analysis_cache (
+  username VARCHAR NOT NULL,
+  depth VARCHAR NOT NULL DEFAULT 'deep',     -- quick or deep
+  stats JSONB NOT NULL,
+  fingerprint VARCHAR,                       -- latest repo updated_at values when analyzed
+  analyzed_at TIMESTAMPTZ DEFAULT NOW(),
+  PRIMARY KEY (username, depth)
+);
+
+-- Migration: Cache quick and deep analyses separately with a repo activity fingerprint
+-- ALTER TABLE github_analysis_cache ADD COLUMN IF NOT EXISTS depth VARCHAR NOT NULL DEFAULT 'deep';
+-- ALTER TABLE github_analysis_cache ADD COLUMN IF NOT EXISTS fingerprint VARCHAR;
+-- ALTER TABLE github_analysis_cache DROP CONSTRAINT IF EXISTS github_analysis_cache_pkey;
+-- ALTER TABLE github_analysis_cache ADD PRIMARY KEY (username, depth);
+
+-- ============================================
+-- Skill synonyms (added on top of the built-in ones in matching/skills.rs)
+-- ============================================
+CREATE TABLE IF NOT EXISTS skill_synonyms (
+  alias VARCHAR PRIMARY KEY,                 -- normalized, e.g. "c sharp"
+  canonical VARCHAR NOT NULL,                -- normalized, e.g. "csharp"
   created_at TIMESTAMPTZ DEFAULT NOW()
 );
 
@@ -196,3 +292,4 @@ CREATE INDEX IF NOT EXISTS idx_code_embeddings_embedding ON code_embeddings USIN
 --   - sourced_candidates
 --   - job_candidates
 --   - code_embeddings (ephemeral)
+--   - github_analysis_cache
@@ -0,0 +1,225 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+
+/// Markers Gemini uses in quota / rate-limit errors
+const RATE_LIMIT_MARKERS: &[&str] = &["resource_exhausted", "rate limit", "quota", "429", "too many requests"];
+
+/// Why an AI response couldn't be used
+/// Callers log it with `log_fallback` and switch to their non-AI fallback
+#[derive(Debug, Clone, PartialEq)]
+pub enum AiResponseError {
+    /// The request itself failed (network, auth, server error)
+    Request(String),
+    /// Quota exhausted or rate limited
+    RateLimited(String),
+    /// No text came back
+    Empty,
+    /// Text came back but wasn't the expected JSON
+    Malformed(String),
+}
+
+impl fmt::Display for AiResponseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Request(e) => write!(f, "request failed: {}", e),
+            Self::RateLimited(e) => write!(f, "rate limited: {}", e),
+            Self::Empty => write!(f, "empty response"),
+            Self::Malformed(e) => write!(f, "malformed response: {}", e),
+        }
+    }
+}
+
+impl std::error::Error for AiResponseError {}
+
+fn is_rate_limit_message(message: &str) -> bool {
+    let lower = message.to_lowercase();
+    RATE_LIMIT_MARKERS.iter().any(|m| lower.contains(m))
+}
+
+impl AiResponseError {
+    /// Classify an error returned by `exec_chat`
+    pub fn from_request_error(error: impl fmt::Display) -> Self {
+        let message = error.to_string();
+        if is_rate_limit_message(&message) {
+            Self::RateLimited(message)
+        } else {
+            Self::Request(message)
+        }
+    }
+}
+
+/// Response text, rejecting empty bodies and plain-text quota errors
+pub fn response_text(text: Option<String>) -> Result<String, AiResponseError> {
+    let text = text.unwrap_or_default();
+    let trimmed = text.trim();
+
+    if trimmed.is_empty() {
+        return Err(AiResponseError::Empty);
+    }
+    // A JSON payload that merely mentions "quota" is still a payload
+    if !trimmed.contains(['{', '[']) && is_rate_limit_message(trimmed) {
+        return Err(AiResponseError::RateLimited(trimmed.to_string()));
+    }
+
+    Ok(trimmed.to_string())
+}
+
+/// Pull the JSON object/array out of a response that may be wrapped in a markdown code block
+pub fn extract_json(response: &str) -> String {
+    let trimmed = response.trim();
+
+    // Markdown code block: skip the opening fence (and language tag), stop at the last closing one,
+    // so fences inside JSON string values (code in reasoning text) are kept
+    if let Some(after_fence) = trimmed.strip_prefix("```") {
+        let body = after_fence.split_once('\n').map(|(_, rest)| rest).unwrap_or(after_fence);
+        let body = body.rfind("```").map_or(body, |end| &body[..end]);
+        return body.trim().to_string();
+    }
+
+    // Otherwise take the outermost object or array
+    let start = trimmed.find(['{', '[']);
+    let end = trimmed.rfind(['}', ']']);
+    match (start, end) {
+        (Some(s), Some(e)) if e > s => trimmed[s..=e].to_string(),
+        _ => trimmed.to_string(),
+    }
+}
+
+/// Validate and parse a JSON response into `T`
+pub fn parse_json<T: DeserializeOwned>(text: Option<String>) -> Result<T, AiResponseError> {
+    let text = response_text(text)?;
+    let json = extract_json(&text);
+    serde_json::from_str(&json).map_err(|e| AiResponseError::Malformed(e.to_string()))
+}
+
+/// The first complete JSON object in a response, dropping any prose before or after it
+/// Braces inside strings are skipped; None when no object closes (e.g. a truncated response)
+pub fn repair_json(response: &str) -> Option<String> {
+    let start = response.find('{')?;
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in response[start..].char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' => depth += 1,
+            '}' => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(response[start..=start + i].to_string());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// `parse_json`, falling back to `repair_json` when the extracted text isn't valid JSON
+pub fn parse_json_repaired<T: DeserializeOwned>(text: Option<String>) -> Result<T, AiResponseError> {
+    let text = response_text(text)?;
+    let error = match serde_json::from_str(&extract_json(&text)) {
+        Ok(parsed) => return Ok(parsed),
+        Err(e) => AiResponseError::Malformed(e.to_string()),
+    };
+    repair_json(&text)
+        .and_then(|json| serde_json::from_str(&json).ok())
+        .ok_or(error)
+}
+
+/// Bound a model-produced score to `0..=max`
+/// Out-of-range values are clamped, infinities go to the nearest bound and NaN counts as 0
+pub fn clamp_score(score: f64, max: f64) -> f64 {
+    if score.is_nan() { 0.0 } else { score.clamp(0.0, max) }
+}
+
+/// Log why an AI task fell back, so degraded results are visible in the logs
+pub fn log_fallback(task: &str, cause: &dyn fmt::Display, fallback: &str) {
+    println!("[AI] {} unavailable ({}), using {}", task, cause, fallback);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract_json_plain() {
+        let input = r#"{"ai_detection_score": 50}"#;
+        assert_eq!(extract_json(input), input);
+    }
+
+    #[test]
+    fn test_extract_json_with_code_block() {
+        let input = "```json\n{\"ai_detection_score\": 50}\n```";
+        assert_eq!(extract_json(input), "{\"ai_detection_score\": 50}");
+    }
+
+    #[test]
+    fn test_extract_json_keeps_fences_inside_strings() {
+        let json = r#"{"description": "Implement:\n```rust\nfn main() {}\n```\nthen test it", "score": 7}"#;
+        assert_eq!(extract_json(json), json);
+        assert_eq!(extract_json(&format!("```json\n{}\n```", json)), json);
+
+        let parsed: serde_json::Value = serde_json::from_str(&extract_json(json)).unwrap();
+        assert_eq!(parsed["score"], 7);
+    }
+
+    #[test]
+    fn test_clamp_score_bounds_and_nan() {
+        assert_eq!(clamp_score(150.0, 100.0), 100.0);
+        assert_eq!(clamp_score(-5.0, 100.0), 0.0);
+        assert_eq!(clamp_score(42.5, 100.0), 42.5);
+        assert_eq!(clamp_score(f64::NAN, 100.0), 0.0);
+        assert_eq!(clamp_score(f64::INFINITY, 1.0), 1.0);
+        assert_eq!(clamp_score(f64::NEG_INFINITY, 1.0), 0.0);
+    }
+
+    #[test]
+    fn test_empty_and_quota_responses_rejected() {
+        assert_eq!(parse_json::<Vec<String>>(None), Err(AiResponseError::Empty));
+        assert_eq!(parse_json::<Vec<String>>(Some("  \n".to_string())), Err(AiResponseError::Empty));
+        assert!(matches!(
+            parse_json::<Vec<String>>(Some("429 RESOURCE_EXHAUSTED: quota exceeded".to_string())),
+            Err(AiResponseError::RateLimited(_))
+        ));
+        assert!(matches!(
+            parse_json::<Vec<String>>(Some("Sorry, I can't help with that".to_string())),
+            Err(AiResponseError::Malformed(_))
+        ));
+        assert_eq!(
+            parse_json::<Vec<String>>(Some("Here you go:\n[\"SRE\", \"Quota Analyst\"]".to_string())),
+            Ok(vec!["SRE".to_string(), "Quota Analyst".to_string()])
+        );
+        assert!(matches!(
+            AiResponseError::from_request_error("HTTP 429 Too Many Requests"),
+            AiResponseError::RateLimited(_)
+        ));
+    }
+
+    #[test]
+    fn test_repair_json_drops_surrounding_prose() {
+        let input = r#"Sure! {"reasoning": "uses {braces} and \"quotes\"", "n": {"x": 1}} Let me know if {anything} else."#;
+        assert_eq!(repair_json(input).unwrap(), r#"{"reasoning": "uses {braces} and \"quotes\"", "n": {"x": 1}}"#);
+        assert_eq!(repair_json(r#"{"truncated": [1, 2"#), None);
+        assert_eq!(repair_json("no json here"), None);
+
+        // extract_json spans to the last brace, so only the repair pass recovers this
+        let parsed: serde_json::Value = parse_json_repaired(Some(input.to_string())).unwrap();
+        assert_eq!(parsed["n"]["x"], 1);
+        assert!(matches!(
+            parse_json_repaired::<serde_json::Value>(Some(r#"{"truncated": [1, 2"#.to_string())),
+            Err(AiResponseError::Malformed(_))
+        ));
+    }
+}
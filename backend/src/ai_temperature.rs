@@ -0,0 +1,141 @@
+use std::sync::OnceLock;
+
+use genai::chat::ChatOptions;
+
+/// Highest temperature Gemini accepts
+const MAX_TEMPERATURE: f64 = 2.0;
+
+/// Sampling temperature for each Gemini task
+/// Scoring and extraction stay low so repeated runs agree; generation tasks get some variety
+#[derive(Debug, Clone, PartialEq)]
+pub struct Temperatures {
+    /// Code characteristics metrics
+    pub code_analysis: f64,
+    /// AI usage detection on GitHub code
+    pub ai_detection: f64,
+    /// AI half of the combined skill score
+    pub skills: f64,
+    /// Culture fit score
+    pub culture: f64,
+    /// Sourcing job title variations
+    pub query_expansion: f64,
+    /// Sourcing relevance filter
+    pub relevance_filter: f64,
+    /// Resume field extraction
+    pub resume: f64,
+    /// Repo purpose when there is no README
+    pub repo_purpose: f64,
+    /// Developer profile summary paragraph
+    pub summary: f64,
+    /// Developer profile
+    pub profile: f64,
+    /// Take-home project generation
+    pub take_home: f64,
+}
+
+impl Default for Temperatures {
+    fn default() -> Self {
+        Self {
+            code_analysis: 0.0,
+            ai_detection: 0.0,
+            skills: 0.1,
+            culture: 0.1,
+            query_expansion: 0.3,
+            relevance_filter: 0.2,
+            resume: 0.1,
+            repo_purpose: 0.3,
+            summary: 0.3,
+            profile: 0.4,
+            take_home: 0.4,
+        }
+    }
+}
+
+impl Temperatures {
+    /// Parse `task=temperature` overrides separated by newlines or `;`
+    /// Unknown tasks and out-of-range values are logged and ignored
+    pub fn with_overrides(spec: &str) -> Self {
+        let mut temperatures = Self::default();
+
+        for entry in spec.split(['\n', ';']).map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((task, value)) = entry.split_once('=') else {
+                println!("[AI] Ignoring malformed temperature {:?}", entry);
+                continue;
+            };
+            let Some(value) = value.trim().parse::<f64>().ok().and_then(valid_temperature) else {
+                println!("[AI] Ignoring temperature {:?}: expected 0.0-{}", entry, MAX_TEMPERATURE);
+                continue;
+            };
+            match temperatures.task_mut(&task.trim().to_lowercase()) {
+                Some(slot) => *slot = value,
+                None => println!("[AI] Ignoring temperature {:?}: unknown task", entry),
+            }
+        }
+
+        temperatures
+    }
+
+    /// Build from AI_TEMPERATURES (e.g. "culture=0.0;take_home=0.6")
+    pub fn from_env() -> Self {
+        Self::with_overrides(&std::env::var("AI_TEMPERATURES").unwrap_or_default())
+    }
+
+    fn task_mut(&mut self, task: &str) -> Option<&mut f64> {
+        match task {
+            "code_analysis" => Some(&mut self.code_analysis),
+            "ai_detection" => Some(&mut self.ai_detection),
+            "skills" => Some(&mut self.skills),
+            "culture" => Some(&mut self.culture),
+            "query_expansion" => Some(&mut self.query_expansion),
+            "relevance_filter" => Some(&mut self.relevance_filter),
+            "resume" => Some(&mut self.resume),
+            "repo_purpose" => Some(&mut self.repo_purpose),
+            "summary" => Some(&mut self.summary),
+            "profile" => Some(&mut self.profile),
+            "take_home" => Some(&mut self.take_home),
+            _ => None,
+        }
+    }
+}
+
+fn valid_temperature(value: f64) -> Option<f64> {
+    (0.0..=MAX_TEMPERATURE).contains(&value).then_some(value)
+}
+
+/// The process-wide temperatures (configured once from env)
+pub fn temperatures() -> &'static Temperatures {
+    static TEMPERATURES: OnceLock<Temperatures> = OnceLock::new();
+    TEMPERATURES.get_or_init(Temperatures::from_env)
+}
+
+/// Chat options for a task, using a per-request override when it is in range
+pub fn chat_options(default: f64, override_temperature: Option<f64>) -> ChatOptions {
+    let temperature = override_temperature.and_then(valid_temperature).unwrap_or(default);
+    ChatOptions::default().with_temperature(temperature)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_override_reaches_chat_options() {
+        let defaults = Temperatures::default();
+
+        assert_eq!(chat_options(defaults.culture, Some(0.7)).temperature, Some(0.7));
+        assert_eq!(chat_options(defaults.culture, None).temperature, Some(0.1));
+        // Out-of-range overrides keep the configured default
+        assert_eq!(chat_options(defaults.skills, Some(3.5)).temperature, Some(0.1));
+        assert_eq!(chat_options(defaults.skills, Some(-1.0)).temperature, Some(0.1));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let temperatures = Temperatures::with_overrides("culture=0.0; Take_Home=0.8;skills=9;bogus=0.1;nonsense");
+
+        assert_eq!(temperatures.culture, 0.0);
+        assert_eq!(temperatures.take_home, 0.8);
+        assert_eq!(temperatures.skills, Temperatures::default().skills);
+        assert_eq!(temperatures.profile, Temperatures::default().profile);
+    }
+}
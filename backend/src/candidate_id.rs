@@ -0,0 +1,65 @@
+use uuid::Uuid;
+
+/// Stable key identifying a person across imports: email first, then LinkedIn profile URL
+/// Both are normalized and expressed as URLs so they live in the UUID URL namespace
+pub fn natural_key(email: Option<&str>, linkedin: Option<&str>) -> Option<String> {
+    let email = email.map(|e| e.trim().to_lowercase()).filter(|e| e.contains('@'));
+    if let Some(email) = email {
+        return Some(format!("mailto:{}", email));
+    }
+
+    let linkedin = linkedin?.trim().to_lowercase();
+    let linkedin = linkedin
+        .strip_prefix("https://")
+        .or_else(|| linkedin.strip_prefix("http://"))
+        .unwrap_or(&linkedin);
+    // Country subdomains (au.linkedin.com) point at the same profile
+    let linkedin = match linkedin.split_once(".linkedin.com/") {
+        Some((_, path)) => format!("linkedin.com/{}", path),
+        None => linkedin.to_string(),
+    };
+    let linkedin = linkedin.split(['?', '#']).next().unwrap_or_default().trim_end_matches('/');
+
+    if linkedin.is_empty() {
+        None
+    } else {
+        Some(format!("https://{}", linkedin))
+    }
+}
+
+/// Deterministic (v5) candidate ID for a natural key
+pub fn deterministic_candidate_id(key: &str) -> Uuid {
+    Uuid::new_v5(&Uuid::NAMESPACE_URL, key.as_bytes())
+}
+
+/// Candidate ID for an import: derived from the natural key when requested and available, random otherwise
+pub fn candidate_id(deterministic: bool, email: Option<&str>, linkedin: Option<&str>) -> Uuid {
+    if deterministic && let Some(key) = natural_key(email, linkedin) {
+        return deterministic_candidate_id(&key);
+    }
+    Uuid::new_v4()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_natural_key_same_id() {
+        let first = candidate_id(true, Some("Jane.Doe@Example.com "), None);
+        let second = candidate_id(true, Some("jane.doe@example.com"), Some("https://linkedin.com/in/janedoe"));
+        assert_eq!(first, second);
+        assert_eq!(first.get_version_num(), 5);
+
+        let from_url = candidate_id(true, None, Some("https://www.linkedin.com/in/JaneDoe/"));
+        let from_regional = candidate_id(true, None, Some("http://au.linkedin.com/in/janedoe?trk=public"));
+        assert_eq!(from_url, from_regional);
+        assert_ne!(first, from_url);
+    }
+
+    #[test]
+    fn test_random_id_without_flag_or_key() {
+        assert_eq!(candidate_id(false, Some("jane@example.com"), None).get_version_num(), 4);
+        assert_eq!(candidate_id(true, Some("not an email"), Some("  ")).get_version_num(), 4);
+    }
+}
@@ -4,16 +4,23 @@ use std::{
     io::{self, Read},
     path::Path,
     process::Command,
+    sync::OnceLock,
 };
 
 use genai::{
     Client,
-    chat::{ChatMessage, ChatOptions, ChatRequest},
+    chat::{ChatMessage, ChatRequest},
 };
 use walkdir::WalkDir;
 
+use crate::ai_response::{parse_json, AiResponseError};
+use crate::ai_temperature::{chat_options, temperatures};
 use crate::code_analysis::characteristics::CodeCharacteristics;
-use crate::github::api::{get_user_repos, get_repo_tree, get_file_content};
+use crate::github::analyze::sampled_repo_trees;
+use crate::github::stats::FetchDiagnostics;
+use crate::github::api::{get_user_repos, get_file_content};
+use crate::github::scrub::scrub;
+use crate::gitlab::api::{self as gitlab, GitLabTreeItem};
 
 const MODEL_GEMINI: &str = "gemini-2.0-flash";
 
@@ -29,25 +36,80 @@ const MAX_FILES: usize = 30;
 const MAX_LINES_PER_FILE: usize = 500;
 const MAX_TOTAL_LINES: usize = 5000;
 const MAX_FILE_SIZE: u64 = 50000; // 50KB
+const MAX_REPOS: usize = 5;
+
+// Recency weighting: a repo's share of sampled files halves every year since its last update
+const RECENCY_HALF_LIFE_DAYS: f32 = 365.0;
+const MIN_RECENCY_WEIGHT: f32 = 0.1;
+
+/// Hosts repos may be cloned from unless GIT_CLONE_HOSTS overrides them
+const DEFAULT_GIT_CLONE_HOSTS: &[&str] = &["github.com", "gitlab.com"];
+
+/// Hosts `validate_git_url` accepts (GIT_CLONE_HOSTS, comma-separated)
+fn git_clone_hosts() -> &'static [String] {
+    static HOSTS: OnceLock<Vec<String>> = OnceLock::new();
+    HOSTS.get_or_init(|| {
+        let configured: Vec<String> = std::env::var("GIT_CLONE_HOSTS")
+            .unwrap_or_default()
+            .split(',')
+            .map(|host| host.trim().to_lowercase())
+            .filter(|host| !host.is_empty())
+            .collect();
+        if configured.is_empty() {
+            DEFAULT_GIT_CLONE_HOSTS.iter().map(|host| host.to_string()).collect()
+        } else {
+            configured
+        }
+    })
+}
 
-pub async fn generate_characteristics_from_repo(
-    url: &str,
-) -> Result<CodeCharacteristics, Box<dyn std::error::Error>> {
-    // Get all code from repo
+/// Only https URLs on an allowed host are cloned, so the argument can't be read as a git option
+/// (`--upload-pack=...`), a local path or another transport (`ext::`, `file://`, `ssh://`)
+pub(crate) fn validate_git_url(url: &str) -> Result<(), String> {
+    validate_git_url_with_hosts(url, git_clone_hosts())
+}
 
-    let repo_path = Path::new("temp_clone");
-    let mut command = Command::new("git");
+fn validate_git_url_with_hosts(url: &str, hosts: &[String]) -> Result<(), String> {
+    let url = url.trim();
+    if url.is_empty() || url.starts_with('-') || url.chars().any(char::is_whitespace) {
+        return Err("Invalid repository URL".to_string());
+    }
+    let parsed = reqwest::Url::parse(url).map_err(|e| format!("Invalid repository URL: {}", e))?;
+    if parsed.scheme() != "https" {
+        return Err("Repository URL must use https".to_string());
+    }
+    let host = parsed.host_str().unwrap_or_default().to_lowercase();
+    if !hosts.contains(&host) {
+        return Err(format!("Repository host must be one of: {}", hosts.join(", ")));
+    }
+    Ok(())
+}
 
-    command
-        .arg("clone")
-        .arg("--depth")
-        .arg("1")
-        .arg(url)
-        .arg(repo_path);
+/// Shallow-clone `source` into its own temp dir and concatenate its code
+/// The checkout is removed when this returns, on success or failure, so concurrent calls never share a directory
+fn clone_repo_code(source: &str) -> Result<String, io::Error> {
+    let checkout = tempfile::Builder::new().prefix("repo_clone_").tempdir()?;
+
+    // ext:: runs arbitrary commands; never allow it, whatever the URL checks say
+    let status = Command::new("git")
+        .args(["-c", "protocol.ext.allow=never", "clone", "--depth", "1", "--quiet", "--"])
+        .arg(source)
+        .arg(checkout.path())
+        .status()?;
+    if !status.success() {
+        return Err(io::Error::other(format!("git clone failed ({})", status)));
+    }
 
-    command.spawn()?.wait()?;
+    concatenate_files(checkout.path())
+}
 
-    let files = concatenate_files(repo_path)?;
+pub async fn generate_characteristics_from_repo(
+    url: &str,
+) -> Result<CodeCharacteristics, Box<dyn std::error::Error>> {
+    // Get all code from repo (cloning and walking the checkout block, so they run off the async workers)
+    validate_git_url(url)?;
+    let source = url.trim().to_string();
+    let files = tokio::task::spawn_blocking(move || clone_repo_code(&source)).await??;
 
     let chat_req = ChatRequest::new(vec![
         ChatMessage::system(super::QUESTION),
@@ -55,27 +117,14 @@ pub async fn generate_characteristics_from_repo(
     ]);
     let client = Client::default();
 
-    let options = ChatOptions::default().with_temperature(0.0);
+    let options = chat_options(temperatures().code_analysis, None);
 
     let chat_res = client
         .exec_chat(MODEL_GEMINI, chat_req, Some(&options))
-        .await?;
-
-    let res = chat_res
-            .content
-            .joined_texts()
-            .ok_or("Failed to join texts")?;
-
-    let lines: Vec<&str> = res.lines()
-            .collect();
-
-    let deseralised: CodeCharacteristics = serde_json::from_str(&lines[1..lines.len()-1].join("\n"))?;
+        .await
+        .map_err(AiResponseError::from_request_error)?;
 
-    let mut command = Command::new("rm");
-    command.arg("-rf")
-        .arg(repo_path);
-
-    command.spawn()?.wait()?;
+    let deseralised = parse_json::<CodeCharacteristics>(chat_res.content.joined_texts())?.clamped();
 
     Ok(deseralised)
 }
@@ -90,9 +139,10 @@ fn concatenate_files(repo_path: &Path) -> Result<String, io::Error> {
         let path = dir.path();
 
         if path.is_file() {
-            all_code.push_str(&format!("\n\n// --- FILE: {} ---\n\n", path.display()));
+            let relative = path.strip_prefix(repo_path).unwrap_or(path);
+            all_code.push_str(&format!("\n\n// --- FILE: {} ---\n\n", relative.display()));
 
-            all_code.push_str(&read_file_contents(path)?);
+            all_code.push_str(&scrub(&read_file_contents(path)?));
         }
     }
 
@@ -124,11 +174,136 @@ fn is_code_file(path: &str) -> bool {
     CODE_EXTENSIONS.iter().any(|ext| path.ends_with(ext))
 }
 
+/// Whether a tree entry is worth sampling: a code file outside vendored/generated directories, under MAX_FILE_SIZE
+/// Entries without a known size (GitLab trees don't report one) are checked after fetching
+fn is_sampled_code_file(path: &str, size: Option<u64>) -> bool {
+    is_code_file(path)
+        && size.unwrap_or(0) < MAX_FILE_SIZE
+        && !path.contains("node_modules/")
+        && !path.contains("vendor/")
+        && !path.contains(".min.")
+        && !path.contains("dist/")
+        && !path.contains("build/")
+}
+
+/// Code files of a GitLab tree to sample, shallowest first (sizes aren't listed, so top-level sources lead)
+fn sampled_gitlab_files(tree: &[GitLabTreeItem]) -> Vec<&GitLabTreeItem> {
+    let mut files: Vec<_> = tree.iter()
+        .filter(|f| f.item_type == "blob")
+        .filter(|f| is_sampled_code_file(&f.path, None))
+        .collect();
+    files.sort_by_key(|f| f.path.matches('/').count());
+    files
+}
+
 /// Extract language from file path
 fn get_language(path: &str) -> Option<String> {
     path.rsplit('.').next().map(|ext| ext.to_lowercase())
 }
 
+/// Sampling weight for a repo last updated at `updated_at` (RFC 3339)
+/// Repos without a timestamp get the minimum weight
+fn recency_weight(updated_at: Option<&str>, now: chrono::DateTime<chrono::Utc>) -> f32 {
+    let Some(updated) = updated_at.and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok()) else {
+        return MIN_RECENCY_WEIGHT;
+    };
+
+    let age_days = now.signed_duration_since(updated).num_days().max(0) as f32;
+    0.5f32.powf(age_days / RECENCY_HALF_LIFE_DAYS).max(MIN_RECENCY_WEIGHT)
+}
+
+/// Split `total` files across repos in proportion to their weights (largest remainder),
+/// giving every repo at least one file when there are enough to go round
+fn allocate_files(weights: &[f32], total: usize) -> Vec<usize> {
+    if weights.is_empty() {
+        return vec![];
+    }
+
+    let floor = if total >= weights.len() { 1 } else { 0 };
+    let remaining = total - floor * weights.len();
+    let weight_sum: f32 = weights.iter().sum();
+
+    let shares: Vec<f32> = weights.iter().map(|w| remaining as f32 * w / weight_sum).collect();
+    let mut allocation: Vec<usize> = shares.iter().map(|s| floor + *s as usize).collect();
+
+    let mut by_remainder: Vec<usize> = (0..weights.len()).collect();
+    by_remainder.sort_by(|&a, &b| (shares[b] - shares[b].floor()).total_cmp(&(shares[a] - shares[a].floor())));
+    let leftover = total - allocation.iter().sum::<usize>();
+    for &i in by_remainder.iter().take(leftover) {
+        allocation[i] += 1;
+    }
+
+    allocation
+}
+
+/// Code collected for characterization, within the MAX_FILES / MAX_TOTAL_LINES budget
+#[derive(Default)]
+struct CodeSample {
+    all_code: String,
+    files_analyzed: u32,
+    total_lines: usize,
+    languages: HashSet<String>,
+}
+
+impl CodeSample {
+    fn is_full(&self) -> bool {
+        self.files_analyzed as usize >= MAX_FILES || self.total_lines >= MAX_TOTAL_LINES
+    }
+
+    /// Add a scrubbed file, truncated to MAX_LINES_PER_FILE; near-empty files are skipped
+    fn add(&mut self, path: &str, repo_name: &str, updated: &str, content: &str) {
+        let lines: Vec<&str> = content.lines().take(MAX_LINES_PER_FILE).collect();
+        let line_count = lines.len();
+
+        // Skip near-empty files
+        if line_count < 10 {
+            return;
+        }
+
+        // Track language
+        if let Some(lang) = get_language(path) {
+            self.languages.insert(lang);
+        }
+
+        // Append with file header
+        self.all_code.push_str(&format!("\n// FILE: {} ({}, updated {})\n", path, repo_name, updated));
+        self.all_code.push_str(&lines.join("\n"));
+        self.all_code.push('\n');
+
+        self.files_analyzed += 1;
+        self.total_lines += line_count;
+    }
+
+    /// Send the sample to Gemini and attach confidence metrics
+    async fn characterize(self) -> Result<CodeCharacteristics, Box<dyn std::error::Error + Send + Sync>> {
+        // Check if we have enough code to analyze
+        if self.files_analyzed == 0 || self.total_lines < 100 {
+            return Err("Not enough code found to analyze".into());
+        }
+
+        let chat_req = ChatRequest::new(vec![
+            ChatMessage::system(super::QUESTION),
+            ChatMessage::user(self.all_code),
+        ]);
+        let client = Client::default();
+        let options = chat_options(temperatures().code_analysis, None);
+
+        let chat_res = client
+            .exec_chat(MODEL_GEMINI, chat_req, Some(&options))
+            .await
+            .map_err(AiResponseError::from_request_error)?;
+
+        let characteristics = parse_json::<CodeCharacteristics>(chat_res.content.joined_texts())?.clamped();
+
+        let languages_vec: Vec<String> = self.languages.into_iter().collect();
+        Ok(characteristics.with_confidence(
+            self.files_analyzed,
+            self.total_lines as u32,
+            languages_vec,
+        ))
+    }
+}
+
 pub async fn generate_characteristics_from_github(
     username: &str,
     token: &str,
@@ -136,44 +311,37 @@ pub async fn generate_characteristics_from_github(
     // 1. Get user's repos (non-forks, sorted by update)
     let repos = get_user_repos(username, token).await?;
 
-    let mut all_code = String::new();
-    let mut files_analyzed: u32 = 0;
-    let mut total_lines: usize = 0;
-    let mut languages: HashSet<String> = HashSet::new();
+    let mut sample = CodeSample::default();
 
-    // 2. For each repo, get file tree and fetch code files
-    for repo in repos.iter().take(5) {
-        if files_analyzed as usize >= MAX_FILES || total_lines >= MAX_TOTAL_LINES {
+    // 2. Get file trees, preferring code repos over docs/config-dominant ones
+    let mut diagnostics = FetchDiagnostics::default();
+    let repo_trees = sampled_repo_trees(repos, |r| (r.owner.login.as_str(), r.name.as_str()), token, MAX_REPOS, &mut diagnostics).await;
+
+    // Recent repos get a larger share of the sampled files so the profile reflects current style
+    let now = chrono::Utc::now();
+    let weights: Vec<f32> = repo_trees.iter().map(|(r, _)| recency_weight(r.updated_at.as_deref(), now)).collect();
+    let files_per_repo = allocate_files(&weights, MAX_FILES);
+
+    // 3. For each repo, fetch code files
+    for ((repo, tree), files_per_repo) in repo_trees.iter().zip(files_per_repo) {
+        if sample.is_full() {
             break;
         }
 
-        // Get repository file tree
-        let tree = match get_repo_tree(&repo.owner.login, &repo.name, token).await {
-            Ok(t) => t,
-            Err(_) => continue, // Skip repos we can't access
-        };
-
         // Filter to code files only, sorted by size (smaller first for variety)
         let mut code_files: Vec<_> = tree.tree.iter()
             .filter(|f| f.item_type == "blob")
-            .filter(|f| is_code_file(&f.path))
-            .filter(|f| f.size.unwrap_or(0) < MAX_FILE_SIZE)
-            .filter(|f| !f.path.contains("node_modules/"))
-            .filter(|f| !f.path.contains("vendor/"))
-            .filter(|f| !f.path.contains(".min."))
-            .filter(|f| !f.path.contains("dist/"))
-            .filter(|f| !f.path.contains("build/"))
+            .filter(|f| is_sampled_code_file(&f.path, f.size))
             .collect();
 
         // Sort by size ascending for variety
         code_files.sort_by_key(|f| f.size.unwrap_or(0));
 
-        // Calculate files to fetch per repo (distribute across repos)
-        let files_per_repo = MAX_FILES / 5;
+        let updated = repo.updated_at.as_deref().and_then(|ts| ts.get(..7)).unwrap_or("unknown");
 
         // Fetch content of representative files
         for file in code_files.iter().take(files_per_repo) {
-            if files_analyzed as usize >= MAX_FILES || total_lines >= MAX_TOTAL_LINES {
+            if sample.is_full() {
                 break;
             }
 
@@ -181,72 +349,195 @@ pub async fn generate_characteristics_from_github(
             let content = match get_file_content(
                 &repo.owner.login, &repo.name, &file.path, token
             ).await {
-                Ok(c) => c,
+                Ok(c) => scrub(&c),
                 Err(_) => continue, // Skip files we can't read
             };
 
-            // Truncate to max lines per file
-            let lines: Vec<&str> = content.lines().take(MAX_LINES_PER_FILE).collect();
-            let line_count = lines.len();
+            sample.add(&file.path, &repo.name, updated, &content);
+        }
+    }
+
+    // 4. Send to Gemini for analysis, adding confidence metrics
+    sample.characterize().await
+}
 
-            // Skip near-empty files
-            if line_count < 10 {
-                continue;
-            }
+/// Same profile as `generate_characteristics_from_github`, sampled from a GitLab user's own projects
+/// `token` (GITLAB_TOKEN) may be empty; public projects are readable without one
+pub async fn generate_characteristics_from_gitlab(
+    username: &str,
+    token: &str,
+) -> Result<CodeCharacteristics, Box<dyn std::error::Error + Send + Sync>> {
+    // 1. Get user's projects (non-forks, most recently active first)
+    let projects = gitlab::get_user_projects(username, token).await?;
+
+    // 2. Get file trees, keeping projects that have code to sample
+    let mut project_files = Vec::new();
+    for project in projects {
+        if project_files.len() >= MAX_REPOS {
+            break;
+        }
+        let Ok(tree) = gitlab::get_repo_tree(project.id, token).await else {
+            continue;
+        };
+        let files: Vec<String> = sampled_gitlab_files(&tree).into_iter().map(|f| f.path.clone()).collect();
+        if !files.is_empty() {
+            project_files.push((project, files));
+        }
+    }
+
+    let now = chrono::Utc::now();
+    let weights: Vec<f32> = project_files.iter().map(|(p, _)| recency_weight(p.last_activity_at.as_deref(), now)).collect();
+    let files_per_project = allocate_files(&weights, MAX_FILES);
+
+    let mut sample = CodeSample::default();
+
+    // 3. For each project, fetch code files
+    for ((project, files), files_per_project) in project_files.iter().zip(files_per_project) {
+        if sample.is_full() {
+            break;
+        }
 
-            // Track language
-            if let Some(lang) = get_language(&file.path) {
-                languages.insert(lang);
+        let git_ref = project.default_branch.as_deref().unwrap_or("HEAD");
+        let updated = project.last_activity_at.as_deref().and_then(|ts| ts.get(..7)).unwrap_or("unknown");
+
+        for path in files.iter().take(files_per_project) {
+            if sample.is_full() {
+                break;
             }
 
-            // Append with file header
-            all_code.push_str(&format!("\n// FILE: {} ({})\n", file.path, repo.name));
-            all_code.push_str(&lines.join("\n"));
-            all_code.push('\n');
+            // The tree has no sizes, so oversized files are dropped once fetched
+            let content = match gitlab::get_file_content(project.id, path, git_ref, token).await {
+                Ok(c) if (c.len() as u64) < MAX_FILE_SIZE => scrub(&c),
+                _ => continue,
+            };
 
-            files_analyzed += 1;
-            total_lines += line_count;
+            sample.add(path, &project.name, updated, &content);
         }
     }
 
-    // Check if we have enough code to analyze
-    if files_analyzed == 0 || total_lines < 100 {
-        return Err("Not enough code found to analyze".into());
+    // 4. Send to Gemini for analysis, adding confidence metrics
+    sample.characterize().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_recent_repos_get_more_files() {
+        let now = chrono::DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let updated = [Some("2025-05-20T10:00:00Z"), Some("2024-06-01T00:00:00Z"), Some("2018-03-01T00:00:00Z"), None];
+        let weights: Vec<f32> = updated.iter().map(|u| recency_weight(*u, now)).collect();
+
+        assert!(weights[0] > 0.95);
+        assert!((weights[1] - 0.5).abs() < 0.01);
+        assert_eq!(weights[2], MIN_RECENCY_WEIGHT);
+        assert_eq!(weights[3], MIN_RECENCY_WEIGHT);
+
+        let allocation = allocate_files(&weights, MAX_FILES);
+        assert_eq!(allocation.iter().sum::<usize>(), MAX_FILES);
+        assert!(allocation[0] > allocation[1] && allocation[1] > allocation[2]);
+        assert!(allocation[0] >= 5 * allocation[3]);
+        assert!(allocation.iter().all(|&n| n >= 1));
     }
 
-    // 3. Send to Gemini for analysis
-    let chat_req = ChatRequest::new(vec![
-        ChatMessage::system(super::QUESTION),
-        ChatMessage::user(all_code),
-    ]);
-    let client = Client::default();
-    let options = ChatOptions::default().with_temperature(0.0);
+    #[test]
+    fn test_gitlab_tree_filtered_to_code_files() {
+        let json = r#"[
+            {"id": "a1", "name": "src", "type": "tree", "path": "src", "mode": "040000"},
+            {"id": "a2", "name": "parser.rs", "type": "blob", "path": "src/parser/parser.rs", "mode": "100644"},
+            {"id": "a3", "name": "main.rs", "type": "blob", "path": "src/main.rs", "mode": "100644"},
+            {"id": "a4", "name": "README.md", "type": "blob", "path": "README.md", "mode": "100644"},
+            {"id": "a5", "name": "lodash.js", "type": "blob", "path": "web/node_modules/lodash/lodash.js", "mode": "100644"},
+            {"id": "a6", "name": "app.min.js", "type": "blob", "path": "web/app.min.js", "mode": "100644"},
+            {"id": "a7", "name": "bundle.js", "type": "blob", "path": "web/dist/bundle.js", "mode": "100644"},
+            {"id": "a8", "name": "deps", "type": "commit", "path": "deps.rs", "mode": "160000"},
+            {"id": "a9", "name": "setup.py", "type": "blob", "path": "setup.py", "mode": "100644"}
+        ]"#;
+        let tree: Vec<GitLabTreeItem> = serde_json::from_str(json).unwrap();
+
+        let paths: Vec<&str> = sampled_gitlab_files(&tree).iter().map(|f| f.path.as_str()).collect();
+        assert_eq!(paths, ["setup.py", "src/main.rs", "src/parser/parser.rs"]);
+    }
 
-    let chat_res = client
-        .exec_chat(MODEL_GEMINI, chat_req, Some(&options))
-        .await?;
-
-    let res = chat_res
-        .content
-        .joined_texts()
-        .ok_or("Failed to join texts")?;
-
-    // Parse JSON response (handle markdown code blocks)
-    let lines: Vec<&str> = res.lines().collect();
-    let json_str = if lines.len() > 2 && lines[0].contains("```") {
-        // Response wrapped in code block
-        lines[1..lines.len()-1].join("\n")
-    } else {
-        res.clone()
-    };
+    #[test]
+    fn test_code_sample_budget() {
+        let mut sample = CodeSample::default();
+        sample.add("tiny.rs", "repo", "2025-05", "fn main() {}\n");
+        assert_eq!(sample.files_analyzed, 0);
+
+        let file = "let x = 1;\n".repeat(MAX_LINES_PER_FILE + 50);
+        while !sample.is_full() {
+            sample.add("src/lib.rs", "repo", "2025-05", &file);
+        }
+        assert_eq!(sample.total_lines, MAX_TOTAL_LINES);
+        assert_eq!(sample.files_analyzed as usize, MAX_TOTAL_LINES / MAX_LINES_PER_FILE);
+        assert_eq!(sample.languages, HashSet::from(["rs".to_string()]));
+        assert!(sample.all_code.contains("// FILE: src/lib.rs (repo, updated 2025-05)"));
+    }
+
+    #[test]
+    fn test_validate_git_url() {
+        let hosts: Vec<String> = DEFAULT_GIT_CLONE_HOSTS.iter().map(|h| h.to_string()).collect();
+        let validate = |url: &str| validate_git_url_with_hosts(url, &hosts);
+
+        assert_eq!(validate("https://github.com/user/repo"), Ok(()));
+        assert_eq!(validate(" https://GitLab.com/user/repo.git "), Ok(()));
+        assert!(validate("--upload-pack=x").is_err());
+        assert!(validate("--upload-pack=touch /tmp/x").is_err());
+        assert!(validate("ext::sh -c touch% /tmp/x").is_err());
+        assert!(validate("ext::sh").is_err());
+        assert!(validate("file:///etc").is_err());
+        assert!(validate("ssh://git@github.com/user/repo.git").is_err());
+        assert!(validate("http://github.com/user/repo").is_err());
+        assert!(validate("https://example.com/user/repo").is_err());
+        assert!(validate("https://github.com.evil.io/user/repo").is_err());
+        assert!(validate("/home/user/repo").is_err());
+        assert!(validate("git@github.com:user/repo.git").is_err());
+
+        let custom = vec!["git.example.com".to_string()];
+        assert_eq!(validate_git_url_with_hosts("https://git.example.com/team/repo", &custom), Ok(()));
+        assert!(validate_git_url_with_hosts("https://github.com/user/repo", &custom).is_err());
+    }
+
+    /// A local repo with a single committed file
+    fn local_repo(file: &str, contents: &str) -> tempfile::TempDir {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(file), contents).unwrap();
+        for args in [
+            vec!["init", "--quiet"],
+            vec!["add", "."],
+            vec!["-c", "user.name=test", "-c", "user.email=test@example.com", "commit", "--quiet", "-m", "init"],
+        ] {
+            let status = Command::new("git").args(&args).current_dir(dir.path()).status().unwrap();
+            assert!(status.success(), "git {:?} failed", args);
+        }
+        dir
+    }
 
-    let characteristics: CodeCharacteristics = serde_json::from_str(&json_str)?;
+    #[rocket::async_test]
+    async fn test_concurrent_clones_use_separate_checkouts() {
+        let first = local_repo("first.rs", "fn first() {}");
+        let second = local_repo("second.py", "def second(): pass");
+        let first_url = format!("file://{}", first.path().display());
+        let second_url = format!("file://{}", second.path().display());
+
+        let (a, b) = tokio::join!(
+            tokio::task::spawn_blocking(move || clone_repo_code(&first_url)),
+            tokio::task::spawn_blocking(move || clone_repo_code(&second_url)),
+        );
+        let (a, b) = (a.unwrap().unwrap(), b.unwrap().unwrap());
+
+        assert!(a.contains("// --- FILE: first.rs ---") && a.contains("fn first()"));
+        assert!(!a.contains("second"));
+        assert!(b.contains("// --- FILE: second.py ---") && b.contains("def second()"));
+        assert!(!b.contains("first"));
+    }
 
-    // 4. Add confidence metrics
-    let languages_vec: Vec<String> = languages.into_iter().collect();
-    Ok(characteristics.with_confidence(
-        files_analyzed,
-        total_lines as u32,
-        languages_vec,
-    ))
+    #[test]
+    fn test_failed_clone_is_an_error() {
+        let missing = tempfile::tempdir().unwrap();
+        let url = format!("file://{}/missing", missing.path().display());
+        assert!(clone_repo_code(&url).is_err());
+    }
 }
@@ -1,3 +1,16 @@
+use crate::ai_response::clamp_score;
+
+/// Typical upper end of the non-ratio fields; dividing by these puts them on roughly the 0-1 scale of the ratios
+pub const LINES_PER_FUNCTION_SCALE: f32 = 50.0;
+pub const NESTING_DEPTH_SCALE: f32 = 5.0;
+pub const ABSTRACTION_LAYER_SCALE: f32 = 5.0;
+
+/// Squared difference of a non-ratio field divided by its scale, capped at 1 like a ratio field's,
+/// so one sprawling value (500 lines per function) can't swamp the other characteristics
+pub fn scaled_diff_sq(a: f32, b: f32, scale: f32) -> f32 {
+    ((a - b).abs() / scale).min(1.0).powi(2)
+}
+
 #[derive(Debug, serde::Deserialize, serde::Serialize, Clone)]
 pub struct CodeCharacteristics {
     pub avg_lines_per_function: f32,
@@ -21,6 +34,25 @@ pub struct CodeCharacteristics {
 }
 
 impl CodeCharacteristics {
+    /// Ratios and scores bounded to 0.0-1.0, averages and counts to non-negative finite values
+    pub fn clamped(self) -> Self {
+        let ratio = |value: f32| clamp_score(value as f64, 1.0) as f32;
+        let amount = |value: f32| clamp_score(value as f64, f32::MAX as f64) as f32;
+        Self {
+            avg_lines_per_function: amount(self.avg_lines_per_function),
+            functional_vs_oop_ratio: ratio(self.functional_vs_oop_ratio),
+            recursion_vs_loop_ratio: ratio(self.recursion_vs_loop_ratio),
+            dependency_coupling_index: ratio(self.dependency_coupling_index),
+            modularity_index_score: ratio(self.modularity_index_score),
+            avg_nesting_depth: amount(self.avg_nesting_depth),
+            abstraction_layer_count: amount(self.abstraction_layer_count),
+            immutability_score: ratio(self.immutability_score),
+            error_handling_centralization_score: ratio(self.error_handling_centralization_score),
+            test_structure_modularity_ratio: ratio(self.test_structure_modularity_ratio),
+            ..self
+        }
+    }
+
     pub fn with_confidence(
         mut self,
         files_analyzed: u32,
@@ -33,3 +65,48 @@ impl CodeCharacteristics {
         self
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clamped_bounds_ratios_and_rejects_nan() {
+        let characteristics = CodeCharacteristics {
+            avg_lines_per_function: f32::NAN,
+            functional_vs_oop_ratio: 1.7,
+            recursion_vs_loop_ratio: -0.3,
+            dependency_coupling_index: f32::INFINITY,
+            modularity_index_score: f32::NAN,
+            avg_nesting_depth: -2.0,
+            abstraction_layer_count: 4.0,
+            immutability_score: 0.6,
+            error_handling_centralization_score: f32::NEG_INFINITY,
+            test_structure_modularity_ratio: 0.25,
+            files_analyzed: 12,
+            total_lines_analyzed: 900,
+            languages_detected: vec!["Rust".to_string()],
+        }
+        .clamped();
+
+        assert_eq!(characteristics.avg_lines_per_function, 0.0);
+        assert_eq!(characteristics.functional_vs_oop_ratio, 1.0);
+        assert_eq!(characteristics.recursion_vs_loop_ratio, 0.0);
+        assert_eq!(characteristics.dependency_coupling_index, 1.0);
+        assert_eq!(characteristics.modularity_index_score, 0.0);
+        assert_eq!(characteristics.avg_nesting_depth, 0.0);
+        assert_eq!(characteristics.abstraction_layer_count, 4.0);
+        assert_eq!(characteristics.immutability_score, 0.6);
+        assert_eq!(characteristics.error_handling_centralization_score, 0.0);
+        assert_eq!(characteristics.test_structure_modularity_ratio, 0.25);
+        assert_eq!(characteristics.files_analyzed, 12);
+    }
+
+    #[test]
+    fn test_scaled_diff_is_capped() {
+        assert_eq!(scaled_diff_sq(45.0, 20.0, LINES_PER_FUNCTION_SCALE), 0.25);
+        assert_eq!(scaled_diff_sq(20.0, 45.0, LINES_PER_FUNCTION_SCALE), 0.25);
+        // An outlier counts no more than opposite ends of a ratio
+        assert_eq!(scaled_diff_sq(500.0, 20.0, LINES_PER_FUNCTION_SCALE), 1.0);
+    }
+}
@@ -0,0 +1,54 @@
+use rocket::response::content::RawJson;
+use serde::Serialize;
+
+/// Malformed IDs, query parameters or request bodies
+pub const INVALID_REQUEST: &str = "invalid_request";
+pub const NOT_FOUND: &str = "not_found";
+pub const DATABASE_ERROR: &str = "database_error";
+/// A required setting (e.g. GITHUB_TOKEN) is missing
+pub const NOT_CONFIGURED: &str = "not_configured";
+/// The account is on the analysis blocklist
+pub const NOT_PERMITTED: &str = "not_permitted";
+/// GitHub or the AI provider failed
+pub const UPSTREAM_ERROR: &str = "upstream_error";
+pub const INTERNAL_ERROR: &str = "internal_error";
+
+/// Error body returned by the JSON endpoints: `{"code": "...", "error": "..."}`
+/// The message stays under `error`, where clients already read it; `code` is the machine-readable kind
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ApiError {
+    pub code: &'static str,
+    #[serde(rename = "error")]
+    pub message: String,
+}
+
+impl ApiError {
+    pub fn new(code: &'static str, message: impl Into<String>) -> Self {
+        Self { code, message: message.into() }
+    }
+
+    /// Serialized body; serde escapes quotes and control characters in the message
+    pub fn to_json(&self) -> RawJson<String> {
+        RawJson(serde_json::to_string(self).expect("ApiError always serializes"))
+    }
+}
+
+/// Error body for `code` and `message`
+pub fn err_json(code: &'static str, message: impl Into<String>) -> RawJson<String> {
+    ApiError::new(code, message).to_json()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_quoted_message_is_valid_json() {
+        let body = err_json(DATABASE_ERROR, "Database error: relation \"jobs\" does not exist\n\tat \\line 1");
+        let value: serde_json::Value = serde_json::from_str(&body.0).unwrap();
+
+        assert_eq!(value["code"], "database_error");
+        assert_eq!(value["error"], "Database error: relation \"jobs\" does not exist\n\tat \\line 1");
+        assert_eq!(value.as_object().unwrap().len(), 2);
+    }
+}
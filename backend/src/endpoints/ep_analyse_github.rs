@@ -1,6 +1,10 @@
 use rocket::{post, response::content::RawJson, serde::json};
 use serde::Deserialize;
 
+use crate::endpoints::api_error::{err_json, INTERNAL_ERROR, NOT_CONFIGURED, UPSTREAM_ERROR};
+use crate::endpoints::ep_github_analysis::check_not_blocked;
+use crate::github::blocklist::analysis_blocklist;
+
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct AnalyseGitHub<'a> {
@@ -9,15 +13,45 @@ pub struct AnalyseGitHub<'a> {
 
 #[post("/analyse_github", data = "<data>")]
 pub async fn analyse_github<'a>(data: json::Json<AnalyseGitHub<'a>>) -> RawJson<String> {
-    let token = std::env::var("GITHUB_TOKEN").unwrap();
+    if let Err(refused) = check_not_blocked(analysis_blocklist(), data.0.username) {
+        return refused;
+    }
+
+    let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
 
-    let result = crate::code_analysis::ai::generate_characteristics_from_github(
+    if token.is_empty() {
+        return err_json(NOT_CONFIGURED, "GitHub token not configured");
+    }
+
+    let result = match crate::code_analysis::ai::generate_characteristics_from_github(
         &data.0.username,
         &token,
     )
     .await
-    .unwrap();
+    {
+        Ok(result) => result,
+        Err(e) => return err_json(UPSTREAM_ERROR, format!("Analysis failed: {}", e)),
+    };
+
+    serde_json::to_string(&result)
+        .map(RawJson)
+        .unwrap_or_else(|_| err_json(INTERNAL_ERROR, "Failed to serialize response"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rocket::async_test]
+    async fn test_missing_token_returns_error() {
+        if std::env::var("GITHUB_TOKEN").is_ok_and(|t| !t.is_empty()) {
+            println!("GITHUB_TOKEN set, skipping");
+            return;
+        }
 
-    let json = serde_json::to_string(&result).unwrap();
-    RawJson(json)
+        let response = analyse_github(json::Json(AnalyseGitHub { username: "octocat" })).await;
+        let body: serde_json::Value = serde_json::from_str(&response.0).unwrap();
+        assert_eq!(body["code"], NOT_CONFIGURED);
+        assert_eq!(body["error"], "GitHub token not configured");
+    }
 }
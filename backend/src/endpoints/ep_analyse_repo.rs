@@ -1,6 +1,8 @@
 use rocket::{post, response::content::RawJson, serde::json};
 use serde::Deserialize;
 
+use crate::endpoints::api_error::{err_json, INTERNAL_ERROR, INVALID_REQUEST, UPSTREAM_ERROR};
+
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct AnalyseRepo<'a> {
@@ -9,10 +11,36 @@ pub struct AnalyseRepo<'a> {
 
 #[post("/analyse_repo", data = "<data>")]
 pub async fn analyse_repo<'a>(data: json::Json<AnalyseRepo<'a>>) -> RawJson<String> {
-    let result = crate::code_analysis::ai::generate_characteristics_from_repo(&data.0.url)
-        .await
-        .unwrap();
+    if data.0.url.trim().is_empty() {
+        return err_json(INVALID_REQUEST, "Repository URL is required");
+    }
+    if let Err(message) = crate::code_analysis::ai::validate_git_url(data.0.url) {
+        return err_json(INVALID_REQUEST, message);
+    }
+
+    let result = match crate::code_analysis::ai::generate_characteristics_from_repo(&data.0.url).await {
+        Ok(result) => result,
+        Err(e) => return err_json(UPSTREAM_ERROR, format!("Analysis failed: {}", e)),
+    };
+
+    serde_json::to_string(&result)
+        .map(RawJson)
+        .unwrap_or_else(|_| err_json(INTERNAL_ERROR, "Failed to serialize response"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rocket::async_test]
+    async fn test_missing_url_returns_error() {
+        let response = analyse_repo(json::Json(AnalyseRepo { url: " " })).await;
+        let body: serde_json::Value = serde_json::from_str(&response.0).unwrap();
+        assert_eq!(body["code"], INVALID_REQUEST);
+        assert_eq!(body["error"], "Repository URL is required");
 
-    let json = serde_json::to_string(&result).unwrap();
-    RawJson(json)
+        let response = analyse_repo(json::Json(AnalyseRepo { url: "--upload-pack=evil" })).await;
+        let body: serde_json::Value = serde_json::from_str(&response.0).unwrap();
+        assert_eq!(body["code"], INVALID_REQUEST);
+    }
 }
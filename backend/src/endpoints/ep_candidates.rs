@@ -1,11 +1,20 @@
-use rocket::{get, post, delete, serde::json};
+use rocket::{get, post, put, delete, serde::json};
+use rocket::http::Status;
 use rocket_db_pools::Connection;
 use rocket::response::content::RawJson;
 use serde::{Deserialize, Serialize};
+use crate::candidate_id::candidate_id;
+use crate::code_analysis::ai::generate_characteristics_from_gitlab;
 use crate::db::MainDatabase;
-use crate::github::analyze::analyze_github_user_deep;
-use crate::github::ai_summary::generate_developer_profile;
-use sqlx::Row;
+use crate::github::blocklist::analysis_blocklist;
+use crate::github::stats_cache::cached_github_stats;
+use crate::github::freshness::{freshness_policy, FreshnessPolicy};
+use crate::github::ai_summary::{cached_developer_profile, CachedProfile, ProfileStyle};
+use crate::github::stats::{GitHubProfile, GitHubStats};
+use crate::matching::skills::split_compound_skills;
+use crate::matching::team_fit::WorkStyle;
+use crate::pipeline::PipelineStage;
+use sqlx::{Connection as _, Row};
 
 #[derive(Deserialize, Serialize, Clone)]
 #[serde(crate = "rocket::serde")]
@@ -21,6 +30,11 @@ pub struct ExperienceInput {
     company: String,
     duration: String,
     description: Option<String>,
+    /// Optional exact dates; overlapping roles are counted once when scoring
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    start_date: Option<chrono::NaiveDate>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end_date: Option<chrono::NaiveDate>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -37,6 +51,24 @@ pub struct LinksInput {
     github: Option<String>,
     linkedin: Option<String>,
     portfolio: Option<String>,
+    /// GitLab profile; analyzed when there is no GitHub account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    gitlab: Option<String>,
+    /// Blog/website listed on the GitHub profile (filled in by analysis)
+    #[serde(rename = "githubBlog", default, skip_serializing_if = "Option::is_none")]
+    github_blog: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    twitter: Option<String>,
+    /// Whether the claimed portfolio matches the GitHub blog
+    #[serde(rename = "portfolioVerified", default, skip_serializing_if = "Option::is_none")]
+    portfolio_verified: Option<bool>,
+}
+
+impl LinksInput {
+    /// Links as found by sourcing, before any GitHub enrichment
+    pub(crate) fn new(github: Option<String>, linkedin: Option<String>, portfolio: Option<String>) -> Self {
+        Self { github, linkedin, portfolio, ..Self::default() }
+    }
 }
 
 #[derive(Deserialize, Serialize, Clone, Default)]
@@ -68,6 +100,18 @@ pub struct CreateCandidate {
     score_breakdown: ScoreBreakdownInput,
     resume_file_name: Option<String>,
     source: String,
+    /// Communication/collaboration/pace preferences; validated and normalized like team members'
+    #[serde(default)]
+    work_style: Option<WorkStyle>,
+    /// Derive the ID from email/LinkedIn so re-imports of the same person reuse it
+    #[serde(default)]
+    deterministic_id: bool,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MoveCandidateStage {
+    stage: String,
 }
 
 #[derive(Deserialize)]
@@ -78,7 +122,9 @@ pub struct LinkCandidateToJob {
     team_compatibility_score: Option<i32>,
 }
 
-#[derive(Serialize)]
+/// Candidate as returned by create/list endpoints
+/// The full GitHubStats blob is left out; fetch it from `/candidates/<id>/github-stats`
+#[derive(Serialize, Default)]
 pub struct CandidateRow {
     id: String,
     name: String,
@@ -94,6 +140,7 @@ pub struct CandidateRow {
     score_breakdown: ScoreBreakdownInput,
     resume_file_name: Option<String>,
     source: String,
+    work_style: Option<WorkStyle>,
     created_at: String,
     // GitHub enrichment fields (populated async)
     code_characteristics: Option<serde_json::Value>,
@@ -103,8 +150,16 @@ pub struct CandidateRow {
     ai_analysis_details: Option<serde_json::Value>,
     developer_profile: Option<String>,
     analysis_metadata: Option<serde_json::Value>,
-    github_stats: Option<serde_json::Value>,
     analysis_status: String,
+    /// Hiring pipeline stage (see `PipelineStage`)
+    pipeline_stage: String,
+    /// When the stored GitHub analysis was taken
+    analyzed_at: Option<String>,
+    /// When an enrichment last confirmed the analysis (it may have been served from the analysis cache)
+    #[serde(skip)]
+    checked_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// The analysis is older than ANALYSIS_TTL_DAYS and may no longer reflect the candidate's code
+    analysis_stale: bool,
 }
 
 #[derive(Serialize)]
@@ -112,12 +167,14 @@ pub struct JobCandidateRow {
     id: String,
     candidate: CandidateRow,
     job_match_score: i32,
+    /// Breakdown behind job_match_score; None until the job is rescored
+    score_breakdown: Option<serde_json::Value>,
     team_compatibility_score: i32,
     added_at: String,
 }
 
 /// Extract GitHub username from a GitHub URL
-fn extract_github_username(url: &str) -> Option<String> {
+pub(crate) fn extract_github_username(url: &str) -> Option<String> {
     let url = url.trim().trim_end_matches('/');
 
     // Handle various GitHub URL formats
@@ -138,22 +195,260 @@ fn extract_github_username(url: &str) -> Option<String> {
     }
 }
 
-#[post("/candidates", data = "<data>")]
-pub async fn create_candidate(data: json::Json<CreateCandidate>, mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let id = uuid::Uuid::new_v4();
+/// Extract GitLab username from a gitlab.com URL (`gitlab.com/<user>` or `gitlab.com/users/<user>`)
+pub(crate) fn extract_gitlab_username(url: &str) -> Option<String> {
+    let url = url.trim().trim_end_matches('/');
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(url);
+    let url = url.strip_prefix("www.").unwrap_or(url);
 
-    // Check if candidate has a GitHub link for auto-enrichment
-    let github_username = data.links.github.as_ref()
-        .and_then(|url| extract_github_username(url));
+    let mut segments = url.strip_prefix("gitlab.com/")?.split(['/', '?', '#']);
+    let username = match segments.next()? {
+        "users" => segments.next()?,
+        username => username,
+    };
 
-    // Set initial analysis status based on whether GitHub link exists
-    let initial_status = if github_username.is_some() { "analyzing" } else { "complete" };
+    if username.is_empty() {
+        None
+    } else {
+        Some(username.to_string())
+    }
+}
 
-    // Insert candidate immediately with analysis status
+/// Normalize a URL for comparison (scheme, www and trailing slash ignored)
+fn normalize_url(url: &str) -> String {
+    let url = url.trim().to_lowercase();
+    let url = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .unwrap_or(&url);
+    let url = url.strip_prefix("www.").unwrap_or(url);
+    url.trim_end_matches('/').to_string()
+}
+
+/// Add GitHub profile links to a candidate's links and cross-check the portfolio
+fn merge_github_links(links: &LinksInput, profile: &GitHubProfile) -> LinksInput {
+    let mut merged = links.clone();
+
+    if let Some(ref blog) = profile.blog {
+        merged.github_blog = Some(blog.clone());
+        merged.portfolio_verified = merged
+            .portfolio
+            .as_ref()
+            .map(|portfolio| normalize_url(portfolio) == normalize_url(blog));
+        if merged.portfolio.is_none() {
+            merged.portfolio = Some(blog.clone());
+        }
+    }
+
+    if let Some(ref twitter) = profile.twitter_username {
+        merged.twitter = Some(format!("https://twitter.com/{}", twitter));
+    }
+
+    merged
+}
+
+/// The candidate's current developer profile and the stats hash it was generated from
+async fn stored_profile(pool: &sqlx::PgPool, candidate_uuid: uuid::Uuid) -> Option<CachedProfile> {
+    let row = sqlx::query("SELECT developer_profile, profile_stats_hash FROM sourced_candidates WHERE id = $1")
+        .bind(candidate_uuid)
+        .fetch_optional(pool)
+        .await
+        .ok()??;
+
+    Some(CachedProfile {
+        stats_hash: row.get::<Option<String>, _>("profile_stats_hash")?,
+        profile: row.get::<Option<String>, _>("developer_profile")?,
+    })
+}
+
+/// Store a finished GitHub analysis on the candidate and stamp when it was checked,
+/// so a re-analysis served from the analysis cache still resets the candidate's freshness
+async fn store_github_enrichment(
+    executor: impl sqlx::PgExecutor<'_>,
+    candidate_uuid: uuid::Uuid,
+    stats: &GitHubStats,
+    profile: Option<&CachedProfile>,
+    links: &LinksInput,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"UPDATE sourced_candidates SET
+           code_characteristics = $1,
+           ai_detection_score = $2,
+           ai_proficiency_score = $3,
+           code_authenticity_score = $4,
+           ai_analysis_details = $5,
+           analysis_metadata = $6,
+           github_stats = $7,
+           developer_profile = $8,
+           links = $9,
+           profile_stats_hash = $10,
+           analysis_status = 'complete',
+           analysis_error = NULL,
+           analysis_checked_at = NOW()
+           WHERE id = $11"#
+    )
+    .bind(serde_json::to_value(&stats.ai_analysis).unwrap())
+    .bind(stats.ai_analysis.ai_detection_score as f64)
+    .bind(stats.ai_analysis.ai_proficiency_score as f64)
+    .bind(stats.ai_analysis.code_authenticity_score as f64)
+    .bind(serde_json::to_value(&stats.ai_analysis.analysis_details).unwrap())
+    .bind(serde_json::to_value(&stats.analysis_metadata).unwrap())
+    .bind(serde_json::to_value(stats).unwrap())
+    .bind(profile.map(|p| &p.profile))
+    .bind(serde_json::to_value(merge_github_links(links, &stats.profile)).unwrap())
+    .bind(profile.map(|p| &p.stats_hash))
+    .bind(candidate_uuid)
+    .execute(executor)
+    .await?;
+
+    Ok(())
+}
+
+/// Run deep GitHub analysis in the background and store the results on the candidate
+/// Returns false when DATABASE_URL or GITHUB_TOKEN is missing, or the account is blocklisted, and nothing was started
+pub(crate) fn spawn_github_enrichment(candidate_uuid: uuid::Uuid, username: String, links: LinksInput) -> bool {
+    let db_url = std::env::var("DATABASE_URL").unwrap_or_default();
+    let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
+
+    if db_url.is_empty() || token.is_empty() || analysis_blocklist().is_blocked(&username) {
+        return false;
+    }
+
+    tokio::spawn(async move {
+        // Update candidate record with enrichment data
+        if let Ok(pool) = sqlx::PgPool::connect(&db_url).await {
+            // Run deep GitHub analysis with code excerpts, reusing a fresh one of the same account
+            let analysis = cached_github_stats(&pool, &username, &token).await;
+
+            if let Ok(ref stats) = analysis {
+                // Generate developer profile from stats (uses code excerpts if available),
+                // reusing the stored one when the stats haven't changed since it was generated
+                let stored = stored_profile(&pool, candidate_uuid).await;
+                let profile = cached_developer_profile(stats, ProfileStyle::Narrative, stored)
+                    .await
+                    .ok();
+
+                // Update all GitHub enrichment fields
+                if let Err(e) = store_github_enrichment(&pool, candidate_uuid, stats, profile.as_ref(), &links).await {
+                    println!("[Candidates] Failed to store GitHub analysis of {}: {}", candidate_uuid, e);
+                }
+            } else if let Err(e) = analysis {
+                // Mark as failed if analysis didn't work, keeping the reason for the status endpoint
+                let _ = sqlx::query(
+                    "UPDATE sourced_candidates SET analysis_status = 'failed', analysis_error = $1 WHERE id = $2"
+                )
+                .bind(e.to_string())
+                .bind(candidate_uuid)
+                .execute(&pool)
+                .await;
+            }
+        }
+    });
+
+    true
+}
+
+/// Analyze a GitLab account in the background and store its code characteristics on the candidate
+/// Returns false when DATABASE_URL is missing and nothing was started; GITLAB_TOKEN is optional
+pub(crate) fn spawn_gitlab_enrichment(candidate_uuid: uuid::Uuid, username: String) -> bool {
+    let db_url = std::env::var("DATABASE_URL").unwrap_or_default();
+    let token = std::env::var("GITLAB_TOKEN").unwrap_or_default();
+
+    if db_url.is_empty() {
+        return false;
+    }
+
+    tokio::spawn(async move {
+        if let Ok(pool) = sqlx::PgPool::connect(&db_url).await {
+            let result = match generate_characteristics_from_gitlab(&username, &token).await {
+                Ok(characteristics) => sqlx::query(
+                    "UPDATE sourced_candidates SET code_characteristics = $1, analysis_status = 'complete', analysis_error = NULL WHERE id = $2"
+                )
+                .bind(serde_json::to_value(&characteristics).unwrap())
+                .bind(candidate_uuid)
+                .execute(&pool)
+                .await,
+                Err(e) => sqlx::query(
+                    "UPDATE sourced_candidates SET analysis_status = 'failed', analysis_error = $1 WHERE id = $2"
+                )
+                .bind(e.to_string())
+                .bind(candidate_uuid)
+                .execute(&pool)
+                .await,
+            };
+            if let Err(e) = result {
+                println!("[GitLab] Failed to store analysis for candidate {}: {}", candidate_uuid, e);
+            }
+        }
+    });
+
+    true
+}
+
+/// Code host account a new candidate is enriched from
+#[derive(Debug, PartialEq)]
+pub(crate) enum EnrichmentTarget {
+    GitHub(String),
+    GitLab(String),
+}
+
+impl EnrichmentTarget {
+    /// Start the background analysis; false if nothing was started
+    pub(crate) fn spawn(self, candidate_uuid: uuid::Uuid, links: LinksInput) -> bool {
+        match self {
+            Self::GitHub(username) => spawn_github_enrichment(candidate_uuid, username, links),
+            Self::GitLab(username) => spawn_gitlab_enrichment(candidate_uuid, username),
+        }
+    }
+}
+
+/// GitHub account if there is one, otherwise a gitlab.com link in the GitLab, GitHub or portfolio field
+pub(crate) fn enrichment_target(links: &LinksInput) -> Option<EnrichmentTarget> {
+    if let Some(username) = enrichment_username(links) {
+        return Some(EnrichmentTarget::GitHub(username));
+    }
+    [&links.gitlab, &links.github, &links.portfolio]
+        .into_iter()
+        .flatten()
+        .find_map(|url| extract_gitlab_username(url))
+        .map(EnrichmentTarget::GitLab)
+}
+
+/// GitHub account to enrich a new candidate from (blocklisted accounts are never analyzed)
+fn enrichment_username(links: &LinksInput) -> Option<String> {
+    links.github.as_ref()
+        .and_then(|url| extract_github_username(url))
+        .filter(|username| !analysis_blocklist().is_blocked(username))
+}
+
+/// A candidate with a GitHub or GitLab account to analyze starts out analyzing; anything else has nothing to wait for
+pub(crate) fn initial_analysis_status(target: Option<&EnrichmentTarget>) -> &'static str {
+    if target.is_some() { "analyzing" } else { "complete" }
+}
+
+/// Insert a new candidate, or refresh the imported fields of an existing one with the same ID
+/// Returns the row's pipeline stage, which a re-import keeps
+async fn insert_candidate_row(
+    conn: &mut sqlx::PgConnection,
+    id: uuid::Uuid,
+    data: &CreateCandidate,
+    initial_status: &str,
+) -> Result<sqlx::postgres::PgRow, sqlx::Error> {
     sqlx::query(
         r#"INSERT INTO sourced_candidates
-           (id, name, email, phone, location, title, skills, experience, education, links, talent_fit_score, score_breakdown, resume_file_name, source, analysis_status)
-           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)"#
+           (id, name, email, phone, location, title, skills, experience, education, links, talent_fit_score, score_breakdown, resume_file_name, source, analysis_status, work_style)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+           ON CONFLICT (id) DO UPDATE SET
+           name = EXCLUDED.name, email = EXCLUDED.email, phone = EXCLUDED.phone,
+           location = EXCLUDED.location, title = EXCLUDED.title, skills = EXCLUDED.skills,
+           experience = EXCLUDED.experience, education = EXCLUDED.education, links = EXCLUDED.links,
+           talent_fit_score = EXCLUDED.talent_fit_score, score_breakdown = EXCLUDED.score_breakdown,
+           resume_file_name = EXCLUDED.resume_file_name, source = EXCLUDED.source,
+           analysis_status = EXCLUDED.analysis_status, analysis_error = NULL, work_style = EXCLUDED.work_style
+           RETURNING pipeline_stage"#
     )
     .bind(id)
     .bind(&data.name)
@@ -170,74 +465,37 @@ pub async fn create_candidate(data: json::Json<CreateCandidate>, mut db: Connect
     .bind(&data.resume_file_name)
     .bind(&data.source)
     .bind(initial_status)
-    .execute(&mut **db)
+    .bind(data.work_style.as_ref().map(|w| serde_json::to_value(w).unwrap()))
+    .fetch_one(conn)
     .await
-    .unwrap();
-
-    // Spawn background task for deep GitHub analysis if username was extracted
-    if let Some(username) = github_username {
-        let db_url = std::env::var("DATABASE_URL").unwrap_or_default();
-        let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
-        let candidate_id = id.to_string();
-
-        if !db_url.is_empty() && !token.is_empty() {
-            tokio::spawn(async move {
-                // Run deep GitHub analysis with code excerpts
-                let stats = analyze_github_user_deep(&username, &token)
-                    .await
-                    .ok();
+}
 
-                // Generate developer profile from stats (uses code excerpts if available)
-                let profile = if let Some(ref s) = stats {
-                    generate_developer_profile(s)
-                        .await
-                        .ok()
-                } else {
-                    None
-                };
-
-                // Update candidate record with enrichment data
-                if let Ok(pool) = sqlx::PgPool::connect(&db_url).await {
-                    let candidate_uuid = uuid::Uuid::parse_str(&candidate_id).unwrap();
-
-                    if let Some(ref stats) = stats {
-                        // Update all GitHub enrichment fields
-                        let _ = sqlx::query(
-                            r#"UPDATE sourced_candidates SET
-                               code_characteristics = $1,
-                               ai_detection_score = $2,
-                               ai_proficiency_score = $3,
-                               code_authenticity_score = $4,
-                               ai_analysis_details = $5,
-                               analysis_metadata = $6,
-                               github_stats = $7,
-                               developer_profile = $8,
-                               analysis_status = 'complete'
-                               WHERE id = $9"#
-                        )
-                        .bind(serde_json::to_value(&stats.ai_analysis).unwrap())
-                        .bind(stats.ai_analysis.ai_detection_score as f64)
-                        .bind(stats.ai_analysis.ai_proficiency_score as f64)
-                        .bind(stats.ai_analysis.code_authenticity_score as f64)
-                        .bind(serde_json::to_value(&stats.ai_analysis.analysis_details).unwrap())
-                        .bind(serde_json::to_value(&stats.analysis_metadata).unwrap())
-                        .bind(serde_json::to_value(&stats).unwrap())
-                        .bind(&profile)
-                        .bind(candidate_uuid)
-                        .execute(&pool)
-                        .await;
-                    } else {
-                        // Mark as failed if analysis didn't work
-                        let _ = sqlx::query(
-                            "UPDATE sourced_candidates SET analysis_status = 'failed' WHERE id = $1"
-                        )
-                        .bind(candidate_uuid)
-                        .execute(&pool)
-                        .await;
-                    }
-                }
-            });
-        }
+#[post("/candidates", data = "<data>")]
+pub async fn create_candidate(mut data: json::Json<CreateCandidate>, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    if let Some(Err(message)) = data.work_style.as_ref().map(|w| w.validate()) {
+        return RawJson(serde_json::json!({ "error": message }).to_string());
+    }
+
+    // Store compound entries ("React/Redux") as separate skills so each can match
+    data.skills = split_compound_skills(
+        std::mem::take(&mut data.skills),
+        |s| &s.name,
+        |s, name| SkillInput { name, level: s.level.clone() },
+    );
+
+    let id = candidate_id(data.deterministic_id, data.email.as_deref(), data.links.linkedin.as_deref());
+
+    // Check if candidate has a GitHub (or GitLab) link for auto-enrichment
+    let target = enrichment_target(&data.links);
+    let initial_status = initial_analysis_status(target.as_ref());
+
+    // Insert candidate immediately with analysis status
+    // A deterministic ID that already exists is a re-import: refresh the imported fields, keeping its pipeline stage
+    let row = insert_candidate_row(&mut db, id, &data, initial_status).await.unwrap();
+
+    // Spawn background task for deep GitHub (or GitLab) analysis if a username was extracted
+    if let Some(target) = target {
+        let _ = target.spawn(id, data.links.clone());
     }
 
     // Return candidate immediately (without waiting for analysis)
@@ -256,6 +514,7 @@ pub async fn create_candidate(data: json::Json<CreateCandidate>, mut db: Connect
         score_breakdown: data.score_breakdown.clone(),
         resume_file_name: data.resume_file_name.clone(),
         source: data.source.clone(),
+        work_style: data.work_style.clone(),
         created_at: chrono::Utc::now().to_string(),
         // GitHub fields will be populated async
         code_characteristics: None,
@@ -265,20 +524,68 @@ pub async fn create_candidate(data: json::Json<CreateCandidate>, mut db: Connect
         ai_analysis_details: None,
         developer_profile: None,
         analysis_metadata: None,
-        github_stats: None,
         analysis_status: initial_status.to_string(),
+        pipeline_stage: row.get::<Option<String>, _>("pipeline_stage").unwrap_or_else(|| PipelineStage::Sourced.name().to_string()),
+        analyzed_at: None,
+        checked_at: None,
+        analysis_stale: false,
     };
 
     RawJson(serde_json::to_string(&candidate).unwrap())
 }
 
-#[post("/jobs/<job_id>/candidates", data = "<data>")]
-pub async fn add_candidate_to_job(job_id: &str, data: json::Json<LinkCandidateToJob>, mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let id = uuid::Uuid::new_v4();
-    let job_uuid = uuid::Uuid::parse_str(job_id).unwrap();
-    let candidate_uuid = uuid::Uuid::parse_str(&data.candidate_id).unwrap();
+/// Which side of a job/candidate link is missing, if any
+fn check_link_targets(job_exists: bool, candidate_exists: bool) -> Result<(), &'static str> {
+    if !job_exists {
+        Err("Job not found")
+    } else if !candidate_exists {
+        Err("Candidate not found")
+    } else {
+        Ok(())
+    }
+}
 
-    sqlx::query(
+fn error_response(status: Status, message: &str) -> (Status, RawJson<String>) {
+    (status, RawJson(serde_json::json!({ "error": message }).to_string()))
+}
+
+/// Link a candidate to a job (updating the scores of an existing link), after checking both exist
+/// Errors are returned as the response to send
+async fn link_candidate(
+    conn: &mut sqlx::PgConnection,
+    id: uuid::Uuid,
+    job_uuid: uuid::Uuid,
+    candidate_uuid: uuid::Uuid,
+    job_match_score: i32,
+    team_compatibility_score: i32,
+) -> Result<(), (Status, RawJson<String>)> {
+    let mut tx = match conn.begin().await {
+        Ok(tx) => tx,
+        Err(e) => return Err(error_response(Status::InternalServerError, &format!("Database error: {}", e))),
+    };
+
+    // Lock both rows so a concurrent delete can't orphan the link
+    let job_exists = sqlx::query("SELECT id FROM jobs WHERE id = $1 FOR SHARE")
+        .bind(job_uuid)
+        .fetch_optional(&mut *tx)
+        .await;
+    let candidate_exists = sqlx::query("SELECT id FROM sourced_candidates WHERE id = $1 FOR SHARE")
+        .bind(candidate_uuid)
+        .fetch_optional(&mut *tx)
+        .await;
+
+    let (job_exists, candidate_exists) = match (job_exists, candidate_exists) {
+        (Ok(job), Ok(candidate)) => (job.is_some(), candidate.is_some()),
+        (Err(e), _) | (_, Err(e)) => {
+            return Err(error_response(Status::InternalServerError, &format!("Database error: {}", e)));
+        }
+    };
+
+    if let Err(message) = check_link_targets(job_exists, candidate_exists) {
+        return Err(error_response(Status::NotFound, message));
+    }
+
+    let result = sqlx::query(
         r#"INSERT INTO job_candidates (id, job_id, candidate_id, job_match_score, team_compatibility_score)
            VALUES ($1, $2, $3, $4, $5)
            ON CONFLICT (job_id, candidate_id) DO UPDATE SET
@@ -288,88 +595,626 @@ pub async fn add_candidate_to_job(job_id: &str, data: json::Json<LinkCandidateTo
     .bind(id)
     .bind(job_uuid)
     .bind(candidate_uuid)
-    .bind(data.job_match_score.unwrap_or(0))
-    .bind(data.team_compatibility_score.unwrap_or(0))
-    .execute(&mut **db)
-    .await
-    .unwrap();
+    .bind(job_match_score)
+    .bind(team_compatibility_score)
+    .execute(&mut *tx)
+    .await;
+
+    if let Err(e) = result {
+        return Err(error_response(Status::InternalServerError, &format!("Failed to link candidate: {}", e)));
+    }
 
-    RawJson(format!(r#"{{"success":true,"id":"{}","candidate_id":"{}"}}"#, id, data.candidate_id))
+    if let Err(e) = tx.commit().await {
+        return Err(error_response(Status::InternalServerError, &format!("Database error: {}", e)));
+    }
+
+    Ok(())
 }
 
-#[get("/jobs/<job_id>/candidates")]
-pub async fn get_job_candidates(job_id: &str, mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let job_uuid = uuid::Uuid::parse_str(job_id).unwrap();
+#[post("/jobs/<job_id>/candidates", data = "<data>")]
+pub async fn add_candidate_to_job(job_id: &str, data: json::Json<LinkCandidateToJob>, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    let id = uuid::Uuid::new_v4();
+    let job_uuid = match uuid::Uuid::parse_str(job_id) {
+        Ok(u) => u,
+        Err(_) => return error_response(Status::BadRequest, "Invalid job ID"),
+    };
+    let candidate_uuid = match uuid::Uuid::parse_str(&data.candidate_id) {
+        Ok(u) => u,
+        Err(_) => return error_response(Status::BadRequest, "Invalid candidate ID"),
+    };
+
+    if let Err(response) = link_candidate(
+        &mut db,
+        id,
+        job_uuid,
+        candidate_uuid,
+        data.job_match_score.unwrap_or(0),
+        data.team_compatibility_score.unwrap_or(0),
+    ).await {
+        return response;
+    }
 
-    let rows = sqlx::query(
-        r#"SELECT jc.id, jc.job_match_score, jc.team_compatibility_score, jc.added_at,
-                  sc.id as candidate_id, sc.name, sc.email, sc.phone, sc.location, sc.title,
+    (Status::Ok, RawJson(format!(r#"{{"success":true,"id":"{}","candidate_id":"{}"}}"#, id, data.candidate_id)))
+}
+
+/// Flag a candidate whose analysis was last checked more than the TTL ago
+/// Returns whether a re-analysis should be queued; failed analyses are left for a manual retry
+fn flag_stale_analysis(candidate: &mut CandidateRow, policy: &FreshnessPolicy, now: chrono::DateTime<chrono::Utc>) -> bool {
+    let checked_at = candidate.checked_at.map(|at| at.to_rfc3339());
+    candidate.analysis_stale = policy.is_stale(checked_at.as_deref().or(candidate.analyzed_at.as_deref()), now);
+    candidate.analysis_stale && policy.auto_refresh && !matches!(candidate.analysis_status.as_str(), "analyzing" | "failed")
+}
+
+/// Start a fresh GitHub analysis for a stale candidate and mark it as analyzing
+async fn queue_reanalysis(candidate: &mut CandidateRow, db: &mut Connection<MainDatabase>) {
+    let Some(username) = candidate.links.github.as_deref().and_then(extract_github_username) else {
+        return;
+    };
+    let Ok(candidate_uuid) = uuid::Uuid::parse_str(&candidate.id) else {
+        return;
+    };
+
+    if !spawn_github_enrichment(candidate_uuid, username, candidate.links.clone()) {
+        return;
+    }
+
+    println!("[FRESHNESS] Re-analyzing candidate {} (analyzed at {:?})", candidate.id, candidate.analyzed_at);
+    let _ = sqlx::query("UPDATE sourced_candidates SET analysis_status = 'analyzing' WHERE id = $1")
+        .bind(candidate_uuid)
+        .execute(&mut ***db)
+        .await;
+    candidate.analysis_status = "analyzing".to_string();
+}
+
+/// Error body for an unrecognized `stage` value
+fn unknown_stage_message(value: &str) -> String {
+    let stages: Vec<&str> = PipelineStage::all().iter().map(|s| s.name()).collect();
+    format!("Unknown pipeline stage '{}' (expected one of: {})", value, stages.join(", "))
+}
+
+/// Parse an optional `stage` filter
+fn parse_stage_filter(stage: Option<&str>) -> Result<Option<PipelineStage>, String> {
+    stage
+        .map(|s| PipelineStage::parse(s).ok_or_else(|| unknown_stage_message(s)))
+        .transpose()
+}
+
+/// `sourced_candidates` columns (aliased `sc`) read by `candidate_from_row`
+const CANDIDATE_COLUMNS: &str = r#"sc.id as candidate_id, sc.name, sc.email, sc.phone, sc.location, sc.title,
                   sc.skills, sc.experience, sc.education, sc.links,
                   sc.talent_fit_score, sc.score_breakdown, sc.resume_file_name,
-                  sc.source, sc.created_at,
+                  sc.source, sc.work_style, sc.created_at,
                   sc.code_characteristics, sc.ai_detection_score, sc.ai_proficiency_score,
                   sc.code_authenticity_score, sc.ai_analysis_details, sc.developer_profile,
-                  sc.analysis_metadata, sc.github_stats, sc.analysis_status
+                  sc.analysis_metadata, sc.analysis_status,
+                  COALESCE(sc.pipeline_stage, 'sourced') AS pipeline_stage,
+                  sc.github_stats->>'analyzed_at' AS analyzed_at, sc.analysis_checked_at"#;
+
+/// Candidate from a row selecting `CANDIDATE_COLUMNS`; staleness is flagged separately
+fn candidate_from_row(r: &sqlx::postgres::PgRow) -> CandidateRow {
+    CandidateRow {
+        id: r.get::<uuid::Uuid, _>("candidate_id").to_string(),
+        name: r.get("name"),
+        email: r.get("email"),
+        phone: r.get("phone"),
+        location: r.get("location"),
+        title: r.get("title"),
+        skills: serde_json::from_value(r.get::<serde_json::Value, _>("skills")).unwrap_or_default(),
+        experience: serde_json::from_value(r.get::<serde_json::Value, _>("experience")).unwrap_or_default(),
+        education: serde_json::from_value(r.get::<serde_json::Value, _>("education")).unwrap_or_default(),
+        links: serde_json::from_value(r.get::<serde_json::Value, _>("links")).unwrap_or_default(),
+        talent_fit_score: r.get::<Option<i32>, _>("talent_fit_score").unwrap_or(0),
+        score_breakdown: serde_json::from_value(r.get::<serde_json::Value, _>("score_breakdown")).unwrap_or_default(),
+        resume_file_name: r.get("resume_file_name"),
+        source: r.get::<Option<String>, _>("source").unwrap_or_else(|| "manual".to_string()),
+        work_style: r.get::<Option<serde_json::Value>, _>("work_style").and_then(|v| serde_json::from_value(v).ok()),
+        created_at: r.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_string(),
+        code_characteristics: r.get("code_characteristics"),
+        ai_detection_score: r.get("ai_detection_score"),
+        ai_proficiency_score: r.get("ai_proficiency_score"),
+        code_authenticity_score: r.get("code_authenticity_score"),
+        ai_analysis_details: r.get("ai_analysis_details"),
+        developer_profile: r.get("developer_profile"),
+        analysis_metadata: r.get("analysis_metadata"),
+        analysis_status: r.get::<Option<String>, _>("analysis_status").unwrap_or_else(|| "complete".to_string()),
+        pipeline_stage: r.get("pipeline_stage"),
+        analyzed_at: r.get("analyzed_at"),
+        checked_at: r.get("analysis_checked_at"),
+        analysis_stale: false,
+    }
+}
+
+const DEFAULT_CANDIDATE_PAGE_LIMIT: usize = 30;
+const MAX_CANDIDATE_PAGE_LIMIT: usize = 100;
+
+/// Filters and page for the talent pool listing
+#[derive(Debug, Clone, PartialEq)]
+struct CandidateSearch {
+    /// ILIKE pattern matched against name and title
+    pattern: Option<String>,
+    /// Skill name, matched case-insensitively
+    skill: Option<String>,
+    /// Pipeline stage name; unset candidates count as sourced
+    stage: Option<&'static str>,
+    limit: usize,
+    offset: usize,
+}
+
+impl CandidateSearch {
+    /// Blank filters are ignored; limit is clamped to 1..=MAX_CANDIDATE_PAGE_LIMIT
+    fn new(q: Option<&str>, skill: Option<&str>, limit: Option<usize>, offset: Option<usize>) -> Self {
+        let q = q.map(str::trim).filter(|q| !q.is_empty());
+        Self {
+            pattern: q.map(|q| format!("%{}%", escape_like(q))),
+            skill: skill.map(str::trim).filter(|s| !s.is_empty()).map(String::from),
+            stage: None,
+            limit: limit.unwrap_or(DEFAULT_CANDIDATE_PAGE_LIMIT).clamp(1, MAX_CANDIDATE_PAGE_LIMIT),
+            offset: offset.unwrap_or(0),
+        }
+    }
+}
+
+/// Escape LIKE wildcards so the search text matches literally
+fn escape_like(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_")
+}
+
+/// One page of the talent pool
+#[derive(Serialize)]
+pub struct CandidatePage {
+    candidates: Vec<CandidateRow>,
+    total: i64,
+    limit: usize,
+    offset: usize,
+    has_more: bool,
+}
+
+const CANDIDATE_SEARCH_FILTER: &str = r#"($1::varchar IS NULL OR sc.name ILIKE $1 OR sc.title ILIKE $1)
+             AND ($2::varchar IS NULL OR EXISTS (
+                 SELECT 1 FROM jsonb_array_elements(COALESCE(sc.skills, '[]'::jsonb)) skill
+                 WHERE LOWER(skill->>'name') = LOWER($2)
+             ))
+             AND ($3::varchar IS NULL OR COALESCE(sc.pipeline_stage, 'sourced') = $3)"#;
+
+/// Matching candidates, newest first, and how many match in total
+async fn search_candidate_rows(
+    conn: &mut sqlx::PgConnection,
+    search: &CandidateSearch,
+) -> Result<CandidatePage, sqlx::Error> {
+    let (total,): (i64,) = sqlx::query_as(&format!(
+        "SELECT COUNT(*) FROM sourced_candidates sc WHERE {}",
+        CANDIDATE_SEARCH_FILTER
+    ))
+    .bind(&search.pattern)
+    .bind(&search.skill)
+    .bind(search.stage)
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let rows = sqlx::query(&format!(
+        "SELECT {} FROM sourced_candidates sc WHERE {} ORDER BY sc.created_at DESC, sc.id LIMIT $4 OFFSET $5",
+        CANDIDATE_COLUMNS, CANDIDATE_SEARCH_FILTER
+    ))
+    .bind(&search.pattern)
+    .bind(&search.skill)
+    .bind(search.stage)
+    .bind(search.limit as i64)
+    .bind(search.offset as i64)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let candidates: Vec<CandidateRow> = rows.iter().map(candidate_from_row).collect();
+    Ok(CandidatePage {
+        has_more: (search.offset + candidates.len()) < total as usize,
+        candidates,
+        total,
+        limit: search.limit,
+        offset: search.offset,
+    })
+}
+
+/// Talent pool: all sourced candidates, optionally searched by name/title (`q`) and filtered by `skill` and pipeline `stage`
+#[get("/candidates?<q>&<skill>&<stage>&<limit>&<offset>")]
+pub async fn get_candidates(
+    q: Option<&str>,
+    skill: Option<&str>,
+    stage: Option<&str>,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    mut db: Connection<MainDatabase>,
+) -> (Status, RawJson<String>) {
+    let stage = match parse_stage_filter(stage) {
+        Ok(stage) => stage.map(|s| s.name()),
+        Err(message) => return error_response(Status::BadRequest, &message),
+    };
+    let search = CandidateSearch { stage, ..CandidateSearch::new(q, skill, limit, offset) };
+
+    let mut page = match search_candidate_rows(&mut db, &search).await {
+        Ok(page) => page,
+        Err(e) => return error_response(Status::InternalServerError, &format!("Failed to fetch candidates: {}", e)),
+    };
+
+    let policy = freshness_policy();
+    let now = chrono::Utc::now();
+    for candidate in &mut page.candidates {
+        if flag_stale_analysis(candidate, policy, now) {
+            queue_reanalysis(candidate, &mut db).await;
+        }
+    }
+
+    (Status::Ok, RawJson(serde_json::to_string(&page).unwrap()))
+}
+
+#[get("/jobs/<job_id>/candidates?<stage>")]
+pub async fn get_job_candidates(job_id: &str, stage: Option<&str>, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    let Ok(job_uuid) = uuid::Uuid::parse_str(job_id) else {
+        return error_response(Status::BadRequest, "Invalid job ID");
+    };
+    let stage = match parse_stage_filter(stage) {
+        Ok(stage) => stage.map(|s| s.name()),
+        Err(message) => return error_response(Status::BadRequest, &message),
+    };
+
+    let query = format!(
+        r#"SELECT jc.id, jc.job_match_score, jc.score_breakdown AS job_score_breakdown, jc.team_compatibility_score, jc.added_at, {}
            FROM job_candidates jc
            JOIN sourced_candidates sc ON jc.candidate_id = sc.id
            WHERE jc.job_id = $1
-           ORDER BY jc.added_at DESC"#
-    )
-    .bind(job_uuid)
-    .fetch_all(&mut **db)
-    .await
-    .unwrap();
+             AND ($2::varchar IS NULL OR COALESCE(sc.pipeline_stage, 'sourced') = $2)
+           ORDER BY jc.added_at DESC"#,
+        CANDIDATE_COLUMNS
+    );
+    let rows = match sqlx::query(&query)
+        .bind(job_uuid)
+        .bind(stage)
+        .fetch_all(&mut **db)
+        .await
+    {
+        Ok(rows) => rows,
+        Err(e) => return error_response(Status::InternalServerError, &format!("Failed to fetch candidates: {}", e)),
+    };
 
-    let job_candidates: Vec<JobCandidateRow> = rows
+    let mut job_candidates: Vec<JobCandidateRow> = rows
         .into_iter()
         .map(|r| {
             JobCandidateRow {
                 id: r.get::<uuid::Uuid, _>("id").to_string(),
-                candidate: CandidateRow {
-                    id: r.get::<uuid::Uuid, _>("candidate_id").to_string(),
-                    name: r.get("name"),
-                    email: r.get("email"),
-                    phone: r.get("phone"),
-                    location: r.get("location"),
-                    title: r.get("title"),
-                    skills: serde_json::from_value(r.get::<serde_json::Value, _>("skills")).unwrap_or_default(),
-                    experience: serde_json::from_value(r.get::<serde_json::Value, _>("experience")).unwrap_or_default(),
-                    education: serde_json::from_value(r.get::<serde_json::Value, _>("education")).unwrap_or_default(),
-                    links: serde_json::from_value(r.get::<serde_json::Value, _>("links")).unwrap_or_default(),
-                    talent_fit_score: r.get::<Option<i32>, _>("talent_fit_score").unwrap_or(0),
-                    score_breakdown: serde_json::from_value(r.get::<serde_json::Value, _>("score_breakdown")).unwrap_or_default(),
-                    resume_file_name: r.get("resume_file_name"),
-                    source: r.get::<Option<String>, _>("source").unwrap_or_else(|| "manual".to_string()),
-                    created_at: r.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_string(),
-                    code_characteristics: r.get("code_characteristics"),
-                    ai_detection_score: r.get("ai_detection_score"),
-                    ai_proficiency_score: r.get("ai_proficiency_score"),
-                    code_authenticity_score: r.get("code_authenticity_score"),
-                    ai_analysis_details: r.get("ai_analysis_details"),
-                    developer_profile: r.get("developer_profile"),
-                    analysis_metadata: r.get("analysis_metadata"),
-                    github_stats: r.get("github_stats"),
-                    analysis_status: r.get::<Option<String>, _>("analysis_status").unwrap_or_else(|| "complete".to_string()),
-                },
+                candidate: candidate_from_row(&r),
                 job_match_score: r.get::<Option<i32>, _>("job_match_score").unwrap_or(0),
+                score_breakdown: r.get("job_score_breakdown"),
                 team_compatibility_score: r.get::<Option<i32>, _>("team_compatibility_score").unwrap_or(0),
                 added_at: r.get::<chrono::DateTime<chrono::Utc>, _>("added_at").to_string(),
             }
         })
         .collect();
 
-    RawJson(serde_json::to_string(&job_candidates).unwrap())
+    let policy = freshness_policy();
+    let now = chrono::Utc::now();
+    for row in &mut job_candidates {
+        if flag_stale_analysis(&mut row.candidate, policy, now) {
+            queue_reanalysis(&mut row.candidate, &mut db).await;
+        }
+    }
+
+    (Status::Ok, RawJson(serde_json::to_string(&job_candidates).unwrap()))
+}
+
+/// Parse the stored `github_stats` JSONB column
+/// None means enrichment hasn't produced stats yet
+fn parse_stored_github_stats(value: Option<serde_json::Value>) -> Result<GitHubStats, (Status, &'static str)> {
+    let value = value.ok_or((Status::NotFound, "GitHub stats not available for this candidate"))?;
+    serde_json::from_value(value).map_err(|_| (Status::InternalServerError, "Stored GitHub stats are invalid"))
 }
 
-#[get("/candidates/count")]
-pub async fn get_candidates_count(mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let row = sqlx::query("SELECT COUNT(*) as count FROM sourced_candidates")
-        .fetch_one(&mut **db)
+#[get("/candidates/<id>/github-stats")]
+pub async fn get_candidate_github_stats(id: &str, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    let candidate_uuid = match uuid::Uuid::parse_str(id) {
+        Ok(u) => u,
+        Err(_) => return error_response(Status::BadRequest, "Invalid candidate ID"),
+    };
+
+    let row = match sqlx::query("SELECT github_stats FROM sourced_candidates WHERE id = $1")
+        .bind(candidate_uuid)
+        .fetch_optional(&mut **db)
         .await
-        .unwrap();
+    {
+        Ok(Some(r)) => r,
+        Ok(None) => return error_response(Status::NotFound, "Candidate not found"),
+        Err(e) => return error_response(Status::InternalServerError, &format!("Failed to fetch candidate: {}", e)),
+    };
+
+    match parse_stored_github_stats(row.get("github_stats")) {
+        Ok(stats) => (Status::Ok, RawJson(serde_json::to_string(&stats).unwrap())),
+        Err((status, message)) => error_response(status, message),
+    }
+}
 
-    let count: i64 = row.get("count");
-    RawJson(format!(r#"{{"count":{}}}"#, count))
+/// Progress of a candidate's background GitHub analysis, cheap enough to poll
+#[derive(Serialize, Debug, PartialEq)]
+pub struct AnalysisStatus {
+    status: String,
+    ai_detection_score: Option<f64>,
+    developer_profile_ready: bool,
+    /// Why the analysis failed; only present when `status` is "failed"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+async fn fetch_analysis_status(conn: &mut sqlx::PgConnection, candidate_uuid: uuid::Uuid) -> Result<Option<AnalysisStatus>, sqlx::Error> {
+    let row = sqlx::query(
+        r#"SELECT analysis_status, ai_detection_score,
+                  developer_profile IS NOT NULL AS developer_profile_ready, analysis_error
+           FROM sourced_candidates WHERE id = $1"#
+    )
+    .bind(candidate_uuid)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row.map(|r| {
+        let status = r.get::<Option<String>, _>("analysis_status").unwrap_or_else(|| "complete".to_string());
+        let reason = if status == "failed" { r.get("analysis_error") } else { None };
+        AnalysisStatus {
+            status,
+            ai_detection_score: r.get("ai_detection_score"),
+            developer_profile_ready: r.get("developer_profile_ready"),
+            reason,
+        }
+    }))
+}
+
+#[get("/candidates/<id>/analysis-status")]
+pub async fn get_candidate_analysis_status(id: &str, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    let candidate_uuid = match uuid::Uuid::parse_str(id) {
+        Ok(u) => u,
+        Err(_) => return error_response(Status::BadRequest, "Invalid candidate ID"),
+    };
+
+    match fetch_analysis_status(&mut db, candidate_uuid).await {
+        Ok(Some(status)) => (Status::Ok, RawJson(serde_json::to_string(&status).unwrap())),
+        Ok(None) => error_response(Status::NotFound, "Candidate not found"),
+        Err(e) => error_response(Status::InternalServerError, &format!("Failed to fetch candidate: {}", e)),
+    }
+}
+
+#[get("/candidates/count?<stage>")]
+pub async fn get_candidates_count(stage: Option<&str>, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    let stage = match parse_stage_filter(stage) {
+        Ok(stage) => stage.map(|s| s.name()),
+        Err(message) => return error_response(Status::BadRequest, &message),
+    };
+
+    match count_candidates(&mut db, stage).await {
+        Ok(count) => (Status::Ok, RawJson(format!(r#"{{"count":{}}}"#, count))),
+        Err(e) => error_response(Status::InternalServerError, &format!("Failed to count candidates: {}", e)),
+    }
+}
+
+/// Number of stored candidates, optionally only those in one pipeline stage
+pub(crate) async fn count_candidates(conn: &mut sqlx::PgConnection, stage: Option<&str>) -> Result<i64, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT COUNT(*) as count FROM sourced_candidates WHERE $1::varchar IS NULL OR COALESCE(pipeline_stage, 'sourced') = $1"
+    )
+        .bind(stage)
+        .fetch_one(conn)
+        .await?;
+
+    Ok(row.get("count"))
+}
+
+/// Move a candidate to the stage chosen by `target` from its current one, under a row lock
+async fn update_pipeline_stage<F>(id: &str, db: &mut Connection<MainDatabase>, target: F) -> (Status, RawJson<String>)
+where
+    F: FnOnce(PipelineStage) -> Result<PipelineStage, String>,
+{
+    let candidate_uuid = match uuid::Uuid::parse_str(id) {
+        Ok(u) => u,
+        Err(_) => return error_response(Status::BadRequest, "Invalid candidate ID"),
+    };
+
+    let mut tx = match (***db).begin().await {
+        Ok(tx) => tx,
+        Err(e) => return error_response(Status::InternalServerError, &format!("Database error: {}", e)),
+    };
+
+    let stored = match sqlx::query_as::<_, (Option<String>,)>("SELECT pipeline_stage FROM sourced_candidates WHERE id = $1 FOR UPDATE")
+        .bind(candidate_uuid)
+        .fetch_optional(&mut *tx)
+        .await
+    {
+        Ok(Some((stage,))) => stage,
+        Ok(None) => return error_response(Status::NotFound, "Candidate not found"),
+        Err(e) => return error_response(Status::InternalServerError, &format!("Database error: {}", e)),
+    };
+
+    let current = stored.as_deref().and_then(PipelineStage::parse).unwrap_or(PipelineStage::Sourced);
+    let next = match target(current) {
+        Ok(next) => next,
+        Err(message) => return error_response(Status::Conflict, &message),
+    };
+
+    let result = sqlx::query("UPDATE sourced_candidates SET pipeline_stage = $2 WHERE id = $1")
+        .bind(candidate_uuid)
+        .bind(next.name())
+        .execute(&mut *tx)
+        .await;
+
+    if let Err(e) = result {
+        return error_response(Status::InternalServerError, &format!("Failed to update pipeline stage: {}", e));
+    }
+
+    if let Err(e) = tx.commit().await {
+        return error_response(Status::InternalServerError, &format!("Database error: {}", e));
+    }
+
+    (Status::Ok, RawJson(serde_json::json!({
+        "id": candidate_uuid.to_string(),
+        "previous_stage": current.name(),
+        "pipeline_stage": next.name(),
+    }).to_string()))
+}
+
+/// Move a candidate to a specific pipeline stage, if the transition is allowed
+#[put("/candidates/<id>/stage", data = "<data>")]
+pub async fn move_candidate_stage(id: &str, data: json::Json<MoveCandidateStage>, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    let Some(stage) = PipelineStage::parse(&data.stage) else {
+        return error_response(Status::BadRequest, &unknown_stage_message(&data.stage));
+    };
+
+    update_pipeline_stage(id, &mut db, |current| current.transition(stage).map_err(|e| e.to_string())).await
+}
+
+/// Advance a candidate to the next pipeline stage (sourced → screened → interviewing → offer → hired)
+#[post("/candidates/<id>/advance")]
+pub async fn advance_candidate(id: &str, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    update_pipeline_stage(id, &mut db, |current| {
+        let next = current
+            .next()
+            .ok_or_else(|| format!("Candidate is {} and can't advance further", current))?;
+        current.transition(next).map_err(|e| e.to_string())
+    }).await
+}
+
+const SCORE_BUCKET_LABELS: [&str; 5] = ["0-19", "20-39", "40-59", "60-79", "80-100"];
+const DEFAULT_TOP_SKILLS: usize = 10;
+const MAX_TOP_SKILLS: usize = 50;
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct ScoreBucket {
+    range: String,
+    count: i64,
+}
+
+#[derive(Serialize, Debug, PartialEq)]
+#[serde(crate = "rocket::serde")]
+pub struct SkillCount {
+    name: String,
+    count: i64,
+}
+
+#[derive(Serialize, Debug)]
+#[serde(crate = "rocket::serde")]
+pub struct CandidateStats {
+    total: i64,
+    score_distribution: Vec<ScoreBucket>,
+    top_skills: Vec<SkillCount>,
+    avg_ai_detection_score: Option<f64>,
+    by_analysis_status: std::collections::BTreeMap<String, i64>,
+    by_source: std::collections::BTreeMap<String, i64>,
+}
+
+/// Count skill names across candidates' skills JSONB, keeping the top N
+fn skill_histogram(skill_lists: &[serde_json::Value], top_n: usize) -> Vec<SkillCount> {
+    let mut counts: std::collections::HashMap<String, (String, i64)> = std::collections::HashMap::new();
+
+    for list in skill_lists {
+        let Some(skills) = list.as_array() else { continue };
+        // Count each skill once per candidate
+        let mut seen = std::collections::HashSet::new();
+        for skill in skills {
+            let Some(name) = skill.get("name").and_then(|n| n.as_str()) else { continue };
+            let name = name.trim();
+            let key = name.to_lowercase();
+            if key.is_empty() || !seen.insert(key.clone()) {
+                continue;
+            }
+            counts.entry(key).or_insert_with(|| (name.to_string(), 0)).1 += 1;
+        }
+    }
+
+    let mut histogram: Vec<SkillCount> = counts
+        .into_values()
+        .map(|(name, count)| SkillCount { name, count })
+        .collect();
+    histogram.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+    histogram.truncate(top_n);
+    histogram
+}
+
+/// Build the stats response from the grouped SQL rows
+fn assemble_candidate_stats(
+    total: i64,
+    avg_ai_detection_score: Option<f64>,
+    bucket_counts: &[(i32, i64)],
+    status_counts: Vec<(String, i64)>,
+    source_counts: Vec<(String, i64)>,
+    skill_lists: &[serde_json::Value],
+    top_n: usize,
+) -> CandidateStats {
+    let score_distribution = SCORE_BUCKET_LABELS
+        .iter()
+        .enumerate()
+        .map(|(i, label)| ScoreBucket {
+            range: label.to_string(),
+            count: bucket_counts
+                .iter()
+                .filter(|(bucket, _)| *bucket as usize == i)
+                .map(|(_, count)| count)
+                .sum(),
+        })
+        .collect();
+
+    CandidateStats {
+        total,
+        score_distribution,
+        top_skills: skill_histogram(skill_lists, top_n),
+        avg_ai_detection_score,
+        by_analysis_status: status_counts.into_iter().collect(),
+        by_source: source_counts.into_iter().collect(),
+    }
+}
+
+/// Aggregate stats across all candidates for the dashboard
+#[get("/candidates/stats?<top>")]
+pub async fn get_candidates_stats(top: Option<usize>, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    let top_n = top.unwrap_or(DEFAULT_TOP_SKILLS).clamp(1, MAX_TOP_SKILLS);
+
+    let totals = sqlx::query_as::<_, (i64, Option<f64>)>(
+        "SELECT COUNT(*), AVG(ai_detection_score) FROM sourced_candidates"
+    )
+    .fetch_one(&mut **db)
+    .await;
+
+    let buckets = sqlx::query_as::<_, (i32, i64)>(
+        r#"SELECT LEAST(GREATEST(COALESCE(talent_fit_score, 0), 0) / 20, 4) AS bucket, COUNT(*)
+           FROM sourced_candidates
+           GROUP BY bucket"#
+    )
+    .fetch_all(&mut **db)
+    .await;
+
+    let statuses = sqlx::query_as::<_, (String, i64)>(
+        r#"SELECT COALESCE(analysis_status, 'pending') AS status, COUNT(*)
+           FROM sourced_candidates
+           GROUP BY status"#
+    )
+    .fetch_all(&mut **db)
+    .await;
+
+    let sources = sqlx::query_as::<_, (String, i64)>(
+        r#"SELECT COALESCE(source, 'manual') AS source, COUNT(*)
+           FROM sourced_candidates
+           GROUP BY 1"#
+    )
+    .fetch_all(&mut **db)
+    .await;
+
+    let skills = sqlx::query_as::<_, (serde_json::Value,)>(
+        "SELECT COALESCE(skills, '[]'::jsonb) FROM sourced_candidates"
+    )
+    .fetch_all(&mut **db)
+    .await;
+
+    match (totals, buckets, statuses, sources, skills) {
+        (Ok((total, avg)), Ok(buckets), Ok(statuses), Ok(sources), Ok(skills)) => {
+            let skill_lists: Vec<serde_json::Value> = skills.into_iter().map(|(v,)| v).collect();
+            let stats = assemble_candidate_stats(total, avg, &buckets, statuses, sources, &skill_lists, top_n);
+            RawJson(serde_json::to_string(&stats).unwrap())
+        }
+        (Err(e), ..) | (_, Err(e), ..) | (_, _, Err(e), ..) | (_, _, _, Err(e), _) | (.., Err(e)) => {
+            RawJson(serde_json::json!({ "error": format!("Database error: {}", e) }).to_string())
+        }
+    }
 }
 
 #[allow(unused_variables)]
@@ -387,3 +1232,595 @@ pub async fn remove_candidate_from_job(job_id: &str, candidate_id: &str, mut db:
 
     RawJson(format!(r#"{{"success":true,"job_id":"{}","candidate_id":"{}"}}"#, job_id, candidate_id))
 }
+
+/// Delete a candidate and its job links in one transaction
+/// Returns false when the candidate doesn't exist
+async fn delete_candidate_rows(conn: &mut sqlx::PgConnection, candidate_uuid: uuid::Uuid) -> Result<bool, sqlx::Error> {
+    let mut tx = conn.begin().await?;
+
+    sqlx::query("DELETE FROM job_candidates WHERE candidate_id = $1")
+        .bind(candidate_uuid)
+        .execute(&mut *tx)
+        .await?;
+    let deleted = sqlx::query("DELETE FROM sourced_candidates WHERE id = $1")
+        .bind(candidate_uuid)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+    tx.commit().await?;
+    Ok(deleted > 0)
+}
+
+/// Delete a candidate with its job links
+/// Code embeddings need no cleanup here: each analysis removes its own as soon as it finishes
+#[delete("/candidates/<id>")]
+pub async fn delete_candidate(id: &str, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    let candidate_uuid = match uuid::Uuid::parse_str(id) {
+        Ok(u) => u,
+        Err(_) => return error_response(Status::BadRequest, "Invalid candidate ID"),
+    };
+
+    match delete_candidate_rows(&mut db, candidate_uuid).await {
+        Ok(true) => {}
+        Ok(false) => return error_response(Status::NotFound, "Candidate not found"),
+        Err(e) => return error_response(Status::InternalServerError, &format!("Failed to delete candidate: {}", e)),
+    }
+
+    (Status::Ok, RawJson(serde_json::json!({ "success": true, "id": candidate_uuid.to_string() }).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn profile_with_blog(blog: Option<&str>, twitter: Option<&str>) -> GitHubProfile {
+        GitHubProfile {
+            name: Some("Octo Cat".to_string()),
+            bio: None,
+            avatar_url: String::new(),
+            public_repos: 3,
+            followers: 10,
+            following: 1,
+            created_at: "2015-01-01T00:00:00Z".to_string(),
+            blog: blog.map(String::from),
+            twitter_username: twitter.map(String::from),
+            company: Some("@github".to_string()),
+            location: None,
+        }
+    }
+
+    #[test]
+    fn test_candidate_stats_from_fixtures() {
+        // Fixture pool: scores 15, 45, 55, 92 / statuses / sources
+        let buckets = vec![(0, 1), (2, 2), (4, 1)];
+        let statuses = vec![("complete".to_string(), 3), ("analyzing".to_string(), 1)];
+        let sources = vec![("linkedin".to_string(), 2), ("manual".to_string(), 2)];
+        let skills = vec![
+            serde_json::json!([{"name": "Rust", "level": "expert"}, {"name": "Go", "level": "familiar"}]),
+            serde_json::json!([{"name": "rust", "level": "advanced"}, {"name": "Python", "level": "expert"}]),
+            serde_json::json!([{"name": "Rust", "level": "beginner"}, {"name": "Python", "level": "familiar"}]),
+            serde_json::json!([]),
+        ];
+
+        let stats = assemble_candidate_stats(4, Some(42.5), &buckets, statuses, sources, &skills, 2);
+
+        assert_eq!(stats.total, 4);
+        let counts: Vec<i64> = stats.score_distribution.iter().map(|b| b.count).collect();
+        assert_eq!(counts, vec![1, 0, 2, 0, 1]);
+        assert_eq!(stats.score_distribution[4].range, "80-100");
+        assert_eq!(stats.by_analysis_status.get("complete"), Some(&3));
+        assert_eq!(stats.by_analysis_status.get("analyzing"), Some(&1));
+        assert_eq!(stats.by_source.get("linkedin"), Some(&2));
+        assert_eq!(stats.top_skills, vec![
+            SkillCount { name: "Rust".to_string(), count: 3 },
+            SkillCount { name: "Python".to_string(), count: 2 },
+        ]);
+        assert_eq!(stats.avg_ai_detection_score, Some(42.5));
+    }
+
+    #[test]
+    fn test_link_nonexistent_job() {
+        assert_eq!(check_link_targets(false, true), Err("Job not found"));
+        // Job is reported first when both are missing
+        assert_eq!(check_link_targets(false, false), Err("Job not found"));
+        let (status, body) = error_response(Status::NotFound, "Job not found");
+        assert_eq!(status, Status::NotFound);
+        assert_eq!(body.0, r#"{"error":"Job not found"}"#);
+    }
+
+    #[test]
+    fn test_link_nonexistent_candidate() {
+        assert_eq!(check_link_targets(true, false), Err("Candidate not found"));
+    }
+
+    #[test]
+    fn test_link_success() {
+        assert_eq!(check_link_targets(true, true), Ok(()));
+    }
+
+    #[test]
+    fn test_extract_gitlab_username() {
+        assert_eq!(extract_gitlab_username("https://gitlab.com/jdoe").as_deref(), Some("jdoe"));
+        assert_eq!(extract_gitlab_username("http://www.gitlab.com/jdoe/compiler/").as_deref(), Some("jdoe"));
+        assert_eq!(extract_gitlab_username("gitlab.com/users/jdoe/projects").as_deref(), Some("jdoe"));
+        assert_eq!(extract_gitlab_username(" https://gitlab.com/jdoe?tab=groups ").as_deref(), Some("jdoe"));
+        assert_eq!(extract_gitlab_username("https://gitlab.com/"), None);
+        assert_eq!(extract_gitlab_username("https://github.com/jdoe"), None);
+        assert_eq!(extract_gitlab_username("https://gitlab.example.com/jdoe"), None);
+    }
+
+    #[test]
+    fn test_enrichment_prefers_github_then_gitlab() {
+        let links = LinksInput {
+            github: Some("https://github.com/octocat".to_string()),
+            gitlab: Some("https://gitlab.com/octo".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(enrichment_target(&links), Some(EnrichmentTarget::GitHub("octocat".to_string())));
+
+        // A GitLab profile pasted into the GitHub or portfolio field is still picked up
+        let links = LinksInput::new(Some("https://gitlab.com/octo".to_string()), None, None);
+        assert_eq!(enrichment_target(&links), Some(EnrichmentTarget::GitLab("octo".to_string())));
+        let links = LinksInput::new(None, None, Some("gitlab.com/users/octo".to_string()));
+        assert_eq!(enrichment_target(&links), Some(EnrichmentTarget::GitLab("octo".to_string())));
+
+        let links = LinksInput::new(None, None, Some("https://octo.dev".to_string()));
+        assert_eq!(enrichment_target(&links), None);
+        assert_eq!(initial_analysis_status(None), "complete");
+    }
+
+    #[test]
+    fn test_merge_github_links_verifies_portfolio() {
+        let links = LinksInput {
+            portfolio: Some("https://www.octo.dev/".to_string()),
+            ..Default::default()
+        };
+        let merged = merge_github_links(&links, &profile_with_blog(Some("http://octo.dev"), Some("octo")));
+
+        assert_eq!(merged.github_blog.as_deref(), Some("http://octo.dev"));
+        assert_eq!(merged.portfolio_verified, Some(true));
+        assert_eq!(merged.twitter.as_deref(), Some("https://twitter.com/octo"));
+    }
+
+    #[test]
+    fn test_merge_github_links_mismatch_and_fill() {
+        let links = LinksInput {
+            portfolio: Some("https://someone-else.com".to_string()),
+            ..Default::default()
+        };
+        let merged = merge_github_links(&links, &profile_with_blog(Some("octo.dev"), None));
+        assert_eq!(merged.portfolio_verified, Some(false));
+
+        let merged = merge_github_links(&LinksInput::default(), &profile_with_blog(Some("octo.dev"), None));
+        assert_eq!(merged.portfolio.as_deref(), Some("octo.dev"));
+        assert_eq!(merged.portfolio_verified, None);
+
+        let merged = merge_github_links(&LinksInput::default(), &profile_with_blog(None, None));
+        assert!(merged.github_blog.is_none());
+        assert!(merged.twitter.is_none());
+    }
+
+    #[test]
+    fn test_stored_github_stats_parsed() {
+        let stored = serde_json::json!({
+            "username": "octocat",
+            "profile": {
+                "name": "Octo Cat", "bio": null, "avatar_url": "https://avatars.example/octocat",
+                "public_repos": 8, "followers": 100, "following": 1,
+                "created_at": "2011-01-25T18:44:36Z", "company": "@github"
+            },
+            "repositories": [],
+            "ai_analysis": {
+                "ai_detection_score": 12.5, "ai_proficiency_score": 40.0, "code_authenticity_score": 88.0,
+                "analysis_details": {"patterns_detected": [], "confidence": 0.7, "reasoning": "Mostly hand-written"}
+            },
+            "languages": {"Rust": 3},
+            "analyzed_at": "2024-01-01T00:00:00Z"
+        });
+
+        let stats = parse_stored_github_stats(Some(stored)).ok().unwrap();
+        assert_eq!(stats.username, "octocat");
+        assert_eq!(stats.profile.company.as_deref(), Some("@github"));
+        assert_eq!(stats.languages.get("Rust"), Some(&3));
+
+        assert_eq!(parse_stored_github_stats(None).err().unwrap().0, Status::NotFound);
+        assert_eq!(
+            parse_stored_github_stats(Some(serde_json::json!({"username": "x"}))).err().unwrap().0,
+            Status::InternalServerError
+        );
+    }
+
+    #[test]
+    fn test_candidate_list_row_omits_github_stats() {
+        let row = JobCandidateRow {
+            id: "jc".to_string(),
+            candidate: CandidateRow {
+                id: "c".to_string(),
+                name: "Jane".to_string(),
+                analysis_status: "complete".to_string(),
+                ..CandidateRow::default()
+            },
+            job_match_score: 80,
+            score_breakdown: None,
+            team_compatibility_score: 70,
+            added_at: "2024-01-01".to_string(),
+        };
+
+        let json = serde_json::to_value(&row).unwrap();
+        assert!(json["candidate"].get("github_stats").is_none());
+        assert!(json["candidate"].get("analysis_metadata").is_some());
+    }
+
+    #[test]
+    fn test_old_analysis_flagged_stale() {
+        let now = chrono::DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z").unwrap().with_timezone(&chrono::Utc);
+        let candidate = |analyzed_at: &str| CandidateRow {
+            analysis_status: "complete".to_string(),
+            analyzed_at: Some(analyzed_at.to_string()),
+            ..CandidateRow::default()
+        };
+
+        let mut old = candidate("2024-01-15T09:30:00+00:00");
+        let mut recent = candidate("2025-05-28T09:30:00+00:00");
+        assert!(!flag_stale_analysis(&mut old, &FreshnessPolicy::default(), now));
+        assert!(!flag_stale_analysis(&mut recent, &FreshnessPolicy::default(), now));
+        assert!(old.analysis_stale);
+        assert!(!recent.analysis_stale);
+
+        // Only stale candidates are re-queued, and only when auto-refresh is on
+        let auto = FreshnessPolicy { auto_refresh: true, ..FreshnessPolicy::default() };
+        assert!(flag_stale_analysis(&mut old, &auto, now));
+        assert!(!flag_stale_analysis(&mut recent, &auto, now));
+        assert_eq!(serde_json::to_value(&old).unwrap()["analysis_stale"], true);
+
+        // A failed analysis isn't retried on every listing
+        let mut failed = CandidateRow { analysis_status: "failed".to_string(), ..candidate("2024-01-15T09:30:00+00:00") };
+        assert!(!flag_stale_analysis(&mut failed, &auto, now));
+        assert!(failed.analysis_stale);
+
+        // An old analysis confirmed unchanged since is fresh
+        let mut confirmed = CandidateRow { checked_at: Some(now - chrono::Duration::days(1)), ..candidate("2024-01-15T09:30:00+00:00") };
+        assert!(!flag_stale_analysis(&mut confirmed, &auto, now));
+        assert!(!confirmed.analysis_stale);
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_cached_reanalysis_clears_stale_flag() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        // The analysis cache hands back the same stats, still carrying their original analyzed_at
+        let cached: GitHubStats = serde_json::from_value(serde_json::json!({
+            "username": "octocat",
+            "profile": {
+                "name": "Octo Cat", "bio": null, "avatar_url": "", "public_repos": 8,
+                "followers": 100, "following": 1, "created_at": "2011-01-25T18:44:36Z"
+            },
+            "repositories": [],
+            "ai_analysis": {
+                "ai_detection_score": 12.5, "ai_proficiency_score": 40.0, "code_authenticity_score": 88.0,
+                "analysis_details": {"patterns_detected": [], "confidence": 0.7, "reasoning": "Mostly hand-written"}
+            },
+            "languages": {},
+            "analyzed_at": "2024-01-01T00:00:00Z"
+        })).unwrap();
+
+        let candidate_id = uuid::Uuid::new_v4();
+        sqlx::query("INSERT INTO sourced_candidates (id, name, title, github_stats, analysis_status) VALUES ($1, 'Octo Cat', 'Engineer', $2, 'analyzing')")
+            .bind(candidate_id)
+            .bind(serde_json::to_value(&cached).unwrap())
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let select = format!("SELECT {} FROM sourced_candidates sc WHERE sc.id = '{}'", CANDIDATE_COLUMNS, candidate_id);
+        let auto = FreshnessPolicy { auto_refresh: true, ..FreshnessPolicy::default() };
+
+        let mut before = candidate_from_row(&sqlx::query(&select).fetch_one(&mut *tx).await.unwrap());
+        before.analysis_status = "complete".to_string();
+        assert!(flag_stale_analysis(&mut before, &auto, chrono::Utc::now()));
+
+        store_github_enrichment(&mut *tx, candidate_id, &cached, None, &LinksInput::default()).await.unwrap();
+
+        let mut after = candidate_from_row(&sqlx::query(&select).fetch_one(&mut *tx).await.unwrap());
+        assert_eq!(after.analyzed_at.as_deref(), Some("2024-01-01T00:00:00Z"));
+        assert!(!flag_stale_analysis(&mut after, &auto, chrono::Utc::now()));
+        assert!(!after.analysis_stale);
+
+        tx.rollback().await.unwrap();
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_link_candidate_checks_both_rows() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let (candidate_id, job_id) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+        sqlx::query("INSERT INTO sourced_candidates (id, name, title) VALUES ($1, 'Octo Cat', 'Engineer')")
+            .bind(candidate_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO jobs (id, title) VALUES ($1, 'Backend Engineer')")
+            .bind(job_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let missing_job = link_candidate(&mut tx, uuid::Uuid::new_v4(), uuid::Uuid::new_v4(), candidate_id, 80, 70).await;
+        let (status, body) = missing_job.unwrap_err();
+        assert_eq!(status, Status::NotFound);
+        assert_eq!(body.0, r#"{"error":"Job not found"}"#);
+
+        let missing_candidate = link_candidate(&mut tx, uuid::Uuid::new_v4(), job_id, uuid::Uuid::new_v4(), 80, 70).await;
+        let (status, body) = missing_candidate.unwrap_err();
+        assert_eq!(status, Status::NotFound);
+        assert_eq!(body.0, r#"{"error":"Candidate not found"}"#);
+
+        let (links,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM job_candidates WHERE job_id = $1 OR candidate_id = $2")
+            .bind(job_id)
+            .bind(candidate_id)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(links, 0);
+
+        // Linking twice updates the scores of the one link
+        link_candidate(&mut tx, uuid::Uuid::new_v4(), job_id, candidate_id, 80, 70).await.unwrap();
+        link_candidate(&mut tx, uuid::Uuid::new_v4(), job_id, candidate_id, 90, 60).await.unwrap();
+        let scores: Vec<(i32, i32)> = sqlx::query_as("SELECT job_match_score, team_compatibility_score FROM job_candidates WHERE job_id = $1 AND candidate_id = $2")
+            .bind(job_id)
+            .bind(candidate_id)
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(scores, vec![(90, 60)]);
+
+        tx.rollback().await.unwrap();
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_delete_candidate_removes_job_links() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let count = |table: &'static str, column: &'static str, id: uuid::Uuid| {
+            format!("SELECT COUNT(*) FROM {} WHERE {} = '{}'", table, column, id)
+        };
+        let (candidate_id, job_id) = (uuid::Uuid::new_v4(), uuid::Uuid::new_v4());
+        sqlx::query("INSERT INTO sourced_candidates (id, name, title, links) VALUES ($1, 'Octo Cat', 'Engineer', $2)")
+            .bind(candidate_id)
+            .bind(serde_json::json!({"github": "https://github.com/octocat"}))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO jobs (id, title) VALUES ($1, 'Backend Engineer')")
+            .bind(job_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO job_candidates (id, job_id, candidate_id) VALUES ($1, $2, $3)")
+            .bind(uuid::Uuid::new_v4())
+            .bind(job_id)
+            .bind(candidate_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let (before,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sourced_candidates").fetch_one(&mut *tx).await.unwrap();
+        let deleted = delete_candidate_rows(&mut tx, candidate_id).await.unwrap();
+        assert!(deleted);
+
+        let (after,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM sourced_candidates").fetch_one(&mut *tx).await.unwrap();
+        assert_eq!(after, before - 1);
+        let (links,): (i64,) = sqlx::query_as(&count("job_candidates", "candidate_id", candidate_id)).fetch_one(&mut *tx).await.unwrap();
+        assert_eq!(links, 0);
+        let (jobs,): (i64,) = sqlx::query_as(&count("jobs", "id", job_id)).fetch_one(&mut *tx).await.unwrap();
+        assert_eq!(jobs, 1);
+
+        // A second delete finds nothing
+        assert!(!delete_candidate_rows(&mut tx, candidate_id).await.unwrap());
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[test]
+    fn test_candidate_search_params() {
+        let search = CandidateSearch::new(Some("  100%_rust "), Some(" "), Some(500), None);
+        assert_eq!(search.pattern.as_deref(), Some("%100\\%\\_rust%"));
+        assert_eq!(search.skill, None);
+        assert_eq!(search.limit, MAX_CANDIDATE_PAGE_LIMIT);
+        assert_eq!(search.offset, 0);
+
+        let search = CandidateSearch::new(None, Some("Rust"), Some(0), Some(20));
+        assert_eq!(search.pattern, None);
+        assert_eq!(search.skill.as_deref(), Some("Rust"));
+        assert_eq!(search.limit, 1);
+    }
+
+    /// Insert a candidate for the search tests
+    async fn insert_candidate(conn: &mut sqlx::PgConnection, name: &str, title: &str, skills: serde_json::Value) {
+        sqlx::query("INSERT INTO sourced_candidates (id, name, title, skills) VALUES ($1, $2, $3, $4)")
+            .bind(uuid::Uuid::new_v4())
+            .bind(name)
+            .bind(title)
+            .bind(skills)
+            .execute(conn)
+            .await
+            .unwrap();
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_candidate_text_search() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        insert_candidate(&mut tx, "Ada Zqxlovelace", "Backend Engineer", serde_json::json!([])).await;
+        insert_candidate(&mut tx, "Grace Hopper", "Zqxlovelace Fan Club Lead", serde_json::json!([])).await;
+        insert_candidate(&mut tx, "Alan Turing", "Researcher", serde_json::json!([])).await;
+
+        // Name or title, case-insensitive
+        let page = search_candidate_rows(&mut tx, &CandidateSearch::new(Some("zQXlovelace"), None, None, None)).await.unwrap();
+        let mut names: Vec<&str> = page.candidates.iter().map(|c| c.name.as_str()).collect();
+        names.sort();
+        assert_eq!(names, vec!["Ada Zqxlovelace", "Grace Hopper"]);
+        assert_eq!(page.total, 2);
+        assert!(!page.has_more);
+
+        // Pages through the matches
+        let first = search_candidate_rows(&mut tx, &CandidateSearch::new(Some("zqxlovelace"), None, Some(1), None)).await.unwrap();
+        assert_eq!(first.candidates.len(), 1);
+        assert!(first.has_more);
+        let second = search_candidate_rows(&mut tx, &CandidateSearch::new(Some("zqxlovelace"), None, Some(1), Some(1))).await.unwrap();
+        assert_eq!(second.candidates.len(), 1);
+        assert_ne!(first.candidates[0].id, second.candidates[0].id);
+        assert!(!second.has_more);
+
+        tx.rollback().await.unwrap();
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_candidate_skill_filter() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        insert_candidate(&mut tx, "Zqx Rustacean", "Engineer", serde_json::json!([{"name": "Rust", "level": "expert"}])).await;
+        insert_candidate(&mut tx, "Zqx Gopher", "Engineer", serde_json::json!([{"name": "Go", "level": "expert"}])).await;
+        insert_candidate(&mut tx, "Zqx Trusty", "Engineer", serde_json::json!([{"name": "Trusted Systems", "level": "advanced"}])).await;
+
+        let page = search_candidate_rows(&mut tx, &CandidateSearch::new(Some("zqx"), Some("rUsT"), None, None)).await.unwrap();
+        let names: Vec<&str> = page.candidates.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Zqx Rustacean"]);
+        assert_eq!(page.candidates[0].skills[0].name, "Rust");
+        assert_eq!(page.total, 1);
+
+        let none = search_candidate_rows(&mut tx, &CandidateSearch::new(Some("zqx"), Some("Haskell"), None, None)).await.unwrap();
+        assert!(none.candidates.is_empty());
+        assert_eq!(none.total, 0);
+
+        tx.rollback().await.unwrap();
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_candidate_stage_filter() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        insert_candidate(&mut tx, "Zqx Sourced", "Engineer", serde_json::json!([])).await;
+        insert_candidate(&mut tx, "Zqx Screened", "Engineer", serde_json::json!([])).await;
+        sqlx::query("UPDATE sourced_candidates SET pipeline_stage = 'screened' WHERE name = 'Zqx Screened'")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let search = |stage: &str| CandidateSearch {
+            stage: parse_stage_filter(Some(stage)).unwrap().map(|s| s.name()),
+            ..CandidateSearch::new(Some("zqx"), None, None, None)
+        };
+        let screened = search_candidate_rows(&mut tx, &search("Screened")).await.unwrap();
+        let names: Vec<&str> = screened.candidates.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Zqx Screened"]);
+        assert_eq!(screened.total, 1);
+
+        // No stored stage counts as sourced
+        let sourced = search_candidate_rows(&mut tx, &search("sourced")).await.unwrap();
+        let names: Vec<&str> = sourced.candidates.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, vec!["Zqx Sourced"]);
+
+        tx.rollback().await.unwrap();
+    }
+
+    /// A create request as the frontend sends it
+    fn create_request(github: Option<&str>) -> CreateCandidate {
+        serde_json::from_value(serde_json::json!({
+            "name": "Zqx Poller",
+            "title": "Engineer",
+            "skills": [],
+            "experience": [],
+            "education": [],
+            "links": {"github": github, "linkedin": null, "portfolio": null},
+            "talent_fit_score": 0,
+            "score_breakdown": {"skillsMatch": 0, "experienceMatch": 0, "workStyleAlignment": 0, "teamFit": 0},
+            "resume_file_name": null,
+            "source": "manual"
+        }))
+        .unwrap()
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_new_candidate_analysis_status() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let mut create = async |github: Option<&str>| {
+            let data = create_request(github);
+            let id = uuid::Uuid::new_v4();
+            let target = enrichment_target(&data.links);
+            insert_candidate_row(&mut tx, id, &data, initial_analysis_status(target.as_ref())).await.unwrap();
+            fetch_analysis_status(&mut tx, id).await.unwrap().unwrap()
+        };
+
+        let without_github = create(None).await;
+        assert_eq!(without_github, AnalysisStatus {
+            status: "complete".to_string(),
+            ai_detection_score: None,
+            developer_profile_ready: false,
+            reason: None,
+        });
+
+        let with_github = create(Some("https://github.com/octocat")).await;
+        assert_eq!(with_github.status, "analyzing");
+        assert!(!with_github.developer_profile_ready);
+
+        // A failure carries its reason
+        let failed = uuid::Uuid::new_v4();
+        insert_candidate_row(&mut tx, failed, &create_request(None), "complete").await.unwrap();
+        sqlx::query("UPDATE sourced_candidates SET analysis_status = 'failed', analysis_error = 'GitHub user not found' WHERE id = $1")
+            .bind(failed)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let status = fetch_analysis_status(&mut tx, failed).await.unwrap().unwrap();
+        assert_eq!(status.reason.as_deref(), Some("GitHub user not found"));
+        assert!(serde_json::to_value(&status).unwrap().get("reason").is_some());
+
+        assert_eq!(fetch_analysis_status(&mut tx, uuid::Uuid::new_v4()).await.unwrap(), None);
+
+        tx.rollback().await.unwrap();
+    }
+}
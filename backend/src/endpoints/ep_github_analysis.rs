@@ -1,114 +1,366 @@
 use rocket::{get, post};
 use rocket::response::content::RawJson;
+use rocket::serde::json::Json;
+use rocket_db_pools::Connection;
+use serde::Deserialize;
+use sqlx::Row;
+use std::collections::HashMap;
 
-use crate::github::analyze::{analyze_github_user, analyze_github_user_deep};
-use crate::github::ai_summary::generate_developer_profile;
+use crate::db::MainDatabase;
+use crate::endpoints::api_error::{
+    err_json, DATABASE_ERROR, INTERNAL_ERROR, INVALID_REQUEST, NOT_CONFIGURED, NOT_FOUND, NOT_PERMITTED, UPSTREAM_ERROR,
+};
+use crate::github::analyze::{
+    analyze_github_user_deep, analyze_github_user_with_depth, get_or_analyze, get_or_analyze_deep, AnalysisLimitOverrides,
+    AnalysisLimits, DepthProfile,
+};
+use crate::github::ai_summary::{cached_developer_profile, ProfileStyle};
+use crate::github::api::get_all_user_repos;
+use crate::github::blocklist::{analysis_blocklist, AnalysisBlocklist, AnalysisNotPermitted};
+use crate::github::repos::{paginate_repositories, RepoSort};
+use crate::github::semantic_search::search_category_infos;
+use crate::github::stats::RepositoryInfo;
+use crate::github::stats_cache::analysis_cache_ttl;
+use crate::matching::skills::{calculate_skill_score, github_language_level, match_tuning};
+use crate::matching::{parse_required_skills, CandidateSkill};
+
+/// List the semantic-search categories used for deep profiles
+#[get("/github/search-categories")]
+pub fn get_search_categories() -> RawJson<String> {
+    RawJson(serde_json::json!({ "categories": search_category_infos() }).to_string())
+}
+
+/// Error body for a failed analysis; a blocklisted account gets the refusal on its own
+fn analysis_failed(label: &str, e: Box<dyn std::error::Error + Send + Sync>) -> RawJson<String> {
+    if e.is::<AnalysisNotPermitted>() {
+        return err_json(NOT_PERMITTED, e.to_string());
+    }
+    err_json(UPSTREAM_ERROR, format!("{} failed: {}", label, e))
+}
+
+/// The not_permitted body when the account is blocklisted, for endpoints that call GitHub directly
+pub(crate) fn check_not_blocked(blocklist: &AnalysisBlocklist, username: &str) -> Result<(), RawJson<String>> {
+    if blocklist.is_blocked(username) {
+        println!("[Blocklist] Refusing to analyze {}", username);
+        return Err(err_json(NOT_PERMITTED, AnalysisNotPermitted.to_string()));
+    }
+    Ok(())
+}
+
+/// Analyze a GitHub user and return full stats with AI analysis
+/// `depth` is one of quick (default), standard or thorough
+#[post("/github/analyze/<username>?<depth>")]
+pub async fn analyze_github(
+    username: &str,
+    depth: Option<&str>,
+    mut db: Connection<MainDatabase>,
+) -> RawJson<String> {
+    let depth = match depth {
+        None => DepthProfile::Quick,
+        Some(value) => match DepthProfile::parse(value) {
+            Some(d) => d,
+            None => {
+                return err_json(INVALID_REQUEST, "Invalid depth, expected quick, standard or thorough");
+            }
+        },
+    };
 
-/// Analyze a GitHub user and return full stats with AI analysis (basic mode)
-#[post("/github/analyze/<username>")]
-pub async fn analyze_github(username: &str) -> RawJson<String> {
     let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
 
     if token.is_empty() {
-        return RawJson(r#"{"error": "GitHub token not configured"}"#.to_string());
+        return err_json(NOT_CONFIGURED, "GitHub token not configured");
     }
 
-    match analyze_github_user(username, &token).await {
+    match analyze_github_user_with_depth(&mut db, username, &token, depth).await {
         Ok(stats) => {
-            RawJson(serde_json::to_string(&stats).unwrap_or_else(|_| {
-                r#"{"error": "Failed to serialize response"}"#.to_string()
-            }))
+            serde_json::to_string(&stats)
+                .map(RawJson)
+                .unwrap_or_else(|_| err_json(INTERNAL_ERROR, "Failed to serialize response"))
         }
         Err(e) => {
-            RawJson(format!(r#"{{"error": "Analysis failed: {}"}}"#, e))
+            analysis_failed("Analysis", e)
         }
     }
 }
 
 /// Deep analyze a GitHub user with code excerpts
-#[post("/github/analyze/<username>/deep")]
+/// An optional JSON body overrides the sampling limits (clamped to hard ceilings)
+#[post("/github/analyze/<username>/deep", data = "<limits>")]
 pub async fn analyze_github_deep(
     username: &str,
+    limits: Option<Json<AnalysisLimitOverrides>>,
+    mut db: Connection<MainDatabase>,
 ) -> RawJson<String> {
     let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
 
     if token.is_empty() {
-        return RawJson(r#"{"error": "GitHub token not configured"}"#.to_string());
+        return err_json(NOT_CONFIGURED, "GitHub token not configured");
     }
 
-    match analyze_github_user_deep(username, &token).await {
+    let limits = match limits {
+        Some(overrides) => AnalysisLimits::default().with_overrides(&overrides),
+        None => AnalysisLimits::default(),
+    };
+
+    match analyze_github_user_deep(Some(&mut **db), username, &token, &limits).await {
         Ok(stats) => {
-            RawJson(serde_json::to_string(&stats).unwrap_or_else(|_| {
-                r#"{"error": "Failed to serialize response"}"#.to_string()
-            }))
+            serde_json::to_string(&stats)
+                .map(RawJson)
+                .unwrap_or_else(|_| err_json(INTERNAL_ERROR, "Failed to serialize response"))
         }
         Err(e) => {
-            RawJson(format!(r#"{{"error": "Deep analysis failed: {}"}}"#, e))
+            analysis_failed("Deep analysis", e)
         }
     }
 }
 
+/// Parse the optional `style` query parameter (brief, narrative or formal)
+fn parse_profile_style(style: Option<&str>) -> Result<ProfileStyle, RawJson<String>> {
+    match style {
+        None => Ok(ProfileStyle::default()),
+        Some(value) => ProfileStyle::parse(value).ok_or_else(|| {
+            err_json(INVALID_REQUEST, "Invalid style, expected brief, narrative or formal")
+        }),
+    }
+}
+
 /// Get AI-generated developer profile (coding style, personality, quirks)
-#[get("/github/profile/<username>")]
-pub async fn get_github_profile(username: &str) -> RawJson<String> {
+/// `style` is one of brief, narrative (default) or formal
+#[get("/github/profile/<username>?<style>")]
+pub async fn get_github_profile(
+    username: &str,
+    style: Option<&str>,
+    mut db: Connection<MainDatabase>,
+) -> RawJson<String> {
+    let style = match parse_profile_style(style) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
     let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
 
     if token.is_empty() {
-        return RawJson(r#"{"error": "GitHub token not configured"}"#.to_string());
+        return err_json(NOT_CONFIGURED, "GitHub token not configured");
     }
 
-    // First get the stats
-    let stats = match analyze_github_user(username, &token).await {
+    // First get the stats (cached until the TTL passes and the user's repos change)
+    let stats = match get_or_analyze(&mut db, username, &token, analysis_cache_ttl()).await {
         Ok(s) => s,
         Err(e) => {
-            return RawJson(format!(r#"{{"error": "Analysis failed: {}"}}"#, e));
+            return analysis_failed("Analysis", e);
         }
     };
 
-    // Then generate developer profile
-    match generate_developer_profile(&stats).await {
-        Ok(profile) => {
+    // Then generate developer profile (reused while the stats are unchanged)
+    match cached_developer_profile(&stats, style, None).await {
+        Ok(cached) => {
             RawJson(serde_json::json!({
                 "username": username,
-                "profile": profile
+                "style": style.name(),
+                "profile": cached.profile
             }).to_string())
         }
         Err(e) => {
-            RawJson(format!(r#"{{"error": "Profile generation failed: {}"}}"#, e))
+            err_json(UPSTREAM_ERROR, format!("Profile generation failed: {}", e))
         }
     }
 }
 
 /// Get deep AI-generated developer profile with code excerpts
-#[get("/github/profile/<username>/deep")]
+#[get("/github/profile/<username>/deep?<style>")]
 pub async fn get_github_profile_deep(
     username: &str,
+    style: Option<&str>,
+    mut db: Connection<MainDatabase>,
 ) -> RawJson<String> {
+    let style = match parse_profile_style(style) {
+        Ok(s) => s,
+        Err(e) => return e,
+    };
+
     let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
 
     if token.is_empty() {
-        return RawJson(r#"{"error": "GitHub token not configured"}"#.to_string());
+        return err_json(NOT_CONFIGURED, "GitHub token not configured");
     }
 
-    // Get deep stats with code excerpts
-    let stats = match analyze_github_user_deep(username, &token).await {
+    // Get deep stats with code excerpts (cached like the quick profile)
+    let stats = match get_or_analyze_deep(&mut db, username, &token, analysis_cache_ttl()).await {
         Ok(s) => s,
         Err(e) => {
-            return RawJson(format!(r#"{{"error": "Deep analysis failed: {}"}}"#, e));
+            return analysis_failed("Deep analysis", e);
         }
     };
 
-    // Generate developer profile with code excerpts
-    match generate_developer_profile(&stats).await {
-        Ok(profile) => {
+    // Generate developer profile with code excerpts (reused while the stats are unchanged)
+    match cached_developer_profile(&stats, style, None).await {
+        Ok(cached) => {
             RawJson(serde_json::json!({
                 "username": username,
-                "profile": profile,
+                "style": style.name(),
+                "profile": cached.profile,
                 "analysis_metadata": stats.analysis_metadata
             }).to_string())
         }
         Err(e) => {
-            RawJson(format!(r#"{{"error": "Profile generation failed: {}"}}"#, e))
+            err_json(UPSTREAM_ERROR, format!("Profile generation failed: {}", e))
+        }
+    }
+}
+
+/// Browse all of a user's repositories, sorted and paginated
+/// `sort` is stars (default) or recent; ranked below `/github/profile/<username>` so the two don't collide
+#[get("/github/<username>/repos?<limit>&<offset>&<sort>", rank = 2)]
+pub async fn get_github_repos(
+    username: &str,
+    limit: Option<usize>,
+    offset: Option<usize>,
+    sort: Option<&str>,
+) -> RawJson<String> {
+    if let Err(refused) = check_not_blocked(analysis_blocklist(), username) {
+        return refused;
+    }
+
+    let sort = match sort {
+        None => RepoSort::Stars,
+        Some(value) => match RepoSort::parse(value) {
+            Some(s) => s,
+            None => return err_json(INVALID_REQUEST, "Invalid sort, expected stars or recent"),
+        },
+    };
+
+    let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
+
+    if token.is_empty() {
+        return err_json(NOT_CONFIGURED, "GitHub token not configured");
+    }
+
+    match get_all_user_repos(username, &token).await {
+        Ok(repos) => {
+            let repos: Vec<RepositoryInfo> = repos.iter().map(RepositoryInfo::from).collect();
+            let page = paginate_repositories(repos, sort, limit, offset);
+            serde_json::to_string(&page)
+                .map(RawJson)
+                .unwrap_or_else(|_| err_json(INTERNAL_ERROR, "Failed to serialize response"))
+        }
+        Err(e) => {
+            err_json(UPSTREAM_ERROR, format!("Failed to fetch repositories: {}", e))
         }
     }
 }
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct GitHubMatchRequest {
+    username: String,
+}
+
+/// Candidate skills from a GitHub languages breakdown (percent of repos), most used first
+/// Languages rounded down to 0% are left out
+fn skills_from_languages(languages: &HashMap<String, u32>) -> Vec<CandidateSkill> {
+    let mut entries: Vec<(&String, u32)> = languages
+        .iter()
+        .map(|(language, percentage)| (language, *percentage))
+        .filter(|(_, percentage)| *percentage > 0)
+        .collect();
+    entries.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+
+    entries
+        .into_iter()
+        .map(|(language, percentage)| CandidateSkill {
+            name: language.clone(),
+            level: github_language_level(percentage).to_string(),
+        })
+        .collect()
+}
+
+/// Score a GitHub user's languages against a job's required skills, without sourcing them
+#[post("/jobs/<job_id>/github-match", data = "<data>")]
+pub async fn github_job_match(
+    job_id: &str,
+    data: Json<GitHubMatchRequest>,
+    mut db: Connection<MainDatabase>,
+) -> RawJson<String> {
+    let Ok(job_uuid) = uuid::Uuid::parse_str(job_id) else {
+        return err_json(INVALID_REQUEST, "Invalid job ID");
+    };
+    let username = data.username.trim();
+    if username.is_empty() {
+        return err_json(INVALID_REQUEST, "username is required");
+    }
+
+    let required_skills = match sqlx::query("SELECT required_skills FROM jobs WHERE id = $1")
+        .bind(job_uuid)
+        .fetch_optional(&mut **db)
+        .await
+    {
+        Ok(Some(row)) => parse_required_skills(&row.get::<Option<serde_json::Value>, _>("required_skills").unwrap_or_default()),
+        Ok(None) => return err_json(NOT_FOUND, "Job not found"),
+        Err(e) => return err_json(DATABASE_ERROR, format!("Database error: {}", e)),
+    };
+
+    let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
+
+    if token.is_empty() {
+        return err_json(NOT_CONFIGURED, "GitHub token not configured");
+    }
+
+    // Shares the cached quick analysis with the other GitHub endpoints
+    let stats = match get_or_analyze(&mut db, username, &token, analysis_cache_ttl()).await {
+        Ok(stats) => stats,
+        Err(e) => return analysis_failed("Analysis", e),
+    };
+
+    let skills = skills_from_languages(&stats.languages);
+    let skill_score = calculate_skill_score(&skills, &required_skills, match_tuning());
+
+    RawJson(serde_json::json!({
+        "job_id": job_id,
+        "username": stats.username,
+        "skill_score": skill_score,
+        "skills": skills,
+        "languages": stats.languages,
+    }).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_categories_lists_builtins() {
+        let response: serde_json::Value = serde_json::from_str(&get_search_categories().0).unwrap();
+        let categories = response["categories"].as_array().unwrap();
+        let names: Vec<&str> = categories.iter().filter_map(|c| c["name"].as_str()).collect();
+
+        for expected in [
+            "error_handling", "naming_style", "comments", "testing", "async_patterns",
+            "validation", "logging", "configuration", "class_structure", "functional_patterns",
+        ] {
+            assert!(names.contains(&expected), "missing {}", expected);
+        }
+        assert!(categories.iter().all(|c| c["query"].is_string() && c["description"].is_string()));
+    }
+
+    #[test]
+    fn test_blocked_account_refused() {
+        let blocklist = AnalysisBlocklist::parse("acme-internal");
+
+        let body: serde_json::Value = serde_json::from_str(&check_not_blocked(&blocklist, "ACME-Internal").unwrap_err().0).unwrap();
+        assert_eq!(body["code"], NOT_PERMITTED);
+        assert_eq!(body["error"], "Analysis not permitted for this account");
+        assert!(check_not_blocked(&blocklist, "octocat").is_ok());
+    }
+
+    #[test]
+    fn test_skills_from_languages_levels() {
+        let languages = HashMap::from([
+            ("Python".to_string(), 40),
+            ("Rust".to_string(), 60),
+            ("Shell".to_string(), 0),
+        ]);
+        let skills = skills_from_languages(&languages);
+        let pairs: Vec<(&str, &str)> = skills.iter().map(|s| (s.name.as_str(), s.level.as_str())).collect();
+        assert_eq!(pairs, vec![("Rust", "expert"), ("Python", "advanced")]);
+    }
+}
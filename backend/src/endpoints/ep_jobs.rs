@@ -3,34 +3,15 @@ use rocket_db_pools::Connection;
 use rocket::response::content::RawJson;
 use serde::{Deserialize, Serialize};
 use crate::db::MainDatabase;
-use crate::matching::RequiredSkill;
+use crate::endpoints::api_error::{err_json, DATABASE_ERROR, INVALID_REQUEST, NOT_FOUND};
+use crate::matching::{RequiredSkill, ScoreWeights, parse_required_skills, parse_required_skills_with_warnings};
+use crate::matching::location::LocationRequirement;
+use crate::matching::team_fit::TeamFitWeights;
+use crate::matching::skill_matrix::{build_skill_matrix, MatrixCandidate};
+use crate::matching::skills::match_tuning;
 use sqlx::Row;
 
-/// Parse required_skills from JSONB - supports both legacy and enhanced formats
-/// Legacy: ["Python", "React"]
-/// Enhanced: [{"name": "Python", "level": "advanced", "mandatory": true}]
-fn parse_required_skills(json_value: &serde_json::Value) -> Vec<RequiredSkill> {
-    match json_value.as_array() {
-        Some(arr) => arr.iter().filter_map(|item| {
-            if let Some(s) = item.as_str() {
-                // Legacy format: just a string
-                Some(RequiredSkill {
-                    name: s.to_string(),
-                    level: Some("intermediate".to_string()),
-                    mandatory: Some(true),
-                })
-            } else if item.is_object() {
-                // Enhanced format: object with name, level, mandatory
-                serde_json::from_value(item.clone()).ok()
-            } else {
-                None
-            }
-        }).collect(),
-        None => vec![],
-    }
-}
-
-#[derive(Deserialize)]
+#[derive(Deserialize, Clone)]
 #[serde(crate = "rocket::serde")]
 pub struct CreateJob {
     title: String,
@@ -38,6 +19,14 @@ pub struct CreateJob {
     location: Option<String>,
     required_skills: serde_json::Value,  // Accept both formats
     experience_level: String,
+    #[serde(default)]
+    team_fit_weights: Option<TeamFitWeights>,
+    /// On-site / hybrid city or remote; None leaves location out of scoring
+    #[serde(default)]
+    location_requirement: Option<LocationRequirement>,
+    /// Component weights for this job's scoring; None uses the defaults
+    #[serde(default)]
+    score_weights: Option<ScoreWeights>,
 }
 
 #[derive(Deserialize)]
@@ -50,6 +39,50 @@ pub struct UpdateJob {
     experience_level: Option<String>,
     status: Option<String>,
     team_id: Option<String>,
+    team_fit_weights: Option<TeamFitWeights>,
+    /// Missing leaves the requirement as is; `null` clears it
+    #[serde(default, deserialize_with = "present")]
+    location_requirement: Option<Option<LocationRequirement>>,
+    score_weights: Option<ScoreWeights>,
+}
+
+/// Some(value) for a field that was sent, even as `null`, so a missing field can be told apart from a cleared one
+fn present<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    T::deserialize(deserializer).map(Some)
+}
+
+/// Optional field overrides applied when cloning a job
+#[derive(Deserialize, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct CloneJob {
+    title: Option<String>,
+    description: Option<String>,
+    location: Option<String>,
+    required_skills: Option<serde_json::Value>,
+    experience_level: Option<String>,
+    team_fit_weights: Option<TeamFitWeights>,
+    location_requirement: Option<LocationRequirement>,
+    score_weights: Option<ScoreWeights>,
+}
+
+impl CloneJob {
+    /// Merge overrides onto the source job's fields
+    fn apply_to(self, source: CreateJob) -> CreateJob {
+        CreateJob {
+            title: self.title.unwrap_or(source.title),
+            description: self.description.or(source.description),
+            location: self.location.or(source.location),
+            required_skills: self.required_skills.unwrap_or(source.required_skills),
+            experience_level: self.experience_level.unwrap_or(source.experience_level),
+            team_fit_weights: self.team_fit_weights.or(source.team_fit_weights),
+            location_requirement: self.location_requirement.or(source.location_requirement),
+            score_weights: self.score_weights.or(source.score_weights),
+        }
+    }
 }
 
 #[derive(Serialize)]
@@ -60,6 +93,9 @@ struct JobRow {
     location: Option<String>,
     required_skills: Vec<RequiredSkill>,  // Always return enhanced format
     experience_level: String,
+    team_fit_weights: TeamFitWeights,
+    location_requirement: Option<LocationRequirement>,
+    score_weights: Option<ScoreWeights>,
     status: String,
     team_id: Option<String>,
     candidate_ids: Vec<String>,
@@ -67,10 +103,23 @@ struct JobRow {
     updated_at: String,
 }
 
+/// Parse a job ID from the path, or the error body to return for a malformed one
+fn parse_job_id(id: &str) -> Result<uuid::Uuid, RawJson<String>> {
+    uuid::Uuid::parse_str(id).map_err(|_| err_json(INVALID_REQUEST, "Invalid job ID"))
+}
+
+fn job_not_found() -> RawJson<String> {
+    err_json(NOT_FOUND, "Job not found")
+}
+
+fn database_error(e: sqlx::Error) -> RawJson<String> {
+    err_json(DATABASE_ERROR, format!("Database error: {}", e))
+}
+
 #[get("/jobs")]
 pub async fn get_jobs(mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let rows = sqlx::query(
-        r#"SELECT j.id, j.title, j.description, j.location, j.required_skills, j.experience_level, j.status, j.team_id, j.created_at, j.updated_at,
+    let rows = match sqlx::query(
+        r#"SELECT j.id, j.title, j.description, j.location, j.required_skills, j.experience_level, j.team_fit_weights, j.location_requirement, j.score_weights, j.status, j.team_id, j.created_at, j.updated_at,
                   COALESCE(ARRAY_AGG(jc.candidate_id) FILTER (WHERE jc.candidate_id IS NOT NULL), '{}') as candidate_ids
            FROM jobs j
            LEFT JOIN job_candidates jc ON j.id = jc.job_id
@@ -78,8 +127,10 @@ pub async fn get_jobs(mut db: Connection<MainDatabase>) -> RawJson<String> {
            ORDER BY j.created_at DESC"#
     )
     .fetch_all(&mut **db)
-    .await
-    .unwrap();
+    .await {
+        Ok(rows) => rows,
+        Err(e) => return database_error(e),
+    };
 
     let jobs: Vec<JobRow> = rows
         .into_iter()
@@ -93,6 +144,9 @@ pub async fn get_jobs(mut db: Connection<MainDatabase>) -> RawJson<String> {
                 location: r.get("location"),
                 required_skills: parse_required_skills(&skills_json),
                 experience_level: r.get::<Option<String>, _>("experience_level").unwrap_or_else(|| "any".to_string()),
+                team_fit_weights: TeamFitWeights::from_stored(r.get("team_fit_weights")),
+                location_requirement: LocationRequirement::from_stored(r.get("location_requirement")),
+                score_weights: ScoreWeights::from_stored(r.get("score_weights")),
                 status: r.get::<Option<String>, _>("status").unwrap_or_else(|| "sourcing".to_string()),
                 team_id: r.get::<Option<uuid::Uuid>, _>("team_id").map(|id| id.to_string()),
                 candidate_ids: candidate_uuids.into_iter().map(|id| id.to_string()).collect(),
@@ -107,10 +161,13 @@ pub async fn get_jobs(mut db: Connection<MainDatabase>) -> RawJson<String> {
 
 #[get("/jobs/<id>")]
 pub async fn get_job(id: &str, mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let uuid = uuid::Uuid::parse_str(id).unwrap();
+    let uuid = match parse_job_id(id) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
 
-    let row = sqlx::query(
-        r#"SELECT j.id, j.title, j.description, j.location, j.required_skills, j.experience_level, j.status, j.team_id, j.created_at, j.updated_at,
+    let row = match sqlx::query(
+        r#"SELECT j.id, j.title, j.description, j.location, j.required_skills, j.experience_level, j.team_fit_weights, j.location_requirement, j.score_weights, j.status, j.team_id, j.created_at, j.updated_at,
                   COALESCE(ARRAY_AGG(jc.candidate_id) FILTER (WHERE jc.candidate_id IS NOT NULL), '{}') as candidate_ids
            FROM jobs j
            LEFT JOIN job_candidates jc ON j.id = jc.job_id
@@ -118,9 +175,12 @@ pub async fn get_job(id: &str, mut db: Connection<MainDatabase>) -> RawJson<Stri
            GROUP BY j.id"#
     )
     .bind(uuid)
-    .fetch_one(&mut **db)
-    .await
-    .unwrap();
+    .fetch_optional(&mut **db)
+    .await {
+        Ok(Some(row)) => row,
+        Ok(None) => return job_not_found(),
+        Err(e) => return database_error(e),
+    };
 
     let skills_json: serde_json::Value = row.get("required_skills");
     let candidate_uuids: Vec<uuid::Uuid> = row.get("candidate_ids");
@@ -131,6 +191,9 @@ pub async fn get_job(id: &str, mut db: Connection<MainDatabase>) -> RawJson<Stri
         location: row.get("location"),
         required_skills: parse_required_skills(&skills_json),
         experience_level: row.get::<Option<String>, _>("experience_level").unwrap_or_else(|| "any".to_string()),
+        team_fit_weights: TeamFitWeights::from_stored(row.get("team_fit_weights")),
+        location_requirement: LocationRequirement::from_stored(row.get("location_requirement")),
+        score_weights: ScoreWeights::from_stored(row.get("score_weights")),
         status: row.get::<Option<String>, _>("status").unwrap_or_else(|| "sourcing".to_string()),
         team_id: row.get::<Option<uuid::Uuid>, _>("team_id").map(|id| id.to_string()),
         candidate_ids: candidate_uuids.into_iter().map(|id| id.to_string()).collect(),
@@ -141,86 +204,317 @@ pub async fn get_job(id: &str, mut db: Connection<MainDatabase>) -> RawJson<Stri
     RawJson(serde_json::to_string(&job).unwrap())
 }
 
-#[post("/jobs", data = "<data>")]
-pub async fn create_job(data: json::Json<CreateJob>, mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let id = uuid::Uuid::new_v4();
-
-    sqlx::query(
-        r#"INSERT INTO jobs (id, title, description, location, required_skills, experience_level) VALUES ($1, $2, $3, $4, $5, $6)"#
-    )
-    .bind(id)
-    .bind(&data.title)
-    .bind(&data.description)
-    .bind(&data.location)
-    .bind(&data.required_skills)
-    .bind(&data.experience_level)
-    .execute(&mut **db)
-    .await
-    .unwrap();
-
-    let job = JobRow {
+/// Build the response row for a freshly created job (no team, no candidates)
+fn new_job_row(id: uuid::Uuid, data: &CreateJob) -> JobRow {
+    JobRow {
         id: id.to_string(),
         title: data.title.clone(),
         description: data.description.clone(),
         location: data.location.clone(),
         required_skills: parse_required_skills(&data.required_skills),
         experience_level: data.experience_level.clone(),
+        team_fit_weights: data.team_fit_weights.clone().unwrap_or_default(),
+        location_requirement: data.location_requirement.clone(),
+        score_weights: data.score_weights.clone(),
         status: "sourcing".to_string(),
         team_id: None,
         candidate_ids: vec![],
         created_at: chrono::Utc::now().to_string(),
         updated_at: chrono::Utc::now().to_string(),
+    }
+}
+
+/// Insert a new job and return its response row
+async fn insert_job(data: &CreateJob, conn: &mut sqlx::PgConnection) -> Result<JobRow, sqlx::Error> {
+    let id = uuid::Uuid::new_v4();
+
+    sqlx::query(
+        r#"INSERT INTO jobs (id, title, description, location, required_skills, experience_level, team_fit_weights, location_requirement, score_weights) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)"#
+    )
+    .bind(id)
+    .bind(&data.title)
+    .bind(&data.description)
+    .bind(&data.location)
+    .bind(&data.required_skills)
+    .bind(&data.experience_level)
+    .bind(data.team_fit_weights.as_ref().and_then(|w| serde_json::to_value(w).ok()))
+    .bind(data.location_requirement.as_ref().and_then(|r| serde_json::to_value(r).ok()))
+    .bind(data.score_weights.as_ref().and_then(|w| serde_json::to_value(w).ok()))
+    .execute(conn)
+    .await?;
+
+    Ok(new_job_row(id, data))
+}
+
+/// Score weights normalized to sum to 1.0, or the error to return when they can't be used
+fn normalize_score_weights(weights: Option<ScoreWeights>) -> Result<Option<ScoreWeights>, String> {
+    weights
+        .map(|w| w.normalized().ok_or_else(|| "score_weights must be non-negative and not all zero".to_string()))
+        .transpose()
+}
+
+#[post("/jobs", data = "<data>")]
+pub async fn create_job(data: json::Json<CreateJob>, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    let mut data = data.into_inner();
+    if let Some(Err(message)) = data.location_requirement.as_ref().map(|r| r.validate()) {
+        return err_json(INVALID_REQUEST, message);
+    }
+    data.score_weights = match normalize_score_weights(data.score_weights) {
+        Ok(weights) => weights,
+        Err(message) => return err_json(INVALID_REQUEST, message),
     };
 
-    RawJson(serde_json::to_string(&job).unwrap())
+    match insert_job(&data, &mut db).await {
+        Ok(job) => RawJson(serde_json::to_string(&job).unwrap()),
+        Err(e) => database_error(e),
+    }
 }
 
-#[put("/jobs/<id>", data = "<data>")]
-pub async fn update_job(id: &str, data: json::Json<UpdateJob>, mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let uuid = uuid::Uuid::parse_str(id).unwrap();
+/// Which required_skills format the input uses: legacy, enhanced, mixed, empty or invalid
+fn required_skills_format(json_value: &serde_json::Value) -> &'static str {
+    let Some(arr) = json_value.as_array() else { return "invalid" };
+    if arr.is_empty() {
+        return "empty";
+    }
+
+    let strings = arr.iter().filter(|v| v.is_string()).count();
+    let objects = arr.iter().filter(|v| v.is_object()).count();
+    match (strings, objects) {
+        (s, 0) if s == arr.len() => "legacy",
+        (0, o) if o == arr.len() => "enhanced",
+        _ => "mixed",
+    }
+}
+
+fn validate_skills_response(input: &serde_json::Value) -> serde_json::Value {
+    // Accept either the raw array or {"required_skills": [...]}
+    let required_skills = input.get("required_skills").unwrap_or(input);
+    let parsed = parse_required_skills_with_warnings(required_skills);
+
+    serde_json::json!({
+        "format": required_skills_format(required_skills),
+        "required_skills": parsed.skills,
+        "warnings": parsed.warnings,
+    })
+}
+
+/// Preview how required_skills input will be interpreted, without saving
+/// POST /api/jobs/validate-skills
+#[post("/jobs/validate-skills", data = "<data>")]
+pub async fn validate_skills(data: json::Json<serde_json::Value>) -> RawJson<String> {
+    RawJson(validate_skills_response(&data).to_string())
+}
+
+/// A stored job's fields as the input to create a copy of it
+async fn load_clone_source(conn: &mut sqlx::PgConnection, id: uuid::Uuid) -> Result<Option<CreateJob>, sqlx::Error> {
+    let row = sqlx::query(
+        "SELECT title, description, location, required_skills, experience_level, team_fit_weights, location_requirement, score_weights FROM jobs WHERE id = $1"
+    )
+    .bind(id)
+    .fetch_optional(conn)
+    .await?;
+
+    Ok(row.map(|row| CreateJob {
+        title: row.get("title"),
+        description: row.get("description"),
+        location: row.get("location"),
+        required_skills: row.get::<Option<serde_json::Value>, _>("required_skills").unwrap_or_else(|| serde_json::json!([])),
+        experience_level: row.get::<Option<String>, _>("experience_level").unwrap_or_else(|| "any".to_string()),
+        team_fit_weights: row.get::<Option<serde_json::Value>, _>("team_fit_weights")
+            .and_then(|v| serde_json::from_value(v).ok()),
+        location_requirement: LocationRequirement::from_stored(row.get("location_requirement")),
+        score_weights: ScoreWeights::from_stored(row.get("score_weights")),
+    }))
+}
+
+/// Clone an existing job into a new one, optionally overriding fields
+/// POST /api/jobs/{id}/clone
+#[post("/jobs/<id>/clone", data = "<data>")]
+pub async fn clone_job(id: &str, data: Option<json::Json<CloneJob>>, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    let uuid = match parse_job_id(id) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    let source = match load_clone_source(&mut db, uuid).await {
+        Ok(Some(source)) => source,
+        Ok(None) => return job_not_found(),
+        Err(e) => return database_error(e),
+    };
+
+    let mut overrides = data.map(|d| d.into_inner()).unwrap_or_default();
+    if let Some(Err(message)) = overrides.location_requirement.as_ref().map(|r| r.validate()) {
+        return err_json(INVALID_REQUEST, message);
+    }
+    overrides.score_weights = match normalize_score_weights(overrides.score_weights) {
+        Ok(weights) => weights,
+        Err(message) => return err_json(INVALID_REQUEST, message),
+    };
+    let cloned = overrides.apply_to(source);
+
+    match insert_job(&cloned, &mut db).await {
+        Ok(job) => RawJson(serde_json::to_string(&job).unwrap()),
+        Err(e) => database_error(e),
+    }
+}
 
+/// Which linked candidate has which required skill, with per-skill coverage across the pool
+/// GET /api/jobs/{id}/skill-matrix
+#[get("/jobs/<id>/skill-matrix")]
+pub async fn get_job_skill_matrix(id: &str, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    let uuid = match parse_job_id(id) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    let required_skills = match sqlx::query("SELECT required_skills FROM jobs WHERE id = $1")
+        .bind(uuid)
+        .fetch_optional(&mut **db)
+        .await
+    {
+        Ok(Some(row)) => parse_required_skills(&row.get::<Option<serde_json::Value>, _>("required_skills").unwrap_or_default()),
+        Ok(None) => return job_not_found(),
+        Err(e) => return database_error(e),
+    };
+
+    let rows = match sqlx::query(
+        r#"SELECT sc.id, sc.name, sc.skills
+           FROM job_candidates jc
+           JOIN sourced_candidates sc ON jc.candidate_id = sc.id
+           WHERE jc.job_id = $1
+           ORDER BY jc.added_at DESC"#
+    )
+    .bind(uuid)
+    .fetch_all(&mut **db)
+    .await {
+        Ok(rows) => rows,
+        Err(e) => return database_error(e),
+    };
+
+    let candidates: Vec<MatrixCandidate> = rows
+        .into_iter()
+        .map(|r| MatrixCandidate {
+            id: r.get::<uuid::Uuid, _>("id").to_string(),
+            name: r.get("name"),
+            skills: r.get::<Option<serde_json::Value>, _>("skills")
+                .and_then(|v| serde_json::from_value(v).ok())
+                .unwrap_or_default(),
+        })
+        .collect();
+
+    let matrix = build_skill_matrix(&required_skills, &candidates, match_tuning());
+    RawJson(serde_json::to_string(&matrix).unwrap())
+}
+
+/// Team for `team_id` in an update: "" unlinks the team, anything else must be a UUID
+fn parse_team_id(team_id: &str) -> Result<Option<uuid::Uuid>, RawJson<String>> {
+    if team_id.is_empty() {
+        return Ok(None);
+    }
+    uuid::Uuid::parse_str(team_id)
+        .map(Some)
+        .map_err(|_| err_json(INVALID_REQUEST, "Invalid team ID"))
+}
+
+/// Write each provided field of an update to the job
+async fn apply_job_update(
+    uuid: uuid::Uuid,
+    data: &UpdateJob,
+    score_weights: Option<ScoreWeights>,
+    team_uuid: Option<Option<uuid::Uuid>>,
+    db: &mut Connection<MainDatabase>,
+) -> Result<(), sqlx::Error> {
     if let Some(ref title) = data.title {
         sqlx::query("UPDATE jobs SET title = $1, updated_at = NOW() WHERE id = $2")
             .bind(title)
             .bind(uuid)
-            .execute(&mut **db).await.unwrap();
+            .execute(&mut ***db).await?;
     }
     if let Some(ref description) = data.description {
         sqlx::query("UPDATE jobs SET description = $1, updated_at = NOW() WHERE id = $2")
             .bind(description)
             .bind(uuid)
-            .execute(&mut **db).await.unwrap();
+            .execute(&mut ***db).await?;
     }
     if let Some(ref location) = data.location {
         sqlx::query("UPDATE jobs SET location = $1, updated_at = NOW() WHERE id = $2")
             .bind(location)
             .bind(uuid)
-            .execute(&mut **db).await.unwrap();
+            .execute(&mut ***db).await?;
     }
     if let Some(ref skills) = data.required_skills {
         sqlx::query("UPDATE jobs SET required_skills = $1, updated_at = NOW() WHERE id = $2")
             .bind(skills)
             .bind(uuid)
-            .execute(&mut **db).await.unwrap();
+            .execute(&mut ***db).await?;
     }
     if let Some(ref level) = data.experience_level {
         sqlx::query("UPDATE jobs SET experience_level = $1, updated_at = NOW() WHERE id = $2")
             .bind(level)
             .bind(uuid)
-            .execute(&mut **db).await.unwrap();
+            .execute(&mut ***db).await?;
     }
     if let Some(ref status) = data.status {
         sqlx::query("UPDATE jobs SET status = $1, updated_at = NOW() WHERE id = $2")
             .bind(status)
             .bind(uuid)
-            .execute(&mut **db).await.unwrap();
+            .execute(&mut ***db).await?;
+    }
+    if let Some(ref weights) = data.team_fit_weights {
+        sqlx::query("UPDATE jobs SET team_fit_weights = $1, updated_at = NOW() WHERE id = $2")
+            .bind(serde_json::to_value(weights).unwrap())
+            .bind(uuid)
+            .execute(&mut ***db).await?;
+    }
+    if let Some(ref requirement) = data.location_requirement {
+        sqlx::query("UPDATE jobs SET location_requirement = $1, updated_at = NOW() WHERE id = $2")
+            .bind(requirement.as_ref().map(|r| serde_json::to_value(r).unwrap()))
+            .bind(uuid)
+            .execute(&mut ***db).await?;
     }
-    if let Some(ref team_id) = data.team_id {
-        let team_uuid = if team_id.is_empty() { None } else { Some(uuid::Uuid::parse_str(team_id).unwrap()) };
+    if let Some(weights) = score_weights {
+        sqlx::query("UPDATE jobs SET score_weights = $1, updated_at = NOW() WHERE id = $2")
+            .bind(serde_json::to_value(weights).unwrap())
+            .bind(uuid)
+            .execute(&mut ***db).await?;
+    }
+    if let Some(team_uuid) = team_uuid {
         sqlx::query("UPDATE jobs SET team_id = $1, updated_at = NOW() WHERE id = $2")
             .bind(team_uuid)
             .bind(uuid)
-            .execute(&mut **db).await.unwrap();
+            .execute(&mut ***db).await?;
+    }
+
+    Ok(())
+}
+
+#[put("/jobs/<id>", data = "<data>")]
+pub async fn update_job(id: &str, data: json::Json<UpdateJob>, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    let uuid = match parse_job_id(id) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
+
+    if let Some(Err(message)) = data.location_requirement.as_ref().and_then(Option::as_ref).map(|r| r.validate()) {
+        return err_json(INVALID_REQUEST, message);
+    }
+    let score_weights = match normalize_score_weights(data.score_weights.clone()) {
+        Ok(weights) => weights,
+        Err(message) => return err_json(INVALID_REQUEST, message),
+    };
+    let team_uuid = match data.team_id.as_deref().map(parse_team_id).transpose() {
+        Ok(team) => team,
+        Err(e) => return e,
+    };
+
+    match sqlx::query("SELECT 1 FROM jobs WHERE id = $1").bind(uuid).fetch_optional(&mut **db).await {
+        Ok(Some(_)) => {}
+        Ok(None) => return job_not_found(),
+        Err(e) => return database_error(e),
+    }
+
+    if let Err(e) = apply_job_update(uuid, &data, score_weights, team_uuid, &mut db).await {
+        return database_error(e);
     }
 
     RawJson(format!(r#"{{"success":true,"id":"{}"}}"#, id))
@@ -228,13 +522,259 @@ pub async fn update_job(id: &str, data: json::Json<UpdateJob>, mut db: Connectio
 
 #[delete("/jobs/<id>")]
 pub async fn delete_job(id: &str, mut db: Connection<MainDatabase>) -> RawJson<String> {
-    let uuid = uuid::Uuid::parse_str(id).unwrap();
+    let uuid = match parse_job_id(id) {
+        Ok(u) => u,
+        Err(e) => return e,
+    };
 
-    sqlx::query("DELETE FROM jobs WHERE id = $1")
+    match sqlx::query("DELETE FROM jobs WHERE id = $1")
         .bind(uuid)
         .execute(&mut **db)
         .await
-        .unwrap();
+    {
+        Ok(result) if result.rows_affected() == 0 => job_not_found(),
+        Ok(_) => RawJson(format!(r#"{{"success":true,"id":"{}"}}"#, id)),
+        Err(e) => database_error(e),
+    }
+}
 
-    RawJson(format!(r#"{{"success":true,"id":"{}"}}"#, id))
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source_job() -> CreateJob {
+        CreateJob {
+            title: "Senior Backend Engineer".to_string(),
+            description: Some("Own the API layer".to_string()),
+            location: Some("Sydney".to_string()),
+            required_skills: serde_json::json!([
+                "Rust",
+                {"name": "PostgreSQL", "level": "advanced", "mandatory": false}
+            ]),
+            experience_level: "senior".to_string(),
+            team_fit_weights: Some(TeamFitWeights { skill_gaps: 1.0, work_style: 1.0, code_style: 3.0 }),
+            location_requirement: Some(LocationRequirement::OnSite { city: "Sydney".to_string() }),
+            score_weights: None,
+        }
+    }
+
+    #[test]
+    fn test_clone_job_gets_new_id_same_skills_fresh_status() {
+        let source_id = uuid::Uuid::new_v4();
+        let source = source_job();
+        let cloned = CloneJob::default().apply_to(source.clone());
+        let row = new_job_row(uuid::Uuid::new_v4(), &cloned);
+
+        assert_ne!(row.id, source_id.to_string());
+        assert_eq!(row.status, "sourcing");
+        assert!(row.team_id.is_none());
+        assert!(row.candidate_ids.is_empty());
+
+        let original: Vec<String> = parse_required_skills(&source.required_skills).into_iter().map(|s| s.name).collect();
+        let copied: Vec<String> = row.required_skills.iter().map(|s| s.name.clone()).collect();
+        assert_eq!(copied, original);
+        assert_eq!(row.title, source.title);
+        assert_eq!(row.experience_level, source.experience_level);
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_clone_job_round_trips_through_database() {
+        use sqlx::Connection as _;
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let source = source_job();
+        let source_id = uuid::Uuid::parse_str(&insert_job(&source, &mut tx).await.unwrap().id).unwrap();
+
+        // The same steps as the clone endpoint
+        let loaded = load_clone_source(&mut tx, source_id).await.unwrap().unwrap();
+        let row = insert_job(&CloneJob::default().apply_to(loaded), &mut tx).await.unwrap();
+
+        assert_ne!(row.id, source_id.to_string());
+        assert_eq!(row.status, "sourcing");
+        assert!(row.team_id.is_none());
+        assert!(row.candidate_ids.is_empty());
+
+        let original: Vec<String> = parse_required_skills(&source.required_skills).into_iter().map(|s| s.name).collect();
+        let copied: Vec<String> = row.required_skills.iter().map(|s| s.name.clone()).collect();
+        assert_eq!(copied, original);
+        assert_eq!(row.title, source.title);
+        assert_eq!(row.experience_level, source.experience_level);
+
+        // Both jobs are stored; the copy starts with no candidates
+        let (stored_title, status): (String, Option<String>) = sqlx::query_as("SELECT title, status FROM jobs WHERE id = $1")
+            .bind(uuid::Uuid::parse_str(&row.id).unwrap())
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(stored_title, source.title);
+        assert_eq!(status.as_deref(), Some("sourcing"));
+        assert!(load_clone_source(&mut tx, source_id).await.unwrap().is_some());
+
+        assert!(load_clone_source(&mut tx, uuid::Uuid::new_v4()).await.unwrap().is_none());
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[test]
+    fn test_validate_legacy_skills() {
+        let response = validate_skills_response(&serde_json::json!(["Python", " React "]));
+
+        assert_eq!(response["format"], "legacy");
+        assert_eq!(response["required_skills"], serde_json::json!([
+            {"name": "Python", "level": "intermediate", "mandatory": true},
+            {"name": "React", "level": "intermediate", "mandatory": true}
+        ]));
+        assert_eq!(response["warnings"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_validate_enhanced_skills() {
+        let response = validate_skills_response(&serde_json::json!({
+            "required_skills": [
+                {"name": "Rust", "level": "Expert", "mandatory": true},
+                {"name": "Docker", "mandatory": false}
+            ]
+        }));
+
+        assert_eq!(response["format"], "enhanced");
+        assert_eq!(response["required_skills"], serde_json::json!([
+            {"name": "Rust", "level": "expert", "mandatory": true},
+            {"name": "Docker", "level": "intermediate", "mandatory": false}
+        ]));
+        assert_eq!(response["warnings"], serde_json::json!([]));
+    }
+
+    #[test]
+    fn test_validate_mixed_and_malformed_skills() {
+        let response = validate_skills_response(&serde_json::json!([
+            "Go",
+            {"name": "Kafka", "level": "guru"},
+            {"level": "advanced"},
+            42,
+            "",
+            {"name": "Redis", "mandatory": "yes"}
+        ]));
+
+        assert_eq!(response["format"], "mixed");
+        let names: Vec<&str> = response["required_skills"].as_array().unwrap()
+            .iter()
+            .map(|s| s["name"].as_str().unwrap())
+            .collect();
+        assert_eq!(names, vec!["Go", "Kafka", "Redis"]);
+        assert_eq!(response["required_skills"][2]["mandatory"], true);
+
+        let warnings = response["warnings"].as_array().unwrap();
+        assert_eq!(warnings.len(), 5);
+        assert!(warnings[0].as_str().unwrap().contains("guru"));
+        assert!(warnings[1].as_str().unwrap().starts_with("Entry 2"));
+
+        let response = validate_skills_response(&serde_json::json!("Rust, Go"));
+        assert_eq!(response["format"], "invalid");
+        assert_eq!(response["warnings"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_clone_job_applies_overrides() {
+        let overrides = CloneJob {
+            title: Some("Senior Backend Engineer (Payments)".to_string()),
+            ..Default::default()
+        };
+        let cloned = overrides.apply_to(source_job());
+
+        assert_eq!(cloned.title, "Senior Backend Engineer (Payments)");
+        assert_eq!(cloned.description.as_deref(), Some("Own the API layer"));
+        assert_eq!(cloned.experience_level, "senior");
+        assert_eq!(cloned.team_fit_weights.unwrap().code_style, 3.0);
+        assert_eq!(cloned.location_requirement, Some(LocationRequirement::OnSite { city: "Sydney".to_string() }));
+    }
+
+    #[test]
+    fn test_update_can_clear_location_requirement() {
+        let update = |body: serde_json::Value| serde_json::from_value::<UpdateJob>(body).unwrap().location_requirement;
+
+        assert_eq!(update(serde_json::json!({})), None);
+        assert_eq!(update(serde_json::json!({"location_requirement": null})), Some(None));
+        assert_eq!(
+            update(serde_json::json!({"location_requirement": {"type": "remote"}})),
+            Some(Some(LocationRequirement::Remote))
+        );
+    }
+
+    #[test]
+    fn test_score_weights_normalized_on_save() {
+        let equal = ScoreWeights { skills: 1.0, experience: 1.0, team_fit: 1.0, culture: 1.0 };
+        assert_eq!(
+            normalize_score_weights(Some(equal)),
+            Ok(Some(ScoreWeights { skills: 0.25, experience: 0.25, team_fit: 0.25, culture: 0.25 }))
+        );
+        assert_eq!(normalize_score_weights(None), Ok(None));
+
+        let negative = ScoreWeights { skills: -0.5, ..ScoreWeights::default() };
+        assert!(normalize_score_weights(Some(negative)).is_err());
+    }
+
+    fn error_of(body: RawJson<String>) -> String {
+        let value: serde_json::Value = serde_json::from_str(&body.0).unwrap();
+        value["error"].as_str().unwrap().to_string()
+    }
+
+    #[test]
+    fn test_malformed_ids_return_error_objects() {
+        assert_eq!(error_of(parse_job_id("not-a-uuid").unwrap_err()), "Invalid job ID");
+        let id = uuid::Uuid::new_v4();
+        assert_eq!(parse_job_id(&id.to_string()).unwrap(), id);
+
+        assert_eq!(error_of(parse_team_id("team-1").unwrap_err()), "Invalid team ID");
+        assert_eq!(parse_team_id("").unwrap(), None);
+        assert_eq!(parse_team_id(&id.to_string()).unwrap(), Some(id));
+    }
+
+    #[test]
+    fn test_missing_job_and_db_errors_are_json() {
+        assert_eq!(error_of(job_not_found()), "Job not found");
+        assert_eq!(error_of(database_error(sqlx::Error::RowNotFound)), format!("Database error: {}", sqlx::Error::RowNotFound));
+
+        // Messages with quotes still produce valid JSON
+        let quoted = sqlx::Error::Protocol("relation \"jobs\" does not exist".to_string());
+        assert!(error_of(database_error(quoted)).contains("\"jobs\""));
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_get_job_requests_return_error_bodies() {
+        use rocket::http::Status;
+        use rocket::local::asynchronous::Client;
+        use rocket_db_pools::Database;
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let figment = rocket::Config::figment().merge(("databases.main.url", url));
+        let rocket = rocket::custom(figment)
+            .attach(MainDatabase::init())
+            .mount("/api/", rocket::routes![get_job]);
+        let client = Client::tracked(rocket).await.unwrap();
+
+        let body = |response: String| serde_json::from_str::<serde_json::Value>(&response).unwrap();
+
+        let response = client.get("/api/jobs/not-a-uuid").dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let malformed = body(response.into_string().await.unwrap());
+        assert_eq!(malformed["code"], "invalid_request");
+        assert_eq!(malformed["error"], "Invalid job ID");
+
+        let response = client.get(format!("/api/jobs/{}", uuid::Uuid::new_v4())).dispatch().await;
+        assert_eq!(response.status(), Status::Ok);
+        let missing = body(response.into_string().await.unwrap());
+        assert_eq!(missing["code"], "not_found");
+        assert_eq!(missing["error"], "Job not found");
+    }
 }
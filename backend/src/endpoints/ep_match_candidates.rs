@@ -1,29 +1,110 @@
-use crate::code_analysis::characteristics::CodeCharacteristics;
-
-//use crate::{code_analysis::characteristics::CodeCharacteristics, db::MainDatabase};
-//
-//#[derive(Deserialize)]
-//#[serde(crate = "rocket::serde")]
-//pub struct MatchCandidates {
-//    target: CodeCharacteristics
-//}
-//
-//#[derive(FromRow)]
-//struct CandidateToCompare {
-//    id: u32,
-//    employed: bool
-//}
-//
-//#[post("/match_candidates", data = "<data>")]
-//pub async fn match_candidates(data: json::Json<MatchCandidates>, mut db: Connection<MainDatabase>) {
-//    let res = sqlx::query_as::<_, CandidateToCompare>("SELECT (id, style, employed) FROM candidates WHERE employed IS FALSE")
-//        .fetch_all(&mut **db).await
-//        .unwrap();
-//
-//    let seralised = res.into_iter()
-//        .map(|row| row);
-//
-//}
+use rocket::{post, serde::json};
+use rocket::response::content::RawJson;
+use rocket_db_pools::Connection;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+
+use crate::code_analysis::characteristics::{
+    scaled_diff_sq, CodeCharacteristics, ABSTRACTION_LAYER_SCALE, LINES_PER_FUNCTION_SCALE, NESTING_DEPTH_SCALE,
+};
+use crate::db::MainDatabase;
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct MatchStylesRequest {
+    target: CodeCharacteristics,
+    weights: Option<Weights>,
+    /// Sourced candidate or team member ids to compare against the target
+    candidate_ids: Vec<String>,
+}
+
+/// A candidate's distance from the target style; lower is closer
+#[derive(Serialize, Debug)]
+pub struct StyleMatch {
+    pub id: String,
+    pub distance: f32,
+    pub characteristics: CodeCharacteristics,
+}
+
+/// A requested candidate that couldn't be compared
+#[derive(Serialize, Debug, PartialEq)]
+pub struct SkippedCandidate {
+    pub id: String,
+    pub reason: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct StyleMatches {
+    /// Closest first
+    pub matches: Vec<StyleMatch>,
+    pub skipped: Vec<SkippedCandidate>,
+}
+
+/// Rank the requested candidates by distance from the target, using the stored `code_characteristics` found for them
+/// Candidates that weren't found, or whose characteristics are NULL or unreadable, are reported as skipped
+pub fn rank_styles(
+    target: CodeCharacteristics,
+    weights: Option<Weights>,
+    candidate_ids: &[String],
+    stored: &[(String, Option<serde_json::Value>)],
+) -> StyleMatches {
+    let mut ids = Vec::new();
+    let mut characteristics = Vec::new();
+    let mut skipped = Vec::new();
+
+    for id in candidate_ids {
+        let reason = match stored.iter().find(|(stored_id, _)| stored_id.eq_ignore_ascii_case(id)) {
+            None if uuid::Uuid::parse_str(id).is_err() => "Invalid candidate ID",
+            None => "Candidate not found",
+            Some((_, None)) => "No code characteristics (GitHub analysis missing or pending)",
+            Some((_, Some(value))) => match serde_json::from_value::<CodeCharacteristics>(value.clone()) {
+                Ok(c) => {
+                    ids.push(id.clone());
+                    characteristics.push(c);
+                    continue;
+                }
+                Err(_) => "Stored code characteristics are unreadable",
+            },
+        };
+        skipped.push(SkippedCandidate { id: id.clone(), reason: reason.to_string() });
+    }
+
+    let mut matches: Vec<StyleMatch> = ids
+        .into_iter()
+        .zip(match_styles(target, characteristics, weights))
+        .map(|(id, (characteristics, distance))| StyleMatch { id, distance, characteristics })
+        .collect();
+    matches.sort_by(|a, b| a.distance.total_cmp(&b.distance));
+
+    StyleMatches { matches, skipped }
+}
+
+/// Rank candidates and team members by how close their coding style is to a target
+/// POST /api/match_styles
+#[post("/match_styles", data = "<data>")]
+pub async fn match_styles_endpoint(data: json::Json<MatchStylesRequest>, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    let data = data.into_inner();
+    let uuids: Vec<uuid::Uuid> = data.candidate_ids.iter().filter_map(|id| uuid::Uuid::parse_str(id).ok()).collect();
+
+    let rows = match sqlx::query(
+        r#"SELECT id, code_characteristics FROM sourced_candidates WHERE id = ANY($1)
+           UNION ALL
+           SELECT id, code_characteristics FROM team_members WHERE id = ANY($1)"#
+    )
+    .bind(&uuids)
+    .fetch_all(&mut **db)
+    .await {
+        Ok(rows) => rows,
+        Err(e) => return RawJson(serde_json::json!({"error": format!("Database error: {}", e)}).to_string()),
+    };
+
+    let stored: Vec<(String, Option<serde_json::Value>)> = rows
+        .iter()
+        .map(|r| (r.get::<uuid::Uuid, _>("id").to_string(), r.get("code_characteristics")))
+        .collect();
+
+    RawJson(serde_json::to_string(&rank_styles(data.target, data.weights, &data.candidate_ids, &stored)).unwrap())
+}
 
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct Weights {
@@ -56,6 +137,9 @@ impl Default for Weights {
     }
 }
 
+/// Weighted Euclidean distance from the target for each candidate
+/// Lines per function, nesting depth and abstraction layers are divided by their scale (50, 5 and 5) and capped
+/// like in team fit's code-style distance, so a weight of 1.0 means the same for every field
 pub fn match_styles(target: CodeCharacteristics, candidates: Vec<CodeCharacteristics>, weights: Option<Weights>) -> Vec<(CodeCharacteristics, f32)> {
     let mut ret = Vec::new();
     let w = weights.unwrap_or_default();
@@ -64,9 +148,9 @@ pub fn match_styles(target: CodeCharacteristics, candidates: Vec<CodeCharacteris
 
     for candidate in candidates {
         let sum = w.dependency_coupling_index * d_sq(target.dependency_coupling_index, candidate.dependency_coupling_index)
-            //+ w.abstraction_layer_count * d_sq(target.abstraction_layer_count, candidate.abstraction_layer_count)
-            //+ w.avg_lines_per_function * d_sq(target.avg_lines_per_function, candidate.avg_lines_per_function)
-            //+ w.avg_nesting_depth * d_sq(target.avg_nesting_depth, candidate.avg_nesting_depth)
+            + w.abstraction_layer_count * scaled_diff_sq(target.abstraction_layer_count, candidate.abstraction_layer_count, ABSTRACTION_LAYER_SCALE)
+            + w.avg_lines_per_function * scaled_diff_sq(target.avg_lines_per_function, candidate.avg_lines_per_function, LINES_PER_FUNCTION_SCALE)
+            + w.avg_nesting_depth * scaled_diff_sq(target.avg_nesting_depth, candidate.avg_nesting_depth, NESTING_DEPTH_SCALE)
             + w.error_handling_centralization_score * d_sq(target.error_handling_centralization_score, candidate.error_handling_centralization_score)
             + w.functional_vs_oop_ratio * d_sq(target.functional_vs_oop_ratio, candidate.functional_vs_oop_ratio)
             + w.immutability_score * d_sq(target.immutability_score, candidate.immutability_score)
@@ -78,4 +162,4 @@ pub fn match_styles(target: CodeCharacteristics, candidates: Vec<CodeCharacteris
     }
 
     ret
-}
\ No newline at end of file
+}
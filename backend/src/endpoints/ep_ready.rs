@@ -0,0 +1,20 @@
+use rocket::get;
+use rocket::response::content::RawJson;
+use rocket_db_pools::Connection;
+
+use crate::db::MainDatabase;
+use crate::github::capabilities::check_embedding_support;
+
+/// Report readiness and optional capabilities
+/// Embedding support is re-checked on each call so a migrated database is picked up without a restart
+#[get("/ready")]
+pub async fn ready(mut db: Connection<MainDatabase>) -> RawJson<String> {
+    let database = sqlx::query("SELECT 1").execute(&mut **db).await.is_ok();
+    let embeddings = database && check_embedding_support(&mut db).await;
+
+    RawJson(serde_json::json!({
+        "status": if embeddings { "ok" } else if database { "degraded" } else { "unavailable" },
+        "database": database,
+        "embeddings": embeddings,
+    }).to_string())
+}
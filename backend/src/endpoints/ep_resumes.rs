@@ -1,9 +1,17 @@
 use rocket::{post, Data, http::ContentType};
 use rocket::response::content::RawJson;
 use rocket::data::ToByteUnit;
+use rocket_db_pools::Connection;
 use serde::{Deserialize, Serialize};
 use genai::{Client, chat::{ChatMessage, ChatRequest}};
+use sqlx::Row;
 use std::io::{Read, Cursor};
+use crate::ai_response::{parse_json, AiResponseError};
+use crate::ai_temperature::{chat_options, temperatures};
+use crate::candidate_id::candidate_id;
+use crate::db::MainDatabase;
+use crate::endpoints::ep_candidates::{enrichment_target, initial_analysis_status, LinksInput};
+use crate::matching::skills::split_compound_skills;
 
 #[derive(Serialize, Deserialize)]
 pub struct ParsedResume {
@@ -20,6 +28,12 @@ pub struct ParsedResume {
     pub linkedin_url: Option<String>,
     pub website_url: Option<String>,
     pub other_links: Vec<String>,
+    /// 0-1 estimate of how reliable the extraction is (see `score_parse_confidence`)
+    #[serde(default)]
+    pub parse_confidence: f32,
+    /// Set when `parse_confidence` is below LOW_CONFIDENCE_THRESHOLD; the data is still returned
+    #[serde(default)]
+    pub low_confidence: bool,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -44,6 +58,52 @@ pub struct EducationExtracted {
     pub field: Option<String>,
 }
 
+/// Candidate a parsed resume was saved as
+#[derive(Serialize, Debug, PartialEq)]
+pub struct StoredResume {
+    pub candidate_id: String,
+    /// False when a candidate with the same email already existed and was updated instead
+    pub created: bool,
+}
+
+/// Parse response: the extracted fields, plus the saved candidate when `save=true`
+#[derive(Serialize)]
+pub struct ParseResumeResponse {
+    #[serde(flatten)]
+    parsed: ParsedResume,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    stored: Option<StoredResume>,
+}
+
+/// Below this the extraction likely came from a garbled scan or a non-resume document
+pub const LOW_CONFIDENCE_THRESHOLD: f32 = 0.4;
+
+/// Extracted text length at which the length component of the confidence maxes out
+const CONFIDENT_TEXT_LENGTH: usize = 1500;
+
+/// Heuristic confidence in an extraction: how much text there was to work with
+/// and whether the fields every resume should have (name, email, experience, links) came out
+fn score_parse_confidence(text: &str, parsed: &ParsedResume) -> f32 {
+    let length = (text.trim().chars().count() as f32 / CONFIDENT_TEXT_LENGTH as f32).min(1.0);
+
+    let name = parsed.name.trim();
+    let has_name = !name.is_empty() && !name.eq_ignore_ascii_case("unknown") && name != "Full Name Here";
+    let has_email = parsed.email.as_deref().is_some_and(|e| e.contains('@'));
+    let has_experience = !parsed.experience.is_empty();
+    let has_url = [&parsed.github_url, &parsed.linkedin_url, &parsed.website_url]
+        .into_iter()
+        .flatten()
+        .chain(&parsed.other_links)
+        .any(|url| url.starts_with("http"));
+
+    let score = 0.2 * length
+        + if has_name { 0.25 } else { 0.0 }
+        + if has_email { 0.2 } else { 0.0 }
+        + if has_experience { 0.2 } else { 0.0 }
+        + if has_url { 0.15 } else { 0.0 };
+    (score * 100.0).round() / 100.0
+}
+
 const RESUME_EXTRACTION_PROMPT: &str = r#"You are parsing a resume. Extract ALL information including URLs that may be broken across lines or spaces.
 
 CRITICAL - URL EXTRACTION:
@@ -115,12 +175,175 @@ fn extract_docx_text(data: &[u8]) -> Result<String, String> {
     document_xml.read_to_string(&mut xml_content)
         .map_err(|e| format!("Failed to read document.xml: {}", e))?;
 
-    // Extract text from XML
+    let mut text = decode_entities(&strip_tags(&xml_content));
+
+    // Append hyperlinks so LLM can see them (DOCX stores links separately)
+    append_links(&mut text, hyperlinks);
+
+    Ok(text.trim().to_string())
+}
+
+/// Extract text from an RTF file by dropping control words and non-content groups
+fn extract_rtf_text(data: &[u8]) -> String {
+    let rtf = String::from_utf8_lossy(data);
+
+    // Link targets live in {\*\fldinst HYPERLINK "..."}, which is skipped below
+    let hyperlinks = rtf
+        .split("HYPERLINK")
+        .skip(1)
+        .filter_map(|part| part.trim_start().strip_prefix('"')?.split('"').next())
+        .filter(|url| url.starts_with("http"))
+        .map(String::from)
+        .collect();
+
+    let mut text = String::new();
+    let mut depth = 0usize;
+    // Depth of the group being skipped (font/color tables, metadata, \* destinations)
+    let mut skip_depth: Option<usize> = None;
+    // Fallback characters still to drop after a \uN escape
+    let mut fallback = 0;
+    let mut chars = rtf.chars().peekable();
+
+    fn emit(text: &mut String, skipping: bool, fallback: &mut i32, ch: char) {
+        if *fallback > 0 {
+            *fallback -= 1;
+        } else if !skipping {
+            text.push(ch);
+        }
+    }
+
+    while let Some(ch) = chars.next() {
+        let skipping = skip_depth.is_some();
+        match ch {
+            '{' => depth += 1,
+            '}' => {
+                if skip_depth == Some(depth) {
+                    skip_depth = None;
+                }
+                depth = depth.saturating_sub(1);
+            }
+            // Line breaks in the source are not content; \par is
+            '\r' | '\n' => {}
+            '\\' => match chars.next() {
+                Some(c @ ('\\' | '{' | '}')) => emit(&mut text, skipping, &mut fallback, c),
+                Some('~') => emit(&mut text, skipping, &mut fallback, ' '),
+                Some('\'') => {
+                    let hex: String = chars.by_ref().take(2).collect();
+                    if let Ok(byte) = u8::from_str_radix(&hex, 16) {
+                        emit(&mut text, skipping, &mut fallback, char::from(byte));
+                    }
+                }
+                Some('*') => {
+                    skip_depth.get_or_insert(depth);
+                }
+                Some(c) if c.is_ascii_alphabetic() => {
+                    let mut word = String::from(c);
+                    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphabetic()) {
+                        word.push(c);
+                        chars.next();
+                    }
+                    let mut param = String::new();
+                    while let Some(&c) = chars.peek().filter(|c| c.is_ascii_digit() || (**c == '-' && param.is_empty())) {
+                        param.push(c);
+                        chars.next();
+                    }
+                    // A single space terminates the control word and is not content
+                    if chars.peek() == Some(&' ') {
+                        chars.next();
+                    }
+
+                    match word.as_str() {
+                        "par" | "line" | "sect" | "page" | "row" => emit(&mut text, skipping, &mut fallback, '\n'),
+                        "tab" | "cell" => emit(&mut text, skipping, &mut fallback, ' '),
+                        "u" => {
+                            // Negative values are the signed 16-bit form of code points above 0x7FFF
+                            let code = param.parse::<i32>().unwrap_or(0).rem_euclid(65536);
+                            if let Some(c) = char::from_u32(code as u32) {
+                                emit(&mut text, skipping, &mut fallback, c);
+                            }
+                            fallback = 1;
+                        }
+                        "fonttbl" | "colortbl" | "stylesheet" | "info" | "pict" | "header" | "footer" => {
+                            skip_depth.get_or_insert(depth);
+                        }
+                        _ => {}
+                    }
+                }
+                _ => {}
+            },
+            _ => emit(&mut text, skipping, &mut fallback, ch),
+        }
+    }
+
+    let mut text = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<_>>()
+        .join("\n");
+    append_links(&mut text, hyperlinks);
+
+    text.trim().to_string()
+}
+
+/// Extract text from an HTML file (strip tags, decode entities, keep link targets)
+fn extract_html_text(data: &[u8]) -> String {
+    let html = String::from_utf8_lossy(data);
+
+    let hyperlinks = ["href=\"", "href='"]
+        .iter()
+        .flat_map(|prefix| {
+            let quote = if prefix.ends_with('"') { '"' } else { '\'' };
+            html.split(prefix).skip(1).filter_map(move |part| part.split(quote).next())
+        })
+        .map(decode_entities)
+        .filter(|url| url.starts_with("http"))
+        .collect();
+
+    let mut text = decode_entities(&strip_tags(&strip_elements(&html, &["script", "style", "head"])));
+    append_links(&mut text, hyperlinks);
+
+    text.trim().to_string()
+}
+
+/// Remove the named elements along with their contents (scripts, styles, page metadata)
+fn strip_elements(html: &str, names: &[&str]) -> String {
+    let mut html = html.to_string();
+    for name in names {
+        loop {
+            // ASCII lowercasing keeps byte offsets aligned with the original
+            let lower = html.to_ascii_lowercase();
+            let Some(start) = find_open_tag(&lower, name) else { break };
+            let close = format!("</{}>", name);
+            let end = lower[start..].find(&close).map_or(html.len(), |i| start + i + close.len());
+            html.replace_range(start..end, " ");
+        }
+    }
+    html
+}
+
+/// Find `<name` as a whole tag, so `<head` doesn't match `<header>`
+fn find_open_tag(lower: &str, name: &str) -> Option<usize> {
+    let open = format!("<{}", name);
+    let mut from = 0;
+    while let Some(i) = lower[from..].find(&open) {
+        let start = from + i;
+        let after = lower[start + open.len()..].chars().next();
+        if after.is_none_or(|c| c == '>' || c == '/' || c.is_whitespace()) {
+            return Some(start);
+        }
+        from = start + open.len();
+    }
+    None
+}
+
+/// Drop markup tags, collapsing whitespace and treating each tag as a word break
+fn strip_tags(markup: &str) -> String {
     let mut text = String::new();
     let mut in_tag = false;
     let mut last_was_space = false;
 
-    for ch in xml_content.chars() {
+    for ch in markup.chars() {
         match ch {
             '<' => {
                 in_tag = true;
@@ -144,16 +367,54 @@ fn extract_docx_text(data: &[u8]) -> Result<String, String> {
             _ => {}
         }
     }
+    text
+}
 
-    // Decode XML entities
-    let mut text = text
-        .replace("&amp;", "&")
-        .replace("&lt;", "<")
-        .replace("&gt;", ">")
-        .replace("&quot;", "\"")
-        .replace("&apos;", "'");
+/// Decode named XML/HTML entities and numeric character references in a single pass
+fn decode_entities(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let decoded = rest.find(';').filter(|&end| end <= 10).and_then(|end| {
+            let entity = &rest[1..end];
+            let ch = match entity {
+                "amp" => Some('&'),
+                "lt" => Some('<'),
+                "gt" => Some('>'),
+                "quot" => Some('"'),
+                "apos" => Some('\''),
+                "nbsp" => Some(' '),
+                _ => entity
+                    .strip_prefix("#x")
+                    .or_else(|| entity.strip_prefix("#X"))
+                    .map(|hex| u32::from_str_radix(hex, 16).ok())
+                    .unwrap_or_else(|| entity.strip_prefix('#').and_then(|dec| dec.parse().ok()))
+                    .and_then(char::from_u32),
+            };
+            ch.map(|c| (c, end))
+        });
+
+        match decoded {
+            Some((c, end)) => {
+                out.push(c);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
 
-    // Append hyperlinks so LLM can see them (DOCX stores links separately)
+/// Append link targets so the LLM sees them even when the visible text doesn't include them
+fn append_links(text: &mut String, hyperlinks: Vec<String>) {
     if !hyperlinks.is_empty() {
         text.push_str("\n\nLinks found in document:\n");
         for link in hyperlinks {
@@ -161,8 +422,34 @@ fn extract_docx_text(data: &[u8]) -> Result<String, String> {
             text.push('\n');
         }
     }
+}
 
-    Ok(text.trim().to_string())
+fn is_rtf(content_type: &ContentType, bytes: &[u8]) -> bool {
+    content_type.sub().as_str().eq_ignore_ascii_case("rtf") || bytes.starts_with(b"{\\rtf")
+}
+
+fn is_html(content_type: &ContentType, bytes: &[u8]) -> bool {
+    let head = String::from_utf8_lossy(&bytes[..bytes.len().min(512)]).trim_start().to_ascii_lowercase();
+    content_type.is_html() || head.starts_with("<!doctype html") || head.starts_with("<html")
+}
+
+/// Pick an extractor from the content type, falling back to magic bytes for generic uploads
+fn extract_resume_text(content_type: &ContentType, bytes: Vec<u8>) -> Result<String, String> {
+    if content_type.is_pdf() || bytes.starts_with(b"%PDF") {
+        extract_pdf_text(&bytes)
+    } else if content_type.to_string().contains("wordprocessingml")
+           || content_type.to_string().contains("msword")
+           || bytes.starts_with(b"PK") // DOCX is a zip file
+    {
+        extract_docx_text(&bytes)
+    } else if is_rtf(content_type, &bytes) {
+        Ok(extract_rtf_text(&bytes))
+    } else if is_html(content_type, &bytes) {
+        Ok(extract_html_text(&bytes))
+    } else {
+        // Try as plain text
+        String::from_utf8(bytes).map_err(|_| "Unsupported file format. Please upload PDF, DOCX, RTF or HTML.".to_string())
+    }
 }
 
 /// Parse resume with Gemini
@@ -178,38 +465,107 @@ async fn parse_with_gemini(text: &str) -> Result<ParsedResume, String> {
 
     let prompt = format!("{}{}", RESUME_EXTRACTION_PROMPT, truncated);
     let request = ChatRequest::new(vec![ChatMessage::user(prompt)]);
+    let options = chat_options(temperatures().resume, None);
 
     let response = client
-        .exec_chat("gemini-2.0-flash", request, None)
+        .exec_chat("gemini-2.0-flash", request, Some(&options))
         .await
+        .map_err(AiResponseError::from_request_error)
         .map_err(|e| format!("Gemini API error: {}", e))?;
 
-    let content = response
-        .first_text()
-        .ok_or("No response from Gemini")?;
-
-    // Extract JSON from response
-    let json_str = if content.contains("```json") {
-        content
-            .split("```json")
-            .nth(1)
-            .and_then(|s| s.split("```").next())
-            .unwrap_or(content)
-    } else if content.contains("```") {
-        content
-            .split("```")
-            .nth(1)
-            .unwrap_or(content)
-    } else {
-        content
+    let mut parsed: ParsedResume = parse_json(response.first_text().map(String::from))
+        .map_err(|e| format!("Failed to parse resume: {}", e))?;
+
+    // "React/Redux" or "HTML/CSS/JS" as one entry would never match the individual skills
+    parsed.skills = split_compound_skills(
+        parsed.skills,
+        |s| &s.name,
+        |s, name| SkillExtracted { name, level: s.level.clone() },
+    );
+    parsed.parse_confidence = score_parse_confidence(text, &parsed);
+    parsed.low_confidence = parsed.parse_confidence < LOW_CONFIDENCE_THRESHOLD;
+    Ok(parsed)
+}
+
+/// Save a parsed resume as a candidate, keyed on its email
+/// An existing candidate with that email gets the resume's skills, experience and education instead of a second row
+/// Returns None when the resume has no usable email
+async fn store_parsed_resume(conn: &mut sqlx::PgConnection, parsed: &ParsedResume) -> Result<Option<StoredResume>, sqlx::Error> {
+    let Some(email) = parsed.email.as_deref().map(|e| e.trim().to_lowercase()).filter(|e| e.contains('@')) else {
+        return Ok(None);
     };
+    let skills = serde_json::to_value(&parsed.skills).unwrap();
+    let experience = serde_json::to_value(&parsed.experience).unwrap();
+    let education = serde_json::to_value(&parsed.education).unwrap();
+
+    // Candidates added by hand or by sourcing have random IDs, so match on the email itself first
+    let existing: Option<uuid::Uuid> = sqlx::query_scalar(
+        "SELECT id FROM sourced_candidates WHERE lower(trim(email)) = $1 ORDER BY created_at ASC LIMIT 1"
+    )
+    .bind(&email)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    if let Some(id) = existing {
+        sqlx::query("UPDATE sourced_candidates SET skills = $2, experience = $3, education = $4 WHERE id = $1")
+            .bind(id)
+            .bind(&skills)
+            .bind(&experience)
+            .bind(&education)
+            .execute(&mut *conn)
+            .await?;
+        return Ok(Some(StoredResume { candidate_id: id.to_string(), created: false }));
+    }
 
-    serde_json::from_str(json_str.trim())
-        .map_err(|e| format!("Failed to parse JSON: {} - Response: {}", e, json_str))
+    let links = LinksInput::new(parsed.github_url.clone(), parsed.linkedin_url.clone(), parsed.website_url.clone());
+    let target = enrichment_target(&links);
+    // Same ID create_candidate derives for deterministic imports, so a concurrent upload lands on the same row
+    let id = candidate_id(true, Some(&email), None);
+
+    let row = sqlx::query(
+        r#"INSERT INTO sourced_candidates
+           (id, name, email, phone, location, title, skills, experience, education, links, source, analysis_status)
+           VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, 'resume', $11)
+           ON CONFLICT (id) DO UPDATE SET
+           skills = EXCLUDED.skills, experience = EXCLUDED.experience, education = EXCLUDED.education
+           RETURNING (xmax = 0) AS inserted"#
+    )
+    .bind(id)
+    .bind(if parsed.name.trim().is_empty() { "Unknown" } else { parsed.name.trim() })
+    .bind(parsed.email.as_deref().map(str::trim))
+    .bind(&parsed.phone)
+    .bind(&parsed.location)
+    .bind(parsed.title.as_deref().unwrap_or("Professional"))
+    .bind(&skills)
+    .bind(&experience)
+    .bind(&education)
+    .bind(serde_json::to_value(&links).unwrap())
+    .bind(initial_analysis_status(target.as_ref()))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let created: bool = row.get("inserted");
+    if created && let Some(target) = target {
+        let _ = target.spawn(id, links);
+    }
+
+    Ok(Some(StoredResume { candidate_id: id.to_string(), created }))
 }
 
-#[post("/resumes/parse", data = "<data>")]
-pub async fn parse_resume(content_type: &ContentType, data: Data<'_>) -> RawJson<String> {
+#[post("/resumes/parse?<save>", data = "<data>")]
+pub async fn parse_resume(
+    content_type: &ContentType,
+    data: Data<'_>,
+    save: Option<bool>,
+    db: Option<Connection<MainDatabase>>,
+) -> RawJson<String> {
+    // Only saving needs the database, so plain parsing keeps working without one
+    let save = save.unwrap_or(false);
+    let mut db = match db {
+        None if save => return RawJson(r#"{"error": "Database unavailable, can't save the resume"}"#.to_string()),
+        db => db,
+    };
+
     // Read the file data
     let bytes = match data.open(10.mebibytes()).into_bytes().await {
         Ok(b) if b.is_complete() => b.into_inner(),
@@ -218,25 +574,9 @@ pub async fn parse_resume(content_type: &ContentType, data: Data<'_>) -> RawJson
     };
 
     // Determine file type and extract text
-    let text = if content_type.is_pdf() || bytes.starts_with(b"%PDF") {
-        match extract_pdf_text(&bytes) {
-            Ok(t) => t,
-            Err(e) => return RawJson(format!(r#"{{"error": "{}"}}"#, e)),
-        }
-    } else if content_type.to_string().contains("wordprocessingml")
-           || content_type.to_string().contains("msword")
-           || bytes.starts_with(b"PK") // DOCX is a zip file
-    {
-        match extract_docx_text(&bytes) {
-            Ok(t) => t,
-            Err(e) => return RawJson(format!(r#"{{"error": "{}"}}"#, e)),
-        }
-    } else {
-        // Try as plain text
-        match String::from_utf8(bytes) {
-            Ok(t) => t,
-            Err(_) => return RawJson(r#"{"error": "Unsupported file format. Please upload PDF or DOCX."}"#.to_string()),
-        }
+    let text = match extract_resume_text(content_type, bytes) {
+        Ok(t) => t,
+        Err(e) => return RawJson(format!(r#"{{"error": "{}"}}"#, e)),
     };
 
     if text.trim().is_empty() {
@@ -244,10 +584,20 @@ pub async fn parse_resume(content_type: &ContentType, data: Data<'_>) -> RawJson
     }
 
     // Parse with Gemini - let the LLM find everything including URLs
-    match parse_with_gemini(&text).await {
-        Ok(parsed) => RawJson(serde_json::to_string(&parsed).unwrap()),
-        Err(e) => RawJson(format!(r#"{{"error": "{}"}}"#, e)),
-    }
+    let parsed = match parse_with_gemini(&text).await {
+        Ok(parsed) => parsed,
+        Err(e) => return RawJson(format!(r#"{{"error": "{}"}}"#, e)),
+    };
+
+    let stored = match db.as_mut() {
+        Some(db) if save => match store_parsed_resume(db, &parsed).await {
+            Ok(stored) => stored,
+            Err(e) => return RawJson(serde_json::json!({ "error": format!("Database error: {}", e) }).to_string()),
+        },
+        _ => None,
+    };
+
+    RawJson(serde_json::to_string(&ParseResumeResponse { parsed, stored }).unwrap())
 }
 
 /// Parse resume from raw text (for testing or alternative input)
@@ -262,3 +612,173 @@ pub async fn parse_resume_text(text: String) -> RawJson<String> {
         Err(e) => RawJson(format!(r#"{{"error": "{}"}}"#, e)),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const RTF_RESUME: &str = r#"{\rtf1\ansi\deff0{\fonttbl{\f0\fswiss Helvetica;}}{\colortbl;\red0\green0\blue0;}
+{\info{\author Word Export}}
+\f0\fs32 {\b Jane Doe}\par
+\fs22 Senior Engineer \endash  R\'e9sum\'e9\par
+{\field{\*\fldinst HYPERLINK "https://github.com/janedoe"}{\fldrslt github.com/janedoe}}\par
+Skills: Rust\{async\}, Python\u8217?s tooling\par
+}"#;
+
+    const HTML_RESUME: &str = r#"<!DOCTYPE html>
+<html>
+<head><title>CV</title><style>body { color: red; }</style></head>
+<body>
+  <h1>Jane&nbsp;Doe</h1>
+  <p>Senior Engineer at R&amp;D Labs &#8211; Sydney</p>
+  <p><a href="https://github.com/janedoe?tab=repositories&amp;q=rust">My projects</a></p>
+  <script>var tracking = "ignore me";</script>
+</body>
+</html>"#;
+
+    #[test]
+    fn test_rtf_resume_recovers_name_and_link() {
+        let text = extract_resume_text(&ContentType::Binary, RTF_RESUME.as_bytes().to_vec()).unwrap();
+
+        assert!(text.starts_with("Jane Doe\n"), "{}", text);
+        assert!(text.contains("Résumé"));
+        assert!(text.contains("Rust{async}, Python\u{2019}s tooling"));
+        assert!(text.contains("https://github.com/janedoe"));
+        assert!(!text.contains("Helvetica"));
+        assert!(!text.contains("Word Export"));
+        assert!(!text.contains('\\'));
+    }
+
+    #[test]
+    fn test_html_resume_recovers_name_and_link() {
+        let text = extract_resume_text(&ContentType::HTML, HTML_RESUME.as_bytes().to_vec()).unwrap();
+
+        assert!(text.starts_with("Jane Doe "), "{}", text);
+        assert!(text.contains("R&D Labs \u{2013} Sydney"));
+        assert!(text.contains("https://github.com/janedoe?tab=repositories&q=rust"));
+        assert!(!text.contains("color: red"));
+        assert!(!text.contains("ignore me"));
+        assert!(!text.contains("CV"));
+    }
+
+    const HTML5_RESUME: &str = r#"<html>
+<head><title>CV</title></head>
+<body>
+  <header><h1>Jane Doe</h1></header>
+  <p>Staff Engineer, Rust and Go</p>
+  <footer><a href="https://github.com/janedoe">GitHub</a></footer>
+</body>
+</html>"#;
+
+    #[test]
+    fn test_html_header_and_footer_are_kept() {
+        let text = extract_resume_text(&ContentType::HTML, HTML5_RESUME.as_bytes().to_vec()).unwrap();
+
+        assert!(text.starts_with("Jane Doe "), "{}", text);
+        assert!(text.contains("Staff Engineer, Rust and Go"));
+        assert!(text.contains("GitHub"));
+        assert!(text.contains("https://github.com/janedoe"));
+        assert!(!text.contains("CV"));
+    }
+
+    #[test]
+    fn test_dispatch_by_magic_bytes_and_content_type() {
+        // Sniffed from the content when the upload is untyped
+        let html = extract_resume_text(&ContentType::Binary, HTML_RESUME.as_bytes().to_vec()).unwrap();
+        assert!(html.starts_with("Jane Doe"));
+
+        let rtf_type = ContentType::new("application", "rtf");
+        let rtf = extract_resume_text(&rtf_type, RTF_RESUME.as_bytes().to_vec()).unwrap();
+        assert!(rtf.starts_with("Jane Doe"));
+
+        let text = extract_resume_text(&ContentType::Plain, b"Jane Doe\n<3 Rust".to_vec()).unwrap();
+        assert_eq!(text, "Jane Doe\n<3 Rust");
+
+        let err = extract_resume_text(&ContentType::Binary, vec![0xff, 0xfe, 0x00]).unwrap_err();
+        assert!(err.contains("RTF or HTML"));
+    }
+
+    fn parsed_resume(email: Option<&str>, skills: &[&str]) -> ParsedResume {
+        ParsedResume {
+            name: "Zqx Resume".to_string(),
+            email: email.map(String::from),
+            phone: None,
+            location: Some("Sydney".to_string()),
+            title: None,
+            summary: None,
+            skills: skills.iter().map(|name| SkillExtracted { name: name.to_string(), level: "advanced".to_string() }).collect(),
+            experience: vec![],
+            education: vec![],
+            github_url: None,
+            linkedin_url: None,
+            website_url: None,
+            other_links: vec![],
+            parse_confidence: 0.0,
+            low_confidence: false,
+        }
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_same_resume_twice_stores_one_candidate() {
+        use sqlx::Connection as _;
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let first = store_parsed_resume(&mut tx, &parsed_resume(Some("zqx.resume@example.com"), &["Rust"])).await.unwrap().unwrap();
+        assert!(first.created);
+
+        // Same person, different casing and a newer skill list
+        let second = store_parsed_resume(&mut tx, &parsed_resume(Some(" Zqx.Resume@Example.com"), &["Rust", "Go"])).await.unwrap().unwrap();
+        assert_eq!(second, StoredResume { candidate_id: first.candidate_id.clone(), created: false });
+
+        let rows = sqlx::query("SELECT skills, source, title FROM sourced_candidates WHERE lower(email) = 'zqx.resume@example.com'")
+            .fetch_all(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(rows.len(), 1);
+        let skills: serde_json::Value = rows[0].get("skills");
+        assert_eq!(skills.as_array().unwrap().len(), 2);
+        assert_eq!(rows[0].get::<String, _>("source"), "resume");
+        assert_eq!(rows[0].get::<String, _>("title"), "Professional");
+
+        // Nothing to key on
+        assert_eq!(store_parsed_resume(&mut tx, &parsed_resume(None, &["Rust"])).await.unwrap(), None);
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[test]
+    fn test_rich_resume_is_confident() {
+        let text = extract_html_text(HTML_RESUME.as_bytes()).repeat(12);
+        let mut parsed = parsed_resume(Some("jane@example.com"), &["Rust"]);
+        parsed.experience.push(ExperienceExtracted {
+            title: "Senior Engineer".to_string(),
+            company: "R&D Labs".to_string(),
+            duration: "2020 - Present".to_string(),
+            description: None,
+        });
+        parsed.github_url = Some("https://github.com/janedoe".to_string());
+
+        let confidence = score_parse_confidence(&text, &parsed);
+        assert!(confidence > 0.7, "{}", confidence);
+        assert!(confidence <= 1.0);
+    }
+
+    #[test]
+    fn test_near_empty_resume_is_low_confidence() {
+        let mut parsed = parsed_resume(None, &[]);
+        parsed.name = "Unknown".to_string();
+        let confidence = score_parse_confidence("J D\n\u{fffd}\u{fffd} 04", &parsed);
+        assert!(confidence < LOW_CONFIDENCE_THRESHOLD, "{}", confidence);
+
+        // A name alone doesn't make a short scan trustworthy
+        parsed.name = "Jane Doe".to_string();
+        assert!(score_parse_confidence("Jane Doe", &parsed) < LOW_CONFIDENCE_THRESHOLD);
+    }
+}
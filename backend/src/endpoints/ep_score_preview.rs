@@ -0,0 +1,185 @@
+use rocket::{post, serde::json};
+use rocket::response::content::RawJson;
+use serde::Deserialize;
+use crate::github::stats::Reputation;
+use crate::matching::{
+    self,
+    CandidateSkill, CandidateExperience, ScoreWeights, TalentFitScore,
+    ScoringCandidate, ScoringJob, ScoringTeam, ScoringConfig,
+    parse_required_skills,
+    location::LocationRequirement,
+    team_fit::{CodeCharacteristics, TeamFitWeights, TeamMemberProfile, WorkStyle},
+};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PreviewCandidate {
+    #[serde(default)]
+    skills: Vec<CandidateSkill>,
+    #[serde(default)]
+    experience: Vec<CandidateExperience>,
+    developer_profile: Option<String>,
+    /// GitHub reputation (as in `GitHubStats.reputation`), for the optional experience bonus
+    reputation: Option<Reputation>,
+    location: Option<String>,
+    work_style: Option<WorkStyle>,
+    code_characteristics: Option<CodeCharacteristics>,
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct PreviewJob {
+    title: String,
+    description: Option<String>,
+    #[serde(default)]
+    required_skills: serde_json::Value,  // Accept both formats
+    #[serde(default = "default_experience_level")]
+    experience_level: String,
+    location_requirement: Option<LocationRequirement>,
+}
+
+fn default_experience_level() -> String {
+    "any".to_string()
+}
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct ScorePreviewRequest {
+    candidate: PreviewCandidate,
+    job: PreviewJob,
+    /// Developer profiles of the hiring team (used for culture fit)
+    #[serde(default)]
+    team_profiles: Vec<String>,
+    /// Skills and styles of the hiring team (used for team fit)
+    #[serde(default)]
+    team_members: Vec<TeamMemberProfile>,
+    /// Skip AI calls so the same input always gives the same score
+    #[serde(default)]
+    deterministic: bool,
+    weights: Option<ScoreWeights>,
+    /// Override the configured temperature for AI scoring (0.0-2.0)
+    temperature: Option<f64>,
+}
+
+/// Run the full matching pipeline on inline data
+async fn preview_score(request: &ScorePreviewRequest) -> TalentFitScore {
+    let candidate = &request.candidate;
+    let job = &request.job;
+    let required_skills = parse_required_skills(&job.required_skills);
+
+    // Previews always score team fit (neutral without member data), with the given profiles for culture
+    matching::score(
+        &ScoringCandidate {
+            skills: &candidate.skills,
+            experience: &candidate.experience,
+            developer_profile: candidate.developer_profile.as_deref(),
+            reputation: candidate.reputation.as_ref(),
+            location: candidate.location.as_deref(),
+            work_style: candidate.work_style.as_ref(),
+            code_characteristics: candidate.code_characteristics.as_ref(),
+        },
+        &ScoringJob {
+            title: &job.title,
+            description: job.description.as_deref(),
+            required_skills: &required_skills,
+            experience_level: &job.experience_level,
+            team_fit_weights: &TeamFitWeights::default(),
+            location_requirement: job.location_requirement.as_ref(),
+        },
+        Some(&ScoringTeam { profiles: &request.team_profiles, members: &request.team_members }),
+        &ScoringConfig {
+            weights: request.weights.clone(),
+            temperature: request.temperature,
+            deterministic: request.deterministic,
+        },
+    ).await
+}
+
+/// Score inline candidate and job data without touching the database
+/// POST /api/score/preview
+#[post("/score/preview", data = "<data>")]
+pub async fn score_preview(data: json::Json<ScorePreviewRequest>) -> RawJson<String> {
+    let score = preview_score(&data).await;
+    RawJson(serde_json::to_string(&score).unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{
+        RequiredSkill,
+        skills::{calculate_skill_score, match_tuning},
+        experience::calculate_experience_score,
+        team_fit::calculate_team_fit_score,
+        culture::calculate_culture_score_offline,
+        calculate_talent_fit,
+    };
+
+    #[rocket::async_test]
+    async fn test_preview_matches_direct_scoring() {
+        let request: ScorePreviewRequest = serde_json::from_value(serde_json::json!({
+            "candidate": {
+                "skills": [
+                    {"name": "Rust", "level": "expert"},
+                    {"name": "Docker", "level": "advanced"}
+                ],
+                "experience": [
+                    {"title": "Backend Engineer", "company": "Acme", "duration": "5 years", "description": null}
+                ],
+                "developer_profile": "Collaborative engineer who enjoys mentoring"
+            },
+            "job": {
+                "title": "Senior Backend Engineer",
+                "description": "Collaborative team, lots of mentoring",
+                "required_skills": ["Rust", {"name": "Kubernetes", "level": "intermediate", "mandatory": false}],
+                "experience_level": "senior"
+            },
+            "deterministic": true
+        })).unwrap();
+
+        let preview = preview_score(&request).await;
+
+        let skills = vec![
+            CandidateSkill { name: "Rust".to_string(), level: "expert".to_string() },
+            CandidateSkill { name: "Docker".to_string(), level: "advanced".to_string() },
+        ];
+        let required = vec![
+            RequiredSkill { name: "Rust".to_string(), level: Some("intermediate".to_string()), mandatory: Some(true) },
+            RequiredSkill { name: "Kubernetes".to_string(), level: Some("intermediate".to_string()), mandatory: Some(false) },
+        ];
+        let experience = vec![CandidateExperience {
+            title: "Backend Engineer".to_string(),
+            company: "Acme".to_string(),
+            duration: "5 years".to_string(),
+            description: None,
+            start_date: None,
+            end_date: None,
+        }];
+        let direct = calculate_talent_fit(
+            calculate_skill_score(&skills, &required, match_tuning()),
+            calculate_experience_score(&experience, "senior", Some("Senior Backend Engineer")),
+            calculate_team_fit_score(&["Rust".to_string(), "Docker".to_string()], None, None, &[], None),
+            calculate_culture_score_offline(
+                Some("Collaborative engineer who enjoys mentoring"),
+                Some("Collaborative team, lots of mentoring"),
+                &[],
+            ),
+            None,
+        );
+
+        assert_eq!(serde_json::to_value(&preview).unwrap(), serde_json::to_value(&direct).unwrap());
+    }
+
+    #[rocket::async_test]
+    async fn test_preview_applies_custom_weights() {
+        let request: ScorePreviewRequest = serde_json::from_value(serde_json::json!({
+            "candidate": {"skills": [{"name": "Go", "level": "expert"}]},
+            "job": {"title": "Go Developer", "required_skills": ["Go"]},
+            "deterministic": true,
+            "weights": {"skills": 1.0, "experience": 0.0, "team_fit": 0.0, "culture": 0.0}
+        })).unwrap();
+
+        let preview = preview_score(&request).await;
+        assert_eq!(preview.total, preview.breakdown.skills.score);
+    }
+}
@@ -0,0 +1,165 @@
+use rocket::{http::Status, post, serde::json};
+use rocket::response::content::RawJson;
+use rocket_db_pools::Connection;
+use serde::Deserialize;
+use sqlx::{Connection as _, PgConnection};
+
+use crate::db::MainDatabase;
+use crate::endpoints::api_error::{err_json, DATABASE_ERROR, INVALID_REQUEST};
+use crate::matching::skills::{load_synonyms, normalize_skill, refresh_synonyms, SynonymMap};
+
+#[derive(Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct NewSkillSynonym {
+    /// The skill name the alias resolves to, e.g. "csharp"
+    canonical: String,
+    alias: String,
+}
+
+/// Normalized `(canonical, alias)`, or why the pair can't be stored
+/// Every name belongs to one group: an alias can't be a canonical name or another canonical's alias,
+/// and the canonical can't itself be an alias
+fn validate_synonym(canonical: &str, alias: &str, synonyms: &SynonymMap) -> Result<(String, String), String> {
+    let canonical = normalize_skill(canonical);
+    let alias = normalize_skill(alias);
+    if canonical.is_empty() || alias.is_empty() {
+        return Err("Both canonical and alias are required".to_string());
+    }
+    if canonical == alias {
+        return Err("Alias must differ from the canonical name".to_string());
+    }
+    if synonyms.contains_key(&alias) {
+        return Err(format!("\"{}\" is already a canonical skill", alias));
+    }
+    let group_of = |name: &str| {
+        synonyms
+            .iter()
+            .find(|(_, aliases)| aliases.iter().any(|a| a == name))
+            .map(|(canonical, _)| canonical.clone())
+    };
+    if let Some(owner) = group_of(&canonical) {
+        return Err(format!("\"{}\" is an alias of \"{}\"; add the alias to \"{}\" instead", canonical, owner, owner));
+    }
+    if let Some(owner) = group_of(&alias)
+        && owner != canonical
+    {
+        return Err(format!("\"{}\" is already an alias of \"{}\"", alias, owner));
+    }
+    Ok((canonical, alias))
+}
+
+/// Why an alias wasn't stored
+enum StoreSynonymError {
+    Invalid(String),
+    Database(sqlx::Error),
+}
+
+impl From<sqlx::Error> for StoreSynonymError {
+    fn from(e: sqlx::Error) -> Self {
+        StoreSynonymError::Database(e)
+    }
+}
+
+/// Validate an alias against the stored synonyms and store it, returning the normalized pair
+/// The table lock makes concurrent adds, from this or another instance, validate one after the other,
+/// so two requests can't each pass the checks and together put a name in two groups
+async fn store_synonym(conn: &mut PgConnection, canonical: &str, alias: &str) -> Result<(String, String), StoreSynonymError> {
+    let mut tx = conn.begin().await?;
+    sqlx::query("LOCK TABLE skill_synonyms IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *tx)
+        .await?;
+
+    let synonyms = load_synonyms(&mut tx).await?;
+    let (canonical, alias) = validate_synonym(canonical, alias, &synonyms).map_err(StoreSynonymError::Invalid)?;
+
+    // Validation already allows an existing alias only for the same canonical, so a conflict changes nothing
+    sqlx::query("INSERT INTO skill_synonyms (canonical, alias) VALUES ($1, $2) ON CONFLICT (alias) DO NOTHING")
+        .bind(&canonical)
+        .bind(&alias)
+        .execute(&mut *tx)
+        .await?;
+    tx.commit().await?;
+
+    Ok((canonical, alias))
+}
+
+/// Add a skill alias used by skill matching; takes effect immediately
+/// Not authenticated: like the rest of the API it relies on only trusted clients reaching the backend
+#[post("/skill-synonyms", data = "<data>")]
+pub async fn add_skill_synonym(data: json::Json<NewSkillSynonym>, mut db: Connection<MainDatabase>) -> (Status, RawJson<String>) {
+    let (canonical, alias) = match store_synonym(&mut db, &data.canonical, &data.alias).await {
+        Ok(pair) => pair,
+        Err(StoreSynonymError::Invalid(message)) => return (Status::BadRequest, err_json(INVALID_REQUEST, message)),
+        Err(StoreSynonymError::Database(e)) => {
+            return (Status::InternalServerError, err_json(DATABASE_ERROR, format!("Database error: {}", e)));
+        }
+    };
+    let synonyms = match refresh_synonyms(&mut db).await {
+        Ok(synonyms) => synonyms,
+        Err(e) => return (Status::InternalServerError, err_json(DATABASE_ERROR, format!("Database error: {}", e))),
+    };
+
+    (Status::Created, RawJson(serde_json::json!({
+        "canonical": canonical,
+        "alias": alias,
+        "aliases": synonyms.get(&canonical).cloned().unwrap_or_default(),
+    }).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::skills::default_synonyms;
+
+    #[test]
+    fn test_validate_synonym() {
+        let synonyms = default_synonyms();
+        assert_eq!(validate_synonym(" CSharp ", "C-Sharp", &synonyms), Ok(("csharp".to_string(), "c sharp".to_string())));
+        assert!(validate_synonym("rust", "", &synonyms).is_err());
+        assert!(validate_synonym("Rust", "rust", &synonyms).is_err());
+        // An alias can't hijack a built-in canonical name
+        assert!(validate_synonym("java", "javascript", &synonyms).is_err());
+    }
+
+    #[test]
+    fn test_names_stay_in_one_group() {
+        let synonyms = default_synonyms();
+
+        // "k8s" already resolves to kubernetes
+        assert_eq!(validate_synonym("docker", "K8s", &synonyms), Err("\"k8s\" is already an alias of \"kubernetes\"".to_string()));
+        // Re-adding an existing pair is fine
+        assert_eq!(validate_synonym("kubernetes", "k8s", &synonyms), Ok(("kubernetes".to_string(), "k8s".to_string())));
+        // The canonical side can't be an alias either
+        assert!(validate_synonym("JS", "jscript", &synonyms).unwrap_err().contains("alias of \"javascript\""));
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_store_validates_against_stored_synonyms() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        // Stored by another instance, so this process's in-memory synonyms don't know it yet
+        sqlx::query("INSERT INTO skill_synonyms (canonical, alias) VALUES ('zqxlang', 'zqx lang')")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let stolen = store_synonym(&mut tx, "zqxother", "ZQX-lang").await;
+        assert!(matches!(stolen, Err(StoreSynonymError::Invalid(message)) if message.contains("alias of \"zqxlang\"")));
+        let repointed: String = sqlx::query_scalar("SELECT canonical FROM skill_synonyms WHERE alias = 'zqx lang'")
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(repointed, "zqxlang");
+
+        // Re-adding the same pair is fine
+        assert!(matches!(store_synonym(&mut tx, "zqxlang", "zqx lang").await, Ok(pair) if pair == ("zqxlang".to_string(), "zqx lang".to_string())));
+
+        tx.rollback().await.unwrap();
+    }
+}
@@ -1,18 +1,36 @@
 use rocket::{post, serde::json};
+use rocket::http::{ContentType, Status};
 use rocket::response::content::RawJson;
+use rocket::response::stream::TextStream;
 use rocket_db_pools::Connection;
 use serde::{Deserialize, Serialize};
 use sqlx::Row;
-use std::collections::HashSet;
-use genai::{Client, chat::{ChatMessage, ChatOptions, ChatRequest}};
+use std::collections::{HashMap, HashSet};
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use futures::future::{BoxFuture, FutureExt, Shared};
+use futures::StreamExt;
+use sqlx::Connection as _;
+use genai::{Client, chat::{ChatMessage, ChatRequest}};
+use crate::ai_response::{log_fallback, parse_json, AiResponseError};
+use crate::ai_temperature::{chat_options, temperatures};
+use crate::candidate_id::{candidate_id, deterministic_candidate_id};
 use crate::db::MainDatabase;
+use crate::endpoints::api_error::{err_json, DATABASE_ERROR, INVALID_REQUEST, NOT_FOUND};
+use crate::endpoints::ep_candidates::{extract_github_username, spawn_github_enrichment, LinksInput};
+use crate::github::blocklist::analysis_blocklist;
+use crate::github::stats_cache::cached_languages;
+use crate::github::api::{get_user_profile, search_users, GitHubUser};
+use crate::github::stats::Reputation;
 use crate::matching::{
+    self,
     CandidateSkill, CandidateExperience, RequiredSkill, ExplainableScore,
-    skills::calculate_skill_score,
-    experience::calculate_experience_score,
-    team_fit::calculate_team_fit_score,
-    culture::calculate_culture_score,
-    calculate_talent_fit,
+    parse_required_skills,
+    TalentFitScore, ScoreWeights,
+    ScoringCandidate, ScoringJob, ScoringTeam, ScoringConfig,
+    location::{is_remote_location, LocationFit, LocationRequirement},
+    team_fit::{CodeCharacteristics, TeamFitWeights, TeamMemberProfile, WorkStyle},
 };
 
 const MODEL_GEMINI: &str = "gemini-2.0-flash";
@@ -46,6 +64,82 @@ struct ProfileSearchResult {
     actual_location: Option<String>,
 }
 
+/// Response schema version this client understands; sent to the scraping service and checked on replies
+const SCRAPER_SCHEMA_VERSION: u32 = 1;
+const SCRAPER_SCHEMA_HEADER: &str = "X-Scraper-Schema-Version";
+
+/// Profile search responses the scraping service may send: a bare array (v1) or a `{results: [...]}` envelope
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum ScraperResponse {
+    Bare(Vec<ProfileSearchResult>),
+    Envelope {
+        results: Vec<ProfileSearchResult>,
+        #[serde(default)]
+        schema_version: Option<u32>,
+    },
+}
+
+/// Why a scraping service response couldn't be used (an empty result list is not an error)
+#[derive(Debug, PartialEq)]
+enum ScraperResponseError {
+    /// The service advertised a schema version newer than this client understands
+    UnsupportedVersion(u32),
+    /// The body didn't match any known response shape
+    SchemaMismatch(String),
+}
+
+impl std::fmt::Display for ScraperResponseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(
+                f,
+                "Scraping service schema mismatch: response is v{} but this backend understands up to v{}",
+                version, SCRAPER_SCHEMA_VERSION
+            ),
+            Self::SchemaMismatch(detail) => write!(f, "Scraping service schema mismatch: {}", detail),
+        }
+    }
+}
+
+impl std::error::Error for ScraperResponseError {}
+
+/// Parse a profile search response, accepting either shape and ignoring unknown fields
+/// `header_version` is the value of the schema version response header, if the service sent one
+fn parse_scraper_response(
+    body: &str,
+    header_version: Option<&str>,
+) -> Result<Vec<ProfileSearchResult>, ScraperResponseError> {
+    let check_version = |version: u32| {
+        if version > SCRAPER_SCHEMA_VERSION {
+            Err(ScraperResponseError::UnsupportedVersion(version))
+        } else {
+            Ok(())
+        }
+    };
+
+    if let Some(version) = header_version.and_then(|v| v.trim().parse::<u32>().ok()) {
+        check_version(version)?;
+    }
+
+    match serde_json::from_str::<ScraperResponse>(body) {
+        Ok(ScraperResponse::Bare(results)) => Ok(results),
+        Ok(ScraperResponse::Envelope { results, schema_version }) => {
+            if let Some(version) = schema_version {
+                check_version(version)?;
+            }
+            Ok(results)
+        }
+        Err(_) => {
+            let preview: String = body.chars().take(120).collect();
+            Err(ScraperResponseError::SchemaMismatch(format!(
+                "expected a profile array or {{\"results\": [...]}}, got: {}",
+                preview
+            )))
+        }
+    }
+}
+
 #[derive(Deserialize, Debug)]
 #[allow(dead_code)]
 struct RelevanceFilterResult {
@@ -81,6 +175,7 @@ async fn search_linkedin_profiles(
 
     let response = client
         .post(format!("{}/api/search/profiles", scraping_url))
+        .header(SCRAPER_SCHEMA_HEADER, SCRAPER_SCHEMA_VERSION.to_string())
         .json(&request)
         .send()
         .await?;
@@ -89,7 +184,20 @@ async fn search_linkedin_profiles(
         return Err(format!("Scraping service error: {}", response.status()).into());
     }
 
-    let mut results: Vec<ProfileSearchResult> = response.json().await?;
+    let header_version = response
+        .headers()
+        .get(SCRAPER_SCHEMA_HEADER)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.to_string());
+    let body = response.text().await?;
+    let results = parse_scraper_response(&body, header_version.as_deref())?;
+
+    // Drop company pages, job listings, articles etc. before they reach the AI filter
+    let total = results.len();
+    let mut results = filter_profile_results(results);
+    if results.len() < total {
+        println!("[Sourcing] Dropped {} non-profile LinkedIn URLs", total - results.len());
+    }
 
     // Limit results to requested count
     results.truncate(count as usize);
@@ -97,6 +205,33 @@ async fn search_linkedin_profiles(
     Ok(results)
 }
 
+/// Check that a URL points at an individual LinkedIn profile (linkedin.com/in/<slug>)
+fn is_linkedin_profile_url(url: &str) -> bool {
+    let lower = url.trim().to_lowercase();
+    let without_scheme = lower
+        .strip_prefix("https://")
+        .or_else(|| lower.strip_prefix("http://"))
+        .unwrap_or(&lower);
+    let (host, path) = match without_scheme.split_once('/') {
+        Some(parts) => parts,
+        None => return false,
+    };
+
+    let is_linkedin_host = host == "linkedin.com" || host.ends_with(".linkedin.com");
+    let slug = path.strip_prefix("in/").unwrap_or("");
+    let slug = slug.split(['/', '?', '#']).next().unwrap_or("");
+
+    is_linkedin_host && !slug.is_empty()
+}
+
+/// Keep only results whose href is a LinkedIn profile URL
+fn filter_profile_results(results: Vec<ProfileSearchResult>) -> Vec<ProfileSearchResult> {
+    results
+        .into_iter()
+        .filter(|r| is_linkedin_profile_url(&r.href))
+        .collect()
+}
+
 // ============================================
 // AI-Powered Query Expansion
 // ============================================
@@ -105,11 +240,11 @@ async fn search_linkedin_profiles(
 // AI-Powered Relevance Filtering
 // ============================================
 
-const BATCH_RELEVANCE_PROMPT: &str = r#"Analyze these LinkedIn search results for a {job_title} position in {job_location}.
+const BATCH_RELEVANCE_PROMPT: &str = r#"Analyze these LinkedIn search results for a {job_title} position {job_location}.
 
 For each result, determine:
 1. Is this person in a role relevant to the job? (e.g., tech/engineering roles for tech jobs)
-2. Are they located in or near the target location?
+{location_check}
 
 Results to analyze:
 {results_json}
@@ -123,8 +258,7 @@ Return a JSON array with one entry per result (same order, same indices):
 
 Be strict - mark is_relevant as FALSE if:
 - They are in an unrelated profession (writer, marketing, sales, HR, recruiter, etc. for a tech job)
-- They are in a completely different country/region than the target location
-- The result is not actually a person's LinkedIn profile (company page, job listing, etc.)
+{location_rejection}- The result is not actually a person's LinkedIn profile (company page, job listing, etc.)
 - The description doesn't indicate they work in the relevant field
 
 For relevant candidates, extract:
@@ -149,33 +283,108 @@ Return ONLY a JSON array of strings, no explanation. Example:
 
 Return ONLY the JSON array:"#;
 
+/// How long expanded queries for a title are reused
+const QUERY_EXPANSION_TTL: Duration = Duration::from_secs(60 * 60);
+/// Cached titles kept before the cache is cleared
+const QUERY_EXPANSION_CACHE_CAPACITY: usize = 500;
+
+type VariationsResult = Result<Vec<String>, AiResponseError>;
+
+/// Query expansions by normalized job title, plus expansions still running
+/// Concurrent requests for the same title await one shared AI call; only successes are cached
+#[derive(Default)]
+struct ExpansionCache {
+    entries: HashMap<String, (Instant, Vec<String>)>,
+    in_flight: HashMap<String, Shared<BoxFuture<'static, VariationsResult>>>,
+}
+
+fn expansion_cache() -> &'static Mutex<ExpansionCache> {
+    static CACHE: OnceLock<Mutex<ExpansionCache>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Cache key: case and whitespace differences don't matter
+fn normalize_job_title(job_title: &str) -> String {
+    job_title.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Cached variations for a title, joining an in-flight expansion or starting one with `generate`
+async fn cached_variations<F, Fut>(
+    cache: &Mutex<ExpansionCache>,
+    job_title: &str,
+    ttl: Duration,
+    generate: F,
+) -> VariationsResult
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = VariationsResult> + Send + 'static,
+{
+    let key = normalize_job_title(job_title);
+
+    let expansion = {
+        let mut cache = cache.lock().unwrap();
+        if let Some((at, variations)) = cache.entries.get(&key)
+            && at.elapsed() < ttl
+        {
+            return Ok(variations.clone());
+        }
+        cache.in_flight
+            .entry(key.clone())
+            .or_insert_with(|| generate().boxed().shared())
+            .clone()
+    };
+
+    let result = expansion.clone().await;
+
+    // A newer expansion may have replaced this one (after the TTL lapsed); only clear our own
+    let mut cache = cache.lock().unwrap();
+    if cache.in_flight.get(&key).is_some_and(|current| current.ptr_eq(&expansion)) {
+        cache.in_flight.remove(&key);
+    }
+    if let Ok(variations) = &result {
+        if cache.entries.len() >= QUERY_EXPANSION_CACHE_CAPACITY {
+            cache.entries.clear();
+        }
+        cache.entries.insert(key, (Instant::now(), variations.clone()));
+    }
+    result
+}
+
 /// Use Gemini to generate alternative search queries for a job title
+/// Expansions are cached per title and shared between concurrent searches
 async fn expand_search_queries(job_title: &str) -> Vec<String> {
+    let title = job_title.to_string();
+    let variations = cached_variations(expansion_cache(), job_title, QUERY_EXPANSION_TTL, || async move {
+        generate_query_variations(&title).await
+    }).await;
+    queries_with_variations(job_title, variations)
+}
+
+/// The original title plus AI variations, or the built-in variations when the AI is unavailable
+fn queries_with_variations(job_title: &str, ai: Result<Vec<String>, AiResponseError>) -> Vec<String> {
     // Always include the original
     let mut queries = vec![job_title.to_string()];
 
-    // Try AI expansion
-    match generate_query_variations(job_title).await {
-        Ok(variations) => {
-            for v in variations {
-                if !queries.contains(&v) {
-                    queries.push(v);
-                }
-            }
-        }
+    let variations = match ai {
+        Ok(variations) => variations,
         Err(e) => {
-            println!("[Sourcing] Query expansion failed: {}, using fallback", e);
-            // Fallback: add common variations manually
-            queries.extend(get_fallback_variations(job_title));
+            log_fallback("Query expansion", &e, "built-in variations");
+            get_fallback_variations(job_title)
+        }
+    };
+
+    for v in variations {
+        if !queries.contains(&v) {
+            queries.push(v);
         }
     }
 
     queries
 }
 
-async fn generate_query_variations(job_title: &str) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+async fn generate_query_variations(job_title: &str) -> Result<Vec<String>, AiResponseError> {
     let client = Client::default();
-    let options = ChatOptions::default().with_temperature(0.3);
+    let options = chat_options(temperatures().query_expansion, None);
 
     let prompt = QUERY_EXPANSION_PROMPT.replace("{job_title}", job_title);
 
@@ -185,28 +394,13 @@ async fn generate_query_variations(job_title: &str) -> Result<Vec<String>, Box<d
 
     let chat_res = client
         .exec_chat(MODEL_GEMINI, chat_req, Some(&options))
-        .await?;
-
-    let response = chat_res
-        .content
-        .joined_texts()
-        .ok_or("No response from AI")?;
-
-    // Parse JSON array from response
-    let cleaned = response.trim();
-    let json_str = if cleaned.starts_with('[') {
-        cleaned.to_string()
-    } else if let Some(start) = cleaned.find('[') {
-        if let Some(end) = cleaned.rfind(']') {
-            cleaned[start..=end].to_string()
-        } else {
-            return Ok(vec![]);
-        }
-    } else {
-        return Ok(vec![]);
-    };
+        .await
+        .map_err(AiResponseError::from_request_error)?;
 
-    let variations: Vec<String> = serde_json::from_str(&json_str)?;
+    let variations: Vec<String> = parse_json(chat_res.content.joined_texts())?;
+    if variations.is_empty() {
+        return Err(AiResponseError::Empty);
+    }
     Ok(variations)
 }
 
@@ -296,6 +490,35 @@ fn get_fallback_variations(job_title: &str) -> Vec<String> {
     variations
 }
 
+/// Location sent to the scraper; remote roles search without one so results aren't limited to a place
+fn scraper_location(location: &str) -> &str {
+    if is_remote_location(location) { "" } else { location }
+}
+
+/// Relevance prompt for a job, leaving out the location criteria for remote roles
+fn relevance_prompt(job_title: &str, job_location: &str, results_json: &str) -> String {
+    let (scope, check, rejection) = if is_remote_location(job_location) {
+        (
+            "(remote - candidates may be located anywhere)".to_string(),
+            "Location does not matter for this role; do not reject anyone based on where they are.",
+            "",
+        )
+    } else {
+        (
+            format!("in {}", job_location),
+            "2. Are they located in or near the target location?",
+            "- They are in a completely different country/region than the target location\n",
+        )
+    };
+
+    BATCH_RELEVANCE_PROMPT
+        .replace("{job_title}", job_title)
+        .replace("{job_location}", &scope)
+        .replace("{location_check}", check)
+        .replace("{location_rejection}", rejection)
+        .replace("{results_json}", results_json)
+}
+
 /// Filter candidates using AI to check relevance to job and location
 async fn batch_filter_candidates(
     results: &[ProfileSearchResult],
@@ -320,64 +543,45 @@ async fn batch_filter_candidates(
     let results_json = serde_json::to_string_pretty(&results_for_prompt)
         .unwrap_or_else(|_| "[]".to_string());
 
-    let prompt = BATCH_RELEVANCE_PROMPT
-        .replace("{job_title}", job_title)
-        .replace("{job_location}", job_location)
-        .replace("{results_json}", &results_json);
+    let prompt = relevance_prompt(job_title, job_location, &results_json);
 
     let client = Client::default();
-    let options = ChatOptions::default().with_temperature(0.2);
+    let options = chat_options(temperatures().relevance_filter, None);
 
     let chat_req = ChatRequest::new(vec![
         ChatMessage::user(prompt),
     ]);
 
-    match client.exec_chat(MODEL_GEMINI, chat_req, Some(&options)).await {
-        Ok(chat_res) => {
-            if let Some(response) = chat_res.content.joined_texts() {
-                // Parse JSON array from response
-                let cleaned = response.trim();
-                let json_str = if cleaned.starts_with('[') {
-                    cleaned.to_string()
-                } else if let Some(start) = cleaned.find('[') {
-                    if let Some(end) = cleaned.rfind(']') {
-                        cleaned[start..=end].to_string()
-                    } else {
-                        println!("[Sourcing] AI filter response missing closing bracket");
-                        return fallback_filter(results);
-                    }
-                } else {
-                    println!("[Sourcing] AI filter response not valid JSON array");
-                    return fallback_filter(results);
-                };
-
-                match serde_json::from_str::<Vec<RelevanceFilterResult>>(&json_str) {
-                    Ok(filter_results) => {
-                        let relevant: Vec<(usize, String, String)> = filter_results
-                            .into_iter()
-                            .filter(|r| r.is_relevant)
-                            .map(|r| (
-                                r.index,
-                                r.actual_role.unwrap_or_else(|| "Unknown".to_string()),
-                                r.actual_location.unwrap_or_else(|| "Unknown".to_string()),
-                            ))
-                            .collect();
-
-                        println!("[Sourcing] AI filter: {} of {} candidates are relevant", relevant.len(), results.len());
-                        relevant
-                    }
-                    Err(e) => {
-                        println!("[Sourcing] Failed to parse AI filter response: {}. Using fallback.", e);
-                        fallback_filter(results)
-                    }
-                }
-            } else {
-                println!("[Sourcing] Empty AI filter response. Using fallback.");
-                fallback_filter(results)
-            }
+    let ai = match client.exec_chat(MODEL_GEMINI, chat_req, Some(&options)).await {
+        Ok(chat_res) => parse_json::<Vec<RelevanceFilterResult>>(chat_res.content.joined_texts()),
+        Err(e) => Err(AiResponseError::from_request_error(e)),
+    };
+
+    relevant_from_ai(results, ai)
+}
+
+/// Relevant (index, role, location) entries from the AI verdicts, or the keyword filter when the AI is unavailable
+fn relevant_from_ai(
+    results: &[ProfileSearchResult],
+    ai: Result<Vec<RelevanceFilterResult>, AiResponseError>,
+) -> Vec<(usize, String, String)> {
+    match ai {
+        Ok(filter_results) => {
+            let relevant: Vec<(usize, String, String)> = filter_results
+                .into_iter()
+                .filter(|r| r.is_relevant)
+                .map(|r| (
+                    r.index,
+                    r.actual_role.unwrap_or_else(|| "Unknown".to_string()),
+                    r.actual_location.unwrap_or_else(|| "Unknown".to_string()),
+                ))
+                .collect();
+
+            println!("[Sourcing] AI filter: {} of {} candidates are relevant", relevant.len(), results.len());
+            relevant
         }
         Err(e) => {
-            println!("[Sourcing] AI filter failed: {}. Using fallback.", e);
+            log_fallback("Relevance filter", &e, "keyword filter");
             fallback_filter(results)
         }
     }
@@ -441,7 +645,10 @@ async fn search_linkedin_with_expansion(
     let mut seen_hrefs: HashSet<String> = HashSet::new();
 
     for query in &queries {
-        match search_linkedin_profiles(query, location, count).await {
+        match search_linkedin_profiles(query, scraper_location(location), count).await {
+            Ok(results) if results.is_empty() => {
+                println!("[Sourcing] Query '{}': no results", query);
+            }
             Ok(results) => {
                 println!("[Sourcing] Query '{}': found {} profiles", query, results.len());
                 for result in results {
@@ -537,10 +744,76 @@ fn extract_skills_from_description(description: &str) -> Vec<CandidateSkill> {
     found_skills
 }
 
+/// Skill level for a language making up `percentage` of a sourced candidate's GitHub repos
+/// Coarser than `skills::github_language_level`, since sourcing only sees the languages breakdown
+fn sourcing_language_level(percentage: u32) -> &'static str {
+    match percentage {
+        41.. => "advanced",
+        15..=40 => "intermediate",
+        _ => "familiar",
+    }
+}
+
+/// Inferred skills with levels taken from the GitHub languages breakdown (percent of repos)
+/// Skills naming a language get its level instead of the flat default; other languages with any share are added
+fn merge_github_skills(desc_skills: Vec<CandidateSkill>, languages: &HashMap<String, u32>) -> Vec<CandidateSkill> {
+    let level_for = |name: &str| {
+        languages
+            .iter()
+            .find(|(language, _)| language.eq_ignore_ascii_case(name))
+            .map(|(_, percentage)| sourcing_language_level(*percentage))
+    };
+
+    let mut skills: Vec<CandidateSkill> = desc_skills
+        .into_iter()
+        .map(|skill| match level_for(&skill.name) {
+            Some(level) => CandidateSkill { level: level.to_string(), ..skill },
+            None => skill,
+        })
+        .collect();
+
+    let mut extra: Vec<(&String, u32)> = languages
+        .iter()
+        .filter(|(language, percentage)| **percentage > 0 && !skills.iter().any(|s| s.name.eq_ignore_ascii_case(language)))
+        .map(|(language, percentage)| (language, *percentage))
+        .collect();
+    extra.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+    skills.extend(extra.into_iter().map(|(language, percentage)| CandidateSkill {
+        name: language.clone(),
+        level: sourcing_language_level(percentage).to_string(),
+    }));
+    skills
+}
+
+/// Re-level the candidate's inferred skills from their GitHub languages, keeping `skills` in step
+fn apply_github_languages(candidate: &mut GeneratedCandidateData, languages: &HashMap<String, u32>) {
+    candidate.candidate_skills = merge_github_skills(std::mem::take(&mut candidate.candidate_skills), languages);
+    candidate.skills = candidate.candidate_skills.iter()
+        .map(|s| SkillMatch {
+            name: s.name.clone(),
+            level: s.level.clone(),
+            match_type: "inferred".to_string(),
+        })
+        .collect();
+}
+
+/// Use stored GitHub analyses (never a new one) to level the skills of candidates with a GitHub link
+async fn apply_cached_github_languages(candidates: &mut [GeneratedCandidateData], conn: &mut sqlx::PgConnection) {
+    for candidate in candidates.iter_mut() {
+        let Some(username) = candidate.links.github.as_deref().and_then(extract_github_username) else {
+            continue;
+        };
+        if let Some(languages) = cached_languages(conn, &username).await {
+            apply_github_languages(candidate, &languages);
+        }
+    }
+}
+
 /// Convert a DDG search result to candidate data
 fn convert_search_result_to_candidate(
     result: &ProfileSearchResult,
     source: &str,
+    deterministic_id: bool,
 ) -> Option<GeneratedCandidateData> {
     let title = result.title.as_ref()?;
     let (name, parsed_job_title) = parse_linkedin_title(title);
@@ -584,13 +857,15 @@ fn convert_search_result_to_candidate(
         company: "Unknown".to_string(),
         duration: "1 year".to_string(), // Default assumption
         description: Some(description.to_string()),
+        start_date: None,
+        end_date: None,
     }];
 
     // Extract LinkedIn username for links
     let linkedin_url = result.href.clone();
 
     Some(GeneratedCandidateData {
-        id: uuid::Uuid::new_v4().to_string(),
+        id: candidate_id(deterministic_id, None, Some(&linkedin_url)).to_string(),
         name,
         title: job_title,
         location,
@@ -608,6 +883,202 @@ fn convert_search_result_to_candidate(
     })
 }
 
+// ============================================
+// GitHub User Search
+// ============================================
+
+/// GitHub profile requests in flight at once while loading search results
+const PROFILE_LOOKUP_CONCURRENCY: usize = 5;
+
+/// Skill names GitHub recognizes as a `language:` qualifier, with the spelling it expects
+const GITHUB_LANGUAGES: &[(&str, &str)] = &[
+    ("rust", "Rust"), ("python", "Python"), ("javascript", "JavaScript"), ("typescript", "TypeScript"),
+    ("go", "Go"), ("golang", "Go"), ("java", "Java"), ("kotlin", "Kotlin"), ("swift", "Swift"),
+    ("c++", "C++"), ("c#", "C#"), ("c", "C"), ("ruby", "Ruby"), ("php", "PHP"), ("scala", "Scala"),
+    ("elixir", "Elixir"), ("haskell", "Haskell"), ("dart", "Dart"), ("clojure", "Clojure"),
+];
+
+/// Programming languages among the job's required skills, mandatory ones first
+fn github_languages(required_skills: &[RequiredSkill]) -> Vec<String> {
+    let mut skills: Vec<&RequiredSkill> = required_skills.iter().collect();
+    skills.sort_by_key(|s| !s.mandatory.unwrap_or(true));
+
+    let mut languages: Vec<String> = Vec::new();
+    for skill in skills {
+        let name = skill.name.trim().to_lowercase();
+        if let Some((_, language)) = GITHUB_LANGUAGES.iter().find(|(alias, _)| *alias == name)
+            && !languages.iter().any(|l| l == language)
+        {
+            languages.push(language.to_string());
+        }
+    }
+    languages
+}
+
+/// User search query for a job: its main language and location, or the role in bios when no language is known
+fn github_user_query(role: &str, location: Option<&str>, languages: &[String]) -> String {
+    let quote = |value: &str| {
+        let value = value.replace('"', "");
+        if value.contains(' ') { format!("\"{}\"", value) } else { value }
+    };
+
+    let mut parts = match languages.first() {
+        Some(language) => vec![format!("language:{}", quote(language))],
+        None => vec![role.to_string(), "in:bio".to_string()],
+    };
+    if let Some(location) = location.map(str::trim).filter(|l| !l.is_empty() && !is_remote_location(l)) {
+        // GitHub locations are free text, so only the city part of "Sydney, Australia" is matched
+        let city = location.split(',').next().unwrap_or(location).trim();
+        parts.push(format!("location:{}", quote(city)));
+    }
+    parts.push("type:user".to_string());
+    parts.join(" ")
+}
+
+/// Search GitHub for users and fetch their profiles; profiles that fail to load are skipped
+async fn search_github_users(
+    query: &str,
+    count: i32,
+    token: &str,
+) -> Result<Vec<GitHubUser>, Box<dyn std::error::Error + Send + Sync>> {
+    let logins = search_users(query, count.max(1) as u32, token).await?;
+    Ok(load_profiles(logins, |login| async move { get_user_profile(&login, token).await }).await)
+}
+
+/// Profiles for `logins` in search order, at most PROFILE_LOOKUP_CONCURRENCY requests at a time
+async fn load_profiles<F, Fut>(logins: Vec<String>, fetch_profile: F) -> Vec<GitHubUser>
+where
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<GitHubUser, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    futures::stream::iter(logins)
+        .map(|login| {
+            let profile = fetch_profile(login.clone());
+            async move { (login, profile.await) }
+        })
+        .buffered(PROFILE_LOOKUP_CONCURRENCY)
+        .filter_map(|(login, profile)| async move {
+            profile.map_err(|e| println!("[Sourcing] Skipping GitHub user {}: {}", login, e)).ok()
+        })
+        .collect()
+        .await
+}
+
+/// Convert a GitHub profile to candidate data; the searched language counts as one of their skills
+fn convert_github_user_to_candidate(
+    user: &GitHubUser,
+    languages: &[String],
+    deterministic_id: bool,
+) -> GeneratedCandidateData {
+    let github_url = format!("https://github.com/{}", user.login);
+    let bio = user.bio.as_deref().map(str::trim).unwrap_or("");
+
+    let mut candidate_skills: Vec<CandidateSkill> = languages
+        .first()
+        .map(|language| CandidateSkill { name: language.clone(), level: "intermediate".to_string() })
+        .into_iter()
+        .collect();
+    for skill in extract_skills_from_description(bio) {
+        if !candidate_skills.iter().any(|s| s.name == skill.name) {
+            candidate_skills.push(skill);
+        }
+    }
+    let skills = candidate_skills.iter()
+        .map(|s| SkillMatch {
+            name: s.name.clone(),
+            level: s.level.clone(),
+            match_type: "inferred".to_string(),
+        })
+        .collect();
+
+    let id = if deterministic_id {
+        deterministic_candidate_id(&github_url.to_lowercase())
+    } else {
+        uuid::Uuid::new_v4()
+    };
+
+    GeneratedCandidateData {
+        id: id.to_string(),
+        name: user.name.clone().filter(|n| !n.trim().is_empty()).unwrap_or_else(|| user.login.clone()),
+        title: if bio.is_empty() { "Unknown".to_string() } else { bio.to_string() },
+        location: user.location.clone().unwrap_or_else(|| "Unknown".to_string()),
+        skills,
+        candidate_skills,
+        experience: vec![],
+        candidate_experience: vec![],
+        education: vec![],
+        links: Links {
+            github: Some(github_url),
+            linkedin: None,
+            portfolio: user.blog.clone().filter(|b| !b.trim().is_empty()),
+        },
+        source: "github".to_string(),
+    }
+}
+
+/// Find GitHub users for the query with `fetch_users(q, count)` and convert them to candidates
+async fn search_github_candidates<F, Fut>(query: &CandidateQuery, fetch_users: F) -> Vec<GeneratedCandidateData>
+where
+    F: FnOnce(String, i32) -> Fut,
+    Fut: Future<Output = Result<Vec<GitHubUser>, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let q = github_user_query(&query.job_title, query.location.as_deref(), &query.languages);
+    println!("[Sourcing] Searching GitHub users: {}", q);
+
+    match fetch_users(q, query.count).await {
+        Ok(users) => users
+            .iter()
+            .map(|user| convert_github_user_to_candidate(user, &query.languages, query.deterministic_ids))
+            .collect(),
+        Err(e) => {
+            println!("[Sourcing] GitHub user search failed: {}", e);
+            vec![]
+        }
+    }
+}
+
+/// Lowercased name with collapsed whitespace, for spotting the same person across sources
+fn candidate_name_key(name: &str) -> String {
+    name.split_whitespace().collect::<Vec<_>>().join(" ").to_lowercase()
+}
+
+/// Profile URL without scheme, "www.", query or trailing slash, lowercased, for comparing links
+fn profile_url_key(url: &str) -> String {
+    let url = url.trim().to_lowercase();
+    let url = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+    let url = url.strip_prefix("www.").unwrap_or(url);
+    let url = url.split(['?', '#']).next().unwrap_or(url);
+    url.trim_end_matches('/').to_string()
+}
+
+/// Whether a GitHub profile's website is the existing candidate's LinkedIn or portfolio page
+fn links_corroborate(existing: &Links, github: &Links) -> bool {
+    let Some(website) = github.portfolio.as_deref().map(profile_url_key) else {
+        return false;
+    };
+    [existing.linkedin.as_deref(), existing.portfolio.as_deref()]
+        .into_iter()
+        .flatten()
+        .any(|url| profile_url_key(url) == website)
+}
+
+/// Add GitHub candidates to the LinkedIn ones; someone found on both keeps the LinkedIn record
+/// A name match alone could be a different person, so the GitHub link is only attached when the GitHub
+/// profile's website points at the LinkedIn record's profile; otherwise the GitHub duplicate is dropped
+fn merge_github_candidates(candidates: &mut Vec<GeneratedCandidateData>, github: Vec<GeneratedCandidateData>) {
+    for candidate in github {
+        let key = candidate_name_key(&candidate.name);
+        match candidates.iter_mut().find(|c| candidate_name_key(&c.name) == key) {
+            Some(existing) => {
+                if existing.links.github.is_none() && links_corroborate(&existing.links, &candidate.links) {
+                    existing.links.github = candidate.links.github;
+                }
+            }
+            None => candidates.push(candidate),
+        }
+    }
+}
+
 #[derive(Deserialize)]
 #[serde(crate = "rocket::serde")]
 pub struct SourcingRequest {
@@ -615,6 +1086,14 @@ pub struct SourcingRequest {
     team_id: Option<String>,
     sources: Vec<String>,
     count: i32,
+    /// Derive candidate IDs from LinkedIn URLs so repeated searches return the same IDs
+    #[serde(default)]
+    deterministic_ids: bool,
+    /// Override the configured temperature for AI scoring (0.0-2.0)
+    temperature: Option<f64>,
+    /// Save the scored candidates to sourced_candidates; their `id`s are then the stored rows' ids
+    #[serde(default)]
+    persist: Option<bool>,
 }
 
 #[derive(Serialize)]
@@ -632,6 +1111,86 @@ struct SourcingResult {
     source: String,
 }
 
+/// Score breakdown in the shape `POST /candidates` stores (`ScoreBreakdownInput`)
+fn stored_score_breakdown(breakdown: &ScoreBreakdown) -> serde_json::Value {
+    serde_json::json!({
+        "skillsMatch": breakdown.skills.score,
+        "experienceMatch": breakdown.experience.score,
+        "workStyleAlignment": breakdown.culture.score,
+        "teamFit": breakdown.team_fit.score,
+    })
+}
+
+/// The profile link identifying a sourced person for its source, as (links key, URL)
+fn dedupe_link(result: &SourcingResult) -> Option<(&'static str, &str)> {
+    match result.source.as_str() {
+        "github" => result.links.github.as_deref().map(|url| ("github", url)),
+        _ => result.links.linkedin.as_deref().map(|url| ("linkedin", url)),
+    }
+}
+
+/// Save a scored candidate like `POST /candidates` would, returning the stored row's id
+/// A candidate whose profile link is already stored for the same source is not inserted again; the existing id is returned.
+/// The (source, source_link) unique index keeps concurrent searches from storing the same profile twice
+async fn persist_candidate(result: &SourcingResult, conn: &mut sqlx::PgConnection) -> Result<uuid::Uuid, sqlx::Error> {
+    let id = uuid::Uuid::parse_str(&result.id).unwrap_or_else(|_| uuid::Uuid::new_v4());
+    let links = LinksInput::new(result.links.github.clone(), result.links.linkedin.clone(), result.links.portfolio.clone());
+    // Blocklisted accounts are never analyzed
+    let github_username = result.links.github.as_deref()
+        .and_then(extract_github_username)
+        .filter(|username| !analysis_blocklist().is_blocked(username));
+    let initial_status = if github_username.is_some() { "analyzing" } else { "complete" };
+
+    // The no-op update makes RETURNING yield the existing row's id on conflict; xmax is 0 only for a new row
+    let row = sqlx::query(
+        r#"INSERT INTO sourced_candidates
+           (id, name, email, phone, location, title, skills, experience, education, links, talent_fit_score, score_breakdown, resume_file_name, source, analysis_status, work_style, source_link)
+           VALUES ($1, $2, NULL, NULL, $3, $4, $5, $6, $7, $8, $9, $10, NULL, $11, $12, NULL, $13)
+           ON CONFLICT (source, source_link) DO UPDATE SET source_link = EXCLUDED.source_link
+           RETURNING id, (xmax = 0) AS inserted"#
+    )
+    .bind(id)
+    .bind(&result.name)
+    .bind(&result.location)
+    .bind(&result.title)
+    .bind(serde_json::to_value(&result.skills).unwrap())
+    .bind(serde_json::to_value(&result.experience).unwrap())
+    .bind(serde_json::to_value(&result.education).unwrap())
+    .bind(serde_json::to_value(&links).unwrap())
+    .bind(result.talent_fit_score)
+    .bind(stored_score_breakdown(&result.score_breakdown))
+    .bind(&result.source)
+    .bind(initial_status)
+    .bind(dedupe_link(result).map(|(_, url)| url))
+    .fetch_one(&mut *conn)
+    .await?;
+
+    let id: uuid::Uuid = row.get("id");
+    if !row.get::<bool, _>("inserted") {
+        return Ok(id);
+    }
+
+    // Enrich from GitHub in the background, as for candidates added by hand
+    if let Some(username) = github_username
+        && !spawn_github_enrichment(id, username, links)
+    {
+        let _ = sqlx::query("UPDATE sourced_candidates SET analysis_status = 'complete' WHERE id = $1")
+            .bind(id)
+            .execute(&mut *conn)
+            .await;
+    }
+
+    Ok(id)
+}
+
+/// `persist_candidate`, pointing the result's `id` at the stored row; a failed save is logged and the result kept unsaved
+async fn persist_result(result: &mut SourcingResult, conn: &mut sqlx::PgConnection) {
+    match persist_candidate(result, conn).await {
+        Ok(id) => result.id = id.to_string(),
+        Err(e) => println!("[Sourcing] Failed to save candidate {}: {}", result.name, e),
+    }
+}
+
 #[derive(Serialize)]
 struct SkillMatch {
     name: String,
@@ -667,6 +1226,9 @@ struct ScoreBreakdown {
     experience: ScoreDetail,
     team_fit: ScoreDetail,
     culture: ScoreDetail,
+    team_fit_excluded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    location: Option<LocationFit>,
 }
 
 #[derive(Serialize)]
@@ -697,32 +1259,60 @@ struct JobData {
     title: String,
     description: Option<String>,
     location: Option<String>,
+    team_fit_weights: TeamFitWeights,
+    location_requirement: Option<LocationRequirement>,
+    /// Per-job component weights; the 40/30/20/10 defaults when None
+    score_weights: Option<ScoreWeights>,
 }
 
 /// Team member profile for compatibility scoring
+#[derive(Clone)]
 struct TeamMemberData {
     developer_profile: Option<String>,
+    profile: TeamMemberProfile,
 }
 
-/// Parse required_skills from JSONB - supports both legacy and enhanced formats
-fn parse_required_skills(json_value: &serde_json::Value) -> Vec<RequiredSkill> {
-    match json_value.as_array() {
-        Some(arr) => arr.iter().filter_map(|item| {
-            if let Some(s) = item.as_str() {
-                // Legacy format: just a string
-                Some(RequiredSkill {
-                    name: s.to_string(),
-                    level: Some("intermediate".to_string()),
-                    mandatory: Some(true),
-                })
-            } else if item.is_object() {
-                // Enhanced format: object with name, level, mandatory
-                serde_json::from_value(item.clone()).ok()
-            } else {
-                None
-            }
-        }).collect(),
-        None => vec![],
+impl TeamMemberData {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Self {
+        let json = |column: &str| row.get::<Option<serde_json::Value>, _>(column);
+        let skills: Vec<CandidateSkill> = json("skills").and_then(|v| serde_json::from_value(v).ok()).unwrap_or_default();
+        TeamMemberData {
+            developer_profile: row.get("developer_profile"),
+            profile: TeamMemberProfile {
+                skills: skills.into_iter().map(|s| s.name).collect(),
+                experience_level: row.get::<Option<String>, _>("experience_level")
+                    .unwrap_or_else(|| "mid".to_string())
+                    .to_lowercase(),
+                work_style: json("work_style").and_then(|v| serde_json::from_value(v).ok()),
+                code_characteristics: json("code_characteristics").and_then(|v| serde_json::from_value(v).ok()),
+            },
+        }
+    }
+}
+
+/// A team's members with what culture and team fit scoring need
+async fn load_team_members(conn: &mut sqlx::PgConnection, team_id: uuid::Uuid) -> Result<Vec<TeamMemberData>, sqlx::Error> {
+    let rows = sqlx::query(
+        "SELECT developer_profile, skills, experience_level, work_style, code_characteristics FROM team_members WHERE team_id = $1"
+    )
+    .bind(team_id)
+    .fetch_all(conn)
+    .await?;
+    Ok(rows.iter().map(TeamMemberData::from_row).collect())
+}
+
+fn job_data_from_row(row: &sqlx::postgres::PgRow) -> JobData {
+    let skills_json: serde_json::Value = row.get("required_skills");
+    JobData {
+        required_skills: parse_required_skills(&skills_json),
+        experience_level: row.get::<Option<String>, _>("experience_level")
+            .unwrap_or_else(|| "any".to_string()),
+        title: row.get("title"),
+        description: row.get("description"),
+        location: row.get("location"),
+        team_fit_weights: TeamFitWeights::from_stored(row.get("team_fit_weights")),
+        location_requirement: LocationRequirement::from_stored(row.get("location_requirement")),
+        score_weights: ScoreWeights::from_stored(row.get("score_weights")),
     }
 }
 
@@ -741,50 +1331,58 @@ struct GeneratedCandidateData {
     source: String,
 }
 
+/// Run the matching pipeline for one candidate against one job
+/// `team_members` is None when no team is assigned; team fit is then skipped rather than scored neutrally
+/// `temperature` overrides the configured temperature for the AI scoring calls
+async fn score_against_job(
+    candidate: &ScoringCandidate<'_>,
+    job_data: &JobData,
+    team_members: Option<&[TeamMemberData]>,
+    temperature: Option<f64>,
+) -> TalentFitScore {
+    let team_profiles: Option<(Vec<String>, Vec<TeamMemberProfile>)> = team_members.map(|members| (
+        members.iter().filter_map(|m| m.developer_profile.clone()).collect(),
+        members.iter().map(|m| m.profile.clone()).collect(),
+    ));
+    let team = team_profiles.as_ref().map(|(profiles, members)| ScoringTeam { profiles, members });
+
+    matching::score(
+        candidate,
+        &ScoringJob {
+            title: &job_data.title,
+            description: job_data.description.as_deref(),
+            required_skills: &job_data.required_skills,
+            experience_level: &job_data.experience_level,
+            team_fit_weights: &job_data.team_fit_weights,
+            location_requirement: job_data.location_requirement.as_ref(),
+        },
+        team.as_ref(),
+        &ScoringConfig { weights: job_data.score_weights.clone(), temperature, ..ScoringConfig::default() },
+    ).await
+}
+
 async fn score_candidate(
     data: GeneratedCandidateData,
     job_data: &JobData,
-    team_members: &[TeamMemberData],
+    team_members: Option<&[TeamMemberData]>,
+    temperature: Option<f64>,
 ) -> SourcingResult {
-    // 1. Skills score
-    let skills_score = calculate_skill_score(&data.candidate_skills, &job_data.required_skills);
-
-    // 2. Experience score
-    let experience_score = calculate_experience_score(
-        &data.candidate_experience,
-        &job_data.experience_level,
-        Some(&job_data.title),
-    );
-
-    // 3. Team fit score (minimal for now since we don't have full team data)
-    let candidate_skill_names: Vec<String> = data.candidate_skills.iter().map(|s| s.name.clone()).collect();
-    let team_fit_score = calculate_team_fit_score(
-        &candidate_skill_names,
-        None, // candidate_work_style
-        None, // candidate_code_style
-        &[], // team_members - would need full TeamMemberProfile
-        None, // ideal_profile
-    );
-
-    // 4. Culture score (AI-powered)
-    let team_profiles: Vec<String> = team_members.iter()
-        .filter_map(|m| m.developer_profile.clone())
-        .collect();
-    let culture_score = calculate_culture_score(
-        None, // candidate_profile
-        job_data.description.as_deref(),
-        &team_profiles,
+    let candidate = ScoringCandidate {
+        skills: &data.candidate_skills,
+        experience: &data.candidate_experience,
+        developer_profile: None,
+        reputation: None,
+        location: Some(&data.location),
+        work_style: None,
+        code_characteristics: None,
+    };
+    let talent_fit = score_against_job(
+        &candidate,
+        job_data,
+        team_members,
+        temperature,
     ).await;
 
-    // 5. Aggregate scores
-    let talent_fit = calculate_talent_fit(
-        skills_score,
-        experience_score,
-        team_fit_score,
-        culture_score,
-        None, // use default weights
-    );
-
     SourcingResult {
         id: data.id,
         name: data.name,
@@ -800,49 +1398,71 @@ async fn score_candidate(
             experience: talent_fit.breakdown.experience.into(),
             team_fit: talent_fit.breakdown.team_fit.into(),
             culture: talent_fit.breakdown.culture.into(),
+            team_fit_excluded: talent_fit.breakdown.team_fit_excluded,
+            location: talent_fit.breakdown.location,
         },
         source: data.source,
     }
 }
 
-#[post("/sourcing/search", data = "<data>")]
-pub async fn search_candidates(
-    data: json::Json<SourcingRequest>,
-    mut db: Connection<MainDatabase>,
-) -> RawJson<String> {
-    let count = data.count.min(50).max(1);
-    let sources = if data.sources.is_empty() {
-        vec!["github".to_string(), "linkedin".to_string()]
-    } else {
-        data.sources.clone()
-    };
+/// Where and how many profiles to search for, and on which sources
+#[derive(Clone)]
+struct CandidateQuery {
+    job_title: String,
+    location: Option<String>,
+    linkedin: bool,
+    github: bool,
+    /// Languages from the job's required skills, used to search GitHub
+    languages: Vec<String>,
+    count: i32,
+    deterministic_ids: bool,
+}
+
+impl CandidateQuery {
+    /// Query for a sourcing request against the job; no sources means GitHub and LinkedIn
+    fn for_request(data: &SourcingRequest, job_data: &JobData) -> Self {
+        let uses = |source: &str| data.sources.is_empty() || data.sources.iter().any(|s| s.trim().eq_ignore_ascii_case(source));
+        CandidateQuery {
+            job_title: job_data.title.clone(),
+            location: job_data.location.clone(),
+            linkedin: uses("linkedin"),
+            github: uses("github"),
+            languages: github_languages(&job_data.required_skills),
+            count: data.count.clamp(1, 50),
+            deterministic_ids: data.deterministic_ids,
+        }
+    }
+}
 
+/// Job, team and options a sourcing search scores against
+struct SourcingContext {
+    job_data: JobData,
+    team_members: Option<Vec<TeamMemberData>>,
+    query: CandidateQuery,
+    temperature: Option<f64>,
+}
+
+/// Load the job and team for a sourcing request
+async fn load_sourcing_context(data: &SourcingRequest, db: &mut Connection<MainDatabase>) -> SourcingContext {
     // Fetch job data from database
     let job_data = if let Ok(job_uuid) = uuid::Uuid::parse_str(&data.job_id) {
         match sqlx::query(
-            r#"SELECT title, description, location, required_skills, experience_level FROM jobs WHERE id = $1"#
+            r#"SELECT title, description, location, required_skills, experience_level, team_fit_weights, location_requirement, score_weights FROM jobs WHERE id = $1"#
         )
         .bind(job_uuid)
-        .fetch_optional(&mut **db)
+        .fetch_optional(&mut ***db)
         .await
         {
-            Ok(Some(row)) => {
-                let skills_json: serde_json::Value = row.get("required_skills");
-                JobData {
-                    required_skills: parse_required_skills(&skills_json),
-                    experience_level: row.get::<Option<String>, _>("experience_level")
-                        .unwrap_or_else(|| "any".to_string()),
-                    title: row.get("title"),
-                    description: row.get("description"),
-                    location: row.get("location"),
-                }
-            }
+            Ok(Some(row)) => job_data_from_row(&row),
             _ => JobData {
                 required_skills: vec![],
                 experience_level: "any".to_string(),
                 title: "Unknown Position".to_string(),
                 description: None,
                 location: None,
+                team_fit_weights: TeamFitWeights::default(),
+                location_requirement: None,
+                score_weights: None,
             },
         }
     } else {
@@ -852,60 +1472,1184 @@ pub async fn search_candidates(
             title: "Unknown Position".to_string(),
             description: None,
             location: None,
+            team_fit_weights: TeamFitWeights::default(),
+            location_requirement: None,
+            score_weights: None,
         }
     };
 
-    // Fetch team member profiles if team_id is provided
-    let team_members: Vec<TeamMemberData> = if let Some(ref team_id) = data.team_id {
+    // Fetch team member profiles if team_id is provided (None = no team, team fit is skipped)
+    let team_id = data.team_id.as_deref().map(str::trim).filter(|id| !id.is_empty());
+    let team_members: Option<Vec<TeamMemberData>> = if let Some(team_id) = team_id {
         if let Ok(team_uuid) = uuid::Uuid::parse_str(team_id) {
-            match sqlx::query(
-                r#"SELECT developer_profile FROM team_members WHERE team_id = $1"#
-            )
-            .bind(team_uuid)
-            .fetch_all(&mut **db)
-            .await
-            {
-                Ok(rows) => rows.iter().map(|r| TeamMemberData {
-                    developer_profile: r.get("developer_profile"),
-                }).collect(),
-                Err(_) => vec![],
-            }
+            Some(load_team_members(db, team_uuid).await.unwrap_or_default())
         } else {
-            vec![]
+            Some(vec![])
         }
     } else {
-        vec![]
+        None
+    };
+
+    let query = CandidateQuery::for_request(data, &job_data);
+
+    SourcingContext { job_data, team_members, query, temperature: data.temperature }
+}
+
+/// Search for profiles matching the job and convert them to (unscored) candidates
+async fn find_candidates(query: CandidateQuery) -> Vec<GeneratedCandidateData> {
+    let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
+    let github_search = |q: String, count: i32| async move {
+        if token.is_empty() {
+            return Err("GitHub token not configured".into());
+        }
+        search_github_users(&q, count, &token).await
     };
+    gather_candidates(query, github_search).await
+}
 
+/// LinkedIn candidates followed by GitHub ones (merged by name), from whichever sources the query uses
+async fn gather_candidates<F, Fut>(query: CandidateQuery, fetch_github_users: F) -> Vec<GeneratedCandidateData>
+where
+    F: FnOnce(String, i32) -> Fut,
+    Fut: Future<Output = Result<Vec<GitHubUser>, Box<dyn std::error::Error + Send + Sync>>>,
+{
     // Try to get real candidates from DDG search if linkedin is in sources
     let mut candidate_data: Vec<GeneratedCandidateData> = Vec::new();
 
-    if sources.contains(&"linkedin".to_string()) {
+    if query.linkedin {
         // Use job title as the search role with AI-powered query expansion
-        let search_role = &job_data.title;
+        let search_role = &query.job_title;
 
         // Use job location if available, otherwise default to broad search
-        let search_location = job_data.location.as_deref().unwrap_or("Australia");
+        let search_location = query.location.as_deref().unwrap_or("Australia");
 
         println!("[Sourcing] Starting LinkedIn search with query expansion for: {} in {}", search_role, search_location);
 
         // Use expanded search with multiple query variations
-        let results = search_linkedin_with_expansion(search_role, search_location, count).await;
+        let results = search_linkedin_with_expansion(search_role, search_location, query.count).await;
 
         println!("[Sourcing] Found {} total unique LinkedIn profiles", results.len());
         for result in &results {
-            if let Some(candidate) = convert_search_result_to_candidate(result, "linkedin") {
+            if let Some(candidate) = convert_search_result_to_candidate(result, "linkedin", query.deterministic_ids) {
                 candidate_data.push(candidate);
             }
         }
     }
 
+    if query.github {
+        let github = search_github_candidates(&query, fetch_github_users).await;
+        println!("[Sourcing] Found {} GitHub users", github.len());
+        merge_github_candidates(&mut candidate_data, github);
+    }
+
+    candidate_data
+}
+
+/// Summary sent as the last line of a streamed search
+#[derive(Serialize, Debug, Default, PartialEq)]
+struct SourcingStats {
+    candidates: usize,
+    top_score: Option<i32>,
+    average_score: Option<i32>,
+    elapsed_ms: u128,
+    #[serde(skip)]
+    score_sum: i64,
+}
+
+impl SourcingStats {
+    fn record(&mut self, score: i32) {
+        self.candidates += 1;
+        self.score_sum += score as i64;
+        self.top_score = Some(self.top_score.map_or(score, |top| top.max(score)));
+        self.average_score = Some((self.score_sum as f64 / self.candidates as f64).round() as i32);
+    }
+}
+
+/// One line of a streamed search
+#[derive(Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+enum SourcingEvent {
+    Candidate(Box<SourcingResult>),
+    SourcingStats(SourcingStats),
+}
+
+impl SourcingEvent {
+    fn to_line(&self) -> String {
+        format!("{}\n", serde_json::to_string(self).unwrap())
+    }
+}
+
+/// Newline-delimited JSON: a `candidate` event as soon as each candidate is scored, then `sourcing_stats`
+/// `found` also yields the connection to persist candidates through, when the request asked for it
+fn sourcing_event_stream(
+    context: SourcingContext,
+    found: impl Future<Output = (Vec<GeneratedCandidateData>, Option<Connection<MainDatabase>>)> + Send + 'static,
+) -> TextStream![String] {
+    TextStream! {
+        let started = Instant::now();
+        let mut stats = SourcingStats::default();
+        let (found, mut persist_db) = found.await;
+
+        for generated in found {
+            let mut candidate = score_candidate(generated, &context.job_data, context.team_members.as_deref(), context.temperature).await;
+            if let Some(db) = persist_db.as_mut() {
+                persist_result(&mut candidate, db).await;
+            }
+            stats.record(candidate.talent_fit_score);
+            yield SourcingEvent::Candidate(Box::new(candidate)).to_line();
+        }
+
+        stats.elapsed_ms = started.elapsed().as_millis();
+        yield SourcingEvent::SourcingStats(stats).to_line();
+    }
+}
+
+#[post("/sourcing/search", data = "<data>")]
+pub async fn search_candidates(
+    data: json::Json<SourcingRequest>,
+    mut db: Connection<MainDatabase>,
+) -> RawJson<String> {
+    let context = load_sourcing_context(&data, &mut db).await;
+    let mut candidate_data = find_candidates(context.query.clone()).await;
+    apply_cached_github_languages(&mut candidate_data, &mut db).await;
+
     // Score each candidate (async - may call AI services)
     let mut candidates: Vec<SourcingResult> = Vec::new();
-    for data in candidate_data {
-        let candidate = score_candidate(data, &job_data, &team_members).await;
+    for generated in candidate_data {
+        let mut candidate = score_candidate(generated, &context.job_data, context.team_members.as_deref(), context.temperature).await;
+        if data.persist == Some(true) {
+            persist_result(&mut candidate, &mut db).await;
+        }
         candidates.push(candidate);
     }
 
     RawJson(serde_json::to_string(&candidates).unwrap())
 }
+
+/// Same search as `/sourcing/search`, streamed as newline-delimited JSON while candidates are scored
+#[post("/sourcing/search/stream", data = "<data>")]
+pub async fn search_candidates_stream(
+    data: json::Json<SourcingRequest>,
+    mut db: Connection<MainDatabase>,
+) -> (ContentType, TextStream![String]) {
+    let context = load_sourcing_context(&data, &mut db).await;
+    let query = context.query.clone();
+    let persist = data.persist == Some(true);
+    let found = async move {
+        let mut found = find_candidates(query).await;
+        apply_cached_github_languages(&mut found, &mut db).await;
+        (found, persist.then_some(db))
+    };
+    (ContentType::new("application", "x-ndjson"), sourcing_event_stream(context, found))
+}
+
+// ============================================
+// Candidate -> Jobs Matching
+// ============================================
+
+#[derive(Deserialize, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct MatchJobsRequest {
+    /// Jobs to score against; defaults to all open jobs
+    job_ids: Option<Vec<String>>,
+    /// Override the configured temperature for AI scoring (0.0-2.0)
+    temperature: Option<f64>,
+}
+
+#[derive(Serialize)]
+struct JobMatch {
+    job_id: String,
+    title: String,
+    talent_fit_score: i32,
+    score_breakdown: ScoreBreakdown,
+}
+
+/// A stored candidate's scoring inputs
+/// The row needs `skills`, `experience`, `developer_profile`, `location`, `work_style`, `code_characteristics`
+/// and `reputation` (`github_stats->'reputation'`)
+struct StoredCandidate {
+    skills: Vec<CandidateSkill>,
+    experience: Vec<CandidateExperience>,
+    developer_profile: Option<String>,
+    location: Option<String>,
+    reputation: Option<Reputation>,
+    work_style: Option<WorkStyle>,
+    code_characteristics: Option<CodeCharacteristics>,
+}
+
+impl StoredCandidate {
+    fn from_row(row: &sqlx::postgres::PgRow) -> Self {
+        let json = |column: &str| row.get::<Option<serde_json::Value>, _>(column);
+        StoredCandidate {
+            skills: json("skills").and_then(|v| serde_json::from_value(v).ok()).unwrap_or_default(),
+            experience: json("experience").and_then(|v| serde_json::from_value(v).ok()).unwrap_or_default(),
+            developer_profile: row.get("developer_profile"),
+            location: row.get("location"),
+            reputation: json("reputation").and_then(|v| serde_json::from_value(v).ok()),
+            work_style: json("work_style").and_then(|v| serde_json::from_value(v).ok()),
+            code_characteristics: json("code_characteristics").and_then(|v| serde_json::from_value(v).ok()),
+        }
+    }
+
+    fn scoring(&self) -> ScoringCandidate<'_> {
+        ScoringCandidate {
+            skills: &self.skills,
+            experience: &self.experience,
+            developer_profile: self.developer_profile.as_deref(),
+            reputation: self.reputation.as_ref(),
+            location: self.location.as_deref(),
+            work_style: self.work_style.as_ref(),
+            code_characteristics: self.code_characteristics.as_ref(),
+        }
+    }
+}
+
+/// A job to score a candidate against, with its team's profiles (None if the job has no team)
+struct JobCandidateContext {
+    job_id: String,
+    job_data: JobData,
+    team_members: Option<Vec<TeamMemberData>>,
+}
+
+/// Score a candidate against each job, best fit first
+async fn rank_jobs_for_candidate(
+    candidate: &ScoringCandidate<'_>,
+    jobs: &[JobCandidateContext],
+    temperature: Option<f64>,
+) -> Vec<JobMatch> {
+    let mut matches = Vec::with_capacity(jobs.len());
+
+    for job in jobs {
+        let talent_fit = score_against_job(
+            candidate,
+            &job.job_data,
+            job.team_members.as_deref(),
+            temperature,
+        ).await;
+
+        matches.push(JobMatch {
+            job_id: job.job_id.clone(),
+            title: job.job_data.title.clone(),
+            talent_fit_score: talent_fit.total,
+            score_breakdown: ScoreBreakdown {
+                skills: talent_fit.breakdown.skills.into(),
+                experience: talent_fit.breakdown.experience.into(),
+                team_fit: talent_fit.breakdown.team_fit.into(),
+                culture: talent_fit.breakdown.culture.into(),
+                team_fit_excluded: talent_fit.breakdown.team_fit_excluded,
+                location: talent_fit.breakdown.location,
+            },
+        });
+    }
+
+    matches.sort_by(|a, b| b.talent_fit_score.cmp(&a.talent_fit_score).then_with(|| a.title.cmp(&b.title)));
+    matches
+}
+
+/// Score a candidate against several jobs and return them ranked by talent fit
+#[post("/candidates/<id>/match-jobs", data = "<data>")]
+pub async fn match_candidate_jobs(
+    id: &str,
+    data: Option<json::Json<MatchJobsRequest>>,
+    mut db: Connection<MainDatabase>,
+) -> RawJson<String> {
+    let candidate_uuid = match uuid::Uuid::parse_str(id) {
+        Ok(u) => u,
+        Err(_) => return RawJson(r#"{"error": "Invalid candidate ID"}"#.to_string()),
+    };
+
+    let candidate = match sqlx::query(
+        "SELECT skills, experience, developer_profile, location, work_style, code_characteristics, github_stats->'reputation' AS reputation FROM sourced_candidates WHERE id = $1"
+    )
+    .bind(candidate_uuid)
+    .fetch_optional(&mut **db)
+    .await
+    {
+        Ok(Some(row)) => row,
+        Ok(None) => return RawJson(r#"{"error": "Candidate not found"}"#.to_string()),
+        Err(e) => return RawJson(serde_json::json!({"error": format!("Database error: {}", e)}).to_string()),
+    };
+
+    let candidate = StoredCandidate::from_row(&candidate);
+
+    let (job_ids, temperature) = match data {
+        Some(d) => {
+            let request = d.into_inner();
+            (request.job_ids, request.temperature)
+        }
+        None => (None, None),
+    };
+    let job_rows = match job_ids {
+        Some(ids) => {
+            let mut uuids = Vec::with_capacity(ids.len());
+            for job_id in &ids {
+                match uuid::Uuid::parse_str(job_id) {
+                    Ok(u) => uuids.push(u),
+                    Err(_) => {
+                        return RawJson(serde_json::json!({"error": format!("Invalid job ID: {}", job_id)}).to_string());
+                    }
+                }
+            }
+            sqlx::query(
+                r#"SELECT id, title, description, location, required_skills, experience_level, team_fit_weights, location_requirement, score_weights, team_id
+                   FROM jobs WHERE id = ANY($1)"#
+            )
+            .bind(uuids)
+            .fetch_all(&mut **db)
+            .await
+        }
+        None => {
+            sqlx::query(
+                r#"SELECT id, title, description, location, required_skills, experience_level, team_fit_weights, location_requirement, score_weights, team_id
+                   FROM jobs WHERE COALESCE(status, 'sourcing') NOT IN ('filled', 'closed')"#
+            )
+            .fetch_all(&mut **db)
+            .await
+        }
+    };
+
+    let job_rows = match job_rows {
+        Ok(rows) => rows,
+        Err(e) => return RawJson(serde_json::json!({"error": format!("Database error: {}", e)}).to_string()),
+    };
+
+    // Load each team's member profiles once
+    let mut team_profiles: std::collections::HashMap<uuid::Uuid, Vec<TeamMemberData>> = std::collections::HashMap::new();
+    let mut jobs = Vec::with_capacity(job_rows.len());
+
+    for row in &job_rows {
+        let team_id: Option<uuid::Uuid> = row.get("team_id");
+        let team_members = match team_id {
+            Some(team_id) => {
+                if let std::collections::hash_map::Entry::Vacant(entry) = team_profiles.entry(team_id) {
+                    entry.insert(load_team_members(&mut db, team_id).await.unwrap_or_default());
+                }
+                Some(team_profiles[&team_id].clone())
+            }
+            None => None,
+        };
+
+        jobs.push(JobCandidateContext {
+            job_id: row.get::<uuid::Uuid, _>("id").to_string(),
+            job_data: job_data_from_row(row),
+            team_members,
+        });
+    }
+
+    let matches = rank_jobs_for_candidate(
+        &candidate.scoring(),
+        &jobs,
+        temperature,
+    ).await;
+
+    RawJson(serde_json::json!({
+        "candidate_id": id,
+        "matches": matches,
+    }).to_string())
+}
+
+// ============================================
+// Job Rescoring
+// ============================================
+
+/// Candidates scored at once when rescoring a job; each may make AI skill and culture calls
+const RESCORE_CONCURRENCY: usize = 4;
+
+#[derive(Deserialize, Default)]
+#[serde(crate = "rocket::serde")]
+pub struct RescoreRequest {
+    /// Override the configured temperature for AI scoring (0.0-2.0)
+    temperature: Option<f64>,
+}
+
+/// A linked candidate's job match score before and after rescoring
+#[derive(Serialize)]
+struct RescoredCandidate {
+    candidate_id: String,
+    name: String,
+    old_score: i32,
+    new_score: i32,
+    score_breakdown: ScoreBreakdown,
+}
+
+/// Rescore every candidate linked to a job against its current requirements; nothing is written
+/// Returns None when the job doesn't exist; candidates come back best fit first
+async fn rescore_job(
+    conn: &mut sqlx::PgConnection,
+    job_uuid: uuid::Uuid,
+    temperature: Option<f64>,
+) -> Result<Option<Vec<RescoredCandidate>>, sqlx::Error> {
+    let Some(job_row) = sqlx::query(
+        r#"SELECT title, description, location, required_skills, experience_level, team_fit_weights, location_requirement, score_weights, team_id
+           FROM jobs WHERE id = $1"#
+    )
+    .bind(job_uuid)
+    .fetch_optional(&mut *conn)
+    .await?
+    else {
+        return Ok(None);
+    };
+    let job_data = job_data_from_row(&job_row);
+
+    let team_members: Option<Vec<TeamMemberData>> = match job_row.get::<Option<uuid::Uuid>, _>("team_id") {
+        Some(team_id) => Some(load_team_members(&mut *conn, team_id).await?),
+        None => None,
+    };
+
+    let rows = sqlx::query(
+        r#"SELECT sc.id, sc.name, sc.skills, sc.experience, sc.developer_profile, sc.location, sc.work_style, sc.code_characteristics,
+                  sc.github_stats->'reputation' AS reputation, jc.job_match_score
+           FROM job_candidates jc
+           JOIN sourced_candidates sc ON sc.id = jc.candidate_id
+           WHERE jc.job_id = $1"#
+    )
+    .bind(job_uuid)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    // Score concurrently; the AI skill and culture calls dominate and are independent per candidate
+    let job_data = &job_data;
+    let team_members = team_members.as_deref();
+    let scored: Vec<(uuid::Uuid, String, i32, TalentFitScore)> = futures::stream::iter(rows)
+        .map(|row| async move {
+            let candidate = StoredCandidate::from_row(&row);
+            let talent_fit = score_against_job(&candidate.scoring(), job_data, team_members, temperature).await;
+            (
+                row.get::<uuid::Uuid, _>("id"),
+                row.get::<String, _>("name"),
+                row.get::<Option<i32>, _>("job_match_score").unwrap_or(0),
+                talent_fit,
+            )
+        })
+        .buffer_unordered(RESCORE_CONCURRENCY)
+        .collect()
+        .await;
+
+    let mut rescored: Vec<RescoredCandidate> = scored
+        .into_iter()
+        .map(|(candidate_uuid, name, old_score, talent_fit)| RescoredCandidate {
+            candidate_id: candidate_uuid.to_string(),
+            name,
+            old_score,
+            new_score: talent_fit.total,
+            score_breakdown: ScoreBreakdown {
+                skills: talent_fit.breakdown.skills.into(),
+                experience: talent_fit.breakdown.experience.into(),
+                team_fit: talent_fit.breakdown.team_fit.into(),
+                culture: talent_fit.breakdown.culture.into(),
+                team_fit_excluded: talent_fit.breakdown.team_fit_excluded,
+                location: talent_fit.breakdown.location,
+            },
+        })
+        .collect();
+
+    rescored.sort_by(|a, b| b.new_score.cmp(&a.new_score).then_with(|| a.name.cmp(&b.name)));
+    Ok(Some(rescored))
+}
+
+/// Write rescored job match scores and their breakdowns; the candidates' own talent_fit_score and score_breakdown
+/// are left alone since a candidate linked to several jobs has a different match score for each
+async fn store_rescored(
+    conn: &mut sqlx::PgConnection,
+    job_uuid: uuid::Uuid,
+    rescored: &[RescoredCandidate],
+) -> Result<(), sqlx::Error> {
+    for candidate in rescored {
+        sqlx::query("UPDATE job_candidates SET job_match_score = $1, score_breakdown = $2 WHERE job_id = $3 AND candidate_id = $4::uuid")
+            .bind(candidate.new_score)
+            .bind(serde_json::to_value(&candidate.score_breakdown).unwrap())
+            .bind(job_uuid)
+            .bind(&candidate.candidate_id)
+            .execute(&mut *conn)
+            .await?;
+    }
+    Ok(())
+}
+
+/// Rescore a job's linked candidates after its requirements change
+#[post("/jobs/<job_id>/rescore", data = "<data>")]
+pub async fn rescore_job_candidates(
+    job_id: &str,
+    data: Option<json::Json<RescoreRequest>>,
+    mut db: Connection<MainDatabase>,
+) -> (Status, RawJson<String>) {
+    let Ok(job_uuid) = uuid::Uuid::parse_str(job_id) else {
+        return (Status::BadRequest, err_json(INVALID_REQUEST, "Invalid job ID"));
+    };
+    let temperature = data.map(|d| d.into_inner()).unwrap_or_default().temperature;
+
+    // Score outside any transaction (the AI calls can take minutes), then write everything at once
+    let rescored = match rescore_job(&mut db, job_uuid, temperature).await {
+        Ok(Some(rescored)) => rescored,
+        Ok(None) => return (Status::NotFound, err_json(NOT_FOUND, "Job not found")),
+        Err(e) => return (Status::InternalServerError, err_json(DATABASE_ERROR, format!("Database error: {}", e))),
+    };
+
+    // All scores are written together, so a failure part-way leaves the old ones in place
+    let mut tx = match (**db).begin().await {
+        Ok(tx) => tx,
+        Err(e) => return (Status::InternalServerError, err_json(DATABASE_ERROR, format!("Database error: {}", e))),
+    };
+    if let Err(e) = store_rescored(&mut tx, job_uuid, &rescored).await {
+        return (Status::InternalServerError, err_json(DATABASE_ERROR, format!("Database error: {}", e)));
+    }
+    if let Err(e) = tx.commit().await {
+        return (Status::InternalServerError, err_json(DATABASE_ERROR, format!("Database error: {}", e)));
+    }
+
+    (Status::Ok, RawJson(serde_json::json!({
+        "job_id": job_id,
+        "candidates": rescored,
+    }).to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::ScoreWeights;
+    use futures::StreamExt;
+
+    fn job(id: &str, title: &str, skills: &[&str], level: &str) -> JobCandidateContext {
+        JobCandidateContext {
+            job_id: id.to_string(),
+            job_data: JobData {
+                required_skills: skills.iter().map(|s| RequiredSkill {
+                    name: s.to_string(),
+                    level: Some("advanced".to_string()),
+                    mandatory: Some(true),
+                }).collect(),
+                experience_level: level.to_string(),
+                title: title.to_string(),
+                description: None,
+                location: None,
+                team_fit_weights: TeamFitWeights::default(),
+                location_requirement: None,
+                score_weights: None,
+            },
+            team_members: None,
+        }
+    }
+
+    fn scoring_candidate<'a>(skills: &'a [CandidateSkill], experience: &'a [CandidateExperience]) -> ScoringCandidate<'a> {
+        ScoringCandidate { skills, experience, developer_profile: None, reputation: None, location: None, work_style: None, code_characteristics: None }
+    }
+
+    #[rocket::async_test]
+    async fn test_candidate_ranked_across_jobs() {
+        let skills = vec![
+            CandidateSkill { name: "Rust".to_string(), level: "expert".to_string() },
+            CandidateSkill { name: "PostgreSQL".to_string(), level: "advanced".to_string() },
+        ];
+        let experience = vec![CandidateExperience {
+            title: "Senior Backend Engineer".to_string(),
+            company: "Acme".to_string(),
+            duration: "6 years".to_string(),
+            description: None,
+            start_date: None,
+            end_date: None,
+        }];
+
+        let jobs = vec![
+            job("ios", "iOS Developer", &["Swift", "Objective-C"], "mid"),
+            job("backend", "Senior Backend Engineer", &["Rust", "Postgres"], "senior"),
+            job("fullstack", "Full Stack Engineer", &["Rust", "React"], "mid"),
+        ];
+
+        // No descriptions, profiles or teams, so culture scoring stays offline
+        let ranked = rank_jobs_for_candidate(&scoring_candidate(&skills, &experience), &jobs, None).await;
+
+        let order: Vec<&str> = ranked.iter().map(|m| m.job_id.as_str()).collect();
+        assert_eq!(order, vec!["backend", "fullstack", "ios"]);
+        assert!(ranked[0].talent_fit_score > ranked[1].talent_fit_score);
+        assert!(ranked[1].talent_fit_score > ranked[2].talent_fit_score);
+    }
+
+    #[test]
+    fn test_filter_profile_results_drops_non_profile_urls() {
+        let result = |href: &str| ProfileSearchResult {
+            href: href.to_string(),
+            title: Some("Jane Doe - Engineer | LinkedIn".to_string()),
+            description: None,
+            actual_role: None,
+            actual_location: None,
+        };
+        let results = vec![
+            result("https://www.linkedin.com/in/jane-doe"),
+            result("https://au.linkedin.com/in/john-smith-123/"),
+            result("https://www.linkedin.com/company/acme"),
+            result("https://www.linkedin.com/jobs/view/123456"),
+            result("https://www.linkedin.com/pulse/how-we-scaled-rust"),
+            result("https://www.linkedin.com/in/"),
+            result("https://notlinkedin.com/in/jane"),
+        ];
+
+        let kept: Vec<String> = filter_profile_results(results).into_iter().map(|r| r.href).collect();
+        assert_eq!(kept, vec![
+            "https://www.linkedin.com/in/jane-doe",
+            "https://au.linkedin.com/in/john-smith-123/",
+        ]);
+    }
+
+    #[test]
+    fn test_empty_ai_expansion_uses_builtin_variations() {
+        let queries = queries_with_variations("DevOps Engineer", parse_json(Some(String::new())));
+        assert_eq!(queries[0], "DevOps Engineer");
+        assert_eq!(queries[1..], get_fallback_variations("DevOps Engineer")[..]);
+    }
+
+    #[test]
+    fn test_empty_ai_filter_uses_keyword_filter() {
+        let results: Vec<ProfileSearchResult> = ["Jane Doe - Backend Engineer - Acme", "John Roe - Copywriter - Ads Co"]
+            .iter()
+            .map(|title| ProfileSearchResult {
+                href: "https://www.linkedin.com/in/example".to_string(),
+                title: Some(title.to_string()),
+                description: None,
+                actual_role: None,
+                actual_location: None,
+            })
+            .collect();
+
+        let relevant = relevant_from_ai(&results, parse_json(Some(String::new())));
+        assert_eq!(relevant, fallback_filter(&results));
+        assert_eq!(relevant.len(), 1);
+        assert_eq!(relevant[0].0, 0);
+    }
+
+    #[rocket::async_test]
+    async fn test_no_team_search_uses_renormalized_weights() {
+        let skills = vec![CandidateSkill { name: "Rust".to_string(), level: "expert".to_string() }];
+        let experience = vec![CandidateExperience {
+            title: "Backend Engineer".to_string(),
+            company: "Acme".to_string(),
+            duration: "3 years".to_string(),
+            description: None,
+            start_date: None,
+            end_date: None,
+        }];
+        let job = job("backend", "Backend Engineer", &["Rust", "Go"], "mid");
+
+        let candidate = scoring_candidate(&skills, &experience);
+        let no_team = score_against_job(&candidate, &job.job_data, None, None).await;
+        let breakdown = &no_team.breakdown;
+        assert!(breakdown.team_fit_excluded);
+
+        // 0.40 / 0.30 / 0.10 scaled up to sum to 1.0
+        let w = ScoreWeights::default().without_team_fit();
+        assert!((w.skills - 0.5).abs() < 1e-6 && (w.experience - 0.375).abs() < 1e-6 && (w.culture - 0.125).abs() < 1e-6);
+        assert_eq!(w.team_fit, 0.0);
+        let expected = breakdown.skills.score as f32 * w.skills
+            + breakdown.experience.score as f32 * w.experience
+            + breakdown.culture.score as f32 * w.culture;
+        assert_eq!(no_team.total, expected.round() as i32);
+
+        // With a team the neutral team-fit score is weighted in as before
+        let with_team = score_against_job(&candidate, &job.job_data, Some(&[]), None).await;
+        assert!(!with_team.breakdown.team_fit_excluded);
+        assert_eq!(with_team.breakdown.team_fit.score, 75);
+
+        // Members' skills set the gaps: the team has Rust, and the candidate doesn't fill the Go gap
+        let members = vec![TeamMemberData {
+            developer_profile: None,
+            profile: TeamMemberProfile {
+                skills: vec!["Rust".to_string()],
+                experience_level: "senior".to_string(),
+                work_style: None,
+                code_characteristics: None,
+            },
+        }];
+        let with_members = score_against_job(&candidate, &job.job_data, Some(&members), None).await;
+        assert_eq!(with_members.breakdown.team_fit.score, 0);
+        assert!(with_members.breakdown.team_fit.missing.contains(&"Skill gap still open: Go".to_string()));
+    }
+
+    #[rocket::async_test]
+    async fn test_concurrent_expansions_share_one_call() {
+        use std::sync::Arc;
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let cache = Mutex::new(ExpansionCache::default());
+        let calls = Arc::new(AtomicUsize::new(0));
+        let generate = |calls: Arc<AtomicUsize>| move || async move {
+            calls.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(vec!["ML Engineer".to_string(), "AI Engineer".to_string()])
+        };
+
+        let (first, second) = futures::join!(
+            cached_variations(&cache, "Machine Learning Engineer", QUERY_EXPANSION_TTL, generate(calls.clone())),
+            cached_variations(&cache, "  machine learning   ENGINEER", QUERY_EXPANSION_TTL, generate(calls.clone())),
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        assert_eq!(first, second);
+        assert_eq!(first.unwrap(), vec!["ML Engineer".to_string(), "AI Engineer".to_string()]);
+
+        // Later requests within the TTL hit the cache; an expired entry is regenerated
+        let _ = cached_variations(&cache, "Machine Learning Engineer", QUERY_EXPANSION_TTL, generate(calls.clone())).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+        let _ = cached_variations(&cache, "Machine Learning Engineer", Duration::ZERO, generate(calls.clone())).await;
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[rocket::async_test]
+    async fn test_failed_expansion_not_cached() {
+        let cache = Mutex::new(ExpansionCache::default());
+
+        let failed = cached_variations(&cache, "Data Engineer", QUERY_EXPANSION_TTL, || async { Err(AiResponseError::Empty) }).await;
+        assert_eq!(failed, Err(AiResponseError::Empty));
+
+        let retried = cached_variations(&cache, "Data Engineer", QUERY_EXPANSION_TTL, || async {
+            Ok(vec!["Analytics Engineer".to_string()])
+        }).await;
+        assert_eq!(retried, Ok(vec!["Analytics Engineer".to_string()]));
+    }
+
+    #[rocket::async_test]
+    async fn test_finished_expansion_keeps_newer_in_flight() {
+        let cache: &'static Mutex<ExpansionCache> = Box::leak(Box::new(Mutex::new(ExpansionCache::default())));
+        let newer = async { Ok(vec!["Newer".to_string()]) }.boxed().shared();
+
+        // While this expansion runs, a newer one for the same title takes its in-flight slot
+        let replacement = newer.clone();
+        let result = cached_variations(cache, "Site Reliability Engineer", QUERY_EXPANSION_TTL, move || async move {
+            cache.lock().unwrap().in_flight.insert("site reliability engineer".to_string(), replacement);
+            Ok(vec!["SRE".to_string()])
+        }).await;
+        assert_eq!(result, Ok(vec!["SRE".to_string()]));
+
+        let cache = cache.lock().unwrap();
+        assert!(cache.in_flight["site reliability engineer"].ptr_eq(&newer));
+    }
+
+    #[test]
+    fn test_remote_job_skips_location_filter() {
+        let remote = relevance_prompt("Backend Engineer", " Remote ", "[]");
+        assert!(!remote.contains("located in or near the target location"));
+        assert!(!remote.contains("different country/region"));
+        assert!(remote.contains("may be located anywhere"));
+        assert_eq!(scraper_location("Anywhere"), "");
+
+        let local = relevance_prompt("Backend Engineer", "Sydney", "[]");
+        assert!(local.contains("position in Sydney"));
+        assert!(local.contains("different country/region than the target location\n- The result"));
+        assert_eq!(scraper_location("Sydney"), "Sydney");
+    }
+
+    #[test]
+    fn test_scraper_response_shapes() {
+        let bare = r#"[{"href": "https://linkedin.com/in/jane", "title": "Jane - Engineer", "description": null, "rank": 1}]"#;
+        let results = parse_scraper_response(bare, None).unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].href, "https://linkedin.com/in/jane");
+
+        let enveloped = r#"{"schema_version": 1, "query": "engineer", "results": [
+            {"href": "https://linkedin.com/in/jane", "title": "Jane - Engineer"},
+            {"href": "https://linkedin.com/in/john", "description": "Sydney", "source": "ddg"}
+        ]}"#;
+        let results = parse_scraper_response(enveloped, Some("1")).unwrap();
+        assert_eq!(results.iter().map(|r| r.href.as_str()).collect::<Vec<_>>(), vec![
+            "https://linkedin.com/in/jane",
+            "https://linkedin.com/in/john",
+        ]);
+        assert_eq!(results[1].title, None);
+
+        // No results is not a schema problem
+        assert_eq!(parse_scraper_response("[]", None).unwrap().len(), 0);
+        assert_eq!(parse_scraper_response(r#"{"results": []}"#, None).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_scraper_schema_mismatch_reported() {
+        let err = parse_scraper_response(r#"{"profiles": [{"url": "x"}]}"#, None).unwrap_err();
+        assert!(matches!(err, ScraperResponseError::SchemaMismatch(_)));
+        assert!(err.to_string().starts_with("Scraping service schema mismatch: expected a profile array"));
+
+        assert_eq!(parse_scraper_response("[]", Some("2")).unwrap_err(), ScraperResponseError::UnsupportedVersion(2));
+        assert_eq!(
+            parse_scraper_response(r#"{"schema_version": 3, "results": []}"#, None).unwrap_err(),
+            ScraperResponseError::UnsupportedVersion(3)
+        );
+    }
+
+    #[rocket::async_test]
+    async fn test_stream_emits_candidates_before_stats() {
+        let profile = |href: &str, title: &str, description: &str| ProfileSearchResult {
+            href: href.to_string(),
+            title: Some(title.to_string()),
+            description: Some(description.to_string()),
+            actual_role: Some("Backend Engineer".to_string()),
+            actual_location: Some("Sydney, Australia".to_string()),
+        };
+        let found: Vec<GeneratedCandidateData> = [
+            profile("https://www.linkedin.com/in/jane", "Jane Doe - Backend Engineer | LinkedIn", "Rust and PostgreSQL"),
+            profile("https://www.linkedin.com/in/john", "John Roe - Backend Engineer | LinkedIn", "Python"),
+        ]
+        .iter()
+        .filter_map(|r| convert_search_result_to_candidate(r, "linkedin", true))
+        .collect();
+        assert_eq!(found.len(), 2);
+
+        let job = job("backend", "Backend Engineer", &["Rust", "PostgreSQL"], "mid").job_data;
+        let context = SourcingContext {
+            query: CandidateQuery {
+                job_title: job.title.clone(),
+                location: None,
+                linkedin: true,
+                github: false,
+                languages: vec![],
+                count: 2,
+                deterministic_ids: true,
+            },
+            job_data: job,
+            team_members: None,
+            temperature: None,
+        };
+
+        let mut lines = Box::pin(sourcing_event_stream(context, async move { (found, None) }).0);
+        let mut next_event = async || -> Option<serde_json::Value> {
+            let line = lines.next().await?;
+            assert!(line.ends_with('\n'));
+            Some(serde_json::from_str(&line).unwrap())
+        };
+
+        // Each candidate arrives as its own event, scored, before the stream completes
+        let first = next_event().await.unwrap();
+        assert_eq!(first["event"], "candidate");
+        assert_eq!(first["name"], "Jane Doe");
+        let first_score = first["talent_fit_score"].as_i64().unwrap();
+
+        let second = next_event().await.unwrap();
+        assert_eq!(second["event"], "candidate");
+        assert_eq!(second["name"], "John Roe");
+        let second_score = second["talent_fit_score"].as_i64().unwrap();
+
+        let stats = next_event().await.unwrap();
+        assert_eq!(stats["event"], "sourcing_stats");
+        assert_eq!(stats["candidates"], 2);
+        assert_eq!(stats["top_score"].as_i64(), Some(first_score.max(second_score)));
+        assert!(stats.get("score_sum").is_none());
+
+        assert!(next_event().await.is_none());
+    }
+
+    fn github_user(login: &str, name: Option<&str>, bio: &str) -> GitHubUser {
+        GitHubUser {
+            login: login.to_string(),
+            name: name.map(str::to_string),
+            bio: Some(bio.to_string()),
+            avatar_url: String::new(),
+            public_repos: 12,
+            followers: 40,
+            following: 3,
+            created_at: "2015-01-01T00:00:00Z".to_string(),
+            blog: None,
+            twitter_username: None,
+            company: None,
+            location: Some("Sydney, Australia".to_string()),
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_github_only_search_links_github() {
+        let request = SourcingRequest {
+            job_id: String::new(),
+            team_id: None,
+            sources: vec!["github".to_string()],
+            count: 5,
+            deterministic_ids: true,
+            temperature: None,
+            persist: None,
+        };
+        let mut job_data = job("backend", "Backend Engineer", &["PostgreSQL", "Rust"], "mid").job_data;
+        job_data.location = Some("Sydney, Australia".to_string());
+
+        let query = CandidateQuery::for_request(&request, &job_data);
+        assert!(query.github && !query.linkedin);
+        assert_eq!(query.languages, vec!["Rust"]);
+
+        let found = gather_candidates(query, |q, count| async move {
+            assert_eq!(q, "language:Rust location:Sydney type:user");
+            assert_eq!(count, 5);
+            Ok(vec![github_user("octocat", Some("The Octocat"), "Rust and Docker tinkerer"), github_user("hubot", None, "")])
+        })
+        .await;
+        assert_eq!(found.len(), 2);
+
+        // GitHub candidates go through the same scoring as LinkedIn ones
+        let mut results = Vec::new();
+        for generated in found {
+            results.push(score_candidate(generated, &job_data, None, None).await);
+        }
+        let octocat = serde_json::to_value(&results[0]).unwrap();
+        assert_eq!(octocat["name"], "The Octocat");
+        assert_eq!(octocat["source"], "github");
+        assert_eq!(octocat["links"]["github"], "https://github.com/octocat");
+        assert!(octocat["links"]["linkedin"].is_null());
+        assert!(octocat["score_breakdown"]["skills"]["matched"].as_array().unwrap().iter().any(|s| s.as_str().unwrap().contains("Rust")));
+
+        // Users without a display name fall back to their login; IDs are stable per GitHub URL
+        assert_eq!(results[1].name, "hubot");
+        assert_eq!(results[1].id, deterministic_candidate_id("https://github.com/hubot").to_string());
+    }
+
+    #[rocket::async_test]
+    async fn test_profiles_loaded_concurrently_in_order() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let (active, peak) = (AtomicUsize::new(0), AtomicUsize::new(0));
+        let logins: Vec<String> = (0..12).map(|i| format!("user{}", i)).collect();
+        let users = load_profiles(logins, |login| {
+            let (active, peak) = (&active, &peak);
+            async move {
+                let now = active.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::time::sleep(Duration::from_millis(10)).await;
+                active.fetch_sub(1, Ordering::SeqCst);
+                if login == "user3" {
+                    return Err("not found".into());
+                }
+                Ok(github_user(&login, None, ""))
+            }
+        })
+        .await;
+
+        // Failed profiles are skipped; the rest keep the search order
+        let expected: Vec<String> = (0..12).filter(|&i| i != 3).map(|i| format!("user{}", i)).collect();
+        assert_eq!(users.iter().map(|u| u.login.clone()).collect::<Vec<_>>(), expected);
+        assert_eq!(peak.load(Ordering::SeqCst), PROFILE_LOOKUP_CONCURRENCY);
+    }
+
+    #[test]
+    fn test_github_results_deduplicated_against_linkedin() {
+        let profile = ProfileSearchResult {
+            href: "https://www.linkedin.com/in/jane".to_string(),
+            title: Some("Jane Doe - Backend Engineer | LinkedIn".to_string()),
+            description: Some("Rust".to_string()),
+            actual_role: None,
+            actual_location: None,
+        };
+        let mut candidates: Vec<GeneratedCandidateData> = convert_search_result_to_candidate(&profile, "linkedin", true).into_iter().collect();
+        let linked_jane = GitHubUser { blog: Some("linkedin.com/in/Jane/".to_string()), ..github_user("janedoe", Some("jane  DOE"), "Rust") };
+        let github = vec![
+            convert_github_user_to_candidate(&linked_jane, &[], true),
+            convert_github_user_to_candidate(&github_user("octocat", Some("The Octocat"), ""), &[], true),
+        ];
+
+        merge_github_candidates(&mut candidates, github);
+        assert_eq!(candidates.len(), 2);
+        assert_eq!(candidates[0].source, "linkedin");
+        assert_eq!(candidates[0].links.github.as_deref(), Some("https://github.com/janedoe"));
+        assert_eq!(candidates[1].name, "The Octocat");
+
+        // Another Jane Doe whose profile doesn't point at the LinkedIn page is dropped, not linked
+        let mut candidates: Vec<GeneratedCandidateData> = convert_search_result_to_candidate(&profile, "linkedin", true).into_iter().collect();
+        let namesake = convert_github_user_to_candidate(&github_user("jdoe42", Some("Jane Doe"), "Go"), &[], true);
+        merge_github_candidates(&mut candidates, vec![namesake]);
+        assert_eq!(candidates.len(), 1);
+        assert_eq!(candidates[0].links.github, None);
+
+        // Remote jobs search without a location; jobs without a language search bios for the role
+        assert_eq!(github_user_query("Data Engineer", Some("Remote"), &[]), "Data Engineer in:bio type:user");
+        assert_eq!(github_user_query("Dev", Some("New York, NY"), &["C++".to_string()]), "language:C++ location:\"New York\" type:user");
+    }
+
+    #[rocket::async_test]
+    async fn test_persisted_candidate_matches_candidate_layout() {
+        use crate::endpoints::ep_candidates::{EducationInput, ExperienceInput, ScoreBreakdownInput, SkillInput};
+
+        let profile = ProfileSearchResult {
+            href: "https://www.linkedin.com/in/jane".to_string(),
+            title: Some("Jane Doe - Backend Engineer | LinkedIn".to_string()),
+            description: Some("Rust and PostgreSQL".to_string()),
+            actual_role: None,
+            actual_location: Some("Sydney, Australia".to_string()),
+        };
+        let generated = convert_search_result_to_candidate(&profile, "linkedin", true).unwrap();
+        let job_data = job("backend", "Backend Engineer", &["Rust"], "mid").job_data;
+        let result = score_candidate(generated, &job_data, None, None).await;
+
+        // Stored columns read back as the candidate endpoints' types
+        let skills: Vec<SkillInput> = serde_json::from_value(serde_json::to_value(&result.skills).unwrap()).unwrap();
+        assert_eq!(skills.len(), result.skills.len());
+        let _: Vec<ExperienceInput> = serde_json::from_value(serde_json::to_value(&result.experience).unwrap()).unwrap();
+        let _: Vec<EducationInput> = serde_json::from_value(serde_json::to_value(&result.education).unwrap()).unwrap();
+        let breakdown = stored_score_breakdown(&result.score_breakdown);
+        let _: ScoreBreakdownInput = serde_json::from_value(breakdown.clone()).unwrap();
+        assert_eq!(breakdown["skillsMatch"], result.score_breakdown.skills.score);
+
+        // Duplicates are looked up by the link their source found them by
+        assert_eq!(dedupe_link(&result), Some(("linkedin", "https://www.linkedin.com/in/jane")));
+        let github = convert_github_user_to_candidate(&github_user("octocat", None, ""), &[], true);
+        let github = score_candidate(github, &job_data, None, None).await;
+        assert_eq!(dedupe_link(&github), Some(("github", "https://github.com/octocat")));
+        assert_eq!(extract_github_username(github.links.github.as_deref().unwrap()).as_deref(), Some("octocat"));
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_persisted_candidates_are_counted_once() {
+        use crate::endpoints::ep_candidates::count_candidates;
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let job_data = job("backend", "Backend Engineer", &["Rust"], "mid").job_data;
+        let scored = async |href: &str, name: &str| {
+            let profile = ProfileSearchResult {
+                href: href.to_string(),
+                title: Some(format!("{} - Backend Engineer | LinkedIn", name)),
+                description: Some("Rust and PostgreSQL".to_string()),
+                actual_role: None,
+                actual_location: None,
+            };
+            let generated = convert_search_result_to_candidate(&profile, "linkedin", false).unwrap();
+            score_candidate(generated, &job_data, None, None).await
+        };
+
+        let before = count_candidates(&mut tx, None).await.unwrap();
+        let sourced_before = count_candidates(&mut tx, Some("sourced")).await.unwrap();
+
+        let jane = scored("https://www.linkedin.com/in/zqx-persist-jane", "Jane Persist").await;
+        let jane_id = persist_candidate(&jane, &mut tx).await.unwrap();
+        let john = scored("https://www.linkedin.com/in/zqx-persist-john", "John Persist").await;
+        persist_candidate(&john, &mut tx).await.unwrap();
+
+        // Found again by another search (with a new random id): the stored row is reused
+        let jane_again = scored("https://www.linkedin.com/in/zqx-persist-jane", "Jane Persist").await;
+        assert_ne!(jane_again.id, jane.id);
+        assert_eq!(persist_candidate(&jane_again, &mut tx).await.unwrap(), jane_id);
+
+        assert_eq!(count_candidates(&mut tx, None).await.unwrap(), before + 2);
+        assert_eq!(count_candidates(&mut tx, Some("sourced")).await.unwrap(), sourced_before + 2);
+
+        tx.rollback().await.unwrap();
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_rescore_follows_required_skills() {
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        // No description, profile or team, so scoring stays offline
+        let job_id = uuid::Uuid::new_v4();
+        sqlx::query("INSERT INTO jobs (id, title, required_skills, experience_level) VALUES ($1, 'Backend Engineer', $2, 'mid')")
+            .bind(job_id)
+            .bind(serde_json::json!(["Rust", "PostgreSQL"]))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let candidate_id = uuid::Uuid::new_v4();
+        sqlx::query("INSERT INTO sourced_candidates (id, name, title, skills, experience) VALUES ($1, 'Zqx Rescore', 'Engineer', $2, $3)")
+            .bind(candidate_id)
+            .bind(serde_json::json!([{"name": "Rust", "level": "expert"}, {"name": "PostgreSQL", "level": "advanced"}]))
+            .bind(serde_json::json!([{"title": "Backend Engineer", "company": "Acme", "duration": "4 years"}]))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO job_candidates (job_id, candidate_id, job_match_score) VALUES ($1, $2, 10)")
+            .bind(job_id)
+            .bind(candidate_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let stored_score = async |tx: &mut sqlx::PgConnection| -> (i32, i32) {
+            let row = sqlx::query(
+                "SELECT jc.job_match_score, sc.talent_fit_score FROM job_candidates jc JOIN sourced_candidates sc ON sc.id = jc.candidate_id WHERE jc.job_id = $1"
+            )
+            .bind(job_id)
+            .fetch_one(tx)
+            .await
+            .unwrap();
+            (row.get("job_match_score"), row.get("talent_fit_score"))
+        };
+
+        // Scoring alone writes nothing
+        let first = rescore_job(&mut tx, job_id, None).await.unwrap().unwrap();
+        assert_eq!(first.len(), 1);
+        assert_eq!(first[0].old_score, 10);
+        assert_eq!(stored_score(&mut tx).await, (10, 0));
+
+        // Only this job's match score changes, not the candidate-wide talent fit
+        store_rescored(&mut tx, job_id, &first).await.unwrap();
+        let matching = first[0].new_score;
+        assert_eq!(stored_score(&mut tx).await, (matching, 0));
+
+        // The job's breakdown is kept next to its score
+        let stored_breakdown: serde_json::Value = sqlx::query_scalar("SELECT score_breakdown FROM job_candidates WHERE job_id = $1")
+            .bind(job_id)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(stored_breakdown, serde_json::to_value(&first[0].score_breakdown).unwrap());
+        assert_eq!(stored_breakdown["skills"]["score"], first[0].score_breakdown.skills.score);
+
+        // The job now asks for skills the candidate doesn't have
+        sqlx::query("UPDATE jobs SET required_skills = $1 WHERE id = $2")
+            .bind(serde_json::json!(["Haskell", "Erlang"]))
+            .bind(job_id)
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+
+        let second = rescore_job(&mut tx, job_id, None).await.unwrap().unwrap();
+        store_rescored(&mut tx, job_id, &second).await.unwrap();
+        assert_eq!(second[0].old_score, matching);
+        assert!(second[0].new_score < matching, "{} >= {}", second[0].new_score, matching);
+        assert_eq!(stored_score(&mut tx).await, (second[0].new_score, 0));
+
+        assert!(rescore_job(&mut tx, uuid::Uuid::new_v4(), None).await.unwrap().is_none());
+
+        tx.rollback().await.unwrap();
+    }
+
+    #[test]
+    fn test_github_languages_set_skill_levels() {
+        let desc_skills = extract_skills_from_description("Rust and Docker engineer, some Python");
+        let languages = HashMap::from([
+            ("Rust".to_string(), 50),
+            ("Python".to_string(), 20),
+            ("Shell".to_string(), 10),
+            ("Makefile".to_string(), 0),
+        ]);
+        let skills = merge_github_skills(desc_skills, &languages);
+        let level = |name: &str| skills.iter().find(|s| s.name == name).map(|s| s.level.as_str());
+
+        assert_eq!(level("Rust"), Some("advanced"));
+        assert_eq!(level("Python"), Some("intermediate"));
+        // Not a language, so the description's default stays
+        assert_eq!(level("Docker"), Some("intermediate"));
+        assert_eq!(level("Shell"), Some("familiar"));
+        assert_eq!(level("Makefile"), None);
+
+        assert_eq!(sourcing_language_level(41), "advanced");
+        assert_eq!(sourcing_language_level(40), "intermediate");
+        assert_eq!(sourcing_language_level(14), "familiar");
+
+        // A small share brings a described skill down to familiar
+        let rarely = HashMap::from([("Python".to_string(), 5)]);
+        let skills = merge_github_skills(extract_skills_from_description("Python developer"), &rarely);
+        assert_eq!(skills[0].level, "familiar");
+    }
+
+    #[test]
+    fn test_apply_github_languages_updates_response_skills() {
+        let user = github_user("rustacean", None, "");
+        let mut candidate = convert_github_user_to_candidate(&user, &["Rust".to_string()], true);
+        assert_eq!(candidate.candidate_skills[0].level, "intermediate");
+
+        apply_github_languages(&mut candidate, &HashMap::from([("Rust".to_string(), 50)]));
+        assert_eq!(candidate.candidate_skills[0].level, "advanced");
+        assert_eq!(candidate.skills[0].level, "advanced");
+        assert_eq!(candidate.skills[0].match_type, "inferred");
+    }
+}
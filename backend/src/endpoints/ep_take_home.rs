@@ -3,10 +3,13 @@ use rocket_db_pools::Connection;
 use rocket::response::content::RawJson;
 use serde::Deserialize;
 use crate::db::MainDatabase;
+use crate::endpoints::api_error::{err_json, ApiError, DATABASE_ERROR, INTERNAL_ERROR, INVALID_REQUEST, NOT_FOUND, UPSTREAM_ERROR};
+use crate::matching::parse_required_skills;
 use crate::github::take_home::{
-    generate_take_home_projects, analyze_candidate_repos,
-    CandidateContext, JobContext, TakeHomeProjects,
-    CandidateSkillContext, RequiredSkillContext,
+    generate_take_home_projects, regenerate_take_home_project, analyze_candidate_repos, minimum_profile,
+    validate_rubric, apply_rubric,
+    CandidateContext, JobContext, TakeHomeProjects, EvaluationCriterion,
+    CandidateSkillContext, RequiredSkillContext, ENRICH_CANDIDATE_MESSAGE,
 };
 use sqlx::Row;
 
@@ -14,6 +17,8 @@ use sqlx::Row;
 #[serde(crate = "rocket::serde")]
 pub struct GenerateProjectsRequest {
     force_regenerate: Option<bool>,
+    /// Fixed evaluation criteria (weights summing to 100) used on every project instead of the generated ones
+    rubric: Option<Vec<EvaluationCriterion>>,
 }
 
 /// Extract GitHub username from a GitHub URL
@@ -38,26 +43,14 @@ fn extract_github_username(url: &str) -> Option<String> {
 }
 
 fn parse_required_skills_context(json: &serde_json::Value) -> Vec<RequiredSkillContext> {
-    match json.as_array() {
-        Some(arr) => arr.iter().filter_map(|item| {
-            if let Some(s) = item.as_str() {
-                Some(RequiredSkillContext {
-                    name: s.to_string(),
-                    level: Some("intermediate".to_string()),
-                    mandatory: true,
-                })
-            } else if item.is_object() {
-                Some(RequiredSkillContext {
-                    name: item.get("name")?.as_str()?.to_string(),
-                    level: item.get("level").and_then(|v| v.as_str()).map(|s| s.to_string()),
-                    mandatory: item.get("mandatory").and_then(|v| v.as_bool()).unwrap_or(true),
-                })
-            } else {
-                None
-            }
-        }).collect(),
-        None => vec![],
-    }
+    parse_required_skills(json)
+        .into_iter()
+        .map(|s| RequiredSkillContext {
+            name: s.name,
+            level: s.level,
+            mandatory: s.mandatory.unwrap_or(true),
+        })
+        .collect()
 }
 
 fn parse_candidate_skills(json: &serde_json::Value) -> Vec<CandidateSkillContext> {
@@ -72,53 +65,23 @@ fn parse_candidate_skills(json: &serde_json::Value) -> Vec<CandidateSkillContext
     }
 }
 
-/// Generate take-home projects for a candidate linked to a job
-/// POST /api/jobs/{job_id}/candidates/{candidate_id}/take-home-projects
-#[post("/jobs/<job_id>/candidates/<candidate_id>/take-home-projects", data = "<data>")]
-pub async fn generate_take_home(
-    job_id: &str,
-    candidate_id: &str,
-    data: Option<json::Json<GenerateProjectsRequest>>,
-    mut db: Connection<MainDatabase>,
-) -> RawJson<String> {
-    let job_uuid = match uuid::Uuid::parse_str(job_id) {
-        Ok(u) => u,
-        Err(_) => return RawJson(r#"{"error": "Invalid job ID"}"#.to_string()),
-    };
-    let candidate_uuid = match uuid::Uuid::parse_str(candidate_id) {
-        Ok(u) => u,
-        Err(_) => return RawJson(r#"{"error": "Invalid candidate ID"}"#.to_string()),
-    };
-    let force = data.map(|d| d.force_regenerate.unwrap_or(false)).unwrap_or(false);
-
-    // Check if projects already exist (unless force regenerate)
-    if !force {
-        let existing = sqlx::query(
-            "SELECT take_home_projects FROM job_candidates WHERE job_id = $1 AND candidate_id = $2"
-        )
-        .bind(job_uuid)
-        .bind(candidate_uuid)
-        .fetch_optional(&mut **db)
-        .await;
-
-        if let Ok(Some(row)) = existing {
-            let projects: Option<serde_json::Value> = row.get("take_home_projects");
-            if let Some(p) = projects {
-                return RawJson(serde_json::to_string(&p).unwrap());
-            }
-        }
-    }
-
+/// Job and candidate details to generate projects from
+/// Fails with the error body when either is missing, they aren't linked, or the candidate needs enrichment first
+async fn load_generation_context(
+    db: &mut Connection<MainDatabase>,
+    job_uuid: uuid::Uuid,
+    candidate_uuid: uuid::Uuid,
+) -> Result<(JobContext, CandidateContext), RawJson<String>> {
     // Fetch job details
     let job_row = match sqlx::query(
         "SELECT title, description, required_skills, experience_level FROM jobs WHERE id = $1"
     )
     .bind(job_uuid)
-    .fetch_optional(&mut **db)
+    .fetch_optional(&mut ***db)
     .await {
         Ok(Some(row)) => row,
-        Ok(None) => return RawJson(r#"{"error": "Job not found"}"#.to_string()),
-        Err(e) => return RawJson(format!(r#"{{"error": "Database error: {}"}}"#, e)),
+        Ok(None) => return Err(err_json(NOT_FOUND, "Job not found")),
+        Err(e) => return Err(err_json(DATABASE_ERROR, format!("Database error: {}", e))),
     };
 
     let job_context = JobContext {
@@ -133,11 +96,11 @@ pub async fn generate_take_home(
         "SELECT name, skills, links, github_stats, developer_profile FROM sourced_candidates WHERE id = $1"
     )
     .bind(candidate_uuid)
-    .fetch_optional(&mut **db)
+    .fetch_optional(&mut ***db)
     .await {
         Ok(Some(row)) => row,
-        Ok(None) => return RawJson(r#"{"error": "Candidate not found"}"#.to_string()),
-        Err(e) => return RawJson(format!(r#"{{"error": "Database error: {}"}}"#, e)),
+        Ok(None) => return Err(err_json(NOT_FOUND, "Candidate not found")),
+        Err(e) => return Err(err_json(DATABASE_ERROR, format!("Database error: {}", e))),
     };
 
     // Verify candidate is linked to job
@@ -146,11 +109,11 @@ pub async fn generate_take_home(
     )
     .bind(job_uuid)
     .bind(candidate_uuid)
-    .fetch_optional(&mut **db)
+    .fetch_optional(&mut ***db)
     .await;
 
     if let Ok(None) = link_check {
-        return RawJson(r#"{"error": "Candidate is not linked to this job"}"#.to_string());
+        return Err(err_json(NOT_FOUND, "Candidate is not linked to this job"));
     }
 
     let links: serde_json::Value = candidate_row.get("links");
@@ -180,19 +143,89 @@ pub async fn generate_take_home(
         developer_profile: candidate_row.get("developer_profile"),
     };
 
+    // Don't spend a generation call on a candidate with nothing to tailor to
+    if !minimum_profile().is_met(&candidate_context) {
+        let mut body = serde_json::json!(ApiError::new(INVALID_REQUEST, ENRICH_CANDIDATE_MESSAGE));
+        body["needs_enrichment"] = serde_json::json!(true);
+        return Err(RawJson(body.to_string()));
+    }
+
+    Ok((job_context, candidate_context))
+}
+
+/// Generate take-home projects for a candidate linked to a job
+/// POST /api/jobs/{job_id}/candidates/{candidate_id}/take-home-projects
+#[post("/jobs/<job_id>/candidates/<candidate_id>/take-home-projects", data = "<data>")]
+pub async fn generate_take_home(
+    job_id: &str,
+    candidate_id: &str,
+    data: Option<json::Json<GenerateProjectsRequest>>,
+    mut db: Connection<MainDatabase>,
+) -> RawJson<String> {
+    let job_uuid = match uuid::Uuid::parse_str(job_id) {
+        Ok(u) => u,
+        Err(_) => return err_json(INVALID_REQUEST, "Invalid job ID"),
+    };
+    let candidate_uuid = match uuid::Uuid::parse_str(candidate_id) {
+        Ok(u) => u,
+        Err(_) => return err_json(INVALID_REQUEST, "Invalid candidate ID"),
+    };
+    let data = data.map(|d| d.into_inner());
+    let force = data.as_ref().and_then(|d| d.force_regenerate).unwrap_or(false);
+    let rubric = data.and_then(|d| d.rubric);
+
+    if let Some(rubric) = &rubric
+        && let Err(message) = validate_rubric(rubric)
+    {
+        return err_json(INVALID_REQUEST, message);
+    }
+
+    // Check if projects already exist (unless force regenerate)
+    if !force {
+        let existing = sqlx::query(
+            "SELECT take_home_projects FROM job_candidates WHERE job_id = $1 AND candidate_id = $2"
+        )
+        .bind(job_uuid)
+        .bind(candidate_uuid)
+        .fetch_optional(&mut **db)
+        .await;
+
+        if let Ok(Some(row)) = existing {
+            let projects: Option<serde_json::Value> = row.get("take_home_projects");
+            match (projects, &rubric) {
+                // Re-score the stored projects against the supplied rubric without regenerating them
+                (Some(p), Some(rubric)) => {
+                    if let Ok(mut stored) = serde_json::from_value::<TakeHomeProjects>(p) {
+                        apply_rubric(&mut stored, rubric);
+                        if let Err(e) = sqlx::query(
+                            "UPDATE job_candidates SET take_home_projects = $1 WHERE job_id = $2 AND candidate_id = $3"
+                        )
+                        .bind(serde_json::to_value(&stored).unwrap())
+                        .bind(job_uuid)
+                        .bind(candidate_uuid)
+                        .execute(&mut **db)
+                        .await
+                        {
+                            return err_json(DATABASE_ERROR, format!("Database error: {}", e));
+                        }
+                        return RawJson(serde_json::to_string(&stored).unwrap());
+                    }
+                }
+                (Some(p), None) => return RawJson(serde_json::to_string(&p).unwrap()),
+                (None, _) => {}
+            }
+        }
+    }
+
+    let (job_context, candidate_context) = match load_generation_context(&mut db, job_uuid, candidate_uuid).await {
+        Ok(contexts) => contexts,
+        Err(body) => return body,
+    };
+
     // Generate projects
-    let projects: TakeHomeProjects = match generate_take_home_projects(&candidate_context, &job_context).await {
+    let projects: TakeHomeProjects = match generate_take_home_projects(&candidate_context, &job_context, rubric.as_deref()).await {
         Ok(p) => p,
-        Err(e) => {
-            // Escape the error message to produce valid JSON
-            let escaped_error = e.to_string()
-                .replace('\\', "\\\\")
-                .replace('"', "\\\"")
-                .replace('\n', "\\n")
-                .replace('\r', "\\r")
-                .replace('\t', "\\t");
-            return RawJson(format!(r#"{{"error": "Failed to generate projects: {}"}}"#, escaped_error));
-        }
+        Err(e) => return err_json(UPSTREAM_ERROR, format!("Failed to generate projects: {}", e)),
     };
 
     // Store in database
@@ -209,6 +242,70 @@ pub async fn generate_take_home(
     RawJson(serde_json::to_string(&projects).unwrap())
 }
 
+/// Replace one stored take-home project with a newly generated one (keeping its id), leaving the others as they are
+/// POST /api/jobs/{job_id}/candidates/{candidate_id}/take-home-projects/{project_id}/regenerate
+#[post("/jobs/<job_id>/candidates/<candidate_id>/take-home-projects/<project_id>/regenerate")]
+pub async fn regenerate_take_home(
+    job_id: &str,
+    candidate_id: &str,
+    project_id: &str,
+    mut db: Connection<MainDatabase>,
+) -> RawJson<String> {
+    let job_uuid = match uuid::Uuid::parse_str(job_id) {
+        Ok(u) => u,
+        Err(_) => return err_json(INVALID_REQUEST, "Invalid job ID"),
+    };
+    let candidate_uuid = match uuid::Uuid::parse_str(candidate_id) {
+        Ok(u) => u,
+        Err(_) => return err_json(INVALID_REQUEST, "Invalid candidate ID"),
+    };
+
+    let stored = match sqlx::query(
+        "SELECT take_home_projects FROM job_candidates WHERE job_id = $1 AND candidate_id = $2"
+    )
+    .bind(job_uuid)
+    .bind(candidate_uuid)
+    .fetch_optional(&mut **db)
+    .await {
+        Ok(Some(row)) => row.get::<Option<serde_json::Value>, _>("take_home_projects"),
+        Ok(None) => return err_json(NOT_FOUND, "Candidate is not linked to this job"),
+        Err(e) => return err_json(DATABASE_ERROR, format!("Database error: {}", e)),
+    };
+    let Some(stored) = stored else {
+        return err_json(NOT_FOUND, "No projects generated yet");
+    };
+    let mut projects: TakeHomeProjects = match serde_json::from_value(stored) {
+        Ok(p) => p,
+        Err(e) => return err_json(INTERNAL_ERROR, format!("Stored projects are invalid: {}", e)),
+    };
+    if !projects.projects.iter().any(|p| p.id == project_id) {
+        return err_json(NOT_FOUND, "Project not found");
+    }
+
+    let (job_context, candidate_context) = match load_generation_context(&mut db, job_uuid, candidate_uuid).await {
+        Ok(contexts) => contexts,
+        Err(body) => return body,
+    };
+
+    if let Err(e) = regenerate_take_home_project(&candidate_context, &job_context, &mut projects, project_id).await {
+        return err_json(UPSTREAM_ERROR, format!("Failed to regenerate project: {}", e));
+    }
+
+    if let Err(e) = sqlx::query(
+        "UPDATE job_candidates SET take_home_projects = $1 WHERE job_id = $2 AND candidate_id = $3"
+    )
+    .bind(serde_json::to_value(&projects).unwrap())
+    .bind(job_uuid)
+    .bind(candidate_uuid)
+    .execute(&mut **db)
+    .await
+    {
+        return err_json(DATABASE_ERROR, format!("Database error: {}", e));
+    }
+
+    RawJson(serde_json::to_string(&projects).unwrap())
+}
+
 /// Get existing take-home projects for a candidate-job pair
 /// GET /api/jobs/{job_id}/candidates/{candidate_id}/take-home-projects
 #[get("/jobs/<job_id>/candidates/<candidate_id>/take-home-projects")]
@@ -219,11 +316,11 @@ pub async fn get_take_home(
 ) -> RawJson<String> {
     let job_uuid = match uuid::Uuid::parse_str(job_id) {
         Ok(u) => u,
-        Err(_) => return RawJson(r#"{"error": "Invalid job ID"}"#.to_string()),
+        Err(_) => return err_json(INVALID_REQUEST, "Invalid job ID"),
     };
     let candidate_uuid = match uuid::Uuid::parse_str(candidate_id) {
         Ok(u) => u,
-        Err(_) => return RawJson(r#"{"error": "Invalid candidate ID"}"#.to_string()),
+        Err(_) => return err_json(INVALID_REQUEST, "Invalid candidate ID"),
     };
 
     let row = sqlx::query(
@@ -249,15 +346,7 @@ pub async fn get_take_home(
                 None => RawJson(r#"{"projects": null, "message": "No projects generated yet"}"#.to_string()),
             }
         }
-        Ok(None) => RawJson(r#"{"error": "Candidate not linked to this job"}"#.to_string()),
-        Err(e) => {
-            let escaped_error = e.to_string()
-                .replace('\\', "\\\\")
-                .replace('"', "\\\"")
-                .replace('\n', "\\n")
-                .replace('\r', "\\r")
-                .replace('\t', "\\t");
-            RawJson(format!(r#"{{"error": "Database error: {}"}}"#, escaped_error))
-        }
+        Ok(None) => err_json(NOT_FOUND, "Candidate not linked to this job"),
+        Err(e) => err_json(DATABASE_ERROR, format!("Database error: {}", e)),
     }
 }
@@ -4,6 +4,9 @@ use rocket_db_pools::Connection;
 use rocket::response::content::RawJson;
 use serde::{Deserialize, Serialize};
 use crate::db::MainDatabase;
+use crate::github::blocklist::analysis_blocklist;
+use crate::matching::parse_required_skills;
+use crate::matching::team_fit::{compute_ideal_profile, TeamMemberProfile, WorkStyle};
 use sqlx::Row;
 
 #[derive(Deserialize)]
@@ -41,14 +44,6 @@ pub struct Skill {
     level: String,
 }
 
-#[derive(Deserialize, Serialize, Clone)]
-#[serde(crate = "rocket::serde")]
-pub struct WorkStyle {
-    communication: String,
-    collaboration: String,
-    pace: String,
-}
-
 #[derive(Serialize)]
 struct TeamMemberRow {
     id: String,
@@ -102,10 +97,10 @@ fn calculate_team_score(members: &[TeamMemberRow]) -> i32 {
 
     // Work style variety (up to 5 points)
     let comm_styles: HashSet<_> = members.iter()
-        .map(|m| m.work_style.communication.as_str())
+        .map(|m| m.work_style.communication.name())
         .collect();
     let collab_styles: HashSet<_> = members.iter()
-        .map(|m| m.work_style.collaboration.as_str())
+        .map(|m| m.work_style.collaboration.name())
         .collect();
     let has_variety = comm_styles.len() > 1 || collab_styles.len() > 1;
 
@@ -126,11 +121,7 @@ fn parse_team_member_row(m: &sqlx::postgres::PgRow) -> TeamMemberRow {
         role: m.get("role"),
         skills: serde_json::from_value(skills_json.unwrap_or(serde_json::json!([]))).unwrap_or_default(),
         experience_level: m.get::<Option<String>, _>("experience_level").unwrap_or_else(|| "mid".to_string()),
-        work_style: serde_json::from_value(work_style_json.unwrap_or(serde_json::json!({"communication":"mixed","collaboration":"balanced","pace":"steady"}))).unwrap_or(WorkStyle {
-            communication: "mixed".to_string(),
-            collaboration: "balanced".to_string(),
-            pace: "steady".to_string(),
-        }),
+        work_style: work_style_json.and_then(|v| serde_json::from_value(v).ok()).unwrap_or_default(),
         github: m.get("github"),
         linkedin: m.get("linkedin"),
         website: m.get("website"),
@@ -145,6 +136,63 @@ fn parse_team_member_row(m: &sqlx::postgres::PgRow) -> TeamMemberRow {
     }
 }
 
+/// Matching-engine view of a team member
+fn member_profile(member: &TeamMemberRow) -> TeamMemberProfile {
+    TeamMemberProfile {
+        skills: member.skills.iter().map(|s| s.name.clone()).collect(),
+        experience_level: member.experience_level.to_lowercase(),
+        work_style: Some(member.work_style.clone()),
+        code_characteristics: member.code_characteristics.clone()
+            .and_then(|v| serde_json::from_value(v).ok()),
+    }
+}
+
+/// Ideal next hire for a team, with the code-style target explained in plain language
+fn ideal_candidate_response(
+    team_id: &str,
+    role: Option<&str>,
+    members: &[TeamMemberRow],
+    required_skills: &[String],
+) -> serde_json::Value {
+    let profiles: Vec<TeamMemberProfile> = members.iter().map(member_profile).collect();
+    let ideal = compute_ideal_profile(&profiles, required_skills);
+    let code_style_explained = ideal.code_style_target.as_ref()
+        .map(|c| c.explain())
+        .unwrap_or_default();
+
+    serde_json::json!({
+        "team_id": team_id,
+        "role": role,
+        "team_size": members.len(),
+        "skill_gaps": ideal.skill_gaps,
+        "preferred_experience": ideal.preferred_experience,
+        "code_style_target": ideal.code_style_target,
+        "code_style_explained": code_style_explained,
+        "work_style_fit": ideal.work_style_fit,
+    })
+}
+
+/// Required skill names across jobs, deduplicated case-insensitively
+/// With a role, only jobs whose title contains it are considered
+fn required_skill_names(jobs: &[(String, serde_json::Value)], role: Option<&str>) -> Vec<String> {
+    let role_lower = role.map(|r| r.trim().to_lowercase()).filter(|r| !r.is_empty());
+    let mut seen: HashSet<String> = HashSet::new();
+    let mut names: Vec<String> = Vec::new();
+
+    for (title, skills) in jobs {
+        if role_lower.as_ref().is_some_and(|r| !title.to_lowercase().contains(r.as_str())) {
+            continue;
+        }
+        for skill in parse_required_skills(skills) {
+            if seen.insert(skill.name.to_lowercase()) {
+                names.push(skill.name);
+            }
+        }
+    }
+
+    names
+}
+
 const TEAM_MEMBER_SELECT: &str = "SELECT id, name, role, skills, experience_level, work_style, github, linkedin, website, code_characteristics, github_stats, ai_detection_score, ai_proficiency_score, code_authenticity_score, ai_analysis_details, developer_profile, analysis_metadata FROM team_members";
 
 /// Fetch team members and recalculate team score
@@ -238,6 +286,52 @@ pub async fn get_team(id: &str, mut db: Connection<MainDatabase>) -> RawJson<Str
     RawJson(serde_json::to_string(&team).unwrap())
 }
 
+/// Ideal next hire for a team: skills the team lacks for its jobs, preferred seniority and code-style target
+/// GET /api/teams/{team_id}/ideal-candidate?role=Backend
+#[get("/teams/<team_id>/ideal-candidate?<role>")]
+pub async fn get_ideal_candidate(team_id: &str, role: Option<&str>, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    let team_uuid = match uuid::Uuid::parse_str(team_id) {
+        Ok(u) => u,
+        Err(_) => return RawJson(r#"{"error": "Invalid team ID"}"#.to_string()),
+    };
+
+    match sqlx::query("SELECT id FROM teams WHERE id = $1")
+        .bind(team_uuid)
+        .fetch_optional(&mut **db)
+        .await
+    {
+        Ok(Some(_)) => {}
+        Ok(None) => return RawJson(r#"{"error": "Team not found"}"#.to_string()),
+        Err(e) => return RawJson(serde_json::json!({"error": format!("Database error: {}", e)}).to_string()),
+    }
+
+    let members: Vec<TeamMemberRow> = match sqlx::query(&format!("{} WHERE team_id = $1", TEAM_MEMBER_SELECT))
+        .bind(team_uuid)
+        .fetch_all(&mut **db)
+        .await
+    {
+        Ok(rows) => rows.iter().map(parse_team_member_row).collect(),
+        Err(e) => return RawJson(serde_json::json!({"error": format!("Database error: {}", e)}).to_string()),
+    };
+
+    let jobs: Vec<(String, serde_json::Value)> = match sqlx::query("SELECT title, required_skills FROM jobs WHERE team_id = $1")
+        .bind(team_uuid)
+        .fetch_all(&mut **db)
+        .await
+    {
+        Ok(rows) => rows.iter()
+            .map(|r| (
+                r.get::<String, _>("title"),
+                r.get::<Option<serde_json::Value>, _>("required_skills").unwrap_or_else(|| serde_json::json!([])),
+            ))
+            .collect(),
+        Err(e) => return RawJson(serde_json::json!({"error": format!("Database error: {}", e)}).to_string()),
+    };
+
+    let required_skills = required_skill_names(&jobs, role);
+    RawJson(ideal_candidate_response(team_id, role, &members, &required_skills).to_string())
+}
+
 #[post("/teams", data = "<data>")]
 pub async fn create_team<'a>(data: json::Json<CreateTeam<'a>>, mut db: Connection<MainDatabase>) -> RawJson<String> {
     let id = uuid::Uuid::new_v4();
@@ -304,8 +398,140 @@ pub async fn delete_team(id: &str, mut db: Connection<MainDatabase>) -> RawJson<
     RawJson(format!(r#"{{"success":true,"id":"{}"}}"#, id))
 }
 
+/// Whether a background analysis may be written: only the latest request's results are kept
+/// Mirrors the `analysis_version <= $n` guard in `store_member_analysis`
+fn is_latest_analysis(stored_version: i32, result_version: i32) -> bool {
+    stored_version <= result_version
+}
+
+/// Results of a member's background GitHub analysis; None fields leave the stored value alone
+struct MemberAnalysis {
+    code_characteristics: Option<serde_json::Value>,
+    github_stats: Option<crate::github::stats::GitHubStats>,
+    developer_profile: Option<String>,
+}
+
+/// Write an analysis unless a newer one was requested since; returns whether it was written
+async fn store_member_analysis(executor: impl sqlx::PgExecutor<'_>, member_uuid: uuid::Uuid, version: i32, analysis: &MemberAnalysis) -> bool {
+    let stats = analysis.github_stats.as_ref();
+
+    let result = sqlx::query(
+        r#"UPDATE team_members SET
+           code_characteristics = COALESCE($1, code_characteristics),
+           github_stats = COALESCE($2, github_stats),
+           ai_detection_score = COALESCE($3, ai_detection_score),
+           ai_proficiency_score = COALESCE($4, ai_proficiency_score),
+           code_authenticity_score = COALESCE($5, code_authenticity_score),
+           ai_analysis_details = COALESCE($6, ai_analysis_details),
+           analysis_metadata = COALESCE($7, analysis_metadata),
+           developer_profile = COALESCE($8, developer_profile)
+           WHERE id = $9 AND COALESCE(analysis_version, 0) <= $10"#
+    )
+    .bind(&analysis.code_characteristics)
+    .bind(stats.map(|s| serde_json::to_value(s).unwrap()))
+    .bind(stats.map(|s| s.ai_analysis.ai_detection_score as f64))
+    .bind(stats.map(|s| s.ai_analysis.ai_proficiency_score as f64))
+    .bind(stats.map(|s| s.ai_analysis.code_authenticity_score as f64))
+    .bind(stats.map(|s| serde_json::to_value(&s.ai_analysis.analysis_details).unwrap()))
+    .bind(stats.map(|s| serde_json::to_value(&s.analysis_metadata).unwrap()))
+    .bind(&analysis.developer_profile)
+    .bind(member_uuid)
+    .bind(version)
+    .execute(executor)
+    .await;
+
+    matches!(result, Ok(done) if done.rows_affected() > 0)
+}
+
+/// Start code analysis, GitHub stats and profile generation for a member in the background
+/// Each request bumps `analysis_version`, so a slower, older run can't overwrite a newer one
+async fn queue_member_analysis(member_uuid: uuid::Uuid, github: &str, db: &mut Connection<MainDatabase>) {
+    let db_url = std::env::var("DATABASE_URL").unwrap_or_default();
+    let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
+
+    if db_url.is_empty() || token.is_empty() {
+        return;
+    }
+
+    if analysis_blocklist().is_blocked(github) {
+        println!("[Teams] Not analyzing member {}: analysis not permitted for {}", member_uuid, github);
+        return;
+    }
+
+    let (version, role) = match sqlx::query_as::<_, (i32, Option<String>)>(
+        "UPDATE team_members SET analysis_version = COALESCE(analysis_version, 0) + 1 WHERE id = $1 RETURNING analysis_version, role"
+    )
+    .bind(member_uuid)
+    .fetch_one(&mut ***db)
+    .await
+    {
+        Ok(row) => row,
+        Err(e) => {
+            println!("[Teams] Could not queue analysis for member {}: {}", member_uuid, e);
+            return;
+        }
+    };
+
+    let github = github.to_string();
+    tokio::spawn(async move {
+        // Get code characteristics - use .ok() to drop non-Send error immediately
+        let chars = crate::code_analysis::ai::generate_characteristics_from_github(&github, &token)
+            .await
+            .ok();
+
+        // Get GitHub stats with AI analysis
+        let stats = crate::github::analyze::analyze_github_user(&github, &token)
+            .await
+            .ok();
+
+        let Ok(pool) = sqlx::PgPool::connect(&db_url).await else {
+            return;
+        };
+
+        // Don't spend a profile generation call on results that are already superseded
+        let current = sqlx::query_scalar::<_, Option<i32>>("SELECT analysis_version FROM team_members WHERE id = $1")
+            .bind(member_uuid)
+            .fetch_optional(&pool)
+            .await;
+        if let Ok(Some(current)) = current
+            && !is_latest_analysis(current.unwrap_or(0), version)
+        {
+            println!("[Teams] Discarded analysis v{} for member {} (v{} requested since)", version, member_uuid, current.unwrap_or(0));
+            return;
+        }
+
+        // Generate developer profile from stats, citing the excerpts that matter for the member's role
+        let profile = if let Some(ref s) = stats {
+            crate::github::ai_summary::generate_developer_profile(
+                s,
+                crate::github::ai_summary::ProfileStyle::Narrative,
+                crate::github::semantic_search::SearchCategory::for_role(role.as_deref().unwrap_or_default()),
+            )
+                .await
+                .ok()
+        } else {
+            None
+        };
+
+        let analysis = MemberAnalysis {
+            code_characteristics: chars.map(|c| serde_json::to_value(&c).unwrap()),
+            github_stats: stats,
+            developer_profile: profile,
+        };
+
+        // The version guard in the UPDATE also covers a newer request landing during profile generation
+        if !store_member_analysis(&pool, member_uuid, version, &analysis).await {
+            println!("[Teams] Discarded analysis v{} for member {} (superseded or member removed)", version, member_uuid);
+        }
+    });
+}
+
 #[post("/teams/<team_id>/members", data = "<data>")]
 pub async fn add_team_member<'a>(team_id: &str, data: json::Json<CreateTeamMember<'a>>, mut db: Connection<MainDatabase>) -> RawJson<String> {
+    if let Err(message) = data.work_style.validate() {
+        return RawJson(serde_json::json!({ "error": message }).to_string());
+    }
+
     let id = uuid::Uuid::new_v4();
     let team_uuid = uuid::Uuid::parse_str(team_id).unwrap();
 
@@ -328,75 +554,7 @@ pub async fn add_team_member<'a>(team_id: &str, data: json::Json<CreateTeamMembe
 
     // Spawn background task for code analysis and GitHub stats if GitHub is provided
     if let Some(github) = data.github {
-        let github = github.to_string();
-        let member_id = id.to_string();
-        let db_url = std::env::var("DATABASE_URL").unwrap_or_default();
-        let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
-
-        if !db_url.is_empty() && !token.is_empty() {
-            tokio::spawn(async move {
-                // Get code characteristics - use .ok() to drop non-Send error immediately
-                let chars = crate::code_analysis::ai::generate_characteristics_from_github(&github, &token)
-                    .await
-                    .ok();
-
-                // Get GitHub stats with AI analysis
-                let stats = crate::github::analyze::analyze_github_user(&github, &token)
-                    .await
-                    .ok();
-
-                // Generate developer profile from stats
-                let profile = if let Some(ref s) = stats {
-                    crate::github::ai_summary::generate_developer_profile(s)
-                        .await
-                        .ok()
-                } else {
-                    None
-                };
-
-                if let Ok(pool) = sqlx::PgPool::connect(&db_url).await {
-                    let member_uuid = uuid::Uuid::parse_str(&member_id).unwrap();
-
-                    if let Some(chars) = chars {
-                        let _ = sqlx::query("UPDATE team_members SET code_characteristics = $1 WHERE id = $2")
-                            .bind(serde_json::to_value(&chars).unwrap())
-                            .bind(member_uuid)
-                            .execute(&pool)
-                            .await;
-                    }
-
-                    if let Some(ref stats) = stats {
-                        // Store full stats as JSON
-                        let _ = sqlx::query("UPDATE team_members SET github_stats = $1 WHERE id = $2")
-                            .bind(serde_json::to_value(&stats).unwrap())
-                            .bind(member_uuid)
-                            .execute(&pool)
-                            .await;
-
-                        // Also store AI analysis in dedicated columns for easier access
-                        let _ = sqlx::query(
-                            "UPDATE team_members SET ai_detection_score = $1, ai_proficiency_score = $2, code_authenticity_score = $3, ai_analysis_details = $4, analysis_metadata = $5 WHERE id = $6"
-                        )
-                            .bind(stats.ai_analysis.ai_detection_score as f64)
-                            .bind(stats.ai_analysis.ai_proficiency_score as f64)
-                            .bind(stats.ai_analysis.code_authenticity_score as f64)
-                            .bind(serde_json::to_value(&stats.ai_analysis.analysis_details).unwrap())
-                            .bind(serde_json::to_value(&stats.analysis_metadata).unwrap())
-                            .bind(member_uuid)
-                            .execute(&pool)
-                            .await;
-                    }
-
-                    if let Some(profile) = profile {
-                        let _ = sqlx::query("UPDATE team_members SET developer_profile = $1 WHERE id = $2")
-                            .bind(&profile)
-                            .bind(member_uuid)
-                            .execute(&pool)
-                            .await;
-                    }
-                }
-            });
-        }
+        queue_member_analysis(id, github, &mut db).await;
     }
 
     // Recalculate team score after adding member
@@ -465,6 +623,10 @@ pub async fn update_team_member<'a>(
 ) -> RawJson<String> {
     let member_uuid = uuid::Uuid::parse_str(member_id).unwrap();
 
+    if let Some(Err(message)) = data.work_style.as_ref().map(|w| w.validate()) {
+        return RawJson(serde_json::json!({ "error": message }).to_string());
+    }
+
     // Build dynamic update query
     if let Some(name) = data.name {
         sqlx::query("UPDATE team_members SET name = $1 WHERE id = $2")
@@ -505,72 +667,7 @@ pub async fn update_team_member<'a>(
 
         // Trigger background code analysis and GitHub stats if GitHub changed
         if let Some(gh) = github_val {
-            let gh = gh.to_string();
-            let mid = member_id.to_string();
-            let db_url = std::env::var("DATABASE_URL").unwrap_or_default();
-            let token = std::env::var("GITHUB_TOKEN").unwrap_or_default();
-
-            if !db_url.is_empty() && !token.is_empty() {
-                tokio::spawn(async move {
-                    let chars = crate::code_analysis::ai::generate_characteristics_from_github(&gh, &token)
-                        .await
-                        .ok();
-
-                    let stats = crate::github::analyze::analyze_github_user(&gh, &token)
-                        .await
-                        .ok();
-
-                    // Generate developer profile from stats
-                    let profile = if let Some(ref s) = stats {
-                        crate::github::ai_summary::generate_developer_profile(s)
-                            .await
-                            .ok()
-                    } else {
-                        None
-                    };
-
-                    if let Ok(pool) = sqlx::PgPool::connect(&db_url).await {
-                        let muuid = uuid::Uuid::parse_str(&mid).unwrap();
-
-                        if let Some(chars) = chars {
-                            let _ = sqlx::query("UPDATE team_members SET code_characteristics = $1 WHERE id = $2")
-                                .bind(serde_json::to_value(&chars).unwrap())
-                                .bind(muuid)
-                                .execute(&pool)
-                                .await;
-                        }
-
-                        if let Some(ref stats) = stats {
-                            let _ = sqlx::query("UPDATE team_members SET github_stats = $1 WHERE id = $2")
-                                .bind(serde_json::to_value(&stats).unwrap())
-                                .bind(muuid)
-                                .execute(&pool)
-                                .await;
-
-                            // Also store AI analysis in dedicated columns
-                            let _ = sqlx::query(
-                                "UPDATE team_members SET ai_detection_score = $1, ai_proficiency_score = $2, code_authenticity_score = $3, ai_analysis_details = $4, analysis_metadata = $5 WHERE id = $6"
-                            )
-                                .bind(stats.ai_analysis.ai_detection_score as f64)
-                                .bind(stats.ai_analysis.ai_proficiency_score as f64)
-                                .bind(stats.ai_analysis.code_authenticity_score as f64)
-                                .bind(serde_json::to_value(&stats.ai_analysis.analysis_details).unwrap())
-                                .bind(serde_json::to_value(&stats.analysis_metadata).unwrap())
-                                .bind(muuid)
-                                .execute(&pool)
-                                .await;
-                        }
-
-                        if let Some(profile) = profile {
-                            let _ = sqlx::query("UPDATE team_members SET developer_profile = $1 WHERE id = $2")
-                                .bind(&profile)
-                                .bind(muuid)
-                                .execute(&pool)
-                                .await;
-                        }
-                    }
-                });
-            }
+            queue_member_analysis(member_uuid, gh, &mut db).await;
         }
     }
     if let Some(linkedin) = data.linkedin {
@@ -603,3 +700,126 @@ pub async fn update_team_member<'a>(
 
     RawJson(serde_json::to_string(&member).unwrap())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::team_fit::Communication;
+
+    fn member(name: &str, skills: &[&str], level: &str, modularity: Option<f32>) -> TeamMemberRow {
+        TeamMemberRow {
+            id: uuid::Uuid::new_v4().to_string(),
+            name: name.to_string(),
+            role: "Engineer".to_string(),
+            skills: skills.iter().map(|s| Skill { name: s.to_string(), level: "advanced".to_string() }).collect(),
+            experience_level: level.to_string(),
+            work_style: WorkStyle {
+                communication: Communication::Async,
+                ..WorkStyle::default()
+            },
+            github: None,
+            linkedin: None,
+            website: None,
+            code_characteristics: modularity.map(|m| serde_json::json!({
+                "avg_lines_per_function": 12.0,
+                "functional_vs_oop_ratio": 0.5,
+                "recursion_vs_loop_ratio": 0.5,
+                "dependency_coupling_index": 0.5,
+                "modularity_index_score": m,
+                "avg_nesting_depth": 3.0,
+                "abstraction_layer_count": 3.0,
+                "immutability_score": 0.8,
+                "error_handling_centralization_score": 0.5,
+                "test_structure_modularity_ratio": 0.5,
+                "files_analyzed": 12
+            })),
+            github_stats: None,
+            ai_detection_score: None,
+            ai_proficiency_score: None,
+            code_authenticity_score: None,
+            ai_analysis_details: None,
+            developer_profile: None,
+            analysis_metadata: None,
+        }
+    }
+
+    #[test]
+    fn test_ideal_candidate_surfaces_missing_skill() {
+        let members = vec![
+            member("Ana", &["Rust", "PostgreSQL"], "senior", Some(0.8)),
+            member("Ben", &["TypeScript"], "mid", Some(0.9)),
+        ];
+        let jobs = vec![
+            ("Backend Engineer".to_string(), serde_json::json!(["Rust", {"name": "Go", "level": "advanced"}, "postgresql"])),
+            ("Frontend Engineer".to_string(), serde_json::json!(["React"])),
+        ];
+
+        let skills = required_skill_names(&jobs, Some("backend"));
+        assert_eq!(skills, vec!["Rust", "Go", "postgresql"]);
+
+        let response = ideal_candidate_response("team-1", Some("backend"), &members, &skills);
+
+        assert_eq!(response["skill_gaps"], serde_json::json!(["Go"]));
+        assert_eq!(response["preferred_experience"], "senior");
+        assert_eq!(response["team_size"], 2);
+        let explained: Vec<&str> = response["code_style_explained"].as_array().unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert!(explained.contains(&"highly modular (0.85)"));
+        assert!(explained.contains(&"immutability-first (0.80)"));
+    }
+
+    #[test]
+    fn test_required_skills_without_role_span_all_jobs() {
+        let jobs = vec![
+            ("Backend Engineer".to_string(), serde_json::json!(["Rust"])),
+            ("Frontend Engineer".to_string(), serde_json::json!(["React", "rust"])),
+        ];
+
+        assert_eq!(required_skill_names(&jobs, None), vec!["Rust", "React"]);
+    }
+
+    fn member_analysis(profile: &str) -> MemberAnalysis {
+        MemberAnalysis {
+            code_characteristics: None,
+            github_stats: None,
+            developer_profile: Some(profile.to_string()),
+        }
+    }
+
+    // Needs a Postgres with schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_older_analysis_does_not_overwrite_newer() {
+        use sqlx::Connection as _;
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = sqlx::PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        // GitHub updated twice: requests got v1 then v2, and v2 finishes first
+        let member_uuid: uuid::Uuid = sqlx::query_scalar(
+            "INSERT INTO team_members (name, role, analysis_version) VALUES ('Version Test', 'Engineer', 2) RETURNING id"
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .unwrap();
+
+        assert!(store_member_analysis(&mut *tx, member_uuid, 2, &member_analysis("new analysis")).await);
+        assert!(!store_member_analysis(&mut *tx, member_uuid, 1, &member_analysis("old analysis")).await);
+
+        let (profile, version): (Option<String>, Option<i32>) =
+            sqlx::query_as("SELECT developer_profile, analysis_version FROM team_members WHERE id = $1")
+                .bind(member_uuid)
+                .fetch_one(&mut *tx)
+                .await
+                .unwrap();
+        assert_eq!(profile.as_deref(), Some("new analysis"));
+        assert_eq!(version, Some(2));
+
+        tx.rollback().await.unwrap();
+    }
+}
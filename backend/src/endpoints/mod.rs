@@ -1,3 +1,4 @@
+pub mod api_error;
 pub mod ep_add_to_db;
 pub mod ep_analyse_repo;
 pub mod ep_analyse_github;
@@ -9,6 +10,9 @@ pub mod ep_candidates;
 pub mod ep_github_analysis;
 pub mod ep_take_home;
 pub mod ep_resumes;
+pub mod ep_score_preview;
+pub mod ep_ready;
+pub mod ep_skill_synonyms;
 
 pub use ep_add_to_db::*;
 pub use ep_analyse_repo::*;
@@ -21,3 +25,6 @@ pub use ep_candidates::*;
 pub use ep_github_analysis::*;
 pub use ep_take_home::*;
 pub use ep_resumes::*;
+pub use ep_score_preview::*;
+pub use ep_ready::*;
+pub use ep_skill_synonyms::*;
@@ -0,0 +1,198 @@
+use std::collections::HashSet;
+use std::future::Future;
+
+use chrono::{DateTime, Datelike, Duration, Utc};
+
+use crate::github::api::{get_user_commits, GitHubCommit, GitHubRepoFull, COMMITS_PER_PAGE};
+use crate::github::stats::ActivityMetrics;
+
+/// Most repos whose commits are fetched for activity metrics
+const MAX_ACTIVITY_REPOS: usize = 10;
+const ACTIVITY_WINDOW_DAYS: i64 = 365;
+const RECENT_WINDOW_DAYS: i64 = 90;
+/// Most pages of commits fetched for one repo
+const MAX_PAGES_PER_REPO: u32 = 10;
+/// Most pages of commits fetched across all repos of one analysis
+const MAX_ACTIVITY_PAGES: u32 = 20;
+/// Pause between repos on top of `github_get`'s rate-limit backoff
+const REPO_PACING_MS: u64 = 50;
+
+/// When the commit was authored, if GitHub reported a parseable date
+pub fn commit_date(commit: &GitHubCommit) -> Option<DateTime<Utc>> {
+    let date = commit.commit.author.as_ref()?.date.as_deref()?;
+    DateTime::parse_from_rfc3339(date).ok().map(|d| d.with_timezone(&Utc))
+}
+
+/// Activity metrics from each repo's commit dates; commits older than a year (or in the future) are ignored
+pub fn compute_activity(commits_by_repo: &[Vec<DateTime<Utc>>], now: DateTime<Utc>) -> ActivityMetrics {
+    let year_start = now - Duration::days(ACTIVITY_WINDOW_DAYS);
+    let recent_start = now - Duration::days(RECENT_WINDOW_DAYS);
+    let in_year = |date: &&DateTime<Utc>| **date > year_start && **date <= now;
+
+    let mut metrics = ActivityMetrics::default();
+    let mut active_weeks = HashSet::new();
+    for dates in commits_by_repo {
+        let mut pushed = false;
+        for date in dates.iter().filter(in_year) {
+            pushed = true;
+            metrics.commits_last_year += 1;
+            if *date > recent_start {
+                metrics.commits_last_90_days += 1;
+            }
+            let week = date.iso_week();
+            active_weeks.insert((week.year(), week.week()));
+        }
+        if pushed {
+            metrics.repos_pushed_last_year += 1;
+        }
+    }
+
+    if !active_weeks.is_empty() {
+        let average = metrics.commits_last_year as f32 / active_weeks.len() as f32;
+        metrics.avg_commits_per_active_week = (average * 10.0).round() / 10.0;
+    }
+    metrics
+}
+
+/// Activity across the user's own repos updated in the last year (most recent first, up to MAX_ACTIVITY_REPOS)
+/// Repos whose commits can't be fetched are left out, and fetching stops once MAX_ACTIVITY_PAGES pages are used
+pub async fn collect_activity(username: &str, repos: &[GitHubRepoFull], token: &str) -> ActivityMetrics {
+    let fetch_commits = |repo: &GitHubRepoFull, since: DateTime<Utc>, max_pages: u32| {
+        let (owner, name) = (repo.owner.login.clone(), repo.name.clone());
+        async move { get_user_commits(&owner, &name, username, Some(since), max_pages, token).await }
+    };
+    collect_activity_with(repos, Utc::now(), fetch_commits).await
+}
+
+/// `collect_activity` with the commit fetch supplied by the caller
+async fn collect_activity_with<F, Fut>(repos: &[GitHubRepoFull], now: DateTime<Utc>, fetch_commits: F) -> ActivityMetrics
+where
+    F: Fn(&GitHubRepoFull, DateTime<Utc>, u32) -> Fut,
+    Fut: Future<Output = Result<Vec<GitHubCommit>, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let since = now - Duration::days(ACTIVITY_WINDOW_DAYS);
+    // A repo not updated since then can't have commits in the window
+    let updated_recently = |repo: &GitHubRepoFull| {
+        DateTime::parse_from_rfc3339(&repo.updated_at).map_or(true, |updated| updated > since)
+    };
+    let mut recent: Vec<&GitHubRepoFull> = repos.iter().filter(|r| !r.fork && updated_recently(r)).collect();
+    recent.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+
+    let mut commits_by_repo = Vec::new();
+    let mut pages_left = MAX_ACTIVITY_PAGES;
+    for repo in recent.into_iter().take(MAX_ACTIVITY_REPOS) {
+        if pages_left == 0 {
+            println!("[Activity] Page budget used up, skipping {} and later repos", repo.name);
+            break;
+        }
+        let max_pages = pages_left.min(MAX_PAGES_PER_REPO);
+        match fetch_commits(repo, since, max_pages).await {
+            Ok(commits) => {
+                // Every full page was followed by another request, unless the cap stopped it
+                let pages_used = (commits.len() / COMMITS_PER_PAGE) as u32 + 1;
+                pages_left -= pages_used.min(max_pages);
+                commits_by_repo.push(commits.iter().filter_map(commit_date).collect());
+            }
+            Err(e) => {
+                pages_left -= 1;
+                println!("[Activity] Skipping {} - commits error: {}", repo.name, e);
+            }
+        }
+        tokio::time::sleep(tokio::time::Duration::from_millis(REPO_PACING_MS)).await;
+    }
+
+    compute_activity(&commits_by_repo, now)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(name: &str, updated_at: &str) -> GitHubRepoFull {
+        serde_json::from_value(serde_json::json!({
+            "name": name, "owner": {"login": "octocat"}, "fork": false, "description": null,
+            "stargazers_count": 0, "forks_count": 0, "watchers_count": 0, "language": "Rust",
+            "size": 100, "created_at": "2020-01-01T00:00:00Z", "updated_at": updated_at
+        }))
+        .unwrap()
+    }
+
+    /// A commits API response body with one commit per date
+    fn commits_response(dates: &[DateTime<Utc>]) -> String {
+        let commits: Vec<serde_json::Value> = dates
+            .iter()
+            .enumerate()
+            .map(|(i, date)| serde_json::json!({
+                "sha": format!("{:040}", i),
+                "commit": {"message": "work", "author": {"name": "Octo", "date": date.to_rfc3339()}}
+            }))
+            .collect();
+        serde_json::to_string(&commits).unwrap()
+    }
+
+    #[rocket::async_test]
+    async fn test_activity_from_mocked_commits() {
+        let now = DateTime::parse_from_rfc3339("2025-06-30T12:00:00Z").unwrap().with_timezone(&Utc);
+        let days_ago = |days: i64| now - Duration::days(days);
+
+        let repos = vec![
+            repo("api", "2025-06-29T00:00:00Z"),
+            repo("cli", "2025-01-10T00:00:00Z"),
+            repo("legacy", "2023-03-01T00:00:00Z"),
+        ];
+        let fetched = std::sync::Mutex::new(Vec::new());
+        let fetch_commits = |repo: &GitHubRepoFull, since: DateTime<Utc>, max_pages: u32| {
+            fetched.lock().unwrap().push(repo.name.clone());
+            assert_eq!(since, days_ago(365));
+            assert_eq!(max_pages, MAX_PAGES_PER_REPO);
+            let body = match repo.name.as_str() {
+                // Two commits in the same week inside the 90 days, one just outside, one from last year
+                "api" => commits_response(&[days_ago(1), days_ago(2), days_ago(91), days_ago(400)]),
+                "cli" => commits_response(&[days_ago(80), days_ago(170)]),
+                _ => commits_response(&[days_ago(800)]),
+            };
+            async move { Ok(serde_json::from_str::<Vec<GitHubCommit>>(&body)?) }
+        };
+
+        let metrics = collect_activity_with(&repos, now, fetch_commits).await;
+        assert_eq!(metrics.commits_last_90_days, 3);
+        assert_eq!(metrics.commits_last_year, 5);
+        assert_eq!(metrics.repos_pushed_last_year, 2);
+        // 5 commits over 4 active weeks
+        assert_eq!(metrics.avg_commits_per_active_week, 1.3);
+        // Repos untouched for over a year aren't fetched
+        assert_eq!(*fetched.lock().unwrap(), vec!["api", "cli"]);
+    }
+
+    #[rocket::async_test]
+    async fn test_activity_stops_at_page_budget() {
+        let now = DateTime::parse_from_rfc3339("2025-06-30T12:00:00Z").unwrap().with_timezone(&Utc);
+        let repos: Vec<GitHubRepoFull> = (0..5).map(|i| repo(&format!("repo{}", i), "2025-06-29T00:00:00Z")).collect();
+        let busy = commits_response(&vec![now - Duration::days(1); MAX_PAGES_PER_REPO as usize * COMMITS_PER_PAGE]);
+
+        let page_caps = std::sync::Mutex::new(Vec::new());
+        let fetch_commits = |_: &GitHubRepoFull, _: DateTime<Utc>, max_pages: u32| {
+            page_caps.lock().unwrap().push(max_pages);
+            // Every repo fills all the pages it's allowed
+            let body = busy.clone();
+            async move {
+                let mut commits = serde_json::from_str::<Vec<GitHubCommit>>(&body)?;
+                commits.truncate(max_pages as usize * COMMITS_PER_PAGE);
+                Ok(commits)
+            }
+        };
+
+        let metrics = collect_activity_with(&repos, now, fetch_commits).await;
+        // Two full repos use up the budget; the rest aren't fetched
+        assert_eq!(*page_caps.lock().unwrap(), vec![MAX_PAGES_PER_REPO, MAX_PAGES_PER_REPO]);
+        assert_eq!(metrics.repos_pushed_last_year, 2);
+        assert_eq!(metrics.commits_last_year, MAX_ACTIVITY_PAGES * COMMITS_PER_PAGE as u32);
+    }
+
+    #[test]
+    fn test_no_recent_commits() {
+        let now = Utc::now();
+        assert_eq!(compute_activity(&[], now), ActivityMetrics::default());
+        assert_eq!(compute_activity(&[vec![now - Duration::days(366)], vec![]], now), ActivityMetrics::default());
+    }
+}
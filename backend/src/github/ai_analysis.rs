@@ -1,9 +1,12 @@
 use genai::{
     Client,
-    chat::{ChatMessage, ChatOptions, ChatRequest},
+    chat::{ChatMessage, ChatRequest},
 };
 use serde::Deserialize;
+use std::future::Future;
 
+use crate::ai_response::{clamp_score, log_fallback, parse_json_repaired, AiResponseError};
+use crate::ai_temperature::{chat_options, temperatures};
 use crate::github::stats::{AIAnalysis, AnalysisDetails};
 
 const MODEL_GEMINI: &str = "gemini-2.0-flash";
@@ -71,6 +74,12 @@ Scoring Guidelines:
 
 Return ONLY the JSON object, no additional text or markdown formatting."#;
 
+/// Follow-up sent after a response that couldn't be parsed
+const JSON_RETRY_PROMPT: &str = "Your previous output was not valid JSON. Return only the JSON object, with no other text.";
+
+/// Re-prompts after a response that isn't valid JSON (request failures are not retried)
+const MAX_JSON_RETRIES: usize = 2;
+
 #[derive(Deserialize)]
 struct AIAnalysisResponse {
     ai_detection_score: f32,
@@ -86,79 +95,166 @@ struct AnalysisDetailsResponse {
     reasoning: String,
 }
 
-pub async fn analyze_code_for_ai_usage(
-    code_samples: &str,
-) -> Result<AIAnalysis, Box<dyn std::error::Error + Send + Sync>> {
+/// AI usage analysis of the code samples, neutral defaults when the AI is unavailable
+pub async fn analyze_code_for_ai_usage(code_samples: &str) -> AIAnalysis {
     if code_samples.trim().is_empty() {
-        return Ok(AIAnalysis::default());
+        return AIAnalysis::default();
     }
 
     let client = Client::default();
-    let options = ChatOptions::default().with_temperature(0.0);
+    let options = chat_options(temperatures().ai_detection, None);
 
-    let chat_req = ChatRequest::new(vec![
-        ChatMessage::system(AI_ANALYSIS_PROMPT),
-        ChatMessage::user(code_samples.to_string()),
-    ]);
+    let response = request_analysis_with(code_samples, |messages| {
+        let (client, options) = (&client, &options);
+        async move {
+            client
+                .exec_chat(MODEL_GEMINI, ChatRequest::new(messages), Some(options))
+                .await
+                .map(|chat_res| chat_res.content.joined_texts())
+                .map_err(AiResponseError::from_request_error)
+        }
+    })
+    .await;
 
-    let chat_res = client
-        .exec_chat(MODEL_GEMINI, chat_req, Some(&options))
-        .await?;
+    analysis_from_response(response)
+}
 
-    let res = chat_res
-        .content
-        .joined_texts()
-        .ok_or("Failed to get response text")?;
+/// Ask `chat` for the analysis, re-prompting for plain JSON while the response doesn't parse
+async fn request_analysis_with<F, Fut>(code_samples: &str, mut chat: F) -> Result<AIAnalysisResponse, AiResponseError>
+where
+    F: FnMut(Vec<ChatMessage>) -> Fut,
+    Fut: Future<Output = Result<Option<String>, AiResponseError>>,
+{
+    let mut messages = vec![
+        ChatMessage::system(AI_ANALYSIS_PROMPT),
+        ChatMessage::user(code_samples.to_string()),
+    ];
 
-    // Parse JSON response (handle potential markdown code blocks)
-    let json_str = extract_json(&res);
+    let mut attempt = 0;
+    loop {
+        let text = chat(messages.clone()).await?;
+        match parse_json_repaired::<AIAnalysisResponse>(text.clone()) {
+            Err(AiResponseError::Malformed(e)) if attempt < MAX_JSON_RETRIES => {
+                attempt += 1;
+                println!("[AI] AI usage analysis returned invalid JSON ({}), retry {}/{}", e, attempt, MAX_JSON_RETRIES);
+                messages.push(ChatMessage::assistant(text.unwrap_or_default()));
+                messages.push(ChatMessage::user(JSON_RETRY_PROMPT));
+            }
+            result => return result,
+        }
+    }
+}
 
-    let response: AIAnalysisResponse = serde_json::from_str(&json_str)
-        .map_err(|e| format!("Failed to parse AI analysis response: {}. Raw: {}", e, json_str))?;
+fn analysis_from_response(response: Result<AIAnalysisResponse, AiResponseError>) -> AIAnalysis {
+    let response = match response {
+        Ok(response) => response,
+        Err(e) => {
+            log_fallback("AI usage analysis", &e, "neutral defaults");
+            return AIAnalysis::default();
+        }
+    };
 
-    Ok(AIAnalysis {
-        ai_detection_score: response.ai_detection_score.clamp(0.0, 100.0),
-        ai_proficiency_score: response.ai_proficiency_score.clamp(0.0, 100.0),
-        code_authenticity_score: response.code_authenticity_score.clamp(0.0, 100.0),
+    AIAnalysis {
+        ai_detection_score: clamp_score(response.ai_detection_score as f64, 100.0) as f32,
+        ai_proficiency_score: clamp_score(response.ai_proficiency_score as f64, 100.0) as f32,
+        code_authenticity_score: clamp_score(response.code_authenticity_score as f64, 100.0) as f32,
         analysis_details: AnalysisDetails {
             patterns_detected: response.analysis_details.patterns_detected,
-            confidence: response.analysis_details.confidence.clamp(0.0, 1.0),
+            confidence: clamp_score(response.analysis_details.confidence as f64, 1.0) as f32,
             reasoning: response.analysis_details.reasoning,
         },
-    })
-}
-
-fn extract_json(response: &str) -> String {
-    let lines: Vec<&str> = response.lines().collect();
-
-    // Handle markdown code blocks
-    if lines.len() > 2 && lines[0].contains("```") {
-        return lines[1..lines.len()-1].join("\n");
     }
-
-    // Try to find JSON object boundaries
-    if let Some(start) = response.find('{') {
-        if let Some(end) = response.rfind('}') {
-            return response[start..=end].to_string();
-        }
-    }
-
-    response.to_string()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    const VALID_RESPONSE: &str = r#"{
+        "ai_detection_score": 35,
+        "ai_proficiency_score": 60,
+        "code_authenticity_score": 80,
+        "analysis_details": {"patterns_detected": ["idiomatic {error} handling"], "confidence": 0.7, "reasoning": "Personal style"}
+    }"#;
+
+    #[test]
+    fn test_empty_response_gives_neutral_defaults() {
+        let analysis = analysis_from_response(parse_json_repaired(Some(String::new())));
+        let defaults = AIAnalysis::default();
+
+        assert_eq!(analysis.ai_detection_score, defaults.ai_detection_score);
+        assert_eq!(analysis.code_authenticity_score, defaults.code_authenticity_score);
+        assert!(analysis.analysis_details.patterns_detected.is_empty());
+    }
+
     #[test]
-    fn test_extract_json_plain() {
-        let input = r#"{"ai_detection_score": 50}"#;
-        assert_eq!(extract_json(input), input);
+    fn test_out_of_range_and_nan_scores_are_clamped() {
+        let analysis = analysis_from_response(Ok(AIAnalysisResponse {
+            ai_detection_score: 180.0,
+            ai_proficiency_score: f32::NAN,
+            code_authenticity_score: -20.0,
+            analysis_details: AnalysisDetailsResponse {
+                patterns_detected: vec![],
+                confidence: f32::INFINITY,
+                reasoning: String::new(),
+            },
+        }));
+
+        assert_eq!(analysis.ai_detection_score, 100.0);
+        assert_eq!(analysis.ai_proficiency_score, 0.0);
+        assert_eq!(analysis.code_authenticity_score, 0.0);
+        assert_eq!(analysis.analysis_details.confidence, 1.0);
     }
 
     #[test]
-    fn test_extract_json_with_code_block() {
-        let input = "```json\n{\"ai_detection_score\": 50}\n```";
-        assert_eq!(extract_json(input), "{\"ai_detection_score\": 50}");
+    fn test_trailing_prose_and_code_fence_parse() {
+        let with_prose = format!("{}\n\nNote: scores are estimates, see {{details}} above.", VALID_RESPONSE);
+        let analysis = analysis_from_response(parse_json_repaired(Some(with_prose)));
+        assert_eq!(analysis.ai_detection_score, 35.0);
+        assert_eq!(analysis.analysis_details.patterns_detected, vec!["idiomatic {error} handling".to_string()]);
+
+        let fenced = format!("Here is the analysis:\n```json\n{}\n```", VALID_RESPONSE);
+        let analysis = analysis_from_response(parse_json_repaired(Some(fenced)));
+        assert_eq!(analysis.code_authenticity_score, 80.0);
+    }
+
+    #[rocket::async_test]
+    async fn test_invalid_json_is_retried() {
+        let mut calls: Vec<usize> = Vec::new();
+        let response = request_analysis_with("fn main() {}", |messages| {
+            calls.push(messages.len());
+            let text = if calls.len() == 1 { "I think the code is mostly human".to_string() } else { VALID_RESPONSE.to_string() };
+            async move { Ok(Some(text)) }
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(response.ai_detection_score, 35.0);
+        // The retry carries the bad answer and the JSON reminder
+        assert_eq!(calls, vec![2, 4]);
+    }
+
+    #[rocket::async_test]
+    async fn test_retries_exhausted_gives_defaults() {
+        let mut calls = 0;
+        let response = request_analysis_with("fn main() {}", |_| {
+            calls += 1;
+            async { Ok(Some("not json".to_string())) }
+        })
+        .await;
+
+        assert_eq!(calls, MAX_JSON_RETRIES + 1);
+        assert!(matches!(response, Err(AiResponseError::Malformed(_))));
+        assert_eq!(analysis_from_response(response).ai_detection_score, AIAnalysis::default().ai_detection_score);
+
+        // Request failures end the analysis without retrying
+        let mut calls = 0;
+        let response = request_analysis_with("fn main() {}", |_| {
+            calls += 1;
+            async { Err(AiResponseError::Request("timeout".to_string())) }
+        })
+        .await;
+        assert_eq!(calls, 1);
+        assert!(matches!(response, Err(AiResponseError::Request(_))));
     }
 }
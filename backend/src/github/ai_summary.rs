@@ -1,13 +1,22 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+
 use genai::{
     Client,
-    chat::{ChatMessage, ChatOptions, ChatRequest},
+    chat::{ChatMessage, ChatRequest},
 };
 
+use crate::ai_temperature::{chat_options, temperatures};
 use crate::github::stats::GitHubStats;
 use crate::github::analyze::get_excerpts_for_profile;
+use crate::github::semantic_search::SearchCategory;
 
 const MODEL_GEMINI: &str = "gemini-2.0-flash";
 
+/// Profiles kept in the in-process cache before it is cleared
+const PROFILE_CACHE_CAPACITY: usize = 500;
+
 const DEVELOPER_PROFILE_PROMPT: &str = r#"You are analyzing a developer's GitHub profile to understand their personality, coding style, and unique characteristics as a programmer.
 
 Generate a 2-3 paragraph profile that captures WHO this developer is, not just what they can do. Focus on:
@@ -35,6 +44,8 @@ The AI analysis scores indicate:
 
 Interpret these to describe HOW they work with (or without) AI tools.
 
+If a PROFILE CONTEXT section is present (company, location, blog, Twitter), use it for background but don't speculate beyond it.
+
 Write in a warm, observational tone - like you're describing a colleague to someone. Be specific about what the data reveals. Avoid generic statements.
 
 Return ONLY the profile text, no JSON or markdown formatting."#;
@@ -74,20 +85,140 @@ The AI analysis scores indicate:
 - ai_proficiency_score: Higher = effective AI tool usage (0-100)
 - code_authenticity_score: Higher = more personal, human-authored style (0-100)
 
+If a PROFILE CONTEXT section is present (company, location, blog, Twitter), use it for background but don't speculate beyond it.
+
 BE SPECIFIC. Reference actual code you see. Don't make generic statements - use evidence from the excerpts.
 
 Write in a warm, observational tone - like you're describing a colleague to someone.
 
 Return ONLY the profile text, no JSON or markdown formatting."#;
 
+const BRIEF_PROFILE_PROMPT: &str = r#"You are summarizing a developer's GitHub profile for a compact list view.
+
+Write ONE sentence (at most 40 words) capturing what kind of developer this is: their main languages or domain and the single most distinctive trait of their coding style. If CODE EXCERPTS are present, base the trait on them.
+
+If a PROFILE CONTEXT section is present (company, location, blog, Twitter), use it only if it sharpens the sentence.
+
+Return ONLY the sentence, no JSON or markdown formatting."#;
+
+const FORMAL_PROFILE_PROMPT: &str = r#"You are preparing a written technical assessment of a developer for a hiring packet, based on their GitHub profile data and, if present, CODE EXCERPTS from their repositories.
+
+Write 2-3 paragraphs in a neutral, professional register covering:
+- Technical scope: primary languages, domains and the kinds of projects they maintain
+- Engineering practice: code organization, error handling, testing and documentation habits, citing the excerpts where available
+- AI tool usage: interpret ai_detection_score, ai_proficiency_score and code_authenticity_score (0-100) factually
+
+If a PROFILE CONTEXT section is present (company, location, blog, Twitter), use it for background but don't speculate beyond it.
+
+Avoid personal or emotive language, superlatives and speculation about personality. State observations and the evidence behind them.
+
+Return ONLY the assessment text, no JSON or markdown formatting."#;
+
+/// Tone and length of a generated developer profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ProfileStyle {
+    /// One-line summary for list views
+    Brief,
+    /// Warm, observational multi-paragraph profile
+    #[default]
+    Narrative,
+    /// Neutral assessment for hiring packets
+    Formal,
+}
+
+impl ProfileStyle {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "brief" => Some(Self::Brief),
+            "narrative" => Some(Self::Narrative),
+            "formal" => Some(Self::Formal),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Brief => "brief",
+            Self::Narrative => "narrative",
+            Self::Formal => "formal",
+        }
+    }
+
+    /// System prompt for this style, depending on whether code excerpts are available
+    fn prompt(&self, has_code: bool) -> &'static str {
+        match (self, has_code) {
+            (Self::Brief, _) => BRIEF_PROFILE_PROMPT,
+            (Self::Narrative, true) => DEVELOPER_PROFILE_WITH_CODE_PROMPT,
+            (Self::Narrative, false) => DEVELOPER_PROFILE_PROMPT,
+            (Self::Formal, _) => FORMAL_PROFILE_PROMPT,
+        }
+    }
+
+    /// Upper bound on the number of words returned
+    fn max_words(&self) -> usize {
+        match self {
+            Self::Brief => 40,
+            Self::Narrative => 500,
+            Self::Formal => 400,
+        }
+    }
+}
+
+/// Trim the model output to the style's target length, cutting at a sentence end when possible
+fn shape_profile(text: &str, style: ProfileStyle) -> String {
+    let text = if style == ProfileStyle::Brief {
+        // A one-liner shouldn't carry paragraph breaks
+        text.split_whitespace().collect::<Vec<_>>().join(" ")
+    } else {
+        text.trim().to_string()
+    };
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    if words.len() <= style.max_words() {
+        return text;
+    }
+
+    let truncated = words[..style.max_words()].join(" ");
+    match truncated.rfind(['.', '!', '?']) {
+        Some(end) if end > truncated.len() / 2 => truncated[..=end].to_string(),
+        _ => format!("{}…", truncated),
+    }
+}
+
+/// Short plain-text summary of the public profile fields (company, location, links)
+fn profile_context(stats: &GitHubStats) -> Option<String> {
+    let profile = &stats.profile;
+    let fields = [
+        ("Company", profile.company.as_ref()),
+        ("Location", profile.location.as_ref()),
+        ("Blog", profile.blog.as_ref()),
+        ("Twitter", profile.twitter_username.as_ref()),
+    ];
+
+    let lines: Vec<String> = fields
+        .iter()
+        .filter_map(|(label, value)| value.map(|v| format!("{}: {}", label, v)))
+        .collect();
+
+    if lines.is_empty() {
+        None
+    } else {
+        Some(format!("## PROFILE CONTEXT:\n{}\n\n", lines.join("\n")))
+    }
+}
+
+/// Generate a developer profile, citing code excerpts from `categories` when the stats have them
+/// Pass `SearchCategory::all()` to include every category, or a role-relevant subset to focus the profile
 pub async fn generate_developer_profile(
     stats: &GitHubStats,
+    style: ProfileStyle,
+    categories: &[SearchCategory],
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let client = Client::default();
-    let options = ChatOptions::default().with_temperature(0.4);
+    let options = chat_options(temperatures().profile, None);
 
     // Check if we have code excerpts for enhanced profile
-    let code_excerpts = get_excerpts_for_profile(stats);
+    let code_excerpts = get_excerpts_for_profile(stats, categories).filter(|e| !e.is_empty());
 
     let (prompt, user_content) = if let Some(excerpts) = code_excerpts {
         // Use enhanced prompt with code excerpts
@@ -96,11 +227,16 @@ pub async fn generate_developer_profile(
             "## GITHUB STATS:\n{}\n\n## CODE EXCERPTS BY CATEGORY:\n{}",
             stats_json, excerpts
         );
-        (DEVELOPER_PROFILE_WITH_CODE_PROMPT, content)
+        (style.prompt(true), content)
     } else {
         // Use basic prompt with just metadata
         let stats_json = serde_json::to_string_pretty(stats)?;
-        (DEVELOPER_PROFILE_PROMPT, stats_json)
+        (style.prompt(false), stats_json)
+    };
+
+    let user_content = match profile_context(stats) {
+        Some(context) => format!("{}{}", context, user_content),
+        None => user_content,
     };
 
     let chat_req = ChatRequest::new(vec![
@@ -117,7 +253,77 @@ pub async fn generate_developer_profile(
         .joined_texts()
         .ok_or("Failed to get response text")?;
 
-    Ok(profile.trim().to_string())
+    Ok(shape_profile(&profile, style))
+}
+
+/// A generated profile and the hash of the stats (and style) it was generated from
+#[derive(Debug, Clone, PartialEq)]
+pub struct CachedProfile {
+    pub stats_hash: String,
+    pub profile: String,
+}
+
+/// Stable hash of everything a profile is generated from
+/// `analyzed_at` is left out so re-analyzing unchanged code gives the same hash
+pub fn profile_stats_hash(stats: &GitHubStats, style: ProfileStyle) -> String {
+    let mut value = serde_json::to_value(stats).unwrap_or_default();
+    if let Some(obj) = value.as_object_mut() {
+        obj.remove("analyzed_at");
+    }
+
+    // FNV-1a over the canonical JSON (object keys are sorted), stable across builds
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in style.name().bytes().chain(value.to_string().bytes()) {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+
+    format!("{:016x}", hash)
+}
+
+fn profile_cache() -> &'static Mutex<HashMap<String, String>> {
+    static CACHE: OnceLock<Mutex<HashMap<String, String>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Reuse a profile generated from the same stats hash (stored or in-process), otherwise generate one
+async fn reuse_or_generate<F, Fut, E>(
+    stats_hash: String,
+    stored: Option<CachedProfile>,
+    generate: F,
+) -> Result<CachedProfile, E>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<String, E>>,
+{
+    if let Some(stored) = stored.filter(|p| p.stats_hash == stats_hash) {
+        return Ok(stored);
+    }
+
+    let cached = profile_cache().lock().unwrap().get(&stats_hash).cloned();
+    if let Some(profile) = cached {
+        return Ok(CachedProfile { stats_hash, profile });
+    }
+
+    let profile = generate().await?;
+
+    let mut cache = profile_cache().lock().unwrap();
+    if cache.len() >= PROFILE_CACHE_CAPACITY {
+        cache.clear();
+    }
+    cache.insert(stats_hash.clone(), profile.clone());
+
+    Ok(CachedProfile { stats_hash, profile })
+}
+
+/// Developer profile, regenerated only when the stats changed since `stored` (or the last in-process call)
+pub async fn cached_developer_profile(
+    stats: &GitHubStats,
+    style: ProfileStyle,
+    stored: Option<CachedProfile>,
+) -> Result<CachedProfile, Box<dyn std::error::Error + Send + Sync>> {
+    let stats_hash = profile_stats_hash(stats, style);
+    reuse_or_generate(stats_hash, stored, || generate_developer_profile(stats, style, SearchCategory::all())).await
 }
 
 /// Generate a shorter profile summary (1 paragraph)
@@ -125,7 +331,7 @@ pub async fn generate_developer_summary(
     stats: &GitHubStats,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let client = Client::default();
-    let options = ChatOptions::default().with_temperature(0.3);
+    let options = chat_options(temperatures().summary, None);
 
     let stats_json = serde_json::to_string_pretty(stats)?;
 
@@ -149,3 +355,97 @@ Return ONLY the paragraph, no formatting."#;
 
     Ok(summary.trim().to_string())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::stats::{AIAnalysis, GitHubProfile};
+
+    fn stats(analyzed_at: &str, followers: u32) -> GitHubStats {
+        GitHubStats {
+            username: "octocat".to_string(),
+            profile: GitHubProfile {
+                name: Some("Octo Cat".to_string()),
+                bio: None,
+                avatar_url: String::new(),
+                public_repos: 3,
+                followers,
+                following: 1,
+                created_at: "2015-01-01T00:00:00Z".to_string(),
+                blog: None,
+                twitter_username: None,
+                company: None,
+                location: None,
+            },
+            repositories: vec![],
+            repositories_truncated: false,
+            reputation: Default::default(),
+            ai_analysis: AIAnalysis::default(),
+            languages: HashMap::from([("Rust".to_string(), 1200), ("Go".to_string(), 300)]),
+            analyzed_at: analyzed_at.to_string(),
+            code_excerpts: None,
+            analysis_metadata: None,
+            fetch_diagnostics: None,
+            activity: None,
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_unchanged_stats_reuse_cached_profile() {
+        let first = stats("2025-01-01T00:00:00Z", 10);
+        let reanalyzed = stats("2025-03-01T00:00:00Z", 10);
+        let hash = profile_stats_hash(&first, ProfileStyle::Narrative);
+        assert_eq!(hash, profile_stats_hash(&reanalyzed, ProfileStyle::Narrative));
+        assert_ne!(hash, profile_stats_hash(&stats("2025-03-01T00:00:00Z", 11), ProfileStyle::Narrative));
+        assert_ne!(hash, profile_stats_hash(&first, ProfileStyle::Brief));
+
+        let generated = reuse_or_generate(hash.clone(), None, || async { Ok::<_, String>("Writes small modules.".to_string()) })
+            .await
+            .unwrap();
+
+        // Same stats again: served from cache, the generator (the AI call) never runs
+        let again = reuse_or_generate(profile_stats_hash(&reanalyzed, ProfileStyle::Narrative), None, || async {
+            Err::<String, _>("AI should not be called".to_string())
+        })
+        .await;
+        assert_eq!(again, Ok(generated.clone()));
+
+        // A stored profile with a matching hash is reused too
+        let stored = CachedProfile { stats_hash: "stored-hash".to_string(), profile: "Stored.".to_string() };
+        let reused = reuse_or_generate("stored-hash".to_string(), Some(stored.clone()), || async {
+            Err::<String, _>("AI should not be called".to_string())
+        })
+        .await;
+        assert_eq!(reused, Ok(stored));
+    }
+
+    #[test]
+    fn test_style_maps_to_prompt() {
+        assert_eq!(ProfileStyle::Brief.prompt(true), BRIEF_PROFILE_PROMPT);
+        assert_eq!(ProfileStyle::Brief.prompt(false), BRIEF_PROFILE_PROMPT);
+        assert_eq!(ProfileStyle::Narrative.prompt(true), DEVELOPER_PROFILE_WITH_CODE_PROMPT);
+        assert_eq!(ProfileStyle::Narrative.prompt(false), DEVELOPER_PROFILE_PROMPT);
+        assert_eq!(ProfileStyle::Formal.prompt(true), FORMAL_PROFILE_PROMPT);
+        assert_eq!(ProfileStyle::Formal.prompt(false), FORMAL_PROFILE_PROMPT);
+
+        assert_eq!(ProfileStyle::parse("Brief"), Some(ProfileStyle::Brief));
+        assert_eq!(ProfileStyle::parse("formal"), Some(ProfileStyle::Formal));
+        assert_eq!(ProfileStyle::parse("poetic"), None);
+        assert_eq!(ProfileStyle::default(), ProfileStyle::Narrative);
+    }
+
+    #[test]
+    fn test_brief_profile_is_shorter() {
+        let paragraph = "Jane writes small, sharply named Rust modules and leans on the type system to rule out bad states. \
+            Error handling is explicit, with custom error enums threaded through every layer. ";
+        let output = format!("{}\n\n{}\n\n{}", paragraph.repeat(3), paragraph.repeat(3), paragraph.repeat(3));
+
+        let brief = shape_profile(&output, ProfileStyle::Brief);
+        let narrative = shape_profile(&output, ProfileStyle::Narrative);
+
+        assert!(brief.split_whitespace().count() <= ProfileStyle::Brief.max_words());
+        assert!(!brief.contains('\n'));
+        assert!(brief.len() * 4 < narrative.len());
+        assert!(brief.ends_with('.'));
+    }
+}
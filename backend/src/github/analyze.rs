@@ -1,14 +1,21 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
 
 use sqlx::PgConnection;
 use uuid::Uuid;
 
 use crate::github::{
-    api::{get_user_profile, get_user_repos_full, get_repo_tree, get_file_content, GitHubRepoFull},
+    activity::collect_activity,
+    api::{get_user_profile, get_user_repos_full, get_repo_tree, get_file_content, GitHubRepoFull, RepoTree, TreeItem},
     ai_analysis::analyze_code_for_ai_usage,
-    embeddings::{chunk_code, store_chunks_batch, detect_language, CodeChunk},
-    semantic_search::{search_all_categories, summarize_excerpts, get_embedding_stats},
-    stats::{GitHubStats, GitHubProfile, RepositoryInfo, AIAnalysis, AnalysisMetadata},
+    blocklist::{analysis_blocklist, unless_blocked},
+    capabilities::embeddings_available,
+    embeddings::{chunk_code, store_chunks_batch, cleanup_embeddings, detect_language, CodeChunk},
+    repos::{cap_repositories, MAX_STATS_REPOSITORIES},
+    scrub::scrub,
+    semantic_search::{search_all_categories, DEFAULT_MIN_SIMILARITY, summarize_excerpts, get_embedding_stats, CodeExcerpt, EmbeddingStats, SearchCategory, SearchResults},
+    stats::{GitHubStats, GitHubProfile, RepositoryInfo, Reputation, AIAnalysis, AnalysisMetadata, FetchDiagnostics},
+    stats_cache::{cached_analysis, AnalysisSource, DEEP_ANALYSIS, QUICK_ANALYSIS},
 };
 
 const CODE_EXTENSIONS: &[&str] = &[
@@ -24,6 +31,134 @@ const MAX_FILES_PER_REPO: usize = 10;
 const MAX_TOTAL_FILES: usize = 30;
 const MAX_FILE_SIZE: u64 = 50000;
 const EXCERPTS_PER_CATEGORY: i32 = 3;
+// Hard ceilings for per-request overrides
+const MAX_REPOS_CEILING: usize = 20;
+const MAX_FILES_PER_REPO_CEILING: usize = 50;
+const MAX_TOTAL_FILES_CEILING: usize = 200;
+const MAX_FILE_SIZE_CEILING: u64 = 200000;
+const DEFAULT_MIN_LANGUAGE_LINES: u32 = 100;
+
+/// Limits controlling how much code is sampled during analysis
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnalysisLimits {
+    pub max_repos: usize,
+    pub max_files_per_repo: usize,
+    pub max_total_files: usize,
+    pub max_file_size: u64,
+    pub excerpts_per_category: i32,
+}
+
+impl Default for AnalysisLimits {
+    fn default() -> Self {
+        Self {
+            max_repos: MAX_REPOS,
+            max_files_per_repo: MAX_FILES_PER_REPO,
+            max_total_files: MAX_TOTAL_FILES,
+            max_file_size: MAX_FILE_SIZE,
+            excerpts_per_category: EXCERPTS_PER_CATEGORY,
+        }
+    }
+}
+
+/// Per-request overrides of the sampling limits; unset fields keep the default
+#[derive(Debug, Clone, Default, PartialEq, serde::Deserialize)]
+#[serde(crate = "rocket::serde")]
+pub struct AnalysisLimitOverrides {
+    pub max_repos: Option<usize>,
+    pub max_files_per_repo: Option<usize>,
+    pub max_total_files: Option<usize>,
+    pub max_file_size: Option<u64>,
+}
+
+impl AnalysisLimits {
+    /// Apply overrides, clamping each to at least 1 and at most its hard ceiling
+    pub fn with_overrides(self, overrides: &AnalysisLimitOverrides) -> Self {
+        Self {
+            max_repos: overrides.max_repos.map_or(self.max_repos, |v| v.clamp(1, MAX_REPOS_CEILING)),
+            max_files_per_repo: overrides
+                .max_files_per_repo
+                .map_or(self.max_files_per_repo, |v| v.clamp(1, MAX_FILES_PER_REPO_CEILING)),
+            max_total_files: overrides
+                .max_total_files
+                .map_or(self.max_total_files, |v| v.clamp(1, MAX_TOTAL_FILES_CEILING)),
+            max_file_size: overrides.max_file_size.map_or(self.max_file_size, |v| v.clamp(1, MAX_FILE_SIZE_CEILING)),
+            ..self
+        }
+    }
+}
+
+/// Internal analysis path selected by a depth profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AnalysisPath {
+    /// Plain code sampling for AI detection, no excerpts (no DB)
+    Basic,
+    /// Keyword-categorized code excerpts (no DB)
+    Samples,
+    /// Embedding-backed semantic search over stored chunks (needs DB)
+    Embeddings,
+}
+
+/// Speed/thoroughness trade-off for GitHub analysis
+/// Quick: basic stats + AI detection
+/// Standard: adds categorized code excerpts
+/// Thorough: more repos/files, excerpts chosen via embeddings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthProfile {
+    Quick,
+    Standard,
+    Thorough,
+}
+
+impl DepthProfile {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "quick" => Some(Self::Quick),
+            "standard" => Some(Self::Standard),
+            "thorough" => Some(Self::Thorough),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Quick => "quick",
+            Self::Standard => "standard",
+            Self::Thorough => "thorough",
+        }
+    }
+
+    pub fn limits(&self) -> AnalysisLimits {
+        match self {
+            Self::Quick => AnalysisLimits {
+                max_repos: 5,
+                max_files_per_repo: 6,
+                max_total_files: 30,
+                max_file_size: MAX_FILE_SIZE,
+                excerpts_per_category: 0,
+            },
+            Self::Standard => AnalysisLimits::default(),
+            Self::Thorough => AnalysisLimits {
+                max_repos: 10,
+                max_files_per_repo: 20,
+                max_total_files: 80,
+                max_file_size: 100000,
+                excerpts_per_category: 5,
+            },
+        }
+    }
+
+    pub fn path(&self) -> AnalysisPath {
+        match self {
+            Self::Quick => AnalysisPath::Basic,
+            Self::Standard => AnalysisPath::Samples,
+            Self::Thorough => AnalysisPath::Embeddings,
+        }
+    }
+
+    pub fn uses_embeddings(&self) -> bool {
+        self.path() == AnalysisPath::Embeddings
+    }
+}
 
 fn is_code_file(path: &str) -> bool {
     CODE_EXTENSIONS.iter().any(|ext| path.ends_with(ext))
@@ -72,37 +207,229 @@ fn should_skip_path(path: &str) -> bool {
         || path_lower.ends_with(".txt")
 }
 
-/// Analyze a GitHub user with deep code analysis using embeddings
-pub async fn analyze_github_user(
+/// Top-level directories that hold packages in a monorepo
+const MONOREPO_ROOTS: &[&str] = &["packages", "apps", "services", "libs", "crates", "modules"];
+
+/// Files that mark a directory as its own package
+const PACKAGE_MANIFESTS: &[&str] = &[
+    "package.json", "Cargo.toml", "go.mod", "pyproject.toml", "setup.py",
+    "pom.xml", "build.gradle", "build.gradle.kts", "composer.json", "Gemfile", "mix.exs",
+];
+
+/// Package directories (e.g. `packages/api`) that carry their own manifest
+/// A repo with two or more of these is treated as a monorepo
+fn detect_packages(tree: &[TreeItem]) -> Vec<String> {
+    let mut packages: Vec<String> = tree.iter()
+        .filter(|f| f.item_type == "blob")
+        .filter_map(|f| {
+            let parts: Vec<&str> = f.path.split('/').collect();
+            match parts.as_slice() {
+                [root, name, manifest] if MONOREPO_ROOTS.contains(root) && PACKAGE_MANIFESTS.contains(manifest) => {
+                    Some(format!("{}/{}", root, name))
+                }
+                _ => None,
+            }
+        })
+        .collect();
+
+    packages.sort();
+    packages.dedup();
+    packages
+}
+
+/// Pick up to `max_files_per_repo` code files from a repo tree
+/// For monorepos the budget is shared round-robin across packages so one package can't fill it
+fn select_code_files<'a>(tree: &'a [TreeItem], limits: &AnalysisLimits) -> Vec<&'a TreeItem> {
+    let code_files: Vec<&TreeItem> = tree.iter()
+        .filter(|f| f.item_type == "blob")
+        .filter(|f| is_code_file(&f.path))
+        .filter(|f| f.size.unwrap_or(0) < limits.max_file_size)
+        .filter(|f| !should_skip_path(&f.path))
+        .collect();
+
+    let packages = detect_packages(tree);
+    if packages.len() < 2 {
+        return code_files.into_iter().take(limits.max_files_per_repo).collect();
+    }
+
+    // One bucket per package, plus a trailing bucket for shared code outside them
+    let mut buckets: Vec<Vec<&TreeItem>> = vec![Vec::new(); packages.len() + 1];
+    for file in code_files {
+        let idx = packages.iter()
+            .position(|p| file.path.starts_with(&format!("{}/", p)))
+            .unwrap_or(packages.len());
+        buckets[idx].push(file);
+    }
+
+    let mut selected = Vec::new();
+    let mut round = 0;
+    while selected.len() < limits.max_files_per_repo {
+        let mut picked_any = false;
+        for bucket in &buckets {
+            if selected.len() >= limits.max_files_per_repo {
+                break;
+            }
+            if let Some(file) = bucket.get(round) {
+                selected.push(*file);
+                picked_any = true;
+            }
+        }
+        if !picked_any {
+            break;
+        }
+        round += 1;
+    }
+
+    selected
+}
+
+/// Docs and config extensions that make up dotfiles repos and documentation sites
+const DOC_CONFIG_EXTENSIONS: &[&str] = &[
+    ".md", ".mdx", ".rst", ".txt", ".adoc", ".json", ".yaml", ".yml", ".toml",
+    ".ini", ".cfg", ".conf", ".lock", ".sh", ".zsh", ".fish", ".vim",
+];
+
+/// Below this share of code bytes (vs docs/config bytes) a repo is treated as docs/config-dominant
+const MIN_CODE_SHARE: f64 = 0.2;
+
+/// What a repo's files are mostly made of
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RepoComposition {
+    Code,
+    DocsOrConfig,
+}
+
+/// Classify a repo by the bytes in sampleable code files vs docs/config files
+fn classify_repo(tree: &[TreeItem]) -> RepoComposition {
+    let mut code_bytes = 0u64;
+    let mut doc_bytes = 0u64;
+
+    for file in tree.iter().filter(|f| f.item_type == "blob") {
+        let size = file.size.unwrap_or(0);
+        let path_lower = file.path.to_lowercase();
+        let file_name = path_lower.rsplit('/').next().unwrap_or("");
+
+        if is_code_file(&file.path) && !should_skip_path(&file.path) {
+            code_bytes += size;
+        } else if DOC_CONFIG_EXTENSIONS.iter().any(|ext| path_lower.ends_with(ext)) || file_name.starts_with('.') {
+            doc_bytes += size;
+        }
+    }
+
+    let total = code_bytes + doc_bytes;
+    if total > 0 && (code_bytes as f64) < total as f64 * MIN_CODE_SHARE {
+        RepoComposition::DocsOrConfig
+    } else {
+        RepoComposition::Code
+    }
+}
+
+/// Code repos first (in their original order), docs/config-dominant ones only to fill remaining slots
+fn order_by_composition<T>(items: Vec<(T, RepoComposition)>, max: usize) -> Vec<T> {
+    let (code, docs): (Vec<_>, Vec<_>) = items
+        .into_iter()
+        .partition(|(_, composition)| *composition == RepoComposition::Code);
+
+    code.into_iter().chain(docs).map(|(item, _)| item).take(max).collect()
+}
+
+/// File trees for up to `max_repos` of `repos` (located by owner and name) to sample code from
+/// Looks ahead past docs/config-dominant repos so they only fill slots code repos can't
+/// Tree fetches are recorded in `diagnostics`
+pub(crate) async fn sampled_repo_trees<T>(
+    repos: Vec<T>,
+    locate: impl Fn(&T) -> (&str, &str),
+    token: &str,
+    max_repos: usize,
+    diagnostics: &mut FetchDiagnostics,
+) -> Vec<(T, RepoTree)> {
+    let fetch_tree = |owner: &str, name: &str| {
+        let (owner, name) = (owner.to_string(), name.to_string());
+        async move { get_repo_tree(&owner, &name, token).await }
+    };
+    select_repo_trees(repos, locate, max_repos, diagnostics, fetch_tree).await
+}
+
+/// `sampled_repo_trees` with the tree fetch supplied by the caller
+async fn select_repo_trees<T, F, Fut>(
+    repos: Vec<T>,
+    locate: impl Fn(&T) -> (&str, &str),
+    max_repos: usize,
+    diagnostics: &mut FetchDiagnostics,
+    fetch_tree: F,
+) -> Vec<(T, RepoTree)>
+where
+    F: Fn(&str, &str) -> Fut,
+    Fut: std::future::Future<Output = Result<RepoTree, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let mut classified = Vec::new();
+    let mut code_repos = 0;
+
+    for repo in repos.into_iter().take(max_repos * 2) {
+        if code_repos >= max_repos {
+            break;
+        }
+
+        let (owner, name) = locate(&repo);
+        let tree = match fetch_tree(owner, name).await {
+            Ok(t) => t,
+            Err(e) => {
+                println!("[Repos] Skipping {} - tree error: {}", name, e);
+                diagnostics.repo_failed(name, e);
+                continue;
+            }
+        };
+        diagnostics.repo_fetched();
+
+        let composition = classify_repo(&tree.tree);
+        if composition == RepoComposition::Code {
+            code_repos += 1;
+        } else {
+            println!("[Repos] {} is mostly docs/config, deprioritizing", name);
+        }
+        classified.push(((repo, tree), composition));
+    }
+
+    order_by_composition(classified, max_repos)
+}
+
+/// Non-fork repos with their trees, code-rich ones first
+async fn own_repo_trees<'a>(
+    repos: &'a [GitHubRepoFull],
+    token: &str,
+    max_repos: usize,
+    diagnostics: &mut FetchDiagnostics,
+) -> Vec<(&'a GitHubRepoFull, RepoTree)> {
+    let own: Vec<&GitHubRepoFull> = repos.iter().filter(|r| !r.fork).collect();
+    sampled_repo_trees(own, |r| (r.owner.login.as_str(), r.name.as_str()), token, max_repos, diagnostics).await
+}
+
+/// Profile, repos and language breakdown shared by every analysis path
+struct UserOverview {
+    profile: GitHubProfile,
+    repos: Vec<GitHubRepoFull>,
+    repositories: Vec<RepositoryInfo>,
+    repositories_truncated: bool,
+    reputation: Reputation,
+    languages: HashMap<String, u32>,
+}
+
+async fn fetch_user_overview(
     username: &str,
     token: &str,
-) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+) -> Result<UserOverview, Box<dyn std::error::Error + Send + Sync>> {
     // 1. Fetch user profile
     let user = get_user_profile(username, token).await?;
 
-    let profile = GitHubProfile {
-        name: user.name,
-        bio: user.bio,
-        avatar_url: user.avatar_url,
-        public_repos: user.public_repos,
-        followers: user.followers,
-        following: user.following,
-        created_at: user.created_at,
-    };
+    let profile = GitHubProfile::from(user);
 
     // 2. Fetch all repos
     let repos = get_user_repos_full(username, token).await?;
 
     // 3. Convert repos to RepositoryInfo
-    let repositories: Vec<RepositoryInfo> = repos.iter().map(|r| RepositoryInfo {
-        name: r.name.clone(),
-        description: r.description.clone(),
-        language: r.language.clone(),
-        is_fork: r.fork,
-        size: r.size,
-        created_at: r.created_at.clone(),
-        updated_at: r.updated_at.clone(),
-    }).collect();
+    let reputation = Reputation::from_repos(&repos, profile.followers);
+    let repositories: Vec<RepositoryInfo> = repos.iter().map(RepositoryInfo::from).collect();
+    let (repositories, repositories_truncated) = cap_repositories(repositories, MAX_STATS_REPOSITORIES);
 
     // 4. Aggregate languages
     let mut languages: HashMap<String, u32> = HashMap::new();
@@ -118,12 +445,68 @@ pub async fn analyze_github_user(
         .map(|(k, v)| (k, ((v as f32 / total_repos) * 100.0) as u32))
         .collect();
 
+    Ok(UserOverview { profile, repos, repositories, repositories_truncated, reputation, languages })
+}
+
+/// Code gathered from a user's repos by the deep analysis paths
+struct CollectedCode {
+    excerpts: SearchResults,
+    metadata: AnalysisMetadata,
+    all_code: String,
+    /// Lines sampled per file-level detected language
+    language_lines: HashMap<String, u32>,
+    diagnostics: FetchDiagnostics,
+}
+
+/// Minimum sampled lines before file-level language detection is trusted
+/// over GitHub's per-repo language guess (MIN_LANGUAGE_DETECTION_LINES)
+fn min_language_detection_lines() -> u32 {
+    std::env::var("MIN_LANGUAGE_DETECTION_LINES")
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(DEFAULT_MIN_LANGUAGE_LINES)
+}
+
+/// Prefer language percentages from sampled files (by line count) over the
+/// repo-level breakdown, unless too little code was sampled to trust them
+fn resolve_languages(
+    repo_languages: HashMap<String, u32>,
+    language_lines: &HashMap<String, u32>,
+    min_lines: u32,
+) -> HashMap<String, u32> {
+    let total: u32 = language_lines.values().sum();
+    if total == 0 || total < min_lines {
+        return repo_languages;
+    }
+
+    language_lines
+        .iter()
+        .map(|(lang, lines)| (lang.clone(), ((*lines as f32 / total as f32) * 100.0) as u32))
+        .collect()
+}
+
+/// Analyze a GitHub user with basic stats and AI detection (no DB)
+pub async fn analyze_github_user(
+    username: &str,
+    token: &str,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    let limits = DepthProfile::Quick.limits();
+    unless_blocked(analysis_blocklist(), username, || analyze_basic(username, token, &limits)).await
+}
+
+async fn analyze_basic(
+    username: &str,
+    token: &str,
+    limits: &AnalysisLimits,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    let overview = fetch_user_overview(username, token).await?;
+
     // 5. Fetch code samples for AI analysis (simplified - no DB)
-    let code_samples = fetch_code_samples_simple(username, &repos, token).await;
+    let code_samples = fetch_code_samples_simple(username, &overview.repos, token, limits).await;
 
     // 6. Analyze code for AI usage patterns
     let ai_analysis = if !code_samples.is_empty() {
-        analyze_code_for_ai_usage(&code_samples).await.unwrap_or_default()
+        analyze_code_for_ai_usage(&code_samples).await
     } else {
         AIAnalysis::default()
     };
@@ -131,146 +514,347 @@ pub async fn analyze_github_user(
     // 7. Build final stats object (without deep analysis for now)
     let stats = GitHubStats {
         username: username.to_string(),
-        profile,
-        repositories,
+        profile: overview.profile,
+        repositories: overview.repositories,
+        repositories_truncated: overview.repositories_truncated,
+        reputation: overview.reputation,
         ai_analysis,
-        languages,
+        languages: overview.languages,
         analyzed_at: chrono::Utc::now().to_rfc3339(),
         code_excerpts: None,
         analysis_metadata: None,
+        fetch_diagnostics: None,
+        activity: None,
     };
 
     Ok(stats)
 }
 
-/// Deep analysis - collects code samples without embeddings
-/// Returns full GitHubStats with code_excerpts and analysis_metadata
-pub async fn analyze_github_user_deep(
+/// Number of most recently updated repos whose timestamps make up the cache fingerprint
+const FINGERPRINT_REPOS: usize = 5;
+
+/// Summary of the latest repo updates; changes whenever the user pushes to or edits a repo
+fn repo_fingerprint(repos: &[GitHubRepoFull]) -> String {
+    let mut updated: Vec<&str> = repos.iter().map(|r| r.updated_at.as_str()).collect();
+    updated.sort_unstable_by(|a, b| b.cmp(a));
+    updated.truncate(FINGERPRINT_REPOS);
+    updated.join(",")
+}
+
+/// Analyses run against the GitHub API for the stats cache
+struct GitHubAnalysisSource<'a> {
+    token: &'a str,
+    deep: bool,
+}
+
+impl AnalysisSource for GitHubAnalysisSource<'_> {
+    async fn fingerprint(&self, username: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+        Ok(repo_fingerprint(&get_user_repos_full(username, self.token).await?))
+    }
+
+    async fn analyze(
+        &self,
+        conn: Option<&mut PgConnection>,
+        username: &str,
+    ) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+        if self.deep {
+            analyze_deep_with(conn, username, self.token, &AnalysisLimits::default()).await
+        } else {
+            analyze_basic(username, self.token, &DepthProfile::Quick.limits()).await
+        }
+    }
+}
+
+/// `analyze_github_user`, reusing the stored result while it is younger than `ttl` or the user's repos are unchanged
+pub async fn get_or_analyze(
+    conn: &mut PgConnection,
     username: &str,
     token: &str,
+    ttl: chrono::Duration,
 ) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
-    println!("[DEEP] Starting analysis for {}", username);
-
-    // 1. Fetch user profile
-    println!("[DEEP] Fetching user profile...");
-    let user = get_user_profile(username, token).await?;
-    println!("[DEEP] Got profile: {:?}", user.name);
-
-    let profile = GitHubProfile {
-        name: user.name,
-        bio: user.bio,
-        avatar_url: user.avatar_url,
-        public_repos: user.public_repos,
-        followers: user.followers,
-        following: user.following,
-        created_at: user.created_at,
-    };
+    cached_analysis(conn, username, QUICK_ANALYSIS, ttl, &GitHubAnalysisSource { token, deep: false }).await
+}
 
-    // 2. Fetch all repos
-    let repos = get_user_repos_full(username, token).await?;
+/// `get_or_analyze` for the deep analysis
+pub async fn get_or_analyze_deep(
+    conn: &mut PgConnection,
+    username: &str,
+    token: &str,
+    ttl: chrono::Duration,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    cached_analysis(conn, username, DEEP_ANALYSIS, ttl, &GitHubAnalysisSource { token, deep: true }).await
+}
 
-    // 3. Convert repos to RepositoryInfo
-    let repositories: Vec<RepositoryInfo> = repos.iter().map(|r| RepositoryInfo {
-        name: r.name.clone(),
-        description: r.description.clone(),
-        language: r.language.clone(),
-        is_fork: r.fork,
-        size: r.size,
-        created_at: r.created_at.clone(),
-        updated_at: r.updated_at.clone(),
-    }).collect();
+/// Deep analysis - picks code excerpts via semantic search when a DB connection and embeddings are available,
+/// otherwise (or when embedding fails) categorizes collected samples by keyword
+/// Returns full GitHubStats with code_excerpts and analysis_metadata
+pub async fn analyze_github_user_deep(
+    conn: Option<&mut PgConnection>,
+    username: &str,
+    token: &str,
+    limits: &AnalysisLimits,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    unless_blocked(analysis_blocklist(), username, || analyze_deep_with(conn, username, token, limits)).await
+}
 
-    // 4. Aggregate languages
-    let mut languages: HashMap<String, u32> = HashMap::new();
-    for repo in repos.iter() {
-        if let Some(ref lang) = repo.language {
-            *languages.entry(lang.clone()).or_insert(0) += 1;
-        }
+/// Semantic-search analysis when a connection and embeddings are available, keyword samples otherwise
+async fn analyze_deep_with(
+    conn: Option<&mut PgConnection>,
+    username: &str,
+    token: &str,
+    limits: &AnalysisLimits,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    match conn {
+        Some(conn) if embeddings_available() => analyze_thorough(conn, username, token, limits).await,
+        _ => analyze_deep(username, token, limits).await,
     }
+}
 
-    let total_repos = repos.len().max(1) as f32;
-    let languages: HashMap<String, u32> = languages
-        .into_iter()
-        .map(|(k, v)| (k, ((v as f32 / total_repos) * 100.0) as u32))
-        .collect();
+async fn analyze_deep(
+    username: &str,
+    token: &str,
+    limits: &AnalysisLimits,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    println!("[DEEP] Starting analysis for {}", username);
+
+    let overview = fetch_user_overview(username, token).await?;
 
     // 5. Deep code analysis WITHOUT embeddings - just collect samples
     println!("[DEEP] Collecting code samples...");
-    let (code_excerpts, analysis_metadata, all_code) =
-        collect_code_samples(&repos, token).await;
-    println!("[DEEP] Collected {} files, {} lines", analysis_metadata.chunks_analyzed, analysis_metadata.total_lines);
+    let collected = collect_code_samples(&overview.repos, token, limits).await;
+    println!("[DEEP] Collected {} files, {} lines", collected.metadata.chunks_analyzed, collected.metadata.total_lines);
+    if collected.diagnostics.files_failed > 0 || collected.diagnostics.repos_failed > 0 {
+        println!(
+            "[DEEP] {} repo and {} file fetches failed",
+            collected.diagnostics.repos_failed, collected.diagnostics.files_failed
+        );
+    }
 
     // 6. Analyze code for AI usage patterns
     println!("[DEEP] Running AI usage analysis...");
-    let ai_analysis = if !all_code.is_empty() {
-        analyze_code_for_ai_usage(&all_code).await.unwrap_or_default()
+    let ai_analysis = if !collected.all_code.is_empty() {
+        analyze_code_for_ai_usage(&collected.all_code).await
     } else {
         AIAnalysis::default()
     };
     println!("[DEEP] AI analysis complete");
 
+    let activity = collect_activity(username, &overview.repos, token).await;
+
     // 7. Build final stats object
     let stats = GitHubStats {
         username: username.to_string(),
-        profile,
-        repositories,
+        profile: overview.profile,
+        repositories: overview.repositories,
+        repositories_truncated: overview.repositories_truncated,
+        reputation: overview.reputation,
         ai_analysis,
-        languages,
+        languages: resolve_languages(overview.languages, &collected.language_lines, min_language_detection_lines()),
         analyzed_at: chrono::Utc::now().to_rfc3339(),
-        code_excerpts: Some(code_excerpts),
-        analysis_metadata: Some(analysis_metadata),
+        code_excerpts: Some(collected.excerpts),
+        analysis_metadata: Some(collected.metadata),
+        fetch_diagnostics: Some(collected.diagnostics),
+        activity: Some(activity),
     };
 
     Ok(stats)
 }
 
+/// Thorough analysis - stores chunk embeddings and picks excerpts via semantic search
+/// Embeddings are removed again once the excerpts have been selected
+pub async fn analyze_github_user_thorough(
+    conn: &mut PgConnection,
+    username: &str,
+    token: &str,
+    limits: &AnalysisLimits,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    unless_blocked(analysis_blocklist(), username, || {
+        analyze_thorough(conn, username, token, limits)
+    })
+    .await
+}
+
+async fn analyze_thorough(
+    conn: &mut PgConnection,
+    username: &str,
+    token: &str,
+    limits: &AnalysisLimits,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    println!("[DEEP] Starting thorough analysis for {}", username);
+
+    let overview = fetch_user_overview(username, token).await?;
+
+    let analysis_id = Uuid::new_v4();
+    let result = deep_analyze_repos(conn, analysis_id, username, &overview.repos, token, limits).await;
+    if embeddings_available() {
+        let _ = cleanup_embeddings(conn, analysis_id).await;
+    }
+    let collected = result?;
+
+    let ai_analysis = if !collected.all_code.is_empty() {
+        analyze_code_for_ai_usage(&collected.all_code).await
+    } else {
+        AIAnalysis::default()
+    };
+    let activity = collect_activity(username, &overview.repos, token).await;
+
+    Ok(GitHubStats {
+        username: username.to_string(),
+        profile: overview.profile,
+        repositories: overview.repositories,
+        repositories_truncated: overview.repositories_truncated,
+        reputation: overview.reputation,
+        ai_analysis,
+        languages: resolve_languages(overview.languages, &collected.language_lines, min_language_detection_lines()),
+        analyzed_at: chrono::Utc::now().to_rfc3339(),
+        code_excerpts: Some(collected.excerpts),
+        analysis_metadata: Some(collected.metadata),
+        fetch_diagnostics: Some(collected.diagnostics),
+        activity: Some(activity),
+    })
+}
+
+/// Run the analysis path selected by a depth profile
+pub async fn analyze_github_user_with_depth(
+    conn: &mut PgConnection,
+    username: &str,
+    token: &str,
+    depth: DepthProfile,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    let limits = depth.limits();
+    unless_blocked(analysis_blocklist(), username, || async {
+        match depth.path() {
+            AnalysisPath::Basic => analyze_basic(username, token, &limits).await,
+            AnalysisPath::Samples => analyze_deep(username, token, &limits).await,
+            AnalysisPath::Embeddings => analyze_thorough(conn, username, token, &limits).await,
+        }
+    })
+    .await
+}
+
+/// Tracks normalized content hashes so the same file copied across repos is sampled once
+#[derive(Default)]
+struct ContentDeduper {
+    seen: HashSet<u64>,
+    duplicates: u32,
+}
+
+impl ContentDeduper {
+    /// Hash of the file body ignoring indentation, trailing whitespace, blank lines and line endings
+    fn content_hash(content: &str) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            line.hash(&mut hasher);
+        }
+        hasher.finish()
+    }
+
+    /// Returns true the first time a file body is seen, false (and counts it) for repeats
+    fn is_new(&mut self, content: &str) -> bool {
+        if self.seen.insert(Self::content_hash(content)) {
+            true
+        } else {
+            self.duplicates += 1;
+            false
+        }
+    }
+}
+
 /// Collect code samples and categorize by keywords (no embeddings)
 async fn collect_code_samples(
     repos: &[GitHubRepoFull],
     token: &str,
-) -> (crate::github::semantic_search::SearchResults, AnalysisMetadata, String) {
-    use crate::github::semantic_search::{SearchResults, CodeExcerpt};
+    limits: &AnalysisLimits,
+) -> CollectedCode {
+    let fetch_tree = |owner: &str, name: &str| {
+        let (owner, name) = (owner.to_string(), name.to_string());
+        async move { get_repo_tree(&owner, &name, token).await }
+    };
+    let fetch_file = |repo: &GitHubRepoFull, path: &str| {
+        let (owner, name, path) = (repo.owner.login.clone(), repo.name.clone(), path.to_string());
+        async move { get_file_content(&owner, &name, &path, token).await }
+    };
+    collect_code_samples_with(repos, limits, fetch_tree, fetch_file).await
+}
 
-    let mut results = SearchResults::default();
+/// `collect_code_samples` with the GitHub fetches supplied by the caller
+async fn collect_code_samples_with<T, TFut, F, FFut>(
+    repos: &[GitHubRepoFull],
+    limits: &AnalysisLimits,
+    fetch_tree: T,
+    fetch_file: F,
+) -> CollectedCode
+where
+    T: Fn(&str, &str) -> TFut,
+    TFut: std::future::Future<Output = Result<RepoTree, Box<dyn std::error::Error + Send + Sync>>>,
+    F: Fn(&GitHubRepoFull, &str) -> FFut,
+    FFut: std::future::Future<Output = Result<String, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let mut diagnostics = FetchDiagnostics::default();
+    let own: Vec<&GitHubRepoFull> = repos.iter().filter(|r| !r.fork).collect();
+    let repo_trees = select_repo_trees(
+        own,
+        |r| (r.owner.login.as_str(), r.name.as_str()),
+        limits.max_repos,
+        &mut diagnostics,
+        fetch_tree,
+    )
+    .await;
+    sample_repo_files(&repo_trees, limits, diagnostics, fetch_file).await
+}
+
+/// Fetch and sample the selected code files of each repo, recording every failed or skipped file
+async fn sample_repo_files<F, Fut>(
+    repo_trees: &[(&GitHubRepoFull, RepoTree)],
+    limits: &AnalysisLimits,
+    mut diagnostics: FetchDiagnostics,
+    fetch: F,
+) -> CollectedCode
+where
+    F: Fn(&GitHubRepoFull, &str) -> Fut,
+    Fut: std::future::Future<Output = Result<String, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let mut collected: Vec<CodeExcerpt> = Vec::new();
     let mut all_code = String::new();
     let mut total_files = 0u32;
     let mut total_lines = 0u32;
     let mut languages_set = std::collections::HashSet::new();
+    let mut language_lines: HashMap<String, u32> = HashMap::new();
+    let mut deduper = ContentDeduper::default();
 
-    let non_fork_repos: Vec<_> = repos.iter().filter(|r| !r.fork).collect();
-    let repos_analyzed = non_fork_repos.len().min(MAX_REPOS) as u32;
+    let repos_analyzed = repo_trees.len() as u32;
 
-    for (repo_idx, repo) in non_fork_repos.iter().take(MAX_REPOS).enumerate() {
-        if total_files >= MAX_TOTAL_FILES as u32 {
+    for (repo_idx, (repo, tree)) in repo_trees.iter().enumerate() {
+        if total_files >= limits.max_total_files as u32 {
             break;
         }
 
         println!("[DEEP] [{}/{}] {}", repo_idx + 1, repos_analyzed, repo.name);
 
-        let tree = match get_repo_tree(&repo.owner.login, &repo.name, token).await {
-            Ok(t) => t,
-            Err(_) => continue,
-        };
-
-        let code_files: Vec<_> = tree.tree.iter()
-            .filter(|f| f.item_type == "blob")
-            .filter(|f| is_code_file(&f.path))
-            .filter(|f| f.size.unwrap_or(0) < MAX_FILE_SIZE)
-            .filter(|f| !should_skip_path(&f.path))
-            .take(MAX_FILES_PER_REPO)
-            .collect();
+        let code_files = select_code_files(&tree.tree, limits);
 
         for file in code_files {
-            if total_files >= MAX_TOTAL_FILES as u32 {
+            if total_files >= limits.max_total_files as u32 {
                 break;
             }
 
-            let content = match get_file_content(&repo.owner.login, &repo.name, &file.path, token).await {
-                Ok(c) => c,
-                Err(_) => continue,
+            let content = match fetch(repo, &file.path).await {
+                Ok(c) => scrub(&c),
+                Err(e) => {
+                    diagnostics.file_failed(&repo.name, &file.path, e);
+                    continue;
+                }
             };
+            diagnostics.file_fetched();
 
             if content.len() < 50 {
+                diagnostics.file_skipped("too_short");
+                continue;
+            }
+
+            if !deduper.is_new(&content) {
+                println!("[DEEP]   Skipping duplicate {} ({})", file.path, repo.name);
+                diagnostics.file_skipped("duplicate");
                 continue;
             }
 
@@ -283,8 +867,9 @@ async fn collect_code_samples(
             let line_count = lines.len() as u32;
             let excerpt_content = lines.join("\n");
 
-            // Categorize by keywords
-            let content_lower = content.to_lowercase();
+            if let Some(ref lang) = language {
+                *language_lines.entry(lang.clone()).or_insert(0) += line_count;
+            }
 
             let excerpt = CodeExcerpt {
                 repo_name: repo.name.clone(),
@@ -295,33 +880,7 @@ async fn collect_code_samples(
                 content: excerpt_content.clone(),
                 similarity: 1.0,
             };
-
-            // Simple keyword categorization
-            if content_lower.contains("error") || content_lower.contains("catch") || content_lower.contains("exception") || content_lower.contains("result") || content_lower.contains("unwrap") {
-                if results.error_handling.len() < 3 { results.error_handling.push(excerpt.clone()); }
-            }
-            if content_lower.contains("async") || content_lower.contains("await") || content_lower.contains("promise") || content_lower.contains("future") {
-                if results.async_patterns.len() < 3 { results.async_patterns.push(excerpt.clone()); }
-            }
-            if content_lower.contains("test") || content_lower.contains("assert") || content_lower.contains("expect") {
-                if results.testing.len() < 3 { results.testing.push(excerpt.clone()); }
-            }
-            if content_lower.contains("log") || content_lower.contains("debug") || content_lower.contains("print") || content_lower.contains("console") {
-                if results.logging.len() < 3 { results.logging.push(excerpt.clone()); }
-            }
-            if content_lower.contains("class") || content_lower.contains("struct") || content_lower.contains("impl") || content_lower.contains("interface") {
-                if results.class_structure.len() < 3 { results.class_structure.push(excerpt.clone()); }
-            }
-            if content_lower.contains("map") || content_lower.contains("filter") || content_lower.contains("reduce") || content_lower.contains("lambda") || content_lower.contains("closure") {
-                if results.functional_patterns.len() < 3 { results.functional_patterns.push(excerpt.clone()); }
-            }
-            if content_lower.contains("valid") || content_lower.contains("check") || content_lower.contains("parse") {
-                if results.validation.len() < 3 { results.validation.push(excerpt.clone()); }
-            }
-            // Always add to naming_style as it shows general coding style
-            if results.naming_style.len() < 3 {
-                results.naming_style.push(excerpt.clone());
-            }
+            collected.push(excerpt);
 
             // Collect for AI analysis
             all_code.push_str(&format!("\n// FILE: {} ({})\n", file.path, repo.name));
@@ -340,57 +899,101 @@ async fn collect_code_samples(
         total_lines,
         repos_analyzed,
         languages_detected: languages_set.into_iter().collect(),
+        duplicates_skipped: deduper.duplicates,
     };
 
-    (results, metadata, all_code)
+    let excerpts = categorize_excerpts(&collected, limits.excerpts_per_category.max(0) as usize);
+
+    CollectedCode { excerpts, metadata, all_code, language_lines, diagnostics }
 }
 
-/// Deep analysis of repositories using embeddings (DEPRECATED - not used)
-#[allow(dead_code)]
+/// Keyword categories an excerpt belongs to
+fn keyword_categories(content: &str) -> Vec<&'static str> {
+    let content_lower = content.to_lowercase();
+    let has_any = |words: &[&str]| words.iter().any(|w| content_lower.contains(w));
+
+    let mut categories = Vec::new();
+    if has_any(&["error", "catch", "exception", "result", "unwrap"]) {
+        categories.push("error_handling");
+    }
+    if has_any(&["async", "await", "promise", "future"]) {
+        categories.push("async_patterns");
+    }
+    if has_any(&["test", "assert", "expect"]) {
+        categories.push("testing");
+    }
+    if has_any(&["log", "debug", "print", "console"]) {
+        categories.push("logging");
+    }
+    if has_any(&["class", "struct", "impl", "interface"]) {
+        categories.push("class_structure");
+    }
+    if has_any(&["map", "filter", "reduce", "lambda", "closure"]) {
+        categories.push("functional_patterns");
+    }
+    if has_any(&["valid", "check", "parse"]) {
+        categories.push("validation");
+    }
+    // Always add to naming_style as it shows general coding style
+    categories.push("naming_style");
+    categories
+}
+
+/// Bucket excerpts by keyword category, spreading each category across repos/files
+fn categorize_excerpts(
+    excerpts: &[crate::github::semantic_search::CodeExcerpt],
+    per_category: usize,
+) -> crate::github::semantic_search::SearchResults {
+    use crate::github::semantic_search::{select_diverse, SearchResults, CodeExcerpt};
+
+    let mut buckets: HashMap<&'static str, Vec<CodeExcerpt>> = HashMap::new();
+    for excerpt in excerpts {
+        for category in keyword_categories(&excerpt.content) {
+            buckets.entry(category).or_default().push(excerpt.clone());
+        }
+    }
+
+    let mut results = SearchResults::default();
+    for (category, candidates) in buckets {
+        results.set(category, select_diverse(candidates, per_category));
+    }
+    results
+}
+
+/// Deep analysis of repositories using embeddings
 async fn deep_analyze_repos(
     conn: &mut PgConnection,
     analysis_id: Uuid,
     username: &str,
     repos: &[GitHubRepoFull],
     token: &str,
-) -> Result<(crate::github::semantic_search::SearchResults, AnalysisMetadata, String), Box<dyn std::error::Error + Send + Sync>> {
+    limits: &AnalysisLimits,
+) -> Result<CollectedCode, Box<dyn std::error::Error + Send + Sync>> {
     let mut all_chunks: Vec<CodeChunk> = Vec::new();
     let mut all_code = String::new();
     let mut total_files = 0;
+    let mut language_lines: HashMap<String, u32> = HashMap::new();
+    let mut deduper = ContentDeduper::default();
+    let mut diagnostics = FetchDiagnostics::default();
 
-    // Only analyze non-fork repos
-    let non_fork_repos: Vec<_> = repos.iter().filter(|r| !r.fork).collect();
-    println!("[DEEP] Found {} non-fork repos to analyze", non_fork_repos.len());
+    // Only analyze non-fork repos, code-rich ones first
+    let repo_trees = own_repo_trees(repos, token, limits.max_repos, &mut diagnostics).await;
+    println!("[DEEP] Found {} non-fork repos to analyze", repo_trees.len());
 
-    for (repo_idx, repo) in non_fork_repos.iter().take(MAX_REPOS).enumerate() {
-        if total_files >= MAX_TOTAL_FILES {
+    for (repo_idx, (repo, tree)) in repo_trees.iter().enumerate() {
+        if total_files >= limits.max_total_files {
             break;
         }
 
-        println!("[DEEP] [{}/{}] Analyzing repo: {}", repo_idx + 1, non_fork_repos.len().min(MAX_REPOS), repo.name);
+        println!("[DEEP] [{}/{}] Analyzing repo: {}", repo_idx + 1, repo_trees.len(), repo.name);
 
-        // Get repository file tree
-        let tree = match get_repo_tree(&repo.owner.login, &repo.name, token).await {
-            Ok(t) => t,
-            Err(e) => {
-                println!("[DEEP]   Skipping {} - tree error: {}", repo.name, e);
-                continue;
-            }
-        };
-
-        // Filter to code files
-        let code_files: Vec<_> = tree.tree.iter()
-            .filter(|f| f.item_type == "blob")
-            .filter(|f| is_code_file(&f.path))
-            .filter(|f| f.size.unwrap_or(0) < MAX_FILE_SIZE)
-            .filter(|f| !should_skip_path(&f.path))
-            .take(MAX_FILES_PER_REPO)
-            .collect();
+        // Filter to code files, spread across packages for monorepos
+        let code_files = select_code_files(&tree.tree, limits);
 
         println!("[DEEP]   Found {} code files", code_files.len());
 
         for (file_idx, file) in code_files.iter().enumerate() {
-            if total_files >= MAX_TOTAL_FILES {
+            if total_files >= limits.max_total_files {
                 break;
             }
 
@@ -399,14 +1002,23 @@ async fn deep_analyze_repos(
             let content = match get_file_content(
                 &repo.owner.login, &repo.name, &file.path, token
             ).await {
-                Ok(c) => c,
+                Ok(c) => scrub(&c),
                 Err(e) => {
                     println!("[DEEP]     Error: {}", e);
+                    diagnostics.file_failed(&repo.name, &file.path, e);
                     continue;
                 }
             };
+            diagnostics.file_fetched();
 
             if content.len() < 100 {
+                diagnostics.file_skipped("too_short");
+                continue;
+            }
+
+            if !deduper.is_new(&content) {
+                println!("[DEEP]     Skipping duplicate of an already sampled file");
+                diagnostics.file_skipped("duplicate");
                 continue;
             }
 
@@ -421,6 +1033,10 @@ async fn deep_analyze_repos(
                 language.as_deref(),
             );
 
+            if let Some(ref lang) = language {
+                *language_lines.entry(lang.clone()).or_insert(0) += content.lines().count() as u32;
+            }
+
             all_chunks.extend(chunks);
 
             // Also collect raw code for AI analysis
@@ -437,27 +1053,105 @@ async fn deep_analyze_repos(
         tokio::time::sleep(tokio::time::Duration::from_millis(50)).await;
     }
 
-    // Store chunks with embeddings
-    println!("[DEEP] Collected {} chunks, storing with embeddings...", all_chunks.len());
-    let stored_count = store_chunks_batch(conn, analysis_id, username, all_chunks).await?;
-    println!("[DEEP] Stored {} chunks", stored_count);
+    let duplicates = deduper.duplicates;
+    let (excerpts, metadata) = select_excerpts(all_chunks, limits, duplicates, embeddings_available(), |chunks| {
+        search_with_embeddings(conn, analysis_id, username, chunks, limits, duplicates)
+    })
+    .await;
+
+    Ok(CollectedCode { excerpts, metadata, all_code, language_lines, diagnostics })
+}
+
+/// Excerpts chosen by `semantic_search`, or categorized by keyword when embeddings are unavailable
+/// (no pgvector/code_embeddings) or the search fails
+async fn select_excerpts<S, Fut>(
+    chunks: Vec<CodeChunk>,
+    limits: &AnalysisLimits,
+    duplicates_skipped: u32,
+    embeddings: bool,
+    semantic_search: S,
+) -> (SearchResults, AnalysisMetadata)
+where
+    S: FnOnce(Vec<CodeChunk>) -> Fut,
+    Fut: std::future::Future<Output = Result<(SearchResults, AnalysisMetadata), Box<dyn std::error::Error + Send + Sync>>>,
+{
+    let per_category = limits.excerpts_per_category.max(0) as usize;
+    if !embeddings {
+        println!("[DEEP] Warning: embeddings unavailable, categorizing excerpts by keyword");
+        return keyword_fallback(&chunks, per_category, duplicates_skipped);
+    }
+
+    match semantic_search(chunks.clone()).await {
+        Ok(found) => found,
+        Err(e) => {
+            println!("[DEEP] Warning: embedding search failed ({}), categorizing excerpts by keyword", e);
+            keyword_fallback(&chunks, per_category, duplicates_skipped)
+        }
+    }
+}
+
+/// Store chunks with embeddings and pick excerpts via semantic search
+async fn search_with_embeddings(
+    conn: &mut PgConnection,
+    analysis_id: Uuid,
+    username: &str,
+    chunks: Vec<CodeChunk>,
+    limits: &AnalysisLimits,
+    duplicates_skipped: u32,
+) -> Result<(SearchResults, AnalysisMetadata), Box<dyn std::error::Error + Send + Sync>> {
+    println!("[DEEP] Collected {} chunks, storing with embeddings...", chunks.len());
+    let stored = store_chunks_batch(conn, analysis_id, username, chunks).await?;
+    println!(
+        "[DEEP] Stored {} chunks ({} already stored, {} duplicates)",
+        stored.inserted, stored.skipped, stored.deduplicated
+    );
+    let stored_count = stored.inserted + stored.skipped;
 
     // Run semantic search across all categories
     println!("[DEEP] Running semantic search...");
-    let code_excerpts = search_all_categories(conn, analysis_id, EXCERPTS_PER_CATEGORY).await?;
+    let code_excerpts = search_all_categories(conn, analysis_id, limits.excerpts_per_category, DEFAULT_MIN_SIMILARITY).await?;
     println!("[DEEP] Semantic search complete");
 
     // Get embedding stats
-    let stats = get_embedding_stats(conn, analysis_id).await?;
+    let stats = EmbeddingStats {
+        deduplicated_chunks: stored.deduplicated as u32,
+        ..get_embedding_stats(conn, analysis_id).await?
+    };
 
     let analysis_metadata = AnalysisMetadata {
         chunks_analyzed: stored_count as u32,
         total_lines: stats.total_lines,
         repos_analyzed: stats.repo_count,
         languages_detected: stats.languages,
+        duplicates_skipped: duplicates_skipped + stats.deduplicated_chunks,
     };
 
-    Ok((code_excerpts, analysis_metadata, all_code))
+    Ok((code_excerpts, analysis_metadata))
+}
+
+/// Keyword-categorized excerpts and metadata for chunks that couldn't be embedded
+fn keyword_fallback(
+    chunks: &[CodeChunk],
+    per_category: usize,
+    duplicates_skipped: u32,
+) -> (SearchResults, AnalysisMetadata) {
+    let excerpts: Vec<CodeExcerpt> = chunks.iter().map(CodeExcerpt::from).collect();
+
+    let repos: HashSet<&str> = chunks.iter().map(|c| c.repo_name.as_str()).collect();
+    let languages: HashSet<&str> = chunks.iter().filter_map(|c| c.language.as_deref()).collect();
+    let total_lines = chunks.iter()
+        .map(|c| (c.line_end - c.line_start + 1).max(0) as u32)
+        .sum();
+
+    let metadata = AnalysisMetadata {
+        chunks_analyzed: chunks.len() as u32,
+        total_lines,
+        repos_analyzed: repos.len() as u32,
+        languages_detected: languages.into_iter().map(String::from).collect(),
+        duplicates_skipped,
+    };
+
+    (categorize_excerpts(&excerpts, per_category), metadata)
 }
 
 /// Simple code fetching without embeddings (for basic analysis)
@@ -465,56 +1159,47 @@ async fn fetch_code_samples_simple(
     _username: &str,
     repos: &[GitHubRepoFull],
     token: &str,
+    limits: &AnalysisLimits,
 ) -> String {
     let mut all_code = String::new();
     let mut files_analyzed: usize = 0;
     let mut total_lines: usize = 0;
+    let mut deduper = ContentDeduper::default();
 
-    const MAX_FILES: usize = 30;
     const MAX_LINES_PER_FILE: usize = 500;
     const MAX_TOTAL_LINES: usize = 5000;
-    const MAX_FILE_SIZE_SIMPLE: u64 = 50000;
-
-    // Only analyze non-fork repos
-    let non_fork_repos: Vec<_> = repos.iter().filter(|r| !r.fork).collect();
 
-    for repo in non_fork_repos.iter().take(5) {
-        if files_analyzed >= MAX_FILES || total_lines >= MAX_TOTAL_LINES {
+    // Only analyze non-fork repos, code-rich ones first
+    for (repo, tree) in own_repo_trees(repos, token, limits.max_repos, &mut FetchDiagnostics::default()).await {
+        if files_analyzed >= limits.max_total_files || total_lines >= MAX_TOTAL_LINES {
             break;
         }
 
-        let tree = match get_repo_tree(&repo.owner.login, &repo.name, token).await {
-            Ok(t) => t,
-            Err(_) => continue,
-        };
-
         let mut code_files: Vec<_> = tree.tree.iter()
             .filter(|f| f.item_type == "blob")
             .filter(|f| is_code_file(&f.path))
-            .filter(|f| f.size.unwrap_or(0) < MAX_FILE_SIZE_SIMPLE)
+            .filter(|f| f.size.unwrap_or(0) < limits.max_file_size)
             .filter(|f| !should_skip_path(&f.path))
             .collect();
 
         code_files.sort_by_key(|f| f.size.unwrap_or(0));
 
-        let files_per_repo = MAX_FILES / 5;
-
-        for file in code_files.iter().take(files_per_repo) {
-            if files_analyzed >= MAX_FILES || total_lines >= MAX_TOTAL_LINES {
+        for file in code_files.iter().take(limits.max_files_per_repo) {
+            if files_analyzed >= limits.max_total_files || total_lines >= MAX_TOTAL_LINES {
                 break;
             }
 
             let content = match get_file_content(
                 &repo.owner.login, &repo.name, &file.path, token
             ).await {
-                Ok(c) => c,
+                Ok(c) => scrub(&c),
                 Err(_) => continue,
             };
 
             let lines: Vec<&str> = content.lines().take(MAX_LINES_PER_FILE).collect();
             let line_count = lines.len();
 
-            if line_count < 10 {
+            if line_count < 10 || !deduper.is_new(&content) {
                 continue;
             }
 
@@ -530,9 +1215,414 @@ async fn fetch_code_samples_simple(
     all_code
 }
 
-/// Get code excerpts summary for the profile generator
-pub fn get_excerpts_for_profile(stats: &GitHubStats) -> Option<String> {
+/// Get code excerpts summary for the profile generator, limited to `categories`
+pub fn get_excerpts_for_profile(stats: &GitHubStats, categories: &[SearchCategory]) -> Option<String> {
     stats.code_excerpts.as_ref().map(|excerpts| {
-        summarize_excerpts(excerpts, 2000)
+        summarize_excerpts(excerpts, 2000, categories)
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::github::api::GitHubUser;
+
+    #[test]
+    fn test_profile_fields_reach_stats() {
+        let user: GitHubUser = serde_json::from_value(serde_json::json!({
+            "login": "octocat",
+            "name": "The Octocat",
+            "bio": null,
+            "avatar_url": "https://avatars.githubusercontent.com/u/583231",
+            "public_repos": 8,
+            "followers": 100,
+            "following": 9,
+            "created_at": "2011-01-25T18:44:36Z",
+            "blog": "https://github.blog",
+            "twitter_username": null,
+            "company": "@github",
+            "location": ""
+        })).unwrap();
+
+        let stats = GitHubStats {
+            username: user.login.clone(),
+            profile: GitHubProfile::from(user),
+            repositories: vec![],
+            repositories_truncated: false,
+            reputation: Reputation::default(),
+            ai_analysis: AIAnalysis::default(),
+            languages: HashMap::new(),
+            analyzed_at: String::new(),
+            code_excerpts: None,
+            analysis_metadata: None,
+            fetch_diagnostics: None,
+            activity: None,
+        };
+
+        assert_eq!(stats.profile.company.as_deref(), Some("@github"));
+        assert_eq!(stats.profile.blog.as_deref(), Some("https://github.blog"));
+        assert!(stats.profile.twitter_username.is_none());
+        // Empty strings from GitHub are treated as missing
+        assert!(stats.profile.location.is_none());
+
+        let json = serde_json::to_value(&stats).unwrap();
+        assert_eq!(json["profile"]["company"], "@github");
+        assert_eq!(json["profile"]["blog"], "https://github.blog");
+    }
+
+    #[test]
+    fn test_categorized_excerpts_span_repos() {
+        use crate::github::semantic_search::CodeExcerpt;
+
+        let file = |repo: &str, path: &str| CodeExcerpt {
+            repo_name: repo.to_string(),
+            file_path: path.to_string(),
+            line_start: 1,
+            line_end: 3,
+            language: Some("Rust".to_string()),
+            content: "fn run() -> Result<(), Error> { Ok(()) }".to_string(),
+            similarity: 1.0,
+        };
+
+        // First repo contributes many matching files before the others appear
+        let excerpts = vec![
+            file("api", "src/a.rs"),
+            file("api", "src/b.rs"),
+            file("api", "src/c.rs"),
+            file("api", "src/d.rs"),
+            file("cli", "src/main.rs"),
+            file("web", "src/lib.rs"),
+        ];
+
+        let results = categorize_excerpts(&excerpts, 3);
+        let repos: std::collections::HashSet<&str> = results.error_handling
+            .iter()
+            .map(|e| e.repo_name.as_str())
+            .collect();
+
+        assert_eq!(results.error_handling.len(), 3);
+        assert_eq!(repos.len(), 3);
+        assert_eq!(results.naming_style.len(), 3);
+        assert!(results.testing.is_empty());
+    }
+
+    #[test]
+    fn test_identical_files_across_repos_sampled_once() {
+        let boilerplate = "fn main() {\n    println!(\"hello\");\n}\n";
+        // Same file re-indented with CRLF line endings in a renamed fork
+        let copied = "fn main() {\r\n  println!(\"hello\");\r\n}\r\n\r\n";
+        let files = [
+            ("starter", "src/main.rs", boilerplate),
+            ("starter-renamed", "src/main.rs", copied),
+            ("starter-renamed", "src/lib.rs", "pub fn add(a: i32, b: i32) -> i32 { a + b }"),
+        ];
+
+        let mut deduper = ContentDeduper::default();
+        let sampled: Vec<(&str, &str)> = files
+            .iter()
+            .filter(|(_, _, content)| deduper.is_new(content))
+            .map(|(repo, path, _)| (*repo, *path))
+            .collect();
+
+        assert_eq!(sampled, vec![("starter", "src/main.rs"), ("starter-renamed", "src/lib.rs")]);
+        assert_eq!(deduper.duplicates, 1);
+    }
+
+    #[test]
+    fn test_monorepo_sampling_spans_packages() {
+        let item = |path: &str| TreeItem {
+            path: path.to_string(),
+            item_type: "blob".to_string(),
+            size: Some(1_000),
+        };
+
+        // Files of the first package alone would fill the whole budget
+        let mut tree = vec![
+            item("package.json"),
+            item("packages/web/package.json"),
+            item("packages/api/package.json"),
+            item("services/worker/Cargo.toml"),
+        ];
+        tree.extend((0..20).map(|i| item(&format!("packages/web/src/c{}.tsx", i))));
+        tree.extend((0..5).map(|i| item(&format!("packages/api/src/r{}.ts", i))));
+        tree.extend((0..5).map(|i| item(&format!("services/worker/src/j{}.rs", i))));
+        tree.extend((0..5).map(|i| item(&format!("scripts/s{}.ts", i))));
+
+        assert_eq!(detect_packages(&tree), vec!["packages/api", "packages/web", "services/worker"]);
+
+        let limits = AnalysisLimits { max_files_per_repo: 9, ..AnalysisLimits::default() };
+        let selected = select_code_files(&tree, &limits);
+        let from = |prefix: &str| selected.iter().filter(|f| f.path.starts_with(prefix)).count();
+
+        assert_eq!(selected.len(), 9);
+        assert!(from("packages/api/") >= 2);
+        assert!(from("packages/web/") >= 2);
+        assert!(from("services/worker/") >= 2);
+    }
+
+    #[test]
+    fn test_single_package_repo_keeps_tree_order() {
+        let tree: Vec<TreeItem> = (0..10)
+            .map(|i| TreeItem { path: format!("src/m{}.rs", i), item_type: "blob".to_string(), size: Some(500) })
+            .collect();
+
+        let limits = AnalysisLimits { max_files_per_repo: 3, ..AnalysisLimits::default() };
+        let paths: Vec<&str> = select_code_files(&tree, &limits).iter().map(|f| f.path.as_str()).collect();
+
+        assert_eq!(paths, vec!["src/m0.rs", "src/m1.rs", "src/m2.rs"]);
+    }
+
+    #[test]
+    fn test_docs_repo_deprioritized_behind_code_repo() {
+        let blob = |path: &str, size: u64| TreeItem { path: path.to_string(), item_type: "blob".to_string(), size: Some(size) };
+
+        let docs_site = vec![
+            blob("README.md", 4000),
+            blob("docs/guide.md", 30000),
+            blob("docs/api.mdx", 25000),
+            blob("mkdocs.yml", 800),
+            blob("scripts/build.js", 600),
+        ];
+        let service = vec![
+            blob("README.md", 2000),
+            blob("src/main.rs", 8000),
+            blob("src/handlers.rs", 12000),
+            blob("Cargo.toml", 500),
+        ];
+
+        assert_eq!(classify_repo(&docs_site), RepoComposition::DocsOrConfig);
+        assert_eq!(classify_repo(&service), RepoComposition::Code);
+
+        let classified = vec![
+            ("docs-site", classify_repo(&docs_site)),
+            ("service", classify_repo(&service)),
+        ];
+        assert_eq!(order_by_composition(classified.clone(), 2), vec!["service", "docs-site"]);
+        // With one slot, the docs repo is skipped entirely
+        assert_eq!(order_by_composition(classified, 1), vec!["service"]);
+    }
+
+    #[test]
+    fn test_file_level_languages_beat_repo_label() {
+        // GitHub labels the repo "HTML" but the sampled files are TypeScript
+        let repo_languages = HashMap::from([("HTML".to_string(), 100)]);
+        let language_lines = HashMap::from([
+            ("TypeScript".to_string(), 950),
+            ("JavaScript".to_string(), 50),
+        ]);
+
+        let languages = resolve_languages(repo_languages, &language_lines, 100);
+
+        assert_eq!(languages.get("TypeScript"), Some(&95));
+        assert_eq!(languages.get("JavaScript"), Some(&5));
+        assert!(!languages.contains_key("HTML"));
+    }
+
+    #[test]
+    fn test_repo_languages_used_without_enough_samples() {
+        let repo_languages = HashMap::from([("HTML".to_string(), 100)]);
+
+        let languages = resolve_languages(repo_languages.clone(), &HashMap::new(), 100);
+        assert_eq!(languages, repo_languages);
+
+        let few_lines = HashMap::from([("TypeScript".to_string(), 40)]);
+        let languages = resolve_languages(repo_languages.clone(), &few_lines, 100);
+        assert_eq!(languages, repo_languages);
+    }
+
+    #[test]
+    fn test_depth_profile_parse() {
+        assert_eq!(DepthProfile::parse("quick"), Some(DepthProfile::Quick));
+        assert_eq!(DepthProfile::parse("Standard"), Some(DepthProfile::Standard));
+        assert_eq!(DepthProfile::parse(" THOROUGH "), Some(DepthProfile::Thorough));
+        assert_eq!(DepthProfile::parse("deep"), None);
+    }
+
+    #[test]
+    fn test_depth_profile_limits_and_paths() {
+        let quick = DepthProfile::Quick;
+        assert_eq!(quick.path(), AnalysisPath::Basic);
+        assert!(!quick.uses_embeddings());
+        assert_eq!(quick.limits().max_repos, 5);
+        assert_eq!(quick.limits().max_files_per_repo, 6);
+
+        let standard = DepthProfile::Standard;
+        assert_eq!(standard.path(), AnalysisPath::Samples);
+        assert!(!standard.uses_embeddings());
+        assert_eq!(standard.limits(), AnalysisLimits::default());
+        assert_eq!(standard.limits().max_total_files, MAX_TOTAL_FILES);
+
+        let thorough = DepthProfile::Thorough;
+        assert_eq!(thorough.path(), AnalysisPath::Embeddings);
+        assert!(thorough.uses_embeddings());
+        assert!(thorough.limits().max_repos > standard.limits().max_repos);
+        assert!(thorough.limits().max_total_files > standard.limits().max_total_files);
+        assert!(thorough.limits().excerpts_per_category > standard.limits().excerpts_per_category);
+    }
+
+    #[test]
+    fn test_keyword_excerpts_without_embeddings() {
+        fn chunk(repo: &str, file: &str, content: &str) -> CodeChunk {
+            CodeChunk {
+                repo_name: repo.to_string(),
+                file_path: file.to_string(),
+                line_start: 1,
+                line_end: 20,
+                language: Some("Rust".to_string()),
+                content: content.to_string(),
+            }
+        }
+
+        crate::github::capabilities::set_embeddings_available(false);
+        assert!(!embeddings_available());
+
+        let chunks = vec![
+            chunk("api", "src/handler.rs", "fn load() -> Result<Config, Error> { parse()? }"),
+            chunk("worker", "src/jobs.rs", "async fn run() { queue.next().await; log::debug!(\"done\"); }"),
+        ];
+        let (excerpts, metadata) = keyword_fallback(&chunks, 3, 1);
+
+        assert_eq!(excerpts.error_handling.len(), 1);
+        assert_eq!(excerpts.async_patterns[0].repo_name, "worker");
+        assert_eq!(excerpts.logging.len(), 1);
+        assert_eq!(excerpts.naming_style.len(), 2);
+        assert_eq!(metadata.chunks_analyzed, 2);
+        assert_eq!(metadata.total_lines, 40);
+        assert_eq!(metadata.repos_analyzed, 2);
+        assert_eq!(metadata.languages_detected, vec!["Rust".to_string()]);
+        assert_eq!(metadata.duplicates_skipped, 1);
+    }
+
+    #[rocket::async_test]
+    async fn test_failed_file_fetches_reported_in_diagnostics() {
+        let repo = |name: &str| -> GitHubRepoFull {
+            serde_json::from_value(serde_json::json!({
+                "name": name, "owner": {"login": "octocat"}, "fork": false, "description": null,
+                "stargazers_count": 0, "forks_count": 0, "watchers_count": 0, "language": "Rust",
+                "size": 100, "created_at": "2020-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z"
+            }))
+            .unwrap()
+        };
+        let tree = |paths: &[&str]| RepoTree {
+            tree: paths
+                .iter()
+                .map(|p| TreeItem { path: p.to_string(), item_type: "blob".to_string(), size: Some(1_000) })
+                .collect(),
+        };
+        let (api, tools) = (repo("api"), repo("tools"));
+        let repo_trees = vec![
+            (&api, tree(&["src/main.rs", "src/db.rs", "src/routes.rs"])),
+            (&tools, tree(&["src/cli.rs", "src/tiny.rs"])),
+        ];
+
+        // Tree failures are recorded before files are sampled
+        let mut diagnostics = FetchDiagnostics::default();
+        diagnostics.repo_failed("archived", "404 Not Found");
+
+        let fetch = |repo: &GitHubRepoFull, path: &str| {
+            let result = match (repo.name.as_str(), path) {
+                ("api", "src/db.rs") | ("tools", "src/cli.rs") => Err("GitHub API rate limit exceeded".into()),
+                (_, "src/tiny.rs") => Ok("fn f() {}".to_string()),
+                (name, path) => Ok(format!("// {}/{}\n{}", name, path, "pub fn handler() -> u32 { 42 }\n".repeat(3))),
+            };
+            async move { result }
+        };
+        let collected = sample_repo_files(&repo_trees, &AnalysisLimits::default(), diagnostics, fetch).await;
+        let diagnostics = collected.diagnostics;
+
+        assert_eq!(collected.metadata.chunks_analyzed, 2);
+        assert_eq!(diagnostics.repos_failed, 1);
+        assert_eq!(diagnostics.files_fetched, 3);
+        assert_eq!(diagnostics.files_failed, 2);
+        assert_eq!(diagnostics.files_skipped, 1);
+        assert_eq!(diagnostics.skip_reasons.get("too_short"), Some(&1));
+
+        let failed: Vec<(&str, Option<&str>)> = diagnostics.failures.iter().map(|f| (f.repo.as_str(), f.path.as_deref())).collect();
+        assert_eq!(failed, vec![("archived", None), ("api", Some("src/db.rs")), ("tools", Some("src/cli.rs"))]);
+        assert_eq!(diagnostics.failures[1].reason, "GitHub API rate limit exceeded");
+    }
+
+    #[rocket::async_test]
+    async fn test_max_repos_override_limits_repos_analyzed() {
+        let repos: Vec<GitHubRepoFull> = ["api", "web", "cli", "worker"]
+            .iter()
+            .map(|name| {
+                serde_json::from_value(serde_json::json!({
+                    "name": name, "owner": {"login": "octocat"}, "fork": false, "description": null,
+                    "stargazers_count": 0, "forks_count": 0, "watchers_count": 0, "language": "Rust",
+                    "size": 100, "created_at": "2020-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z"
+                }))
+                .unwrap()
+            })
+            .collect();
+        let fetch_tree = |_: &str, name: &str| {
+            let tree = RepoTree {
+                tree: vec![TreeItem { path: format!("src/{}.rs", name), item_type: "blob".to_string(), size: Some(1_000) }],
+            };
+            async move { Ok(tree) }
+        };
+        let fetch_file = |repo: &GitHubRepoFull, _: &str| {
+            let content = format!("// {}\n{}", repo.name, "pub fn handler() -> u32 { 42 }\n".repeat(3));
+            async move { Ok(content) }
+        };
+
+        let overrides = AnalysisLimitOverrides { max_repos: Some(2), ..Default::default() };
+        let limits = AnalysisLimits::default().with_overrides(&overrides);
+        let collected = collect_code_samples_with(&repos, &limits, fetch_tree, fetch_file).await;
+        assert_eq!(collected.metadata.repos_analyzed, 2);
+        assert_eq!(collected.metadata.chunks_analyzed, 2);
+
+        let collected = collect_code_samples_with(&repos, &AnalysisLimits::default(), fetch_tree, fetch_file).await;
+        assert_eq!(collected.metadata.repos_analyzed, 4);
+    }
+
+    #[test]
+    fn test_limit_overrides_clamped_to_ceilings() {
+        let overrides = AnalysisLimitOverrides {
+            max_repos: Some(1_000),
+            max_files_per_repo: Some(0),
+            max_total_files: None,
+            max_file_size: Some(u64::MAX),
+        };
+        let limits = AnalysisLimits::default().with_overrides(&overrides);
+
+        assert_eq!(limits.max_repos, MAX_REPOS_CEILING);
+        assert_eq!(limits.max_files_per_repo, 1);
+        assert_eq!(limits.max_total_files, MAX_TOTAL_FILES);
+        assert_eq!(limits.max_file_size, MAX_FILE_SIZE_CEILING);
+        assert_eq!(limits.excerpts_per_category, EXCERPTS_PER_CATEGORY);
+    }
+
+    #[rocket::async_test]
+    async fn test_excerpts_come_from_semantic_search_when_embeddings_enabled() {
+        let chunk = |repo: &str, content: &str| CodeChunk {
+            repo_name: repo.to_string(),
+            file_path: "src/lib.rs".to_string(),
+            line_start: 1,
+            line_end: 10,
+            language: Some("Rust".to_string()),
+            content: content.to_string(),
+        };
+        // Mentions "error" only in a comment, so keyword categorization misfiles it
+        let chunks = vec![chunk("api", "// no error handling here\nfn add(a: i32, b: i32) -> i32 { a + b }")];
+        let limits = AnalysisLimits::default();
+
+        let mut semantic = SearchResults::default();
+        semantic.set("async_patterns", vec![CodeExcerpt { similarity: 0.82, ..CodeExcerpt::from(&chunks[0]) }]);
+        let metadata = AnalysisMetadata { chunks_analyzed: 1, total_lines: 10, repos_analyzed: 1, languages_detected: vec![], duplicates_skipped: 0 };
+        let search = |found: Result<(SearchResults, AnalysisMetadata), &'static str>| {
+            move |_: Vec<CodeChunk>| async move { found.map_err(|e| e.into()) }
+        };
+
+        let (excerpts, _) = select_excerpts(chunks.clone(), &limits, 0, true, search(Ok((semantic, metadata.clone())))).await;
+        assert!(excerpts.error_handling.is_empty());
+        assert_eq!(excerpts.async_patterns.len(), 1);
+        assert_eq!(excerpts.async_patterns[0].similarity, 0.82);
+
+        // A failed search, or no embeddings at all, falls back to keywords
+        let (excerpts, _) = select_excerpts(chunks.clone(), &limits, 0, true, search(Err("embedding request failed"))).await;
+        assert_eq!(excerpts.error_handling.len(), 1);
+        let (excerpts, _) = select_excerpts(chunks, &limits, 0, false, search(Ok((SearchResults::default(), metadata)))).await;
+        assert_eq!(excerpts.error_handling.len(), 1);
+    }
+}
@@ -1,12 +1,29 @@
+use std::sync::OnceLock;
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 
 const GITHUB_API: &str = "https://api.github.com";
 
+/// Retries after a rate limit before the request is given up (GITHUB_RATE_LIMIT_RETRIES)
+const DEFAULT_RATE_LIMIT_RETRIES: u32 = 3;
+/// Longest single back-off, whether from Retry-After or the rate limit reset (GITHUB_RATE_LIMIT_MAX_WAIT_SECS)
+const DEFAULT_RATE_LIMIT_MAX_WAIT_SECS: u64 = 300;
+/// GitHub asks for at least a minute when a rate limited response doesn't say how long to wait
+const RATE_LIMIT_FALLBACK_WAIT: Duration = Duration::from_secs(60);
+
+/// Bytes inspected when deciding whether a file is binary
+const BINARY_SNIFF_LEN: usize = 8000;
+/// Share of control bytes (or invalid UTF-8 sequences) above which content is treated as binary
+const MAX_NON_TEXT_RATIO: f32 = 0.1;
+
 #[derive(Deserialize)]
 pub struct GitHubRepo {
     pub name: String,
     pub owner: RepoOwner,
     pub fork: bool,
+    #[serde(default)]
+    pub updated_at: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -23,6 +40,14 @@ pub struct GitHubCommit {
 #[derive(Deserialize)]
 pub struct CommitInfo {
     pub message: String,
+    #[serde(default)]
+    pub author: Option<CommitSignature>,
+}
+
+#[derive(Deserialize)]
+pub struct CommitSignature {
+    /// ISO 8601 authoring time
+    pub date: Option<String>,
 }
 
 #[derive(Deserialize)]
@@ -57,6 +82,9 @@ pub struct TreeItem {
 pub struct FileContent {
     pub content: Option<String>,
     pub encoding: Option<String>,
+    /// Raw file URL; the only source of content for files over 1MB (encoding "none")
+    #[serde(default)]
+    pub download_url: Option<String>,
 }
 
 // Extended repo data for full analysis
@@ -86,6 +114,222 @@ pub struct GitHubUser {
     pub followers: u32,
     pub following: u32,
     pub created_at: String,
+    pub blog: Option<String>,
+    pub twitter_username: Option<String>,
+    pub company: Option<String>,
+    pub location: Option<String>,
+}
+
+/// How to back off when GitHub rate limits a request (primary or secondary limit)
+pub struct RateLimitPolicy {
+    pub max_retries: u32,
+    pub max_wait: Duration,
+}
+
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: DEFAULT_RATE_LIMIT_RETRIES,
+            max_wait: Duration::from_secs(DEFAULT_RATE_LIMIT_MAX_WAIT_SECS),
+        }
+    }
+}
+
+impl RateLimitPolicy {
+    /// Build from GITHUB_RATE_LIMIT_RETRIES and GITHUB_RATE_LIMIT_MAX_WAIT_SECS, falling back to their
+    /// older GITHUB_SECONDARY_LIMIT_* names; invalid values are logged and the defaults kept
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+
+        if let Some((name, retries)) = env_or_legacy("GITHUB_RATE_LIMIT_RETRIES", "GITHUB_SECONDARY_LIMIT_RETRIES") {
+            match retries.trim().parse::<u32>() {
+                Ok(retries) => policy.max_retries = retries,
+                _ => println!("[GitHub] Ignoring invalid {} {:?}", name, retries),
+            }
+        }
+
+        if let Some((name, secs)) = env_or_legacy("GITHUB_RATE_LIMIT_MAX_WAIT_SECS", "GITHUB_SECONDARY_LIMIT_MAX_WAIT_SECS") {
+            match secs.trim().parse::<u64>() {
+                Ok(secs) if secs > 0 => policy.max_wait = Duration::from_secs(secs),
+                _ => println!("[GitHub] Ignoring invalid {} {:?}", name, secs),
+            }
+        }
+
+        policy
+    }
+}
+
+/// The variable's value, or the legacy name's when only that is set, along with the name that was read
+fn env_or_legacy(name: &'static str, legacy: &'static str) -> Option<(&'static str, String)> {
+    [name, legacy].into_iter().find_map(|key| std::env::var(key).ok().map(|value| (key, value)))
+}
+
+/// The process-wide rate limit policy (configured once from env)
+pub fn rate_limit_policy() -> &'static RateLimitPolicy {
+    static POLICY: OnceLock<RateLimitPolicy> = OnceLock::new();
+    POLICY.get_or_init(RateLimitPolicy::from_env)
+}
+
+/// A failed GitHub request; returned boxed by the helpers below, so callers react with `downcast_ref`
+#[derive(Debug)]
+pub enum GitHubError {
+    /// Still rate limited after the retries ran out
+    RateLimited { retry_after: Option<Duration>, message: String },
+    /// 404: the user, repo or file doesn't exist (or isn't visible to the token)
+    NotFound { path: String },
+    /// Any other failure; `status` is None when no response came back
+    Other { status: Option<u16>, message: String },
+}
+
+impl std::fmt::Display for GitHubError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RateLimited { retry_after: Some(wait), message } => {
+                write!(f, "GitHub rate limit exceeded, retry in {}s: {}", wait.as_secs(), message)
+            }
+            Self::RateLimited { retry_after: None, message } => write!(f, "GitHub rate limit exceeded: {}", message),
+            Self::NotFound { path } => write!(f, "GitHub resource not found: {}", path),
+            Self::Other { status: Some(status), message } => write!(f, "GitHub API returned {}: {}", status, message),
+            Self::Other { status: None, message } => write!(f, "GitHub request failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for GitHubError {}
+
+impl From<reqwest::Error> for GitHubError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Other { status: e.status().map(|s| s.as_u16()), message: e.to_string() }
+    }
+}
+
+/// Rate limit headers of a 403/429 response
+#[derive(Debug, Default)]
+struct RateLimitHeaders {
+    /// X-RateLimit-Remaining
+    remaining: Option<u64>,
+    /// X-RateLimit-Reset, in epoch seconds
+    reset: Option<u64>,
+    /// Retry-After, in seconds
+    retry_after: Option<u64>,
+}
+
+impl RateLimitHeaders {
+    fn from_response(response: &reqwest::Response) -> Self {
+        let header = |name: &str| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|v| v.to_str().ok())
+                .and_then(|v| v.trim().parse::<u64>().ok())
+        };
+        Self {
+            remaining: header("x-ratelimit-remaining"),
+            reset: header("x-ratelimit-reset"),
+            retry_after: header("retry-after"),
+        }
+    }
+}
+
+/// How long to wait before retrying, if a 403/429 is a rate limit rather than a permission error
+/// Retry-After wins; an exhausted primary limit waits until X-RateLimit-Reset; secondary limits are
+/// recognized by their message, and GitHub asks for at least a minute when nothing says how long
+fn rate_limit_wait(status: u16, headers: &RateLimitHeaders, body: &str, now: u64) -> Option<Duration> {
+    if status != 403 && status != 429 {
+        return None;
+    }
+    if let Some(secs) = headers.retry_after {
+        return Some(Duration::from_secs(secs));
+    }
+    if headers.remaining == Some(0) {
+        return Some(
+            headers
+                .reset
+                .map(|reset| Duration::from_secs(reset.saturating_sub(now)))
+                .unwrap_or(RATE_LIMIT_FALLBACK_WAIT),
+        );
+    }
+
+    let message = body.to_lowercase();
+    if status == 429 || message.contains("rate limit") || message.contains("abuse detection") {
+        return Some(RATE_LIMIT_FALLBACK_WAIT);
+    }
+    None
+}
+
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Send a GitHub request, backing off and retrying while it is rate limited
+async fn send_with_backoff(
+    request: reqwest::RequestBuilder,
+    policy: &RateLimitPolicy,
+) -> Result<reqwest::Response, GitHubError> {
+    let mut retries = 0;
+    loop {
+        let attempt = match request.try_clone() {
+            Some(attempt) => attempt,
+            // Streaming bodies can't be replayed; send once without retrying
+            None => return Ok(request.send().await?),
+        };
+        let response = attempt.send().await?;
+
+        let status = response.status().as_u16();
+        if status != 403 && status != 429 {
+            return Ok(response);
+        }
+
+        let headers = RateLimitHeaders::from_response(&response);
+        let url = response.url().path().to_string();
+        let body = response.text().await?;
+
+        match rate_limit_wait(status, &headers, &body, unix_now()) {
+            Some(wait) if retries < policy.max_retries => {
+                let wait = wait.min(policy.max_wait);
+                retries += 1;
+                println!(
+                    "[GitHub] Rate limited on {}, retrying in {}s ({}/{})",
+                    url, wait.as_secs(), retries, policy.max_retries
+                );
+                tokio::time::sleep(wait).await;
+            }
+            Some(wait) => return Err(GitHubError::RateLimited { retry_after: Some(wait), message: body }),
+            None => return Err(GitHubError::Other { status: Some(status), message: body }),
+        }
+    }
+}
+
+/// GET a GitHub URL with the token, honouring rate limits; non-success statuses become a `GitHubError`
+pub async fn github_get(client: &reqwest::Client, url: &str, token: &str) -> Result<reqwest::Response, GitHubError> {
+    github_get_with(client, url, token, rate_limit_policy()).await
+}
+
+async fn github_get_with(
+    client: &reqwest::Client,
+    url: &str,
+    token: &str,
+    policy: &RateLimitPolicy,
+) -> Result<reqwest::Response, GitHubError> {
+    let request = client
+        .get(url)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("User-Agent", "FastboardAI")
+        .header("Accept", "application/vnd.github.v3+json");
+    let response = send_with_backoff(request, policy).await?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if status.as_u16() == 404 {
+        return Err(GitHubError::NotFound { path: response.url().path().to_string() });
+    }
+    let message = response.text().await.unwrap_or_default();
+    Err(GitHubError::Other { status: Some(status.as_u16()), message })
 }
 
 pub async fn get_user_repos(
@@ -95,11 +339,7 @@ pub async fn get_user_repos(
     let client = reqwest::Client::new();
     let url = format!("{}/users/{}/repos?sort=updated&per_page=10", GITHUB_API, username);
 
-    let repos: Vec<GitHubRepo> = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "FastboardAI")
-        .send()
+    let repos: Vec<GitHubRepo> = github_get(&client, &url, token)
         .await?
         .json()
         .await?;
@@ -108,30 +348,60 @@ pub async fn get_user_repos(
     Ok(repos.into_iter().filter(|r| !r.fork).collect())
 }
 
+/// The author's most recent commits, up to `max_pages` pages of COMMITS_PER_PAGE, optionally only those since a time
 pub async fn get_user_commits(
     owner: &str,
     repo: &str,
     author: &str,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    max_pages: u32,
     token: &str,
-) -> Result<Vec<GitHubCommit>, Box<dyn std::error::Error>> {
+) -> Result<Vec<GitHubCommit>, Box<dyn std::error::Error + Send + Sync>> {
     let client = reqwest::Client::new();
-    let url = format!(
-        "{}/repos/{}/{}/commits?author={}&per_page=20",
-        GITHUB_API, owner, repo, author
-    );
-
-    let commits: Vec<GitHubCommit> = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "FastboardAI")
-        .send()
-        .await?
-        .json()
-        .await?;
+    let mut commits = Vec::new();
+
+    for page in 1..=max_pages {
+        let url = user_commits_url(owner, repo, author, since, page)?;
+        let response = match github_get(&client, url.as_str(), token).await {
+            Ok(response) => response,
+            // GitHub answers 409 for an empty repository
+            Err(GitHubError::Other { status: Some(409), .. }) => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let batch: Vec<GitHubCommit> = response.json().await?;
+        let last_page = batch.len() < COMMITS_PER_PAGE;
+        commits.extend(batch);
+        if last_page {
+            break;
+        }
+    }
 
     Ok(commits)
 }
 
+/// Most commits GitHub returns per page of `/commits`
+pub const COMMITS_PER_PAGE: usize = 100;
+
+/// One page of a repo's commits by `author`, optionally only those since a date
+fn user_commits_url(
+    owner: &str,
+    repo: &str,
+    author: &str,
+    since: Option<chrono::DateTime<chrono::Utc>>,
+    page: u32,
+) -> Result<reqwest::Url, Box<dyn std::error::Error + Send + Sync>> {
+    let mut params = vec![
+        ("author", author.to_string()),
+        ("per_page", COMMITS_PER_PAGE.to_string()),
+        ("page", page.to_string()),
+    ];
+    if let Some(since) = since {
+        params.push(("since", since.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)));
+    }
+    Ok(reqwest::Url::parse_with_params(&format!("{}/repos/{}/{}/commits", GITHUB_API, owner, repo), &params)?)
+}
+
 pub async fn get_commit_detail(
     owner: &str,
     repo: &str,
@@ -141,11 +411,7 @@ pub async fn get_commit_detail(
     let client = reqwest::Client::new();
     let url = format!("{}/repos/{}/{}/commits/{}", GITHUB_API, owner, repo, sha);
 
-    let detail: CommitDetail = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "FastboardAI")
-        .send()
+    let detail: CommitDetail = github_get(&client, &url, token)
         .await?
         .json()
         .await?;
@@ -166,11 +432,7 @@ pub async fn get_repo_tree(
         GITHUB_API, owner, repo
     );
 
-    let tree: RepoTree = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "FastboardAI")
-        .send()
+    let tree: RepoTree = github_get(&client, &url, token)
         .await?
         .json()
         .await?;
@@ -179,6 +441,7 @@ pub async fn get_repo_tree(
 }
 
 /// Get raw file content from repository
+/// Binary content comes back empty so callers skip it like any other empty file
 pub async fn get_file_content(
     owner: &str,
     repo: &str,
@@ -193,28 +456,73 @@ pub async fn get_file_content(
         GITHUB_API, owner, repo, path
     );
 
-    let content: FileContent = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "FastboardAI")
-        .header("Accept", "application/vnd.github.v3+json")
-        .send()
+    let content: FileContent = github_get(&client, &url, token)
         .await?
         .json()
         .await?;
 
-    // GitHub returns base64 encoded content
-    if let Some(encoded) = content.content {
-        // Remove newlines from base64 string
+    let inline = content.content.filter(|c| !c.trim().is_empty() && content.encoding.as_deref() != Some("none"));
+
+    let bytes = if let Some(encoded) = inline {
+        // GitHub returns base64 encoded content; remove newlines from base64 string
         let cleaned = encoded.replace('\n', "");
         use base64::{Engine as _, engine::general_purpose::STANDARD};
-        let decoded = STANDARD.decode(&cleaned)?;
-        Ok(String::from_utf8_lossy(&decoded).to_string())
+        STANDARD.decode(&cleaned)?
+    } else if let Some(download_url) = content.download_url {
+        // Too large for the contents API, fetch the raw file instead
+        github_get(&client, &download_url, token)
+            .await?
+            .bytes()
+            .await?
+            .to_vec()
     } else {
-        Ok(String::new())
+        return Ok(String::new());
+    };
+
+    match decode_text(&bytes) {
+        Some(text) => Ok(text),
+        None => {
+            println!("[GitHub] Skipping binary file {}/{}: {}", owner, repo, path);
+            Ok(String::new())
+        }
     }
 }
 
+/// Whether the leading bytes look like binary data (NUL bytes or too many control characters)
+fn looks_binary(bytes: &[u8]) -> bool {
+    let sample = &bytes[..bytes.len().min(BINARY_SNIFF_LEN)];
+    if sample.is_empty() {
+        return false;
+    }
+    if sample.contains(&0) {
+        return true;
+    }
+
+    let control = sample
+        .iter()
+        .filter(|&&b| (b < 0x20 && !matches!(b, b'\t' | b'\n' | b'\r' | 0x0c | 0x1b)) || b == 0x7f)
+        .count();
+
+    control as f32 / sample.len() as f32 > MAX_NON_TEXT_RATIO
+}
+
+/// Decode file bytes as text, or None if they look binary
+/// Stray invalid UTF-8 (e.g. a Latin-1 comment) is replaced; mostly-invalid content is rejected
+pub(crate) fn decode_text(bytes: &[u8]) -> Option<String> {
+    if looks_binary(bytes) {
+        return None;
+    }
+
+    let text = String::from_utf8_lossy(bytes);
+    let total = text.chars().count();
+    let replaced = text.chars().filter(|&c| c == char::REPLACEMENT_CHARACTER).count();
+    if total > 0 && replaced as f32 / total as f32 > MAX_NON_TEXT_RATIO {
+        return None;
+    }
+
+    Some(text.into_owned())
+}
+
 /// Get user profile data
 pub async fn get_user_profile(
     username: &str,
@@ -223,11 +531,7 @@ pub async fn get_user_profile(
     let client = reqwest::Client::new();
     let url = format!("{}/users/{}", GITHUB_API, username);
 
-    let user: GitHubUser = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "FastboardAI")
-        .send()
+    let user: GitHubUser = github_get(&client, &url, token)
         .await?
         .json()
         .await?;
@@ -235,6 +539,37 @@ pub async fn get_user_profile(
     Ok(user)
 }
 
+// User search results; only the login is used, profiles are fetched separately
+#[derive(Deserialize)]
+struct UserSearchResponse {
+    items: Vec<UserSearchItem>,
+}
+
+#[derive(Deserialize)]
+struct UserSearchItem {
+    login: String,
+}
+
+/// Logins matching a user search query (`q` syntax, e.g. "language:rust location:sydney"), most followed first
+pub async fn search_users(
+    query: &str,
+    per_page: u32,
+    token: &str,
+) -> Result<Vec<String>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+    let url = reqwest::Url::parse_with_params(
+        &format!("{}/search/users", GITHUB_API),
+        &[("q", query), ("sort", "followers"), ("per_page", &per_page.min(100).to_string())],
+    )?;
+
+    let results: UserSearchResponse = github_get(&client, url.as_str(), token)
+        .await?
+        .json()
+        .await?;
+
+    Ok(results.items.into_iter().map(|item| item.login).collect())
+}
+
 /// Get all user repos with full metadata (including forks)
 pub async fn get_user_repos_full(
     username: &str,
@@ -243,11 +578,7 @@ pub async fn get_user_repos_full(
     let client = reqwest::Client::new();
     let url = format!("{}/users/{}/repos?sort=updated&per_page=30", GITHUB_API, username);
 
-    let repos: Vec<GitHubRepoFull> = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "FastboardAI")
-        .send()
+    let repos: Vec<GitHubRepoFull> = github_get(&client, &url, token)
         .await?
         .json()
         .await?;
@@ -268,12 +599,12 @@ pub async fn get_repo_commit_count(
         GITHUB_API, owner, repo, author
     );
 
-    let response = client
-        .get(&url)
-        .header("Authorization", format!("Bearer {}", token))
-        .header("User-Agent", "FastboardAI")
-        .send()
-        .await?;
+    let response = match github_get(&client, &url, token).await {
+        Ok(response) => response,
+        // GitHub answers 409 for an empty repository
+        Err(GitHubError::Other { status: Some(409), .. }) => return Ok(0),
+        Err(e) => return Err(e.into()),
+    };
 
     let commits: Vec<GitHubCommit> = response.json().await.unwrap_or_default();
     Ok(commits.len() as u32)
@@ -295,12 +626,7 @@ pub async fn get_all_user_repos(
             GITHUB_API, username, page
         );
 
-        let response = client
-            .get(&url)
-            .header("Authorization", format!("Bearer {}", token))
-            .header("User-Agent", "FastboardAI")
-            .send()
-            .await?;
+        let response = github_get(&client, &url, token).await?;
 
         let repos: Vec<GitHubRepoFull> = response.json().await.unwrap_or_default();
 
@@ -341,3 +667,147 @@ pub async fn get_readme_content(
 
     Ok(None)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_blob_skipped() {
+        // PNG header followed by compressed-looking bytes
+        let mut png = vec![0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a, 0x00, 0x00, 0x00, 0x0d];
+        png.extend((0..=255u8).cycle().take(2048));
+        assert!(looks_binary(&png));
+        assert_eq!(decode_text(&png), None);
+
+        // Random high bytes without NULs are still rejected (mostly invalid UTF-8)
+        let noise: Vec<u8> = (0..1024u32).map(|i| 0x80 | (i * 37 % 0x7f) as u8).collect();
+        assert_eq!(decode_text(&noise), None);
+    }
+
+    #[test]
+    fn test_text_kept_with_stray_invalid_bytes() {
+        let source = "fn main() {\n\tprintln!(\"hi\");\n}\n";
+        assert_eq!(decode_text(source.as_bytes()).as_deref(), Some(source));
+
+        // One Latin-1 byte in an otherwise UTF-8 file is replaced, not rejected
+        let mut latin1 = b"// Author: Jos".to_vec();
+        latin1.push(0xe9);
+        latin1.extend_from_slice(b"\nlet x = 1;\n");
+        let decoded = decode_text(&latin1).unwrap();
+        assert!(decoded.contains("let x = 1;"));
+        assert!(decoded.contains(char::REPLACEMENT_CHARACTER));
+    }
+
+    /// Serve each canned response to one connection in turn, counting the requests received
+    fn mock_github(responses: Vec<String>) -> (String, std::sync::Arc<std::sync::atomic::AtomicUsize>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/users/octocat", listener.local_addr().unwrap());
+        let hits = std::sync::Arc::new(std::sync::atomic::AtomicUsize::new(0));
+        let counter = hits.clone();
+
+        std::thread::spawn(move || {
+            for response in responses {
+                let (mut stream, _) = listener.accept().unwrap();
+                let mut buf = [0u8; 4096];
+                let _ = stream.read(&mut buf);
+                counter.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                stream.write_all(response.as_bytes()).unwrap();
+            }
+        });
+
+        (url, hits)
+    }
+
+    fn http_response(status: &str, extra_headers: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n{}\r\n{}",
+            status, body.len(), extra_headers, body
+        )
+    }
+
+    const SECONDARY_LIMIT_BODY: &str = r#"{"message":"You have exceeded a secondary rate limit. Please wait a few minutes before you try again.","documentation_url":"https://docs.github.com/rest/overview/rate-limits-for-the-rest-api"}"#;
+
+    #[rocket::async_test]
+    async fn test_rate_limit_retried() {
+        let (url, hits) = mock_github(vec![
+            http_response("403 Forbidden", "Retry-After: 0\r\n", SECONDARY_LIMIT_BODY),
+            http_response("200 OK", "", r#"{"login":"octocat"}"#),
+        ]);
+
+        let response = github_get_with(&reqwest::Client::new(), &url, "token", &RateLimitPolicy::default())
+            .await
+            .unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(response.text().await.unwrap(), r#"{"login":"octocat"}"#);
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        // An exhausted primary limit waits for its (already passed) reset
+        let (url, hits) = mock_github(vec![
+            http_response("403 Forbidden", "X-RateLimit-Remaining: 0\r\nX-RateLimit-Reset: 0\r\n", r#"{"message":"API rate limit exceeded for user ID 1."}"#),
+            http_response("200 OK", "", r#"{"login":"octocat"}"#),
+        ]);
+        let response = github_get_with(&reqwest::Client::new(), &url, "token", &RateLimitPolicy::default()).await.unwrap();
+        assert_eq!(response.status().as_u16(), 200);
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[rocket::async_test]
+    async fn test_rate_limit_gives_up_after_retries() {
+        let limited = || http_response("429 Too Many Requests", "Retry-After: 0\r\n", r#"{"message":"slow down"}"#);
+        let (url, hits) = mock_github(vec![limited(), limited()]);
+        let policy = RateLimitPolicy { max_retries: 1, max_wait: Duration::from_secs(1) };
+
+        let err = github_get_with(&reqwest::Client::new(), &url, "token", &policy).await.unwrap_err();
+        assert!(matches!(err, GitHubError::RateLimited { .. }));
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 2);
+    }
+
+    #[rocket::async_test]
+    async fn test_errors_are_typed() {
+        let (url, hits) = mock_github(vec![
+            http_response("403 Forbidden", "", r#"{"message":"Resource not accessible by integration"}"#),
+            http_response("404 Not Found", "", r#"{"message":"Not Found"}"#),
+        ]);
+
+        // A permission error is not retried
+        let err = github_get_with(&reqwest::Client::new(), &url, "token", &RateLimitPolicy::default()).await.unwrap_err();
+        assert!(matches!(err, GitHubError::Other { status: Some(403), .. }));
+        assert_eq!(hits.load(std::sync::atomic::Ordering::SeqCst), 1);
+
+        let err = github_get_with(&reqwest::Client::new(), &url, "token", &RateLimitPolicy::default()).await.unwrap_err();
+        assert!(matches!(&err, GitHubError::NotFound { path } if path == "/users/octocat"));
+
+        // Callers get the error boxed and can still tell the cases apart
+        let boxed: Box<dyn std::error::Error + Send + Sync> = err.into();
+        assert!(matches!(boxed.downcast_ref::<GitHubError>(), Some(GitHubError::NotFound { .. })));
+    }
+
+    #[test]
+    fn test_user_commits_url_encodes_params() {
+        let since = chrono::DateTime::parse_from_rfc3339("2024-01-02T03:04:05Z").unwrap().with_timezone(&chrono::Utc);
+        let url = user_commits_url("octo", "repo", "a&b=c d", Some(since), 2).unwrap();
+        assert_eq!(url.path(), "/repos/octo/repo/commits");
+
+        let params: Vec<(String, String)> = url.query_pairs().into_owned().collect();
+        assert_eq!(params[0], ("author".to_string(), "a&b=c d".to_string()));
+        assert_eq!(params[2], ("page".to_string(), "2".to_string()));
+        assert_eq!(params[3], ("since".to_string(), "2024-01-02T03:04:05Z".to_string()));
+        assert!(!url.query().unwrap().contains("a&b"));
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        let headers = |remaining, reset, retry_after| RateLimitHeaders { remaining, reset, retry_after };
+        let now = 1_700_000_000;
+
+        // Retry-After drives the wait, then the primary limit reset; a secondary limit without either waits a minute
+        assert_eq!(rate_limit_wait(403, &headers(None, None, Some(7)), SECONDARY_LIMIT_BODY, now), Some(Duration::from_secs(7)));
+        assert_eq!(rate_limit_wait(403, &headers(Some(0), Some(now + 42), None), "", now), Some(Duration::from_secs(42)));
+        assert_eq!(rate_limit_wait(429, &headers(None, None, None), "abuse detection mechanism", now), Some(RATE_LIMIT_FALLBACK_WAIT));
+        assert_eq!(rate_limit_wait(403, &headers(Some(12), None, None), "Resource not accessible", now), None);
+        assert_eq!(rate_limit_wait(404, &headers(None, None, Some(7)), SECONDARY_LIMIT_BODY, now), None);
+    }
+}
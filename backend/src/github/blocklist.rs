@@ -0,0 +1,99 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::sync::OnceLock;
+
+/// GitHub accounts (users or organizations) that must never be analyzed
+#[derive(Debug, Default)]
+pub struct AnalysisBlocklist {
+    accounts: HashSet<String>,
+}
+
+impl AnalysisBlocklist {
+    /// Parse a comma-separated list of logins; `@` prefixes, blanks and case are ignored
+    pub fn parse(value: &str) -> Self {
+        let accounts = value
+            .split(',')
+            .map(|login| login.trim().trim_start_matches('@').to_lowercase())
+            .filter(|login| !login.is_empty())
+            .collect();
+        Self { accounts }
+    }
+
+    /// Build from ANALYSIS_BLOCKLIST
+    pub fn from_env() -> Self {
+        let blocklist = std::env::var("ANALYSIS_BLOCKLIST")
+            .map(|value| Self::parse(&value))
+            .unwrap_or_default();
+        if !blocklist.accounts.is_empty() {
+            println!("[Blocklist] {} GitHub account(s) excluded from analysis", blocklist.accounts.len());
+        }
+        blocklist
+    }
+
+    pub fn is_blocked(&self, username: &str) -> bool {
+        self.accounts.contains(&username.trim().to_lowercase())
+    }
+}
+
+/// The process-wide analysis blocklist (configured once from env)
+pub fn analysis_blocklist() -> &'static AnalysisBlocklist {
+    static BLOCKLIST: OnceLock<AnalysisBlocklist> = OnceLock::new();
+    BLOCKLIST.get_or_init(AnalysisBlocklist::from_env)
+}
+
+/// An analysis refused because the account is on the blocklist
+#[derive(Debug)]
+pub struct AnalysisNotPermitted;
+
+impl std::fmt::Display for AnalysisNotPermitted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Analysis not permitted for this account")
+    }
+}
+
+impl std::error::Error for AnalysisNotPermitted {}
+
+/// Run `analyze` unless the account is blocklisted, in which case it fails before anything is fetched
+pub async fn unless_blocked<T, F, Fut>(
+    blocklist: &AnalysisBlocklist,
+    username: &str,
+    analyze: F,
+) -> Result<T, Box<dyn std::error::Error + Send + Sync>>
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<T, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    if blocklist.is_blocked(username) {
+        println!("[Blocklist] Refusing to analyze {}", username);
+        return Err(Box::new(AnalysisNotPermitted));
+    }
+    analyze().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[rocket::async_test]
+    async fn test_blocked_account_short_circuits_before_fetch() {
+        let blocklist = AnalysisBlocklist::parse(" @Acme-Internal , private-bot,,");
+        assert!(blocklist.is_blocked("acme-internal"));
+        assert!(blocklist.is_blocked("Private-Bot"));
+        assert!(!blocklist.is_blocked("octocat"));
+
+        let fetches = AtomicUsize::new(0);
+        let fetch = || async {
+            fetches.fetch_add(1, Ordering::SeqCst);
+            Ok::<_, Box<dyn std::error::Error + Send + Sync>>("stats")
+        };
+
+        let err = unless_blocked(&blocklist, "ACME-internal", fetch).await.unwrap_err();
+        assert!(err.is::<AnalysisNotPermitted>());
+        assert_eq!(err.to_string(), "Analysis not permitted for this account");
+        assert_eq!(fetches.load(Ordering::SeqCst), 0);
+
+        assert_eq!(unless_blocked(&blocklist, "octocat", fetch).await.unwrap(), "stats");
+        assert_eq!(fetches.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,47 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use sqlx::{PgConnection, Row};
+
+/// Whether pgvector and the code_embeddings table are present
+/// Starts false so nothing touches embeddings until a check has confirmed them
+static EMBEDDINGS_AVAILABLE: AtomicBool = AtomicBool::new(false);
+
+/// Detect pgvector and the code_embeddings table, recording the result
+/// Query failures count as unavailable
+pub async fn check_embedding_support(conn: &mut PgConnection) -> bool {
+    let row = sqlx::query(
+        "SELECT EXISTS (SELECT 1 FROM pg_extension WHERE extname = 'vector') AS has_vector,
+                to_regclass('public.code_embeddings') IS NOT NULL AS has_table"
+    )
+    .fetch_one(conn)
+    .await;
+
+    let available = match row {
+        Ok(row) => {
+            let has_vector: bool = row.get("has_vector");
+            let has_table: bool = row.get("has_table");
+            if !has_vector {
+                println!("[CAPABILITIES] Warning: pgvector extension not installed, embeddings disabled");
+            } else if !has_table {
+                println!("[CAPABILITIES] Warning: code_embeddings table missing, embeddings disabled");
+            }
+            has_vector && has_table
+        }
+        Err(e) => {
+            println!("[CAPABILITIES] Warning: embedding capability check failed ({}), embeddings disabled", e);
+            false
+        }
+    };
+
+    set_embeddings_available(available);
+    available
+}
+
+pub fn set_embeddings_available(available: bool) {
+    EMBEDDINGS_AVAILABLE.store(available, Ordering::Relaxed);
+}
+
+/// Whether embedding-backed analysis can run (as of the last check)
+pub fn embeddings_available() -> bool {
+    EMBEDDINGS_AVAILABLE.load(Ordering::Relaxed)
+}
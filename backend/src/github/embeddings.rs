@@ -1,13 +1,131 @@
+use std::collections::HashSet;
+use std::future::Future;
+use std::ops::Range;
+use std::sync::OnceLock;
+
+use futures::StreamExt;
+
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use sqlx::PgConnection;
+use syn::spanned::Spanned;
 use uuid::Uuid;
 
 const GEMINI_EMBEDDING_MODEL: &str = "text-embedding-004";
 const GEMINI_API_URL: &str = "https://generativelanguage.googleapis.com/v1beta/models";
+const OPENAI_EMBEDDING_MODEL: &str = "text-embedding-3-small";
+const OPENAI_EMBEDDINGS_URL: &str = "https://api.openai.com/v1/embeddings";
+/// Most texts sent in one embedding request (Gemini's batchEmbedContents limit)
+const MAX_EMBEDDING_BATCH: usize = 100;
+/// Single-text requests in flight when a batch falls back; batch failures are usually rate limits
+const FALLBACK_CONCURRENCY: usize = 5;
 const CHUNK_SIZE: usize = 300;
 const MAX_CHUNKS_PER_FILE: usize = 10;
 
+/// Which embedding API turns code into vectors
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EmbeddingProvider {
+    /// Gemini text-embedding-004 (GEMINI_API_KEY)
+    Gemini,
+    /// OpenAI text-embedding-3-small (OPENAI_API_KEY)
+    OpenAI,
+}
+
+impl EmbeddingProvider {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "gemini" => Some(Self::Gemini),
+            "openai" => Some(Self::OpenAI),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Gemini => "gemini",
+            Self::OpenAI => "openai",
+        }
+    }
+
+    /// Length of the vectors the provider's model returns
+    pub fn default_dimension(&self) -> usize {
+        match self {
+            Self::Gemini => 768,
+            Self::OpenAI => 1536,
+        }
+    }
+}
+
+/// Embedding provider and the vector length the code_embeddings column holds
+#[derive(Debug, Clone, PartialEq)]
+pub struct EmbeddingConfig {
+    pub provider: EmbeddingProvider,
+    pub dimension: usize,
+}
+
+impl Default for EmbeddingConfig {
+    fn default() -> Self {
+        Self {
+            provider: EmbeddingProvider::Gemini,
+            dimension: EmbeddingProvider::Gemini.default_dimension(),
+        }
+    }
+}
+
+impl EmbeddingConfig {
+    /// Build from EMBEDDING_PROVIDER and EMBEDDING_DIMENSION
+    /// The dimension defaults to the provider's; invalid values are logged and the defaults kept
+    pub fn from_env() -> Self {
+        Self::from_values(
+            std::env::var("EMBEDDING_PROVIDER").ok().as_deref(),
+            std::env::var("EMBEDDING_DIMENSION").ok().as_deref(),
+        )
+    }
+
+    fn from_values(provider: Option<&str>, dimension: Option<&str>) -> Self {
+        let mut config = Self::default();
+
+        if let Some(value) = provider {
+            match EmbeddingProvider::parse(value) {
+                Some(provider) => {
+                    config.provider = provider;
+                    config.dimension = provider.default_dimension();
+                }
+                None => println!("[Embeddings] Ignoring invalid EMBEDDING_PROVIDER {:?}", value),
+            }
+        }
+
+        if let Some(value) = dimension {
+            match value.trim().parse::<usize>() {
+                Ok(dimension) if dimension > 0 => config.dimension = dimension,
+                _ => println!("[Embeddings] Ignoring invalid EMBEDDING_DIMENSION {:?}", value),
+            }
+        }
+
+        config
+    }
+}
+
+/// The process-wide embedding configuration (configured once from env)
+pub fn embedding_config() -> &'static EmbeddingConfig {
+    static CONFIG: OnceLock<EmbeddingConfig> = OnceLock::new();
+    CONFIG.get_or_init(EmbeddingConfig::from_env)
+}
+
+/// An embedding that doesn't fit the code_embeddings vector column
+fn check_dimension(embedding: &[f32], expected: usize) -> Result<(), String> {
+    if embedding.len() == expected {
+        Ok(())
+    } else {
+        Err(format!(
+            "Embedding has {} dimensions, code_embeddings expects {}",
+            embedding.len(),
+            expected
+        ))
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CodeChunk {
     pub repo_name: String,
@@ -44,29 +162,108 @@ struct EmbeddingValue {
     values: Vec<f32>,
 }
 
-/// Generate embedding for a text using Gemini text-embedding-004
-pub async fn generate_embedding(
-    text: &str,
-) -> Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync>> {
-    let api_key = std::env::var("GEMINI_API_KEY")?;
-    let client = Client::new();
+#[derive(Serialize)]
+struct OpenAIEmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
 
-    let url = format!(
-        "{}/{}:embedContent?key={}",
-        GEMINI_API_URL, GEMINI_EMBEDDING_MODEL, api_key
-    );
+#[derive(Deserialize)]
+struct OpenAIEmbeddingResponse {
+    #[serde(default)]
+    data: Vec<OpenAIEmbedding>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIEmbedding {
+    index: usize,
+    embedding: Vec<f32>,
+}
+
+#[derive(Serialize)]
+struct BatchEmbeddingRequest {
+    requests: Vec<EmbeddingRequest>,
+}
 
-    let request = EmbeddingRequest {
+#[derive(Deserialize)]
+struct BatchEmbeddingResponse {
+    #[serde(default)]
+    embeddings: Vec<EmbeddingValue>,
+}
+
+fn embedding_request(text: &str) -> EmbeddingRequest {
+    EmbeddingRequest {
         model: format!("models/{}", GEMINI_EMBEDDING_MODEL),
         content: ContentPart {
             parts: vec![TextPart {
                 text: text.to_string(),
             }],
         },
+    }
+}
+
+/// Generate embedding for a text with the configured provider
+pub async fn generate_embedding(
+    text: &str,
+) -> Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = Client::new();
+
+    match embedding_config().provider {
+        EmbeddingProvider::Gemini => {
+            let api_key = std::env::var("GEMINI_API_KEY")?;
+            let url = format!(
+                "{}/{}:embedContent?key={}",
+                GEMINI_API_URL, GEMINI_EMBEDDING_MODEL, api_key
+            );
+            gemini_embed_at(&client, &url, text).await
+        }
+        EmbeddingProvider::OpenAI => {
+            let api_key = std::env::var("OPENAI_API_KEY")?;
+            let mut embeddings = openai_embed_at(&client, OPENAI_EMBEDDINGS_URL, &api_key, &[text.to_string()]).await?;
+            embeddings.pop().ok_or_else(|| "No embedding returned".into())
+        }
+    }
+}
+
+/// Generate embeddings for several texts with the configured provider, in the order given
+/// Texts are sent MAX_EMBEDDING_BATCH at a time
+pub async fn generate_embeddings_batch(
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = Client::new();
+    let provider = embedding_config().provider;
+    let api_key = match provider {
+        EmbeddingProvider::Gemini => std::env::var("GEMINI_API_KEY")?,
+        EmbeddingProvider::OpenAI => std::env::var("OPENAI_API_KEY")?,
     };
 
+    let mut embeddings = Vec::with_capacity(texts.len());
+    for batch in texts.chunks(MAX_EMBEDDING_BATCH) {
+        let batch_embeddings = match provider {
+            EmbeddingProvider::Gemini => {
+                let url = format!(
+                    "{}/{}:batchEmbedContents?key={}",
+                    GEMINI_API_URL, GEMINI_EMBEDDING_MODEL, api_key
+                );
+                gemini_batch_embed_at(&client, &url, batch).await?
+            }
+            EmbeddingProvider::OpenAI => openai_embed_at(&client, OPENAI_EMBEDDINGS_URL, &api_key, batch).await?,
+        };
+        embeddings.extend(batch_embeddings);
+    }
+    Ok(embeddings)
+}
+
+/// One Gemini embedContent request
+async fn gemini_embed_at(
+    client: &Client,
+    url: &str,
+    text: &str,
+) -> Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync>> {
+    let request = embedding_request(text);
+
     let response: EmbeddingResponse = client
-        .post(&url)
+        .post(url)
         .json(&request)
         .send()
         .await?
@@ -79,7 +276,99 @@ pub async fn generate_embedding(
         .ok_or_else(|| "No embedding returned".into())
 }
 
-/// Split code into chunks of approximately CHUNK_SIZE lines
+/// One OpenAI /v1/embeddings request; results come back tagged with their input index
+async fn openai_embed_at(
+    client: &Client,
+    url: &str,
+    api_key: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+    let request = OpenAIEmbeddingRequest {
+        model: OPENAI_EMBEDDING_MODEL,
+        input: texts,
+    };
+
+    let mut response: OpenAIEmbeddingResponse = client
+        .post(url)
+        .bearer_auth(api_key)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if response.data.len() != texts.len() {
+        return Err(format!(
+            "Expected {} embeddings, got {}",
+            texts.len(),
+            response.data.len()
+        )
+        .into());
+    }
+
+    response.data.sort_by_key(|e| e.index);
+    Ok(response.data.into_iter().map(|e| e.embedding).collect())
+}
+
+/// One Gemini batchEmbedContents request; fails unless there is an embedding for every text
+async fn gemini_batch_embed_at(
+    client: &Client,
+    url: &str,
+    texts: &[String],
+) -> Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>> {
+    let request = BatchEmbeddingRequest {
+        requests: texts.iter().map(|text| embedding_request(text)).collect(),
+    };
+
+    let response: BatchEmbeddingResponse = client
+        .post(url)
+        .json(&request)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?;
+
+    if response.embeddings.len() != texts.len() {
+        return Err(format!(
+            "Expected {} embeddings, got {}",
+            texts.len(),
+            response.embeddings.len()
+        )
+        .into());
+    }
+
+    Ok(response.embeddings.into_iter().map(|e| e.values).collect())
+}
+
+/// Embed `texts` with one batch call, falling back to a call per text (FALLBACK_CONCURRENCY at a time)
+/// if the batch fails, so a bad text only loses its own embedding
+async fn embed_with_fallback<B, BFut, S, SFut>(
+    texts: Vec<String>,
+    embed_batch: B,
+    embed_one: S,
+) -> Vec<Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync>>>
+where
+    B: FnOnce(Vec<String>) -> BFut,
+    BFut: Future<Output = Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>>>,
+    S: Fn(String) -> SFut,
+    SFut: Future<Output = Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    match embed_batch(texts.clone()).await {
+        Ok(embeddings) => embeddings.into_iter().map(Ok).collect(),
+        Err(e) => {
+            println!("[Embeddings] Batch of {} failed ({}), embedding one at a time", texts.len(), e);
+            futures::stream::iter(texts)
+                .map(embed_one)
+                .buffered(FALLBACK_CONCURRENCY)
+                .collect()
+                .await
+        }
+    }
+}
+
+/// Split code into chunks along `chunk_code_by_function`, dropping near-empty ones
 pub fn chunk_code(
     content: &str,
     repo_name: &str,
@@ -87,40 +376,112 @@ pub fn chunk_code(
     language: Option<&str>,
 ) -> Vec<CodeChunk> {
     let lines: Vec<&str> = content.lines().collect();
-    let mut chunks = Vec::new();
 
-    if lines.is_empty() {
-        return chunks;
+    chunk_code_by_function(content, language)
+        .into_iter()
+        .filter_map(|range| {
+            let chunk_content = lines[range.clone()].join("\n");
+            (chunk_content.trim().len() > 50).then(|| CodeChunk {
+                repo_name: repo_name.to_string(),
+                file_path: file_path.to_string(),
+                line_start: (range.start + 1) as i32,
+                line_end: range.end as i32,
+                language: language.map(|s| s.to_string()),
+                content: chunk_content,
+            })
+        })
+        .take(MAX_CHUNKS_PER_FILE)
+        .collect()
+}
+
+/// Line ranges (0-based, end exclusive) to chunk a file on
+/// Rust and Python files are split where top-level functions, impls and classes start, so a chunk
+/// doesn't mix unrelated definitions; other languages (and Rust that doesn't parse) use CHUNK_SIZE-line windows.
+/// A file with more definitions than MAX_CHUNKS_PER_FILE has adjacent ones packed together (up to
+/// CHUNK_SIZE lines) so none is dropped. No range is longer than CHUNK_SIZE lines, so a huge function is still split
+pub fn chunk_code_by_function(content: &str, language: Option<&str>) -> Vec<Range<usize>> {
+    let ranges = definition_ranges(content, language);
+    if ranges.len() > MAX_CHUNKS_PER_FILE {
+        merge_adjacent(ranges)
+    } else {
+        ranges
     }
+}
 
-    let mut i = 0;
-    let mut chunk_count = 0;
+/// One range per top-level definition (windowed when longer than CHUNK_SIZE)
+fn definition_ranges(content: &str, language: Option<&str>) -> Vec<Range<usize>> {
+    let lines: Vec<&str> = content.lines().collect();
+    let starts = match language {
+        Some("Rust") => rust_definition_starts(content).unwrap_or_default(),
+        Some("Python") => python_definition_starts(&lines),
+        _ => Vec::new(),
+    };
 
-    while i < lines.len() && chunk_count < MAX_CHUNKS_PER_FILE {
-        let start = i;
-        let end = (i + CHUNK_SIZE).min(lines.len());
+    let mut bounds: Vec<usize> = std::iter::once(0)
+        .chain(starts.into_iter().filter(|&line| line < lines.len()))
+        .chain(std::iter::once(lines.len()))
+        .collect();
+    bounds.sort_unstable();
+    bounds.dedup();
 
-        // Try to find a natural break point (empty line, function end, etc.)
-        let actual_end = find_natural_break(&lines, start, end);
+    bounds
+        .windows(2)
+        .flat_map(|pair| line_windows(&lines, pair[0]..pair[1]))
+        .collect()
+}
 
-        let chunk_content: String = lines[start..actual_end].join("\n");
+/// Join consecutive ranges while the combined range stays within CHUNK_SIZE lines
+fn merge_adjacent(ranges: Vec<Range<usize>>) -> Vec<Range<usize>> {
+    let mut merged: Vec<Range<usize>> = Vec::new();
+    for range in ranges {
+        match merged.last_mut() {
+            Some(last) if last.end == range.start && range.end - last.start <= CHUNK_SIZE => last.end = range.end,
+            _ => merged.push(range),
+        }
+    }
+    merged
+}
 
-        if chunk_content.trim().len() > 50 {
-            chunks.push(CodeChunk {
-                repo_name: repo_name.to_string(),
-                file_path: file_path.to_string(),
-                line_start: (start + 1) as i32,
-                line_end: actual_end as i32,
-                language: language.map(|s| s.to_string()),
-                content: chunk_content,
-            });
-            chunk_count += 1;
+/// First line (0-based, including attributes and doc comments) of each top-level fn, impl, trait and mod
+fn rust_definition_starts(content: &str) -> Option<Vec<usize>> {
+    let file = syn::parse_file(content).ok()?;
+    let starts = file
+        .items
+        .iter()
+        .filter(|item| matches!(item, syn::Item::Fn(_) | syn::Item::Impl(_) | syn::Item::Trait(_) | syn::Item::Mod(_)))
+        .map(|item| item.span().start().line.saturating_sub(1))
+        .collect();
+    Some(starts)
+}
+
+/// First line (0-based, including decorators) of each unindented def, async def and class
+fn python_definition_starts(lines: &[&str]) -> Vec<usize> {
+    let mut starts = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        if ["def ", "async def ", "class "].iter().any(|keyword| line.starts_with(keyword)) {
+            let mut start = i;
+            while start > 0 && lines[start - 1].starts_with('@') {
+                start -= 1;
+            }
+            starts.push(start);
         }
+    }
+    starts
+}
+
+/// Split a range of lines into windows of at most CHUNK_SIZE, breaking oversized ones at natural break points
+fn line_windows(lines: &[&str], range: Range<usize>) -> Vec<Range<usize>> {
+    let mut windows = Vec::new();
+    let mut start = range.start;
 
-        i = actual_end;
+    while start < range.end {
+        let end = (start + CHUNK_SIZE).min(range.end);
+        let end = if end < range.end { find_natural_break(&lines[..range.end], start, end) } else { end };
+        windows.push(start..end);
+        start = end;
     }
 
-    chunks
+    windows
 }
 
 /// Find a natural break point near the target end
@@ -139,116 +500,228 @@ fn find_natural_break(lines: &[&str], _start: usize, target_end: usize) -> usize
     target_end
 }
 
+/// Outcome of storing a chunk set; re-runs report already-stored chunks as skipped
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StoreResult {
+    pub inserted: usize,
+    pub skipped: usize,
+    /// Chunks not embedded because an identical chunk (by content hash) is stored or kept
+    pub deduplicated: usize,
+}
+
+/// Identity of a stored chunk within an analysis (matches the table's unique key)
+type ChunkKey = (String, String, i32);
+
+fn chunk_key(chunk: &CodeChunk) -> ChunkKey {
+    (chunk.repo_name.clone(), chunk.file_path.clone(), chunk.line_start)
+}
+
+/// Hex sha256 of a chunk ignoring indentation, trailing whitespace, blank lines and line endings
+pub fn content_hash(content: &str) -> String {
+    let mut hasher = Sha256::new();
+    for line in content.lines().map(str::trim).filter(|l| !l.is_empty()) {
+        hasher.update(line.as_bytes());
+        hasher.update(b"\n");
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+/// Chunks already stored for an analysis, by position and by content
+#[derive(Debug, Default)]
+struct StoredChunks {
+    keys: HashSet<ChunkKey>,
+    hashes: HashSet<String>,
+}
+
+/// Chunks still to store, with counts of the ones left out
+#[derive(Debug)]
+struct NewChunks {
+    fresh: Vec<CodeChunk>,
+    /// Already stored at the same position (or repeated within the set)
+    skipped: usize,
+    /// Same content as a stored chunk or one kept earlier in the set
+    deduplicated: usize,
+}
+
+/// Split chunks into those still to store and counts of ones already stored or duplicated
+fn partition_new_chunks(chunks: Vec<CodeChunk>, existing: &StoredChunks) -> NewChunks {
+    let mut seen_keys: HashSet<ChunkKey> = HashSet::new();
+    let mut seen_hashes: HashSet<String> = HashSet::new();
+    let mut new = NewChunks { fresh: Vec::new(), skipped: 0, deduplicated: 0 };
+
+    for chunk in chunks {
+        let key = chunk_key(&chunk);
+        if existing.keys.contains(&key) || !seen_keys.insert(key) {
+            new.skipped += 1;
+            continue;
+        }
+        let hash = content_hash(&chunk.content);
+        if existing.hashes.contains(&hash) || !seen_hashes.insert(hash) {
+            new.deduplicated += 1;
+        } else {
+            new.fresh.push(chunk);
+        }
+    }
+
+    new
+}
+
+/// Keys and content hashes of chunks already stored for an analysis
+async fn existing_chunks(
+    conn: &mut PgConnection,
+    analysis_id: Uuid,
+) -> Result<StoredChunks, sqlx::Error> {
+    let rows = sqlx::query_as::<_, (String, String, i32, Option<String>)>(
+        "SELECT repo_name, file_path, line_start, content_hash FROM code_embeddings WHERE analysis_id = $1",
+    )
+    .bind(analysis_id)
+    .fetch_all(&mut *conn)
+    .await?;
+
+    let mut stored = StoredChunks::default();
+    for (repo_name, file_path, line_start, hash) in rows {
+        stored.keys.insert((repo_name, file_path, line_start));
+        stored.hashes.extend(hash);
+    }
+    Ok(stored)
+}
+
+/// Insert a single chunk; returns false if it was already stored
+async fn insert_chunk(
+    conn: &mut PgConnection,
+    analysis_id: Uuid,
+    username: &str,
+    chunk: &CodeChunk,
+    embedding: &[f32],
+) -> Result<bool, sqlx::Error> {
+    // Convert embedding to pgvector format string
+    let embedding_str = format!(
+        "[{}]",
+        embedding
+            .iter()
+            .map(|v| v.to_string())
+            .collect::<Vec<_>>()
+            .join(",")
+    );
+
+    let result = sqlx::query(
+        r#"
+        INSERT INTO code_embeddings
+            (analysis_id, username, repo_name, file_path, line_start, line_end, language, content, embedding, content_hash)
+        VALUES
+            ($1, $2, $3, $4, $5, $6, $7, $8, $9::vector, $10)
+        ON CONFLICT DO NOTHING
+        "#,
+    )
+    .bind(analysis_id)
+    .bind(username)
+    .bind(&chunk.repo_name)
+    .bind(&chunk.file_path)
+    .bind(chunk.line_start)
+    .bind(chunk.line_end)
+    .bind(&chunk.language)
+    .bind(&chunk.content)
+    .bind(&embedding_str)
+    .bind(content_hash(&chunk.content))
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(result.rows_affected() > 0)
+}
+
 /// Store code chunks with embeddings in the database
+/// Safe to re-run for the same analysis: stored chunks are skipped, not duplicated,
+/// and chunks with the same content as a stored one are not embedded again
 pub async fn store_chunks_with_embeddings(
     conn: &mut PgConnection,
     analysis_id: Uuid,
     username: &str,
     chunks: &[CodeChunk],
-) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-    let mut stored = 0;
+) -> Result<StoreResult, Box<dyn std::error::Error + Send + Sync>> {
+    let existing = existing_chunks(conn, analysis_id).await?;
+    let new = partition_new_chunks(chunks.to_vec(), &existing);
+    let mut result = StoreResult { inserted: 0, skipped: new.skipped, deduplicated: new.deduplicated };
 
-    for chunk in chunks {
+    for chunk in &new.fresh {
         // Generate embedding for this chunk
         let embedding = match generate_embedding(&chunk.content).await {
             Ok(e) => e,
             Err(_) => continue, // Skip chunks that fail to embed
         };
+        if let Err(e) = check_dimension(&embedding, embedding_config().dimension) {
+            println!("[Embeddings] Skipping {}:{}: {}", chunk.file_path, chunk.line_start, e);
+            continue;
+        }
 
-        // Convert embedding to pgvector format string
-        let embedding_str = format!(
-            "[{}]",
-            embedding
-                .iter()
-                .map(|v| v.to_string())
-                .collect::<Vec<_>>()
-                .join(",")
-        );
-
-        // Insert into database
-        let result = sqlx::query(
-            r#"
-            INSERT INTO code_embeddings
-                (analysis_id, username, repo_name, file_path, line_start, line_end, language, content, embedding)
-            VALUES
-                ($1, $2, $3, $4, $5, $6, $7, $8, $9::vector)
-            "#,
-        )
-        .bind(analysis_id)
-        .bind(username)
-        .bind(&chunk.repo_name)
-        .bind(&chunk.file_path)
-        .bind(chunk.line_start)
-        .bind(chunk.line_end)
-        .bind(&chunk.language)
-        .bind(&chunk.content)
-        .bind(&embedding_str)
-        .execute(&mut *conn)
-        .await;
-
-        if result.is_ok() {
-            stored += 1;
+        match insert_chunk(conn, analysis_id, username, chunk, &embedding).await {
+            Ok(true) => result.inserted += 1,
+            Ok(false) => result.skipped += 1,
+            Err(_) => {}
         }
     }
 
-    Ok(stored)
+    Ok(result)
 }
 
-/// Batch store chunks - generates embeddings in parallel batches
+/// Batch store chunks - one embedding request per batch of chunks
+/// Safe to re-run for the same analysis: stored chunks are skipped, not duplicated,
+/// and chunks with the same content as a stored one are not embedded again
 pub async fn store_chunks_batch(
     conn: &mut PgConnection,
     analysis_id: Uuid,
     username: &str,
     chunks: Vec<CodeChunk>,
-) -> Result<usize, Box<dyn std::error::Error + Send + Sync>> {
-    use futures::future::join_all;
-
-    let mut total_stored = 0;
-    let batch_size = 5; // Process 5 chunks at a time to avoid rate limits
+) -> Result<StoreResult, Box<dyn std::error::Error + Send + Sync>> {
+    store_chunks_batch_with(
+        conn,
+        analysis_id,
+        username,
+        chunks,
+        embedding_config().dimension,
+        |texts| async move { generate_embeddings_batch(&texts).await },
+        |text| async move { generate_embedding(&text).await },
+    )
+    .await
+}
 
-    for batch in chunks.chunks(batch_size) {
-        // Generate embeddings in parallel
-        let embedding_futures: Vec<_> = batch
-            .iter()
-            .map(|chunk| generate_embedding(&chunk.content))
-            .collect();
+/// `store_chunks_batch` with the batch and single-text embedders supplied
+/// Embeddings that aren't `dimension` long are skipped rather than sent to the vector column
+async fn store_chunks_batch_with<B, BFut, S, SFut>(
+    conn: &mut PgConnection,
+    analysis_id: Uuid,
+    username: &str,
+    chunks: Vec<CodeChunk>,
+    dimension: usize,
+    embed_batch: B,
+    embed_one: S,
+) -> Result<StoreResult, Box<dyn std::error::Error + Send + Sync>>
+where
+    B: Fn(Vec<String>) -> BFut,
+    BFut: Future<Output = Result<Vec<Vec<f32>>, Box<dyn std::error::Error + Send + Sync>>>,
+    S: Fn(String) -> SFut,
+    SFut: Future<Output = Result<Vec<f32>, Box<dyn std::error::Error + Send + Sync>>>,
+{
+    // Only embed chunks that aren't stored yet (resuming an interrupted run) and aren't copies
+    let existing = existing_chunks(conn, analysis_id).await?;
+    let new = partition_new_chunks(chunks, &existing);
+    let mut result = StoreResult { inserted: 0, skipped: new.skipped, deduplicated: new.deduplicated };
+    let batch_size = 20; // Chunks per embedding request
 
-        let embeddings = join_all(embedding_futures).await;
+    for batch in new.fresh.chunks(batch_size) {
+        let texts: Vec<String> = batch.iter().map(|chunk| chunk.content.clone()).collect();
+        let embeddings = embed_with_fallback(texts, &embed_batch, &embed_one).await;
 
         // Store successful embeddings
         for (chunk, embedding_result) in batch.iter().zip(embeddings.into_iter()) {
             if let Ok(embedding) = embedding_result {
-                let embedding_str = format!(
-                    "[{}]",
-                    embedding
-                        .iter()
-                        .map(|v| v.to_string())
-                        .collect::<Vec<_>>()
-                        .join(",")
-                );
-
-                let result = sqlx::query(
-                    r#"
-                    INSERT INTO code_embeddings
-                        (analysis_id, username, repo_name, file_path, line_start, line_end, language, content, embedding)
-                    VALUES
-                        ($1, $2, $3, $4, $5, $6, $7, $8, $9::vector)
-                    "#,
-                )
-                .bind(analysis_id)
-                .bind(username)
-                .bind(&chunk.repo_name)
-                .bind(&chunk.file_path)
-                .bind(chunk.line_start)
-                .bind(chunk.line_end)
-                .bind(&chunk.language)
-                .bind(&chunk.content)
-                .bind(&embedding_str)
-                .execute(&mut *conn)
-                .await;
-
-                if result.is_ok() {
-                    total_stored += 1;
+                if let Err(e) = check_dimension(&embedding, dimension) {
+                    println!("[Embeddings] Skipping {}:{}: {}", chunk.file_path, chunk.line_start, e);
+                    continue;
+                }
+                match insert_chunk(conn, analysis_id, username, chunk, &embedding).await {
+                    Ok(true) => result.inserted += 1,
+                    Ok(false) => result.skipped += 1,
+                    Err(_) => {}
                 }
             }
         }
@@ -257,7 +730,7 @@ pub async fn store_chunks_batch(
         tokio::time::sleep(tokio::time::Duration::from_millis(100)).await;
     }
 
-    Ok(total_stored)
+    Ok(result)
 }
 
 /// Delete all embeddings for an analysis session
@@ -299,3 +772,411 @@ pub fn detect_language(file_path: &str) -> Option<String> {
         _ => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(repo: &str, file: &str, line_start: i32) -> CodeChunk {
+        CodeChunk {
+            repo_name: repo.to_string(),
+            file_path: file.to_string(),
+            line_start,
+            line_end: line_start + 299,
+            language: Some("Rust".to_string()),
+            content: "fn main() {}".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_storing_same_chunks_twice_is_idempotent() {
+        let chunks = vec![
+            chunk("api", "src/main.rs", 1),
+            chunk("api", "src/main.rs", 301),
+            chunk("cli", "src/lib.rs", 1),
+        ]
+        .into_iter()
+        .enumerate()
+        .map(|(i, c)| CodeChunk { content: format!("fn part_{}() {{}}", i), ..c })
+        .collect::<Vec<_>>();
+
+        // First run: nothing stored yet, everything is new
+        let mut stored = StoredChunks::default();
+        let new = partition_new_chunks(chunks.clone(), &stored);
+        assert_eq!(new.fresh.len(), 3);
+        assert_eq!(new.skipped, 0);
+        stored.keys.extend(new.fresh.iter().map(chunk_key));
+        stored.hashes.extend(new.fresh.iter().map(|c| content_hash(&c.content)));
+
+        // Second run over the same set: no new rows
+        let new = partition_new_chunks(chunks, &stored);
+        assert!(new.fresh.is_empty());
+        assert_eq!(new.skipped, 3);
+        assert_eq!(new.deduplicated, 0);
+        assert_eq!(stored.keys.len(), 3);
+    }
+
+    #[test]
+    fn test_partition_dedups_within_a_set() {
+        let chunks = vec![
+            chunk("api", "src/main.rs", 1),
+            chunk("api", "src/main.rs", 1),
+            CodeChunk { content: "fn other() {}".to_string(), ..chunk("api", "src/main.rs", 301) },
+        ];
+
+        let new = partition_new_chunks(chunks, &StoredChunks::default());
+        assert_eq!(new.fresh.len(), 2);
+        assert_eq!(new.skipped, 1);
+    }
+
+    #[test]
+    fn test_identical_chunks_across_repos_embedded_once() {
+        let config = "[package]\nname = \"app\"\n\n  version = \"0.1.0\"  \n";
+        let chunks = vec![
+            CodeChunk { content: config.to_string(), ..chunk("api", "Cargo.toml", 1) },
+            CodeChunk { content: config.replace("  ", "").replace('\n', "\r\n"), ..chunk("cli", "Cargo.toml", 1) },
+        ];
+        assert_eq!(content_hash(&chunks[0].content), content_hash(&chunks[1].content));
+        assert_eq!(content_hash(config).len(), 64);
+
+        let new = partition_new_chunks(chunks.clone(), &StoredChunks::default());
+        assert_eq!(new.fresh.len(), 1);
+        assert_eq!(new.fresh[0].repo_name, "api");
+        assert_eq!(new.deduplicated, 1);
+
+        // A copy of content already stored for this analysis (e.g. by an interrupted run being resumed) isn't embedded either
+        let stored = StoredChunks { keys: HashSet::new(), hashes: HashSet::from([content_hash(config)]) };
+        let new = partition_new_chunks(vec![chunks[1].clone()], &stored);
+        assert!(new.fresh.is_empty());
+        assert_eq!(new.deduplicated, 1);
+    }
+
+    // Needs a Postgres with pgvector and schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_identical_chunks_stored_once() {
+        use sqlx::{Connection, PgConnection};
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+        let analysis_id = Uuid::new_v4();
+
+        let chunks = vec![chunk("api", "build.rs", 1), chunk("cli", "build.rs", 1)];
+        let result = store_chunks_batch_with(
+            &mut tx,
+            analysis_id,
+            "octocat",
+            chunks,
+            768,
+            |texts| async move { Ok(texts.iter().map(|_| vec![1.0; 768]).collect()) },
+            |_| async { Ok(vec![1.0; 768]) },
+        )
+        .await
+        .unwrap();
+        assert_eq!(result, StoreResult { inserted: 1, skipped: 0, deduplicated: 1 });
+
+        let (rows,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM code_embeddings WHERE analysis_id = $1")
+            .bind(analysis_id)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(rows, 1);
+
+        tx.rollback().await.unwrap();
+    }
+
+    // Needs a Postgres with pgvector and schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_storing_same_chunks_twice_adds_no_rows() {
+        use sqlx::{Connection, PgConnection};
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+        let analysis_id = Uuid::new_v4();
+
+        let chunks: Vec<CodeChunk> = [chunk("api", "src/main.rs", 1), chunk("api", "src/main.rs", 301), chunk("cli", "src/lib.rs", 1)]
+            .into_iter()
+            .enumerate()
+            .map(|(i, c)| CodeChunk { content: format!("fn part_{}() {{}}", i), ..c })
+            .collect();
+        let mut results = Vec::new();
+        for _ in 0..2 {
+            let result = store_chunks_batch_with(
+                &mut tx,
+                analysis_id,
+                "octocat",
+                chunks.clone(),
+                768,
+                |texts| async move { Ok(texts.iter().map(|_| vec![1.0; 768]).collect()) },
+                |_| async { Ok(vec![1.0; 768]) },
+            )
+            .await
+            .unwrap();
+            results.push(result);
+        }
+        assert_eq!(results[0], StoreResult { inserted: 3, skipped: 0, deduplicated: 0 });
+        assert_eq!(results[1], StoreResult { inserted: 0, skipped: 3, deduplicated: 0 });
+
+        // A chunk stored by a concurrent run after the existing rows were read is caught by the unique key
+        assert!(!insert_chunk(&mut tx, analysis_id, "octocat", &chunks[0], &[1.0; 768]).await.unwrap());
+
+        let (rows,): (i64,) = sqlx::query_as("SELECT COUNT(*) FROM code_embeddings WHERE analysis_id = $1")
+            .bind(analysis_id)
+            .fetch_one(&mut *tx)
+            .await
+            .unwrap();
+        assert_eq!(rows, 3);
+
+        tx.rollback().await.unwrap();
+    }
+
+    /// Answer one request with `body`, handing back the request body that was sent
+    fn mock_embedding_api(body: String) -> (String, std::sync::mpsc::Receiver<String>) {
+        use std::io::{Read, Write};
+
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/embeddings", listener.local_addr().unwrap());
+        let (sent, received) = std::sync::mpsc::channel();
+
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = Vec::new();
+            let mut buf = [0u8; 4096];
+            // Read headers and the full body before answering
+            loop {
+                let n = stream.read(&mut buf).unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_string();
+                if let Some((headers, body)) = text.split_once("\r\n\r\n") {
+                    let length = headers
+                        .lines()
+                        .find_map(|l| l.to_lowercase().strip_prefix("content-length:").map(|v| v.trim().parse::<usize>().unwrap()))
+                        .unwrap_or(0);
+                    if body.len() >= length {
+                        let _ = sent.send(body.to_string());
+                        break;
+                    }
+                }
+            }
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+
+        (url, received)
+    }
+
+    #[rocket::async_test]
+    async fn test_batch_embeddings_keep_text_order() {
+        let (url, request) = mock_embedding_api(
+            r#"{"embeddings":[{"values":[1.0]},{"values":[2.0]},{"values":[3.0]}]}"#.to_string(),
+        );
+        let texts = vec!["first".to_string(), "second".to_string(), "third".to_string()];
+
+        let embeddings = gemini_batch_embed_at(&Client::new(), &url, &texts).await.unwrap();
+        assert_eq!(embeddings, vec![vec![1.0], vec![2.0], vec![3.0]]);
+
+        let sent: serde_json::Value = serde_json::from_str(&request.recv().unwrap()).unwrap();
+        let sent_texts: Vec<&str> = sent["requests"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|r| r["content"]["parts"][0]["text"].as_str().unwrap())
+            .collect();
+        assert_eq!(sent_texts, vec!["first", "second", "third"]);
+        assert_eq!(sent["requests"][0]["model"], "models/text-embedding-004");
+    }
+
+    #[rocket::async_test]
+    async fn test_batch_of_one_and_short_responses() {
+        let (url, _) = mock_embedding_api(r#"{"embeddings":[{"values":[0.5,0.25]}]}"#.to_string());
+        let embeddings = gemini_batch_embed_at(&Client::new(), &url, &["only".to_string()]).await.unwrap();
+        assert_eq!(embeddings, vec![vec![0.5, 0.25]]);
+
+        // A response missing embeddings can't be matched up with the texts
+        let (url, _) = mock_embedding_api(r#"{"embeddings":[{"values":[1.0]}]}"#.to_string());
+        let err = gemini_batch_embed_at(&Client::new(), &url, &["a".to_string(), "b".to_string()]).await.unwrap_err();
+        assert_eq!(err.to_string(), "Expected 2 embeddings, got 1");
+    }
+
+    #[rocket::async_test]
+    async fn test_failed_batch_falls_back_to_single_embeddings() {
+        let texts = vec!["good".to_string(), "bad".to_string(), "fine".to_string()];
+        let single = |text: String| async move {
+            if text == "bad" {
+                Err("too long".into())
+            } else {
+                Ok(vec![text.len() as f32])
+            }
+        };
+
+        let results = embed_with_fallback(texts.clone(), |_| async { Err("batch rejected".into()) }, single).await;
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0].as_ref().unwrap(), &vec![4.0]);
+        assert!(results[1].is_err());
+        assert_eq!(results[2].as_ref().unwrap(), &vec![4.0]);
+
+        // A successful batch is used as is
+        let results = embed_with_fallback(texts, |t| async move { Ok(t.iter().map(|_| vec![9.0]).collect()) }, single).await;
+        assert!(results.iter().all(|r| r.as_ref().unwrap() == &vec![9.0]));
+    }
+
+    #[rocket::async_test]
+    async fn test_fallback_limits_concurrent_requests() {
+        use std::sync::atomic::{AtomicUsize, Ordering};
+
+        let in_flight = AtomicUsize::new(0);
+        let peak = AtomicUsize::new(0);
+        let single = |text: String| {
+            let (in_flight, peak) = (&in_flight, &peak);
+            async move {
+                let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(now, Ordering::SeqCst);
+                tokio::task::yield_now().await;
+                in_flight.fetch_sub(1, Ordering::SeqCst);
+                Ok(vec![text.len() as f32])
+            }
+        };
+
+        let texts: Vec<String> = (0..20).map(|i| "x".repeat(i + 1)).collect();
+        let results = embed_with_fallback(texts, |_| async { Err("429 Too Many Requests".into()) }, single).await;
+
+        // Results stay in text order
+        let lengths: Vec<f32> = results.into_iter().map(|r| r.unwrap()[0]).collect();
+        assert_eq!(lengths, (1..=20).map(|i| i as f32).collect::<Vec<_>>());
+        assert_eq!(peak.load(Ordering::SeqCst), FALLBACK_CONCURRENCY);
+    }
+
+    #[rocket::async_test]
+    async fn test_gemini_embedding_response_shape() {
+        let (url, request) = mock_embedding_api(r#"{"embedding":{"values":[0.1,0.2,0.3]}}"#.to_string());
+
+        let embedding = gemini_embed_at(&Client::new(), &url, "fn main() {}").await.unwrap();
+        assert_eq!(embedding, vec![0.1, 0.2, 0.3]);
+
+        let sent: serde_json::Value = serde_json::from_str(&request.recv().unwrap()).unwrap();
+        assert_eq!(sent["model"], "models/text-embedding-004");
+        assert_eq!(sent["content"]["parts"][0]["text"], "fn main() {}");
+    }
+
+    #[rocket::async_test]
+    async fn test_openai_embedding_response_shape() {
+        // Results are matched to inputs by index, whatever order they arrive in
+        let (url, request) = mock_embedding_api(
+            r#"{"object":"list","data":[{"object":"embedding","index":1,"embedding":[2.0]},{"object":"embedding","index":0,"embedding":[1.0]}],"model":"text-embedding-3-small"}"#.to_string(),
+        );
+        let texts = vec!["first".to_string(), "second".to_string()];
+
+        let embeddings = openai_embed_at(&Client::new(), &url, "sk-test", &texts).await.unwrap();
+        assert_eq!(embeddings, vec![vec![1.0], vec![2.0]]);
+
+        let sent: serde_json::Value = serde_json::from_str(&request.recv().unwrap()).unwrap();
+        assert_eq!(sent["model"], "text-embedding-3-small");
+        assert_eq!(sent["input"], serde_json::json!(["first", "second"]));
+
+        let (url, _) = mock_embedding_api(r#"{"data":[]}"#.to_string());
+        let err = openai_embed_at(&Client::new(), &url, "sk-test", &texts).await.unwrap_err();
+        assert_eq!(err.to_string(), "Expected 2 embeddings, got 0");
+    }
+
+    #[test]
+    fn test_embedding_config_and_dimension_check() {
+        assert_eq!(EmbeddingConfig::from_values(None, None), EmbeddingConfig::default());
+        assert_eq!(EmbeddingConfig::default().dimension, 768);
+
+        let openai = EmbeddingConfig::from_values(Some(" OpenAI "), None);
+        assert_eq!(openai, EmbeddingConfig { provider: EmbeddingProvider::OpenAI, dimension: 1536 });
+        assert_eq!(EmbeddingConfig::from_values(Some("openai"), Some("512")).dimension, 512);
+
+        // Invalid values keep the defaults
+        assert_eq!(EmbeddingConfig::from_values(Some("cohere"), Some("0")), EmbeddingConfig::default());
+
+        assert!(check_dimension(&[0.0; 768], 768).is_ok());
+        assert_eq!(
+            check_dimension(&[0.0; 1536], 768).unwrap_err(),
+            "Embedding has 1536 dimensions, code_embeddings expects 768"
+        );
+    }
+
+    /// A Rust function with `body_lines` lines in its body
+    fn rust_fn(name: &str, body_lines: usize) -> String {
+        let body: String = (0..body_lines).map(|i| format!("    let value_{} = compute_something({});\n", i, i)).collect();
+        format!("/// Does {}\nfn {}() {{\n{}}}\n", name, name, body)
+    }
+
+    #[test]
+    fn test_rust_file_chunked_per_function() {
+        let content = [rust_fn("parse", 4), rust_fn("validate", 6), rust_fn("render", 3)].join("\n");
+        let lines: Vec<&str> = content.lines().collect();
+
+        // Each definition starts at its doc comment
+        let ranges = definition_ranges(&content, Some("Rust"));
+        assert_eq!(ranges.len(), 3);
+        for (range, name) in ranges.iter().zip(["parse", "validate", "render"]) {
+            assert_eq!(lines[range.start], format!("/// Does {}", name));
+        }
+
+        // Each chunk starts at the function's doc comment and holds only that function
+        let chunks = chunk_code(&content, "api", "src/lib.rs", Some("Rust"));
+        assert_eq!(chunks.len(), 3);
+        for (chunk, name) in chunks.iter().zip(["parse", "validate", "render"]) {
+            assert_eq!(lines[chunk.line_start as usize - 1], format!("/// Does {}", name));
+            assert_eq!(chunk.content.matches("fn ").count(), 1);
+            assert!(chunk.content.contains(&format!("fn {}()", name)));
+        }
+        assert_eq!(chunks[1].line_start, chunks[0].line_end + 1);
+        assert_eq!(chunks[2].line_end as usize, lines.len());
+    }
+
+    #[test]
+    fn test_many_small_functions_all_embedded() {
+        let content: Vec<String> = (0..40).map(|i| rust_fn(&format!("handler_{}", i), 8)).collect();
+        let content = content.join("\n");
+        let lines: Vec<&str> = content.lines().collect();
+
+        // More definitions than the per-file cap, so adjacent ones are packed together
+        let chunks = chunk_code(&content, "api", "src/handlers.rs", Some("Rust"));
+        assert!(chunks.len() < MAX_CHUNKS_PER_FILE, "{}", chunks.len());
+        assert_eq!(chunks.iter().map(|c| c.content.matches("fn handler_").count()).sum::<usize>(), 40);
+
+        // Chunks only break between functions
+        for chunk in &chunks {
+            assert!(lines[chunk.line_start as usize - 1].starts_with("/// Does handler_"));
+            assert!((chunk.line_end - chunk.line_start + 1) as usize <= CHUNK_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_huge_function_split_into_bounded_chunks() {
+        let content = rust_fn("generated", 700);
+        let ranges = chunk_code_by_function(&content, Some("Rust"));
+
+        assert_eq!(ranges.len(), 3);
+        assert!(ranges.iter().all(|r| r.len() <= CHUNK_SIZE));
+        assert_eq!(ranges.last().unwrap().end, content.lines().count());
+    }
+
+    #[test]
+    fn test_python_and_fallback_chunking() {
+        let content = "import os\n\n@cached\ndef load(path):\n    return open(path).read()\n\nclass Store:\n    def get(self):\n        return 1\n";
+        let ranges = definition_ranges(content, Some("Python"));
+        // The decorator belongs to its function; the method stays inside its class
+        assert_eq!(ranges, vec![0..2, 2..6, 6..9]);
+        assert_eq!(chunk_code_by_function(content, Some("Python")), ranges);
+
+        // Other languages, and Rust that doesn't parse, use line windows
+        assert_eq!(chunk_code_by_function(content, Some("Go")), vec![0..9]);
+        assert_eq!(chunk_code_by_function("fn broken( {\n}", Some("Rust")), vec![0..2]);
+        assert!(chunk_code_by_function("", Some("Rust")).is_empty());
+    }
+}
@@ -0,0 +1,72 @@
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, Utc};
+
+/// Default age after which a GitHub analysis is considered stale
+const DEFAULT_TTL_DAYS: i64 = 90;
+
+/// When a stored GitHub analysis stops being trusted, and whether to redo it automatically
+pub struct FreshnessPolicy {
+    pub ttl: Duration,
+    pub auto_refresh: bool,
+}
+
+impl Default for FreshnessPolicy {
+    fn default() -> Self {
+        Self {
+            ttl: Duration::days(DEFAULT_TTL_DAYS),
+            auto_refresh: false,
+        }
+    }
+}
+
+impl FreshnessPolicy {
+    /// Build from ANALYSIS_TTL_DAYS and ANALYSIS_AUTO_REFRESH
+    /// Invalid values are logged and the defaults kept
+    pub fn from_env() -> Self {
+        let mut policy = Self::default();
+
+        if let Ok(days) = std::env::var("ANALYSIS_TTL_DAYS") {
+            match days.trim().parse::<i64>() {
+                Ok(days) if days > 0 => policy.ttl = Duration::days(days),
+                _ => println!("[FRESHNESS] Ignoring invalid ANALYSIS_TTL_DAYS {:?}", days),
+            }
+        }
+
+        if let Ok(flag) = std::env::var("ANALYSIS_AUTO_REFRESH") {
+            policy.auto_refresh = matches!(flag.trim().to_lowercase().as_str(), "1" | "true" | "yes");
+        }
+
+        policy
+    }
+
+    /// Whether an analysis taken at `analyzed_at` (RFC 3339) is older than the TTL
+    /// Candidates without an analysis, or with an unreadable timestamp, are not flagged
+    pub fn is_stale(&self, analyzed_at: Option<&str>, now: DateTime<Utc>) -> bool {
+        analyzed_at
+            .and_then(|ts| DateTime::parse_from_rfc3339(ts).ok())
+            .is_some_and(|ts| now.signed_duration_since(ts) > self.ttl)
+    }
+}
+
+/// The process-wide freshness policy (configured once from env)
+pub fn freshness_policy() -> &'static FreshnessPolicy {
+    static POLICY: OnceLock<FreshnessPolicy> = OnceLock::new();
+    POLICY.get_or_init(FreshnessPolicy::from_env)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_staleness_against_ttl() {
+        let policy = FreshnessPolicy::default();
+        let now = DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z").unwrap().with_timezone(&Utc);
+
+        assert!(policy.is_stale(Some("2024-06-01T00:00:00Z"), now));
+        assert!(!policy.is_stale(Some("2025-05-20T12:00:00+00:00"), now));
+        assert!(!policy.is_stale(Some("not a date"), now));
+        assert!(!policy.is_stale(None, now));
+    }
+}
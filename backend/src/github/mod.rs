@@ -1,8 +1,15 @@
+pub mod activity;
 pub mod api;
 pub mod stats;
 pub mod ai_analysis;
 pub mod ai_summary;
 pub mod analyze;
+pub mod blocklist;
+pub mod capabilities;
 pub mod embeddings;
 pub mod semantic_search;
+pub mod repos;
+pub mod scrub;
+pub mod freshness;
+pub mod stats_cache;
 pub mod take_home;
@@ -0,0 +1,153 @@
+use serde::Serialize;
+
+use crate::github::stats::RepositoryInfo;
+
+/// Repositories kept in `GitHubStats.repositories`
+pub const MAX_STATS_REPOSITORIES: usize = 20;
+
+/// Page size for repo browsing when no limit is given, and the largest allowed
+pub const DEFAULT_PAGE_LIMIT: usize = 30;
+pub const MAX_PAGE_LIMIT: usize = 100;
+
+/// Order for repository listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepoSort {
+    /// Most starred first, most recently updated breaking ties
+    Stars,
+    /// Most recently updated first
+    Recent,
+}
+
+impl RepoSort {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "stars" => Some(Self::Stars),
+            "recent" | "updated" => Some(Self::Recent),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Stars => "stars",
+            Self::Recent => "recent",
+        }
+    }
+}
+
+/// Sort repositories in place (timestamps are RFC 3339, so they order as strings)
+pub fn sort_repositories(repos: &mut [RepositoryInfo], sort: RepoSort) {
+    match sort {
+        RepoSort::Stars => repos.sort_by(|a, b| {
+            b.stars.cmp(&a.stars).then_with(|| b.updated_at.cmp(&a.updated_at))
+        }),
+        RepoSort::Recent => repos.sort_by(|a, b| b.updated_at.cmp(&a.updated_at)),
+    }
+}
+
+/// Top repositories by stars, and whether any were dropped
+pub fn cap_repositories(mut repos: Vec<RepositoryInfo>, cap: usize) -> (Vec<RepositoryInfo>, bool) {
+    sort_repositories(&mut repos, RepoSort::Stars);
+    let truncated = repos.len() > cap;
+    repos.truncate(cap);
+    (repos, truncated)
+}
+
+/// One page of a user's repositories
+#[derive(Serialize)]
+pub struct RepoPage {
+    pub repositories: Vec<RepositoryInfo>,
+    pub total: usize,
+    pub limit: usize,
+    pub offset: usize,
+    pub sort: &'static str,
+    pub has_more: bool,
+}
+
+/// Sort all repositories and return the requested page (limit clamped to 1..=MAX_PAGE_LIMIT)
+pub fn paginate_repositories(
+    mut repos: Vec<RepositoryInfo>,
+    sort: RepoSort,
+    limit: Option<usize>,
+    offset: Option<usize>,
+) -> RepoPage {
+    let limit = limit.unwrap_or(DEFAULT_PAGE_LIMIT).clamp(1, MAX_PAGE_LIMIT);
+    let offset = offset.unwrap_or(0);
+    let total = repos.len();
+
+    sort_repositories(&mut repos, sort);
+    let repositories: Vec<RepositoryInfo> = repos.into_iter().skip(offset).take(limit).collect();
+
+    RepoPage {
+        has_more: offset + repositories.len() < total,
+        repositories,
+        total,
+        limit,
+        offset,
+        sort: sort.name(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn repo(name: &str, stars: u32, updated_at: &str) -> RepositoryInfo {
+        RepositoryInfo {
+            name: name.to_string(),
+            description: None,
+            language: Some("Rust".to_string()),
+            is_fork: false,
+            size: 100,
+            stars,
+            created_at: "2020-01-01T00:00:00Z".to_string(),
+            updated_at: updated_at.to_string(),
+        }
+    }
+
+    fn many_repos(count: u32) -> Vec<RepositoryInfo> {
+        (0..count)
+            .map(|i| repo(&format!("repo-{}", i), i % 7, &format!("2024-01-{:02}T00:00:00Z", i % 28 + 1)))
+            .collect()
+    }
+
+    #[test]
+    fn test_stats_repositories_capped_by_stars() {
+        let (capped, truncated) = cap_repositories(many_repos(45), MAX_STATS_REPOSITORIES);
+        assert!(truncated);
+        assert_eq!(capped.len(), MAX_STATS_REPOSITORIES);
+        assert!(capped.windows(2).all(|w| w[0].stars >= w[1].stars));
+        assert_eq!(capped[0].stars, 6);
+
+        let (small, truncated) = cap_repositories(many_repos(3), MAX_STATS_REPOSITORIES);
+        assert!(!truncated);
+        assert_eq!(small.len(), 3);
+    }
+
+    #[test]
+    fn test_pages_cover_full_list() {
+        let repos = many_repos(45);
+        let mut seen = Vec::new();
+        let mut offset = 0;
+
+        loop {
+            let page = paginate_repositories(repos.clone(), RepoSort::Recent, Some(20), Some(offset));
+            assert_eq!(page.total, 45);
+            assert!(page.repositories.windows(2).all(|w| w[0].updated_at >= w[1].updated_at));
+            seen.extend(page.repositories.iter().map(|r| r.name.clone()));
+            offset += page.repositories.len();
+            if !page.has_more {
+                break;
+            }
+        }
+
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 45);
+
+        let clamped = paginate_repositories(repos, RepoSort::Stars, Some(500), Some(40));
+        assert_eq!(clamped.limit, MAX_PAGE_LIMIT);
+        assert_eq!(clamped.repositories.len(), 5);
+        assert!(!clamped.has_more);
+    }
+}
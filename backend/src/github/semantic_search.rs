@@ -2,10 +2,10 @@ use serde::{Deserialize, Serialize};
 use sqlx::PgConnection;
 use uuid::Uuid;
 
-use crate::github::embeddings::generate_embedding;
+use crate::github::embeddings::{generate_embedding, CodeChunk};
 
 /// Categories for semantic search queries
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SearchCategory {
     ErrorHandling,
     NamingConventions,
@@ -52,6 +52,47 @@ impl SearchCategory {
         }
     }
 
+    /// Look up a category by its `name()`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let name = name.trim().to_lowercase();
+        Self::all().iter().copied().find(|c| c.name() == name)
+    }
+
+    /// Get a short description of what the category looks for
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::ErrorHandling => "How errors are raised, propagated and recovered from",
+            Self::NamingConventions => "Naming style for functions, variables and types",
+            Self::CodeComments => "Comments, doc strings and TODO/FIXME notes",
+            Self::TestingPatterns => "Unit and integration tests, mocks and assertions",
+            Self::AsyncPatterns => "Async/await, promises, futures and concurrency",
+            Self::DataValidation => "Input checking, parsing and sanitization",
+            Self::Logging => "Logging, tracing and debug output",
+            Self::Configuration => "Config files, environment variables and setup",
+            Self::ClassStructure => "Classes, structs, traits and interfaces",
+            Self::FunctionalPatterns => "Map/filter/reduce, closures and higher-order functions",
+        }
+    }
+
+    /// Categories most relevant to a role title, for focusing a profile on role-relevant evidence
+    /// Titles that don't match a known kind of role get every category
+    pub fn for_role(role: &str) -> &'static [SearchCategory] {
+        let role = role.to_lowercase();
+        let words: Vec<&str> = role.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()).collect();
+        // A word of the title starting with any of `prefixes` ("test" matches "Tester")
+        let mentions = |prefixes: &[&str]| prefixes.iter().any(|p| words.iter().any(|w| w.starts_with(p)));
+
+        if mentions(&["qa", "test", "quality"]) {
+            &[Self::TestingPatterns, Self::ErrorHandling, Self::DataValidation, Self::CodeComments]
+        } else if mentions(&["system", "infra", "platform", "devops", "sre", "reliability", "embedded"]) {
+            &[Self::AsyncPatterns, Self::ErrorHandling, Self::Configuration, Self::Logging]
+        } else if mentions(&["frontend", "front", "ui"]) {
+            &[Self::FunctionalPatterns, Self::ClassStructure, Self::NamingConventions, Self::TestingPatterns]
+        } else {
+            Self::all()
+        }
+    }
+
     /// Get all categories
     pub fn all() -> &'static [SearchCategory] {
         &[
@@ -69,6 +110,29 @@ impl SearchCategory {
     }
 }
 
+/// A search category as exposed to clients
+#[derive(Debug, Clone, Serialize)]
+pub struct SearchCategoryInfo {
+    pub name: &'static str,
+    pub query: &'static str,
+    pub description: &'static str,
+}
+
+impl From<SearchCategory> for SearchCategoryInfo {
+    fn from(category: SearchCategory) -> Self {
+        Self {
+            name: category.name(),
+            query: category.query(),
+            description: category.description(),
+        }
+    }
+}
+
+/// Describe every search category
+pub fn search_category_infos() -> Vec<SearchCategoryInfo> {
+    SearchCategory::all().iter().copied().map(SearchCategoryInfo::from).collect()
+}
+
 /// A code excerpt retrieved from semantic search
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CodeExcerpt {
@@ -81,6 +145,21 @@ pub struct CodeExcerpt {
     pub similarity: f32,
 }
 
+impl From<&CodeChunk> for CodeExcerpt {
+    /// Excerpt for a chunk picked without a similarity search
+    fn from(chunk: &CodeChunk) -> Self {
+        Self {
+            repo_name: chunk.repo_name.clone(),
+            file_path: chunk.file_path.clone(),
+            line_start: chunk.line_start,
+            line_end: chunk.line_end,
+            language: chunk.language.clone(),
+            content: chunk.content.clone(),
+            similarity: 1.0,
+        }
+    }
+}
+
 /// Results from semantic search across all categories
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
 pub struct SearchResults {
@@ -129,7 +208,7 @@ impl SearchResults {
     }
 
     /// Set excerpts for a category
-    fn set(&mut self, category: &str, excerpts: Vec<CodeExcerpt>) {
+    pub(crate) fn set(&mut self, category: &str, excerpts: Vec<CodeExcerpt>) {
         match category {
             "error_handling" => self.error_handling = excerpts,
             "naming_style" => self.naming_style = excerpts,
@@ -146,16 +225,78 @@ impl SearchResults {
     }
 }
 
-/// Search for similar code chunks using pgvector
+/// Pick up to `limit` excerpts, preferring distinct repos, then distinct files
+/// Candidates are expected in rank order; ties keep that order
+pub fn select_diverse(candidates: Vec<CodeExcerpt>, limit: usize) -> Vec<CodeExcerpt> {
+    use std::collections::HashSet;
+
+    let mut selected: Vec<CodeExcerpt> = Vec::new();
+    let mut taken = vec![false; candidates.len()];
+    let mut seen_repos: HashSet<&str> = HashSet::new();
+    let mut seen_files: HashSet<(&str, &str)> = HashSet::new();
+
+    // Pass 1: one excerpt per repo
+    for (i, c) in candidates.iter().enumerate() {
+        if selected.len() >= limit {
+            break;
+        }
+        if seen_repos.insert(c.repo_name.as_str()) {
+            seen_files.insert((c.repo_name.as_str(), c.file_path.as_str()));
+            taken[i] = true;
+            selected.push(c.clone());
+        }
+    }
+
+    // Pass 2: one excerpt per file
+    for (i, c) in candidates.iter().enumerate() {
+        if selected.len() >= limit {
+            break;
+        }
+        if !taken[i] && seen_files.insert((c.repo_name.as_str(), c.file_path.as_str())) {
+            taken[i] = true;
+            selected.push(c.clone());
+        }
+    }
+
+    // Pass 3: fill with whatever ranks highest
+    for (i, c) in candidates.iter().enumerate() {
+        if selected.len() >= limit {
+            break;
+        }
+        if !taken[i] {
+            taken[i] = true;
+            selected.push(c.clone());
+        }
+    }
+
+    selected
+}
+
+/// Cosine similarity below which a chunk is considered unrelated to a category query
+pub const DEFAULT_MIN_SIMILARITY: f32 = 0.25;
+
+/// Search for similar code chunks using pgvector, dropping chunks less similar than `min_similarity`
 pub async fn search_similar(
     conn: &mut PgConnection,
     analysis_id: Uuid,
     query: &str,
     limit: i32,
+    min_similarity: f32,
 ) -> Result<Vec<CodeExcerpt>, Box<dyn std::error::Error + Send + Sync>> {
     // Generate embedding for the search query
     let query_embedding = generate_embedding(query).await?;
 
+    search_similar_to_embedding(conn, analysis_id, &query_embedding, limit, min_similarity).await
+}
+
+/// Chunks of an analysis closest to `query_embedding`, at least `min_similarity` similar, best first
+async fn search_similar_to_embedding(
+    conn: &mut PgConnection,
+    analysis_id: Uuid,
+    query_embedding: &[f32],
+    limit: i32,
+    min_similarity: f32,
+) -> Result<Vec<CodeExcerpt>, Box<dyn std::error::Error + Send + Sync>> {
     let embedding_str = format!(
         "[{}]",
         query_embedding
@@ -178,6 +319,7 @@ pub async fn search_similar(
             1 - (embedding <=> $1::vector) as similarity
         FROM code_embeddings
         WHERE analysis_id = $2
+          AND 1 - (embedding <=> $1::vector) >= $4
         ORDER BY embedding <=> $1::vector
         LIMIT $3
         "#,
@@ -185,6 +327,7 @@ pub async fn search_similar(
     .bind(&embedding_str)
     .bind(analysis_id)
     .bind(limit)
+    .bind(min_similarity as f64)
     .fetch_all(&mut *conn)
     .await?;
 
@@ -208,31 +351,40 @@ pub async fn search_similar(
     Ok(excerpts)
 }
 
-/// Run semantic search across all categories
+/// Run semantic search across all categories; categories with nothing above `min_similarity` stay empty
 pub async fn search_all_categories(
     conn: &mut PgConnection,
     analysis_id: Uuid,
     excerpts_per_category: i32,
+    min_similarity: f32,
 ) -> Result<SearchResults, Box<dyn std::error::Error + Send + Sync>> {
     let mut results = SearchResults::default();
 
+    // Over-fetch so there is room to pick excerpts from different repos/files
+    let candidate_limit = excerpts_per_category.max(0) * 3;
+
     for category in SearchCategory::all() {
-        let excerpts = search_similar(conn, analysis_id, category.query(), excerpts_per_category)
+        let candidates = search_similar(conn, analysis_id, category.query(), candidate_limit, min_similarity)
             .await
             .unwrap_or_default();
 
+        let excerpts = select_diverse(candidates, excerpts_per_category.max(0) as usize);
         results.set(category.name(), excerpts);
     }
 
     Ok(results)
 }
 
-/// Get a sample of code from each category for the profile generator
-/// Returns a condensed view suitable for AI analysis
-pub fn summarize_excerpts(results: &SearchResults, max_chars_per_category: usize) -> String {
+/// Get a sample of code from each of `categories` for the profile generator
+/// Returns a condensed view suitable for AI analysis (pass `SearchCategory::all()` for every category)
+pub fn summarize_excerpts(
+    results: &SearchResults,
+    max_chars_per_category: usize,
+    categories: &[SearchCategory],
+) -> String {
     let mut summary = String::new();
 
-    for category in SearchCategory::all() {
+    for category in categories {
         let excerpts = results.get(category.name());
         if excerpts.is_empty() {
             continue;
@@ -303,6 +455,7 @@ pub async fn get_embedding_stats(
         total_lines: row.1.unwrap_or(0) as u32,
         repo_count: row.2.unwrap_or(0) as u32,
         languages: languages.into_iter().map(|(l,)| l).collect(),
+        deduplicated_chunks: 0,
     })
 }
 
@@ -312,4 +465,138 @@ pub struct EmbeddingStats {
     pub total_lines: u32,
     pub repo_count: u32,
     pub languages: Vec<String>,
+    /// Chunks left out because an identical chunk was stored; not in the table, so set from the `StoreResult`
+    #[serde(default)]
+    pub deduplicated_chunks: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn excerpt(repo: &str, file: &str, line_start: i32) -> CodeExcerpt {
+        CodeExcerpt {
+            repo_name: repo.to_string(),
+            file_path: file.to_string(),
+            line_start,
+            line_end: line_start + 10,
+            language: Some("Rust".to_string()),
+            content: String::new(),
+            similarity: 0.9,
+        }
+    }
+
+    #[test]
+    fn test_select_diverse_prefers_distinct_repos() {
+        let candidates = vec![
+            excerpt("alpha", "src/a.rs", 1),
+            excerpt("alpha", "src/a.rs", 50),
+            excerpt("alpha", "src/b.rs", 1),
+            excerpt("beta", "src/lib.rs", 1),
+            excerpt("gamma", "main.py", 1),
+        ];
+
+        let selected = select_diverse(candidates, 3);
+        let repos: Vec<&str> = selected.iter().map(|e| e.repo_name.as_str()).collect();
+        assert_eq!(repos, vec!["alpha", "beta", "gamma"]);
+    }
+
+    #[test]
+    fn test_select_diverse_falls_back_to_files_then_rank() {
+        let candidates = vec![
+            excerpt("alpha", "src/a.rs", 1),
+            excerpt("alpha", "src/a.rs", 50),
+            excerpt("alpha", "src/b.rs", 1),
+        ];
+
+        let selected = select_diverse(candidates, 3);
+        assert_eq!(selected[0].file_path, "src/a.rs");
+        assert_eq!(selected[1].file_path, "src/b.rs");
+        assert_eq!(selected[2].line_start, 50);
+    }
+
+    #[test]
+    fn test_summary_limited_to_requested_categories() {
+        let mut results = SearchResults::default();
+        for category in SearchCategory::all() {
+            results.set(category.name(), vec![excerpt("alpha", "src/a.rs", 1)]);
+        }
+
+        let requested: Vec<SearchCategory> = ["testing", "error_handling"]
+            .iter()
+            .filter_map(|name| SearchCategory::from_name(name))
+            .collect();
+        let summary = summarize_excerpts(&results, 2000, &requested);
+
+        let headers: Vec<&str> = summary.lines().filter(|l| l.starts_with("=== ")).collect();
+        assert_eq!(headers, vec!["=== TESTING ===", "=== ERROR_HANDLING ==="]);
+
+        let full = summarize_excerpts(&results, 2000, SearchCategory::all());
+        assert_eq!(full.lines().filter(|l| l.starts_with("=== ")).count(), SearchCategory::all().len());
+    }
+
+    #[test]
+    fn test_categories_for_role() {
+        let names = |role: &str| SearchCategory::for_role(role).iter().map(|c| c.name()).collect::<Vec<_>>();
+        assert_eq!(names("QA Engineer"), vec!["testing", "error_handling", "validation", "comments"]);
+        assert_eq!(names("Senior Systems Engineer"), vec!["async_patterns", "error_handling", "configuration", "logging"]);
+        assert_eq!(names("Frontend Developer")[0], "functional_patterns");
+        assert_eq!(names("Front-end Engineer")[0], "functional_patterns");
+        assert_eq!(SearchCategory::for_role("Engineering Manager").len(), SearchCategory::all().len());
+        // Matches start at a word, so "build" doesn't make a UI role
+        assert_eq!(SearchCategory::for_role("Build Engineer").len(), SearchCategory::all().len());
+    }
+
+    /// One-hot-ish 768-dim vector with the given leading components
+    fn embedding(components: &[f32]) -> Vec<f32> {
+        let mut v = vec![0.0; 768];
+        v[..components.len()].copy_from_slice(components);
+        v
+    }
+
+    fn vector_literal(v: &[f32]) -> String {
+        format!("[{}]", v.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(","))
+    }
+
+    // Needs a Postgres with pgvector and schema.sql applied; skipped unless TEST_DATABASE_URL is set
+    #[rocket::async_test]
+    async fn test_low_similarity_chunks_are_excluded() {
+        use sqlx::{Connection, PgConnection};
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+        let analysis_id = Uuid::new_v4();
+
+        // Cosine similarity to the query [1, 0, ...]: 1.0, ~0.71 and 0.0
+        let seeded = [("close.rs", embedding(&[1.0])), ("related.rs", embedding(&[1.0, 1.0])), ("unrelated.rs", embedding(&[0.0, 1.0]))];
+        for (i, (file, v)) in seeded.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO code_embeddings (analysis_id, username, repo_name, file_path, line_start, line_end, content, embedding)
+                 VALUES ($1, 'octocat', 'alpha', $2, $3, $3, '', $4::vector)",
+            )
+            .bind(analysis_id)
+            .bind(file)
+            .bind(i as i32)
+            .bind(vector_literal(v))
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        }
+
+        let query = embedding(&[1.0]);
+        let found = search_similar_to_embedding(&mut tx, analysis_id, &query, 10, DEFAULT_MIN_SIMILARITY).await.unwrap();
+        let files: Vec<&str> = found.iter().map(|e| e.file_path.as_str()).collect();
+        assert_eq!(files, vec!["close.rs", "related.rs"]);
+        assert!(found.iter().all(|e| e.similarity >= DEFAULT_MIN_SIMILARITY));
+
+        // Nothing clears the bar: an empty result, not an error
+        let none = search_similar_to_embedding(&mut tx, analysis_id, &embedding(&[0.0, 0.0, 1.0]), 10, DEFAULT_MIN_SIMILARITY).await.unwrap();
+        assert!(none.is_empty());
+
+        tx.rollback().await.unwrap();
+    }
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::github::api::{GitHubRepoFull, GitHubUser};
 use crate::github::semantic_search::SearchResults;
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -12,6 +13,39 @@ pub struct GitHubProfile {
     pub followers: u32,
     pub following: u32,
     pub created_at: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub blog: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub twitter_username: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub company: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+}
+
+/// GitHub returns "" for unset profile fields
+fn non_empty(value: Option<String>) -> Option<String> {
+    value
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+impl From<GitHubUser> for GitHubProfile {
+    fn from(user: GitHubUser) -> Self {
+        Self {
+            name: user.name,
+            bio: user.bio,
+            avatar_url: user.avatar_url,
+            public_repos: user.public_repos,
+            followers: user.followers,
+            following: user.following,
+            created_at: user.created_at,
+            blog: non_empty(user.blog),
+            twitter_username: non_empty(user.twitter_username),
+            company: non_empty(user.company),
+            location: non_empty(user.location),
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Clone)]
@@ -21,10 +55,55 @@ pub struct RepositoryInfo {
     pub language: Option<String>,
     pub is_fork: bool,
     pub size: u32,
+    #[serde(default)]
+    pub stars: u32,
     pub created_at: String,
     pub updated_at: String,
 }
 
+impl From<&GitHubRepoFull> for RepositoryInfo {
+    fn from(repo: &GitHubRepoFull) -> Self {
+        Self {
+            name: repo.name.clone(),
+            description: repo.description.clone(),
+            language: repo.language.clone(),
+            is_fork: repo.fork,
+            size: repo.size,
+            stars: repo.stargazers_count,
+            created_at: repo.created_at.clone(),
+            updated_at: repo.updated_at.clone(),
+        }
+    }
+}
+
+/// Open-source reputation from the user's own (non-fork) repos and profile
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct Reputation {
+    pub total_stars: u32,
+    /// Times other people forked the user's repos
+    pub total_forks: u32,
+    pub most_starred_repo: Option<String>,
+    pub most_starred_stars: u32,
+    pub followers: u32,
+}
+
+impl Reputation {
+    pub fn from_repos(repos: &[GitHubRepoFull], followers: u32) -> Self {
+        let owned: Vec<&GitHubRepoFull> = repos.iter().filter(|r| !r.fork).collect();
+        let most_starred = owned.iter()
+            .filter(|r| r.stargazers_count > 0)
+            .max_by_key(|r| r.stargazers_count);
+
+        Self {
+            total_stars: owned.iter().map(|r| r.stargazers_count).sum(),
+            total_forks: owned.iter().map(|r| r.forks_count).sum(),
+            most_starred_repo: most_starred.map(|r| r.name.clone()),
+            most_starred_stars: most_starred.map_or(0, |r| r.stargazers_count),
+            followers,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone)]
 pub struct AIAnalysis {
     pub ai_detection_score: f32,
@@ -46,6 +125,10 @@ pub struct AnalysisMetadata {
     pub total_lines: u32,
     pub repos_analyzed: u32,
     pub languages_detected: Vec<String>,
+    /// Files skipped because an identical copy was already sampled, plus (with embeddings)
+    /// chunks not embedded because an identical chunk was stored
+    #[serde(default)]
+    pub duplicates_skipped: u32,
 }
 
 impl Default for AnalysisMetadata {
@@ -55,6 +138,76 @@ impl Default for AnalysisMetadata {
             total_lines: 0,
             repos_analyzed: 0,
             languages_detected: vec![],
+            duplicates_skipped: 0,
+        }
+    }
+}
+
+/// Most individual failures kept in `FetchDiagnostics::failures`; the counts stay exact
+const MAX_RECORDED_FAILURES: usize = 50;
+
+/// A repo tree or file that couldn't be fetched
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct FetchFailure {
+    pub repo: String,
+    /// None when the whole repo tree failed
+    pub path: Option<String>,
+    pub reason: String,
+}
+
+/// How active and consistent the developer has been, from their commits in their most recently updated repos
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct ActivityMetrics {
+    pub commits_last_90_days: u32,
+    pub commits_last_year: u32,
+    /// Commits in the last year divided by the weeks that had any
+    pub avg_commits_per_active_week: f32,
+    /// Repos with at least one of the user's commits in the last year
+    pub repos_pushed_last_year: u32,
+}
+
+/// What happened to each fetch while sampling code, so a thin analysis can be told apart from failed fetches
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct FetchDiagnostics {
+    pub repos_fetched: u32,
+    pub repos_failed: u32,
+    pub files_fetched: u32,
+    pub files_failed: u32,
+    pub files_skipped: u32,
+    /// Skipped file counts by reason ("too_short", "duplicate")
+    pub skip_reasons: BTreeMap<String, u32>,
+    /// The failed fetches with their errors (first 50)
+    pub failures: Vec<FetchFailure>,
+}
+
+impl FetchDiagnostics {
+    pub fn repo_fetched(&mut self) {
+        self.repos_fetched += 1;
+    }
+
+    pub fn repo_failed(&mut self, repo: &str, reason: impl ToString) {
+        self.repos_failed += 1;
+        self.push_failure(repo, None, reason.to_string());
+    }
+
+    pub fn file_fetched(&mut self) {
+        self.files_fetched += 1;
+    }
+
+    pub fn file_failed(&mut self, repo: &str, path: &str, reason: impl ToString) {
+        self.files_failed += 1;
+        self.push_failure(repo, Some(path), reason.to_string());
+    }
+
+    /// A file that was fetched but not sampled
+    pub fn file_skipped(&mut self, reason: &str) {
+        self.files_skipped += 1;
+        *self.skip_reasons.entry(reason.to_string()).or_insert(0) += 1;
+    }
+
+    fn push_failure(&mut self, repo: &str, path: Option<&str>, reason: String) {
+        if self.failures.len() < MAX_RECORDED_FAILURES {
+            self.failures.push(FetchFailure { repo: repo.to_string(), path: path.map(str::to_string), reason });
         }
     }
 }
@@ -63,7 +216,13 @@ impl Default for AnalysisMetadata {
 pub struct GitHubStats {
     pub username: String,
     pub profile: GitHubProfile,
+    /// Top repositories by stars, capped; browse the rest via `/github/<username>/repos`
     pub repositories: Vec<RepositoryInfo>,
+    /// Whether `repositories` was capped
+    #[serde(default)]
+    pub repositories_truncated: bool,
+    #[serde(default)]
+    pub reputation: Reputation,
     pub ai_analysis: AIAnalysis,
     pub languages: HashMap<String, u32>,
     pub analyzed_at: String,
@@ -71,6 +230,12 @@ pub struct GitHubStats {
     pub code_excerpts: Option<SearchResults>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub analysis_metadata: Option<AnalysisMetadata>,
+    /// Fetch outcomes from code sampling (deep and thorough analyses)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fetch_diagnostics: Option<FetchDiagnostics>,
+    /// Commit activity (deep and thorough analyses)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<ActivityMetrics>,
 }
 
 impl Default for AIAnalysis {
@@ -0,0 +1,345 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{PgConnection, PgExecutor, PgPool, Row};
+
+use crate::github::analyze::{analyze_github_user_deep, get_or_analyze_deep, AnalysisLimits};
+use crate::github::blocklist::{analysis_blocklist, unless_blocked};
+use crate::github::stats::GitHubStats;
+
+/// Default age after which `get_or_analyze` re-checks a cached analysis against GitHub
+const DEFAULT_CACHE_TTL_HOURS: i64 = 24;
+
+/// Analysis depths stored side by side for the same account
+pub const QUICK_ANALYSIS: &str = "quick";
+pub const DEEP_ANALYSIS: &str = "deep";
+
+/// GitHub usernames are case-insensitive, so the cache is keyed on the normalized form
+fn cache_key(username: &str) -> String {
+    username.trim().to_lowercase()
+}
+
+/// How long `get_or_analyze` serves a cached analysis without asking GitHub (GITHUB_ANALYSIS_CACHE_TTL_HOURS)
+pub fn analysis_cache_ttl() -> Duration {
+    static TTL: OnceLock<Duration> = OnceLock::new();
+    *TTL.get_or_init(|| {
+        let Ok(hours) = std::env::var("GITHUB_ANALYSIS_CACHE_TTL_HOURS") else {
+            return Duration::hours(DEFAULT_CACHE_TTL_HOURS);
+        };
+        match hours.trim().parse::<i64>() {
+            Ok(hours) if hours > 0 => Duration::hours(hours),
+            _ => {
+                println!("[StatsCache] Ignoring invalid GITHUB_ANALYSIS_CACHE_TTL_HOURS {:?}", hours);
+                Duration::hours(DEFAULT_CACHE_TTL_HOURS)
+            }
+        }
+    })
+}
+
+/// A stored analysis and what decides whether it can be reused
+pub struct CachedAnalysis {
+    pub stats: GitHubStats,
+    /// The account's latest repo update timestamps when it was analyzed; None when stored without one
+    pub fingerprint: Option<String>,
+    /// When the entry was stored or last confirmed unchanged
+    pub cached_at: DateTime<Utc>,
+}
+
+/// Where fresh analyses come from; GitHub in production, a mock in tests
+pub(crate) trait AnalysisSource {
+    /// Cheap summary of the account's repo activity that changes whenever a repo is updated
+    async fn fingerprint(&self, username: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>>;
+
+    /// `conn` is the caller's connection, for analyses that store and search embeddings
+    async fn analyze(
+        &self,
+        conn: Option<&mut PgConnection>,
+        username: &str,
+    ) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>>;
+}
+
+/// How `reuse_or_revalidate` got its stats
+#[derive(Debug, PartialEq)]
+pub(crate) enum CacheOutcome {
+    /// Younger than the TTL, served without contacting GitHub
+    Fresh,
+    /// Past the TTL but no repo changed since, so the analysis was kept
+    Unchanged,
+    /// Analyzed again; holds the new fingerprint
+    Analyzed(String),
+}
+
+/// Serve `cached` while it is younger than `ttl`; after that only re-analyze if the account's repos changed
+/// (checking the fingerprint costs a request, so it waits for the TTL)
+pub(crate) async fn reuse_or_revalidate<S: AnalysisSource>(
+    cached: Option<CachedAnalysis>,
+    ttl: Duration,
+    now: DateTime<Utc>,
+    username: &str,
+    source: &S,
+    conn: Option<&mut PgConnection>,
+) -> Result<(GitHubStats, CacheOutcome), Box<dyn std::error::Error + Send + Sync>> {
+    let cached = match cached {
+        Some(entry) if now.signed_duration_since(entry.cached_at) < ttl => return Ok((entry.stats, CacheOutcome::Fresh)),
+        other => other,
+    };
+
+    let fingerprint = source.fingerprint(username).await?;
+    if let Some(entry) = cached
+        && entry.fingerprint.as_deref() == Some(fingerprint.as_str())
+    {
+        return Ok((entry.stats, CacheOutcome::Unchanged));
+    }
+
+    let stats = source.analyze(conn, username).await?;
+    Ok((stats, CacheOutcome::Analyzed(fingerprint)))
+}
+
+async fn load_cache_entry(executor: impl PgExecutor<'_>, username: &str, depth: &str) -> Option<CachedAnalysis> {
+    let row = sqlx::query("SELECT stats, fingerprint, analyzed_at FROM github_analysis_cache WHERE username = $1 AND depth = $2")
+        .bind(cache_key(username))
+        .bind(depth)
+        .fetch_optional(executor)
+        .await
+        .ok()??;
+
+    Some(CachedAnalysis {
+        stats: serde_json::from_value(row.get::<serde_json::Value, _>("stats")).ok()?,
+        fingerprint: row.get("fingerprint"),
+        cached_at: row.get::<Option<DateTime<Utc>>, _>("analyzed_at").unwrap_or_default(),
+    })
+}
+
+async fn store_cache_entry(executor: impl PgExecutor<'_>, depth: &str, stats: &GitHubStats, fingerprint: Option<&str>) {
+    let result = sqlx::query(
+        r#"INSERT INTO github_analysis_cache (username, depth, stats, fingerprint, analyzed_at)
+           VALUES ($1, $2, $3, $4, NOW())
+           ON CONFLICT (username, depth) DO UPDATE
+           SET stats = EXCLUDED.stats, fingerprint = EXCLUDED.fingerprint, analyzed_at = EXCLUDED.analyzed_at"#
+    )
+    .bind(cache_key(&stats.username))
+    .bind(depth)
+    .bind(serde_json::to_value(stats).unwrap())
+    .bind(fingerprint)
+    .execute(executor)
+    .await;
+
+    if let Err(e) = result {
+        println!("[StatsCache] Failed to store analysis for {}: {}", stats.username, e);
+    }
+}
+
+/// Mark an entry as confirmed unchanged so the TTL starts over
+async fn touch_cache_entry(conn: &mut PgConnection, username: &str, depth: &str) {
+    let result = sqlx::query("UPDATE github_analysis_cache SET analyzed_at = NOW() WHERE username = $1 AND depth = $2")
+        .bind(cache_key(username))
+        .bind(depth)
+        .execute(conn)
+        .await;
+
+    if let Err(e) = result {
+        println!("[StatsCache] Failed to refresh cached analysis for {}: {}", username, e);
+    }
+}
+
+/// Cached analysis of `username` at `depth`, revalidated and stored through the github_analysis_cache table
+pub(crate) async fn cached_analysis<S: AnalysisSource>(
+    conn: &mut PgConnection,
+    username: &str,
+    depth: &str,
+    ttl: Duration,
+    source: &S,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    unless_blocked(analysis_blocklist(), username, || async {
+        let cached = load_cache_entry(&mut *conn, username, depth).await;
+        let (stats, outcome) = reuse_or_revalidate(cached, ttl, Utc::now(), username, source, Some(&mut *conn)).await?;
+
+        match outcome {
+            CacheOutcome::Fresh => println!("[StatsCache] Serving cached {} analysis of {}", depth, username),
+            CacheOutcome::Unchanged => {
+                println!("[StatsCache] No repo changes for {} since {}, keeping analysis", username, stats.analyzed_at);
+                touch_cache_entry(conn, username, depth).await;
+            }
+            CacheOutcome::Analyzed(fingerprint) => store_cache_entry(&mut *conn, depth, &stats, Some(&fingerprint)).await,
+        }
+        Ok(stats)
+    })
+    .await
+}
+
+/// Languages breakdown (percent of repos) from a stored analysis of the account, deep preferred over quick
+/// Read from the cache only, so it never calls GitHub; None for unanalyzed or blocklisted accounts
+pub async fn cached_languages(conn: &mut PgConnection, username: &str) -> Option<HashMap<String, u32>> {
+    if analysis_blocklist().is_blocked(username) {
+        return None;
+    }
+    for depth in [DEEP_ANALYSIS, QUICK_ANALYSIS] {
+        if let Some(entry) = load_cache_entry(&mut *conn, username, depth).await {
+            return Some(entry.stats.languages);
+        }
+    }
+    None
+}
+
+/// Deep GitHub analysis shared across candidates: a stored analysis of the same account is reused
+/// under the same TTL and repo-fingerprint policy as `get_or_analyze_deep`, otherwise the account is
+/// analyzed and the result stored for the next candidate
+pub async fn cached_github_stats(
+    pool: &PgPool,
+    username: &str,
+    token: &str,
+) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+    match pool.acquire().await {
+        Ok(mut conn) => get_or_analyze_deep(&mut conn, username, token, analysis_cache_ttl()).await,
+        Err(e) => {
+            // Without a connection nothing can be cached, but the analysis itself still works
+            println!("[StatsCache] No connection for {} ({}), analyzing uncached", username, e);
+            analyze_github_user_deep(None, username, token, &AnalysisLimits::default()).await
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use crate::github::stats::{AIAnalysis, GitHubProfile};
+
+    fn stats(username: &str, analyzed_at: &str) -> GitHubStats {
+        GitHubStats {
+            username: username.to_string(),
+            profile: GitHubProfile {
+                name: None,
+                bio: None,
+                avatar_url: String::new(),
+                public_repos: 1,
+                followers: 0,
+                following: 0,
+                created_at: "2015-01-01T00:00:00Z".to_string(),
+                blog: None,
+                location: None,
+                company: None,
+                twitter_username: None,
+            },
+            repositories: vec![],
+            repositories_truncated: false,
+            reputation: Default::default(),
+            ai_analysis: AIAnalysis::default(),
+            languages: HashMap::new(),
+            analyzed_at: analyzed_at.to_string(),
+            code_excerpts: None,
+            analysis_metadata: None,
+            fetch_diagnostics: None,
+            activity: None,
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_second_candidate_reuses_cached_stats() {
+        let ttl = Duration::hours(24);
+        let now = DateTime::parse_from_rfc3339("2025-06-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let source = CountingSource { fingerprint: "2025-05-30T00:00:00Z", calls: AtomicUsize::new(0) };
+
+        // First candidate: nothing cached, the account is analyzed
+        let (first, outcome) = reuse_or_revalidate(None, ttl, now, "octocat", &source, None).await.unwrap();
+        assert!(matches!(outcome, CacheOutcome::Analyzed(_)));
+        let calls = source.calls.load(Ordering::SeqCst);
+
+        // Second candidate with the same GitHub (linked with different casing) gets the stored analysis
+        assert_eq!(cache_key(" OctoCat "), cache_key(&first.username));
+        let cached = CachedAnalysis {
+            stats: first.clone(),
+            fingerprint: Some("2025-05-30T00:00:00Z".to_string()),
+            cached_at: now - Duration::hours(1),
+        };
+        let (second, outcome) = reuse_or_revalidate(Some(cached), ttl, now, "OctoCat", &source, None).await.unwrap();
+        assert_eq!(outcome, CacheOutcome::Fresh);
+        assert_eq!(second.analyzed_at, first.analyzed_at);
+        assert_eq!(source.calls.load(Ordering::SeqCst), calls);
+    }
+
+    /// Counts every GitHub request a real source would make
+    struct CountingSource {
+        fingerprint: &'static str,
+        calls: AtomicUsize,
+    }
+
+    impl AnalysisSource for CountingSource {
+        async fn fingerprint(&self, _username: &str) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(self.fingerprint.to_string())
+        }
+
+        async fn analyze(
+            &self,
+            _conn: Option<&mut PgConnection>,
+            username: &str,
+        ) -> Result<GitHubStats, Box<dyn std::error::Error + Send + Sync>> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            Ok(stats(username, "2025-06-01T00:00:00Z"))
+        }
+    }
+
+    #[rocket::async_test]
+    async fn test_second_call_within_ttl_makes_no_github_calls() {
+        let ttl = Duration::hours(24);
+        let now = DateTime::parse_from_rfc3339("2025-06-01T12:00:00Z").unwrap().with_timezone(&Utc);
+        let source = CountingSource { fingerprint: "2025-05-31T08:00:00Z", calls: AtomicUsize::new(0) };
+
+        // First call: nothing cached, fingerprint and analysis are fetched
+        let (first, outcome) = reuse_or_revalidate(None, ttl, now, "octocat", &source, None).await.unwrap();
+        assert_eq!(outcome, CacheOutcome::Analyzed("2025-05-31T08:00:00Z".to_string()));
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2);
+
+        // Second call within the TTL is served from the cache
+        let entry = |cached_at: DateTime<Utc>, fingerprint: &str| CachedAnalysis {
+            stats: first.clone(),
+            fingerprint: Some(fingerprint.to_string()),
+            cached_at,
+        };
+        let (second, outcome) = reuse_or_revalidate(Some(entry(now - Duration::hours(2), "2025-05-31T08:00:00Z")), ttl, now, "octocat", &source, None)
+            .await
+            .unwrap();
+        assert_eq!(outcome, CacheOutcome::Fresh);
+        assert_eq!(second.analyzed_at, first.analyzed_at);
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2);
+
+        // Past the TTL with no repo updates only the fingerprint is checked
+        let stale = now - Duration::hours(30);
+        let (_, outcome) = reuse_or_revalidate(Some(entry(stale, "2025-05-31T08:00:00Z")), ttl, now, "octocat", &source, None).await.unwrap();
+        assert_eq!(outcome, CacheOutcome::Unchanged);
+        assert_eq!(source.calls.load(Ordering::SeqCst), 3);
+
+        // A repo updated since the analysis triggers a new one
+        let (_, outcome) = reuse_or_revalidate(Some(entry(stale, "2025-04-01T00:00:00Z")), ttl, now, "octocat", &source, None).await.unwrap();
+        assert!(matches!(outcome, CacheOutcome::Analyzed(_)));
+        assert_eq!(source.calls.load(Ordering::SeqCst), 5);
+    }
+
+    #[rocket::async_test]
+    async fn test_cached_languages_prefers_deep_analysis() {
+        use sqlx::Connection;
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        assert_eq!(cached_languages(&mut tx, "languages-test-user").await, None);
+
+        let mut quick = stats("Languages-Test-User", "2025-06-01T00:00:00Z");
+        quick.languages = HashMap::from([("Rust".to_string(), 30)]);
+        store_cache_entry(&mut *tx, QUICK_ANALYSIS, &quick, None).await;
+        assert_eq!(cached_languages(&mut tx, "languages-test-user").await, Some(quick.languages.clone()));
+
+        let mut deep = quick.clone();
+        deep.languages = HashMap::from([("Rust".to_string(), 50)]);
+        store_cache_entry(&mut *tx, DEEP_ANALYSIS, &deep, None).await;
+        assert_eq!(cached_languages(&mut tx, "LANGUAGES-TEST-USER").await, Some(deep.languages));
+
+        tx.rollback().await.unwrap();
+    }
+}
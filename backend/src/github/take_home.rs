@@ -1,12 +1,74 @@
 use genai::{
     Client,
-    chat::{ChatMessage, ChatOptions, ChatRequest},
+    chat::{ChatMessage, ChatRequest},
 };
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
 
+use crate::ai_response::{clamp_score, parse_json, AiResponseError};
+use crate::ai_temperature::{chat_options, temperatures};
 use crate::github::api::{get_all_user_repos, get_readme_content, GitHubRepoFull};
 
 const MODEL_GEMINI: &str = "gemini-2.0-flash";
+/// Range a generated project's time estimate is clamped to
+const MIN_TIME_ESTIMATE_HOURS: i32 = 1;
+const MAX_TIME_ESTIMATE_HOURS: i32 = 40;
+
+/// Message returned when a candidate has too little data to tailor projects to
+pub const ENRICH_CANDIDATE_MESSAGE: &str =
+    "Not enough candidate data to tailor take-home projects. Add skills or a GitHub profile with repositories and try again.";
+
+// ============================================
+// Minimum Profile Check
+// ============================================
+
+/// How much signal a candidate needs before take-home generation runs
+/// Either threshold being met is enough
+pub struct MinimumProfile {
+    pub min_skills: usize,
+    pub min_repos: usize,
+}
+
+impl Default for MinimumProfile {
+    fn default() -> Self {
+        Self { min_skills: 1, min_repos: 1 }
+    }
+}
+
+impl MinimumProfile {
+    /// Build from TAKE_HOME_MIN_SKILLS and TAKE_HOME_MIN_REPOS
+    /// Invalid values are logged and the defaults kept
+    pub fn from_env() -> Self {
+        let mut minimum = Self::default();
+
+        for (var, field) in [
+            ("TAKE_HOME_MIN_SKILLS", &mut minimum.min_skills),
+            ("TAKE_HOME_MIN_REPOS", &mut minimum.min_repos),
+        ] {
+            if let Ok(value) = std::env::var(var) {
+                match value.trim().parse::<usize>() {
+                    Ok(n) if n > 0 => *field = n,
+                    _ => println!("[TAKE-HOME] Ignoring invalid {} {:?}", var, value),
+                }
+            }
+        }
+
+        minimum
+    }
+
+    /// Whether the candidate has enough claimed skills or analyzable (non-fork) repos
+    pub fn is_met(&self, candidate: &CandidateContext) -> bool {
+        let skills = candidate.claimed_skills.iter().filter(|s| !s.name.trim().is_empty()).count();
+        let repos = candidate.repos.iter().filter(|r| !r.is_fork).count();
+        skills >= self.min_skills || repos >= self.min_repos
+    }
+}
+
+/// The process-wide minimum profile (configured once from env)
+pub fn minimum_profile() -> &'static MinimumProfile {
+    static MINIMUM: OnceLock<MinimumProfile> = OnceLock::new();
+    MINIMUM.get_or_init(MinimumProfile::from_env)
+}
 
 // ============================================
 // Input Structures
@@ -99,6 +161,9 @@ pub struct AnalysisSummary {
 pub struct TakeHomeProjects {
     pub projects: Vec<TakeHomeProject>,
     pub analysis_summary: AnalysisSummary,
+    /// Rubric applied to every project, kept so a regenerated project is scored the same way
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rubric: Option<Vec<EvaluationCriterion>>,
 }
 
 // ============================================
@@ -196,6 +261,14 @@ IMPORTANT:
 
 Return ONLY the JSON object, no additional text or markdown formatting."#;
 
+/// Appended to the generation prompt when a single project is replaced
+const REPLACEMENT_PROJECT_PROMPT: &str = r#"
+
+## REPLACEMENT PROJECT:
+Ignore the 2-3 project count above and return exactly ONE project in "projects" (same JSON structure).
+It replaces a project the reviewer rejected. Avoid duplicating these projects they are keeping:
+{kept_projects}"#;
+
 // ============================================
 // Functions
 // ============================================
@@ -206,7 +279,7 @@ pub async fn infer_repo_purpose(
     description: Option<&str>,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     let client = Client::default();
-    let options = ChatOptions::default().with_temperature(0.3);
+    let options = chat_options(temperatures().repo_purpose, None);
 
     let prompt = REPO_PURPOSE_PROMPT
         .replace("{repo_name}", repo_name)
@@ -271,22 +344,157 @@ pub async fn analyze_candidate_repos(
     Ok(analyses)
 }
 
+/// Check a team-supplied rubric: named criteria with weights of 1-100 summing to 100
+pub fn validate_rubric(rubric: &[EvaluationCriterion]) -> Result<(), String> {
+    if rubric.is_empty() {
+        return Err("Rubric must have at least one criterion".to_string());
+    }
+    if let Some(blank) = rubric.iter().position(|c| c.criterion.trim().is_empty()) {
+        return Err(format!("Rubric criterion {} has no name", blank + 1));
+    }
+    if let Some(bad) = rubric.iter().find(|c| c.weight <= 0) {
+        return Err(format!("Rubric criterion '{}' must have a positive weight", bad.criterion));
+    }
+    if let Some(bad) = rubric.iter().find(|c| c.weight > 100) {
+        return Err(format!("Rubric criterion '{}' must have a weight of at most 100", bad.criterion));
+    }
+
+    // Summed as i64 so a long rubric can't overflow
+    let total: i64 = rubric.iter().map(|c| i64::from(c.weight)).sum();
+    if total != 100 {
+        return Err(format!("Rubric weights must sum to 100 (got {})", total));
+    }
+    Ok(())
+}
+
+/// Use the same rubric for every project in place of the generated criteria, and remember it for replacements
+pub fn apply_rubric(projects: &mut TakeHomeProjects, rubric: &[EvaluationCriterion]) {
+    for project in &mut projects.projects {
+        project.evaluation_criteria = rubric.to_vec();
+    }
+    projects.rubric = Some(rubric.to_vec());
+}
+
+/// Keep the model's percentages and criterion weights within 0-100, and time estimates within 1-40 hours
+fn clamp_generated_scores(projects: &mut TakeHomeProjects) {
+    let clamp = |value: i32| clamp_score(value as f64, 100.0) as i32;
+    projects.analysis_summary.skill_match_percentage = clamp(projects.analysis_summary.skill_match_percentage);
+    for project in &mut projects.projects {
+        project.time_estimate_hours = project.time_estimate_hours.clamp(MIN_TIME_ESTIMATE_HOURS, MAX_TIME_ESTIMATE_HOURS);
+        for criterion in &mut project.evaluation_criteria {
+            criterion.weight = clamp(criterion.weight);
+        }
+    }
+}
+
+/// Rescale a project's (non-negative) criterion weights to sum to exactly 100
+/// Rounding leftovers go to the largest criterion; all-zero weights are split evenly
+pub fn normalize_criteria_weights(project: &mut TakeHomeProject) {
+    let criteria = &mut project.evaluation_criteria;
+    if criteria.is_empty() {
+        return;
+    }
+
+    let total: i32 = criteria.iter().map(|c| c.weight).sum();
+    if total == 0 {
+        let share = 100 / criteria.len() as i32;
+        criteria.iter_mut().for_each(|c| c.weight = share);
+    } else if total != 100 {
+        criteria.iter_mut().for_each(|c| c.weight = c.weight * 100 / total);
+    }
+
+    let remainder = 100 - criteria.iter().map(|c| c.weight).sum::<i32>();
+    if let Some(largest) = criteria.iter_mut().max_by_key(|c| c.weight) {
+        largest.weight += remainder;
+    }
+}
+
 /// Generate take-home projects for a candidate-job pair
+/// A (validated) `rubric` replaces the model's evaluation criteria on every project
 pub async fn generate_take_home_projects(
     candidate: &CandidateContext,
     job: &JobContext,
+    rubric: Option<&[EvaluationCriterion]>,
 ) -> Result<TakeHomeProjects, Box<dyn std::error::Error + Send + Sync>> {
-    let client = Client::default();
-    let options = ChatOptions::default().with_temperature(0.4);
+    if !minimum_profile().is_met(candidate) {
+        return Err(ENRICH_CANDIDATE_MESSAGE.into());
+    }
 
+    let mut projects = request_projects(generation_prompt(candidate, job)).await?;
+
+    // Add UUIDs to each project
+    for project in &mut projects.projects {
+        project.id = uuid::Uuid::new_v4().to_string();
+    }
+
+    if let Some(rubric) = rubric {
+        apply_rubric(&mut projects, rubric);
+    }
+
+    Ok(projects)
+}
+
+/// Generate one project to replace `project_id` in a stored set, unlike the projects being kept
+/// The replacement keeps the replaced project's id; the rest of the set is untouched
+pub async fn regenerate_take_home_project(
+    candidate: &CandidateContext,
+    job: &JobContext,
+    projects: &mut TakeHomeProjects,
+    project_id: &str,
+) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+    if !projects.projects.iter().any(|p| p.id == project_id) {
+        return Err(format!("Project {} not found", project_id).into());
+    }
+    if !minimum_profile().is_met(candidate) {
+        return Err(ENRICH_CANDIDATE_MESSAGE.into());
+    }
+
+    let kept: Vec<String> = projects.projects
+        .iter()
+        .filter(|p| p.id != project_id)
+        .map(|p| format!("- {}", p.title))
+        .collect();
+    let prompt = generation_prompt(candidate, job)
+        + &REPLACEMENT_PROJECT_PROMPT.replace("{kept_projects}", &kept.join("\n"));
+
+    let replacement = request_projects(prompt)
+        .await?
+        .projects
+        .into_iter()
+        .next()
+        .ok_or("Model returned no replacement project")?;
+    replace_project(projects, project_id, replacement);
+    Ok(())
+}
+
+/// Put `replacement` in place of the project with `project_id`, keeping that id and the set's order
+/// The set's rubric, if it has one, replaces the replacement's generated criteria
+/// Returns false (and changes nothing) when no project has the id
+pub fn replace_project(projects: &mut TakeHomeProjects, project_id: &str, mut replacement: TakeHomeProject) -> bool {
+    let Some(slot) = projects.projects.iter_mut().find(|p| p.id == project_id) else {
+        return false;
+    };
+    replacement.id = project_id.to_string();
+    if let Some(rubric) = &projects.rubric {
+        replacement.evaluation_criteria = rubric.clone();
+    }
+    *slot = replacement;
+    true
+}
+
+// ============================================
+// Helper Functions
+// ============================================
+
+/// The project generation prompt filled in for a candidate and job
+fn generation_prompt(candidate: &CandidateContext, job: &JobContext) -> String {
     // Analyze skill gaps
     let (matched, gaps) = analyze_skill_gaps(&candidate.claimed_skills, &job.required_skills);
 
     // Format repos for prompt (limit to 30 for prompt size)
     let repos_analysis = format_repos_for_prompt(&candidate.repos);
 
-    // Build prompt
-    let prompt = PROJECT_GENERATION_PROMPT
+    PROJECT_GENERATION_PROMPT
         .replace("{candidate_name}", &candidate.name)
         .replace("{claimed_skills}", &format_skills(&candidate.claimed_skills))
         .replace("{developer_profile}", candidate.developer_profile.as_deref().unwrap_or("Not available"))
@@ -296,7 +504,13 @@ pub async fn generate_take_home_projects(
         .replace("{required_skills}", &format_required_skills(&job.required_skills))
         .replace("{experience_level}", &job.experience_level)
         .replace("{matched_skills}", &matched.join(", "))
-        .replace("{skill_gaps}", &if gaps.is_empty() { "None identified".to_string() } else { gaps.join(", ") });
+        .replace("{skill_gaps}", &if gaps.is_empty() { "None identified".to_string() } else { gaps.join(", ") })
+}
+
+/// Send a generation prompt and parse the projects, with scores clamped and criterion weights normalized
+async fn request_projects(prompt: String) -> Result<TakeHomeProjects, Box<dyn std::error::Error + Send + Sync>> {
+    let client = Client::default();
+    let options = chat_options(temperatures().take_home, None);
 
     let chat_req = ChatRequest::new(vec![
         ChatMessage::user(prompt),
@@ -304,30 +518,15 @@ pub async fn generate_take_home_projects(
 
     let chat_res = client
         .exec_chat(MODEL_GEMINI, chat_req, Some(&options))
-        .await?;
-
-    let response = chat_res
-        .content
-        .joined_texts()
-        .ok_or("No response from AI")?;
-
-    let json_str = extract_json(&response);
-
-    let mut projects: TakeHomeProjects = serde_json::from_str(&json_str)
-        .map_err(|e| format!("Failed to parse AI response: {}. Raw: {}", e, json_str))?;
-
-    // Add UUIDs to each project
-    for project in &mut projects.projects {
-        project.id = uuid::Uuid::new_v4().to_string();
-    }
+        .await
+        .map_err(AiResponseError::from_request_error)?;
 
+    let mut projects: TakeHomeProjects = parse_json(chat_res.content.joined_texts())?;
+    clamp_generated_scores(&mut projects);
+    projects.projects.iter_mut().for_each(normalize_criteria_weights);
     Ok(projects)
 }
 
-// ============================================
-// Helper Functions
-// ============================================
-
 fn format_repos_for_prompt(repos: &[RepoAnalysis]) -> String {
     if repos.is_empty() {
         return "No GitHub repositories available. Generate projects based on claimed skills and job requirements only.".to_string();
@@ -425,20 +624,232 @@ fn skills_match_simple(candidate_skill: &str, required_skill: &str) -> bool {
     c.contains(&r) || r.contains(&c)
 }
 
-fn extract_json(response: &str) -> String {
-    let lines: Vec<&str> = response.lines().collect();
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bare_candidate() -> CandidateContext {
+        CandidateContext {
+            name: "Bare Candidate".to_string(),
+            claimed_skills: vec![],
+            repos: vec![],
+            github_stats: None,
+            developer_profile: None,
+        }
+    }
+
+    fn job() -> JobContext {
+        JobContext {
+            title: "Backend Engineer".to_string(),
+            description: None,
+            required_skills: vec![],
+            experience_level: "mid".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_minimum_profile_thresholds() {
+        let minimum = MinimumProfile { min_skills: 2, min_repos: 1 };
+        let mut candidate = bare_candidate();
+        assert!(!minimum.is_met(&candidate));
+
+        candidate.claimed_skills.push(CandidateSkillContext { name: "Rust".to_string(), level: "expert".to_string() });
+        assert!(!minimum.is_met(&candidate));
+
+        candidate.repos.push(RepoAnalysis {
+            name: "fork".to_string(),
+            description: None,
+            readme_content: None,
+            inferred_purpose: None,
+            primary_language: None,
+            size: 10,
+            is_fork: true,
+        });
+        assert!(!minimum.is_met(&candidate));
+
+        candidate.claimed_skills.push(CandidateSkillContext { name: "SQL".to_string(), level: "intermediate".to_string() });
+        assert!(minimum.is_met(&candidate));
+    }
+
+    #[rocket::async_test]
+    async fn test_bare_candidate_skips_generation() {
+        // Returns the precheck message rather than a model/auth error, so no request was made
+        let err = generate_take_home_projects(&bare_candidate(), &job(), None).await.unwrap_err();
+        assert_eq!(err.to_string(), ENRICH_CANDIDATE_MESSAGE);
+    }
+
+    fn criterion(name: &str, weight: i32) -> EvaluationCriterion {
+        EvaluationCriterion { criterion: name.to_string(), weight, description: format!("{} rubric", name) }
+    }
+
+    #[test]
+    fn test_rubric_replaces_generated_criteria() {
+        let generated = r#"{
+          "projects": [
+            {"id": "", "title": "API", "description": "d", "skill_focus": [], "requirements": [], "deliverables": [],
+             "evaluation_criteria": [{"criterion": "Code Quality", "weight": 60, "description": "x"}, {"criterion": "Testing", "weight": 40, "description": "y"}],
+             "time_estimate_hours": 4, "difficulty": "mid", "skill_gaps_addressed": [], "based_on_repos": []},
+            {"id": "", "title": "CLI", "description": "d", "skill_focus": [], "requirements": [], "deliverables": [],
+             "evaluation_criteria": [{"criterion": "Functionality", "weight": 100, "description": "z"}],
+             "time_estimate_hours": 3, "difficulty": "mid", "skill_gaps_addressed": [], "based_on_repos": []}
+          ],
+          "analysis_summary": {"repos_analyzed": 0, "readmes_found": 0, "primary_languages": [], "skill_match_percentage": 50, "identified_gaps": []}
+        }"#;
+        let mut projects: TakeHomeProjects = serde_json::from_str(generated).unwrap();
+        let rubric = vec![criterion("Correctness", 50), criterion("Design", 30), criterion("Communication", 20)];
+        assert_eq!(validate_rubric(&rubric), Ok(()));
+
+        apply_rubric(&mut projects, &rubric);
+
+        for project in &projects.projects {
+            let names: Vec<&str> = project.evaluation_criteria.iter().map(|c| c.criterion.as_str()).collect();
+            assert_eq!(names, vec!["Correctness", "Design", "Communication"]);
+            assert_eq!(project.evaluation_criteria.iter().map(|c| c.weight).sum::<i32>(), 100);
+        }
+    }
+
+    #[test]
+    fn test_rubric_must_sum_to_100() {
+        assert_eq!(
+            validate_rubric(&[criterion("Correctness", 50), criterion("Design", 30)]),
+            Err("Rubric weights must sum to 100 (got 80)".to_string())
+        );
+        assert!(validate_rubric(&[]).is_err());
+        assert!(validate_rubric(&[criterion("Correctness", 110), criterion("Design", -10)]).is_err());
+        assert!(validate_rubric(&[criterion(" ", 100)]).is_err());
+
+        // Weights that would overflow an i32 sum are rejected rather than wrapping around to 100
+        let huge = vec![criterion("A", i32::MAX), criterion("B", i32::MAX), criterion("C", 102)];
+        assert_eq!(
+            validate_rubric(&huge),
+            Err("Rubric criterion 'A' must have a weight of at most 100".to_string())
+        );
+    }
 
-    // Handle markdown code blocks
-    if lines.len() > 2 && lines[0].contains("```") {
-        return lines[1..lines.len()-1].join("\n");
+    #[test]
+    fn test_generated_scores_are_clamped() {
+        let mut projects: TakeHomeProjects = serde_json::from_str(r#"{
+          "projects": [
+            {"id": "", "title": "API", "description": "d", "skill_focus": [], "requirements": [], "deliverables": [],
+             "evaluation_criteria": [{"criterion": "Code Quality", "weight": 150, "description": "x"}, {"criterion": "Testing", "weight": -50, "description": "y"}],
+             "time_estimate_hours": 4, "difficulty": "mid", "skill_gaps_addressed": [], "based_on_repos": []}
+          ],
+          "analysis_summary": {"repos_analyzed": 0, "readmes_found": 0, "primary_languages": [], "skill_match_percentage": 130, "identified_gaps": []}
+        }"#).unwrap();
+
+        clamp_generated_scores(&mut projects);
+
+        assert_eq!(projects.analysis_summary.skill_match_percentage, 100);
+        let weights: Vec<i32> = projects.projects[0].evaluation_criteria.iter().map(|c| c.weight).collect();
+        assert_eq!(weights, vec![100, 0]);
     }
 
-    // Try to find JSON object boundaries
-    if let Some(start) = response.find('{') {
-        if let Some(end) = response.rfind('}') {
-            return response[start..=end].to_string();
+    fn project_with_weights(weights: &[i32]) -> TakeHomeProject {
+        TakeHomeProject {
+            id: String::new(),
+            title: "API".to_string(),
+            description: "d".to_string(),
+            skill_focus: vec![],
+            requirements: vec![],
+            deliverables: vec![],
+            evaluation_criteria: weights.iter().enumerate().map(|(i, &w)| criterion(&format!("C{}", i), w)).collect(),
+            time_estimate_hours: 4,
+            difficulty: "mid".to_string(),
+            skill_gaps_addressed: vec![],
+            based_on_repos: vec![],
         }
     }
 
-    response.to_string()
+    fn weights(project: &TakeHomeProject) -> Vec<i32> {
+        project.evaluation_criteria.iter().map(|c| c.weight).collect()
+    }
+
+    #[test]
+    fn test_weights_summing_to_95_normalized() {
+        let mut project = project_with_weights(&[30, 40, 15, 10]);
+        normalize_criteria_weights(&mut project);
+        // 31.6, 42.1, 15.8, 10.5 round down to 98; the largest criterion takes the other 2
+        assert_eq!(weights(&project), vec![31, 44, 15, 10]);
+    }
+
+    #[test]
+    fn test_weights_summing_to_110_normalized() {
+        let mut project = project_with_weights(&[30, 40, 20, 20]);
+        normalize_criteria_weights(&mut project);
+        assert_eq!(weights(&project), vec![27, 37, 18, 18]);
+
+        // Already valid, all-zero and empty sets
+        let mut valid = project_with_weights(&[60, 40]);
+        normalize_criteria_weights(&mut valid);
+        assert_eq!(weights(&valid), vec![60, 40]);
+        let mut zero = project_with_weights(&[0, 0, 0]);
+        normalize_criteria_weights(&mut zero);
+        assert_eq!(weights(&zero).iter().sum::<i32>(), 100);
+        let mut empty = project_with_weights(&[]);
+        normalize_criteria_weights(&mut empty);
+        assert!(weights(&empty).is_empty());
+    }
+
+    #[test]
+    fn test_time_estimate_clamped() {
+        let mut projects = TakeHomeProjects {
+            projects: vec![project_with_weights(&[100]), project_with_weights(&[100])],
+            analysis_summary: serde_json::from_str(
+                r#"{"repos_analyzed": 0, "readmes_found": 0, "primary_languages": [], "skill_match_percentage": 50, "identified_gaps": []}"#,
+            )
+            .unwrap(),
+            rubric: None,
+        };
+        projects.projects[0].time_estimate_hours = 0;
+        projects.projects[1].time_estimate_hours = 120;
+
+        clamp_generated_scores(&mut projects);
+        let hours: Vec<i32> = projects.projects.iter().map(|p| p.time_estimate_hours).collect();
+        assert_eq!(hours, vec![1, 40]);
+    }
+
+    #[test]
+    fn test_replacing_one_project_keeps_the_other_ids() {
+        let mut projects = TakeHomeProjects {
+            projects: ["first", "second", "third"]
+                .iter()
+                .map(|id| TakeHomeProject { id: id.to_string(), title: format!("{} project", id), ..project_with_weights(&[100]) })
+                .collect(),
+            analysis_summary: serde_json::from_str(
+                r#"{"repos_analyzed": 0, "readmes_found": 0, "primary_languages": [], "skill_match_percentage": 50, "identified_gaps": []}"#,
+            )
+            .unwrap(),
+            rubric: None,
+        };
+        let replacement = TakeHomeProject { id: "fresh-uuid".to_string(), title: "Rate limiter".to_string(), ..project_with_weights(&[100]) };
+
+        assert!(replace_project(&mut projects, "third", replacement.clone()));
+        let ids: Vec<&str> = projects.projects.iter().map(|p| p.id.as_str()).collect();
+        assert_eq!(ids, vec!["first", "second", "third"]);
+        let titles: Vec<&str> = projects.projects.iter().map(|p| p.title.as_str()).collect();
+        assert_eq!(titles, vec!["first project", "second project", "Rate limiter"]);
+
+        assert!(!replace_project(&mut projects, "missing", replacement));
+        assert_eq!(projects.projects[2].title, "Rate limiter");
+    }
+
+    #[test]
+    fn test_replacement_is_scored_with_the_stored_rubric() {
+        let mut projects: TakeHomeProjects = serde_json::from_value(serde_json::json!({
+            "projects": [serde_json::to_value(TakeHomeProject { id: "first".to_string(), ..project_with_weights(&[100]) }).unwrap()],
+            "analysis_summary": {"repos_analyzed": 0, "readmes_found": 0, "primary_languages": [], "skill_match_percentage": 50, "identified_gaps": []}
+        }))
+        .unwrap();
+        // Sets stored before rubrics were kept have none
+        assert!(projects.rubric.is_none());
+
+        let rubric = vec![criterion("Correctness", 70), criterion("Design", 30)];
+        apply_rubric(&mut projects, &rubric);
+        // The rubric survives being stored as JSON
+        projects = serde_json::from_value(serde_json::to_value(&projects).unwrap()).unwrap();
+
+        assert!(replace_project(&mut projects, "first", project_with_weights(&[60, 40])));
+        let names: Vec<&str> = projects.projects[0].evaluation_criteria.iter().map(|c| c.criterion.as_str()).collect();
+        assert_eq!(names, vec!["Correctness", "Design"]);
+    }
 }
@@ -0,0 +1,205 @@
+use serde::Deserialize;
+
+use crate::github::api::decode_text;
+
+const GITLAB_API: &str = "https://gitlab.com/api/v4";
+
+/// Tree entries requested per page (GitLab's maximum)
+const TREE_PAGE_SIZE: usize = 100;
+/// Pages of a project tree walked before giving up on the rest; large monorepos are sampled from the first part
+const MAX_TREE_PAGES: usize = 5;
+
+#[derive(Deserialize)]
+pub struct GitLabProject {
+    pub id: u64,
+    pub name: String,
+    pub path_with_namespace: String,
+    #[serde(default)]
+    pub default_branch: Option<String>,
+    /// RFC 3339; GitLab's equivalent of GitHub's `updated_at`
+    #[serde(default)]
+    pub last_activity_at: Option<String>,
+    /// Only present on forks
+    #[serde(default)]
+    pub forked_from_project: Option<serde_json::Value>,
+}
+
+impl GitLabProject {
+    pub fn is_fork(&self) -> bool {
+        self.forked_from_project.as_ref().is_some_and(|p| !p.is_null())
+    }
+}
+
+/// Entry of the repository tree API
+#[derive(Deserialize)]
+pub struct GitLabTreeItem {
+    pub path: String,
+    /// "blob", "tree" or "commit" (submodule)
+    #[serde(rename = "type")]
+    pub item_type: String,
+}
+
+/// A failed GitLab request
+#[derive(Debug)]
+pub enum GitLabError {
+    /// 404: the user, project or file doesn't exist (or isn't visible to the token)
+    NotFound { path: String },
+    /// Any other failure; `status` is None when no response came back
+    Other { status: Option<u16>, message: String },
+}
+
+impl std::fmt::Display for GitLabError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NotFound { path } => write!(f, "GitLab resource not found: {}", path),
+            Self::Other { status: Some(status), message } => write!(f, "GitLab API returned {}: {}", status, message),
+            Self::Other { status: None, message } => write!(f, "GitLab request failed: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for GitLabError {}
+
+impl From<reqwest::Error> for GitLabError {
+    fn from(e: reqwest::Error) -> Self {
+        Self::Other { status: e.status().map(|s| s.as_u16()), message: e.to_string() }
+    }
+}
+
+/// GET a GitLab URL; the token is optional since public projects are readable without one
+pub async fn gitlab_get(client: &reqwest::Client, url: &str, token: &str) -> Result<reqwest::Response, GitLabError> {
+    let mut request = client.get(url).header("User-Agent", "FastboardAI");
+    if !token.is_empty() {
+        request = request.header("PRIVATE-TOKEN", token);
+    }
+    let response = request.send().await?;
+
+    let status = response.status();
+    if status.is_success() {
+        return Ok(response);
+    }
+    if status.as_u16() == 404 {
+        return Err(GitLabError::NotFound { path: response.url().path().to_string() });
+    }
+    let message = response.text().await.unwrap_or_default();
+    Err(GitLabError::Other { status: Some(status.as_u16()), message })
+}
+
+/// Percent-encode a value for use as one path segment or query value (GitLab wants file paths with `/` as %2F)
+fn encode_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// The user's own projects, most recently active first, without forks
+pub async fn get_user_projects(
+    username: &str,
+    token: &str,
+) -> Result<Vec<GitLabProject>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+    let url = format!(
+        "{}/users/{}/projects?order_by=last_activity_at&sort=desc&per_page=10",
+        GITLAB_API, encode_component(username)
+    );
+
+    let projects: Vec<GitLabProject> = gitlab_get(&client, &url, token)
+        .await?
+        .json()
+        .await?;
+
+    Ok(projects.into_iter().filter(|p| !p.is_fork()).collect())
+}
+
+/// Get a project's file tree (recursive, default branch)
+pub async fn get_repo_tree(
+    project_id: u64,
+    token: &str,
+) -> Result<Vec<GitLabTreeItem>, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::new();
+    let mut tree = Vec::new();
+
+    // The tree API is paginated; a short page is the last one
+    for page in 1..=MAX_TREE_PAGES {
+        let url = format!(
+            "{}/projects/{}/repository/tree?recursive=true&per_page={}&page={}",
+            GITLAB_API, project_id, TREE_PAGE_SIZE, page
+        );
+        let items: Vec<GitLabTreeItem> = match gitlab_get(&client, &url, token).await {
+            Ok(response) => response.json().await?,
+            // An empty project has no repository to list
+            Err(GitLabError::NotFound { .. }) if page == 1 => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let last = items.len() < TREE_PAGE_SIZE;
+        tree.extend(items);
+        if last {
+            break;
+        }
+    }
+
+    Ok(tree)
+}
+
+/// Get raw file content from a project
+/// Binary content comes back empty so callers skip it like any other empty file
+pub async fn get_file_content(
+    project_id: u64,
+    path: &str,
+    git_ref: &str,
+    token: &str,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(10))
+        .build()?;
+    let url = format!(
+        "{}/projects/{}/repository/files/{}/raw?ref={}",
+        GITLAB_API, project_id, encode_component(path), encode_component(git_ref)
+    );
+
+    let bytes = gitlab_get(&client, &url, token)
+        .await?
+        .bytes()
+        .await?;
+
+    match decode_text(&bytes) {
+        Some(text) => Ok(text),
+        None => {
+            println!("[GitLab] Skipping binary file {}: {}", project_id, path);
+            Ok(String::new())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_projects_parsed_and_forks_detected() {
+        let json = r#"[
+            {"id": 101, "name": "compiler", "path_with_namespace": "jdoe/compiler", "default_branch": "main",
+             "last_activity_at": "2025-05-01T10:00:00.000Z"},
+            {"id": 102, "name": "gitlab-runner", "path_with_namespace": "jdoe/gitlab-runner", "default_branch": "main",
+             "last_activity_at": "2025-04-01T10:00:00.000Z", "forked_from_project": {"id": 250833}},
+            {"id": 103, "name": "empty", "path_with_namespace": "jdoe/empty", "last_activity_at": null}
+        ]"#;
+        let projects: Vec<GitLabProject> = serde_json::from_str(json).unwrap();
+
+        let forks: Vec<bool> = projects.iter().map(GitLabProject::is_fork).collect();
+        assert_eq!(forks, [false, true, false]);
+        assert_eq!(projects[2].default_branch, None);
+    }
+
+    #[test]
+    fn test_file_paths_encoded_as_one_segment() {
+        assert_eq!(encode_component("src/lib/main.rs"), "src%2Flib%2Fmain.rs");
+        assert_eq!(encode_component("docs/a b+c.md"), "docs%2Fa%20b%2Bc.md");
+        assert_eq!(encode_component("feature/x"), "feature%2Fx");
+    }
+}
@@ -1,5 +1,10 @@
+pub mod ai_response;
+pub mod ai_temperature;
+pub mod candidate_id;
 pub mod code_analysis;
 pub mod db;
 pub mod endpoints;
 pub mod github;
+pub mod gitlab;
 pub mod matching;
+pub mod pipeline;
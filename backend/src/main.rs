@@ -1,7 +1,8 @@
 use std::error::Error;
 
-use backend::{db::MainDatabase, endpoints};
+use backend::{db::MainDatabase, endpoints, github::capabilities::check_embedding_support, matching::skills::refresh_synonyms};
 use dotenv::dotenv;
+use rocket::fairing::AdHoc;
 use rocket::routes;
 use rocket_db_pools::Database;
 use rocket_cors::{AllowedOrigins, CorsOptions};
@@ -18,6 +19,24 @@ async fn main() -> Result<(), Box<dyn Error>> {
     let _server = rocket::build()
         .attach(MainDatabase::init())
         .attach(cors)
+        .attach(AdHoc::on_liftoff("Embedding capability check", |rocket| Box::pin(async move {
+            if let Some(db) = MainDatabase::fetch(rocket)
+                && let Ok(mut conn) = db.acquire().await
+            {
+                let available = check_embedding_support(&mut conn).await;
+                println!("[CAPABILITIES] Embeddings {}", if available { "available" } else { "unavailable, using keyword excerpts" });
+            }
+        })))
+        .attach(AdHoc::on_liftoff("Skill synonyms", |rocket| Box::pin(async move {
+            if let Some(db) = MainDatabase::fetch(rocket)
+                && let Ok(mut conn) = db.acquire().await
+            {
+                match refresh_synonyms(&mut conn).await {
+                    Ok(synonyms) => println!("[Matching] Loaded synonyms for {} skills", synonyms.len()),
+                    Err(e) => println!("[Matching] Warning: could not load skill_synonyms ({}), using built-in synonyms", e),
+                }
+            }
+        })))
         .mount("/api/", routes![
             endpoints::add_to_db,
             endpoints::analyse_repo,
@@ -26,10 +45,15 @@ async fn main() -> Result<(), Box<dyn Error>> {
             endpoints::get_jobs,
             endpoints::get_job,
             endpoints::create_job,
+            endpoints::clone_job,
+            endpoints::get_job_skill_matrix,
+            endpoints::validate_skills,
+            endpoints::add_skill_synonym,
             endpoints::update_job,
             endpoints::delete_job,
             // Teams
             endpoints::get_teams,
+            endpoints::get_ideal_candidate,
             endpoints::get_team,
             endpoints::create_team,
             endpoints::update_team,
@@ -39,9 +63,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
             endpoints::remove_team_member,
             // Sourcing
             endpoints::search_candidates,
+            endpoints::search_candidates_stream,
             // Candidates
             endpoints::create_candidate,
+            endpoints::get_candidates,
+            endpoints::delete_candidate,
             endpoints::get_candidates_count,
+            endpoints::get_candidates_stats,
+            endpoints::get_candidate_github_stats,
+            endpoints::get_candidate_analysis_status,
+            endpoints::move_candidate_stage,
+            endpoints::advance_candidate,
+            endpoints::match_candidate_jobs,
+            endpoints::rescore_job_candidates,
+            endpoints::match_styles_endpoint,
             endpoints::add_candidate_to_job,
             endpoints::get_job_candidates,
             endpoints::remove_candidate_from_job,
@@ -50,12 +85,20 @@ async fn main() -> Result<(), Box<dyn Error>> {
             endpoints::analyze_github_deep,
             endpoints::get_github_profile,
             endpoints::get_github_profile_deep,
+            endpoints::get_search_categories,
+            endpoints::get_github_repos,
+            endpoints::github_job_match,
             // Take-Home Projects
             endpoints::generate_take_home,
+            endpoints::regenerate_take_home,
             endpoints::get_take_home,
             // Resume Parsing
             endpoints::parse_resume,
             endpoints::parse_resume_text,
+            // Scoring
+            endpoints::score_preview,
+            // Health
+            endpoints::ready,
         ])
         .launch()
         .await?;
@@ -2,10 +2,18 @@ use super::ExplainableScore;
 use genai::chat::{ChatMessage, ChatRequest};
 use genai::Client;
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use crate::ai_response::{clamp_score, log_fallback, parse_json, AiResponseError};
+use crate::ai_temperature::{chat_options, temperatures};
 
 #[derive(Deserialize)]
 struct CultureAnalysis {
-    score: i32,
+    /// Accepts fractional scores; clamped to 0-100 before use
+    score: f64,
     reasoning: String,
     strengths: Vec<String>,
     concerns: Vec<String>,
@@ -28,10 +36,135 @@ Respond with JSON only:
 }
 "#;
 
+/// How long an AI culture score is reused for the same inputs (CULTURE_CACHE_TTL_SECS, 0 = off)
+const DEFAULT_CULTURE_CACHE_TTL_SECS: u64 = 60 * 60;
+/// Cached scores kept before the least recently used one is evicted
+const CULTURE_CACHE_CAPACITY: usize = 1000;
+
+struct CachedCulture {
+    stored_at: Instant,
+    last_used: u64,
+    score: ExplainableScore,
+}
+
+/// AI culture scores by input hash, so rescoring a pool against the same job and team doesn't repeat calls
+/// Only successful AI results are stored; the heuristic fallback is recomputed every time
+#[derive(Default)]
+struct CultureCache {
+    entries: HashMap<String, CachedCulture>,
+    /// Use counter for LRU eviction
+    clock: u64,
+}
+
+impl CultureCache {
+    fn get(&mut self, key: &str, ttl: Duration) -> Option<ExplainableScore> {
+        self.clock += 1;
+        let entry = self.entries.get_mut(key)?;
+        if entry.stored_at.elapsed() >= ttl {
+            self.entries.remove(key);
+            return None;
+        }
+        entry.last_used = self.clock;
+        Some(entry.score.clone())
+    }
+
+    fn insert(&mut self, key: String, score: ExplainableScore, capacity: usize) {
+        if self.entries.len() >= capacity && !self.entries.contains_key(&key) {
+            let oldest = self.entries.iter().min_by_key(|(_, e)| e.last_used).map(|(k, _)| k.clone());
+            if let Some(oldest) = oldest {
+                self.entries.remove(&oldest);
+            }
+        }
+        self.clock += 1;
+        self.entries.insert(key, CachedCulture { stored_at: Instant::now(), last_used: self.clock, score });
+    }
+}
+
+/// Process-wide rather than Rocket managed state: `matching::score` also runs inside sourcing
+/// streams and spawned enrichment tasks that have no `&State` to pass down, and the profile and
+/// query-expansion caches are held the same way
+fn culture_cache() -> &'static Mutex<CultureCache> {
+    static CACHE: OnceLock<Mutex<CultureCache>> = OnceLock::new();
+    CACHE.get_or_init(Default::default)
+}
+
+/// Lifetime of cached AI culture scores (CULTURE_CACHE_TTL_SECS); zero disables the cache
+fn culture_cache_ttl() -> Duration {
+    static TTL: OnceLock<Duration> = OnceLock::new();
+    *TTL.get_or_init(|| {
+        let value = std::env::var("CULTURE_CACHE_TTL_SECS").unwrap_or_default();
+        if value.trim().is_empty() {
+            return Duration::from_secs(DEFAULT_CULTURE_CACHE_TTL_SECS);
+        }
+        match value.trim().parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => {
+                println!("[CULTURE] Ignoring invalid CULTURE_CACHE_TTL_SECS {:?}", value);
+                Duration::from_secs(DEFAULT_CULTURE_CACHE_TTL_SECS)
+            }
+        }
+    })
+}
+
+/// Hex sha256 of everything the AI sees, plus the temperature override
+/// Each part is length-prefixed so moving text between fields changes the key
+fn culture_cache_key(
+    candidate_profile: Option<&str>,
+    job_description: Option<&str>,
+    team_profiles: &[String],
+    temperature: Option<f64>,
+) -> String {
+    let mut hasher = Sha256::new();
+    let parts = [candidate_profile, job_description].into_iter().chain(team_profiles.iter().map(|p| Some(p.as_str())));
+    for part in parts {
+        match part {
+            Some(text) => {
+                hasher.update((text.len() as u64 + 1).to_le_bytes());
+                hasher.update(text.as_bytes());
+            }
+            None => hasher.update(0u64.to_le_bytes()),
+        }
+    }
+    hasher.update(format!("{:?}", temperature).as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// Cached AI score for `key`, or a fresh one from `analyze`; failures fall back to the heuristic uncached
+async fn cached_culture_score<F, Fut>(
+    cache: &Mutex<CultureCache>,
+    key: String,
+    ttl: Duration,
+    analyze: F,
+    candidate_profile: Option<&str>,
+    job_description: Option<&str>,
+) -> ExplainableScore
+where
+    F: FnOnce() -> Fut,
+    Fut: Future<Output = Result<CultureAnalysis, AiResponseError>>,
+{
+    if ttl.is_zero() {
+        return culture_score_from_ai(analyze().await, candidate_profile, job_description);
+    }
+    if let Some(score) = cache.lock().unwrap().get(&key, ttl) {
+        return score;
+    }
+
+    let ai = analyze().await;
+    let succeeded = ai.is_ok();
+    let score = culture_score_from_ai(ai, candidate_profile, job_description);
+    if succeeded {
+        cache.lock().unwrap().insert(key, score.clone(), CULTURE_CACHE_CAPACITY);
+    }
+    score
+}
+
+/// `temperature` overrides the configured culture temperature for this call
+/// AI results are cached per input for CULTURE_CACHE_TTL_SECS
 pub async fn calculate_culture_score(
     candidate_profile: Option<&str>,
     job_description: Option<&str>,
     team_profiles: &[String],
+    temperature: Option<f64>,
 ) -> ExplainableScore {
     // If no data available, return neutral score
     if candidate_profile.is_none() && job_description.is_none() && team_profiles.is_empty() {
@@ -41,6 +174,7 @@ pub async fn calculate_culture_score(
             missing: vec![],
             bonus: vec![],
             reasoning: Some("Insufficient data for culture analysis".to_string()),
+            excluded: false,
         };
     }
 
@@ -62,55 +196,73 @@ pub async fn calculate_culture_score(
         }
     }
 
-    // Try AI analysis
-    match analyze_with_gemini(&context).await {
+    let key = culture_cache_key(candidate_profile, job_description, team_profiles, temperature);
+    cached_culture_score(
+        culture_cache(),
+        key,
+        culture_cache_ttl(),
+        || async { analyze_with_gemini(&context, temperature).await },
+        candidate_profile,
+        job_description,
+    ).await
+}
+
+/// AI culture score, falling back to the keyword heuristic when the AI is unavailable
+fn culture_score_from_ai(
+    ai: Result<CultureAnalysis, AiResponseError>,
+    candidate_profile: Option<&str>,
+    job_description: Option<&str>,
+) -> ExplainableScore {
+    match ai {
         Ok(analysis) => ExplainableScore {
-            score: analysis.score.min(100).max(0),
+            score: clamp_score(analysis.score, 100.0).round() as i32,
             matched: analysis.strengths,
             missing: analysis.concerns,
             bonus: vec![],
             reasoning: Some(analysis.reasoning),
+            excluded: false,
         },
-        Err(_) => {
-            // Fallback to heuristic scoring
+        Err(e) => {
+            log_fallback("Culture analysis", &e, "keyword heuristic");
             calculate_heuristic_culture_score(candidate_profile, job_description)
         }
     }
 }
 
-async fn analyze_with_gemini(context: &str) -> Result<CultureAnalysis, Box<dyn std::error::Error + Send + Sync>> {
+/// Culture score without calling the AI (deterministic, keyword heuristic only)
+pub fn calculate_culture_score_offline(
+    candidate_profile: Option<&str>,
+    job_description: Option<&str>,
+    team_profiles: &[String],
+) -> ExplainableScore {
+    if candidate_profile.is_none() && job_description.is_none() && team_profiles.is_empty() {
+        return ExplainableScore {
+            score: 70,
+            matched: vec![],
+            missing: vec![],
+            bonus: vec![],
+            reasoning: Some("Insufficient data for culture analysis".to_string()),
+            excluded: false,
+        };
+    }
+
+    calculate_heuristic_culture_score(candidate_profile, job_description)
+}
+
+async fn analyze_with_gemini(context: &str, temperature: Option<f64>) -> Result<CultureAnalysis, AiResponseError> {
     let client = Client::default();
 
     let prompt = format!("{}\n\nContext:\n{}", CULTURE_PROMPT, context);
 
     let request = ChatRequest::new(vec![ChatMessage::user(prompt)]);
+    let options = chat_options(temperatures().culture, temperature);
 
     let response = client
-        .exec_chat("gemini-2.0-flash", request, None)
-        .await?;
-
-    let content = response
-        .first_text()
-        .ok_or("No response content")?;
-
-    // Extract JSON from response
-    let json_str = if content.contains("```json") {
-        content
-            .split("```json")
-            .nth(1)
-            .and_then(|s| s.split("```").next())
-            .unwrap_or(content)
-    } else if content.contains("```") {
-        content
-            .split("```")
-            .nth(1)
-            .unwrap_or(content)
-    } else {
-        content
-    };
-
-    let analysis: CultureAnalysis = serde_json::from_str(json_str.trim())?;
-    Ok(analysis)
+        .exec_chat("gemini-2.0-flash", request, Some(&options))
+        .await
+        .map_err(AiResponseError::from_request_error)?;
+
+    parse_json(response.first_text().map(String::from))
 }
 
 fn calculate_heuristic_culture_score(
@@ -161,5 +313,98 @@ fn calculate_heuristic_culture_score(
         missing,
         bonus: vec![],
         reasoning: Some("Heuristic culture analysis (AI unavailable)".to_string()),
+        excluded: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_ai_response_falls_back_to_heuristic() {
+        let profile = Some("Collaborative engineer who enjoys mentoring");
+        let job = Some("We are a collaborative team and mentor juniors");
+
+        let ai = parse_json::<CultureAnalysis>(Some(String::new()));
+        let result = culture_score_from_ai(ai, profile, job);
+
+        assert_eq!(result.score, 80);
+        assert_eq!(result.matched, vec!["Values collaboration", "Mentorship experience"]);
+        assert_eq!(result.reasoning.as_deref(), Some("Heuristic culture analysis (AI unavailable)"));
+    }
+
+    #[test]
+    fn test_out_of_range_ai_culture_scores_are_clamped() {
+        let analysis = |score: f64| {
+            Ok(CultureAnalysis { score, reasoning: String::new(), strengths: vec![], concerns: vec![] })
+        };
+
+        assert_eq!(culture_score_from_ai(analysis(140.0), None, None).score, 100);
+        assert_eq!(culture_score_from_ai(analysis(-5.0), None, None).score, 0);
+        assert_eq!(culture_score_from_ai(analysis(f64::NAN), None, None).score, 0);
+        assert_eq!(culture_score_from_ai(analysis(f64::NEG_INFINITY), None, None).score, 0);
+        assert_eq!(culture_score_from_ai(analysis(64.5), None, None).score, 65);
+    }
+
+    #[rocket::async_test]
+    async fn test_identical_inputs_call_ai_once() {
+        let cache = Mutex::new(CultureCache::default());
+        let calls = std::sync::atomic::AtomicUsize::new(0);
+        let team = vec!["Pragmatic reviewer".to_string()];
+        let profile = Some("Collaborative engineer");
+        let job = Some("Small product team");
+        let ttl = Duration::from_secs(60);
+
+        let score = async |profile: Option<&str>, ai: Result<f64, ()>| {
+            let key = culture_cache_key(profile, job, &team, None);
+            cached_culture_score(&cache, key, ttl, || async {
+                calls.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+                ai.map(|score| CultureAnalysis { score, reasoning: "Good fit".to_string(), strengths: vec![], concerns: vec![] })
+                    .map_err(|_| AiResponseError::Empty)
+            }, profile, job).await
+        };
+
+        let first = score(profile, Ok(82.0)).await;
+        let second = score(profile, Ok(10.0)).await;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 1);
+        assert_eq!((first.score, second.score), (82, 82));
+
+        // Different inputs are a different key
+        score(Some("Independent engineer"), Ok(55.0)).await;
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 2);
+
+        // A heuristic fallback isn't cached, so the next call retries the AI
+        let other = Some("Remote-first engineer");
+        let fallback = score(other, Err(())).await;
+        assert_eq!(fallback.reasoning.as_deref(), Some("Heuristic culture analysis (AI unavailable)"));
+        assert_eq!(score(other, Ok(64.0)).await.score, 64);
+        assert_eq!(calls.load(std::sync::atomic::Ordering::SeqCst), 4);
+    }
+
+    #[test]
+    fn test_culture_cache_evicts_least_recently_used() {
+        let mut cache = CultureCache::default();
+        let ttl = Duration::from_secs(60);
+        let score = |score: i32| ExplainableScore { score, matched: vec![], missing: vec![], bonus: vec![], reasoning: None, excluded: false };
+
+        cache.insert("a".to_string(), score(1), 2);
+        cache.insert("b".to_string(), score(2), 2);
+        assert!(cache.get("a", ttl).is_some());
+        cache.insert("c".to_string(), score(3), 2);
+
+        assert!(cache.get("b", ttl).is_none());
+        assert_eq!(cache.get("a", ttl).map(|s| s.score), Some(1));
+        assert_eq!(cache.get("c", Duration::ZERO).map(|s| s.score), None);
+        assert_eq!(cache.entries.len(), 1);
+
+        assert_ne!(
+            culture_cache_key(Some("ab"), Some("c"), &[], None),
+            culture_cache_key(Some("a"), Some("bc"), &[], None),
+        );
+        assert_ne!(
+            culture_cache_key(Some("a"), None, &[], None),
+            culture_cache_key(Some("a"), None, &[], Some(0.0)),
+        );
     }
 }
@@ -1,6 +1,163 @@
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use chrono::{Datelike, NaiveDate};
+
 use super::{CandidateExperience, ExplainableScore};
+use crate::github::stats::Reputation;
+
+/// Level names `level_years_required` understands directly
+const CANONICAL_LEVELS: &[&str] = &[
+    "entry", "junior", "mid", "intermediate", "senior", "lead", "principal", "staff", "any",
+];
+
+/// Bucket used when a level is neither canonical nor mapped
+const DEFAULT_LEVEL: &str = "mid";
+
+/// Upper bound for REPUTATION_EXPERIENCE_BONUS, so reputation stays a minor signal
+const MAX_REPUTATION_BONUS: i32 = 10;
+
+/// Points per seniority step climbed across a candidate's career, and the most growth can add
+const GROWTH_POINTS_PER_STEP: i32 = 4;
+const MAX_GROWTH_BONUS: i32 = 8;
+
+/// With PENALIZE_OVERQUALIFICATION, years past a junior/entry role's ideal that are still scored in full,
+/// the points lost per year beyond them, and the lowest the years score goes
+const OVERQUALIFIED_GRACE_YEARS: f32 = 3.0;
+const OVERQUALIFIED_POINTS_PER_YEAR: f32 = 5.0;
+const MIN_OVERQUALIFIED_SCORE: f32 = 60.0;
+
+/// Month name prefixes, for dates like "Jan 2020" or "September 2019"
+const MONTHS: [&str; 12] = ["jan", "feb", "mar", "apr", "may", "jun", "jul", "aug", "sep", "oct", "nov", "dec"];
+
+/// Title abbreviations for canonical levels ("Sr. Engineer", "Jr Developer")
+const TITLE_LEVEL_ABBREVIATIONS: &[(&str, &str)] = &[("sr", "senior"), ("jr", "junior"), ("intern", "entry")];
+
+/// Translates org-specific level names ("L4", "SDE II", "IC5") to canonical buckets
+#[derive(Debug, Default)]
+pub struct LevelMap {
+    aliases: HashMap<String, String>,
+}
+
+impl LevelMap {
+    /// Parse `alias=bucket` entries separated by newlines or `;`
+    /// Entries with an unknown bucket are logged and ignored
+    pub fn with_mappings(spec: &str) -> Self {
+        let mut map = Self::default();
+
+        for entry in spec.split(['\n', ';']).map(str::trim).filter(|e| !e.is_empty()) {
+            let Some((alias, bucket)) = entry.split_once('=') else {
+                println!("[EXPERIENCE] Ignoring malformed level mapping {:?}", entry);
+                continue;
+            };
+            let bucket = bucket.trim().to_lowercase();
+            if !CANONICAL_LEVELS.contains(&bucket.as_str()) {
+                println!("[EXPERIENCE] Ignoring mapping {:?}: unknown level {:?}", entry, bucket);
+                continue;
+            }
+            map.aliases.insert(alias.trim().to_lowercase(), bucket);
+        }
+
+        map
+    }
+
+    /// Build from EXPERIENCE_LEVEL_MAP (e.g. "L4=mid;L6=senior;SDE II=mid")
+    pub fn from_env() -> Self {
+        Self::with_mappings(&std::env::var("EXPERIENCE_LEVEL_MAP").unwrap_or_default())
+    }
+
+    /// Canonical bucket for a level, or None if it is neither canonical nor mapped
+    pub fn resolve(&self, level: &str) -> Option<&str> {
+        let lower = level.trim().to_lowercase();
+        if let Some(canonical) = CANONICAL_LEVELS.iter().find(|l| **l == lower) {
+            return Some(canonical);
+        }
+        self.aliases.get(&lower).map(String::as_str)
+    }
+
+    /// Canonical bucket for a level, falling back to mid (with a warning) when unknown
+    pub fn normalize(&self, level: &str) -> String {
+        match self.resolve(level) {
+            Some(bucket) => bucket.to_string(),
+            None => {
+                println!("[EXPERIENCE] Unknown experience level {:?}, defaulting to {}", level, DEFAULT_LEVEL);
+                DEFAULT_LEVEL.to_string()
+            }
+        }
+    }
+}
+
+/// The process-wide level map (configured once from env)
+fn level_map() -> &'static LevelMap {
+    static LEVEL_MAP: OnceLock<LevelMap> = OnceLock::new();
+    LEVEL_MAP.get_or_init(LevelMap::from_env)
+}
+
+/// Normalize a level with the process-wide map (configured once from env)
+pub fn normalize_level(level: &str) -> String {
+    level_map().normalize(level)
+}
+
+/// Most experience points open-source reputation can add (REPUTATION_EXPERIENCE_BONUS, 0 = off)
+pub fn reputation_bonus_max() -> i32 {
+    static MAX_POINTS: OnceLock<i32> = OnceLock::new();
+    *MAX_POINTS.get_or_init(|| {
+        let value = std::env::var("REPUTATION_EXPERIENCE_BONUS").unwrap_or_default();
+        if value.trim().is_empty() {
+            return 0;
+        }
+        match value.trim().parse::<i32>() {
+            Ok(points) if (0..=MAX_REPUTATION_BONUS).contains(&points) => points,
+            _ => {
+                println!("[EXPERIENCE] Ignoring invalid REPUTATION_EXPERIENCE_BONUS {:?}", value);
+                0
+            }
+        }
+    })
+}
+
+/// Whether junior/entry roles score down candidates far beyond their experience range (PENALIZE_OVERQUALIFICATION)
+pub fn penalize_overqualification() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("PENALIZE_OVERQUALIFICATION")
+            .map(|flag| matches!(flag.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+    })
+}
+
+/// About 1.5 points per order of magnitude of stars, capped at `max_points`
+pub fn reputation_points(reputation: &Reputation, max_points: i32) -> i32 {
+    let points = ((reputation.total_stars as f32 + 1.0).log10() * 1.5).round() as i32;
+    points.clamp(0, max_points.max(0))
+}
+
+/// Credit open-source work as a small experience bonus
+pub fn apply_reputation_bonus(score: &mut ExplainableScore, reputation: Option<&Reputation>, max_points: i32) {
+    let Some(reputation) = reputation else {
+        return;
+    };
+    let points = reputation_points(reputation, max_points);
+    if points == 0 {
+        return;
+    }
+
+    score.score = (score.score + points).min(100);
+    score.bonus.push(match &reputation.most_starred_repo {
+        Some(repo) => format!(
+            "Open source: {} stars (most starred: {}, {})",
+            reputation.total_stars, repo, reputation.most_starred_stars
+        ),
+        None => format!("Open source: {} stars", reputation.total_stars),
+    });
+}
 
 fn parse_duration(duration: &str) -> f32 {
+    // Date ranges like "Mar 2021 - Present" or "June 2018 - Dec 2020"
+    if let Some((start, end)) = parse_date_range(duration) {
+        return years_between(start, end).max(0.5);
+    }
+
     let lower = duration.to_lowercase();
     let mut years = 0.0;
     
@@ -15,7 +172,8 @@ fn parse_duration(duration: &str) -> f32 {
                 } else if unit.starts_with("month") {
                     years += num / 12.0;
                 }
-            } else {
+            } else if num < 1900.0 {
+                // A bare trailing number is years, unless it is a calendar year ("sometime in 2020")
                 years += num;
             }
         }
@@ -39,9 +197,143 @@ fn parse_duration(duration: &str) -> f32 {
     years.max(0.5) // Minimum 6 months if we found something
 }
 
+/// Seniority step of a canonical level, for comparing titles; "any" has no rank
+fn level_rank(level: &str) -> Option<i32> {
+    match level {
+        "entry" | "junior" => Some(0),
+        "mid" | "intermediate" => Some(1),
+        "senior" => Some(2),
+        "lead" | "principal" | "staff" => Some(3),
+        _ => None,
+    }
+}
+
+/// Canonical level a job title implies: the whole title or any word of it, through the level map
+/// Titles without a level word ("Software Engineer") count as mid
+fn title_level<'a>(title: &str, map: &'a LevelMap) -> &'a str {
+    if let Some(level) = map.resolve(title) {
+        return level;
+    }
+    title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .find_map(|word| {
+            TITLE_LEVEL_ABBREVIATIONS
+                .iter()
+                .find(|(abbreviation, _)| *abbreviation == word)
+                .map(|(_, level)| *level)
+                .or_else(|| map.resolve(word).filter(|level| *level != "any"))
+        })
+        .unwrap_or(DEFAULT_LEVEL)
+}
+
+/// One side of a date range: "2019", "Jan 2020", "September 2019", or "Present" (today)
+fn parse_range_date(part: &str, today: NaiveDate) -> Option<NaiveDate> {
+    let lower = part.trim().to_lowercase();
+    if matches!(lower.as_str(), "present" | "current" | "now" | "today") {
+        return Some(today);
+    }
+
+    let mut year = None;
+    let mut month = 1;
+    for word in lower.split(|c: char| !c.is_alphanumeric()).filter(|w| !w.is_empty()) {
+        if let Ok(y) = word.parse::<i32>()
+            && (1950..=2100).contains(&y)
+        {
+            year = Some(y);
+        } else if let Some(index) = MONTHS.iter().position(|m| word.starts_with(m)) {
+            month = index as u32 + 1;
+        } else {
+            return None;
+        }
+    }
+    NaiveDate::from_ymd_opt(year?, month, 1)
+}
+
+fn parse_date_range_at(range: &str, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    let range = range.to_lowercase().replace(" to ", " - ");
+    let (start, end) = range.split_once(['-', '–', '—'])?;
+    let start = parse_range_date(start, today).filter(|d| *d <= today)?;
+    let end = parse_range_date(end, today)?;
+    (end >= start).then_some((start, end))
+}
+
+/// Start and end of a dated duration: "Jan 2020 - Mar 2022", "2019-2022" or "2021 - Present"
+/// Bare years start in January; None for undated durations like "2 years"
+pub fn parse_date_range(range: &str) -> Option<(NaiveDate, NaiveDate)> {
+    parse_date_range_at(range, chrono::Utc::now().date_naive())
+}
+
+/// Calendar years between two dates, counted in months so "2019-2022" is exactly 3
+fn years_between(start: NaiveDate, end: NaiveDate) -> f32 {
+    let months = (end.year() - start.year()) * 12 + end.month() as i32 - start.month() as i32;
+    let days = end.day() as f32 - start.day() as f32;
+    (months as f32 + days / 30.44) / 12.0
+}
+
+/// When a role ran: its explicit dates if set, otherwise the range in its duration
+fn experience_interval(exp: &CandidateExperience, today: NaiveDate) -> Option<(NaiveDate, NaiveDate)> {
+    match exp.start_date {
+        Some(start) => {
+            let end = exp.end_date.unwrap_or(today);
+            (end >= start).then_some((start, end))
+        }
+        None => parse_date_range_at(&exp.duration, today),
+    }
+}
+
+/// Years of experience with overlapping dated roles counted once
+/// Undated roles can't be placed in time, so their parsed durations are added on top
+fn total_experience_years(candidate_experience: &[CandidateExperience], today: NaiveDate) -> f32 {
+    let mut intervals = Vec::new();
+    let mut undated_years = 0.0;
+    for exp in candidate_experience {
+        match experience_interval(exp, today) {
+            Some(interval) => intervals.push(interval),
+            None => undated_years += parse_duration(&exp.duration),
+        }
+    }
+    intervals.sort();
+
+    let mut merged: Vec<(NaiveDate, NaiveDate)> = Vec::new();
+    for (start, end) in intervals {
+        match merged.last_mut() {
+            Some(last) if start <= last.1 => last.1 = last.1.max(end),
+            _ => merged.push((start, end)),
+        }
+    }
+    merged.iter().map(|(start, end)| years_between(*start, *end)).sum::<f32>() + undated_years
+}
+
+/// Seniority steps climbed from the earliest role to the latest, e.g. junior to senior is 2
+/// None when any role is undated or two roles can't be put in order, since the trajectory would be a guess
+fn seniority_growth(
+    candidate_experience: &[CandidateExperience],
+    map: &LevelMap,
+    today: NaiveDate,
+) -> Option<(i32, String, String)> {
+    let mut roles = candidate_experience
+        .iter()
+        .map(|exp| experience_interval(exp, today).map(|interval| (interval, title_level(&exp.title, map))))
+        .collect::<Option<Vec<_>>>()?;
+    if roles.len() < 2 {
+        return None;
+    }
+    roles.sort_by_key(|(interval, _)| *interval);
+    if roles.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+        return None;
+    }
+
+    let first = roles.first()?.1;
+    let last = roles.last()?.1;
+    let steps = level_rank(last)? - level_rank(first)?;
+    Some((steps, first.to_string(), last.to_string()))
+}
+
 fn level_years_required(level: &str) -> (f32, f32) {
-    // Returns (min_years, ideal_years)
-    match level.to_lowercase().as_str() {
+    // Returns (min_years, ideal_years); expects a level already passed through normalize_level
+    match level {
         "entry" | "junior" => (0.0, 1.0),
         "mid" | "intermediate" => (2.0, 4.0),
         "senior" => (5.0, 8.0),
@@ -51,25 +343,41 @@ fn level_years_required(level: &str) -> (f32, f32) {
     }
 }
 
+/// Experience score, with the overqualification penalty set by PENALIZE_OVERQUALIFICATION
 pub fn calculate_experience_score(
     candidate_experience: &[CandidateExperience],
     required_level: &str,
     job_title: Option<&str>,
+) -> ExplainableScore {
+    calculate_experience_score_with(candidate_experience, required_level, job_title, penalize_overqualification())
+}
+
+/// Experience score; with `penalize_overqualification`, junior/entry roles lose points for each year
+/// a candidate is well past the role's ideal (senior and above are never penalized)
+pub fn calculate_experience_score_with(
+    candidate_experience: &[CandidateExperience],
+    required_level: &str,
+    job_title: Option<&str>,
+    penalize_overqualification: bool,
 ) -> ExplainableScore {
     let mut matched: Vec<String> = Vec::new();
     let mut missing: Vec<String> = Vec::new();
     let mut bonus: Vec<String> = Vec::new();
     
-    // Calculate total years
-    let total_years: f32 = candidate_experience.iter()
-        .map(|exp| parse_duration(&exp.duration))
-        .sum();
+    // Calculate total years, counting overlapping roles once
+    let today = chrono::Utc::now().date_naive();
+    let total_years = total_experience_years(candidate_experience, today);
     
-    let (min_years, ideal_years) = level_years_required(required_level);
+    let level = normalize_level(required_level);
+    let (min_years, ideal_years) = level_years_required(&level);
     
     // Base score from years
-    let years_score = if required_level.to_lowercase() == "any" {
+    let excess_years = total_years - ideal_years - OVERQUALIFIED_GRACE_YEARS;
+    let overqualified = penalize_overqualification && matches!(level.as_str(), "entry" | "junior") && excess_years > 0.0;
+    let years_score = if level == "any" {
         100.0
+    } else if overqualified {
+        (100.0 - excess_years * OVERQUALIFIED_POINTS_PER_YEAR).max(MIN_OVERQUALIFIED_SCORE)
     } else if total_years >= ideal_years {
         100.0
     } else if total_years >= min_years {
@@ -84,6 +392,9 @@ pub fn calculate_experience_score(
     } else {
         missing.push(format!("Need {:.1}+ years, has {:.1}", min_years, total_years));
     }
+    if overqualified {
+        missing.push(format!("Significantly exceeds level: {:.1} years for a {} role", total_years, required_level));
+    }
     
     // Role relevance bonus
     let mut relevance_bonus: f32 = 0.0;
@@ -104,6 +415,14 @@ pub fn calculate_experience_score(
         }
     }
     relevance_bonus = relevance_bonus.min(15.0);
+
+    // Growth bonus for climbing in seniority over time
+    if let Some((steps, from, to)) = seniority_growth(candidate_experience, level_map(), today)
+        && steps > 0
+    {
+        relevance_bonus += (steps * GROWTH_POINTS_PER_STEP).min(MAX_GROWTH_BONUS) as f32;
+        bonus.push(format!("Career growth: {} to {}", from, to));
+    }
     
     // Company prestige bonus (simplified)
     let top_companies = ["google", "meta", "amazon", "microsoft", "apple", "netflix", 
@@ -119,8 +438,10 @@ pub fn calculate_experience_score(
     
     let final_score = ((years_score + relevance_bonus) as i32).min(100).max(0);
     
-    let reasoning = if required_level.to_lowercase() == "any" {
+    let reasoning = if level == "any" {
         "Any experience level accepted".to_string()
+    } else if overqualified {
+        format!("Overqualified ({:.1} years for {} role)", total_years, required_level)
     } else if total_years >= ideal_years {
         format!("Exceeds experience requirement ({:.1} years for {} role)", total_years, required_level)
     } else if total_years >= min_years {
@@ -135,5 +456,219 @@ pub fn calculate_experience_score(
         missing,
         bonus,
         reasoning: Some(reasoning),
+        excluded: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_loaded_mapping_translates_org_levels() {
+        let map = LevelMap::with_mappings("SDE II=mid; L6=Senior\nIC5 = senior");
+
+        assert_eq!(map.normalize("SDE II"), "mid");
+        assert_eq!(map.normalize("l6"), "senior");
+        assert_eq!(map.normalize("IC5"), "senior");
+        assert_eq!(level_years_required(&map.normalize("L6")), level_years_required("senior"));
+        // Canonical levels pass through untouched
+        assert_eq!(map.normalize("Staff"), "staff");
+        assert_eq!(map.normalize("any"), "any");
+    }
+
+    #[test]
+    fn test_unknown_level_defaults_to_mid() {
+        let map = LevelMap::with_mappings("L6=senior;L9=wizard;garbage");
+
+        assert_eq!(map.resolve("L9"), None);
+        assert_eq!(map.resolve("Distinguished"), None);
+        assert_eq!(map.normalize("Distinguished"), "mid");
+    }
+
+    #[test]
+    fn test_starred_repo_earns_reputation_bonus() {
+        let repos: Vec<crate::github::api::GitHubRepoFull> = serde_json::from_value(serde_json::json!([
+            {"name": "fastlib", "owner": {"login": "maintainer"}, "fork": false, "description": null,
+             "stargazers_count": 5200, "forks_count": 310, "watchers_count": 5200, "language": "Rust",
+             "size": 900, "created_at": "2019-01-01T00:00:00Z", "updated_at": "2025-01-01T00:00:00Z"},
+            {"name": "dotfiles", "owner": {"login": "maintainer"}, "fork": false, "description": null,
+             "stargazers_count": 3, "forks_count": 0, "watchers_count": 3, "language": null,
+             "size": 10, "created_at": "2019-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z"},
+            {"name": "tokio", "owner": {"login": "maintainer"}, "fork": true, "description": null,
+             "stargazers_count": 90000, "forks_count": 0, "watchers_count": 0, "language": "Rust",
+             "size": 10, "created_at": "2019-01-01T00:00:00Z", "updated_at": "2024-01-01T00:00:00Z"}
+        ])).unwrap();
+
+        let reputation = Reputation::from_repos(&repos, 800);
+        assert_eq!(reputation.total_stars, 5203);
+        assert_eq!(reputation.total_forks, 310);
+        assert_eq!(reputation.most_starred_repo.as_deref(), Some("fastlib"));
+        assert_eq!(reputation.most_starred_stars, 5200);
+        assert_eq!(reputation.followers, 800);
+
+        let experience = vec![CandidateExperience {
+            title: "Engineer".to_string(),
+            company: "Acme".to_string(),
+            duration: "2 years".to_string(),
+            description: None,
+            start_date: None,
+            end_date: None,
+        }];
+        let base = calculate_experience_score(&experience, "senior", None);
+
+        let mut disabled = base.clone();
+        apply_reputation_bonus(&mut disabled, Some(&reputation), 0);
+        assert_eq!(disabled.score, base.score);
+        assert!(disabled.bonus.is_empty());
+
+        let mut enabled = base.clone();
+        apply_reputation_bonus(&mut enabled, Some(&reputation), 5);
+        assert_eq!(enabled.score, base.score + 5);
+        assert!(enabled.bonus[0].contains("fastlib"));
+
+        // Empty repos earn nothing
+        let mut empty = base.clone();
+        apply_reputation_bonus(&mut empty, Some(&Reputation::default()), 5);
+        assert_eq!(empty.score, base.score);
+    }
+
+    fn today() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2025, 6, 1).unwrap()
+    }
+
+    fn role(title: &str, duration: &str) -> CandidateExperience {
+        CandidateExperience {
+            title: title.to_string(),
+            company: "Acme".to_string(),
+            duration: duration.to_string(),
+            description: None,
+            start_date: None,
+            end_date: None,
+        }
+    }
+
+    #[test]
+    fn test_seniority_progression_earns_growth_bonus() {
+        // Listed most recent first, as resumes usually are
+        let growing = vec![
+            role("Senior Software Engineer", "2020 - Present"),
+            role("Software Engineer", "2017-2020"),
+            role("Junior Developer", "2015-2017"),
+        ];
+        let flat = vec![
+            role("Junior Developer", "2019 - Present"),
+            role("Jr. Developer", "2015-2019"),
+        ];
+
+        assert_eq!(seniority_growth(&growing, &LevelMap::default(), today()), Some((2, "junior".to_string(), "senior".to_string())));
+        assert_eq!(seniority_growth(&flat, &LevelMap::default(), today()), Some((0, "junior".to_string(), "junior".to_string())));
+
+        let grown = calculate_experience_score(&growing, "staff", None);
+        assert!(grown.bonus.contains(&"Career growth: junior to senior".to_string()));
+        let stayed = calculate_experience_score(&flat, "staff", None);
+        assert!(stayed.bonus.iter().all(|b| !b.starts_with("Career growth")));
+
+        // A closed history keeps the score under the cap, so the bonus shows in the total
+        let bounded = vec![
+            role("Senior Software Engineer", "2020-2022"),
+            role("Software Engineer", "2017-2020"),
+            role("Junior Developer", "2015-2017"),
+        ];
+        assert_eq!(seniority_growth(&bounded, &LevelMap::default(), today()), Some((2, "junior".to_string(), "senior".to_string())));
+        // Same years of experience; only the trajectory differs
+        let same_years: Vec<CandidateExperience> = bounded.iter().map(|r| role("Junior Developer", &r.duration)).collect();
+        assert_eq!(
+            calculate_experience_score(&bounded, "staff", None).score,
+            calculate_experience_score(&same_years, "staff", None).score + 8
+        );
+
+        // Org levels count through the level map
+        let map = LevelMap::with_mappings("L3=junior;L5=senior");
+        assert_eq!(seniority_growth(&[role("Engineer L5", "2021-2024"), role("Engineer L3", "2018-2021")], &map, today()).map(|g| g.0), Some(2));
+    }
+
+    #[test]
+    fn test_undated_or_ambiguous_history_skips_trajectory() {
+        let undated = vec![role("Senior Engineer", "3 years"), role("Junior Engineer", "2015-2017")];
+        assert_eq!(seniority_growth(&undated, &LevelMap::default(), today()), None);
+        assert!(calculate_experience_score(&undated, "staff", None).bonus.is_empty());
+
+        // Two roles over the same years can't be ordered
+        let overlapping = vec![role("Senior Engineer", "2018-2020"), role("Junior Engineer", "2018-2020")];
+        assert_eq!(seniority_growth(&overlapping, &LevelMap::default(), today()), None);
+
+        assert_eq!(seniority_growth(&[role("Junior Engineer", "2015-2017")], &LevelMap::default(), today()), None);
+    }
+
+    #[test]
+    fn test_parse_date_range_formats() {
+        let date = |y, m| NaiveDate::from_ymd_opt(y, m, 1).unwrap();
+        assert_eq!(parse_date_range_at("Jan 2020 - Mar 2022", today()), Some((date(2020, 1), date(2022, 3))));
+        assert_eq!(parse_date_range_at("2019-2022", today()), Some((date(2019, 1), date(2022, 1))));
+        assert_eq!(parse_date_range_at("2021 - Present", today()), Some((date(2021, 1), today())));
+        assert_eq!(parse_date_range_at("September 2018 to June 2019", today()), Some((date(2018, 9), date(2019, 6))));
+        assert_eq!(parse_date_range_at("2 years", today()), None);
+        assert_eq!(parse_date_range_at("2022-2019", today()), None);
+    }
+
+    #[test]
+    fn test_overlapping_roles_counted_once() {
+        let concurrent = vec![role("Engineer", "2019-2021"), role("Consultant", "Jan 2019 - Jan 2021")];
+        assert!((total_experience_years(&concurrent, today()) - 2.0).abs() < 0.01);
+
+        // Partial overlap merges into one span; explicit dates take precedence over the duration text
+        let mut contract = role("Contractor", "ongoing");
+        contract.start_date = NaiveDate::from_ymd_opt(2020, 1, 1);
+        contract.end_date = NaiveDate::from_ymd_opt(2023, 1, 1);
+        let partial = vec![role("Engineer", "2019-2021"), contract];
+        assert!((total_experience_years(&partial, today()) - 4.0).abs() < 0.01);
+
+        // Without parseable dates durations still add up
+        let undated = vec![role("Engineer", "2 years"), role("Consultant", "2 years")];
+        assert_eq!(total_experience_years(&undated, today()), 4.0);
+
+        let score = calculate_experience_score(&concurrent, "senior", None);
+        assert_eq!(score.missing, vec!["Need 5.0+ years, has 2.0"]);
+    }
+
+    #[test]
+    fn test_parse_duration_month_names_and_present() {
+        let since_2020 = years_between(NaiveDate::from_ymd_opt(2020, 1, 1).unwrap(), chrono::Utc::now().date_naive());
+        assert!((parse_duration("Jan 2020 - Present") - since_2020).abs() < 0.01);
+        assert!((parse_duration("June 2018 - Dec 2020") - 2.5).abs() < 0.01);
+        assert!((parse_duration("Mar 2021 – Mar 2023") - 2.0).abs() < 0.01);
+
+        // Malformed dates still get the minimum
+        assert_eq!(parse_duration("sometime in 2020"), 0.5);
+
+        // Numeric forms keep working
+        assert_eq!(parse_duration("2 years"), 2.0);
+        assert_eq!(parse_duration("18 months"), 1.5);
+        assert_eq!(parse_duration("2019-2022"), 3.0);
+        assert_eq!(parse_duration("4"), 4.0);
+    }
+
+    #[test]
+    fn test_overqualification_penalty_only_when_enabled() {
+        let veteran = vec![role("Principal Engineer", "12 years")];
+
+        let off = calculate_experience_score_with(&veteran, "junior", None, false);
+        assert_eq!(off.score, 100);
+        assert!(off.missing.is_empty());
+
+        let on = calculate_experience_score_with(&veteran, "junior", None, true);
+        assert!(on.score < 100);
+        assert_eq!(on.score, 60);
+        assert!(on.missing.iter().any(|m| m.starts_with("Significantly exceeds level")));
+
+        // Within the grace years, or on senior roles, nothing changes
+        let close = vec![role("Engineer", "3 years")];
+        assert_eq!(calculate_experience_score_with(&close, "junior", None, true).score, 100);
+        assert_eq!(
+            calculate_experience_score_with(&veteran, "senior", None, true).score,
+            calculate_experience_score_with(&veteran, "senior", None, false).score
+        );
+        assert_eq!(calculate_experience_score_with(&veteran, "lead", None, true).score, 100);
     }
 }
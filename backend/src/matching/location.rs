@@ -0,0 +1,267 @@
+use serde::{Deserialize, Serialize};
+
+use super::TalentFitScore;
+
+/// Points taken off the talent fit total when a candidate is outside an on-site job's city
+const ON_SITE_MISMATCH_PENALTY: i32 = 40;
+/// Hybrid roles only need occasional office days, so the penalty is softer
+const HYBRID_MISMATCH_PENALTY: i32 = 20;
+
+/// Common alternative names, mapped to the form jobs are usually posted with
+/// DC keeps its suffix so the state of Washington ("Seattle, Washington") isn't mistaken for it
+const LOCATION_ALIASES: &[(&str, &str)] = &[
+    ("nyc", "new york"),
+    ("new york city", "new york"),
+    ("sf", "san francisco"),
+    ("bengaluru", "bangalore"),
+    ("washington d c", "washington dc"),
+];
+
+/// Aliases that are also state codes ("Baton Rouge, LA"), so only trusted for a whole location, never a comma part
+const WHOLE_LOCATION_ALIASES: &[(&str, &str)] = &[
+    ("la", "los angeles"),
+];
+
+/// Where the person in a job has to work
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LocationRequirement {
+    OnSite { city: String },
+    Hybrid { city: String },
+    Remote,
+}
+
+impl LocationRequirement {
+    /// Requirement stored as JSONB on a job; missing or malformed values mean no requirement
+    pub fn from_stored(value: Option<serde_json::Value>) -> Option<Self> {
+        value.and_then(|v| serde_json::from_value(v).ok())
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        match self {
+            Self::OnSite { city } | Self::Hybrid { city } if normalize_location(city).is_empty() => {
+                Err("location_requirement needs a city for on_site and hybrid jobs".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::OnSite { .. } => "on_site",
+            Self::Hybrid { .. } => "hybrid",
+            Self::Remote => "remote",
+        }
+    }
+}
+
+/// Lowercased place name without punctuation, "Greater ... Area"-style wrappers or alternative names,
+/// so "Greater Sydney Area" and "sydney" compare equal
+pub fn normalize_location(location: &str) -> String {
+    let name = normalize_location_part(location);
+    alias_for(WHOLE_LOCATION_ALIASES, &name).unwrap_or(name)
+}
+
+/// `normalize_location` without the aliases that are ambiguous inside a longer location
+fn normalize_location_part(location: &str) -> String {
+    let cleaned: String = location
+        .to_lowercase()
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { ' ' })
+        .collect();
+    let mut name = cleaned.split_whitespace().collect::<Vec<_>>().join(" ");
+
+    if let Some(rest) = name.strip_prefix("greater ") {
+        name = rest.to_string();
+    }
+    for suffix in [" metropolitan area", " bay area", " metro area", " area", " metro"] {
+        if let Some(rest) = name.strip_suffix(suffix) {
+            name = rest.to_string();
+            break;
+        }
+    }
+
+    alias_for(LOCATION_ALIASES, &name).unwrap_or(name)
+}
+
+fn alias_for(aliases: &[(&str, &str)], name: &str) -> Option<String> {
+    aliases
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, canonical)| canonical.to_string())
+}
+
+/// Whether a location means the role is remote (no geographic constraint)
+pub fn is_remote_location(location: &str) -> bool {
+    matches!(normalize_location(location).as_str(), "remote" | "anywhere")
+}
+
+/// Whether the candidate's location, or a run of its comma-separated parts, is the city
+/// ("Sydney, NSW, Australia" is in Sydney, "Washington, DC, USA" in Washington DC)
+pub fn location_matches(city: &str, candidate_location: &str) -> bool {
+    let city = normalize_location(city);
+    if city.is_empty() {
+        return false;
+    }
+    if normalize_location(candidate_location) == city {
+        return true;
+    }
+
+    let parts: Vec<&str> = candidate_location.split(',').collect();
+    (0..parts.len()).any(|start| {
+        (start + 1..=parts.len()).any(|end| normalize_location_part(&parts[start..end].join(" ")) == city)
+    })
+}
+
+/// How a candidate's location compared with an on-site or hybrid job
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LocationFit {
+    /// on_site or hybrid
+    pub requirement: String,
+    pub city: String,
+    pub candidate_location: Option<String>,
+    /// None when the candidate's location is unknown
+    pub matches: Option<bool>,
+    /// Points taken off the talent fit total
+    pub penalty: i32,
+    pub reasoning: String,
+}
+
+/// Check a candidate against the job's location requirement; remote jobs (and jobs without one) are not checked
+pub fn evaluate_location(requirement: Option<&LocationRequirement>, candidate_location: Option<&str>) -> Option<LocationFit> {
+    let requirement = requirement?;
+    let (city, penalty) = match requirement {
+        LocationRequirement::OnSite { city } => (city, ON_SITE_MISMATCH_PENALTY),
+        LocationRequirement::Hybrid { city } => (city, HYBRID_MISMATCH_PENALTY),
+        LocationRequirement::Remote => return None,
+    };
+    let requirement = requirement.name().to_string();
+    // Sourcing records "Unknown" when a profile doesn't mention a location
+    let candidate_location = candidate_location
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && normalize_location(l) != "unknown");
+
+    let fit = match candidate_location {
+        None => LocationFit {
+            requirement,
+            city: city.clone(),
+            candidate_location: None,
+            matches: None,
+            penalty: 0,
+            reasoning: format!("Candidate location unknown; confirm they can work in {}", city),
+        },
+        Some(location) if location_matches(city, location) => LocationFit {
+            requirement,
+            city: city.clone(),
+            candidate_location: Some(location.to_string()),
+            matches: Some(true),
+            penalty: 0,
+            reasoning: format!("Based in {}", city),
+        },
+        Some(location) => LocationFit {
+            reasoning: format!("Based in {}, outside {} ({} role, -{} points)", location, city, requirement.replace('_', "-"), penalty),
+            requirement,
+            city: city.clone(),
+            candidate_location: Some(location.to_string()),
+            matches: Some(false),
+            penalty,
+        },
+    };
+    Some(fit)
+}
+
+/// Apply the job's location requirement to an aggregated score and record the check in the breakdown
+pub fn apply_location_requirement(
+    score: &mut TalentFitScore,
+    requirement: Option<&LocationRequirement>,
+    candidate_location: Option<&str>,
+) {
+    let Some(fit) = evaluate_location(requirement, candidate_location) else {
+        return;
+    };
+    score.total = (score.total - fit.penalty).max(0);
+    score.breakdown.location = Some(fit);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{calculate_talent_fit, ExplainableScore};
+
+    fn score(total: i32) -> TalentFitScore {
+        let component = ExplainableScore {
+            score: total,
+            matched: vec![],
+            missing: vec![],
+            bonus: vec![],
+            reasoning: None,
+            excluded: false,
+        };
+        calculate_talent_fit(component.clone(), component.clone(), component.clone(), component, None)
+    }
+
+    #[test]
+    fn test_on_site_job_penalizes_far_away_candidate() {
+        let on_site = LocationRequirement::OnSite { city: "Sydney".to_string() };
+
+        let mut far = score(90);
+        apply_location_requirement(&mut far, Some(&on_site), Some("Berlin, Germany"));
+        assert_eq!(far.total, 50);
+        let fit = far.breakdown.location.unwrap();
+        assert_eq!(fit.matches, Some(false));
+        assert_eq!(fit.penalty, 40);
+        assert!(fit.reasoning.contains("outside Sydney"));
+
+        // The local candidate now outranks the stronger one abroad
+        let mut local = score(70);
+        apply_location_requirement(&mut local, Some(&on_site), Some("Greater Sydney Area, NSW, Australia"));
+        assert_eq!(local.total, 70);
+        assert_eq!(local.breakdown.location.unwrap().matches, Some(true));
+
+        let mut hybrid = score(90);
+        apply_location_requirement(&mut hybrid, Some(&LocationRequirement::Hybrid { city: "NYC".to_string() }), Some("Lisbon"));
+        assert_eq!(hybrid.total, 70);
+
+        // An unknown location is surfaced but not penalized
+        let mut unknown = score(90);
+        apply_location_requirement(&mut unknown, Some(&on_site), Some("Unknown"));
+        assert_eq!(unknown.total, 90);
+        assert_eq!(unknown.breakdown.location.unwrap().matches, None);
+    }
+
+    #[test]
+    fn test_remote_job_does_not_penalize_location() {
+        let mut far = score(90);
+        apply_location_requirement(&mut far, Some(&LocationRequirement::Remote), Some("Berlin, Germany"));
+        assert_eq!(far.total, 90);
+        assert!(far.breakdown.location.is_none());
+
+        let mut no_requirement = score(90);
+        apply_location_requirement(&mut no_requirement, None, Some("Berlin, Germany"));
+        assert_eq!(no_requirement.total, 90);
+    }
+
+    #[test]
+    fn test_location_normalization() {
+        assert_eq!(normalize_location("  San Francisco Bay Area "), "san francisco");
+        assert_eq!(normalize_location("New York City"), "new york");
+        assert!(location_matches("New York", "NYC, NY"));
+        assert!(location_matches("washington dc", "Washington D.C. Metro Area"));
+        assert!(location_matches("Washington, DC", "Washington, DC, USA"));
+        assert!(location_matches("LA", "Los Angeles, CA"));
+        assert!(location_matches("Los Angeles", "LA"));
+        assert!(is_remote_location(" Remote "));
+        assert!(!location_matches("", "Sydney"));
+
+        // State names and codes inside a longer location aren't cities
+        assert!(!location_matches("Los Angeles", "Baton Rouge, LA"));
+        assert!(!location_matches("LA", "Baton Rouge, LA"));
+        assert!(!location_matches("Washington DC", "Seattle, Washington"));
+        assert!(!location_matches("Washington D.C.", "Spokane, WA, Washington, USA"));
+
+        let parsed: LocationRequirement = serde_json::from_value(serde_json::json!({"type": "on_site", "city": "Sydney"})).unwrap();
+        assert_eq!(parsed, LocationRequirement::OnSite { city: "Sydney".to_string() });
+        assert!(LocationRequirement::Hybrid { city: " ".to_string() }.validate().is_err());
+        assert!(LocationRequirement::Remote.validate().is_ok());
+    }
+}
@@ -2,9 +2,19 @@ pub mod skills;
 pub mod experience;
 pub mod team_fit;
 pub mod culture;
+pub mod skill_matrix;
+pub mod location;
 
+use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
+use crate::github::stats::Reputation;
+use experience::{apply_reputation_bonus, calculate_experience_score, reputation_bonus_max};
+use skills::{calculate_skill_score, match_tuning, pipeline_skill_score};
+use team_fit::{calculate_team_fit_score_weighted, compute_ideal_profile, CodeCharacteristics, TeamFitWeights, TeamMemberProfile, WorkStyle};
+use culture::{calculate_culture_score, calculate_culture_score_offline};
+use location::{apply_location_requirement, LocationFit, LocationRequirement};
+
 /// Required skill with level and mandatory flag
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RequiredSkill {
@@ -13,6 +23,98 @@ pub struct RequiredSkill {
     pub mandatory: Option<bool>,    // true = required, false = nice-to-have
 }
 
+const KNOWN_LEVELS: &[&str] = &["beginner", "familiar", "intermediate", "proficient", "advanced", "expert"];
+
+/// Result of parsing a job's required_skills JSON
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct ParsedRequiredSkills {
+    pub skills: Vec<RequiredSkill>,
+    pub warnings: Vec<String>,
+}
+
+/// Parse required_skills JSON - supports both legacy and enhanced formats
+/// Legacy: ["Python", "React"]
+/// Enhanced: [{"name": "Python", "level": "advanced", "mandatory": true}]
+/// Entries are normalized to the enhanced format (level defaults to
+/// intermediate, mandatory to true); anything dropped gets a warning
+pub fn parse_required_skills_with_warnings(json_value: &serde_json::Value) -> ParsedRequiredSkills {
+    let mut parsed = ParsedRequiredSkills::default();
+
+    let arr = match json_value.as_array() {
+        Some(arr) => arr,
+        None => {
+            if !json_value.is_null() {
+                parsed.warnings.push("required_skills must be an array; nothing was parsed".to_string());
+            }
+            return parsed;
+        }
+    };
+
+    for (i, item) in arr.iter().enumerate() {
+        if let Some(s) = item.as_str() {
+            // Legacy format: just a string
+            let name = s.trim();
+            if name.is_empty() {
+                parsed.warnings.push(format!("Entry {}: empty skill name dropped", i));
+                continue;
+            }
+            parsed.skills.push(RequiredSkill {
+                name: name.to_string(),
+                level: Some("intermediate".to_string()),
+                mandatory: Some(true),
+            });
+        } else if let Some(obj) = item.as_object() {
+            // Enhanced format: object with name, level, mandatory
+            let name = match obj.get("name").and_then(|v| v.as_str()).map(str::trim) {
+                Some(name) if !name.is_empty() => name,
+                _ => {
+                    parsed.warnings.push(format!("Entry {}: object without a \"name\" string dropped", i));
+                    continue;
+                }
+            };
+
+            let level = match obj.get("level") {
+                None | Some(serde_json::Value::Null) => "intermediate".to_string(),
+                Some(serde_json::Value::String(level)) => {
+                    let level = level.trim().to_lowercase();
+                    if !KNOWN_LEVELS.contains(&level.as_str()) {
+                        parsed.warnings.push(format!("Entry {} ({}): unrecognized level \"{}\"", i, name, level));
+                    }
+                    level
+                }
+                Some(_) => {
+                    parsed.warnings.push(format!("Entry {} ({}): level is not a string, using intermediate", i, name));
+                    "intermediate".to_string()
+                }
+            };
+
+            let mandatory = match obj.get("mandatory") {
+                None | Some(serde_json::Value::Null) => true,
+                Some(serde_json::Value::Bool(b)) => *b,
+                Some(_) => {
+                    parsed.warnings.push(format!("Entry {} ({}): mandatory is not a boolean, using true", i, name));
+                    true
+                }
+            };
+
+            parsed.skills.push(RequiredSkill {
+                name: name.to_string(),
+                level: Some(level),
+                mandatory: Some(mandatory),
+            });
+        } else {
+            parsed.warnings.push(format!("Entry {}: expected a string or object, got {}", i, item));
+        }
+    }
+
+    parsed
+}
+
+/// Parse required_skills JSON, discarding warnings
+pub fn parse_required_skills(json_value: &serde_json::Value) -> Vec<RequiredSkill> {
+    parse_required_skills_with_warnings(json_value).skills
+}
+
 /// Candidate skill with proficiency level
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CandidateSkill {
@@ -27,6 +129,12 @@ pub struct CandidateExperience {
     pub company: String,
     pub duration: String,
     pub description: Option<String>,
+    /// When set, used instead of the dates in `duration` to place the role in time
+    #[serde(default)]
+    pub start_date: Option<NaiveDate>,
+    /// None with a start date means the role is ongoing
+    #[serde(default)]
+    pub end_date: Option<NaiveDate>,
 }
 
 /// Explainable score with reasoning
@@ -37,6 +145,9 @@ pub struct ExplainableScore {
     pub missing: Vec<String>,
     pub bonus: Vec<String>,
     pub reasoning: Option<String>,
+    /// Left out of the talent fit total, with its weight spread over the other components
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub excluded: bool,
 }
 
 /// Complete talent fit score with breakdown
@@ -53,10 +164,19 @@ pub struct ScoreBreakdown {
     pub experience: ExplainableScore,
     pub team_fit: ExplainableScore,
     pub culture: ExplainableScore,
+    /// No team was assigned, so team fit was left out and its weight redistributed
+    #[serde(default)]
+    pub team_fit_excluded: bool,
+    /// The job has no required skills and NO_REQUIRED_SKILLS_POLICY=exclude, so skills were left out
+    #[serde(default)]
+    pub skills_excluded: bool,
+    /// Check against an on-site or hybrid job's city; its penalty is already taken off the total
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<LocationFit>,
 }
 
 /// Weights for score components (must sum to 1.0)
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ScoreWeights {
     pub skills: f32,        // Default: 0.40
     pub experience: f32,    // Default: 0.30
@@ -75,7 +195,61 @@ impl Default for ScoreWeights {
     }
 }
 
+impl ScoreWeights {
+    /// Weights from JSON with all four fields present; None when a field is missing or a weight is unusable
+    pub fn from_json(value: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value::<Self>(value.clone()).ok()?.normalized()
+    }
+
+    /// Weights stored as JSONB on a job; missing or invalid values mean the defaults apply
+    pub fn from_stored(value: Option<serde_json::Value>) -> Option<Self> {
+        value.as_ref().and_then(Self::from_json)
+    }
+
+    /// Weights scaled to sum to 1.0 (with a warning when they didn't)
+    /// None when any weight is negative or not finite, or all of them are zero
+    pub fn normalized(self) -> Option<Self> {
+        let weights = [self.skills, self.experience, self.team_fit, self.culture];
+        if weights.iter().any(|w| !w.is_finite() || *w < 0.0) {
+            return None;
+        }
+        let total: f32 = weights.iter().sum();
+        if total <= 0.0 {
+            return None;
+        }
+        if (total - 1.0).abs() <= 0.001 {
+            return Some(self);
+        }
+
+        println!("[MATCHING] Score weights sum to {:.3}, normalizing to 1.0", total);
+        Some(Self {
+            skills: self.skills / total,
+            experience: self.experience / total,
+            team_fit: self.team_fit / total,
+            culture: self.culture / total,
+        })
+    }
+
+    /// Weights with team fit dropped and its share spread over the other components in proportion
+    pub fn without_team_fit(&self) -> Self {
+        let total = self.skills + self.experience + self.team_fit + self.culture;
+        let rest = self.skills + self.experience + self.culture;
+        if rest <= 0.0 {
+            return Self::default().without_team_fit();
+        }
+
+        let scale = total / rest;
+        Self {
+            skills: self.skills * scale,
+            experience: self.experience * scale,
+            team_fit: 0.0,
+            culture: self.culture * scale,
+        }
+    }
+}
+
 /// Calculate aggregate talent fit score
+/// Components marked `excluded` are left out and the remaining weights scaled up to the same total
 pub fn calculate_talent_fit(
     skills_score: ExplainableScore,
     experience_score: ExplainableScore,
@@ -84,21 +258,356 @@ pub fn calculate_talent_fit(
     weights: Option<ScoreWeights>,
 ) -> TalentFitScore {
     let w = weights.unwrap_or_default();
-    
-    let total = (
-        skills_score.score as f32 * w.skills +
-        experience_score.score as f32 * w.experience +
-        team_fit_score.score as f32 * w.team_fit +
-        culture_score.score as f32 * w.culture
-    ).round() as i32;
-    
+
+    let components = [
+        (&skills_score, w.skills),
+        (&experience_score, w.experience),
+        (&team_fit_score, w.team_fit),
+        (&culture_score, w.culture),
+    ];
+    let total_weight: f32 = components.iter().map(|(_, weight)| weight).sum();
+    let included_weight: f32 = components.iter().filter(|(s, _)| !s.excluded).map(|(_, weight)| weight).sum();
+    let scale = if included_weight > 0.0 { total_weight / included_weight } else { 0.0 };
+
+    let total = components
+        .iter()
+        .filter(|(s, _)| !s.excluded)
+        .map(|(s, weight)| s.score as f32 * weight * scale)
+        .sum::<f32>()
+        .round() as i32;
+
     TalentFitScore {
         total: total.min(100).max(0),
         breakdown: ScoreBreakdown {
+            skills_excluded: skills_score.excluded,
+            team_fit_excluded: team_fit_score.excluded,
             skills: skills_score,
             experience: experience_score,
             team_fit: team_fit_score,
             culture: culture_score,
+            location: None,
         },
     }
 }
+
+/// Aggregate talent fit when no team is assigned: skills, experience and culture only,
+/// with the team-fit weight redistributed across them
+pub fn calculate_talent_fit_without_team(
+    skills_score: ExplainableScore,
+    experience_score: ExplainableScore,
+    culture_score: ExplainableScore,
+    weights: Option<ScoreWeights>,
+) -> TalentFitScore {
+    let excluded = ExplainableScore {
+        score: 0,
+        matched: vec![],
+        missing: vec![],
+        bonus: vec![],
+        reasoning: Some("Excluded: no team assigned (weight redistributed)".to_string()),
+        excluded: true,
+    };
+
+    calculate_talent_fit(
+        skills_score,
+        experience_score,
+        excluded,
+        culture_score,
+        Some(weights.unwrap_or_default().without_team_fit()),
+    )
+}
+
+/// Candidate data the matching pipeline scores
+pub struct ScoringCandidate<'a> {
+    pub skills: &'a [CandidateSkill],
+    pub experience: &'a [CandidateExperience],
+    pub developer_profile: Option<&'a str>,
+    /// Adds a small experience bonus when REPUTATION_EXPERIENCE_BONUS is set
+    pub reputation: Option<&'a Reputation>,
+    /// Checked against the job's location requirement
+    pub location: Option<&'a str>,
+    /// Compared with the team's work styles for team fit
+    pub work_style: Option<&'a WorkStyle>,
+    /// Compared with the team's code-style target for team fit
+    pub code_characteristics: Option<&'a CodeCharacteristics>,
+}
+
+/// Job data the matching pipeline scores against
+pub struct ScoringJob<'a> {
+    pub title: &'a str,
+    pub description: Option<&'a str>,
+    pub required_skills: &'a [RequiredSkill],
+    pub experience_level: &'a str,
+    pub team_fit_weights: &'a TeamFitWeights,
+    /// On-site and hybrid requirements penalize candidates outside the city
+    pub location_requirement: Option<&'a LocationRequirement>,
+}
+
+/// The hiring team; pass None when no team is assigned and team fit is left out
+pub struct ScoringTeam<'a> {
+    /// Developer profiles of the team members (used for culture fit)
+    pub profiles: &'a [String],
+    /// Skills and styles of the team members (used for team fit)
+    pub members: &'a [TeamMemberProfile],
+}
+
+/// Per-request scoring options
+#[derive(Default)]
+pub struct ScoringConfig {
+    /// Component weights; defaults when None
+    pub weights: Option<ScoreWeights>,
+    /// Override the configured temperature for the AI scoring calls
+    pub temperature: Option<f64>,
+    /// Skip AI calls so the same input always gives the same score
+    pub deterministic: bool,
+}
+
+/// Candidate summary for AI skill analysis: skills, roles and the developer profile when there is one
+fn candidate_description(candidate: &ScoringCandidate) -> String {
+    let mut lines: Vec<String> = Vec::new();
+    if !candidate.skills.is_empty() {
+        let skills: Vec<String> = candidate.skills.iter().map(|s| format!("{} ({})", s.name, s.level)).collect();
+        lines.push(format!("Skills: {}", skills.join(", ")));
+    }
+    for role in candidate.experience {
+        let mut line = format!("- {} at {} ({})", role.title, role.company, role.duration);
+        if let Some(description) = role.description.as_deref().filter(|d| !d.trim().is_empty()) {
+            line.push_str(&format!(": {}", description.trim()));
+        }
+        lines.push(line);
+    }
+    if let Some(profile) = candidate.developer_profile.filter(|p| !p.trim().is_empty()) {
+        lines.push(format!("Profile: {}", profile.trim()));
+    }
+    lines.join("\n")
+}
+
+/// Job summary for AI skill analysis
+fn job_info(job: &ScoringJob) -> String {
+    let skills: Vec<String> = job.required_skills.iter()
+        .map(|s| match &s.level {
+            Some(level) => format!("{} ({})", s.name, level),
+            None => s.name.clone(),
+        })
+        .collect();
+
+    let mut info = format!("Title: {}\nLevel: {}\nRequired skills: {}", job.title, job.experience_level, skills.join(", "));
+    if let Some(description) = job.description.filter(|d| !d.trim().is_empty()) {
+        info.push_str(&format!("\nDescription: {}", description.trim()));
+    }
+    info
+}
+
+/// The full matching pipeline: skills, experience, team fit and culture, aggregated into a talent fit score
+/// Shared by sourcing, job matching and the score preview so they can't drift apart
+pub async fn score(
+    candidate: &ScoringCandidate<'_>,
+    job: &ScoringJob<'_>,
+    team: Option<&ScoringTeam<'_>>,
+    config: &ScoringConfig,
+) -> TalentFitScore {
+    // 1. Skills (AI-blended with USE_AI_SKILLS, unless deterministic)
+    let skills_score = if config.deterministic {
+        calculate_skill_score(candidate.skills, job.required_skills, match_tuning())
+    } else {
+        pipeline_skill_score(
+            candidate.skills,
+            job.required_skills,
+            &candidate_description(candidate),
+            &job_info(job),
+            config.temperature,
+        ).await
+    };
+
+    // 2. Experience
+    let mut experience_score = calculate_experience_score(
+        candidate.experience,
+        job.experience_level,
+        Some(job.title),
+    );
+    apply_reputation_bonus(&mut experience_score, candidate.reputation, reputation_bonus_max());
+
+    // 3. Culture
+    let team_profiles = team.map(|t| t.profiles).unwrap_or_default();
+    let culture_score = if config.deterministic {
+        calculate_culture_score_offline(candidate.developer_profile, job.description, team_profiles)
+    } else {
+        calculate_culture_score(candidate.developer_profile, job.description, team_profiles, config.temperature).await
+    };
+
+    // No team assigned: leave team fit out and renormalize the other weights
+    let mut talent_fit = match team {
+        None => calculate_talent_fit_without_team(skills_score, experience_score, culture_score, config.weights.clone()),
+        Some(team) => {
+            // 4. Team fit against the team's members and the ideal next hire they imply
+            let candidate_skill_names: Vec<String> = candidate.skills.iter().map(|s| s.name.clone()).collect();
+            let required_skill_names: Vec<String> = job.required_skills.iter().map(|s| s.name.clone()).collect();
+            let ideal = compute_ideal_profile(team.members, &required_skill_names);
+            let team_fit_score = calculate_team_fit_score_weighted(
+                &candidate_skill_names,
+                candidate.work_style,
+                candidate.code_characteristics,
+                team.members,
+                Some(&ideal),
+                job.team_fit_weights,
+            );
+
+            // 5. Aggregate
+            calculate_talent_fit(skills_score, experience_score, team_fit_score, culture_score, config.weights.clone())
+        }
+    };
+
+    // 6. Location is a hard requirement on top of the weighted score, not a component of it
+    apply_location_requirement(&mut talent_fit, job.location_requirement, candidate.location);
+    talent_fit
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[rocket::async_test]
+    async fn test_score_matches_component_pipeline() {
+        let skills = vec![
+            CandidateSkill { name: "Rust".to_string(), level: "expert".to_string() },
+            CandidateSkill { name: "Docker".to_string(), level: "advanced".to_string() },
+        ];
+        let experience = vec![CandidateExperience {
+            title: "Backend Engineer".to_string(),
+            company: "Acme".to_string(),
+            duration: "5 years".to_string(),
+            description: None,
+            start_date: None,
+            end_date: None,
+        }];
+        let required = parse_required_skills(&serde_json::json!(["Rust", {"name": "Kubernetes", "mandatory": false}]));
+        let weights = TeamFitWeights::default();
+        let profiles = vec!["Mentors juniors, prefers written design docs".to_string()];
+
+        let candidate = ScoringCandidate {
+            skills: &skills,
+            experience: &experience,
+            developer_profile: Some("Collaborative engineer who enjoys mentoring"),
+            reputation: None,
+            location: None,
+            work_style: None,
+            code_characteristics: None,
+        };
+        let job = ScoringJob {
+            title: "Senior Backend Engineer",
+            description: Some("Collaborative team, lots of mentoring"),
+            required_skills: &required,
+            experience_level: "senior",
+            team_fit_weights: &weights,
+            location_requirement: None,
+        };
+        let config = ScoringConfig { deterministic: true, ..ScoringConfig::default() };
+
+        // The same fixture scored component by component, as the endpoints used to inline it
+        let skills_score = calculate_skill_score(&skills, &required, match_tuning());
+        let experience_score = calculate_experience_score(&experience, "senior", Some("Senior Backend Engineer"));
+        let culture = |team: &[String]| calculate_culture_score_offline(
+            Some("Collaborative engineer who enjoys mentoring"),
+            Some("Collaborative team, lots of mentoring"),
+            team,
+        );
+
+        let with_team = score(&candidate, &job, Some(&ScoringTeam { profiles: &profiles, members: &[] }), &config).await;
+        let inline = calculate_talent_fit(
+            skills_score.clone(),
+            experience_score.clone(),
+            team_fit::calculate_team_fit_score(&["Rust".to_string(), "Docker".to_string()], None, None, &[], None),
+            culture(&profiles),
+            None,
+        );
+        assert_eq!(serde_json::to_value(&with_team).unwrap(), serde_json::to_value(&inline).unwrap());
+
+        let no_team = score(&candidate, &job, None, &config).await;
+        let inline = calculate_talent_fit_without_team(skills_score, experience_score, culture(&[]), None);
+        assert_eq!(serde_json::to_value(&no_team).unwrap(), serde_json::to_value(&inline).unwrap());
+        assert!(no_team.breakdown.team_fit_excluded);
+    }
+
+    #[rocket::async_test]
+    async fn test_score_uses_team_members_for_team_fit() {
+        let skills = vec![CandidateSkill { name: "Kubernetes".to_string(), level: "advanced".to_string() }];
+        let required = parse_required_skills(&serde_json::json!(["Rust", "Kubernetes"]));
+        let weights = TeamFitWeights::default();
+        let members = vec![TeamMemberProfile {
+            skills: vec!["Rust".to_string()],
+            experience_level: "senior".to_string(),
+            work_style: None,
+            code_characteristics: None,
+        }];
+
+        let candidate = ScoringCandidate {
+            skills: &skills,
+            experience: &[],
+            developer_profile: None,
+            reputation: None,
+            location: None,
+            work_style: None,
+            code_characteristics: None,
+        };
+        let job = ScoringJob {
+            title: "Platform Engineer",
+            description: None,
+            required_skills: &required,
+            experience_level: "any",
+            team_fit_weights: &weights,
+            location_requirement: None,
+        };
+        let config = ScoringConfig { deterministic: true, ..ScoringConfig::default() };
+
+        // The team already has Rust, so Kubernetes is the one gap and the candidate fills it
+        let scored = score(&candidate, &job, Some(&ScoringTeam { profiles: &[], members: &members }), &config).await;
+        assert_eq!(scored.breakdown.team_fit.score, 100);
+        assert!(scored.breakdown.team_fit.bonus.contains(&"Fills skill gap: Kubernetes".to_string()));
+
+        // Without member data the team score stays neutral
+        let neutral = score(&candidate, &job, Some(&ScoringTeam { profiles: &[], members: &[] }), &config).await;
+        assert_eq!(neutral.breakdown.team_fit.score, 75);
+    }
+
+    #[test]
+    fn test_score_weights_from_json() {
+        let custom = ScoreWeights::from_json(&serde_json::json!({
+            "skills": 0.25, "experience": 0.25, "team_fit": 0.1, "culture": 0.4
+        }))
+        .unwrap();
+        assert_eq!(custom, ScoreWeights { skills: 0.25, experience: 0.25, team_fit: 0.1, culture: 0.4 });
+
+        let component = |score: i32| ExplainableScore {
+            score,
+            matched: vec![],
+            missing: vec![],
+            bonus: vec![],
+            reasoning: None,
+            excluded: false,
+        };
+        // Culture-heavy weights lift a strong culture fit over the default split
+        let fit = |weights: Option<ScoreWeights>| {
+            calculate_talent_fit(component(40), component(40), component(40), component(100), weights).total
+        };
+        assert_eq!(fit(Some(custom)), 64);
+        assert_eq!(fit(None), 46);
+
+        // A missing field (or a negative weight) falls back to the defaults
+        let partial = ScoreWeights::from_json(&serde_json::json!({"skills": 0.7, "experience": 0.3, "team_fit": 0.0}));
+        assert_eq!(partial, None);
+        assert_eq!(fit(partial), fit(Some(ScoreWeights::default())));
+        assert_eq!(ScoreWeights::from_stored(Some(serde_json::json!({"skills": -1, "experience": 1, "team_fit": 1, "culture": 1}))), None);
+        assert_eq!(ScoreWeights::from_stored(None), None);
+    }
+
+    #[test]
+    fn test_score_weights_normalized_when_not_summing_to_one() {
+        let equal = ScoreWeights::from_json(&serde_json::json!({
+            "skills": 0.5, "experience": 0.5, "team_fit": 0.5, "culture": 0.5
+        }))
+        .unwrap();
+        assert_eq!(equal, ScoreWeights { skills: 0.25, experience: 0.25, team_fit: 0.25, culture: 0.25 });
+
+        let zeros = serde_json::json!({"skills": 0, "experience": 0, "team_fit": 0, "culture": 0});
+        assert_eq!(ScoreWeights::from_json(&zeros), None);
+    }
+}
@@ -0,0 +1,144 @@
+use serde::Serialize;
+
+use super::{CandidateSkill, RequiredSkill};
+use super::skills::{best_skill_match, MatchTuning, SkillMatchCell};
+
+/// One candidate's row: a cell per required skill, in the job's order
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillMatrixRow {
+    pub candidate_id: String,
+    pub name: String,
+    pub cells: Vec<SkillMatchCell>,
+    /// Required skills this candidate matched at all
+    pub matched_count: usize,
+}
+
+/// How many candidates in the pool match a required skill
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SkillCoverage {
+    pub skill: String,
+    pub mandatory: bool,
+    pub candidates_matched: usize,
+    pub total_candidates: usize,
+    pub summary: String,
+}
+
+/// Which linked candidate has which required skill
+#[derive(Debug, Clone, Serialize)]
+pub struct SkillMatrix {
+    pub skills: Vec<String>,
+    pub candidates: Vec<SkillMatrixRow>,
+    pub coverage: Vec<SkillCoverage>,
+}
+
+/// A candidate to place in the matrix
+pub struct MatrixCandidate {
+    pub id: String,
+    pub name: String,
+    pub skills: Vec<CandidateSkill>,
+}
+
+/// Match every candidate against every required skill; candidates without skills miss everything
+pub fn build_skill_matrix(
+    required_skills: &[RequiredSkill],
+    candidates: &[MatrixCandidate],
+    tuning: &MatchTuning,
+) -> SkillMatrix {
+    let rows: Vec<SkillMatrixRow> = candidates
+        .iter()
+        .map(|candidate| {
+            let cells: Vec<SkillMatchCell> = required_skills
+                .iter()
+                .map(|req| best_skill_match(&candidate.skills, &req.name, tuning))
+                .collect();
+            SkillMatrixRow {
+                candidate_id: candidate.id.clone(),
+                name: candidate.name.clone(),
+                matched_count: cells.iter().filter(|c| c.match_type.is_some()).count(),
+                cells,
+            }
+        })
+        .collect();
+
+    let coverage = required_skills
+        .iter()
+        .enumerate()
+        .map(|(i, req)| {
+            let matched = rows.iter().filter(|row| row.cells[i].match_type.is_some()).count();
+            SkillCoverage {
+                skill: req.name.clone(),
+                mandatory: req.mandatory.unwrap_or(true),
+                candidates_matched: matched,
+                total_candidates: rows.len(),
+                summary: format!("{} of {} candidates know {}", matched, rows.len(), req.name),
+            }
+        })
+        .collect();
+
+    SkillMatrix {
+        skills: required_skills.iter().map(|r| r.name.clone()).collect(),
+        candidates: rows,
+        coverage,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn required(name: &str) -> RequiredSkill {
+        RequiredSkill { name: name.to_string(), level: Some("advanced".to_string()), mandatory: Some(true) }
+    }
+
+    fn candidate(id: &str, skills: &[&str]) -> MatrixCandidate {
+        MatrixCandidate {
+            id: id.to_string(),
+            name: id.to_uppercase(),
+            skills: skills
+                .iter()
+                .map(|s| CandidateSkill { name: s.to_string(), level: "advanced".to_string() })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_matrix_cells_and_coverage() {
+        let required = vec![required("Rust"), required("Kubernetes"), required("PostgreSQL")];
+        let candidates = vec![
+            candidate("alice", &["Rust", "k8s"]),
+            candidate("bob", &["Postgres", "React"]),
+        ];
+
+        let matrix = build_skill_matrix(&required, &candidates, &MatchTuning::default());
+        assert_eq!(matrix.skills, vec!["Rust", "Kubernetes", "PostgreSQL"]);
+
+        let types = |row: &SkillMatrixRow| -> Vec<Option<String>> {
+            row.cells.iter().map(|c| c.match_type.clone()).collect()
+        };
+        let alice = &matrix.candidates[0];
+        assert_eq!(types(alice), vec![Some("exact".to_string()), Some("synonym".to_string()), None]);
+        assert_eq!(alice.cells[0].score, 1.0);
+        assert_eq!(alice.cells[1].matched_skill.as_deref(), Some("k8s"));
+        assert_eq!(alice.cells[2].score, 0.0);
+        assert_eq!(alice.matched_count, 2);
+
+        let bob = &matrix.candidates[1];
+        assert_eq!(types(bob), vec![None, None, Some("synonym".to_string())]);
+        assert_eq!(bob.cells[2].score, 0.95);
+
+        let counts: Vec<usize> = matrix.coverage.iter().map(|c| c.candidates_matched).collect();
+        assert_eq!(counts, vec![1, 1, 1]);
+        assert_eq!(matrix.coverage[1].summary, "1 of 2 candidates know Kubernetes");
+    }
+
+    #[test]
+    fn test_candidate_without_skills_misses_everything() {
+        let required = vec![required("Rust"), required("Go")];
+        let matrix = build_skill_matrix(&required, &[candidate("empty", &[])], &MatchTuning::default());
+
+        let row = &matrix.candidates[0];
+        assert_eq!(row.matched_count, 0);
+        assert!(row.cells.iter().all(|c| c.match_type.is_none() && c.score == 0.0));
+        assert!(matrix.coverage.iter().all(|c| c.candidates_matched == 0 && c.total_candidates == 1));
+    }
+}
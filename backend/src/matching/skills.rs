@@ -1,12 +1,18 @@
 use super::{CandidateSkill, RequiredSkill, ExplainableScore};
 use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, OnceLock, RwLock};
 use genai::chat::{ChatMessage, ChatRequest};
 use genai::Client;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use sqlx::{PgConnection, Row};
+use crate::ai_response::{clamp_score, log_fallback, parse_json, AiResponseError};
+use crate::ai_temperature::{chat_options, temperatures};
 
 #[derive(Deserialize)]
 struct AISkillAnalysis {
-    score: i32,
+    /// Accepts fractional scores; clamped to 0-100 before use
+    score: f64,
     reasoning: String,
     strengths: Vec<String>,
     gaps: Vec<String>,
@@ -52,7 +58,20 @@ fn level_weight(level: &str) -> f32 {
     }
 }
 
-fn get_synonyms() -> HashMap<&'static str, Vec<&'static str>> {
+/// Skill level for a language making up `percentage` of a user's GitHub repos
+pub fn github_language_level(percentage: u32) -> &'static str {
+    match percentage {
+        50.. => "expert",
+        25..=49 => "advanced",
+        10..=24 => "intermediate",
+        _ => "beginner",
+    }
+}
+
+/// Canonical skill name to its aliases, all normalized
+pub type SynonymMap = HashMap<String, Vec<String>>;
+
+fn builtin_synonyms() -> HashMap<&'static str, Vec<&'static str>> {
     let mut map = HashMap::new();
     map.insert("javascript", vec!["js", "ecmascript", "es6"]);
     map.insert("typescript", vec!["ts"]);
@@ -69,11 +88,279 @@ fn get_synonyms() -> HashMap<&'static str, Vec<&'static str>> {
     map
 }
 
-fn normalize_skill(name: &str) -> String {
+/// The built-in synonyms, normalized
+pub fn default_synonyms() -> SynonymMap {
+    merge_synonyms(std::iter::empty())
+}
+
+/// Built-in synonyms plus `(canonical, alias)` pairs; duplicates and self-aliases are dropped
+fn merge_synonyms(extra: impl IntoIterator<Item = (String, String)>) -> SynonymMap {
+    let mut map: SynonymMap = HashMap::new();
+    let builtin = builtin_synonyms()
+        .into_iter()
+        .flat_map(|(canonical, aliases)| aliases.into_iter().map(move |alias| (canonical.to_string(), alias.to_string())));
+
+    for (canonical, alias) in builtin.chain(extra) {
+        let canonical = normalize_skill(&canonical);
+        let alias = normalize_skill(&alias);
+        if canonical.is_empty() {
+            continue;
+        }
+        let aliases = map.entry(canonical.clone()).or_default();
+        if !alias.is_empty() && alias != canonical && !aliases.contains(&alias) {
+            aliases.push(alias);
+        }
+    }
+    map
+}
+
+/// Built-in synonyms merged with the rows of the skill_synonyms table
+pub async fn load_synonyms(conn: &mut PgConnection) -> Result<SynonymMap, sqlx::Error> {
+    let rows = sqlx::query("SELECT canonical, alias FROM skill_synonyms")
+        .fetch_all(conn)
+        .await?;
+    Ok(merge_synonyms(rows.iter().map(|row| (row.get("canonical"), row.get("alias")))))
+}
+
+fn synonym_store() -> &'static RwLock<Arc<SynonymMap>> {
+    static SYNONYMS: OnceLock<RwLock<Arc<SynonymMap>>> = OnceLock::new();
+    SYNONYMS.get_or_init(|| RwLock::new(Arc::new(default_synonyms())))
+}
+
+/// The synonyms used for matching: the built-in ones until `refresh_synonyms` has loaded the table
+pub fn skill_synonyms() -> Arc<SynonymMap> {
+    synonym_store().read().unwrap_or_else(|e| e.into_inner()).clone()
+}
+
+/// Reload the synonyms from the database; on failure the current ones stay in use
+pub async fn refresh_synonyms(conn: &mut PgConnection) -> Result<Arc<SynonymMap>, sqlx::Error> {
+    let synonyms = Arc::new(load_synonyms(conn).await?);
+    *synonym_store().write().unwrap_or_else(|e| e.into_inner()) = synonyms.clone();
+    Ok(synonyms)
+}
+
+/// Whether two normalized names are the same skill through the synonym table
+fn are_synonyms(a: &str, b: &str, synonyms: &SynonymMap) -> bool {
+    synonyms.iter().any(|(canonical, aliases)| {
+        let names = |n: &str| canonical == n || aliases.iter().any(|alias| alias == n);
+        names(a) && names(b)
+    })
+}
+
+/// Pairs that look alike but are different technologies; never fuzzy/partial matched
+const FALSE_FRIENDS: &[(&str, &str)] = &[
+    ("react", "preact"),
+    ("java", "javascript"),
+    ("go", "mongo"),
+    ("go", "django"),
+    ("sql", "nosql"),
+    ("c", "objective c"),
+];
+
+/// How to score skills when a job lists no required skills
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum NoRequirements {
+    /// Everyone scores 100 on skills
+    #[default]
+    PerfectScore,
+    /// Everyone scores 50: skills weren't evaluated either way
+    Neutral,
+    /// Skills are left out of the total and their weight spread over the other components
+    Exclude,
+}
+
+impl NoRequirements {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "perfect" | "perfect_score" => Some(Self::PerfectScore),
+            "neutral" => Some(Self::Neutral),
+            "exclude" => Some(Self::Exclude),
+            _ => None,
+        }
+    }
+}
+
+/// Strictness knobs for fuzzy skill matching
+#[derive(Debug, Clone)]
+pub struct MatchTuning {
+    /// Minimum similarity (Levenshtein, or Jaro-Winkler for truncations) for a fuzzy match
+    pub fuzzy_threshold: f32,
+    /// Score given when one skill name contains the other
+    pub partial_score: f32,
+    /// Points for a fully relevant adjacent (unrequired) skill
+    pub bonus_per_skill: f32,
+    /// Maximum total bonus points
+    pub bonus_cap: i32,
+    /// Skill score for jobs without required skills
+    pub no_requirements: NoRequirements,
+}
+
+impl Default for MatchTuning {
+    fn default() -> Self {
+        Self {
+            fuzzy_threshold: 0.8,
+            partial_score: 0.7,
+            bonus_per_skill: 2.0,
+            bonus_cap: 5,
+            no_requirements: NoRequirements::default(),
+        }
+    }
+}
+
+impl MatchTuning {
+    /// Defaults, with the no-requirements policy from NO_REQUIRED_SKILLS_POLICY (perfect, neutral or exclude)
+    pub fn from_env() -> Self {
+        let no_requirements = match std::env::var("NO_REQUIRED_SKILLS_POLICY") {
+            Ok(value) => NoRequirements::parse(&value).unwrap_or_else(|| {
+                println!("[Matching] Ignoring invalid NO_REQUIRED_SKILLS_POLICY {:?}", value);
+                NoRequirements::default()
+            }),
+            Err(_) => NoRequirements::default(),
+        };
+
+        Self { no_requirements, ..Self::default() }
+    }
+}
+
+/// The process-wide match tuning (configured once from env)
+pub fn match_tuning() -> &'static MatchTuning {
+    static TUNING: OnceLock<MatchTuning> = OnceLock::new();
+    TUNING.get_or_init(MatchTuning::from_env)
+}
+
+/// Skill taxonomy used to find skills adjacent to the required ones
+/// Keys are canonical names (synonyms are resolved first)
+const SKILL_CATEGORIES: &[(&str, &[&str])] = &[
+    ("frontend", &["react", "vue", "angular", "svelte", "preact", "next js", "nextjs", "nuxt", "html", "css", "tailwind", "redux", "javascript", "typescript"]),
+    ("javascript", &["javascript", "typescript", "node", "express", "deno", "bun"]),
+    ("systems", &["rust", "c", "c++", "go", "golang", "zig"]),
+    ("jvm", &["java", "kotlin", "scala", "spring", "spring boot", "clojure"]),
+    ("python", &["python", "django", "flask", "fastapi"]),
+    ("databases", &["postgresql", "mysql", "mongodb", "redis", "sqlite", "sql", "dynamodb", "cassandra", "elasticsearch"]),
+    ("cloud", &["amazon web services", "google cloud platform", "azure", "serverless", "lambda"]),
+    ("devops", &["docker", "kubernetes", "terraform", "ansible", "jenkins", "helm", "ci/cd", "github actions"]),
+    ("data_ml", &["machine learning", "tensorflow", "pytorch", "scikit learn", "pandas", "numpy", "spark"]),
+    ("mobile", &["swift", "kotlin", "react native", "flutter", "ios", "android", "objective c"]),
+    ("api", &["graphql", "rest", "grpc", "openapi"]),
+];
+
+/// Resolve a normalized skill name to its canonical synonym, if any
+fn canonical_skill(norm: &str) -> String {
+    skill_synonyms()
+        .iter()
+        .find(|(canonical, aliases)| *canonical == norm || aliases.iter().any(|alias| alias == norm))
+        .map(|(canonical, _)| canonical.clone())
+        .unwrap_or_else(|| norm.to_string())
+}
+
+/// Taxonomy categories a skill belongs to
+fn skill_categories(name: &str) -> Vec<&'static str> {
+    let canonical = canonical_skill(&normalize_skill(name));
+    SKILL_CATEGORIES
+        .iter()
+        .filter(|(_, skills)| skills.contains(&canonical.as_str()))
+        .map(|(category, _)| *category)
+        .collect()
+}
+
+/// Skills whose names contain a separator but are a single technology
+const SINGLE_SKILLS_WITH_SEPARATORS: &[&str] = &["ci/cd", "tcp/ip", "pl/sql", "i/o", "a/b testing", "ui/ux", "r&d"];
+
+/// Split a compound entry like "React/Redux" or "HTML, CSS & JS" into its skills
+/// Hyphens never split ("Objective-C"); known slash names like "CI/CD" stay whole;
+/// parts naming the same skill through the taxonomy ("React/ReactJS") collapse into one
+pub fn split_compound_skill(name: &str) -> Vec<String> {
+    let trimmed = name.trim();
+    if SINGLE_SKILLS_WITH_SEPARATORS.contains(&trimmed.to_lowercase().as_str()) {
+        return vec![trimmed.to_string()];
+    }
+
+    let mut seen: Vec<String> = Vec::new();
+    let mut parts: Vec<String> = Vec::new();
+    for part in trimmed.split(['/', '&', ',']).map(str::trim).filter(|p| !p.is_empty()) {
+        let canonical = canonical_skill(&normalize_skill(part));
+        if !seen.contains(&canonical) {
+            seen.push(canonical);
+            parts.push(part.to_string());
+        }
+    }
+
+    if parts.is_empty() {
+        vec![trimmed.to_string()]
+    } else {
+        parts
+    }
+}
+
+/// Explode compound entries across a skill list (each part keeps the entry's level),
+/// dropping parts that repeat a skill already listed
+pub fn split_compound_skills<T>(
+    skills: Vec<T>,
+    name: impl Fn(&T) -> &str,
+    with_name: impl Fn(&T, String) -> T,
+) -> Vec<T> {
+    let mut seen: Vec<String> = Vec::new();
+    let mut result = Vec::with_capacity(skills.len());
+    for skill in &skills {
+        for part in split_compound_skill(name(skill)) {
+            let canonical = canonical_skill(&normalize_skill(&part));
+            if !seen.contains(&canonical) {
+                seen.push(canonical);
+                result.push(with_name(skill, part));
+            }
+        }
+    }
+    result
+}
+
+fn is_false_friend(a: &str, b: &str) -> bool {
+    FALSE_FRIENDS
+        .iter()
+        .any(|(x, y)| (a == *x && b == *y) || (a == *y && b == *x))
+}
+
+pub fn normalize_skill(name: &str) -> String {
     name.to_lowercase().replace("-", " ").replace("_", " ").trim().to_string()
 }
 
-fn skills_match(candidate_skill: &str, required_skill: &str) -> Option<(String, f32)> {
+/// Normalized Levenshtein similarity (0..1); 0 when both names are empty
+fn levenshtein_similarity(a: &str, b: &str) -> f32 {
+    let max_len = a.len().max(b.len());
+    if max_len == 0 {
+        return 0.0;
+    }
+    1.0 - (strsim::levenshtein(a, b) as f32 / max_len as f32)
+}
+
+/// Shortest a prefix can be, relative to the longer name, to count as a truncation
+const MIN_TRUNCATION_COVERAGE: f32 = 0.7;
+
+/// Whether the shorter name is a prefix covering most of the longer one ("postgre" of "postgresql")
+fn is_truncation(a: &str, b: &str) -> bool {
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    long.starts_with(short) && short.len() as f32 >= long.len() as f32 * MIN_TRUNCATION_COVERAGE
+}
+
+/// Levenshtein similarity, or Jaro-Winkler when one name is a truncation of the other
+/// Jaro-Winkler rewards any shared prefix, so unrestricted it would pair "python" with "pytorch"
+fn fuzzy_similarity(a: &str, b: &str) -> f32 {
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+    let levenshtein = levenshtein_similarity(a, b);
+    if is_truncation(a, b) {
+        levenshtein.max(strsim::jaro_winkler(a, b) as f32)
+    } else {
+        levenshtein
+    }
+}
+
+fn skills_match(
+    candidate_skill: &str,
+    required_skill: &str,
+    synonyms: &SynonymMap,
+    tuning: &MatchTuning,
+) -> Option<(String, f32)> {
     let candidate_norm = normalize_skill(candidate_skill);
     let required_norm = normalize_skill(required_skill);
     
@@ -81,43 +368,67 @@ fn skills_match(candidate_skill: &str, required_skill: &str) -> Option<(String,
         return Some(("exact".to_string(), 1.0));
     }
     
-    let synonyms = get_synonyms();
-    for (canonical, syns) in &synonyms {
-        let all_variants: Vec<&str> = std::iter::once(*canonical).chain(syns.iter().copied()).collect();
-        let candidate_matches = all_variants.iter().any(|v| normalize_skill(v) == candidate_norm);
-        let required_matches = all_variants.iter().any(|v| normalize_skill(v) == required_norm);
-        if candidate_matches && required_matches {
-            return Some(("synonym".to_string(), 0.95));
-        }
+    if are_synonyms(&candidate_norm, &required_norm, synonyms) {
+        return Some(("synonym".to_string(), 0.95));
+    }
+
+    if is_false_friend(&candidate_norm, &required_norm) {
+        return None;
     }
     
-    let distance = strsim::levenshtein(&candidate_norm, &required_norm);
-    let max_len = candidate_norm.len().max(required_norm.len());
-    if max_len > 0 {
-        let similarity = 1.0 - (distance as f32 / max_len as f32);
-        if similarity >= 0.8 {
-            return Some(("fuzzy".to_string(), similarity * 0.9));
-        }
+    let similarity = fuzzy_similarity(&candidate_norm, &required_norm);
+    if similarity >= tuning.fuzzy_threshold {
+        return Some(("fuzzy".to_string(), similarity * 0.9));
     }
     
     if candidate_norm.contains(&required_norm) || required_norm.contains(&candidate_norm) {
-        return Some(("partial".to_string(), 0.7));
+        return Some(("partial".to_string(), tuning.partial_score));
     }
     
     None
 }
 
+/// A candidate's best match for a single required skill; a miss has no match type and score 0
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct SkillMatchCell {
+    pub matched_skill: Option<String>,
+    pub match_type: Option<String>,
+    pub score: f32,
+}
+
+/// Best `skills_match` of any candidate skill against one required skill (levels are not considered)
+pub fn best_skill_match(candidate_skills: &[CandidateSkill], required_skill: &str, tuning: &MatchTuning) -> SkillMatchCell {
+    let synonyms = skill_synonyms();
+    candidate_skills
+        .iter()
+        .filter_map(|cand| skills_match(&cand.name, required_skill, &synonyms, tuning).map(|(mtype, score)| (cand, mtype, score)))
+        .max_by(|a, b| a.2.total_cmp(&b.2))
+        .map(|(cand, mtype, score)| SkillMatchCell {
+            matched_skill: Some(cand.name.clone()),
+            match_type: Some(mtype),
+            score: (score * 100.0).round() / 100.0,
+        })
+        .unwrap_or(SkillMatchCell { matched_skill: None, match_type: None, score: 0.0 })
+}
+
 pub fn calculate_skill_score(
     candidate_skills: &[CandidateSkill],
     required_skills: &[RequiredSkill],
+    tuning: &MatchTuning,
 ) -> ExplainableScore {
     if required_skills.is_empty() {
+        let (score, reasoning, excluded) = match tuning.no_requirements {
+            NoRequirements::PerfectScore => (100, "No skills required", false),
+            NoRequirements::Neutral => (50, "No skills required (neutral score)", false),
+            NoRequirements::Exclude => (0, "Excluded: no skills required (weight redistributed)", true),
+        };
         return ExplainableScore {
-            score: 100,
+            score,
             matched: vec![],
             missing: vec![],
             bonus: vec![],
-            reasoning: Some("No skills required".to_string()),
+            reasoning: Some(reasoning.to_string()),
+            excluded,
         };
     }
     
@@ -127,6 +438,7 @@ pub fn calculate_skill_score(
     let mut total_score = 0.0;
     let mut total_weight = 0.0;
     let mut used: Vec<bool> = vec![false; candidate_skills.len()];
+    let synonyms = skill_synonyms();
     
     for req in required_skills {
         let is_mandatory = req.mandatory.unwrap_or(true);
@@ -136,7 +448,7 @@ pub fn calculate_skill_score(
         let mut best: Option<(usize, String, f32)> = None;
         for (i, cand) in candidate_skills.iter().enumerate() {
             if used[i] { continue; }
-            if let Some((mtype, mscore)) = skills_match(&cand.name, &req.name) {
+            if let Some((mtype, mscore)) = skills_match(&cand.name, &req.name, &synonyms, tuning) {
                 let cand_w = level_weight(&cand.level);
                 let req_w = level_weight(req_level);
                 let level_score = if cand_w >= req_w {
@@ -165,18 +477,38 @@ pub fn calculate_skill_score(
         total_weight += weight;
     }
     
+    // Bonus for unrequired skills adjacent to required ones (same taxonomy category),
+    // weighted by the candidate's level and whether the related requirement is mandatory
+    let required_categories: Vec<(Vec<&str>, bool)> = required_skills
+        .iter()
+        .map(|r| (skill_categories(&r.name), r.mandatory.unwrap_or(true)))
+        .collect();
+    let mut bonus_total = 0.0;
+
     for (i, cand) in candidate_skills.iter().enumerate() {
-        if !used[i] {
-            let norm = normalize_skill(&cand.name);
-            let keywords = ["rust", "go", "python", "typescript", "react", "aws", "docker", "kubernetes"];
-            if keywords.iter().any(|k| norm.contains(k)) {
-                bonus.push(format!("{} ({})", cand.name, cand.level));
-            }
+        if used[i] {
+            continue;
+        }
+        let cand_categories = skill_categories(&cand.name);
+        let relevance = required_categories
+            .iter()
+            .filter(|(cats, _)| cats.iter().any(|c| cand_categories.contains(c)))
+            .map(|(_, mandatory)| if *mandatory { 1.0 } else { 0.5 })
+            .fold(0.0_f32, f32::max);
+
+        if relevance > 0.0 {
+            let shared: Vec<&str> = cand_categories
+                .iter()
+                .copied()
+                .filter(|c| required_categories.iter().any(|(cats, _)| cats.contains(c)))
+                .collect();
+            bonus.push(format!("{} ({}) - adjacent: {}", cand.name, cand.level, shared.join(", ")));
+            bonus_total += tuning.bonus_per_skill * relevance * level_weight(&cand.level);
         }
     }
     
     let base = if total_weight > 0.0 { (total_score / total_weight * 100.0).round() as i32 } else { 100 };
-    let bonus_pts = (bonus.len() as i32 * 2).min(5);
+    let bonus_pts = (bonus_total.round() as i32).min(tuning.bonus_cap).max(0);
     let final_score = (base + bonus_pts).min(100).max(0);
     
     let mandatory_missing = missing.iter().filter(|m| !m.contains("nice-to-have")).count();
@@ -188,21 +520,143 @@ pub fn calculate_skill_score(
         format!("Good match, missing {} nice-to-have", missing.len())
     };
     
-    ExplainableScore { score: final_score, matched, missing, bonus, reasoning: Some(reasoning) }
+    ExplainableScore { score: final_score, matched, missing, bonus, reasoning: Some(reasoning), excluded: false }
+}
+
+async fn analyze_skills_with_gemini(context: &str, temperature: Option<f64>) -> Result<AISkillAnalysis, AiResponseError> {
+    let client = Client::default();
+    let prompt = format!("{}\n\n{}", AI_SKILL_PROMPT, context);
+    let request = ChatRequest::new(vec![ChatMessage::user(prompt)]);
+    let options = chat_options(temperatures().skills, temperature);
+
+    let response = client
+        .exec_chat("gemini-2.0-flash", request, Some(&options))
+        .await
+        .map_err(AiResponseError::from_request_error)?;
+
+    parse_json(response.first_text().map(String::from))
+}
+
+/// Combined skill score with each scorer's contribution kept separate
+/// `matched`/`missing` on the flattened score are the AI entries followed by the algorithmic ones
+#[derive(Debug, Clone, Serialize)]
+pub struct CombinedSkillScore {
+    #[serde(flatten)]
+    pub combined: ExplainableScore,
+    pub ai_matched: Vec<String>,
+    pub ai_missing: Vec<String>,
+    pub ai_reasoning: Option<String>,
+    pub algo_matched: Vec<String>,
+    pub algo_missing: Vec<String>,
+}
+
+impl CombinedSkillScore {
+    /// Algorithmic score on its own, with a note on why the AI was left out
+    fn algo_only(algo_score: ExplainableScore, note: &str) -> Self {
+        let reasoning = format!("Algo only ({}) | {}", note, algo_score.reasoning.clone().unwrap_or_default());
+
+        Self {
+            ai_matched: vec![],
+            ai_missing: vec![],
+            ai_reasoning: None,
+            algo_matched: algo_score.matched.clone(),
+            algo_missing: algo_score.missing.clone(),
+            combined: ExplainableScore { reasoning: Some(reasoning), ..algo_score },
+        }
+    }
+}
+
+/// Share of the combined skill score taken from the AI analysis when AI_SKILL_WEIGHT isn't set
+pub const DEFAULT_AI_SKILL_WEIGHT: f32 = 0.9;
+
+/// AI share of the pipeline's combined skill score (AI_SKILL_WEIGHT, 0.0-1.0, default 0.9)
+pub fn ai_skill_weight() -> f32 {
+    static WEIGHT: OnceLock<f32> = OnceLock::new();
+    *WEIGHT.get_or_init(|| {
+        std::env::var("AI_SKILL_WEIGHT")
+            .ok()
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(DEFAULT_AI_SKILL_WEIGHT)
+    })
+}
+
+/// AI share clamped to 0.0-1.0; NaN falls back to the default
+/// Rounded to 0.1% so f32 noise (0.9f32 is 0.8999999...) can't tip the rounded score
+fn effective_ai_weight(ai_weight: f32) -> f64 {
+    let weight = if ai_weight.is_nan() { DEFAULT_AI_SKILL_WEIGHT } else { ai_weight.clamp(0.0, 1.0) };
+    (weight as f64 * 1000.0).round() / 1000.0
+}
+
+/// Blend AI and algorithmic scores by `ai_weight`; without a usable AI result the algorithmic score stands alone
+fn combine_skill_scores(
+    ai: Result<AISkillAnalysis, AiResponseError>,
+    algo_score: ExplainableScore,
+    ai_weight: f32,
+) -> CombinedSkillScore {
+    let analysis = match ai {
+        Ok(analysis) => analysis,
+        Err(e) => {
+            log_fallback("Skill analysis", &e, "algorithmic score");
+            return CombinedSkillScore::algo_only(algo_score, &format!("AI unavailable: {}", e));
+        }
+    };
+
+    let ai_weight = effective_ai_weight(ai_weight);
+    let algo_weight = 1.0 - ai_weight;
+    let ai_score = clamp_score(analysis.score, 100.0);
+    let combined = clamp_score(ai_score * ai_weight + algo_score.score as f64 * algo_weight, 100.0).round() as i32;
+
+    let matched = [analysis.strengths.as_slice(), algo_score.matched.as_slice()].concat();
+    let missing = [analysis.gaps.as_slice(), algo_score.missing.as_slice()].concat();
+
+    let reasoning = format!(
+        "AI: {} | Algo: {} (AI {:.0}% / Algo {:.0}%)",
+        analysis.reasoning,
+        algo_score.reasoning.unwrap_or_default(),
+        ai_weight * 100.0,
+        algo_weight * 100.0
+    );
+
+    CombinedSkillScore {
+        combined: ExplainableScore {
+            score: combined,
+            matched,
+            missing,
+            bonus: algo_score.bonus,
+            reasoning: Some(reasoning),
+            excluded: false,
+        },
+        ai_matched: analysis.strengths,
+        ai_missing: analysis.gaps,
+        ai_reasoning: Some(analysis.reasoning),
+        algo_matched: algo_score.matched,
+        algo_missing: algo_score.missing,
+    }
 }
 
-async fn calculate_ai_skill_match(
+/// Combined scoring with the AI analysis supplied by `analyze` (called with the candidate/job context)
+async fn combined_skill_score_with<F, Fut>(
+    candidate_skills: &[CandidateSkill],
+    required_skills: &[RequiredSkill],
     candidate_description: &str,
     job_info: &str,
-) -> ExplainableScore {
+    ai_weight: f32,
+    analyze: F,
+) -> CombinedSkillScore
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<AISkillAnalysis, AiResponseError>>,
+{
+    let tuning = match_tuning();
+    let algo_score = calculate_skill_score(candidate_skills, required_skills, tuning);
+
+    // Without requirements the policy decides the score; the AI would only judge against the description
+    if required_skills.is_empty() && tuning.no_requirements != NoRequirements::PerfectScore {
+        return CombinedSkillScore::algo_only(algo_score, "no skills required");
+    }
+
     if candidate_description.is_empty() || job_info.is_empty() {
-        return ExplainableScore {
-            score: 70,
-            matched: vec![],
-            missing: vec![],
-            bonus: vec![],
-            reasoning: Some("Insufficient data for AI skill analysis".to_string()),
-        };
+        return CombinedSkillScore::algo_only(algo_score, "insufficient data for AI skill analysis");
     }
 
     let context = format!(
@@ -210,85 +664,413 @@ async fn calculate_ai_skill_match(
         candidate_description, job_info
     );
 
-    match analyze_skills_with_gemini(&context).await {
-        Ok(analysis) => ExplainableScore {
-            score: analysis.score.min(100).max(0),
-            matched: analysis.strengths,
-            missing: analysis.gaps,
-            bonus: vec![],
-            reasoning: Some(analysis.reasoning),
-        },
-        Err(_) => ExplainableScore {
-            score: 70,
-            matched: vec![],
-            missing: vec![],
-            bonus: vec![],
-            reasoning: Some("AI skill analysis unavailable".to_string()),
-        },
-    }
+    combine_skill_scores(analyze(context).await, algo_score, ai_weight)
 }
 
-async fn analyze_skills_with_gemini(context: &str) -> Result<AISkillAnalysis, Box<dyn std::error::Error + Send + Sync>> {
-    let client = Client::default();
-    let prompt = format!("{}\n\n{}", AI_SKILL_PROMPT, context);
-    let request = ChatRequest::new(vec![ChatMessage::user(prompt)]);
-
-    let response = client
-        .exec_chat("gemini-2.0-flash", request, None)
-        .await?;
+/// Combined skill scoring: `ai_weight` (0.0-1.0) AI, the rest algorithmic; algorithmic only when the AI is unavailable
+/// `temperature` overrides the configured skills temperature for this call
+pub async fn calculate_combined_skill_score(
+    candidate_skills: &[CandidateSkill],
+    required_skills: &[RequiredSkill],
+    candidate_description: &str,
+    job_info: &str,
+    ai_weight: f32,
+    temperature: Option<f64>,
+) -> CombinedSkillScore {
+    combined_skill_score_with(candidate_skills, required_skills, candidate_description, job_info, ai_weight, |context| async move {
+        analyze_skills_with_gemini(&context, temperature).await
+    })
+    .await
+}
 
-    let content = response
-        .first_text()
-        .ok_or("No response content")?;
-
-    let json_str = if content.contains("```json") {
-        content
-            .split("```json")
-            .nth(1)
-            .and_then(|s| s.split("```").next())
-            .unwrap_or(content)
-    } else if content.contains("```") {
-        content
-            .split("```")
-            .nth(1)
-            .unwrap_or(content)
-    } else {
-        content
-    };
+/// Whether the scoring pipeline blends AI skill analysis into the skills component (USE_AI_SKILLS)
+pub fn ai_skills_enabled() -> bool {
+    static ENABLED: OnceLock<bool> = OnceLock::new();
+    *ENABLED.get_or_init(|| {
+        std::env::var("USE_AI_SKILLS")
+            .map(|flag| matches!(flag.trim().to_lowercase().as_str(), "1" | "true" | "yes"))
+            .unwrap_or(false)
+    })
+}
 
-    let analysis: AISkillAnalysis = serde_json::from_str(json_str.trim())?;
-    Ok(analysis)
+async fn pipeline_skill_score_with<F, Fut>(
+    use_ai: bool,
+    ai_weight: f32,
+    candidate_skills: &[CandidateSkill],
+    required_skills: &[RequiredSkill],
+    candidate_description: &str,
+    job_info: &str,
+    analyze: F,
+) -> ExplainableScore
+where
+    F: FnOnce(String) -> Fut,
+    Fut: Future<Output = Result<AISkillAnalysis, AiResponseError>>,
+{
+    if !use_ai {
+        return calculate_skill_score(candidate_skills, required_skills, match_tuning());
+    }
+    combined_skill_score_with(candidate_skills, required_skills, candidate_description, job_info, ai_weight, analyze)
+        .await
+        .combined
 }
 
-/// Combined skill scoring: 90% AI + 10% algorithmic
-pub async fn calculate_combined_skill_score(
+/// Skills component for sourcing and job matching: the combined AI + algorithmic score (weighted by AI_SKILL_WEIGHT)
+/// with USE_AI_SKILLS set, the algorithmic score otherwise
+pub async fn pipeline_skill_score(
     candidate_skills: &[CandidateSkill],
     required_skills: &[RequiredSkill],
     candidate_description: &str,
     job_info: &str,
+    temperature: Option<f64>,
 ) -> ExplainableScore {
-    let algo_score = calculate_skill_score(candidate_skills, required_skills);
-    let ai_score = calculate_ai_skill_match(candidate_description, job_info).await;
+    pipeline_skill_score_with(ai_skills_enabled(), ai_skill_weight(), candidate_skills, required_skills, candidate_description, job_info, |context| async move {
+        analyze_skills_with_gemini(&context, temperature).await
+    })
+    .await
+}
 
-    let combined = ((ai_score.score as f32 * 0.9) + (algo_score.score as f32 * 0.1)).round() as i32;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    let mut matched = ai_score.matched;
-    matched.extend(algo_score.matched.into_iter().map(|s| format!("[Algo] {}", s)));
+    fn cand(name: &str) -> CandidateSkill {
+        CandidateSkill { name: name.to_string(), level: "advanced".to_string() }
+    }
 
-    let mut missing = ai_score.missing;
-    missing.extend(algo_score.missing.into_iter().map(|s| format!("[Algo] {}", s)));
+    fn req(name: &str) -> RequiredSkill {
+        RequiredSkill { name: name.to_string(), level: Some("advanced".to_string()), mandatory: Some(true) }
+    }
 
-    let reasoning = format!(
-        "AI: {} | Algo: {}",
-        ai_score.reasoning.unwrap_or_default(),
-        algo_score.reasoning.unwrap_or_default()
-    );
+    #[test]
+    fn test_raised_threshold_rejects_borderline_fuzzy() {
+        // "kotlinn" vs "kotlin": Jaro-Winkler ~0.97
+        let default = MatchTuning::default();
+        let strict = MatchTuning { fuzzy_threshold: 0.99, ..MatchTuning::default() };
+
+        assert!(matches!(skills_match("kotlinn", "kotlin", &default_synonyms(), &default), Some((ref t, _)) if t == "fuzzy"));
+        // Substring fallback still applies, but no longer as fuzzy
+        assert!(matches!(skills_match("kotlinn", "kotlin", &default_synonyms(), &strict), Some((ref t, _)) if t == "partial"));
+        // "xwift" vs "swift": not a truncation, Levenshtein similarity 0.8
+        let raised = MatchTuning { fuzzy_threshold: 0.9, ..MatchTuning::default() };
+        assert_eq!(skills_match("xwift", "swift", &default_synonyms(), &default).map(|m| m.0), Some("fuzzy".to_string()));
+        assert_eq!(skills_match("xwift", "swift", &default_synonyms(), &raised), None);
+    }
+
+    #[test]
+    fn test_false_friends_never_match() {
+        let loose = MatchTuning { fuzzy_threshold: 0.5, ..MatchTuning::default() };
+        assert_eq!(skills_match("Preact", "React", &default_synonyms(), &loose), None);
+        assert_eq!(skills_match("JavaScript", "Java", &default_synonyms(), &loose), None);
+        assert_eq!(skills_match("MongoDB", "mongo", &default_synonyms(), &loose).map(|m| m.0), Some("synonym".to_string()));
+
+        let score = calculate_skill_score(&[cand("preact")], &[req("react")], &MatchTuning::default());
+        assert!(score.matched.is_empty());
+        assert_eq!(score.missing.len(), 1);
+    }
+
+    #[test]
+    fn test_adjacent_skill_earns_bonus_unrelated_does_not() {
+        let candidate = vec![
+            CandidateSkill { name: "Kubernetes".to_string(), level: "advanced".to_string() },
+            CandidateSkill { name: "Docker".to_string(), level: "expert".to_string() },
+            CandidateSkill { name: "Photoshop".to_string(), level: "expert".to_string() },
+        ];
+        let required = vec![req("k8s")];
+
+        let score = calculate_skill_score(&candidate, &required, &MatchTuning::default());
+
+        assert_eq!(score.bonus.len(), 1);
+        assert!(score.bonus[0].starts_with("Docker"));
+        assert!(score.bonus[0].contains("devops"));
+
+        // Previously hardcoded keywords no longer earn bonus on their own
+        let score = calculate_skill_score(
+            &[cand("React"), cand("Rust")],
+            &[req("React")],
+            &MatchTuning::default(),
+        );
+        assert!(score.bonus.is_empty());
+    }
+
+    #[test]
+    fn test_bonus_cap_is_configurable() {
+        let candidate = vec![
+            cand("PostgreSQL"),
+            CandidateSkill { name: "Redis".to_string(), level: "expert".to_string() },
+            CandidateSkill { name: "MySQL".to_string(), level: "expert".to_string() },
+            CandidateSkill { name: "MongoDB".to_string(), level: "expert".to_string() },
+        ];
+        let required = vec![
+            RequiredSkill { name: "PostgreSQL".to_string(), level: Some("advanced".to_string()), mandatory: Some(true) },
+            RequiredSkill { name: "Kafka".to_string(), level: Some("advanced".to_string()), mandatory: Some(true) },
+        ];
+
+        let uncapped = MatchTuning { bonus_cap: 100, ..MatchTuning::default() };
+        let capped = MatchTuning { bonus_cap: 1, ..MatchTuning::default() };
+        let none = MatchTuning { bonus_cap: 0, ..MatchTuning::default() };
+
+        let base = calculate_skill_score(&candidate, &required, &none).score;
+        assert_eq!(calculate_skill_score(&candidate, &required, &capped).score, base + 1);
+        assert_eq!(calculate_skill_score(&candidate, &required, &uncapped).score, base + 6);
+    }
+
+    #[test]
+    fn test_partial_score_is_configurable() {
+        let tuning = MatchTuning { fuzzy_threshold: 0.99, partial_score: 0.5, ..MatchTuning::default() };
+        assert_eq!(skills_match("react native", "react", &default_synonyms(), &tuning), Some(("partial".to_string(), 0.5)));
+    }
+
+    #[test]
+    fn test_empty_ai_response_falls_back_to_algorithmic_score() {
+        let algo = calculate_skill_score(&[cand("Rust")], &[req("Rust"), req("Go")], &MatchTuning::default());
+        let algo_score = algo.score;
+
+        let ai = parse_json::<AISkillAnalysis>(Some(String::new()));
+        let result = combine_skill_scores(ai, algo, DEFAULT_AI_SKILL_WEIGHT);
+
+        assert_eq!(result.combined.score, algo_score);
+        assert!(result.combined.reasoning.unwrap().starts_with("Algo only (AI unavailable: empty response)"));
+        assert!(result.ai_matched.is_empty());
+    }
+
+    #[test]
+    fn test_combined_score_partitions_ai_and_algo_entries() {
+        let algo = calculate_skill_score(&[cand("Rust")], &[req("Rust"), req("Go")], &MatchTuning::default());
+        let ai = Ok(AISkillAnalysis {
+            score: 80.0,
+            reasoning: "Strong systems background".to_string(),
+            strengths: vec!["Systems programming".to_string()],
+            gaps: vec!["No Kubernetes".to_string()],
+        });
+
+        let result = combine_skill_scores(ai, algo.clone(), DEFAULT_AI_SKILL_WEIGHT);
+
+        assert_eq!(result.ai_matched, vec!["Systems programming"]);
+        assert_eq!(result.ai_missing, vec!["No Kubernetes"]);
+        assert_eq!(result.ai_reasoning.as_deref(), Some("Strong systems background"));
+        assert_eq!(result.algo_matched, algo.matched);
+        assert_eq!(result.algo_missing, algo.missing);
+
+        // Flattened view keeps both, AI first, without string prefixes
+        let json = serde_json::to_value(&result).unwrap();
+        assert_eq!(json["matched"][0], "Systems programming");
+        assert_eq!(json["matched"].as_array().unwrap().len(), 1 + algo.matched.len());
+        assert!(json["missing"].as_array().unwrap().iter().all(|m| !m.as_str().unwrap().starts_with("[Algo]")));
+        assert_eq!(json["algo_missing"], serde_json::json!(algo.missing));
+    }
+
+    #[test]
+    fn test_no_requirements_policies() {
+        use crate::matching::{calculate_talent_fit, ScoreWeights};
+
+        let fixed = |score: i32| ExplainableScore {
+            score,
+            matched: vec![],
+            missing: vec![],
+            bonus: vec![],
+            reasoning: None,
+            excluded: false,
+        };
+        let talent_fit = |policy: NoRequirements| {
+            let tuning = MatchTuning { no_requirements: policy, ..MatchTuning::default() };
+            let skills = calculate_skill_score(&[cand("Rust")], &[], &tuning);
+            (skills.score, calculate_talent_fit(skills, fixed(60), fixed(60), fixed(60), Some(ScoreWeights::default())))
+        };
+
+        // Default keeps the historical perfect score
+        assert_eq!(MatchTuning::default().no_requirements, NoRequirements::PerfectScore);
+        let (score, perfect) = talent_fit(NoRequirements::PerfectScore);
+        assert_eq!(score, 100);
+        assert_eq!(perfect.total, 76);
+        assert!(!perfect.breakdown.skills_excluded);
+
+        let (score, neutral) = talent_fit(NoRequirements::Neutral);
+        assert_eq!(score, 50);
+        assert_eq!(neutral.total, 56);
+
+        // Excluded skills drop out and the other components carry the full weight
+        let (_, excluded) = talent_fit(NoRequirements::Exclude);
+        assert!(excluded.breakdown.skills_excluded);
+        assert!(excluded.breakdown.skills.excluded);
+        assert_eq!(excluded.total, 60);
+
+        assert_eq!(NoRequirements::parse("Exclude"), Some(NoRequirements::Exclude));
+        assert_eq!(NoRequirements::parse("perfect"), Some(NoRequirements::PerfectScore));
+        assert_eq!(NoRequirements::parse("zero"), None);
+    }
+
+    #[rocket::async_test]
+    async fn test_ai_skills_flag_changes_skills_component() {
+        let candidate = vec![cand("Rust"), cand("Distributed Systems")];
+        let required = vec![req("Rust"), req("Go")];
+        let analyze = |context: String| async move {
+            assert!(context.contains("CANDIDATE:\nSenior Rust engineer") && context.contains("JOB:\nBackend Engineer"));
+            Ok(AISkillAnalysis {
+                score: 85.0,
+                reasoning: "Go is easy to pick up for a Rust engineer".to_string(),
+                strengths: vec!["Systems background".to_string()],
+                gaps: vec![],
+            })
+        };
+        let description = "Senior Rust engineer";
+        let job_info = "Backend Engineer";
+
+        let algo = pipeline_skill_score_with(false, DEFAULT_AI_SKILL_WEIGHT, &candidate, &required, description, job_info, analyze).await;
+        assert_eq!(algo.score, calculate_skill_score(&candidate, &required, match_tuning()).score);
+
+        let ai = pipeline_skill_score_with(true, DEFAULT_AI_SKILL_WEIGHT, &candidate, &required, description, job_info, analyze).await;
+        assert_ne!(ai.score, algo.score);
+        assert_eq!(ai.score, (85.0 * 0.9 + algo.score as f32 * 0.1).round() as i32);
+        assert!(ai.reasoning.unwrap().starts_with("AI: Go is easy to pick up"));
+
+        // The configured weight reaches the blend
+        let ai_only = pipeline_skill_score_with(true, 1.0, &candidate, &required, description, job_info, analyze).await;
+        assert_eq!(ai_only.score, 85);
+        assert!(ai_only.reasoning.unwrap().ends_with("(AI 100% / Algo 0%)"));
+        let algo_weighted = pipeline_skill_score_with(true, 0.0, &candidate, &required, description, job_info, analyze).await;
+        assert_eq!(algo_weighted.score, algo.score);
+    }
+
+    #[test]
+    fn test_compound_skills_split_but_hyphenated_kept() {
+        assert_eq!(split_compound_skill("HTML/CSS/JS"), vec!["HTML", "CSS", "JS"]);
+        assert_eq!(split_compound_skill("Objective-C"), vec!["Objective-C"]);
+        assert_eq!(split_compound_skill("React/Redux"), vec!["React", "Redux"]);
+        assert_eq!(split_compound_skill("Docker, Kubernetes & Helm"), vec!["Docker", "Kubernetes", "Helm"]);
+        assert_eq!(split_compound_skill("CI/CD"), vec!["CI/CD"]);
+        // Synonyms through the taxonomy are one skill
+        assert_eq!(split_compound_skill("React/ReactJS"), vec!["React"]);
+
+        let skills = split_compound_skills(
+            vec![cand("HTML/CSS/JS"), cand("JavaScript"), cand("Objective-C")],
+            |s| &s.name,
+            |s, name| CandidateSkill { name, level: s.level.clone() },
+        );
+        let names: Vec<&str> = skills.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["HTML", "CSS", "JS", "Objective-C"]);
+        assert!(skills.iter().all(|s| s.level == "advanced"));
+
+        // The split skills now match individual requirements
+        let score = calculate_skill_score(&skills, &[req("CSS"), req("HTML")], &MatchTuning::default());
+        assert_eq!(score.missing.len(), 0);
+    }
+
+    #[test]
+    fn test_out_of_range_ai_skill_scores_are_clamped() {
+        let algo = calculate_skill_score(&[cand("Rust")], &[req("Rust")], &MatchTuning::default());
+        let analysis = |score: f64| {
+            Ok(AISkillAnalysis { score, reasoning: String::new(), strengths: vec![], gaps: vec![] })
+        };
+
+        assert_eq!(combine_skill_scores(analysis(250.0), algo.clone(), DEFAULT_AI_SKILL_WEIGHT).combined.score, 100);
+        assert_eq!(combine_skill_scores(analysis(-40.0), algo.clone(), DEFAULT_AI_SKILL_WEIGHT).combined.score, 10);
+        assert_eq!(combine_skill_scores(analysis(f64::NAN), algo.clone(), DEFAULT_AI_SKILL_WEIGHT).combined.score, 10);
+        assert_eq!(combine_skill_scores(analysis(f64::INFINITY), algo.clone(), DEFAULT_AI_SKILL_WEIGHT).combined.score, 100);
+        assert_eq!(combine_skill_scores(analysis(72.6), algo, DEFAULT_AI_SKILL_WEIGHT).combined.score, 75);
+    }
+
+    #[test]
+    fn test_ai_weight_sets_blend() {
+        let algo = calculate_skill_score(&[cand("Rust")], &[req("Rust"), req("Go")], &MatchTuning::default());
+        let analysis = || {
+            Ok(AISkillAnalysis { score: 90.0, reasoning: "Solid".to_string(), strengths: vec![], gaps: vec![] })
+        };
+
+        let algo_only = combine_skill_scores(analysis(), algo.clone(), 0.0);
+        assert_eq!(algo_only.combined.score, algo.score);
+        assert!(algo_only.combined.reasoning.unwrap().ends_with("(AI 0% / Algo 100%)"));
+
+        let ai_only = combine_skill_scores(analysis(), algo.clone(), 1.0);
+        assert_eq!(ai_only.combined.score, 90);
+        assert!(ai_only.combined.reasoning.unwrap().ends_with("(AI 100% / Algo 0%)"));
+
+        let default = combine_skill_scores(analysis(), algo.clone(), DEFAULT_AI_SKILL_WEIGHT);
+        assert_eq!(default.combined.score, (90.0 * 0.9 + algo.score as f64 * 0.1).round() as i32);
+        assert!(default.combined.reasoning.unwrap().ends_with("(AI 90% / Algo 10%)"));
+
+        // Out-of-range weights are clamped, NaN uses the default
+        assert_eq!(combine_skill_scores(analysis(), algo.clone(), 3.0).combined.score, 90);
+        assert_eq!(combine_skill_scores(analysis(), algo.clone(), f32::NAN).combined.score, default.combined.score);
+    }
+
+    #[test]
+    fn test_jaro_winkler_matches_truncated_names() {
+        // Levenshtein alone misses the truncation; Jaro-Winkler rewards the shared prefix
+        let levenshtein = levenshtein_similarity("postgre", "postgresql");
+        let jaro_winkler = strsim::jaro_winkler("postgre", "postgresql") as f32;
+        assert!(levenshtein < 0.8);
+        assert!(jaro_winkler >= 0.8);
+        assert_eq!(fuzzy_similarity("postgre", "postgresql"), jaro_winkler);
+
+        let matched = skills_match("Postgre", "PostgreSQL", &default_synonyms(), &MatchTuning::default()).unwrap();
+        assert_eq!(matched.0, "fuzzy");
+        assert!((matched.1 - jaro_winkler * 0.9).abs() < 1e-6);
+
+        // Synonyms still win over fuzzy similarity
+        assert_eq!(skills_match("Postgres", "PostgreSQL", &default_synonyms(), &MatchTuning::default()).map(|m| m.0), Some("synonym".to_string()));
+    }
+
+    #[test]
+    fn test_shared_prefix_alone_is_not_fuzzy() {
+        let tuning = MatchTuning::default();
+
+        // Jaro-Winkler is ~0.82 here, but neither name is a truncation of the other
+        assert!(strsim::jaro_winkler("python", "pytorch") >= 0.8);
+        assert!(fuzzy_similarity("python", "pytorch") < tuning.fuzzy_threshold);
+        assert_eq!(skills_match("Python", "PyTorch", &default_synonyms(), &tuning), None);
+
+        // A short prefix doesn't count as a truncation
+        assert!(fuzzy_similarity("java", "javascript") < tuning.fuzzy_threshold);
+        assert_eq!(skills_match("Java", "JavaScript", &default_synonyms(), &tuning), None);
+        assert!(fuzzy_similarity("go", "golang") < tuning.fuzzy_threshold);
+        assert_eq!(skills_match("Go", "Golang Tooling", &default_synonyms(), &tuning).map(|m| m.0), Some("partial".to_string()));
+    }
+
+    #[test]
+    fn test_merge_synonyms_adds_to_builtin() {
+        let merged = merge_synonyms(vec![
+            ("JavaScript".to_string(), "JS".to_string()),
+            ("javascript".to_string(), "vanilla-js".to_string()),
+            ("elixir".to_string(), "Elixir".to_string()),
+        ]);
+        let javascript = &merged["javascript"];
+        assert_eq!(javascript.iter().filter(|a| *a == "js").count(), 1);
+        assert!(javascript.contains(&"vanilla js".to_string()));
+        assert!(merged["elixir"].is_empty());
+        assert_eq!(merged["kubernetes"], vec!["k8s".to_string()]);
+    }
+
+    #[rocket::async_test]
+    async fn test_db_synonym_matches() {
+        use sqlx::{Connection, PgConnection};
+
+        let Ok(url) = std::env::var("TEST_DATABASE_URL") else {
+            println!("TEST_DATABASE_URL not set, skipping");
+            return;
+        };
+        let mut conn = PgConnection::connect(&url).await.unwrap();
+        let mut tx = conn.begin().await.unwrap();
+
+        let tuning = MatchTuning::default();
+        let before = load_synonyms(&mut tx).await.unwrap();
+        assert_ne!(skills_match("C Sharp", "CSharp", &before, &tuning).map(|m| m.0), Some("synonym".to_string()));
+
+        sqlx::query("INSERT INTO skill_synonyms (canonical, alias) VALUES ('csharp', 'c sharp')")
+            .execute(&mut *tx)
+            .await
+            .unwrap();
+        let after = load_synonyms(&mut tx).await.unwrap();
+        assert_eq!(skills_match("C Sharp", "CSharp", &after, &tuning), Some(("synonym".to_string(), 0.95)));
+        assert_eq!(skills_match("JS", "JavaScript", &after, &tuning).map(|m| m.0), Some("synonym".to_string()));
+
+        tx.rollback().await.unwrap();
+    }
 
-    ExplainableScore {
-        score: combined.min(100).max(0),
-        matched,
-        missing,
-        bonus: algo_score.bonus,
-        reasoning: Some(reasoning),
+    #[test]
+    fn test_github_language_levels() {
+        assert_eq!(github_language_level(50), "expert");
+        assert_eq!(github_language_level(49), "advanced");
+        assert_eq!(github_language_level(10), "intermediate");
+        assert_eq!(github_language_level(9), "beginner");
     }
 }
@@ -1,11 +1,170 @@
 use super::ExplainableScore;
 use serde::{Deserialize, Serialize};
+use crate::code_analysis::characteristics::{scaled_diff_sq, ABSTRACTION_LAYER_SCALE, LINES_PER_FUNCTION_SCALE, NESTING_DEPTH_SCALE};
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Lowercase, trimmed, with spaces/underscores as hyphens ("Fast_Paced " -> "fast-paced")
+fn normalize_style(value: &str) -> String {
+    value.trim().to_lowercase().replace([' ', '_'], "-")
+}
+
+/// How someone prefers to communicate
+/// Unrecognized stored values deserialize to `Other` so older rows still load
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Communication {
+    Async,
+    Sync,
+    Mixed,
+    Other(String),
+}
+
+impl Communication {
+    pub fn parse(value: &str) -> Option<Self> {
+        match normalize_style(value).as_str() {
+            "async" | "asynchronous" | "async-first" | "written" => Some(Self::Async),
+            "sync" | "synchronous" | "real-time" | "realtime" | "meetings" => Some(Self::Sync),
+            "mixed" | "hybrid" | "both" | "flexible" => Some(Self::Mixed),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Async => "async",
+            Self::Sync => "sync",
+            Self::Mixed => "mixed",
+            Self::Other(value) => value,
+        }
+    }
+}
+
+impl From<String> for Communication {
+    fn from(value: String) -> Self {
+        Self::parse(&value).unwrap_or_else(|| Self::Other(normalize_style(&value)))
+    }
+}
+
+impl From<Communication> for String {
+    fn from(value: Communication) -> Self {
+        value.name().to_string()
+    }
+}
+
+/// How someone prefers to work with others
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Collaboration {
+    Independent,
+    Collaborative,
+    Balanced,
+    Other(String),
+}
+
+impl Collaboration {
+    pub fn parse(value: &str) -> Option<Self> {
+        match normalize_style(value).as_str() {
+            "independent" | "solo" | "autonomous" | "individual" => Some(Self::Independent),
+            "collaborative" | "pair" | "pairing" | "team" | "teamwork" => Some(Self::Collaborative),
+            "balanced" | "mixed" | "hybrid" | "both" => Some(Self::Balanced),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Independent => "independent",
+            Self::Collaborative => "collaborative",
+            Self::Balanced => "balanced",
+            Self::Other(value) => value,
+        }
+    }
+}
+
+impl From<String> for Collaboration {
+    fn from(value: String) -> Self {
+        Self::parse(&value).unwrap_or_else(|| Self::Other(normalize_style(&value)))
+    }
+}
+
+impl From<Collaboration> for String {
+    fn from(value: Collaboration) -> Self {
+        value.name().to_string()
+    }
+}
+
+/// Preferred working pace
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(from = "String", into = "String")]
+pub enum Pace {
+    Fast,
+    Steady,
+    Flexible,
+    Other(String),
+}
+
+impl Pace {
+    pub fn parse(value: &str) -> Option<Self> {
+        match normalize_style(value).as_str() {
+            "fast" | "fast-paced" | "rapid" | "quick" => Some(Self::Fast),
+            "steady" | "consistent" | "regular" | "moderate" => Some(Self::Steady),
+            "flexible" | "adaptive" | "variable" | "varies" => Some(Self::Flexible),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Fast => "fast",
+            Self::Steady => "steady",
+            Self::Flexible => "flexible",
+            Self::Other(value) => value,
+        }
+    }
+}
+
+impl From<String> for Pace {
+    fn from(value: String) -> Self {
+        Self::parse(&value).unwrap_or_else(|| Self::Other(normalize_style(&value)))
+    }
+}
+
+impl From<Pace> for String {
+    fn from(value: Pace) -> Self {
+        value.name().to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct WorkStyle {
-    pub communication: String,
-    pub collaboration: String,
-    pub pace: String,
+    pub communication: Communication,
+    pub collaboration: Collaboration,
+    pub pace: Pace,
+}
+
+impl Default for WorkStyle {
+    fn default() -> Self {
+        Self {
+            communication: Communication::Mixed,
+            collaboration: Collaboration::Balanced,
+            pace: Pace::Steady,
+        }
+    }
+}
+
+impl WorkStyle {
+    /// Reject values outside the known styles (and their accepted spellings) on input
+    pub fn validate(&self) -> Result<(), String> {
+        if let Communication::Other(value) = &self.communication {
+            return Err(format!("Unknown communication style '{}' (expected async, sync or mixed)", value));
+        }
+        if let Collaboration::Other(value) = &self.collaboration {
+            return Err(format!("Unknown collaboration style '{}' (expected independent, collaborative or balanced)", value));
+        }
+        if let Pace::Other(value) = &self.pace {
+            return Err(format!("Unknown pace '{}' (expected fast, steady or flexible)", value));
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +181,47 @@ pub struct CodeCharacteristics {
     pub test_structure_modularity_ratio: f32,
 }
 
+impl CodeCharacteristics {
+    /// Plain-language reading of the metrics, e.g. "highly modular (0.82)"
+    pub fn explain(&self) -> Vec<String> {
+        // (low label, high label, value) for 0-1 ratios; middling values are not called out
+        let ratios = [
+            ("object-oriented", "functional", self.functional_vs_oop_ratio),
+            ("loop-oriented", "recursion-heavy", self.recursion_vs_loop_ratio),
+            ("loosely coupled", "tightly coupled", self.dependency_coupling_index),
+            ("monolithic", "highly modular", self.modularity_index_score),
+            ("mutation-friendly", "immutability-first", self.immutability_score),
+            ("local error handling", "centralized error handling", self.error_handling_centralization_score),
+            ("ad-hoc test layout", "well-structured tests", self.test_structure_modularity_ratio),
+        ];
+
+        let mut traits: Vec<String> = ratios.iter()
+            .filter_map(|(low, high, value)| {
+                if *value >= 0.65 {
+                    Some(format!("{} ({:.2})", high, value))
+                } else if *value <= 0.35 {
+                    Some(format!("{} ({:.2})", low, value))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if self.avg_lines_per_function <= 15.0 {
+            traits.push(format!("short functions (~{:.0} lines)", self.avg_lines_per_function));
+        } else if self.avg_lines_per_function >= 40.0 {
+            traits.push(format!("long functions (~{:.0} lines)", self.avg_lines_per_function));
+        }
+        if self.avg_nesting_depth >= 4.0 {
+            traits.push(format!("deep nesting (~{:.1} levels)", self.avg_nesting_depth));
+        } else if self.avg_nesting_depth <= 2.0 {
+            traits.push(format!("flat control flow (~{:.1} levels)", self.avg_nesting_depth));
+        }
+
+        traits
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TeamMemberProfile {
     pub skills: Vec<String>,
@@ -38,8 +238,43 @@ pub struct IdealCandidateProfile {
     pub work_style_fit: Option<WorkStyle>,
 }
 
+/// Relative weight of each team-fit subcomponent (configurable per job)
+/// Only components with data contribute; their weights are normalized over what's present
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamFitWeights {
+    pub skill_gaps: f32,
+    pub work_style: f32,
+    pub code_style: f32,
+}
+
+impl Default for TeamFitWeights {
+    fn default() -> Self {
+        Self {
+            skill_gaps: 1.0,
+            work_style: 1.0,
+            code_style: 1.0,
+        }
+    }
+}
+
+impl TeamFitWeights {
+    /// Weights stored as JSONB on a job; missing or malformed values fall back to equal weights
+    pub fn from_stored(value: Option<serde_json::Value>) -> Self {
+        value
+            .and_then(|v| serde_json::from_value(v).ok())
+            .unwrap_or_default()
+    }
+}
+
+/// Euclidean distance over all ten code characteristics
+/// Lines per function, nesting depth and abstraction layers are divided by their typical scale and capped at a
+/// difference of 1, so each counts like one ratio field: a sprawling style differs, but can't swamp the rest
 fn calculate_code_style_distance(a: &CodeCharacteristics, b: &CodeCharacteristics) -> f32 {
     let diffs = [
+        scaled_diff_sq(a.avg_lines_per_function, b.avg_lines_per_function, LINES_PER_FUNCTION_SCALE),
+        scaled_diff_sq(a.avg_nesting_depth, b.avg_nesting_depth, NESTING_DEPTH_SCALE),
+        scaled_diff_sq(a.abstraction_layer_count, b.abstraction_layer_count, ABSTRACTION_LAYER_SCALE),
         (a.functional_vs_oop_ratio - b.functional_vs_oop_ratio).powi(2),
         (a.recursion_vs_loop_ratio - b.recursion_vs_loop_ratio).powi(2),
         (a.dependency_coupling_index - b.dependency_coupling_index).powi(2),
@@ -56,7 +291,7 @@ fn calculate_work_style_match(candidate: &WorkStyle, team_avg: &WorkStyle) -> f3
 
     if candidate.communication == team_avg.communication {
         score += 40.0;
-    } else if candidate.communication == "mixed" || team_avg.communication == "mixed" {
+    } else if candidate.communication == Communication::Mixed || team_avg.communication == Communication::Mixed {
         score += 30.0;
     } else {
         score += 15.0;
@@ -64,7 +299,7 @@ fn calculate_work_style_match(candidate: &WorkStyle, team_avg: &WorkStyle) -> f3
 
     if candidate.collaboration == team_avg.collaboration {
         score += 35.0;
-    } else if candidate.collaboration == "balanced" || team_avg.collaboration == "balanced" {
+    } else if candidate.collaboration == Collaboration::Balanced || team_avg.collaboration == Collaboration::Balanced {
         score += 25.0;
     } else {
         score += 10.0;
@@ -72,7 +307,7 @@ fn calculate_work_style_match(candidate: &WorkStyle, team_avg: &WorkStyle) -> f3
 
     if candidate.pace == team_avg.pace {
         score += 25.0;
-    } else if candidate.pace == "flexible" || team_avg.pace == "flexible" {
+    } else if candidate.pace == Pace::Flexible || team_avg.pace == Pace::Flexible {
         score += 20.0;
     } else {
         score += 10.0;
@@ -144,6 +379,25 @@ pub fn calculate_team_fit_score(
     candidate_code_style: Option<&CodeCharacteristics>,
     team_members: &[TeamMemberProfile],
     ideal_profile: Option<&IdealCandidateProfile>,
+) -> ExplainableScore {
+    calculate_team_fit_score_weighted(
+        candidate_skills,
+        candidate_work_style,
+        candidate_code_style,
+        team_members,
+        ideal_profile,
+        &TeamFitWeights::default(),
+    )
+}
+
+/// Team fit as a weighted average of skill-gap, work-style and code-style components
+pub fn calculate_team_fit_score_weighted(
+    candidate_skills: &[String],
+    candidate_work_style: Option<&WorkStyle>,
+    candidate_code_style: Option<&CodeCharacteristics>,
+    team_members: &[TeamMemberProfile],
+    ideal_profile: Option<&IdealCandidateProfile>,
+    weights: &TeamFitWeights,
 ) -> ExplainableScore {
     let mut matched: Vec<String> = Vec::new();
     let mut missing: Vec<String> = Vec::new();
@@ -156,26 +410,30 @@ pub fn calculate_team_fit_score(
             missing: vec![],
             bonus: vec![],
             reasoning: Some("Default score for first team member".to_string()),
+            excluded: false,
         };
     }
 
-    let mut score_components: Vec<f32> = Vec::new();
+    // (score, weight) per present component
+    let mut score_components: Vec<(f32, f32)> = Vec::new();
+    let mut applied_weights: Vec<String> = Vec::new();
 
     // Skill complementarity
     if let Some(ideal) = ideal_profile {
-        let gaps_filled: Vec<&String> = ideal.skill_gaps.iter()
-            .filter(|gap| candidate_skills.iter().any(|s| s.to_lowercase() == gap.to_lowercase()))
-            .collect();
+        // Scales with the share of gaps filled; each gap left open is listed so it's clear what still needs hiring
+        if !ideal.skill_gaps.is_empty() {
+            let (gaps_filled, gaps_open): (Vec<&String>, Vec<&String>) = ideal.skill_gaps.iter()
+                .partition(|gap| candidate_skills.iter().any(|s| s.to_lowercase() == gap.to_lowercase()));
 
-        if !gaps_filled.is_empty() {
-            let gap_score = (gaps_filled.len() as f32 / ideal.skill_gaps.len().max(1) as f32 * 100.0).min(100.0);
-            score_components.push(gap_score);
+            let gap_score = (gaps_filled.len() as f32 / ideal.skill_gaps.len() as f32 * 100.0).min(100.0);
+            score_components.push((gap_score, weights.skill_gaps));
+            applied_weights.push(format!("skill gaps {:.1}", weights.skill_gaps));
             for gap in gaps_filled {
                 bonus.push(format!("Fills skill gap: {}", gap));
             }
-        } else if !ideal.skill_gaps.is_empty() {
-            score_components.push(50.0);
-            missing.push(format!("Does not fill {} skill gaps", ideal.skill_gaps.len()));
+            for gap in gaps_open {
+                missing.push(format!("Skill gap still open: {}", gap));
+            }
         }
     }
 
@@ -190,7 +448,8 @@ pub fn calculate_team_fit_score(
                 .map(|ts| calculate_work_style_match(cand_style, ts))
                 .sum::<f32>() / team_styles.len() as f32;
 
-            score_components.push(avg_score);
+            score_components.push((avg_score, weights.work_style));
+            applied_weights.push(format!("work style {:.1}", weights.work_style));
             if avg_score >= 80.0 {
                 matched.push("Work style aligns well with team".to_string());
             } else if avg_score >= 60.0 {
@@ -206,7 +465,8 @@ pub fn calculate_team_fit_score(
         if let Some(ref target) = ideal.code_style_target {
             let distance = calculate_code_style_distance(cand_code, target);
             let code_score = ((1.0 - distance / 3.0) * 100.0).max(0.0).min(100.0);
-            score_components.push(code_score);
+            score_components.push((code_score, weights.code_style));
+            applied_weights.push(format!("code style {:.1}", weights.code_style));
 
             if code_score >= 80.0 {
                 matched.push("Coding style very similar to team".to_string());
@@ -218,19 +478,26 @@ pub fn calculate_team_fit_score(
         }
     }
 
+    let total_weight: f32 = score_components.iter().map(|(_, w)| w.max(0.0)).sum();
     let final_score = if score_components.is_empty() {
         75
+    } else if total_weight <= 0.0 {
+        // All present components weighted to zero: fall back to a plain average
+        (score_components.iter().map(|(s, _)| s).sum::<f32>() / score_components.len() as f32).round() as i32
     } else {
-        (score_components.iter().sum::<f32>() / score_components.len() as f32).round() as i32
+        (score_components.iter().map(|(s, w)| s * w.max(0.0)).sum::<f32>() / total_weight).round() as i32
     };
 
-    let reasoning = if matched.len() > missing.len() {
+    let mut reasoning = if matched.len() > missing.len() {
         "Good team fit based on style and skills".to_string()
     } else if missing.is_empty() {
         "Moderate team fit".to_string()
     } else {
         "Some compatibility concerns".to_string()
     };
+    if !applied_weights.is_empty() {
+        reasoning.push_str(&format!(" (weights: {})", applied_weights.join(", ")));
+    }
 
     ExplainableScore {
         score: final_score.min(100).max(0),
@@ -238,5 +505,157 @@ pub fn calculate_team_fit_score(
         missing,
         bonus,
         reasoning: Some(reasoning),
+        excluded: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code_style(value: f32) -> CodeCharacteristics {
+        CodeCharacteristics {
+            avg_lines_per_function: 20.0,
+            functional_vs_oop_ratio: value,
+            recursion_vs_loop_ratio: value,
+            dependency_coupling_index: value,
+            modularity_index_score: value,
+            avg_nesting_depth: 2.0,
+            abstraction_layer_count: 3.0,
+            immutability_score: value,
+            error_handling_centralization_score: value,
+            test_structure_modularity_ratio: value,
+        }
+    }
+
+    #[test]
+    fn test_code_style_weight_changes_score() {
+        let style = WorkStyle {
+            communication: Communication::Async,
+            collaboration: Collaboration::Balanced,
+            pace: Pace::Steady,
+        };
+        let team = vec![TeamMemberProfile {
+            skills: vec!["Rust".to_string()],
+            experience_level: "senior".to_string(),
+            work_style: Some(style.clone()),
+            code_characteristics: Some(code_style(0.9)),
+        }];
+        let ideal = compute_ideal_profile(&team, &[]);
+        // Same work style as the team, very different code style
+        let candidate_code = code_style(0.1);
+
+        let score = |weights: &TeamFitWeights| calculate_team_fit_score_weighted(
+            &["Rust".to_string()],
+            Some(&style),
+            Some(&candidate_code),
+            &team,
+            Some(&ideal),
+            weights,
+        );
+
+        let equal = score(&TeamFitWeights::default());
+        let code_heavy = score(&TeamFitWeights { skill_gaps: 1.0, work_style: 1.0, code_style: 5.0 });
+
+        assert!(code_heavy.score < equal.score);
+        assert_eq!(equal.score, calculate_team_fit_score(
+            &["Rust".to_string()], Some(&style), Some(&candidate_code), &team, Some(&ideal),
+        ).score);
+        assert!(code_heavy.reasoning.unwrap().contains("code style 5.0"));
+    }
+
+    fn parse_style(communication: &str, collaboration: &str, pace: &str) -> WorkStyle {
+        serde_json::from_value(serde_json::json!({
+            "communication": communication,
+            "collaboration": collaboration,
+            "pace": pace,
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_work_style_spellings_normalize_equal() {
+        let lower = parse_style("async", "balanced", "fast");
+        let upper = parse_style("Async", " Balanced ", "Fast-Paced");
+        let spelled_out = parse_style("asynchronous", "mixed", "fast_paced");
+        assert_eq!(lower, upper);
+        assert_eq!(lower, spelled_out);
+        assert_eq!(upper.validate(), Ok(()));
+
+        // Normalized values are what gets stored
+        assert_eq!(
+            serde_json::to_value(&upper).unwrap(),
+            serde_json::json!({"communication": "async", "collaboration": "balanced", "pace": "fast"})
+        );
+
+        // Same style regardless of spelling: full marks
+        assert_eq!(calculate_work_style_match(&upper, &lower), 100.0);
+        // Async vs sync, balanced on both sides, fast vs steady
+        let team = parse_style("Sync", "balanced", "steady");
+        assert_eq!(calculate_work_style_match(&upper, &team), 15.0 + 35.0 + 10.0);
+    }
+
+    #[test]
+    fn test_unknown_work_style_loads_but_fails_validation() {
+        let stored = parse_style("Carrier Pigeon", "balanced", "steady");
+        assert_eq!(stored.communication, Communication::Other("carrier-pigeon".to_string()));
+        assert_eq!(
+            stored.validate(),
+            Err("Unknown communication style 'carrier-pigeon' (expected async, sync or mixed)".to_string())
+        );
+        assert_eq!(serde_json::to_value(&stored).unwrap()["communication"], "carrier-pigeon");
+    }
+
+    #[test]
+    fn test_unfilled_gaps_scale_score_and_are_listed() {
+        let team = vec![TeamMemberProfile {
+            skills: vec!["Rust".to_string()],
+            experience_level: "senior".to_string(),
+            work_style: None,
+            code_characteristics: None,
+        }];
+        let required: Vec<String> = ["Go", "Kafka", "Terraform", "GraphQL"].iter().map(|s| s.to_string()).collect();
+        let ideal = compute_ideal_profile(&team, &required);
+        assert_eq!(ideal.skill_gaps.len(), 4);
+
+        // Only the gap component has data, so the score is that component
+        let score = calculate_team_fit_score(&["go".to_string(), "Kafka".to_string()], None, None, &team, Some(&ideal));
+        assert_eq!(score.score, 50);
+        assert_eq!(score.bonus, vec!["Fills skill gap: Go", "Fills skill gap: Kafka"]);
+        assert_eq!(score.missing, vec!["Skill gap still open: Terraform", "Skill gap still open: GraphQL"]);
+
+        let none_filled = calculate_team_fit_score(&["Rust".to_string()], None, None, &team, Some(&ideal));
+        assert_eq!(none_filled.score, 0);
+        assert_eq!(none_filled.missing.len(), 4);
+
+        let all_filled = calculate_team_fit_score(&required, None, None, &team, Some(&ideal));
+        assert_eq!(all_filled.score, 100);
+        assert!(all_filled.missing.is_empty());
+    }
+
+    #[test]
+    fn test_nesting_depth_counts_in_code_style_distance() {
+        let team = vec![TeamMemberProfile {
+            skills: vec![],
+            experience_level: "senior".to_string(),
+            work_style: None,
+            code_characteristics: Some(CodeCharacteristics { avg_nesting_depth: 2.0, ..code_style(0.5) }),
+        }];
+        let ideal = compute_ideal_profile(&team, &[]);
+        let target = ideal.code_style_target.as_ref().unwrap();
+
+        // Identical ratios, very different nesting
+        let shallow = CodeCharacteristics { avg_nesting_depth: 2.0, ..code_style(0.5) };
+        let deep = CodeCharacteristics { avg_nesting_depth: 7.0, ..code_style(0.5) };
+        assert_eq!(calculate_code_style_distance(&shallow, target), 0.0);
+        assert!(calculate_code_style_distance(&deep, target) >= 0.9);
+
+        let score = |style: &CodeCharacteristics| calculate_team_fit_score(&[], None, Some(style), &team, Some(&ideal)).score;
+        assert_eq!(score(&shallow), 100);
+        assert!(score(&deep) <= 70);
+
+        // A huge difference in one scaled field counts like a full swing of one ratio, no more
+        let sprawling = CodeCharacteristics { avg_lines_per_function: 500.0, ..shallow.clone() };
+        assert_eq!(calculate_code_style_distance(&sprawling, &shallow), 1.0);
     }
 }
@@ -0,0 +1,147 @@
+use std::fmt;
+
+/// Where a candidate is in the hiring pipeline
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PipelineStage {
+    Sourced,
+    Screened,
+    Interviewing,
+    Offer,
+    Hired,
+    Rejected,
+}
+
+impl PipelineStage {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "sourced" => Some(Self::Sourced),
+            "screened" => Some(Self::Screened),
+            "interviewing" => Some(Self::Interviewing),
+            "offer" => Some(Self::Offer),
+            "hired" => Some(Self::Hired),
+            "rejected" => Some(Self::Rejected),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sourced => "sourced",
+            Self::Screened => "screened",
+            Self::Interviewing => "interviewing",
+            Self::Offer => "offer",
+            Self::Hired => "hired",
+            Self::Rejected => "rejected",
+        }
+    }
+
+    /// Every stage, in pipeline order
+    pub fn all() -> &'static [PipelineStage] {
+        &[
+            Self::Sourced,
+            Self::Screened,
+            Self::Interviewing,
+            Self::Offer,
+            Self::Hired,
+            Self::Rejected,
+        ]
+    }
+
+    /// The next stage forward, if the candidate can still advance
+    pub fn next(&self) -> Option<Self> {
+        match self {
+            Self::Sourced => Some(Self::Screened),
+            Self::Screened => Some(Self::Interviewing),
+            Self::Interviewing => Some(Self::Offer),
+            Self::Offer => Some(Self::Hired),
+            Self::Hired | Self::Rejected => None,
+        }
+    }
+
+    /// Stages a candidate in this stage may move to
+    /// Anyone still in progress can be rejected; rejected candidates can be reconsidered from the start;
+    /// hired is final
+    pub fn allowed_transitions(&self) -> &'static [PipelineStage] {
+        match self {
+            Self::Sourced => &[Self::Screened, Self::Rejected],
+            Self::Screened => &[Self::Interviewing, Self::Rejected],
+            Self::Interviewing => &[Self::Offer, Self::Rejected],
+            Self::Offer => &[Self::Hired, Self::Rejected],
+            Self::Hired => &[],
+            Self::Rejected => &[Self::Sourced],
+        }
+    }
+
+    /// Validate a move to `to` (staying in the same stage is allowed and changes nothing)
+    pub fn transition(self, to: PipelineStage) -> Result<PipelineStage, TransitionError> {
+        if self == to || self.allowed_transitions().contains(&to) {
+            Ok(to)
+        } else {
+            Err(TransitionError { from: self, to })
+        }
+    }
+}
+
+impl fmt::Display for PipelineStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// A move the pipeline doesn't allow
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransitionError {
+    pub from: PipelineStage,
+    pub to: PipelineStage,
+}
+
+impl fmt::Display for TransitionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let allowed: Vec<&str> = self.from.allowed_transitions().iter().map(|s| s.name()).collect();
+        let allowed = if allowed.is_empty() { "none, it is final".to_string() } else { allowed.join(", ") };
+        write!(f, "Cannot move a candidate from {} to {} (allowed from {}: {})", self.from, self.to, self.from, allowed)
+    }
+}
+
+impl std::error::Error for TransitionError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_valid_transitions_succeed() {
+        assert_eq!(PipelineStage::Sourced.transition(PipelineStage::Screened), Ok(PipelineStage::Screened));
+        assert_eq!(PipelineStage::Offer.transition(PipelineStage::Hired), Ok(PipelineStage::Hired));
+        assert_eq!(PipelineStage::Interviewing.transition(PipelineStage::Rejected), Ok(PipelineStage::Rejected));
+        assert_eq!(PipelineStage::Rejected.transition(PipelineStage::Sourced), Ok(PipelineStage::Sourced));
+        assert_eq!(PipelineStage::Hired.transition(PipelineStage::Hired), Ok(PipelineStage::Hired));
+
+        // Advancing walks the whole pipeline
+        let mut stage = PipelineStage::Sourced;
+        while let Some(next) = stage.next() {
+            stage = stage.transition(next).unwrap();
+        }
+        assert_eq!(stage, PipelineStage::Hired);
+    }
+
+    #[test]
+    fn test_illegal_transition_rejected() {
+        let err = PipelineStage::Hired.transition(PipelineStage::Sourced).unwrap_err();
+        assert_eq!(err, TransitionError { from: PipelineStage::Hired, to: PipelineStage::Sourced });
+        assert_eq!(err.to_string(), "Cannot move a candidate from hired to sourced (allowed from hired: none, it is final)");
+
+        // Stages can't be skipped
+        let err = PipelineStage::Sourced.transition(PipelineStage::Offer).unwrap_err();
+        assert!(err.to_string().contains("allowed from sourced: screened, rejected"));
+    }
+
+    #[test]
+    fn test_stage_names_round_trip() {
+        for stage in PipelineStage::all() {
+            assert_eq!(PipelineStage::parse(stage.name()), Some(*stage));
+        }
+        assert_eq!(PipelineStage::parse(" Interviewing "), Some(PipelineStage::Interviewing));
+        assert_eq!(PipelineStage::parse("phone-screen"), None);
+    }
+}
@@ -1,4 +1,4 @@
-use backend::{code_analysis::characteristics::CodeCharacteristics, endpoints::match_styles};
+use backend::{code_analysis::characteristics::CodeCharacteristics, endpoints::{match_styles, rank_styles}};
 
 #[test]
 pub fn match_engine_test() {
@@ -52,5 +52,73 @@ pub fn match_engine_test() {
     "test_structure_modularity_ratio": 0.0
 }"#).unwrap();
 
-    println!("{:#?}", match_styles(target, vec![user1, user2, user3], None));
-}
\ No newline at end of file
+    let matches = match_styles(target, vec![user1, user2, user3], None);
+    println!("{:#?}", matches);
+
+    // user1 mostly differs in error handling centralization; user3 is the furthest (much more functional, coupled)
+    // Lines per function and nesting count after scaling by 50 and 5
+    let distances: Vec<f32> = matches.iter().map(|(_, d)| *d).collect();
+    let user1_sq = 0.42 + (2.7f32 / 50.0).powi(2) + (0.1f32 / 5.0).powi(2);
+    assert!((distances[0] - user1_sq.sqrt()).abs() < 1e-4);
+    assert!(distances[0] < distances[1] && distances[1] < distances[2]);
+}
+
+#[test]
+pub fn rank_styles_endpoint_test() {
+    let characteristics = |functional: f32, coupling: f32, errors: f32| serde_json::json!({
+        "avg_lines_per_function": 12.0,
+        "functional_vs_oop_ratio": functional,
+        "recursion_vs_loop_ratio": 0.0,
+        "dependency_coupling_index": coupling,
+        "modularity_index_score": 0.7,
+        "avg_nesting_depth": 1.2,
+        "abstraction_layer_count": 2.0,
+        "immutability_score": 0.2,
+        "error_handling_centralization_score": errors,
+        "test_structure_modularity_ratio": 0.0
+    });
+    let target = serde_json::from_value::<CodeCharacteristics>(characteristics(0.1, 0.3, 0.6)).unwrap();
+
+    let far = "00000000-0000-0000-0000-000000000003".to_string();
+    let close = "00000000-0000-0000-0000-000000000001".to_string();
+    let middle = "00000000-0000-0000-0000-000000000002".to_string();
+    let pending = "00000000-0000-0000-0000-000000000004".to_string();
+    let stored = vec![
+        (far.clone(), Some(characteristics(1.0, 0.6, 0.4))),
+        (close.clone(), Some(characteristics(0.0, 0.3, 0.0))),
+        (middle.clone(), Some(characteristics(0.8, 0.3, 0.7))),
+        (pending.clone(), None),
+    ];
+    let requested = vec![far.clone(), pending.clone(), close.clone(), middle.clone(), "not-a-uuid".to_string()];
+
+    let ranked = rank_styles(target, None, &requested, &stored);
+    let order: Vec<&str> = ranked.matches.iter().map(|m| m.id.as_str()).collect();
+    assert_eq!(order, vec![close.as_str(), middle.as_str(), far.as_str()]);
+    assert!(ranked.matches.windows(2).all(|w| w[0].distance <= w[1].distance));
+
+    let skipped: Vec<&str> = ranked.skipped.iter().map(|s| s.id.as_str()).collect();
+    assert_eq!(skipped, vec![pending.as_str(), "not-a-uuid"]);
+    assert!(ranked.skipped[0].reason.starts_with("No code characteristics"));
+    assert_eq!(ranked.skipped[1].reason, "Invalid candidate ID");
+}
+
+#[test]
+pub fn function_length_counts_in_distance() {
+    let style = |lines: f32| serde_json::from_value::<CodeCharacteristics>(serde_json::json!({
+        "avg_lines_per_function": lines,
+        "functional_vs_oop_ratio": 0.5,
+        "recursion_vs_loop_ratio": 0.1,
+        "dependency_coupling_index": 0.3,
+        "modularity_index_score": 0.7,
+        "avg_nesting_depth": 1.5,
+        "abstraction_layer_count": 2.0,
+        "immutability_score": 0.4,
+        "error_handling_centralization_score": 0.6,
+        "test_structure_modularity_ratio": 0.2
+    })).unwrap();
+
+    // Identical on every ratio, 5 vs 40 lines per function: (35 / 50)^2 under the hood
+    let matches = match_styles(style(5.0), vec![style(40.0), style(5.0)], None);
+    assert!((matches[0].1 - 0.7).abs() < 1e-5);
+    assert_eq!(matches[1].1, 0.0);
+}
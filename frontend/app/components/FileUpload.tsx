@@ -32,8 +32,14 @@ export function FileUpload({ onFilesSelected, isProcessing = false }: FileUpload
         (file) =>
           file.type === "application/pdf" ||
           file.type === "application/vnd.openxmlformats-officedocument.wordprocessingml.document" ||
+          file.type === "application/rtf" ||
+          file.type === "text/rtf" ||
+          file.type === "text/html" ||
           file.name.endsWith(".pdf") ||
-          file.name.endsWith(".docx")
+          file.name.endsWith(".docx") ||
+          file.name.endsWith(".rtf") ||
+          file.name.endsWith(".html") ||
+          file.name.endsWith(".htm")
       );
 
       if (files.length > 0) {
@@ -83,7 +89,7 @@ export function FileUpload({ onFilesSelected, isProcessing = false }: FileUpload
         <input
           type="file"
           multiple
-          accept=".pdf,.docx,application/pdf,application/vnd.openxmlformats-officedocument.wordprocessingml.document"
+          accept=".pdf,.docx,.rtf,.html,.htm,application/pdf,application/vnd.openxmlformats-officedocument.wordprocessingml.document,application/rtf,text/rtf,text/html"
           onChange={handleFileInput}
           className="absolute inset-0 h-full w-full cursor-pointer opacity-0"
           disabled={isProcessing}
@@ -108,7 +114,7 @@ export function FileUpload({ onFilesSelected, isProcessing = false }: FileUpload
             <p className="mt-1 text-sm text-slate-500">
               {isProcessing
                 ? "Extracting candidate information"
-                : "or click to browse (PDF, DOCX)"}
+                : "or click to browse (PDF, DOCX, RTF, HTML)"}
             </p>
           </div>
 
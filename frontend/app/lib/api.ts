@@ -1,3 +1,5 @@
+import type { WorkStyle } from "../types";
+
 const API_BASE = process.env.NEXT_PUBLIC_API_URL || "http://localhost:8000";
 
 // Helper for fetch with error handling
@@ -9,9 +11,43 @@ async function fetchJson<T>(url: string, options?: RequestInit): Promise<T> {
   return response.json();
 }
 
+// Error body returned by the job, take-home and GitHub analysis endpoints
+export interface ApiErrorBody {
+  code:
+    | "invalid_request"
+    | "not_found"
+    | "database_error"
+    | "not_configured"
+    | "not_permitted"
+    | "upstream_error"
+    | "internal_error";
+  error: string;
+}
+
 // Enhanced skill format (backend accepts both string and object formats)
 export type ApiJobSkill = string | { name: string; level?: string; mandatory?: boolean };
 
+// Relative weights of the team-fit subcomponents (default 1 each)
+export interface TeamFitWeights {
+  skill_gaps: number;
+  work_style: number;
+  code_style: number;
+}
+
+// Where the hire has to work; on-site and hybrid jobs penalize candidates outside the city
+export type LocationRequirement =
+  | { type: "on_site"; city: string }
+  | { type: "hybrid"; city: string }
+  | { type: "remote" };
+
+// Talent fit component weights; normalized to sum to 1 when saved
+export interface ScoreWeights {
+  skills: number;
+  experience: number;
+  team_fit: number;
+  culture: number;
+}
+
 // Jobs API
 export interface ApiJob {
   id: string;
@@ -20,6 +56,9 @@ export interface ApiJob {
   location: string | null;
   required_skills: ApiJobSkill[];
   experience_level: string;
+  team_fit_weights: TeamFitWeights;
+  location_requirement: LocationRequirement | null;
+  score_weights: ScoreWeights | null;
   status: string;
   team_id: string | null;
   candidate_ids: string[];
@@ -33,6 +72,9 @@ export interface CreateJobInput {
   location?: string;
   required_skills: ApiJobSkill[];
   experience_level: string;
+  team_fit_weights?: Partial<TeamFitWeights>;
+  location_requirement?: LocationRequirement;
+  score_weights?: ScoreWeights;
 }
 
 export interface UpdateJobInput {
@@ -43,6 +85,10 @@ export interface UpdateJobInput {
   experience_level?: string;
   status?: string;
   team_id?: string;
+  team_fit_weights?: Partial<TeamFitWeights>;
+  /** null removes the requirement */
+  location_requirement?: LocationRequirement | null;
+  score_weights?: ScoreWeights;
 }
 
 // Teams API
@@ -147,6 +193,10 @@ export interface SourcingRequest {
   team_id?: string;
   sources: string[];
   count: number;
+  deterministic_ids?: boolean;
+  temperature?: number;
+  /** Save results to sourced_candidates; ids are then the stored candidates' ids */
+  persist?: boolean;
 }
 
 export interface SourcedCandidate {
@@ -163,6 +213,18 @@ export interface SourcedCandidate {
   source: string;
 }
 
+// Last line of a streamed search
+export interface SourcingStats {
+  candidates: number;
+  top_score: number | null;
+  average_score: number | null;
+  elapsed_ms: number;
+}
+
+export type SourcingEvent =
+  | ({ event: "candidate" } & SourcedCandidate)
+  | ({ event: "sourcing_stats" } & SourcingStats);
+
 // Candidates API
 export interface ApiCandidateSkill {
   name: string;
@@ -174,6 +236,9 @@ export interface ApiCandidateExperience {
   company: string;
   duration: string;
   description?: string;
+  // YYYY-MM-DD; no end_date means the role is ongoing
+  start_date?: string;
+  end_date?: string;
 }
 
 export interface ApiCandidateEducation {
@@ -186,6 +251,8 @@ export interface ApiCandidateLinks {
   github?: string;
   linkedin?: string;
   portfolio?: string;
+  /** gitlab.com profile, analyzed when there is no GitHub link */
+  gitlab?: string;
 }
 
 export interface ApiScoreBreakdown {
@@ -210,6 +277,7 @@ export interface ApiCandidate {
   score_breakdown: ApiScoreBreakdown;
   resume_file_name: string | null;
   source: string;
+  work_style: WorkStyle | null;
   created_at: string;
   // GitHub enrichment fields (populated async)
   code_characteristics: Record<string, unknown> | null;
@@ -219,8 +287,47 @@ export interface ApiCandidate {
   ai_analysis_details: Record<string, unknown> | null;
   developer_profile: string | null;
   analysis_metadata: Record<string, unknown> | null;
-  github_stats: Record<string, unknown> | null;
   analysis_status: string;
+  pipeline_stage: PipelineStage;
+  analyzed_at: string | null;
+  analysis_stale: boolean;
+}
+
+export interface CandidatePage {
+  candidates: ApiCandidate[];
+  total: number;
+  limit: number;
+  offset: number;
+  has_more: boolean;
+}
+
+export interface CandidateSearchParams {
+  q?: string;
+  skill?: string;
+  stage?: PipelineStage;
+  limit?: number;
+  offset?: number;
+}
+
+export interface CandidateAnalysisStatus {
+  status: string;
+  ai_detection_score: number | null;
+  developer_profile_ready: boolean;
+  reason?: string;
+}
+
+export type PipelineStage =
+  | "sourced"
+  | "screened"
+  | "interviewing"
+  | "offer"
+  | "hired"
+  | "rejected";
+
+export interface PipelineStageChange {
+  id: string;
+  previous_stage: PipelineStage;
+  pipeline_stage: PipelineStage;
 }
 
 export interface CreateCandidateInput {
@@ -237,16 +344,50 @@ export interface CreateCandidateInput {
   score_breakdown: ApiScoreBreakdown;
   resume_file_name?: string;
   source: string;
+  work_style?: WorkStyle;
+  deterministic_id?: boolean;
 }
 
 export interface JobCandidateResponse {
   id: string;
   candidate: ApiCandidate;
   job_match_score: number;
+  /** Breakdown behind job_match_score; null until the job is rescored */
+  score_breakdown: Record<string, unknown> | null;
   team_compatibility_score: number;
   added_at: string;
 }
 
+export interface GitHubJobMatch {
+  job_id: string;
+  username: string;
+  skill_score: {
+    score: number;
+    matched: string[];
+    missing: string[];
+    bonus: string[];
+    reasoning: string | null;
+  };
+  /** Derived from `languages`, most used first */
+  skills: ApiCandidateSkill[];
+  /** Percent of the user's repos per language */
+  languages: Record<string, number>;
+}
+
+export interface RescoredCandidate {
+  candidate_id: string;
+  name: string;
+  old_score: number;
+  new_score: number;
+  score_breakdown: Record<string, unknown>;
+}
+
+export interface RescoreResponse {
+  job_id: string;
+  /** Best fit first */
+  candidates: RescoredCandidate[];
+}
+
 export interface LinkCandidateInput {
   candidate_id: string;
   job_match_score?: number;
@@ -285,9 +426,47 @@ export interface AnalysisSummary {
 export interface TakeHomeProjects {
   projects: TakeHomeProject[];
   analysis_summary: AnalysisSummary;
+  rubric?: EvaluationCriterion[];
   generated_at?: string;
 }
 
+// Semantic-search category (matches backend SearchCategoryInfo)
+export interface SearchCategoryInfo {
+  name: string;
+  query: string;
+  description: string;
+}
+
+// Skill matrix for a job (matches backend SkillMatrix)
+export interface SkillMatchCell {
+  matched_skill: string | null;
+  match_type: "exact" | "synonym" | "fuzzy" | "partial" | null;
+  score: number;
+}
+
+export interface SkillMatrix {
+  skills: string[];
+  candidates: {
+    candidate_id: string;
+    name: string;
+    cells: SkillMatchCell[];
+    matched_count: number;
+  }[];
+  coverage: {
+    skill: string;
+    mandatory: boolean;
+    candidates_matched: number;
+    total_candidates: number;
+    summary: string;
+  }[];
+}
+
+export interface SkillSynonym {
+  canonical: string;
+  alias: string;
+  aliases: string[];
+}
+
 export const api = {
   // Jobs
   getJobs: (): Promise<ApiJob[]> =>
@@ -296,6 +475,16 @@ export const api = {
   getJob: (id: string): Promise<ApiJob> =>
     fetchJson(`${API_BASE}/api/jobs/${id}`),
 
+  getJobSkillMatrix: (id: string): Promise<SkillMatrix> =>
+    fetchJson(`${API_BASE}/api/jobs/${id}/skill-matrix`),
+
+  addSkillSynonym: (canonical: string, alias: string): Promise<SkillSynonym> =>
+    fetchJson(`${API_BASE}/api/skill-synonyms`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ canonical, alias }),
+    }),
+
   createJob: (data: CreateJobInput): Promise<ApiJob> =>
     fetchJson(`${API_BASE}/api/jobs`, {
       method: "POST",
@@ -372,6 +561,39 @@ export const api = {
       body: JSON.stringify(params),
     }),
 
+  // Streams each candidate to onEvent as soon as it's scored; resolves with the final stats
+  searchCandidatesStream: async (
+    params: SourcingRequest,
+    onEvent: (event: SourcingEvent) => void
+  ): Promise<SourcingStats | null> => {
+    const response = await fetch(`${API_BASE}/api/sourcing/search/stream`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify(params),
+    });
+    if (!response.ok || !response.body) {
+      throw new Error(`API error: ${response.status} ${response.statusText}`);
+    }
+
+    const reader = response.body.getReader();
+    const decoder = new TextDecoder();
+    let buffered = "";
+    let stats: SourcingStats | null = null;
+    for (;;) {
+      const { done, value } = await reader.read();
+      buffered += decoder.decode(value, { stream: !done });
+      const lines = buffered.split("\n");
+      buffered = done ? "" : lines.pop() ?? "";
+      for (const line of lines) {
+        if (!line.trim()) continue;
+        const event = JSON.parse(line) as SourcingEvent;
+        if (event.event === "sourcing_stats") stats = event;
+        onEvent(event);
+      }
+      if (done) return stats;
+    }
+  },
+
   // Candidates
   createCandidate: (data: CreateCandidateInput): Promise<ApiCandidate> =>
     fetchJson(`${API_BASE}/api/candidates`, {
@@ -380,8 +602,22 @@ export const api = {
       body: JSON.stringify(data),
     }),
 
-  getJobCandidates: (jobId: string): Promise<JobCandidateResponse[]> =>
-    fetchJson(`${API_BASE}/api/jobs/${jobId}/candidates`),
+  getCandidates: (params: CandidateSearchParams = {}): Promise<CandidatePage> => {
+    const query = new URLSearchParams();
+    for (const [key, value] of Object.entries(params)) {
+      if (value !== undefined && value !== "") query.set(key, String(value));
+    }
+    const search = query.toString();
+    return fetchJson(`${API_BASE}/api/candidates${search ? `?${search}` : ""}`);
+  },
+
+  deleteCandidate: (id: string): Promise<{ success: boolean; id: string }> =>
+    fetchJson(`${API_BASE}/api/candidates/${id}`, {
+      method: "DELETE",
+    }),
+
+  getJobCandidates: (jobId: string, stage?: PipelineStage): Promise<JobCandidateResponse[]> =>
+    fetchJson(`${API_BASE}/api/jobs/${jobId}/candidates${stage ? `?stage=${stage}` : ""}`),
 
   addCandidateToJob: (
     jobId: string,
@@ -393,6 +629,13 @@ export const api = {
       body: JSON.stringify(data),
     }),
 
+  rescoreJobCandidates: (jobId: string, temperature?: number): Promise<RescoreResponse> =>
+    fetchJson(`${API_BASE}/api/jobs/${jobId}/rescore`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify(temperature === undefined ? {} : { temperature }),
+    }),
+
   removeCandidateFromJob: (
     jobId: string,
     candidateId: string
@@ -401,13 +644,34 @@ export const api = {
       method: "DELETE",
     }),
 
+  // Full GitHub stats (not included in candidate lists)
+  getCandidateGithubStats: (candidateId: string): Promise<Record<string, unknown>> =>
+    fetchJson(`${API_BASE}/api/candidates/${candidateId}/github-stats`),
+
+  // Cheap poll for background GitHub enrichment
+  getCandidateAnalysisStatus: (candidateId: string): Promise<CandidateAnalysisStatus> =>
+    fetchJson(`${API_BASE}/api/candidates/${candidateId}/analysis-status`),
+
+  // Pipeline stage (409 for moves the pipeline doesn't allow)
+  moveCandidateStage: (candidateId: string, stage: PipelineStage): Promise<PipelineStageChange> =>
+    fetchJson(`${API_BASE}/api/candidates/${candidateId}/stage`, {
+      method: "PUT",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ stage }),
+    }),
+
+  advanceCandidate: (candidateId: string): Promise<PipelineStageChange> =>
+    fetchJson(`${API_BASE}/api/candidates/${candidateId}/advance`, {
+      method: "POST",
+    }),
+
   // Candidates count
-  getCandidatesCount: (): Promise<{ count: number }> =>
-    fetchJson(`${API_BASE}/api/candidates/count`),
+  getCandidatesCount: (stage?: PipelineStage): Promise<{ count: number }> =>
+    fetchJson(`${API_BASE}/api/candidates/count${stage ? `?stage=${stage}` : ""}`),
 
   // Resume parsing
-  parseResume: async (file: File): Promise<ParsedResume> => {
-    const response = await fetch(`${API_BASE}/api/resumes/parse`, {
+  parseResume: async (file: File, save = false): Promise<ParsedResume> => {
+    const response = await fetch(`${API_BASE}/api/resumes/parse${save ? "?save=true" : ""}`, {
       method: "POST",
       headers: {
         "Content-Type": file.type || "application/octet-stream",
@@ -424,16 +688,35 @@ export const api = {
   generateTakeHomeProjects: (
     jobId: string,
     candidateId: string,
-    forceRegenerate?: boolean
+    forceRegenerate?: boolean,
+    rubric?: EvaluationCriterion[]
   ): Promise<TakeHomeProjects> =>
     fetchJson(`${API_BASE}/api/jobs/${jobId}/candidates/${candidateId}/take-home-projects`, {
       method: "POST",
       headers: { "Content-Type": "application/json" },
-      body: JSON.stringify({ force_regenerate: forceRegenerate }),
+      body: JSON.stringify({ force_regenerate: forceRegenerate, rubric }),
     }),
 
+  // Replace one project, keeping its id and the other projects
+  regenerateTakeHomeProject: (jobId: string, candidateId: string, projectId: string): Promise<TakeHomeProjects> =>
+    fetchJson(
+      `${API_BASE}/api/jobs/${jobId}/candidates/${candidateId}/take-home-projects/${projectId}/regenerate`,
+      { method: "POST" }
+    ),
+
   getTakeHomeProjects: (jobId: string, candidateId: string): Promise<TakeHomeProjects> =>
     fetchJson(`${API_BASE}/api/jobs/${jobId}/candidates/${candidateId}/take-home-projects`),
+
+  // GitHub analysis
+  getSearchCategories: (): Promise<{ categories: SearchCategoryInfo[] }> =>
+    fetchJson(`${API_BASE}/api/github/search-categories`),
+
+  githubJobMatch: (jobId: string, username: string): Promise<GitHubJobMatch> =>
+    fetchJson(`${API_BASE}/api/jobs/${jobId}/github-match`, {
+      method: "POST",
+      headers: { "Content-Type": "application/json" },
+      body: JSON.stringify({ username }),
+    }),
 };
 
 // Resume parsing types
@@ -461,5 +744,13 @@ export interface ParsedResume {
   linkedin_url: string | null;
   website_url: string | null;
   other_links: string[];
+  /** 0-1 heuristic reliability of the extraction */
+  parse_confidence: number;
+  /** parse_confidence below 0.4 (e.g. a garbled scan); the fields may be incomplete */
+  low_confidence: boolean;
+  /** Present when parsed with `save`: the candidate the resume was stored as */
+  candidate_id?: string;
+  /** False when an existing candidate with the same email was updated */
+  created?: boolean;
   error?: string;
 }
@@ -23,6 +23,8 @@ export interface SocialLinks {
   github?: string;
   portfolio?: string;
   twitter?: string;
+  githubBlog?: string;
+  portfolioVerified?: boolean;
 }
 
 export interface ScoreBreakdown {
@@ -65,7 +67,7 @@ export interface Candidate {
 export type JobStatus = "sourcing" | "reviewing" | "filled" | "closed";
 export type ExperienceLevel = "entry" | "mid" | "senior" | "lead" | "any";
 
-// Work style preferences for team members
+// Work style preferences for team members; the backend also accepts common spellings ("Async", "asynchronous", "fast-paced")
 export interface WorkStyle {
   communication: "async" | "sync" | "mixed";
   collaboration: "independent" | "collaborative" | "balanced";
@@ -102,6 +104,7 @@ export interface AnalysisMetadata {
   total_lines: number;
   repos_analyzed: number;
   languages_detected: string[];
+  duplicates_skipped?: number;
 }
 
 // Team member = existing employee (not a candidate)